@@ -0,0 +1,255 @@
+//! # Neo Merkle
+//!
+//! Binary SHA-256 Merkle trees, hashed the way Neo hashes the transactions
+//! of a block: a parent is the double SHA-256 of its two children, and the
+//! last node of an odd level is paired with itself. A tree of one leaf has
+//! that leaf as its root, and an empty tree has a zero root.
+//!
+//! The crate is `no_std` with `alloc`, so the host and the guest program
+//! build the same trees.
+//!
+//! ```rust
+//! use neo_merkle::{hash256, MerkleTree};
+//!
+//! let leaves: Vec<_> = [b"a", b"b", b"c"].iter().map(|data| hash256(*data)).collect();
+//! let tree = MerkleTree::new(leaves.clone());
+//!
+//! let proof = tree.proof(2).unwrap();
+//! assert!(proof.verify(&leaves[2], &tree.root()));
+//! assert!(!proof.verify(&leaves[1], &tree.root()));
+//! ```
+//!
+//! Pairing the last node with itself means `[a, b, c]` and `[a, b, c, c]`
+//! have the same root. A [`MerkleProof`] names the leaf count it was made
+//! for, so verifiers that need to tell such trees apart must check it
+//! against a count they trust.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A 32-byte node of a tree
+pub type Hash = [u8; 32];
+
+/// Double SHA-256, Neo's `Hash256`
+pub fn hash256(data: &[u8]) -> Hash {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// Parent of two nodes: the double SHA-256 of `left` followed by `right`
+pub fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    Sha256::digest(hasher.finalize()).into()
+}
+
+/// Root of a tree over `leaves`, without keeping the inner nodes
+pub fn root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = parents(&level);
+    }
+    level[0]
+}
+
+/// Number of levels above the leaves of a tree with `leaf_count` leaves,
+/// which is also the length of its proofs
+pub fn depth(leaf_count: u64) -> usize {
+    let mut width = leaf_count;
+    let mut depth = 0;
+    while width > 1 {
+        width = width.div_ceil(2);
+        depth += 1;
+    }
+    depth
+}
+
+fn parents(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// A Merkle tree that keeps every level, to produce proofs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    /// `levels[0]` holds the leaves and the last level the root
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = parents(level);
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Root of the tree, zero when it has no leaves
+    pub fn root(&self) -> Hash {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or([0; 32])
+    }
+
+    pub fn leaves(&self) -> &[Hash] {
+        &self.levels[0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves().is_empty()
+    }
+
+    /// Proof that the leaf at `index` is in the tree, `None` past the last
+    /// leaf
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut position = index;
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .map(|level| {
+                let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+                position /= 2;
+                *sibling
+            })
+            .collect();
+        Some(MerkleProof {
+            index: index as u64,
+            leaf_count: self.len() as u64,
+            siblings,
+        })
+    }
+}
+
+/// Inclusion proof for one leaf: the siblings on its path to the root,
+/// from the leaf level up
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Position of the leaf
+    pub index: u64,
+    /// Leaves in the tree the proof was made for
+    pub leaf_count: u64,
+    pub siblings: Vec<Hash>,
+}
+
+impl MerkleProof {
+    /// Root of the tree `leaf` is in if the proof holds
+    ///
+    /// `None` if the proof cannot belong to a tree of `leaf_count` leaves:
+    /// the index is out of range, the path has the wrong length, or the
+    /// last node of an odd level is not paired with itself.
+    pub fn compute_root(&self, leaf: &Hash) -> Option<Hash> {
+        if self.index >= self.leaf_count || self.siblings.len() != depth(self.leaf_count) {
+            return None;
+        }
+        let (mut node, mut index, mut width) = (*leaf, self.index, self.leaf_count);
+        for sibling in &self.siblings {
+            node = if index % 2 == 1 {
+                hash_pair(sibling, &node)
+            } else if index + 1 == width {
+                if *sibling != node {
+                    return None;
+                }
+                hash_pair(&node, &node)
+            } else {
+                hash_pair(&node, sibling)
+            };
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+        Some(node)
+    }
+
+    /// Whether `leaf` is in the tree with this `root`
+    pub fn verify(&self, leaf: &Hash, root: &Hash) -> bool {
+        self.compute_root(leaf)
+            .is_some_and(|computed| computed == *root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u8) -> Vec<Hash> {
+        (0..count).map(|i| hash256(&[i])).collect()
+    }
+
+    #[test]
+    fn test_root_matches_neo() {
+        assert_eq!(root(&[]), [0; 32]);
+        let [a, b, c] = leaves(3)[..] else {
+            unreachable!()
+        };
+        assert_eq!(root(&[a]), a);
+
+        let mut concatenated = [0u8; 64];
+        concatenated[..32].copy_from_slice(&a);
+        concatenated[32..].copy_from_slice(&b);
+        assert_eq!(root(&[a, b]), hash256(&concatenated));
+
+        let cc = hash_pair(&c, &c);
+        assert_eq!(root(&[a, b, c]), hash_pair(&hash_pair(&a, &b), &cc));
+        assert_eq!(root(&[a, b, c]), root(&[a, b, c, c]));
+    }
+
+    #[test]
+    fn test_proofs_for_every_leaf() {
+        for count in 0..=17 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone());
+            assert_eq!(tree.root(), root(&leaves));
+            assert!(tree.proof(leaves.len()).is_none());
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.siblings.len(), depth(count.into()));
+                assert!(proof.verify(leaf, &tree.root()), "{index} of {count}");
+                assert!(!proof.verify(&hash256(b"other"), &tree.root()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_proofs() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::new(leaves.clone());
+        let proof = tree.proof(4).unwrap();
+
+        let mut moved = proof.clone();
+        moved.index = 5;
+        assert_eq!(moved.compute_root(&leaves[4]), None);
+
+        let mut short = proof.clone();
+        short.siblings.pop();
+        assert_eq!(short.compute_root(&leaves[4]), None);
+
+        // The last leaf of an odd level must be paired with itself
+        let mut mispaired = proof.clone();
+        mispaired.siblings[0] = leaves[3];
+        assert_eq!(mispaired.compute_root(&leaves[4]), None);
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        let decoded: MerkleProof = bincode::deserialize(&bytes).unwrap();
+        assert!(decoded.verify(&leaves[4], &tree.root()));
+    }
+}
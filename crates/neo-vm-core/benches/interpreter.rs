@@ -0,0 +1,118 @@
+//! Interpreter hot paths: stack item copies and slot setup
+//!
+//! Run with `cargo bench -p neo-vm-core --bench interpreter`.
+
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
+use neo_vm_core::{NeoVM, OpCode, ScriptBuilder, VMState};
+use std::hint::black_box;
+
+fn run(script: &[u8]) -> NeoVM {
+    let mut vm = NeoVM::new(100_000_000);
+    vm.load_script(script.to_vec()).unwrap();
+    vm.run();
+    vm
+}
+
+fn bench_script(group: &mut BenchmarkGroup<'_, WallTime>, name: &str, script: &[u8]) {
+    assert!(matches!(run(script).state, VMState::Halt), "{name} faulted");
+    group.bench_function(name, |b| b.iter(|| black_box(run(script))));
+}
+
+/// Run `setup`, then count down from `iterations`, running `body` once per
+/// iteration
+///
+/// `body` must leave the stack as it found it.
+fn counted_loop(setup: &[u8], iterations: i16, body: &[u8]) -> Vec<u8> {
+    let mut script = setup.to_vec();
+    script.push(OpCode::PUSHINT16 as u8);
+    script.extend_from_slice(&iterations.to_le_bytes());
+    script.extend_from_slice(&[
+        OpCode::DUP as u8,
+        OpCode::JMPIFNOT as u8,
+        (body.len() + 5) as u8,
+    ]);
+    script.extend_from_slice(body);
+    script.extend_from_slice(&[
+        OpCode::DEC as u8,
+        OpCode::JMP as u8,
+        (-(body.len() as i8 + 4)) as u8,
+        OpCode::DROP as u8,
+        OpCode::RET as u8,
+    ]);
+    script
+}
+
+fn bench_byte_strings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("byte_strings");
+    group.throughput(Throughput::Elements(1000));
+    let mut data = ScriptBuilder::new();
+    data.emit_push_bytes(&[0xAB; 1024]);
+    let data = data.into_bytes();
+
+    // Copy a 1KB byte string from below the loop counter
+    let body = [OpCode::OVER as u8, OpCode::DROP as u8];
+    let script = counted_loop(&data, 1000, &body);
+    bench_script(&mut group, "over_1kb", &script);
+
+    // Load a 1KB byte string from an argument slot
+    let mut setup = data.clone();
+    setup.extend_from_slice(&[OpCode::INITSLOT as u8, 0, 1]);
+    let body = [OpCode::LDARG0 as u8, OpCode::DROP as u8];
+    let script = counted_loop(&setup, 1000, &body);
+    bench_script(&mut group, "ldarg_1kb", &script);
+
+    group.finish();
+}
+
+fn bench_calls(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calls");
+
+    // Each iteration calls `f(a, b) { return a + b; }` with one unused local
+    let body = [
+        OpCode::PUSH1 as u8,
+        OpCode::PUSH2 as u8,
+        OpCode::CALL as u8,
+        0, // patched below
+        OpCode::DROP as u8,
+    ];
+    let mut script = counted_loop(&[], 1000, &body);
+    let call_offset = 6 + 2;
+    script[call_offset + 1] = (script.len() - call_offset) as u8;
+    script.extend_from_slice(&[
+        // This VM's CALL pushes the return address, taken as a third argument
+        OpCode::INITSLOT as u8,
+        1,
+        3,
+        OpCode::LDARG0 as u8,
+        OpCode::LDARG as u8,
+        1,
+        OpCode::ADD as u8,
+        OpCode::RET as u8,
+    ]);
+    group.throughput(Throughput::Elements(1000));
+    bench_script(&mut group, "initslot_call", &script);
+
+    group.finish();
+}
+
+fn bench_arithmetic(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arithmetic");
+
+    let body = [
+        OpCode::PUSH7 as u8,
+        OpCode::PUSH5 as u8,
+        OpCode::MUL as u8,
+        OpCode::PUSH3 as u8,
+        OpCode::ADD as u8,
+        OpCode::DROP as u8,
+    ];
+    let script = counted_loop(&[], 1000, &body);
+    group.throughput(Throughput::Elements(1000));
+    bench_script(&mut group, "loop", &script);
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_byte_strings, bench_calls, bench_arithmetic);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+//! Reference workloads from [`neo_vm_core::bench`]
+//!
+//! Throughput is in VM instructions, so criterion reports instructions per
+//! second. Run with `cargo bench -p neo-vm-core --bench workloads`; the
+//! prover's `sp1_cycles` bench runs the same scripts under SP1.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use neo_vm_core::bench::{bench_script, workloads};
+use neo_vm_core::{NeoVM, VMState};
+use std::hint::black_box;
+
+fn bench_workloads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("workloads");
+
+    for workload in workloads() {
+        let result = bench_script(&workload.script, 1).unwrap();
+        assert!(
+            matches!(result.state, VMState::Halt),
+            "{} faulted",
+            workload.name
+        );
+        group.throughput(Throughput::Elements(result.instructions));
+        group.bench_function(workload.name, |b| {
+            b.iter(|| {
+                let mut vm = NeoVM::new(u64::MAX);
+                vm.load_script(workload.script.clone()).unwrap();
+                vm.run();
+                black_box(vm)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_workloads);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+//! Build script generating the opcode and gas price tables from
+//! `opcodes.toml`
+
+#[path = "build/opcode_tables.rs"]
+mod opcode_tables;
+
+use opcode_tables::{write_out, OpcodeTable};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build/opcode_tables.rs");
+    let table = OpcodeTable::load("opcodes.toml".as_ref());
+    write_out("opcode.rs", &table.opcodes());
+    write_out("gas_prices.rs", &table.gas_prices());
+}
@@ -0,0 +1,191 @@
+//! Rust tables generated from `opcodes.toml`
+//!
+//! Shared by the build scripts of neo-vm-core and the guest program, which
+//! can't depend on neo-vm-core, so both charge from the same prices.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+
+const HEADER: &str = "// Generated from opcodes.toml by build/opcode_tables.rs; do not edit\n";
+
+/// The parsed opcode table
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpcodeTable {
+    legacy: Vec<LegacyRange>,
+    opcode: Vec<Opcode>,
+}
+
+/// Legacy price of every opcode in `from..=to`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyRange {
+    from: u8,
+    to: u8,
+    price: u16,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Opcode {
+    name: String,
+    code: u8,
+    operand: Option<usize>,
+    prefix: Option<usize>,
+    price: u32,
+    #[serde(default = "official")]
+    official: bool,
+}
+
+fn official() -> bool {
+    true
+}
+
+impl OpcodeTable {
+    /// Read and check the table at `path`, panicking on any error
+    pub fn load(path: &Path) -> Self {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let text = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("reading {}: {e}", path.display()));
+        let table: Self =
+            toml::from_str(&text).unwrap_or_else(|e| panic!("parsing {}: {e}", path.display()));
+        if let Err(e) = table.check() {
+            panic!("{}: {e}", path.display());
+        }
+        table
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let mut codes = HashSet::new();
+        let mut names = HashSet::new();
+        for op in &self.opcode {
+            if !codes.insert(op.code) {
+                return Err(format!("opcode 0x{:02X} is defined twice", op.code));
+            }
+            if !names.insert(op.name.as_str()) {
+                return Err(format!("{} is defined twice", op.name));
+            }
+            let identifier = op.name.starts_with(|c: char| c.is_ascii_uppercase())
+                && op
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+            if !identifier {
+                return Err(format!("{} is not an upper case identifier", op.name));
+            }
+            match (op.operand, op.prefix) {
+                (Some(_), Some(_)) => {
+                    return Err(format!("{} has both an operand and a prefix", op.name))
+                }
+                (_, Some(width)) if ![1, 2, 4].contains(&width) => {
+                    return Err(format!("{} has a {width}-byte prefix", op.name))
+                }
+                _ => {}
+            }
+        }
+
+        let mut next = 0usize;
+        for range in &self.legacy {
+            if range.from as usize != next || range.to < range.from {
+                return Err(format!(
+                    "legacy range 0x{:02X}-0x{:02X} does not start at 0x{next:02X}",
+                    range.from, range.to
+                ));
+            }
+            next = range.to as usize + 1;
+        }
+        if next != 256 {
+            return Err(format!("legacy ranges stop before 0x{next:02X}"));
+        }
+        Ok(())
+    }
+
+    /// `LEGACY_OPCODE_PRICES` and `NEO_N3_OPCODE_PRICES`, free of paths and
+    /// imports so the guest program can include them too
+    pub fn gas_prices(&self) -> String {
+        let mut legacy = [0u16; 256];
+        for range in &self.legacy {
+            legacy[range.from as usize..=range.to as usize].fill(range.price);
+        }
+        let mut neo_n3 = [0u32; 256];
+        for op in &self.opcode {
+            neo_n3[op.code as usize] = op.price;
+        }
+
+        let mut out = String::from(HEADER);
+        out.push_str("\n/// Legacy prices, the table the engine used before schedules existed\n");
+        out.push_str("const LEGACY_OPCODE_PRICES: [u16; 256] = ");
+        write_rows(&mut out, &legacy);
+        out.push_str(
+            "\n/// Neo N3 `OpCodePrices` in fee units; undefined opcodes fault and cost 0\n\
+             ///\n\
+             /// The crypto opcodes 0xF0-0xF3 have no Neo equivalent and are priced like\n\
+             /// the `CryptoLib` methods and the `System.Crypto.CheckSig` syscall they\n\
+             /// stand in for; `HASH160` pays for both of its hashes.\n",
+        );
+        out.push_str("const NEO_N3_OPCODE_PRICES: [u32; 256] = ");
+        write_rows(&mut out, &neo_n3);
+        out
+    }
+
+    /// `OpCode` and `OPCODE_INFO`, for neo-vm-core's `opcode` module
+    pub fn opcodes(&self) -> String {
+        let mut ops: Vec<&Opcode> = self.opcode.iter().collect();
+        ops.sort_by_key(|op| op.code);
+
+        let mut out = String::from(HEADER);
+        out.push_str(
+            "\n/// Neo VM Operation Codes\n\
+             ///\n\
+             /// Names follow the official Neo N3 specification exactly.\n\
+             #[derive(Debug, Clone, Copy, PartialEq, Eq)]\n\
+             #[repr(u8)]\n\
+             #[allow(non_camel_case_types)]\n\
+             pub enum OpCode {\n",
+        );
+        for op in ops.iter().filter(|op| op.official) {
+            writeln!(out, "    {} = 0x{:02X},", op.name, op.code).unwrap();
+        }
+        out.push_str("}\n\n/// Entry for each opcode byte, `None` where undefined\n");
+        out.push_str("static OPCODE_INFO: [Option<OpcodeInfo>; 256] = [\n");
+        let mut ops = ops.into_iter().peekable();
+        for code in 0..=u8::MAX {
+            match ops.next_if(|op| op.code == code) {
+                Some(op) => {
+                    let operand = match (op.operand, op.prefix) {
+                        (_, Some(width)) => format!("OperandSize::Prefix({width})"),
+                        (size, None) => format!("OperandSize::Fixed({})", size.unwrap_or(0)),
+                    };
+                    writeln!(
+                        out,
+                        "    Some(OpcodeInfo {{ name: {:?}, code: 0x{:02X}, operand: {operand}, official: {} }}),",
+                        op.name, op.code, op.official
+                    )
+                    .unwrap();
+                }
+                None => out.push_str("    None,\n"),
+            }
+        }
+        out.push_str("];\n");
+        out
+    }
+}
+
+/// `values` as an array literal, 16 to a row
+fn write_rows<T: std::fmt::Display>(out: &mut String, values: &[T]) {
+    out.push_str("[\n");
+    for (row, chunk) in values.chunks(16).enumerate() {
+        let cells: Vec<String> = chunk.iter().map(T::to_string).collect();
+        writeln!(out, "    // 0x{:02X}\n    {},", row * 16, cells.join(", ")).unwrap();
+    }
+    out.push_str("];\n");
+}
+
+/// Write `contents` to `name` in `OUT_DIR`
+pub fn write_out(name: &str, contents: &str) {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set for build scripts");
+    let path = Path::new(&out_dir).join(name);
+    std::fs::write(&path, contents).unwrap_or_else(|e| panic!("writing {}: {e}", path.display()));
+}
@@ -152,8 +152,10 @@ fn main() {
 
     // Simulate hash verification (used in real contracts)
     let test_data = b"transfer_signature_data";
-    let _hash_result = neo_vm_core::CryptoLib::new()
-        .invoke("sha256", vec![StackItem::ByteString(test_data.to_vec())]);
+    let _hash_result = neo_vm_core::CryptoLib::new().invoke(
+        "sha256",
+        vec![StackItem::ByteString(test_data.to_vec().into())],
+    );
 
     println!("Signature verification: ready (data hashed)");
 
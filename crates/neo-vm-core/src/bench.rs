@@ -0,0 +1,202 @@
+//! Benchmark helpers and reference workloads
+//!
+//! [`bench_script`] times a script on the host VM so contract authors can
+//! compare their scripts against the reference [`workloads`], which are the
+//! same scripts the crate's criterion benches and the prover's SP1 cycle
+//! bench run.
+//!
+//! The workloads are straight-line scripts so they also run on the SP1 guest
+//! program, which has no jumps; only [`deep_calls`] needs `CALL`.
+
+use crate::engine::syscall;
+use crate::{NeoVM, OpCode, ScriptBuilder, VMError, VMState};
+use std::time::{Duration, Instant};
+
+/// Host timing of a script, see [`bench_script`]
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    /// State the script finished in
+    pub state: VMState,
+    /// Instructions executed by one run
+    pub instructions: u64,
+    /// Gas consumed by one run
+    pub gas_consumed: u64,
+    /// Number of timed runs
+    pub iterations: u32,
+    /// Total time of the timed runs
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    /// Average time of one run
+    pub fn time_per_run(&self) -> Duration {
+        self.elapsed / self.iterations.max(1)
+    }
+
+    /// Instructions executed per second, averaged over all runs
+    pub fn instructions_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        (self.instructions * self.iterations as u64) as f64 / secs
+    }
+}
+
+/// Run `script` `iterations` times on fresh VMs and time the runs
+///
+/// Each run decodes the script and executes it without a gas limit, as a
+/// contract call would. One untimed run first counts instructions and gas;
+/// scripts that fault are still timed, with the fault reported in
+/// [`BenchResult::state`]. Fails only if the script cannot be decoded.
+pub fn bench_script(script: &[u8], iterations: u32) -> Result<BenchResult, VMError> {
+    let mut vm = NeoVM::new(u64::MAX);
+    vm.load_script(script.to_vec())?;
+    let mut instructions = 0u64;
+    while !matches!(vm.state, VMState::Halt | VMState::Fault) {
+        instructions += 1;
+        if vm.execute_next().is_err() {
+            vm.state = VMState::Fault;
+        }
+    }
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let mut run = NeoVM::new(u64::MAX);
+        run.load_script(script.to_vec())?;
+        run.run();
+        std::hint::black_box(&run);
+    }
+
+    Ok(BenchResult {
+        state: vm.state,
+        instructions,
+        gas_consumed: vm.gas_consumed,
+        iterations,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// A named reference script
+#[derive(Debug, Clone)]
+pub struct Workload {
+    pub name: &'static str,
+    pub script: Vec<u8>,
+}
+
+/// The reference workloads at their default sizes
+pub fn workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            name: "arithmetic",
+            script: arithmetic(1000),
+        },
+        Workload {
+            name: "hashing",
+            script: hashing(1000),
+        },
+        Workload {
+            name: "storage",
+            script: storage(250),
+        },
+        Workload {
+            name: "deep_calls",
+            script: deep_calls(1000),
+        },
+    ]
+}
+
+/// `7 * 5 + 3`, computed and dropped `rounds` times
+pub fn arithmetic(rounds: usize) -> Vec<u8> {
+    let mut sb = ScriptBuilder::new();
+    for _ in 0..rounds {
+        sb.emit(OpCode::PUSH7)
+            .emit(OpCode::PUSH5)
+            .emit(OpCode::MUL)
+            .emit(OpCode::PUSH3)
+            .emit(OpCode::ADD)
+            .emit(OpCode::DROP);
+    }
+    sb.emit(OpCode::RET);
+    sb.into_bytes()
+}
+
+/// A chain of `rounds` SHA256 hashes over a 32-byte seed
+pub fn hashing(rounds: usize) -> Vec<u8> {
+    /// SHA256, which has no `OpCode` variant
+    const SHA256: u8 = 0xF0;
+
+    let mut sb = ScriptBuilder::new();
+    sb.emit_push_bytes(&[0x42; 32]);
+    let mut script = sb.into_bytes();
+    script.extend_from_slice(&vec![SHA256; rounds]);
+    script.push(OpCode::RET as u8);
+    script
+}
+
+/// `entries` storage writes, each read back with a get
+pub fn storage(entries: u32) -> Vec<u8> {
+    let mut sb = ScriptBuilder::new();
+    for i in 0..entries {
+        let key = i.to_le_bytes();
+        sb.emit_push_bytes(&[0x42; 32])
+            .emit_push_bytes(&key)
+            .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+            .emit_push_bytes(&key)
+            .emit_syscall(syscall::SYSTEM_STORAGE_GET)
+            .emit(OpCode::DROP);
+    }
+    sb.emit(OpCode::RET);
+    sb.into_bytes()
+}
+
+/// `depth` nested calls, each function calling the next before returning
+///
+/// `depth` must stay below [`crate::DEFAULT_MAX_INVOCATION_DEPTH`].
+pub fn deep_calls(depth: usize) -> Vec<u8> {
+    let mut sb = ScriptBuilder::new();
+    sb.emit_with_operand(OpCode::CALL, &[3]).emit(OpCode::RET);
+    for level in 0..depth {
+        // Drop the return address CALL pushes
+        sb.emit(OpCode::DROP);
+        if level + 1 < depth {
+            sb.emit_with_operand(OpCode::CALL, &[3]);
+        }
+        sb.emit(OpCode::RET);
+    }
+    sb.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workloads_halt() {
+        for workload in workloads() {
+            let result = bench_script(&workload.script, 1).unwrap();
+            assert!(
+                matches!(result.state, VMState::Halt),
+                "{} faulted",
+                workload.name
+            );
+            assert!(result.gas_consumed > 0);
+        }
+    }
+
+    #[test]
+    fn test_bench_script_counts_instructions() {
+        let result = bench_script(&arithmetic(10), 3).unwrap();
+        assert_eq!(result.instructions, 61);
+        assert_eq!(result.iterations, 3);
+
+        // CALL, RET, then DROP/CALL/RET per level with one CALL fewer
+        assert_eq!(bench_script(&deep_calls(4), 1).unwrap().instructions, 13);
+
+        let faulting = [OpCode::PUSH5 as u8, OpCode::PUSH0 as u8, OpCode::DIV as u8];
+        assert!(matches!(
+            bench_script(&faulting, 1).unwrap().state,
+            VMState::Fault
+        ));
+    }
+}
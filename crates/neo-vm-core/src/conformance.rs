@@ -0,0 +1,430 @@
+//! Conformance runner for the reference neo-vm JSON test vectors
+//!
+//! The C# neo-vm repository ships its unit tests as JSON files (one per
+//! opcode under `tests/Neo.VM.Tests/Tests/OpCodes`). Each test assembles a
+//! script from opcode names and hex data, then applies a sequence of
+//! debugger actions and checks the VM state, invocation stack and result
+//! stack after each one. This module runs those files against [`NeoVM`] and
+//! collects a per-opcode [`ConformanceReport`].
+//!
+//! Stacks in the vectors are listed top first. Scripts are assembled with
+//! the reference opcode numbering from `opcodes.toml`, not the engine's
+//! dispatch, so a numbering mismatch shows up as a failing vector rather
+//! than being hidden.
+
+use crate::engine::{NeoVM, VMState};
+use crate::opcode::OpcodeInfo;
+use crate::stack_item::StackItem;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+/// Gas limit for vector scripts; the reference tests do not meter gas
+const GAS_LIMIT: u64 = 1_000_000_000;
+
+/// Reference opcode byte for `name`
+pub fn opcode_by_name(name: &str) -> Option<u8> {
+    OpcodeInfo::by_name(name)
+        .filter(|info| info.official)
+        .map(|info| info.code)
+}
+
+/// Reference name of opcode byte `op`
+pub fn opcode_name(op: u8) -> Option<&'static str> {
+    OpcodeInfo::of(op)
+        .filter(|info| info.official)
+        .map(|info| info.name)
+}
+
+/// Errors reading a test vector
+#[derive(Debug, Error)]
+pub enum ConformanceError {
+    #[error("Unknown opcode: {0}")]
+    UnknownOpcode(String),
+    #[error("Invalid hex: {0}")]
+    InvalidHex(String),
+    #[error("Invalid stack item: {0}")]
+    InvalidItem(String),
+    #[error("Invalid vector file: {0}")]
+    InvalidFile(String),
+}
+
+/// One JSON file: the tests of a single opcode
+#[derive(Debug, Clone, Deserialize)]
+pub struct VectorFile {
+    #[serde(default)]
+    pub category: String,
+    /// Opcode under test
+    pub name: String,
+    pub tests: Vec<VectorTest>,
+}
+
+impl VectorFile {
+    pub fn from_json(json: &str) -> Result<Self, ConformanceError> {
+        serde_json::from_str(json).map_err(|e| ConformanceError::InvalidFile(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VectorTest {
+    pub name: String,
+    /// Opcode names and `0x`-prefixed data, concatenated in order
+    pub script: Vec<String>,
+    pub steps: Vec<VectorStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VectorStep {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub actions: Vec<Action>,
+    pub result: ExpectedResult,
+}
+
+/// Debugger action applied before a step's result is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    Execute,
+    StepInto,
+    StepOut,
+    StepOver,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedResult {
+    pub state: String,
+    #[serde(default)]
+    pub invocation_stack: Option<Vec<ExpectedContext>>,
+    #[serde(default)]
+    pub result_stack: Option<Vec<Value>>,
+}
+
+/// Expected execution context, innermost first
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedContext {
+    pub instruction_pointer: usize,
+    #[serde(default)]
+    pub next_instruction: Option<String>,
+    #[serde(default)]
+    pub evaluation_stack: Option<Vec<Value>>,
+    #[serde(default)]
+    pub static_fields: Option<Vec<Value>>,
+    #[serde(default)]
+    pub local_variables: Option<Vec<Value>>,
+    #[serde(default)]
+    pub arguments: Option<Vec<Value>>,
+}
+
+/// Assemble a vector script
+pub fn assemble(script: &[String]) -> Result<Vec<u8>, ConformanceError> {
+    let mut out = Vec::new();
+    for part in script {
+        match part.strip_prefix("0x") {
+            Some(data) => out
+                .extend(hex::decode(data).map_err(|_| ConformanceError::InvalidHex(part.clone()))?),
+            None => out.push(
+                opcode_by_name(part)
+                    .ok_or_else(|| ConformanceError::UnknownOpcode(part.clone()))?,
+            ),
+        }
+    }
+    Ok(out)
+}
+
+/// Parse a vector stack item, e.g. `{"type": "Integer", "value": 1}`
+pub fn parse_item(value: &Value) -> Result<StackItem, ConformanceError> {
+    let invalid = || ConformanceError::InvalidItem(value.to_string());
+    let kind = value
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(invalid)?;
+    let inner = value.get("value");
+    let bytes = || -> Result<Vec<u8>, ConformanceError> {
+        let text = inner.and_then(Value::as_str).ok_or_else(invalid)?;
+        hex::decode(text.strip_prefix("0x").unwrap_or(text))
+            .map_err(|_| ConformanceError::InvalidHex(text.to_string()))
+    };
+    let items = || -> Result<Vec<StackItem>, ConformanceError> {
+        inner
+            .and_then(Value::as_array)
+            .ok_or_else(invalid)?
+            .iter()
+            .map(parse_item)
+            .collect()
+    };
+
+    Ok(match kind {
+        "Null" => StackItem::Null,
+        "Boolean" => StackItem::Boolean(inner.and_then(Value::as_bool).ok_or_else(invalid)?),
+        "Integer" => {
+            StackItem::Integer(parse_integer(inner.ok_or_else(invalid)?).ok_or_else(invalid)?)
+        }
+        "ByteString" => StackItem::ByteString(bytes()?.into()),
+        "Buffer" => StackItem::Buffer(bytes()?),
+        "Array" => StackItem::Array(items()?),
+        "Struct" => StackItem::Struct(items()?),
+        "Map" => StackItem::Map(
+            inner
+                .and_then(Value::as_object)
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|(key, item)| Ok((parse_map_key(key), parse_item(item)?)))
+                .collect::<Result<_, ConformanceError>>()?,
+        ),
+        "Pointer" => StackItem::Pointer(
+            inner
+                .and_then(Value::as_u64)
+                .and_then(|p| u32::try_from(p).ok())
+                .ok_or_else(invalid)?,
+        ),
+        _ => return Err(invalid()),
+    })
+}
+
+/// Integers are written as JSON numbers or, when large, as decimal strings
+fn parse_integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::Number(n) => n.as_i64().map(i128::from),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Map keys are hex byte strings or decimal integers
+fn parse_map_key(key: &str) -> StackItem {
+    match key.strip_prefix("0x").map(hex::decode) {
+        Some(Ok(bytes)) => StackItem::ByteString(bytes.into()),
+        _ => key
+            .parse()
+            .map(StackItem::Integer)
+            .unwrap_or_else(|_| StackItem::ByteString(key.as_bytes().to_vec().into())),
+    }
+}
+
+/// Run one test, returning a description of the first mismatch
+pub fn run_test(test: &VectorTest) -> Result<(), String> {
+    let script = assemble(&test.script).map_err(|e| e.to_string())?;
+    let mut vm = NeoVM::new(GAS_LIMIT);
+    if vm.load_script(script).is_err() {
+        vm.state = VMState::Fault;
+    }
+
+    for (i, step) in test.steps.iter().enumerate() {
+        for action in &step.actions {
+            apply(&mut vm, *action);
+        }
+        check(&vm, &step.result).map_err(|e| match &step.name {
+            Some(name) => format!("step {i} ({name}): {e}"),
+            None => format!("step {i}: {e}"),
+        })?;
+    }
+    Ok(())
+}
+
+fn is_done(vm: &NeoVM) -> bool {
+    matches!(vm.state, VMState::Halt | VMState::Fault)
+}
+
+fn step(vm: &mut NeoVM) {
+    if vm.execute_next().is_err() {
+        vm.state = VMState::Fault;
+    }
+}
+
+fn apply(vm: &mut NeoVM, action: Action) {
+    if is_done(vm) {
+        return;
+    }
+    let depth = vm.invocation_stack.len();
+    match action {
+        Action::Execute => {
+            while !is_done(vm) {
+                step(vm);
+            }
+            return;
+        }
+        Action::StepInto => step(vm),
+        Action::StepOut => {
+            while !is_done(vm) && vm.invocation_stack.len() >= depth {
+                step(vm);
+            }
+        }
+        Action::StepOver => {
+            step(vm);
+            while !is_done(vm) && vm.invocation_stack.len() > depth {
+                step(vm);
+            }
+        }
+    }
+    if !is_done(vm) {
+        vm.state = VMState::Break;
+    }
+}
+
+fn state_name(state: &VMState) -> &'static str {
+    match state {
+        VMState::None => "NONE",
+        VMState::Halt => "HALT",
+        VMState::Fault => "FAULT",
+        VMState::Break => "BREAK",
+    }
+}
+
+/// Compare `actual`, bottom first, with `expected`, top first
+fn check_stack(what: &str, actual: &[StackItem], expected: &[Value]) -> Result<(), String> {
+    let expected = expected
+        .iter()
+        .rev()
+        .map(parse_item)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if actual != expected.as_slice() {
+        return Err(format!("{what}: expected {expected:?}, got {actual:?}"));
+    }
+    Ok(())
+}
+
+/// Compare slots, which the vectors list in index order
+fn check_slots(what: &str, actual: &[StackItem], expected: &[Value]) -> Result<(), String> {
+    let expected = expected
+        .iter()
+        .map(parse_item)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if actual != expected.as_slice() {
+        return Err(format!("{what}: expected {expected:?}, got {actual:?}"));
+    }
+    Ok(())
+}
+
+fn check(vm: &NeoVM, expected: &ExpectedResult) -> Result<(), String> {
+    let state = state_name(&vm.state);
+    if !state.eq_ignore_ascii_case(&expected.state) {
+        return Err(format!("expected state {}, got {state}", expected.state));
+    }
+
+    if let Some(contexts) = &expected.invocation_stack {
+        if contexts.len() != vm.invocation_stack.len() {
+            return Err(format!(
+                "expected {} contexts, got {}",
+                contexts.len(),
+                vm.invocation_stack.len()
+            ));
+        }
+        for (depth, (want, ctx)) in contexts
+            .iter()
+            .zip(vm.invocation_stack.iter().rev())
+            .enumerate()
+        {
+            if want.instruction_pointer != ctx.ip {
+                return Err(format!(
+                    "context {depth}: expected ip {}, got {}",
+                    want.instruction_pointer, ctx.ip
+                ));
+            }
+            if let Some(next) = &want.next_instruction {
+                let actual = ctx
+                    .script
+                    .get(ctx.ip)
+                    .map_or(Some("RET"), |op| opcode_name(*op));
+                if actual != Some(next.as_str()) {
+                    return Err(format!(
+                        "context {depth}: expected next instruction {next}, got {actual:?}"
+                    ));
+                }
+            }
+            // Contexts share the engine's stack and slots, so only the
+            // innermost one can be compared
+            if depth > 0 {
+                continue;
+            }
+            if let Some(stack) = &want.evaluation_stack {
+                check_stack("evaluation stack", &vm.eval_stack, stack)?;
+            }
+            if let Some(fields) = &want.static_fields {
+                check_slots("static fields", &vm.static_slots, fields)?;
+            }
+            if let Some(locals) = &want.local_variables {
+                check_slots("local variables", &vm.local_slots, locals)?;
+            }
+            if let Some(args) = &want.arguments {
+                check_slots("arguments", &vm.argument_slots, args)?;
+            }
+        }
+    }
+
+    if let Some(stack) = &expected.result_stack {
+        if matches!(vm.state, VMState::Halt) {
+            check_stack("result stack", &vm.eval_stack, stack)?;
+        }
+    }
+    Ok(())
+}
+
+/// Results for the tests of one opcode
+#[derive(Debug, Clone, Default)]
+pub struct OpcodeConformance {
+    pub passed: usize,
+    /// Test names with the reason each one failed
+    pub failed: Vec<(String, String)>,
+}
+
+/// Per-opcode results of one or more vector files
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceReport {
+    pub opcodes: BTreeMap<String, OpcodeConformance>,
+}
+
+impl ConformanceReport {
+    /// Run every test in `file`, recording the results under its opcode
+    pub fn run_file(&mut self, file: &VectorFile) {
+        let entry = self.opcodes.entry(file.name.clone()).or_default();
+        for test in &file.tests {
+            match run_test(test) {
+                Ok(()) => entry.passed += 1,
+                Err(reason) => entry.failed.push((test.name.clone(), reason)),
+            }
+        }
+    }
+
+    pub fn passed(&self) -> usize {
+        self.opcodes.values().map(|o| o.passed).sum()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.opcodes.values().map(|o| o.failed.len()).sum()
+    }
+
+    pub fn is_conformant(&self) -> bool {
+        self.failed() == 0
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (opcode, result) in &self.opcodes {
+            let total = result.passed + result.failed.len();
+            let mark = if result.failed.is_empty() {
+                "ok"
+            } else {
+                "FAIL"
+            };
+            writeln!(f, "{opcode:<12} {}/{total} {mark}", result.passed)?;
+            for (test, reason) in &result.failed {
+                writeln!(f, "    {test}: {reason}")?;
+            }
+        }
+        write!(
+            f,
+            "{} passed, {} failed across {} opcodes",
+            self.passed(),
+            self.failed(),
+            self.opcodes.len()
+        )
+    }
+}
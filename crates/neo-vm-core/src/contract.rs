@@ -0,0 +1,159 @@
+//! Contract calls and call flags
+//!
+//! Contracts registered in [`NeoVM::contracts`](crate::NeoVM::contracts) are
+//! called with `System.Contract.Call`, which names the contract, the method,
+//! the [`CallFlags`] the callee may use and an array of arguments.
+//!
+//! Every execution context carries call flags, as on Neo. A loaded script
+//! starts with [`CallFlags::ALL`]; a called contract gets the requested flags
+//! narrowed to the caller's, and to [`CallFlags::READ_ONLY`] for safe
+//! methods. Syscalls fail with [`VMError::MissingCallFlags`] when the current
+//! context lacks what they need, so a read-only call cannot write storage or
+//! emit notifications.
+//!
+//! [`VMError::MissingCallFlags`]: crate::VMError::MissingCallFlags
+
+use crate::instruction::{decode_script, DecodedScript};
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+use std::sync::Arc;
+
+/// What an execution context is allowed to do, with Neo's bit values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CallFlags(u8);
+
+impl CallFlags {
+    pub const NONE: CallFlags = CallFlags(0);
+    /// Read contract storage
+    pub const READ_STATES: CallFlags = CallFlags(0b0001);
+    /// Write or delete contract storage
+    pub const WRITE_STATES: CallFlags = CallFlags(0b0010);
+    /// Call other contracts
+    pub const ALLOW_CALL: CallFlags = CallFlags(0b0100);
+    /// Log and send notifications
+    pub const ALLOW_NOTIFY: CallFlags = CallFlags(0b1000);
+    pub const STATES: CallFlags = CallFlags(0b0011);
+    pub const READ_ONLY: CallFlags = CallFlags(0b0101);
+    pub const ALL: CallFlags = CallFlags(0b1111);
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Flags from their bit values, `None` if any undefined bit is set
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL.0 == 0 {
+            Some(CallFlags(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Whether every flag in `other` is set
+    pub const fn contains(self, other: CallFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitAnd for CallFlags {
+    type Output = CallFlags;
+
+    fn bitand(self, rhs: CallFlags) -> CallFlags {
+        CallFlags(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for CallFlags {
+    type Output = CallFlags;
+
+    fn bitor(self, rhs: CallFlags) -> CallFlags {
+        CallFlags(self.0 | rhs.0)
+    }
+}
+
+/// Neo's flag names, e.g. `ReadStates, AllowCall`
+impl fmt::Display for CallFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [(CallFlags, &str); 4] = [
+            (CallFlags::READ_STATES, "ReadStates"),
+            (CallFlags::WRITE_STATES, "WriteStates"),
+            (CallFlags::ALLOW_CALL, "AllowCall"),
+            (CallFlags::ALLOW_NOTIFY, "AllowNotify"),
+        ];
+        match *self {
+            CallFlags::NONE => f.write_str("None"),
+            CallFlags::ALL => f.write_str("All"),
+            flags => {
+                let names: Vec<_> = NAMES
+                    .iter()
+                    .filter(|(flag, _)| flags.contains(*flag))
+                    .map(|(_, name)| *name)
+                    .collect();
+                f.write_str(&names.join(", "))
+            }
+        }
+    }
+}
+
+/// A method from a contract's ABI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractMethod {
+    pub name: String,
+    pub parameters: usize,
+    /// Entry point within the contract script
+    pub offset: usize,
+    /// Safe methods run read-only whatever flags the caller passes
+    pub safe: bool,
+}
+
+/// A contract callable through `System.Contract.Call`
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub hash: [u8; 20],
+    pub methods: Vec<ContractMethod>,
+    pub(crate) script: Arc<[u8]>,
+    pub(crate) instructions: Arc<DecodedScript>,
+}
+
+impl Contract {
+    /// Create a contract, decoding its script once for every call
+    pub fn new(hash: [u8; 20], script: Vec<u8>, methods: Vec<ContractMethod>) -> Self {
+        let instructions = Arc::new(decode_script(&script));
+        Self {
+            hash,
+            methods,
+            script: Arc::from(script),
+            instructions,
+        }
+    }
+
+    pub fn script(&self) -> &[u8] {
+        &self.script
+    }
+
+    /// Find a method by name and parameter count, as Neo resolves calls
+    pub fn method(&self, name: &str, parameters: usize) -> Option<&ContractMethod> {
+        self.methods
+            .iter()
+            .find(|m| m.name == name && m.parameters == parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_flags() {
+        assert_eq!(
+            CallFlags::READ_STATES | CallFlags::ALLOW_CALL,
+            CallFlags::READ_ONLY
+        );
+        assert!(CallFlags::ALL.contains(CallFlags::STATES));
+        assert!(!CallFlags::READ_ONLY.contains(CallFlags::WRITE_STATES));
+        assert_eq!(CallFlags::from_bits(0x0F), Some(CallFlags::ALL));
+        assert_eq!(CallFlags::from_bits(0x10), None);
+        assert_eq!(CallFlags::READ_ONLY.to_string(), "ReadStates, AllowCall");
+        assert_eq!(CallFlags::NONE.to_string(), "None");
+    }
+}
@@ -0,0 +1,256 @@
+//! Neo contract parameters and their RPC JSON format
+//!
+//! Neo RPC methods such as `invokefunction`, SDKs and wallets describe
+//! arguments as contract parameters, e.g. `{"type":"Integer","value":"5"}`.
+//! [`ContractParameter`] reads and writes that JSON, and
+//! [`StackItem::from_contract_parameter`] turns a parameter into the item
+//! the reference `ScriptBuilder` would push, so existing tooling can supply
+//! `ProofInput::arguments` directly.
+//!
+//! Hashes are written big-endian with a `0x` prefix, as `UInt160` and
+//! `UInt256` print them, and held little-endian as they are on the stack.
+//! Byte arrays and signatures are base64, public keys hex.
+
+use crate::stack_item::StackItem;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// A typed argument as used by Neo RPC and wallets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractParameter {
+    Any,
+    Boolean(bool),
+    Integer(i128),
+    ByteArray(Vec<u8>),
+    String(String),
+    /// Little-endian script hash
+    Hash160([u8; 20]),
+    /// Little-endian hash
+    Hash256([u8; 32]),
+    /// Compressed secp256r1 point
+    PublicKey([u8; 33]),
+    Signature([u8; 64]),
+    Array(Vec<ContractParameter>),
+    Map(Vec<(ContractParameter, ContractParameter)>),
+}
+
+/// Why a parameter or stack item could not be converted
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ContractParameterError {
+    #[error("Invalid contract parameter: {0}")]
+    Invalid(String),
+    #[error("Unsupported contract parameter type '{0}'")]
+    UnsupportedType(String),
+    #[error("{0} stack items have no contract parameter form")]
+    Unrepresentable(&'static str),
+}
+
+impl ContractParameter {
+    /// Name of the parameter type, as used in the `type` field
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Any => "Any",
+            Self::Boolean(_) => "Boolean",
+            Self::Integer(_) => "Integer",
+            Self::ByteArray(_) => "ByteArray",
+            Self::String(_) => "String",
+            Self::Hash160(_) => "Hash160",
+            Self::Hash256(_) => "Hash256",
+            Self::PublicKey(_) => "PublicKey",
+            Self::Signature(_) => "Signature",
+            Self::Array(_) => "Array",
+            Self::Map(_) => "Map",
+        }
+    }
+
+    /// Encode as Neo RPC JSON
+    pub fn to_json(&self) -> Value {
+        let value = match self {
+            Self::Any => return json!({ "type": "Any" }),
+            Self::Boolean(b) => json!(b),
+            Self::Integer(i) => json!(i.to_string()),
+            Self::ByteArray(bytes) => json!(BASE64.encode(bytes)),
+            Self::String(s) => json!(s),
+            Self::Hash160(hash) => json!(hash_to_string(hash)),
+            Self::Hash256(hash) => json!(hash_to_string(hash)),
+            Self::PublicKey(key) => json!(hex::encode(key)),
+            Self::Signature(sig) => json!(BASE64.encode(sig)),
+            Self::Array(items) => Value::Array(items.iter().map(Self::to_json).collect()),
+            Self::Map(entries) => Value::Array(
+                entries
+                    .iter()
+                    .map(|(k, v)| json!({ "key": k.to_json(), "value": v.to_json() }))
+                    .collect(),
+            ),
+        };
+        json!({ "type": self.type_name(), "value": value })
+    }
+
+    /// Decode Neo RPC JSON
+    ///
+    /// Integers may be given as decimal strings or JSON numbers.
+    pub fn from_json(json: &Value) -> Result<Self, ContractParameterError> {
+        let invalid = || ContractParameterError::Invalid(json.to_string());
+        let kind = json
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(invalid)?;
+        let value = json.get("value").filter(|v| !v.is_null());
+        let text = || value.and_then(Value::as_str).ok_or_else(invalid);
+        let base64 = || BASE64.decode(text()?).map_err(|_| invalid());
+
+        Ok(match kind {
+            "Any" => Self::Any,
+            "Boolean" => Self::Boolean(value.and_then(Value::as_bool).ok_or_else(invalid)?),
+            "Integer" => Self::Integer(
+                match value.ok_or_else(invalid)? {
+                    Value::String(s) => s.parse().ok(),
+                    Value::Number(n) => n.as_i64().map(i128::from),
+                    _ => None,
+                }
+                .ok_or_else(invalid)?,
+            ),
+            "ByteArray" => Self::ByteArray(base64()?),
+            "String" => Self::String(text()?.to_string()),
+            "Hash160" => Self::Hash160(parse_hash(text()?).ok_or_else(invalid)?),
+            "Hash256" => Self::Hash256(parse_hash(text()?).ok_or_else(invalid)?),
+            "PublicKey" => {
+                let key: [u8; 33] = hex::decode(text()?)
+                    .ok()
+                    .and_then(|k| k.try_into().ok())
+                    .ok_or_else(invalid)?;
+                if !matches!(key[0], 0x02 | 0x03) {
+                    return Err(invalid());
+                }
+                Self::PublicKey(key)
+            }
+            "Signature" => Self::Signature(base64()?.try_into().map_err(|_| invalid())?),
+            "Array" => Self::Array(
+                value
+                    .and_then(Value::as_array)
+                    .ok_or_else(invalid)?
+                    .iter()
+                    .map(Self::from_json)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "Map" => Self::Map(
+                value
+                    .and_then(Value::as_array)
+                    .ok_or_else(invalid)?
+                    .iter()
+                    .map(|entry| {
+                        let key = entry.get("key").ok_or_else(invalid)?;
+                        let value = entry.get("value").ok_or_else(invalid)?;
+                        Ok((Self::from_json(key)?, Self::from_json(value)?))
+                    })
+                    .collect::<Result<_, ContractParameterError>>()?,
+            ),
+            other => return Err(ContractParameterError::UnsupportedType(other.to_string())),
+        })
+    }
+}
+
+impl Serialize for ContractParameter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ContractParameter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        Self::from_json(&json).map_err(de::Error::custom)
+    }
+}
+
+impl StackItem {
+    /// The item pushed for `param`
+    ///
+    /// Strings become their UTF-8 bytes and hashes their little-endian
+    /// bytes; byte-like parameters all become byte strings.
+    pub fn from_contract_parameter(param: &ContractParameter) -> Self {
+        match param {
+            ContractParameter::Any => StackItem::Null,
+            ContractParameter::Boolean(b) => StackItem::Boolean(*b),
+            ContractParameter::Integer(i) => StackItem::Integer(*i),
+            ContractParameter::ByteArray(bytes) => StackItem::ByteString(bytes.clone().into()),
+            ContractParameter::String(s) => StackItem::ByteString(s.clone().into_bytes().into()),
+            ContractParameter::Hash160(hash) => StackItem::ByteString(hash.to_vec().into()),
+            ContractParameter::Hash256(hash) => StackItem::ByteString(hash.to_vec().into()),
+            ContractParameter::PublicKey(key) => StackItem::ByteString(key.to_vec().into()),
+            ContractParameter::Signature(sig) => StackItem::ByteString(sig.to_vec().into()),
+            ContractParameter::Array(items) => {
+                StackItem::Array(items.iter().map(Self::from_contract_parameter).collect())
+            }
+            ContractParameter::Map(entries) => StackItem::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            Self::from_contract_parameter(k),
+                            Self::from_contract_parameter(v),
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The parameter describing this item
+    ///
+    /// The stack does not keep parameter types, so byte strings and buffers
+    /// come back as `ByteArray` and structs as `Array`. Pointers have no
+    /// parameter form.
+    pub fn to_contract_parameter(&self) -> Result<ContractParameter, ContractParameterError> {
+        Ok(match self {
+            StackItem::Null => ContractParameter::Any,
+            StackItem::Boolean(b) => ContractParameter::Boolean(*b),
+            StackItem::Integer(i) => ContractParameter::Integer(*i),
+            StackItem::ByteString(bytes) => ContractParameter::ByteArray(bytes.to_vec()),
+            StackItem::Buffer(bytes) => ContractParameter::ByteArray(bytes.clone()),
+            StackItem::Array(items) | StackItem::Struct(items) => ContractParameter::Array(
+                items
+                    .iter()
+                    .map(Self::to_contract_parameter)
+                    .collect::<Result<_, _>>()?,
+            ),
+            StackItem::Map(entries) => ContractParameter::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| Ok((k.to_contract_parameter()?, v.to_contract_parameter()?)))
+                    .collect::<Result<_, ContractParameterError>>()?,
+            ),
+            StackItem::Pointer(_) => {
+                return Err(ContractParameterError::Unrepresentable("Pointer"))
+            }
+        })
+    }
+}
+
+/// Parse a JSON array of contract parameters into stack items, e.g. the
+/// `params` of an `invokefunction` request
+pub fn parse_arguments(json: &str) -> Result<Vec<StackItem>, ContractParameterError> {
+    let params: Vec<ContractParameter> =
+        serde_json::from_str(json).map_err(|e| ContractParameterError::Invalid(e.to_string()))?;
+    Ok(params
+        .iter()
+        .map(StackItem::from_contract_parameter)
+        .collect())
+}
+
+/// Big-endian `0x` hex of a little-endian hash
+fn hash_to_string(hash: &[u8]) -> String {
+    let mut bytes = hash.to_vec();
+    bytes.reverse();
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Inverse of [`hash_to_string`]; the `0x` prefix is optional
+fn parse_hash<const N: usize>(text: &str) -> Option<[u8; N]> {
+    let mut bytes = hex::decode(text.strip_prefix("0x").unwrap_or(text)).ok()?;
+    bytes.reverse();
+    bytes.try_into().ok()
+}
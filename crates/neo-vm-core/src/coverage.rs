@@ -0,0 +1,76 @@
+//! Execution coverage
+//!
+//! With [`NeoVM::enable_coverage`](crate::NeoVM::enable_coverage), the VM
+//! counts how often each instruction of the loaded script starts executing,
+//! including instructions that fault. Instructions of called contracts are
+//! not counted, since their offsets belong to other scripts.
+
+use crate::instruction::decode_script;
+
+/// Hit counts by byte offset in the loaded script
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    hits: Vec<u64>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one execution of the instruction at `ip`
+    #[inline]
+    pub fn record(&mut self, ip: usize) {
+        if ip >= self.hits.len() {
+            self.hits.resize(ip + 1, 0);
+        }
+        self.hits[ip] += 1;
+    }
+
+    /// Times the instruction at `ip` executed
+    pub fn hits(&self, ip: usize) -> u64 {
+        self.hits.get(ip).copied().unwrap_or(0)
+    }
+
+    /// Offsets that executed at least once, in order
+    pub fn covered_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.hits
+            .iter()
+            .enumerate()
+            .filter(|(_, &hits)| hits > 0)
+            .map(|(ip, _)| ip)
+    }
+
+    /// Instructions of `script` that executed, out of all its instructions
+    ///
+    /// Instructions are found by decoding `script` from the start, so data
+    /// after an undecodable byte is not counted.
+    pub fn summary(&self, script: &[u8]) -> (usize, usize) {
+        let instructions = decode_script(script);
+        let covered = instructions
+            .instructions()
+            .iter()
+            .filter(|instruction| self.hits(instruction.offset) > 0)
+            .count();
+        (covered, instructions.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage_summary() {
+        // PUSH1, JMP +3, PUSH2, RET
+        let script = [0x11, 0x22, 0x03, 0x12, 0x40];
+        let mut coverage = Coverage::new();
+        for ip in [0, 1, 4, 4] {
+            coverage.record(ip);
+        }
+        assert_eq!(coverage.hits(4), 2);
+        assert_eq!(coverage.hits(3), 0);
+        assert_eq!(coverage.covered_offsets().collect::<Vec<_>>(), [0, 1, 4]);
+        assert_eq!(coverage.summary(&script), (3, 4));
+    }
+}
@@ -0,0 +1,220 @@
+//! Script disassembler
+//!
+//! Renders scripts one instruction per line with decoded operands and jump
+//! targets, for the CLI, the debugger and the wasm bindings. Names and
+//! operand sizes come from the [`OpcodeInfo`] table.
+
+use crate::instruction::OperandSize;
+use crate::OpcodeInfo;
+
+pub struct Disassembler<'a> {
+    script: &'a [u8],
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(script: &'a [u8]) -> Self {
+        Self { script }
+    }
+
+    pub fn disassemble(&self) -> String {
+        let mut output = String::new();
+        let mut ip = 0;
+
+        while ip < self.script.len() {
+            let (name, size) = self.decode_instruction(ip);
+            let bytes = &self.script[ip..ip + size.min(self.script.len() - ip)];
+            let hex_bytes = bytes
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            output.push_str(&format!("{:04X}:  {:16}  {}\n", ip, hex_bytes, name));
+
+            ip += size;
+        }
+
+        output
+    }
+
+    pub fn decode_instruction(&self, ip: usize) -> (String, usize) {
+        if ip >= self.script.len() {
+            return ("???".to_string(), 1);
+        }
+
+        let op = self.script[ip];
+        let Some(info) = OpcodeInfo::of(op) else {
+            return (format!("??? (0x{:02X})", op), 1);
+        };
+        let name = info.name;
+        let (operand, len) = match info.operand {
+            OperandSize::Fixed(n) => (ip + 1, n),
+            OperandSize::Prefix(1) => (ip + 2, self.read_u8(ip + 1) as usize),
+            OperandSize::Prefix(2) => (ip + 3, self.read_u16(ip + 1) as usize),
+            OperandSize::Prefix(width) => (ip + 1 + width, self.read_u32(ip + 1) as usize),
+        };
+        let size = operand - ip + len;
+
+        let text = match name {
+            // Constants
+            "PUSHINT8" => format!("{} {}", name, self.read_i8(operand)),
+            "PUSHINT16" => format!("{} {}", name, self.read_i16(operand)),
+            "PUSHINT32" => format!("{} {}", name, self.read_i32(operand)),
+            "PUSHINT64" => format!("{} {}", name, self.read_i64(operand)),
+            "PUSHA" => format!("{} {:+}", name, self.read_i32(operand)),
+            "PUSHDATA1" => {
+                let data = self.read_bytes(operand, len);
+                format!("{} 0x{}", name, hex::encode(&data))
+            }
+            "PUSHDATA2" => {
+                let data = self.read_bytes(operand, len.min(32));
+                let suffix = if len > 32 { "..." } else { "" };
+                format!("{} 0x{}{}", name, hex::encode(&data), suffix)
+            }
+            "PUSHDATA4" => format!("{} [{}B]", name, len),
+
+            // Flow control
+            "CALLT" => format!("{} {}", name, self.read_u16(operand)),
+            "TRY" => {
+                let catch = self.read_i8(operand);
+                let finally = self.read_i8(operand + 1);
+                format!("{} catch:{:+} finally:{:+}", name, catch, finally)
+            }
+            "TRY_L" => {
+                let catch = self.read_i32(operand);
+                let finally = self.read_i32(operand + 4);
+                format!("{} catch:{:+} finally:{:+}", name, catch, finally)
+            }
+            "ENDTRY" => format!("{} {:+}", name, self.read_i8(operand)),
+            "ENDTRY_L" => format!("{} {:+}", name, self.read_i32(operand)),
+            "SYSCALL" => {
+                let id = self.read_u32(operand);
+                format!("{} {} (0x{:08X})", name, self.syscall_name(id), id)
+            }
+            // Jumps and calls
+            _ if (0x22..=0x35).contains(&op) => {
+                let offset = if len == 1 {
+                    self.read_i8(operand) as i32
+                } else {
+                    self.read_i32(operand)
+                };
+                let target = (ip as isize + offset as isize) as usize;
+                format!("{} {:+} -> 0x{:04X}", name, offset, target)
+            }
+
+            // Slots
+            "INITSLOT" => {
+                let locals = self.read_u8(operand);
+                let args = self.read_u8(operand + 1);
+                format!("{} locals:{} args:{}", name, locals, args)
+            }
+
+            // Types
+            "NEWARRAY_T" | "ISTYPE" | "CONVERT" => {
+                format!("{} {}", name, self.type_name(self.read_u8(operand)))
+            }
+
+            // INITSSLOT's count and the slot indexes; any other operand as hex
+            _ => match len {
+                0 => name.to_string(),
+                1 => format!("{} {}", name, self.read_u8(operand)),
+                _ => format!("{} 0x{}", name, hex::encode(self.read_bytes(operand, len))),
+            },
+        };
+        (text, size)
+    }
+
+    fn read_u8(&self, pos: usize) -> u8 {
+        self.script.get(pos).copied().unwrap_or(0)
+    }
+
+    fn read_i8(&self, pos: usize) -> i8 {
+        self.read_u8(pos) as i8
+    }
+
+    fn read_u16(&self, pos: usize) -> u16 {
+        let b0 = self.read_u8(pos) as u16;
+        let b1 = self.read_u8(pos + 1) as u16;
+        b0 | (b1 << 8)
+    }
+
+    fn read_i16(&self, pos: usize) -> i16 {
+        self.read_u16(pos) as i16
+    }
+
+    fn read_u32(&self, pos: usize) -> u32 {
+        let b0 = self.read_u8(pos) as u32;
+        let b1 = self.read_u8(pos + 1) as u32;
+        let b2 = self.read_u8(pos + 2) as u32;
+        let b3 = self.read_u8(pos + 3) as u32;
+        b0 | (b1 << 8) | (b2 << 16) | (b3 << 24)
+    }
+
+    fn read_i32(&self, pos: usize) -> i32 {
+        self.read_u32(pos) as i32
+    }
+
+    fn read_i64(&self, pos: usize) -> i64 {
+        let lo = self.read_u32(pos) as u64;
+        let hi = self.read_u32(pos + 4) as u64;
+        (lo | (hi << 32)) as i64
+    }
+
+    fn read_bytes(&self, pos: usize, len: usize) -> Vec<u8> {
+        let end = (pos + len).min(self.script.len());
+        self.script.get(pos..end).unwrap_or(&[]).to_vec()
+    }
+
+    fn syscall_name(&self, id: u32) -> &'static str {
+        match id {
+            0x01 => "System.Runtime.Log",
+            0x02 => "System.Runtime.Notify",
+            0x03 => "System.Runtime.GetTime",
+            0x10 => "System.Storage.Get",
+            0x11 => "System.Storage.Put",
+            0x12 => "System.Storage.Delete",
+            _ => "Unknown",
+        }
+    }
+
+    fn type_name(&self, t: u8) -> &'static str {
+        match t {
+            0x00 => "Any",
+            0x10 => "Pointer",
+            0x20 => "Boolean",
+            0x21 => "Integer",
+            0x28 => "ByteString",
+            0x30 => "Buffer",
+            0x40 => "Array",
+            0x41 => "Struct",
+            0x48 => "Map",
+            0x60 => "InteropInterface",
+            _ => "Unknown",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        // PUSH2, PUSH3, ADD, JMP +2, PUSHDATA1 "ab", SYSCALL GetTime, RET
+        let script = [
+            0x12, 0x13, 0x9E, 0x22, 0x02, 0x0C, 0x02, b'a', b'b', 0x41, 0x03, 0x00, 0x00, 0x00,
+            0x40,
+        ];
+        let listing = Disassembler::new(&script).disassemble();
+        let lines: Vec<_> = listing.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[2].ends_with("ADD"));
+        assert!(lines[3].ends_with("JMP +2 -> 0x0005"));
+        assert!(lines[4].ends_with("PUSHDATA1 0x6162"));
+        assert!(lines[5].contains("System.Runtime.GetTime"));
+        assert_eq!(
+            Disassembler::new(&[0x06]).decode_instruction(0),
+            ("??? (0x06)".to_string(), 1)
+        );
+    }
+}
@@ -4,13 +4,34 @@
 //!
 //! Core execution engine for Neo zkVM.
 
+use crate::contract::{CallFlags, Contract};
+use crate::coverage::Coverage;
+use crate::fault::FaultInfo;
+use crate::gas::{GasBreakdown, GasSchedule, OpCategory};
+use crate::hook::{ExecutionHook, HookAction};
+use crate::instruction::{decode_script, DecodedScript, Instruction};
+use crate::limits::VMLimits;
+use crate::native::NativeRegistry;
+use crate::neo_types::UInt160;
+use crate::replay::{Replay, ReplayEvent, ReplayLog};
+use crate::runtime::{NotificationEvent, RuntimeConfig, TimeProvider, MAX_EVENT_NAME, PLATFORM};
 use crate::stack_item::StackItem;
-use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use crate::storage::{make_key, StorageBackend, StorageContext, TrackedStorage};
+use crate::trace::TraceSink;
+use crate::watch::{WatchHit, WatchKind, Watchpoint};
+use bytes::Bytes;
+use k256::ecdsa::signature::Verifier;
 use ripemd::Ripemd160;
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+pub use crate::trace::{ExecutionTrace, TraceStep};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum VMError {
     #[error("Stack underflow")]
     StackUnderflow,
@@ -38,6 +59,62 @@ pub enum VMError {
     SignatureVerificationFailed,
     #[error("Invocation depth exceeded: max {0}")]
     InvocationDepthExceeded(usize),
+    #[error("No script container to check signatures against")]
+    NoScriptContainer,
+    #[error("Missing call flags: {0}")]
+    MissingCallFlags(CallFlags),
+    #[error("Contract not found")]
+    ContractNotFound,
+    #[error("Method {name} taking {parameters} parameters not found")]
+    MethodNotFound { name: String, parameters: usize },
+    #[error("Storage key is not covered by a storage witness")]
+    UnwitnessedStorage,
+    #[error("Execution diverged from the replay log")]
+    ReplayDiverged,
+    /// A jump, call or `PUSHA` to an offset that is neither an instruction
+    /// boundary nor the end of the script
+    #[error("Jump target {0} is not an instruction boundary")]
+    InvalidJumpTarget(isize),
+    /// A byte string or buffer longer than [`VMLimits::max_item_size`]
+    #[error("Item of {0} bytes exceeds the item size limit")]
+    ItemTooLarge(usize),
+    #[error("Step limit exceeded: max {0} instructions")]
+    StepLimitExceeded(u64),
+    /// A native contract method failed, with its message
+    #[error("Native contract call failed: {0}")]
+    NativeCallFailed(String),
+}
+
+impl VMError {
+    /// Stable number identifying the kind of error, used by
+    /// [`FaultCode`](crate::fault::FaultCode)
+    pub fn code(&self) -> u8 {
+        match self {
+            VMError::StackUnderflow => 1,
+            VMError::StackOverflow(_) => 2,
+            VMError::InvalidOpcode(_) => 3,
+            VMError::OutOfGas => 4,
+            VMError::DivisionByZero => 5,
+            VMError::InvalidType => 6,
+            VMError::UnknownSyscall(_) => 7,
+            VMError::InvalidOperation => 8,
+            VMError::InvalidScript => 9,
+            VMError::InvalidPublicKey => 10,
+            VMError::InvalidSignature => 11,
+            VMError::SignatureVerificationFailed => 12,
+            VMError::InvocationDepthExceeded(_) => 13,
+            VMError::NoScriptContainer => 14,
+            VMError::MissingCallFlags(_) => 15,
+            VMError::ContractNotFound => 16,
+            VMError::MethodNotFound { .. } => 17,
+            VMError::UnwitnessedStorage => 18,
+            VMError::ReplayDiverged => 19,
+            VMError::InvalidJumpTarget(_) => 20,
+            VMError::ItemTooLarge(_) => 21,
+            VMError::StepLimitExceeded(_) => 22,
+            VMError::NativeCallFailed(_) => 23,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -50,8 +127,51 @@ pub enum VMState {
 
 #[derive(Debug, Clone)]
 pub struct ExecutionContext {
-    pub script: Vec<u8>,
+    /// Script bytes, shared by every context running this script
+    pub script: Arc<[u8]>,
     pub ip: usize,
+    /// Decoded instructions, shared by every context running this script
+    pub instructions: Arc<DecodedScript>,
+    /// What this context may do, see [`crate::contract`]
+    pub call_flags: CallFlags,
+    /// Contract running in this context, `None` for a loaded script, which
+    /// uses [`NeoVM::storage_context`]
+    pub script_hash: Option<[u8; 20]>,
+}
+
+impl ExecutionContext {
+    /// Create a context for a freshly loaded script, decoding it once
+    pub fn new(script: Vec<u8>) -> Self {
+        let instructions = Arc::new(decode_script(&script));
+        Self {
+            script: Arc::from(script),
+            ip: 0,
+            instructions,
+            call_flags: CallFlags::ALL,
+            script_hash: None,
+        }
+    }
+
+    /// Target of a jump at `base_ip` by `offset`
+    ///
+    /// Targets must start a decoded instruction or be the end of the script,
+    /// so no jump lands inside an operand.
+    fn jump_target(&self, base_ip: usize, offset: i32) -> Result<usize, VMError> {
+        let target = base_ip as isize + offset as isize;
+        match usize::try_from(target) {
+            Ok(ip) if ip == self.script.len() || self.instructions.get(ip).is_some() => Ok(ip),
+            _ => Err(VMError::InvalidJumpTarget(target)),
+        }
+    }
+
+    /// Fetch the instruction at `ip`, decoding on the fly if it is not cached
+    #[inline]
+    fn fetch(&self, ip: usize) -> Result<Instruction, VMError> {
+        match self.instructions.get(ip) {
+            Some(instruction) => Ok(*instruction),
+            None => Instruction::decode(&self.script, ip),
+        }
+    }
 }
 
 // SAFETY: ExecutionContext is designed for single-threaded use within NeoVM.
@@ -63,38 +183,31 @@ pub mod syscall {
     pub const SYSTEM_RUNTIME_LOG: u32 = 0x01;
     pub const SYSTEM_RUNTIME_NOTIFY: u32 = 0x02;
     pub const SYSTEM_RUNTIME_GETTIME: u32 = 0x03;
+    pub const SYSTEM_RUNTIME_GETTRIGGER: u32 = 0x04;
+    pub const SYSTEM_RUNTIME_GETPLATFORM: u32 = 0x05;
+    pub const SYSTEM_RUNTIME_GETNETWORK: u32 = 0x06;
+    pub const SYSTEM_RUNTIME_GETRANDOM: u32 = 0x07;
+    pub const SYSTEM_RUNTIME_GETINVOCATIONCOUNTER: u32 = 0x08;
+    pub const SYSTEM_RUNTIME_BURNGAS: u32 = 0x09;
     pub const SYSTEM_STORAGE_GET: u32 = 0x10;
     pub const SYSTEM_STORAGE_PUT: u32 = 0x11;
     pub const SYSTEM_STORAGE_DELETE: u32 = 0x12;
-}
+    pub const SYSTEM_CRYPTO_CHECKSIG: u32 = 0x20;
+    pub const SYSTEM_CRYPTO_CHECKMULTISIG: u32 = 0x21;
+    pub const SYSTEM_CONTRACT_CALL: u32 = 0x30;
+
+    use crate::contract::CallFlags;
 
-/// Gas cost lookup table for O(1) opcode cost retrieval
-/// Uses u16 to support CHECKSIG's high gas cost (32768)
-const GAS_COSTS: [u16; 256] = [
-    // 0x00-0x0F (PUSHINT8-PUSHM1)
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 0x10-0x1F (PUSH0-PUSH16)
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 0x20-0x2F
-    1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x30-0x3F (flow control)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
-    // 0x40-0x4F (RET, DEPTH, CLEAR, stack ops)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x50-0x5F (stack ops)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x60-0x6F (slot ops)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x70-0x7F (slot ops)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x80-0x8F (splice/buffer ops)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x90-0x9F (bitwise/invert/equality)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xA0-0xAF (arithmetic)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xB0-0xBF (comparison/min/max/within)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xC0-0xCF (compound types)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xD0-0xDF (compound types)
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0xE0-0xEF (reserved)
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-    // 0xF0-0xFF (crypto: SHA256, RIPEMD160, CHECKSIG)
-    512, 512, 512, 32768, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-];
-
-#[inline]
-fn get_gas_cost(op: u8) -> u64 {
-    GAS_COSTS[op as usize] as u64
+    /// Flags the current context needs to make syscall `id`
+    pub fn required_call_flags(id: u32) -> CallFlags {
+        match id {
+            SYSTEM_RUNTIME_LOG | SYSTEM_RUNTIME_NOTIFY => CallFlags::ALLOW_NOTIFY,
+            SYSTEM_STORAGE_GET => CallFlags::READ_STATES,
+            SYSTEM_STORAGE_PUT | SYSTEM_STORAGE_DELETE => CallFlags::WRITE_STATES,
+            SYSTEM_CONTRACT_CALL => CallFlags::READ_STATES | CallFlags::ALLOW_CALL,
+            _ => CallFlags::NONE,
+        }
+    }
 }
 
 /// Maximum script size in bytes (1MB)
@@ -106,39 +219,136 @@ pub const DEFAULT_MAX_STACK_DEPTH: usize = 2048;
 /// Default maximum invocation depth
 pub const DEFAULT_MAX_INVOCATION_DEPTH: usize = 1024;
 
-/// Execution trace step for proof generation
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct TraceStep {
-    pub ip: usize,
-    pub opcode: u8,
-    pub stack_hash: [u8; 32],
-    pub gas_consumed: u64,
-}
-
-/// Full execution trace
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
-pub struct ExecutionTrace {
-    pub steps: Vec<TraceStep>,
-    pub initial_state_hash: [u8; 32],
-    pub final_state_hash: [u8; 32],
-}
-
 pub struct NeoVM {
     pub state: VMState,
     pub eval_stack: Vec<StackItem>,
     pub invocation_stack: Vec<ExecutionContext>,
     pub gas_consumed: u64,
     pub gas_limit: u64,
-    pub max_stack_depth: usize,
-    pub max_invocation_depth: usize,
-    pub notifications: Vec<StackItem>,
+    /// Prices charged for opcodes and syscalls
+    pub gas_schedule: GasSchedule,
+    /// `gas_consumed` split by the kind of instruction that was charged
+    pub gas_breakdown: GasBreakdown,
+    /// Category the running instruction's gas is charged to
+    gas_category: OpCategory,
+    /// Stack, script, item size and step limits
+    pub limits: VMLimits,
+    /// Instructions executed, checked against [`VMLimits::max_trace_steps`]
+    pub steps: u64,
+    /// Events sent with `System.Runtime.Notify`, in order
+    pub notifications: Vec<NotificationEvent>,
     pub logs: Vec<String>,
+    /// Where the last fault happened, `None` unless the VM faulted
+    pub fault_info: Option<FaultInfo>,
     pub trace: ExecutionTrace,
     pub tracing_enabled: bool,
+    /// Destination for trace steps; `None` buffers them in `trace.steps`
+    pub trace_sink: Option<Box<dyn TraceSink>>,
+    /// Instrumentation callbacks, see [`NeoVM::set_hook`]
+    pub hook: Option<Box<dyn ExecutionHook>>,
+    /// Checked by [`NeoVM::run_with_limits`] before every instruction
+    pub cancellation: Option<CancellationToken>,
+    /// Instruction hit counts, see [`NeoVM::enable_coverage`]
+    pub coverage: Option<Coverage>,
+    /// Watched values, see [`NeoVM::add_watch`]
+    pub watches: Vec<Watchpoint>,
+    /// Change that caused the last watchpoint break
+    pub watch_hit: Option<WatchHit>,
     // Slot support for Neo VM compatibility
     pub local_slots: Vec<StackItem>,
     pub argument_slots: Vec<StackItem>,
     pub static_slots: Vec<StackItem>,
+    /// Contract storage, journaled per invocation context
+    pub storage: TrackedStorage,
+    /// Context used by the storage syscalls of a loaded script
+    pub storage_context: StorageContext,
+    /// Full storage keys the storage syscalls may touch, when storage comes
+    /// from witnesses against a state root; `None` allows every key
+    pub witnessed_keys: Option<BTreeSet<Vec<u8>>>,
+    /// Contracts `System.Contract.Call` can call, by hash
+    pub contracts: BTreeMap<[u8; 20], Contract>,
+    /// Native contracts `System.Contract.Call` invokes in place; their
+    /// hashes take precedence over `contracts`. Their Ledger's block is the
+    /// VM's [`time`](Self::time)
+    pub natives: NativeRegistry,
+    /// Network, trigger and random seed reported by `System.Runtime`
+    pub runtime: RuntimeConfig,
+    /// `System.Runtime.GetRandom` calls made so far
+    pub random_draws: u32,
+    /// Times each contract has been called, for
+    /// `System.Runtime.GetInvocationCounter`
+    pub invocation_counters: BTreeMap<[u8; 20], u32>,
+    /// Message signed by the script container's witnesses, checked by
+    /// `System.Crypto.CheckSig`
+    pub sign_data: Option<Vec<u8>>,
+    /// Nondeterministic values being recorded or replayed, see
+    /// [`crate::replay`]
+    replay: Option<Replay>,
+}
+
+/// Why [`NeoVM::run_with_limits`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// Execution completed normally
+    Halt,
+    /// Execution faulted
+    Fault,
+    /// The execution hook requested a break
+    Break,
+    /// The step budget was used up
+    StepLimit,
+    /// The deadline passed
+    Deadline,
+    /// The cancellation token was triggered
+    Cancelled,
+}
+
+/// Cooperative cancellation flag shared between a VM and its controller
+///
+/// Clones share the same flag, so a service can keep one half and cancel a
+/// run happening on another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the VM to stop at the next instruction boundary
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Point-in-time copy of a VM's execution state
+///
+/// Covers everything execution mutates: state, stacks, slots, gas, storage
+/// and collected logs/notifications. Scripts are shared with the live VM, so
+/// taking a snapshot does not copy them. Limits, tracing and the hook are
+/// configuration and are left untouched by [`NeoVM::restore`].
+#[derive(Debug, Clone)]
+pub struct VmSnapshot {
+    pub state: VMState,
+    pub eval_stack: Vec<StackItem>,
+    pub invocation_stack: Vec<ExecutionContext>,
+    pub gas_consumed: u64,
+    pub gas_breakdown: GasBreakdown,
+    pub steps: u64,
+    pub notifications: Vec<NotificationEvent>,
+    pub logs: Vec<String>,
+    pub fault_info: Option<FaultInfo>,
+    pub local_slots: Vec<StackItem>,
+    pub argument_slots: Vec<StackItem>,
+    pub static_slots: Vec<StackItem>,
+    pub storage: TrackedStorage,
+    pub random_draws: u32,
+    pub invocation_counters: BTreeMap<[u8; 20], u32>,
 }
 
 impl NeoVM {
@@ -150,39 +360,53 @@ impl NeoVM {
     /// Create a new VM with default limits
     #[inline]
     pub fn new(gas_limit: u64) -> Self {
-        Self::with_limits(
-            gas_limit,
-            DEFAULT_MAX_STACK_DEPTH,
-            DEFAULT_MAX_INVOCATION_DEPTH,
-        )
+        Self::with_limits(gas_limit, VMLimits::default())
     }
 
     /// Create a new VM with custom limits
     #[inline]
-    pub fn with_limits(
-        gas_limit: u64,
-        max_stack_depth: usize,
-        max_invocation_depth: usize,
-    ) -> Self {
+    pub fn with_limits(gas_limit: u64, limits: VMLimits) -> Self {
         Self {
             state: VMState::None,
             eval_stack: Vec::with_capacity(Self::DEFAULT_STACK_CAPACITY),
             invocation_stack: Vec::with_capacity(Self::DEFAULT_INVOCATION_CAPACITY),
             gas_consumed: 0,
             gas_limit,
-            max_stack_depth,
-            max_invocation_depth,
+            gas_schedule: GasSchedule::default(),
+            gas_breakdown: GasBreakdown::default(),
+            gas_category: OpCategory::Constants,
+            limits,
+            steps: 0,
             notifications: Vec::new(),
             logs: Vec::new(),
+            fault_info: None,
             trace: ExecutionTrace::default(),
             tracing_enabled: false,
+            trace_sink: None,
+            hook: None,
+            cancellation: None,
+            coverage: None,
+            watches: Vec::new(),
+            watch_hit: None,
             local_slots: Vec::with_capacity(Self::DEFAULT_STACK_CAPACITY),
             argument_slots: Vec::with_capacity(Self::DEFAULT_STACK_CAPACITY),
             static_slots: Vec::with_capacity(Self::DEFAULT_STACK_CAPACITY),
+            storage: TrackedStorage::new(),
+            storage_context: StorageContext::default(),
+            witnessed_keys: None,
+            contracts: BTreeMap::new(),
+            natives: NativeRegistry::new(),
+            runtime: RuntimeConfig::default(),
+            random_draws: 0,
+            invocation_counters: BTreeMap::new(),
+            sign_data: None,
+            replay: None,
         }
     }
 
-    /// Run the VM until halt or fault
+    /// Run the VM until halt, fault, or a break requested by the hook
+    ///
+    /// Calling `run` again after a break resumes execution.
     #[inline]
     pub fn run(&mut self) {
         while !matches!(self.state, VMState::Halt | VMState::Fault) {
@@ -190,87 +414,324 @@ impl NeoVM {
                 self.state = VMState::Fault;
                 break;
             }
+            if matches!(self.state, VMState::Break) {
+                break;
+            }
+        }
+    }
+
+    /// Run like [`NeoVM::run`], but stop after `max_steps` instructions, once
+    /// `deadline` passes, or when the cancellation token is triggered
+    ///
+    /// Stopping on a limit leaves the VM resumable: calling `run` or
+    /// `run_with_limits` again continues where execution left off.
+    pub fn run_with_limits(&mut self, max_steps: u64, deadline: Option<Instant>) -> RunOutcome {
+        /// Instructions executed between deadline checks
+        const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+        let mut steps = 0u64;
+        loop {
+            match self.state {
+                VMState::Halt => return RunOutcome::Halt,
+                VMState::Fault => return RunOutcome::Fault,
+                VMState::Break if steps > 0 => return RunOutcome::Break,
+                _ => {}
+            }
+            if steps >= max_steps {
+                return RunOutcome::StepLimit;
+            }
+            if self
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                return RunOutcome::Cancelled;
+            }
+            if steps.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+                && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return RunOutcome::Deadline;
+            }
+
+            if self.execute_next().is_err() {
+                self.state = VMState::Fault;
+                return RunOutcome::Fault;
+            }
+            steps += 1;
+        }
+    }
+
+    /// Capture the current execution state
+    pub fn snapshot(&self) -> VmSnapshot {
+        VmSnapshot {
+            state: self.state.clone(),
+            eval_stack: self.eval_stack.clone(),
+            invocation_stack: self.invocation_stack.clone(),
+            gas_consumed: self.gas_consumed,
+            gas_breakdown: self.gas_breakdown,
+            steps: self.steps,
+            notifications: self.notifications.clone(),
+            logs: self.logs.clone(),
+            fault_info: self.fault_info.clone(),
+            local_slots: self.local_slots.clone(),
+            argument_slots: self.argument_slots.clone(),
+            static_slots: self.static_slots.clone(),
+            storage: self.storage.clone(),
+            random_draws: self.random_draws,
+            invocation_counters: self.invocation_counters.clone(),
+        }
+    }
+
+    /// Return to a previously captured execution state
+    pub fn restore(&mut self, snapshot: &VmSnapshot) {
+        self.state = snapshot.state.clone();
+        self.eval_stack.clone_from(&snapshot.eval_stack);
+        self.invocation_stack.clone_from(&snapshot.invocation_stack);
+        self.gas_consumed = snapshot.gas_consumed;
+        self.gas_breakdown = snapshot.gas_breakdown;
+        self.steps = snapshot.steps;
+        self.notifications.clone_from(&snapshot.notifications);
+        self.logs.clone_from(&snapshot.logs);
+        self.fault_info.clone_from(&snapshot.fault_info);
+        self.local_slots.clone_from(&snapshot.local_slots);
+        self.argument_slots.clone_from(&snapshot.argument_slots);
+        self.static_slots.clone_from(&snapshot.static_slots);
+        self.storage.clone_from(&snapshot.storage);
+        self.random_draws = snapshot.random_draws;
+        self.invocation_counters
+            .clone_from(&snapshot.invocation_counters);
+        self.refresh_watches();
+    }
+
+    /// Block reported by `System.Runtime.GetTime` and the Ledger native's
+    /// `currentIndex`; block 0 at timestamp 0 for a new VM
+    pub fn time(&self) -> Arc<dyn TimeProvider> {
+        self.natives.time()
+    }
+
+    /// Run in the block of `time`, for `GetTime` and the Ledger alike
+    pub fn set_time(&mut self, time: Arc<dyn TimeProvider>) {
+        self.natives.set_time(time);
+    }
+
+    /// Install an execution hook, replacing any existing one
+    pub fn set_hook(&mut self, hook: impl ExecutionHook + 'static) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Remove and return the installed execution hook
+    pub fn take_hook(&mut self) -> Option<Box<dyn ExecutionHook>> {
+        self.hook.take()
+    }
+
+    /// Break whenever the value described by `kind` changes
+    ///
+    /// The value is read now, so only later changes trigger the watch.
+    pub fn add_watch(&mut self, kind: WatchKind) {
+        let value = self.watched_value(&kind);
+        self.watches.push(Watchpoint { kind, value });
+    }
+
+    /// Remove the watchpoints on `kind`, returning whether there were any
+    pub fn remove_watch(&mut self, kind: &WatchKind) -> bool {
+        let len = self.watches.len();
+        self.watches.retain(|watch| watch.kind != *kind);
+        self.watches.len() != len
+    }
+
+    fn watched_value(&self, kind: &WatchKind) -> Option<StackItem> {
+        match kind {
+            WatchKind::StorageKey(key) => self
+                .storage
+                .get(&self.storage_context, key)
+                .map(|value| StackItem::ByteString(value.into())),
+            WatchKind::StackValue(index) => self.eval_stack.get(*index).cloned(),
+        }
+    }
+
+    /// Re-read every watched value, e.g. after a restore
+    fn refresh_watches(&mut self) {
+        for i in 0..self.watches.len() {
+            self.watches[i].value = self.watched_value(&self.watches[i].kind);
+        }
+        self.watch_hit = None;
+    }
+
+    /// Compare watched values after the instruction at `ip`, breaking on the
+    /// first change
+    fn check_watches(&mut self, ip: usize) {
+        let mut hit = None;
+        for i in 0..self.watches.len() {
+            let value = self.watched_value(&self.watches[i].kind);
+            if value != self.watches[i].value {
+                let old = std::mem::replace(&mut self.watches[i].value, value.clone());
+                hit.get_or_insert(WatchHit {
+                    kind: self.watches[i].kind.clone(),
+                    old,
+                    new: value,
+                    ip,
+                });
+            }
+        }
+        if hit.is_some() {
+            self.watch_hit = hit;
+            // A halted VM has nothing left to pause before
+            if matches!(self.state, VMState::None) {
+                self.state = VMState::Break;
+            }
         }
     }
 
+    /// Log every nondeterministic value the VM is fed from now on, see
+    /// [`replay_log`](Self::replay_log)
+    pub fn record_replay(&mut self) {
+        self.replay = Some(Replay::record());
+    }
+
+    /// Feed the VM the values in `log` instead of its own time, random
+    /// numbers and storage
+    ///
+    /// Storage keys the log holds are loaded as the execution first touches
+    /// them. Asking for a value the logged execution did not faults with
+    /// [`VMError::ReplayDiverged`].
+    pub fn replay(&mut self, log: ReplayLog) {
+        self.replay = Some(Replay::play(log));
+    }
+
+    /// Values recorded since [`record_replay`](Self::record_replay), or the
+    /// log being replayed
+    pub fn replay_log(&self) -> Option<&ReplayLog> {
+        self.replay.as_ref().map(Replay::log)
+    }
+
+    /// Start counting which instructions of the loaded script execute
+    pub fn enable_coverage(&mut self) {
+        self.coverage.get_or_insert_with(Coverage::new);
+    }
+
     #[inline]
     pub fn enable_tracing(&mut self) {
         self.tracing_enabled = true;
-        self.trace.initial_state_hash = self.compute_state_hash();
+        self.trace.start(self.compute_state_hash());
+    }
+
+    /// Enable tracing with steps sent to `sink` instead of `trace.steps`
+    pub fn enable_tracing_with(&mut self, sink: Box<dyn TraceSink>) {
+        self.trace_sink = Some(sink);
+        self.enable_tracing();
+    }
+
+    /// Flush the installed trace sink, if any
+    pub fn flush_trace(&mut self) -> std::io::Result<()> {
+        match self.trace_sink.as_mut() {
+            Some(sink) => sink.flush(),
+            None => Ok(()),
+        }
     }
 
     #[inline]
     fn compute_state_hash(&self) -> [u8; 32] {
-        use sha2::Digest;
         let mut hasher = Sha256::new();
+        let mut buf = Vec::new();
         for item in &self.eval_stack {
-            hasher.update(format!("{:?}", item).as_bytes());
+            buf.clear();
+            item.write_canonical(&mut buf);
+            hasher.update(&buf);
         }
         hasher.update(self.gas_consumed.to_le_bytes());
         hasher.finalize().into()
     }
 
-    fn read_u8(ctx: &mut ExecutionContext) -> Result<u8, VMError> {
-        if ctx.ip >= ctx.script.len() {
-            return Err(VMError::InvalidScript);
+    fn pop_usize_nonneg(&mut self) -> Result<usize, VMError> {
+        let value = self
+            .eval_stack
+            .pop()
+            .and_then(|x| x.to_integer())
+            .ok_or(VMError::StackUnderflow)?;
+        if value < 0 {
+            return Err(VMError::InvalidOperation);
         }
-        let byte = ctx.script[ctx.ip];
-        ctx.ip += 1;
-        Ok(byte)
+        Ok(value as usize)
     }
 
-    fn read_i8(ctx: &mut ExecutionContext) -> Result<i8, VMError> {
-        Ok(Self::read_u8(ctx)? as i8)
+    /// Pop a byte string or buffer
+    fn pop_bytes(&mut self) -> Result<Bytes, VMError> {
+        match self.eval_stack.pop().ok_or(VMError::StackUnderflow)? {
+            StackItem::ByteString(b) => Ok(b),
+            StackItem::Buffer(b) => Ok(b.into()),
+            _ => Err(VMError::InvalidType),
+        }
     }
 
-    fn read_u16_le(ctx: &mut ExecutionContext) -> Result<u16, VMError> {
-        if ctx.ip + 1 >= ctx.script.len() {
-            return Err(VMError::InvalidScript);
+    /// Pop a count followed by that many byte strings
+    ///
+    /// Items come back in pop order, so the last one pushed is first.
+    fn pop_byte_list(&mut self) -> Result<Vec<Bytes>, VMError> {
+        let count = match self.eval_stack.pop().ok_or(VMError::StackUnderflow)? {
+            StackItem::Integer(n) => usize::try_from(n).map_err(|_| VMError::InvalidOperation)?,
+            _ => return Err(VMError::InvalidType),
+        };
+        if count > self.eval_stack.len() {
+            return Err(VMError::StackUnderflow);
         }
-        let val = u16::from_le_bytes([ctx.script[ctx.ip], ctx.script[ctx.ip + 1]]);
-        ctx.ip += 2;
-        Ok(val)
+        (0..count).map(|_| self.pop_bytes()).collect()
     }
 
-    fn read_u32_le(ctx: &mut ExecutionContext) -> Result<u32, VMError> {
-        if ctx.ip + 3 >= ctx.script.len() {
-            return Err(VMError::InvalidScript);
+    /// Operand bytes of `instruction` in the current context
+    #[inline]
+    fn operand(&self, instruction: &Instruction) -> Result<&[u8], VMError> {
+        let ctx = self
+            .invocation_stack
+            .last()
+            .ok_or(VMError::StackUnderflow)?;
+        Ok(instruction.operand(&ctx.script))
+    }
+
+    /// Slot index of a load or store in the group starting at `base`: the
+    /// first seven opcodes name slots 0-6, the eighth takes an operand
+    #[inline]
+    fn slot_index(&self, instruction: &Instruction, base: u8) -> Result<usize, VMError> {
+        match instruction.opcode - base {
+            7 => Ok(self.operand(instruction)?[0] as usize),
+            index => Ok(index as usize),
         }
-        let val = u32::from_le_bytes([
-            ctx.script[ctx.ip],
-            ctx.script[ctx.ip + 1],
-            ctx.script[ctx.ip + 2],
-            ctx.script[ctx.ip + 3],
-        ]);
-        ctx.ip += 4;
-        Ok(val)
     }
 
-    fn pop_usize_nonneg(&mut self) -> Result<usize, VMError> {
-        let value = self
-            .eval_stack
-            .pop()
-            .and_then(|x| x.to_integer())
-            .ok_or(VMError::StackUnderflow)?;
-        if value < 0 {
+    /// Integer of a `PUSHINT` operand, sign-extended; a `PUSHINT256` that
+    /// does not fit in an `i128` is an overflow
+    fn int_operand(operand: &[u8]) -> Result<i128, VMError> {
+        let (low, high) = operand.split_at(operand.len().min(16));
+        let negative = low.last().is_some_and(|&b| b & 0x80 != 0);
+        let extension = if negative { 0xFF } else { 0x00 };
+        if high.iter().any(|&b| b != extension) {
             return Err(VMError::InvalidOperation);
         }
-        Ok(value as usize)
+        let mut bytes = [extension; 16];
+        bytes[..low.len()].copy_from_slice(low);
+        Ok(i128::from_le_bytes(bytes))
     }
 
-    fn relative_target(base_ip: usize, offset: i8, script_len: usize) -> Result<usize, VMError> {
-        let target = base_ip as isize + offset as isize;
-        if target < 0 || target as usize > script_len {
-            return Err(VMError::InvalidScript);
+    /// Signed offset of a jump or call: one byte, or four for the `_L` forms
+    #[inline]
+    fn jump_offset(&self, instruction: &Instruction) -> Result<i32, VMError> {
+        match *self.operand(instruction)? {
+            [offset] => Ok(offset as i8 as i32),
+            [a, b, c, d] => Ok(i32::from_le_bytes([a, b, c, d])),
+            _ => Err(VMError::InvalidScript),
         }
-        Ok(target as usize)
     }
 
     /// Push an item to the eval stack with depth checking
     #[inline]
-    fn push(&mut self, item: StackItem) -> Result<(), VMError> {
-        if self.eval_stack.len() >= self.max_stack_depth {
-            return Err(VMError::StackOverflow(self.max_stack_depth));
+    pub(crate) fn push(&mut self, item: StackItem) -> Result<(), VMError> {
+        if self.eval_stack.len() >= self.limits.max_stack {
+            return Err(VMError::StackOverflow(self.limits.max_stack));
+        }
+        if let Some(bytes) = item
+            .as_bytes()
+            .filter(|b| b.len() > self.limits.max_item_size)
+        {
+            return Err(VMError::ItemTooLarge(bytes.len()));
         }
         self.eval_stack.push(item);
         Ok(())
@@ -279,20 +740,35 @@ impl NeoVM {
     /// Check if pushing to the invocation stack would exceed the limit
     #[inline]
     fn check_invocation_depth(&self) -> Result<(), VMError> {
-        if self.invocation_stack.len() >= self.max_invocation_depth {
-            return Err(VMError::InvocationDepthExceeded(self.max_invocation_depth));
+        if self.invocation_stack.len() >= self.limits.max_invocation_depth {
+            return Err(VMError::InvocationDepthExceeded(
+                self.limits.max_invocation_depth,
+            ));
         }
         Ok(())
     }
 
     #[inline]
     pub fn load_script(&mut self, script: Vec<u8>) -> Result<(), VMError> {
-        if script.len() > MAX_SCRIPT_SIZE {
+        self.load_script_with_flags(script, CallFlags::ALL)
+    }
+
+    /// Load a script that may only do what `call_flags` allows, e.g.
+    /// [`CallFlags::READ_ONLY`] for a test invocation
+    pub fn load_script_with_flags(
+        &mut self,
+        script: Vec<u8>,
+        call_flags: CallFlags,
+    ) -> Result<(), VMError> {
+        if script.len() > self.limits.max_script_size {
             return Err(VMError::InvalidScript);
         }
         self.check_invocation_depth()?;
-        self.invocation_stack
-            .push(ExecutionContext { script, ip: 0 });
+        self.invocation_stack.push(ExecutionContext {
+            call_flags,
+            ..ExecutionContext::new(script)
+        });
+        self.storage.checkpoint();
         Ok(())
     }
 
@@ -304,6 +780,10 @@ impl NeoVM {
 
         if ctx.ip >= ctx.script.len() {
             self.state = VMState::Halt;
+            // Falling off the end returns from every open context
+            while self.storage.checkpoint_depth() > 0 {
+                self.storage.commit();
+            }
             if self.tracing_enabled {
                 self.trace.final_state_hash = self.compute_state_hash();
             }
@@ -311,15 +791,38 @@ impl NeoVM {
         }
 
         let ip = ctx.ip;
-        let op = ctx.script[ctx.ip];
-        ctx.ip += 1;
+        let op = ctx.script[ip];
+        let in_loaded_script = ctx.script_hash.is_none();
+
+        // A break pauses before the instruction; resuming executes it
+        if matches!(self.state, VMState::Break) {
+            self.state = VMState::None;
+        } else if let Some(hook) = self.hook.as_mut() {
+            if hook.before_op(ip, op) == HookAction::Break {
+                self.state = VMState::Break;
+                return Ok(());
+            }
+        }
+
+        if self.steps >= self.limits.max_trace_steps {
+            let error = VMError::StepLimitExceeded(self.limits.max_trace_steps);
+            return Err(self.fault(ip, op, error));
+        }
+        self.steps += 1;
+
+        if let Some(coverage) = self.coverage.as_mut().filter(|_| in_loaded_script) {
+            coverage.record(ip);
+        }
 
         // Gas metering
-        let gas_cost = get_gas_cost(op);
-        self.gas_consumed += gas_cost;
-        if self.gas_consumed > self.gas_limit {
-            self.state = VMState::Fault;
-            return Err(VMError::OutOfGas);
+        self.gas_category = match ctx.script.get(ip + 1..ip + 5) {
+            Some(id) if op == 0x41 => {
+                OpCategory::of_syscall(u32::from_le_bytes(id.try_into().expect("4 bytes")))
+            }
+            _ => OpCategory::of_opcode(op),
+        };
+        if let Err(e) = self.charge(self.gas_schedule.opcode_price(op)) {
+            return Err(self.fault(ip, op, e));
         }
 
         // Record trace step
@@ -330,17 +833,80 @@ impl NeoVM {
                 stack_hash: self.compute_state_hash(),
                 gas_consumed: self.gas_consumed,
             };
-            self.trace.steps.push(step);
+            self.trace.chain(&step);
+            match self.trace_sink.as_mut() {
+                Some(sink) => sink.record(&step),
+                None => self.trace.steps.push(step),
+            }
         }
 
-        if let Err(e) = self.execute_op(op) {
-            self.state = VMState::Fault;
-            return Err(e);
+        let result = self
+            .fetch_instruction(ip)
+            .and_then(|instruction| self.execute_op(&instruction));
+        if let Err(e) = result {
+            return Err(self.fault(ip, op, e));
+        }
+        if let Some(hook) = self.hook.as_mut() {
+            hook.after_op(ip, op, self.gas_consumed);
+        }
+        if !self.watches.is_empty() {
+            self.check_watches(ip);
+        }
+        Ok(())
+    }
+
+    /// Add `gas` to the consumed gas, failing once it exceeds the limit
+    #[inline]
+    fn charge(&mut self, gas: u64) -> Result<(), VMError> {
+        self.gas_consumed = self.gas_consumed.saturating_add(gas);
+        self.gas_breakdown.add(self.gas_category, gas);
+        if self.gas_consumed > self.gas_limit {
+            return Err(VMError::OutOfGas);
         }
         Ok(())
     }
 
-    fn execute_op(&mut self, op: u8) -> Result<(), VMError> {
+    /// Charge for the `len` bytes `op` works on, see [`GasSchedule::data_price`]
+    #[inline]
+    fn charge_data(&mut self, op: u8, len: usize) -> Result<(), VMError> {
+        self.charge(self.gas_schedule.data_price(op, len))
+    }
+
+    /// Enter the fault state, recording [`NeoVM::fault_info`] and notifying
+    /// the hook
+    ///
+    /// Storage writes of every open context are reverted.
+    fn fault(&mut self, ip: usize, opcode: u8, error: VMError) -> VMError {
+        self.state = VMState::Fault;
+        self.fault_info = Some(FaultInfo {
+            ip,
+            opcode,
+            error: error.clone(),
+            invocation_depth: self.invocation_stack.len(),
+        });
+        while self.storage.checkpoint_depth() > 0 {
+            self.storage.revert();
+        }
+        if let Some(hook) = self.hook.as_mut() {
+            hook.on_fault(ip, &error);
+        }
+        error
+    }
+
+    /// Fetch the instruction at `ip` and advance the current context past it
+    #[inline]
+    fn fetch_instruction(&mut self, ip: usize) -> Result<Instruction, VMError> {
+        let ctx = self
+            .invocation_stack
+            .last_mut()
+            .ok_or(VMError::StackUnderflow)?;
+        let instruction = ctx.fetch(ip)?;
+        ctx.ip = instruction.next_offset();
+        Ok(instruction)
+    }
+
+    fn execute_op(&mut self, instruction: &Instruction) -> Result<(), VMError> {
+        let op = instruction.opcode;
         match op {
             0x10 => self.push(StackItem::Integer(0))?,
             0x11..=0x20 => {
@@ -349,51 +915,30 @@ impl NeoVM {
             }
             0x0F => self.push(StackItem::Integer(-1))?,
             0x0B => self.push(StackItem::Null)?,
-            // PUSHDATA1 - Push data with 1-byte length prefix
-            0x0C => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let len = Self::read_u8(ctx)? as usize;
-                if ctx.ip + len > ctx.script.len() {
-                    return Err(VMError::InvalidScript);
-                }
-                let data = ctx.script[ctx.ip..ctx.ip + len].to_vec();
-                ctx.ip += len;
+            0x08 => self.push(StackItem::Boolean(true))?,
+            0x09 => self.push(StackItem::Boolean(false))?,
+            // PUSHDATA1, PUSHDATA2, PUSHDATA4 - Push data with a 1, 2 or
+            // 4-byte length prefix
+            0x0C..=0x0E => {
+                self.charge_data(op, instruction.operand_len)?;
+                let data = Bytes::copy_from_slice(self.operand(instruction)?);
                 self.push(StackItem::ByteString(data))?;
             }
-            // PUSHDATA2 - Push data with 2-byte length prefix
-            0x0D => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let len = Self::read_u16_le(ctx)? as usize;
-                if ctx.ip + len > ctx.script.len() {
-                    return Err(VMError::InvalidScript);
-                }
-                let data = ctx.script[ctx.ip..ctx.ip + len].to_vec();
-                ctx.ip += len;
-                self.push(StackItem::ByteString(data))?;
-            }
-            // PUSHINT8
-            0x00 => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let val = Self::read_u8(ctx)? as i8 as i128;
+            // PUSHINT8..PUSHINT256, little-endian two's complement
+            0x00..=0x05 => {
+                let val = Self::int_operand(self.operand(instruction)?)?;
                 self.push(StackItem::Integer(val))?;
             }
-            // PUSHINT16
-            0x01 => {
+            // PUSHA - Pointer to an offset relative to this instruction
+            0x0A => {
+                let operand = self.operand(instruction)?;
+                let offset = i32::from_le_bytes(operand.try_into().expect("4-byte operand"));
                 let ctx = self
                     .invocation_stack
-                    .last_mut()
+                    .last()
                     .ok_or(VMError::StackUnderflow)?;
-                let val = i16::from_le_bytes(Self::read_u16_le(ctx)?.to_le_bytes()) as i128;
-                self.push(StackItem::Integer(val))?;
+                let target = ctx.jump_target(instruction.offset, offset)?;
+                self.push(StackItem::Pointer(target as u32))?;
             }
             0x45 => {
                 self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
@@ -499,12 +1044,73 @@ impl NeoVM {
                     .pop()
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
-                if exp < 0 {
-                    return Err(VMError::InvalidOperation);
+                let exp = u32::try_from(exp).map_err(|_| VMError::InvalidOperation)?;
+                let result = base.checked_pow(exp).ok_or(VMError::InvalidOperation)?;
+                self.push(StackItem::Integer(result))?;
+            }
+            // SQRT
+            0xA4 => {
+                let a = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                let result = a.checked_isqrt().ok_or(VMError::InvalidOperation)?;
+                self.push(StackItem::Integer(result))?;
+            }
+            // MODMUL
+            0xA5 => {
+                let modulus = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                let b = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                let a = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                if modulus == 0 {
+                    return Err(VMError::DivisionByZero);
                 }
-                let result = base.pow(exp as u32);
+                let result = a
+                    .checked_mul(b)
+                    .and_then(|product| product.checked_rem(modulus))
+                    .ok_or(VMError::InvalidOperation)?;
                 self.push(StackItem::Integer(result))?;
             }
+            // MODPOW, or the modular inverse for an exponent of -1
+            0xA6 => {
+                let modulus = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                let exp = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                let base = self
+                    .eval_stack
+                    .pop()
+                    .and_then(|x| x.to_integer())
+                    .ok_or(VMError::StackUnderflow)?;
+                if modulus == 0 {
+                    return Err(VMError::DivisionByZero);
+                }
+                let result = if exp == -1 {
+                    mod_inverse(base, modulus)
+                } else {
+                    mod_pow(base, exp, modulus)
+                };
+                self.push(StackItem::Integer(result.ok_or(VMError::InvalidOperation)?))?;
+            }
             // SHL
             0xA8 => {
                 let shift = self
@@ -520,8 +1126,10 @@ impl NeoVM {
                 if !(0..=256).contains(&shift) {
                     return Err(VMError::InvalidOperation);
                 }
+                // Shifting bits out of an i128 overflows, like ADD or MUL
                 let result = value
                     .checked_shl(shift as u32)
+                    .filter(|result| result >> shift as u32 == value)
                     .ok_or(VMError::InvalidOperation)?;
                 self.push(StackItem::Integer(result))?;
             }
@@ -708,13 +1316,19 @@ impl NeoVM {
             0x97 => {
                 let b = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let a = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
-                self.push(StackItem::Boolean(a == b))?;
+                let equal = a
+                    .equals(&b, self.limits.max_stack)
+                    .ok_or(VMError::InvalidOperation)?;
+                self.push(StackItem::Boolean(equal))?;
             }
             // NOTEQUAL
             0x98 => {
                 let b = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let a = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
-                self.push(StackItem::Boolean(a != b))?;
+                let equal = a
+                    .equals(&b, self.limits.max_stack)
+                    .ok_or(VMError::InvalidOperation)?;
+                self.push(StackItem::Boolean(!equal))?;
             }
             // ISNULL
             0xD8 => {
@@ -937,40 +1551,55 @@ impl NeoVM {
                 let start = len - n;
                 self.eval_stack[start..].reverse();
             }
+            // INITSSLOT - Initialize static fields
+            0x56 => {
+                let count = self.operand(instruction)?[0] as usize;
+                if count == 0 {
+                    return Err(VMError::InvalidOperation);
+                }
+                self.static_slots.clear();
+                self.static_slots.resize(count, StackItem::Null);
+            }
             // INITSLOT - Initialize local and argument slots
             0x57 => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let local_count = Self::read_u8(ctx)? as usize;
-                let arg_count = Self::read_u8(ctx)? as usize;
-                self.local_slots = vec![StackItem::Null; local_count];
+                let operand = self.operand(instruction)?;
+                let local_count = operand[0] as usize;
+                let arg_count = operand[1] as usize;
+                if local_count == 0 && arg_count == 0 {
+                    return Err(VMError::InvalidOperation);
+                }
+                // Refill the slot buffers in place so calls do not allocate
+                self.local_slots.clear();
+                self.local_slots.resize(local_count, StackItem::Null);
                 // Pop arguments from stack into argument slots
-                self.argument_slots = Vec::with_capacity(arg_count);
+                self.argument_slots.clear();
                 for _ in 0..arg_count {
                     let arg = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                     self.argument_slots.push(arg);
                 }
-                self.argument_slots.reverse();
             }
-            // LDLOC0-LDLOC6 - Load local variable 0-6
-            0x66..=0x6C => {
-                let idx = (op - 0x66) as usize;
+            // LDSFLD0-LDSFLD6, LDSFLD - Load static field
+            0x58..=0x5F => {
+                let idx = self.slot_index(instruction, 0x58)?;
                 let item = self
-                    .local_slots
+                    .static_slots
                     .get(idx)
                     .cloned()
                     .ok_or(VMError::InvalidOperation)?;
                 self.push(item)?;
             }
-            // LDLOC_S - Load local variable (short form)
-            0x6D => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let idx = Self::read_u8(ctx)? as usize;
+            // STSFLD0-STSFLD6, STSFLD - Store static field
+            0x60..=0x67 => {
+                let idx = self.slot_index(instruction, 0x60)?;
+                let item = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                *self
+                    .static_slots
+                    .get_mut(idx)
+                    .ok_or(VMError::InvalidOperation)? = item;
+            }
+            // LDLOC0-LDLOC6, LDLOC - Load local variable
+            0x68..=0x6F => {
+                let idx = self.slot_index(instruction, 0x68)?;
                 let item = self
                     .local_slots
                     .get(idx)
@@ -978,31 +1607,18 @@ impl NeoVM {
                     .ok_or(VMError::InvalidOperation)?;
                 self.push(item)?;
             }
-            // STLOC0-STLOC6 - Store local variable 0-6
-            0x6E..=0x72 => {
-                let val = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
-                let idx = (op - 0x6E) as usize;
-                if idx >= self.local_slots.len() {
-                    self.local_slots.resize(idx + 1, StackItem::Null);
-                }
-                self.local_slots[idx] = val;
-            }
-            // STLOC_S - Store local variable (short form)
-            0x73 => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let idx = Self::read_u8(ctx)? as usize;
+            // STLOC0-STLOC6, STLOC - Store local variable
+            0x70..=0x77 => {
+                let idx = self.slot_index(instruction, 0x70)?;
                 let item = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
-                if idx >= self.local_slots.len() {
-                    return Err(VMError::InvalidOperation);
-                }
-                self.local_slots[idx] = item;
+                *self
+                    .local_slots
+                    .get_mut(idx)
+                    .ok_or(VMError::InvalidOperation)? = item;
             }
-            // LDARG0-LDARG6 - Load argument 0-6
-            0x74..=0x79 => {
-                let idx = (op - 0x74) as usize;
+            // LDARG0-LDARG6, LDARG - Load argument
+            0x78..=0x7F => {
+                let idx = self.slot_index(instruction, 0x78)?;
                 let item = self
                     .argument_slots
                     .get(idx)
@@ -1010,19 +1626,14 @@ impl NeoVM {
                     .ok_or(VMError::InvalidOperation)?;
                 self.push(item)?;
             }
-            // LDARG - Load argument
-            0x7A => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let idx = Self::read_u8(ctx)? as usize;
-                let item = self
+            // STARG0-STARG6, STARG - Store argument
+            0x80..=0x87 => {
+                let idx = self.slot_index(instruction, 0x80)?;
+                let item = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                *self
                     .argument_slots
-                    .get(idx)
-                    .cloned()
-                    .ok_or(VMError::InvalidOperation)?;
-                self.push(item)?;
+                    .get_mut(idx)
+                    .ok_or(VMError::InvalidOperation)? = item;
             }
             // NOP
             0x21 => {}
@@ -1034,50 +1645,46 @@ impl NeoVM {
                     return Err(VMError::InvalidOperation);
                 }
             }
-            // JMP (1-byte offset)
-            0x22 => {
+            // JMP, JMP_L
+            0x22 | 0x23 => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
-                ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                ctx.ip = ctx.jump_target(instruction.offset, offset)?;
             }
-            // JMPIF (1-byte offset)
-            0x24 => {
+            // JMPIF, JMPIF_L
+            0x24 | 0x25 => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let cond = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 if cond.to_bool() {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPIFNOT (1-byte offset)
-            0x26 => {
+            // JMPIFNOT, JMPIFNOT_L
+            0x26 | 0x27 => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let cond = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 if !cond.to_bool() {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPEQ - Jump if equal
-            0x28 => {
+            // JMPEQ, JMPEQ_L - Jump if equal
+            0x28 | 0x29 => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let b = self
                     .eval_stack
                     .pop()
@@ -1089,17 +1696,16 @@ impl NeoVM {
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
                 if a == b {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPNE - Jump if not equal
-            0x2A => {
+            // JMPNE, JMPNE_L - Jump if not equal
+            0x2A | 0x2B => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let b = self
                     .eval_stack
                     .pop()
@@ -1111,17 +1717,16 @@ impl NeoVM {
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
                 if a != b {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPGT - Jump if greater than
-            0x2C => {
+            // JMPGT, JMPGT_L - Jump if greater than
+            0x2C | 0x2D => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let b = self
                     .eval_stack
                     .pop()
@@ -1133,17 +1738,16 @@ impl NeoVM {
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
                 if a > b {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPGE - Jump if greater or equal
-            0x2E => {
+            // JMPGE, JMPGE_L - Jump if greater or equal
+            0x2E | 0x2F => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let b = self
                     .eval_stack
                     .pop()
@@ -1155,17 +1759,16 @@ impl NeoVM {
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
                 if a >= b {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPLT - Jump if less than
-            0x30 => {
+            // JMPLT, JMPLT_L - Jump if less than
+            0x30 | 0x31 => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let b = self
                     .eval_stack
                     .pop()
@@ -1177,17 +1780,16 @@ impl NeoVM {
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
                 if a < b {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // JMPLE - Jump if less or equal
-            0x32 => {
+            // JMPLE, JMPLE_L - Jump if less or equal
+            0x32 | 0x33 => {
+                let offset = self.jump_offset(instruction)?;
                 let ctx = self
                     .invocation_stack
                     .last_mut()
                     .ok_or(VMError::StackUnderflow)?;
-                let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                let offset = Self::read_i8(ctx)?;
                 let b = self
                     .eval_stack
                     .pop()
@@ -1199,25 +1801,30 @@ impl NeoVM {
                     .and_then(|x| x.to_integer())
                     .ok_or(VMError::StackUnderflow)?;
                 if a <= b {
-                    ctx.ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
+                    ctx.ip = ctx.jump_target(instruction.offset, offset)?;
                 }
             }
-            // CALL (1-byte offset)
-            0x34 => {
+            // CALL, CALL_L
+            0x34 | 0x35 => {
                 self.check_invocation_depth()?;
-                let (return_ip, target_ip, script) = {
+                let offset = self.jump_offset(instruction)?;
+                let return_ip = instruction.next_offset();
+                let callee = {
                     let ctx = self
                         .invocation_stack
-                        .last_mut()
+                        .last()
                         .ok_or(VMError::StackUnderflow)?;
-                    let base_ip = ctx.ip.checked_sub(1).ok_or(VMError::InvalidScript)?;
-                    let offset = Self::read_i8(ctx)?;
-                    let return_ip = ctx.ip;
-                    let target_ip = Self::relative_target(base_ip, offset, ctx.script.len())?;
-                    let script = ctx.script.clone();
-                    (return_ip, target_ip, script)
+                    let target_ip = ctx.jump_target(instruction.offset, offset)?;
+                    ExecutionContext {
+                        script: Arc::clone(&ctx.script),
+                        ip: target_ip,
+                        instructions: Arc::clone(&ctx.instructions),
+                        call_flags: ctx.call_flags,
+                        script_hash: ctx.script_hash,
+                    }
                 };
-                self.invocation_stack.push(ExecutionContext { script, ip: target_ip });
+                self.invocation_stack.push(callee);
+                self.storage.checkpoint();
                 // Store return address (simplified)
                 self.push(StackItem::Pointer(return_ip as u32))?;
             }
@@ -1225,75 +1832,85 @@ impl NeoVM {
             0xF0 => {
                 let data = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let bytes = match data {
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b,
-                    StackItem::Integer(i) => i.to_le_bytes().to_vec(),
+                    StackItem::ByteString(b) => b,
+                    StackItem::Buffer(b) => b.into(),
+                    StackItem::Integer(i) => Bytes::copy_from_slice(&i.to_le_bytes()),
                     _ => return Err(VMError::InvalidType),
                 };
+                self.charge_data(op, bytes.len())?;
                 let mut hasher = Sha256::new();
                 hasher.update(&bytes);
                 let result = hasher.finalize().to_vec();
-                self.push(StackItem::ByteString(result))?;
+                self.push(StackItem::ByteString(result.into()))?;
             }
             // RIPEMD160
             0xF1 => {
                 let data = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let bytes = match data {
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b,
-                    StackItem::Integer(i) => i.to_le_bytes().to_vec(),
+                    StackItem::ByteString(b) => b,
+                    StackItem::Buffer(b) => b.into(),
+                    StackItem::Integer(i) => Bytes::copy_from_slice(&i.to_le_bytes()),
                     _ => return Err(VMError::InvalidType),
                 };
+                self.charge_data(op, bytes.len())?;
                 let mut hasher = Ripemd160::new();
                 hasher.update(&bytes);
                 let result = hasher.finalize().to_vec();
-                self.push(StackItem::ByteString(result))?;
+                self.push(StackItem::ByteString(result.into()))?;
             }
             // SHA256 + RIPEMD160 (Hash160)
             0xF2 => {
                 let data = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let bytes = match data {
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b,
-                    StackItem::Integer(i) => i.to_le_bytes().to_vec(),
+                    StackItem::ByteString(b) => b,
+                    StackItem::Buffer(b) => b.into(),
+                    StackItem::Integer(i) => Bytes::copy_from_slice(&i.to_le_bytes()),
                     _ => return Err(VMError::InvalidType),
                 };
+                self.charge_data(op, bytes.len())?;
                 let sha_result = Sha256::digest(&bytes);
                 let result = Ripemd160::digest(sha_result).to_vec();
-                self.push(StackItem::ByteString(result))?;
+                self.push(StackItem::ByteString(result.into()))?;
             }
-            // CHECKSIG (ECDSA secp256k1)
+            // CHECKSIG (ECDSA secp256r1 or secp256k1)
             0xF3 => {
                 let pubkey = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let sig = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let msg = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
 
                 let pubkey_bytes = match pubkey {
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b,
+                    StackItem::ByteString(b) => b,
+                    StackItem::Buffer(b) => b.into(),
                     _ => return Err(VMError::InvalidType),
                 };
                 let sig_bytes = match sig {
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b,
+                    StackItem::ByteString(b) => b,
+                    StackItem::Buffer(b) => b.into(),
                     _ => return Err(VMError::InvalidType),
                 };
                 let msg_bytes = match msg {
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b,
+                    StackItem::ByteString(b) => b,
+                    StackItem::Buffer(b) => b.into(),
                     _ => return Err(VMError::InvalidType),
                 };
+                self.charge_data(op, msg_bytes.len())?;
 
-                let result = VerifyingKey::from_sec1_bytes(&pubkey_bytes)
-                    .map_err(|_| VMError::InvalidPublicKey)?;
-                let signature =
-                    Signature::from_slice(&sig_bytes).map_err(|_| VMError::InvalidSignature)?;
                 let msg_hash = Sha256::digest(&msg_bytes);
-
-                let verified = result.verify(&msg_hash, &signature).is_ok();
+                let verified = verify_signature(&pubkey_bytes, &sig_bytes, &msg_hash)?;
                 self.push(StackItem::Boolean(verified))?;
             }
             // SYSCALL
             0x41 => {
-                let ctx = self
-                    .invocation_stack
-                    .last_mut()
-                    .ok_or(VMError::StackUnderflow)?;
-                let id = Self::read_u32_le(ctx)?;
+                let operand = self.operand(instruction)?;
+                let id = u32::from_le_bytes([operand[0], operand[1], operand[2], operand[3]]);
+                if let Some(hook) = self.hook.as_mut() {
+                    hook.on_syscall(id);
+                }
+                let required = syscall::required_call_flags(id);
+                if !self.current_call_flags()?.contains(required) {
+                    return Err(VMError::MissingCallFlags(required));
+                }
+                self.charge(self.gas_schedule.syscall_price(id))?;
                 self.execute_syscall(id)?;
             }
             // NEWARRAY0 - Create empty array
@@ -1303,6 +1920,9 @@ impl NeoVM {
             // NEWARRAY - Create array with n elements
             0xC3 => {
                 let n = self.pop_usize_nonneg()?;
+                if n > self.limits.max_stack {
+                    return Err(VMError::InvalidOperation);
+                }
                 let arr = vec![StackItem::Null; n];
                 self.push(StackItem::Array(arr))?;
             }
@@ -1313,6 +1933,9 @@ impl NeoVM {
             // NEWSTRUCT - Create struct with n elements
             0xC6 => {
                 let n = self.pop_usize_nonneg()?;
+                if n > self.limits.max_stack {
+                    return Err(VMError::InvalidOperation);
+                }
                 let s = vec![StackItem::Null; n];
                 self.push(StackItem::Struct(s))?;
             }
@@ -1326,11 +1949,45 @@ impl NeoVM {
                 let size = match &item {
                     StackItem::Array(a) | StackItem::Struct(a) => a.len(),
                     StackItem::Map(m) => m.len(),
-                    StackItem::ByteString(b) | StackItem::Buffer(b) => b.len(),
+                    StackItem::ByteString(b) => b.len(),
+                    StackItem::Buffer(b) => b.len(),
                     _ => return Err(VMError::InvalidType),
                 };
                 self.push(StackItem::Integer(size as i128))?;
             }
+            // HASKEY - Check for an index of an array or a key of a map
+            0xCB => {
+                let key = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                let container = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                let found = match (&container, &key) {
+                    (StackItem::Array(a) | StackItem::Struct(a), StackItem::Integer(i)) => {
+                        if *i < 0 {
+                            return Err(VMError::InvalidOperation);
+                        }
+                        (*i as u128) < a.len() as u128
+                    }
+                    (StackItem::Map(m), k) if k.is_primitive() => m.iter().any(|(mk, _)| mk == k),
+                    _ => return Err(VMError::InvalidType),
+                };
+                self.push(StackItem::Boolean(found))?;
+            }
+            // KEYS - Keys of a map, in insertion order
+            0xCC => {
+                let keys = match self.eval_stack.pop().ok_or(VMError::StackUnderflow)? {
+                    StackItem::Map(m) => m.into_iter().map(|(k, _)| k).collect(),
+                    _ => return Err(VMError::InvalidType),
+                };
+                self.push(StackItem::Array(keys))?;
+            }
+            // VALUES - Items of an array or values of a map, in order
+            0xCD => {
+                let values = match self.eval_stack.pop().ok_or(VMError::StackUnderflow)? {
+                    StackItem::Array(a) | StackItem::Struct(a) => a,
+                    StackItem::Map(m) => m.into_iter().map(|(_, v)| v).collect(),
+                    _ => return Err(VMError::InvalidType),
+                };
+                self.push(StackItem::Array(values))?;
+            }
             // PICKITEM - Get item from array/map
             0xCE => {
                 let key = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
@@ -1344,7 +2001,7 @@ impl NeoVM {
                         .get(i as usize)
                         .cloned()
                         .ok_or(VMError::InvalidOperation)?,
-                    (StackItem::Map(m), k) => m
+                    (StackItem::Map(m), k) if k.is_primitive() => m
                         .iter()
                         .find(|(mk, _)| *mk == k)
                         .map(|(_, v)| v.clone())
@@ -1353,20 +2010,21 @@ impl NeoVM {
                 };
                 self.push(item)?;
             }
-            // SETITEM - Set item in array/map
+            // SETITEM - Set item in array/struct/map, copying a struct value
             0xD0 => {
                 let value = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                let value = self.copy_on_assign(value)?;
                 let key = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let container = self.eval_stack.last_mut().ok_or(VMError::StackUnderflow)?;
                 match (container, key) {
-                    (StackItem::Array(a), StackItem::Integer(i)) => {
+                    (StackItem::Array(a) | StackItem::Struct(a), StackItem::Integer(i)) => {
                         let idx = i as usize;
                         if idx >= a.len() {
                             return Err(VMError::InvalidOperation);
                         }
                         a[idx] = value;
                     }
-                    (StackItem::Map(m), k) => {
+                    (StackItem::Map(m), k) if k.is_primitive() => {
                         if let Some(entry) = m.iter_mut().find(|(mk, _)| *mk == k) {
                             entry.1 = value;
                         } else {
@@ -1376,28 +2034,29 @@ impl NeoVM {
                     _ => return Err(VMError::InvalidType),
                 }
             }
-            // APPEND - Append to array
+            // APPEND - Append to array/struct, copying a struct item
             0xCF => {
                 let item = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                let item = self.copy_on_assign(item)?;
                 let container = self.eval_stack.last_mut().ok_or(VMError::StackUnderflow)?;
                 match container {
-                    StackItem::Array(a) => a.push(item),
+                    StackItem::Array(a) | StackItem::Struct(a) => a.push(item),
                     _ => return Err(VMError::InvalidType),
                 }
             }
-            // REMOVE - Remove from array/map
+            // REMOVE - Remove from array/struct/map
             0xD2 => {
                 let key = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 let container = self.eval_stack.last_mut().ok_or(VMError::StackUnderflow)?;
                 match (container, key) {
-                    (StackItem::Array(a), StackItem::Integer(i)) => {
+                    (StackItem::Array(a) | StackItem::Struct(a), StackItem::Integer(i)) => {
                         let idx = i as usize;
                         if idx >= a.len() {
                             return Err(VMError::InvalidOperation);
                         }
                         a.remove(idx);
                     }
-                    (StackItem::Map(m), k) => {
+                    (StackItem::Map(m), k) if k.is_primitive() => {
                         m.retain(|(mk, _)| *mk != k);
                     }
                     _ => return Err(VMError::InvalidType),
@@ -1408,6 +2067,7 @@ impl NeoVM {
                 self.invocation_stack
                     .pop()
                     .ok_or(VMError::InvalidOperation)?;
+                self.storage.commit();
                 if self.invocation_stack.is_empty() {
                     self.state = VMState::Halt;
                 }
@@ -1417,32 +2077,376 @@ impl NeoVM {
         Ok(())
     }
 
+    /// `item` as stored into a container: structs are values, so a struct
+    /// is copied, within the stack depth limit
+    fn copy_on_assign(&self, item: StackItem) -> Result<StackItem, VMError> {
+        match item {
+            StackItem::Struct(_) => item
+                .clone_struct(self.limits.max_stack)
+                .ok_or(VMError::InvalidOperation),
+            item => Ok(item),
+        }
+    }
+
+    /// Call flags of the current context
+    fn current_call_flags(&self) -> Result<CallFlags, VMError> {
+        self.invocation_stack
+            .last()
+            .map(|ctx| ctx.call_flags)
+            .ok_or(VMError::StackUnderflow)
+    }
+
+    /// Hash of the contract running in the current context, or the Hash160
+    /// of a loaded script, as Neo's `CurrentScriptHash`
+    fn current_script_hash(&self) -> Result<[u8; 20], VMError> {
+        let ctx = self
+            .invocation_stack
+            .last()
+            .ok_or(VMError::StackUnderflow)?;
+        Ok(match ctx.script_hash {
+            Some(hash) => hash,
+            None => UInt160::from_script(&ctx.script).into(),
+        })
+    }
+
+    /// Storage context of the contract running in the current context
+    fn current_storage_context(&self) -> StorageContext {
+        match self.invocation_stack.last().and_then(|ctx| ctx.script_hash) {
+            Some(script_hash) => StorageContext {
+                script_hash,
+                read_only: false,
+            },
+            None => self.storage_context.clone(),
+        }
+    }
+
+    /// Storage context for `key`, failing if `key` is not in
+    /// [`witnessed_keys`](Self::witnessed_keys)
+    fn witnessed_context(&mut self, key: &[u8]) -> Result<StorageContext, VMError> {
+        let context = self.current_storage_context();
+        if let Some(keys) = &self.witnessed_keys {
+            if !keys.contains(&make_key(&context, key)) {
+                return Err(VMError::UnwitnessedStorage);
+            }
+        }
+        self.replay_storage(&context, key)?;
+        Ok(context)
+    }
+
+    /// Log or load the value `key` held before its first access
+    fn replay_storage(&mut self, context: &StorageContext, key: &[u8]) -> Result<(), VMError> {
+        let Some(replay) = self.replay.as_mut() else {
+            return Ok(());
+        };
+        if !replay.first_touch(context, key) {
+            return Ok(());
+        }
+        let replaying = replay.is_replaying();
+        let live = ReplayEvent::Storage {
+            key: make_key(context, key),
+            value: if replaying {
+                None
+            } else {
+                self.storage.get(context, key)
+            },
+        };
+        match replay.pass(live) {
+            Some(ReplayEvent::Storage { value, .. }) => {
+                if replaying {
+                    self.storage.preload(context, key, value.as_deref());
+                }
+                Ok(())
+            }
+            _ => Err(VMError::ReplayDiverged),
+        }
+    }
+
+    /// `live`, or the value standing in for it in the replay log
+    fn replayed(&mut self, live: ReplayEvent) -> Result<ReplayEvent, VMError> {
+        match self.replay.as_mut() {
+            Some(replay) => replay.pass(live).ok_or(VMError::ReplayDiverged),
+            None => Ok(live),
+        }
+    }
+
+    /// `System.Contract.Call(hash, method, flags, args)`
+    ///
+    /// Arguments are pushed so the first one ends up on top of the stack, and
+    /// the callee's return value is left on the stack when it returns.
+    fn call_contract(&mut self) -> Result<(), VMError> {
+        let hash: [u8; 20] = self
+            .pop_bytes()?
+            .as_ref()
+            .try_into()
+            .map_err(|_| VMError::InvalidOperation)?;
+        let method =
+            String::from_utf8(self.pop_bytes()?.to_vec()).map_err(|_| VMError::InvalidOperation)?;
+        let requested = self
+            .eval_stack
+            .pop()
+            .ok_or(VMError::StackUnderflow)?
+            .to_integer()
+            .and_then(|bits| u8::try_from(bits).ok())
+            .and_then(CallFlags::from_bits)
+            .ok_or(VMError::InvalidOperation)?;
+        let args = match self.eval_stack.pop().ok_or(VMError::StackUnderflow)? {
+            StackItem::Array(args) => args,
+            _ => return Err(VMError::InvalidType),
+        };
+        // Methods starting with an underscore are reserved for the runtime
+        if method.starts_with('_') {
+            return Err(VMError::InvalidOperation);
+        }
+        if self.natives.contains(&hash) {
+            let result = self
+                .natives
+                .invoke(&hash, &method, args)
+                .map_err(VMError::NativeCallFailed)?;
+            return self.push(result);
+        }
+
+        let contract = self.contracts.get(&hash).ok_or(VMError::ContractNotFound)?;
+        let entry =
+            contract
+                .method(&method, args.len())
+                .ok_or_else(|| VMError::MethodNotFound {
+                    name: method.clone(),
+                    parameters: args.len(),
+                })?;
+        let mut call_flags = requested & self.current_call_flags()?;
+        if entry.safe {
+            call_flags = call_flags & CallFlags::READ_ONLY;
+        }
+        if entry.offset >= contract.script.len() {
+            return Err(VMError::InvalidScript);
+        }
+        let callee = ExecutionContext {
+            script: Arc::clone(&contract.script),
+            ip: entry.offset,
+            instructions: Arc::clone(&contract.instructions),
+            call_flags,
+            script_hash: Some(hash),
+        };
+
+        self.check_invocation_depth()?;
+        for arg in args.into_iter().rev() {
+            self.push(arg)?;
+        }
+        *self.invocation_counters.entry(hash).or_insert(0) += 1;
+        self.invocation_stack.push(callee);
+        self.storage.checkpoint();
+        Ok(())
+    }
+
     fn execute_syscall(&mut self, id: u32) -> Result<(), VMError> {
         match id {
             syscall::SYSTEM_RUNTIME_LOG => {
                 let msg = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
                 if let StackItem::ByteString(b) = msg {
-                    if let Ok(s) = String::from_utf8(b) {
-                        self.logs.push(s);
+                    if let Ok(s) = std::str::from_utf8(&b) {
+                        self.logs.push(s.to_string());
                     }
                 }
                 Ok(())
             }
             syscall::SYSTEM_RUNTIME_NOTIFY => {
-                let item = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
-                self.notifications.push(item);
+                // Notify(eventName, state): the name is on top
+                let name = self.pop_bytes()?;
+                if name.len() > MAX_EVENT_NAME {
+                    return Err(VMError::InvalidOperation);
+                }
+                let event_name =
+                    String::from_utf8(name.to_vec()).map_err(|_| VMError::InvalidOperation)?;
+                let state = self.eval_stack.pop().ok_or(VMError::StackUnderflow)?;
+                if !matches!(state, StackItem::Array(_)) {
+                    return Err(VMError::InvalidType);
+                }
+                let script_hash = self.current_script_hash()?;
+                self.notifications.push(NotificationEvent {
+                    script_hash,
+                    event_name,
+                    state,
+                });
                 Ok(())
             }
             syscall::SYSTEM_RUNTIME_GETTIME => {
-                // Return a mock timestamp for zkVM
-                self.push(StackItem::Integer(0))?;
+                let live = ReplayEvent::Time(self.time().current_block().timestamp);
+                let time = match self.replayed(live)? {
+                    ReplayEvent::Time(time) => time,
+                    _ => return Err(VMError::ReplayDiverged),
+                };
+                self.push(StackItem::Integer(time as i128))
+            }
+            syscall::SYSTEM_RUNTIME_GETTRIGGER => {
+                self.push(StackItem::Integer(self.runtime.trigger.id() as i128))
+            }
+            syscall::SYSTEM_RUNTIME_GETPLATFORM => self.push(StackItem::ByteString(
+                Bytes::from_static(PLATFORM.as_bytes()),
+            )),
+            syscall::SYSTEM_RUNTIME_GETNETWORK => {
+                self.push(StackItem::Integer(self.runtime.network as i128))
+            }
+            syscall::SYSTEM_RUNTIME_GETRANDOM => {
+                let live = ReplayEvent::Random(self.runtime.random(self.random_draws));
+                let value = match self.replayed(live)? {
+                    ReplayEvent::Random(value) => value,
+                    _ => return Err(VMError::ReplayDiverged),
+                };
+                self.random_draws = self
+                    .random_draws
+                    .checked_add(1)
+                    .ok_or(VMError::InvalidOperation)?;
+                self.push(StackItem::Integer(value))
+            }
+            syscall::SYSTEM_RUNTIME_GETINVOCATIONCOUNTER => {
+                // A loaded script runs once
+                let count = match self.invocation_stack.last().and_then(|ctx| ctx.script_hash) {
+                    Some(hash) => self.invocation_counters.get(&hash).copied().unwrap_or(1),
+                    None => 1,
+                };
+                self.push(StackItem::Integer(count as i128))
+            }
+            syscall::SYSTEM_RUNTIME_BURNGAS => {
+                let datoshi = self
+                    .eval_stack
+                    .pop()
+                    .ok_or(VMError::StackUnderflow)?
+                    .to_integer()
+                    .ok_or(VMError::InvalidType)?;
+                if datoshi <= 0 {
+                    return Err(VMError::InvalidOperation);
+                }
+                self.charge(u64::try_from(datoshi).unwrap_or(u64::MAX))
+            }
+            syscall::SYSTEM_STORAGE_GET => {
+                let key = self.pop_bytes()?;
+                let context = self.witnessed_context(&key)?;
+                let item = match self.storage.get(&context, &key) {
+                    Some(value) => StackItem::ByteString(value.into()),
+                    None => StackItem::Null,
+                };
+                self.push(item)
+            }
+            syscall::SYSTEM_STORAGE_PUT => {
+                let key = self.pop_bytes()?;
+                let value = self.pop_bytes()?;
+                let context = self.witnessed_context(&key)?;
+                let old_len = self.storage.get(&context, &key).map(|old| old.len());
+                self.charge(
+                    self.gas_schedule
+                        .storage_put_price(key.len(), value.len(), old_len),
+                )?;
+                self.storage.put(&context, &key, &value);
                 Ok(())
             }
+            syscall::SYSTEM_STORAGE_DELETE => {
+                let key = self.pop_bytes()?;
+                let context = self.witnessed_context(&key)?;
+                self.storage.delete(&context, &key);
+                Ok(())
+            }
+            syscall::SYSTEM_CRYPTO_CHECKSIG => {
+                let pubkey = self.pop_bytes()?;
+                let signature = self.pop_bytes()?;
+                let sign_data = self.sign_data.as_ref().ok_or(VMError::NoScriptContainer)?;
+                let verified = verify_signature(&pubkey, &signature, sign_data)?;
+                self.push(StackItem::Boolean(verified))
+            }
+            syscall::SYSTEM_CRYPTO_CHECKMULTISIG => {
+                let pubkeys = self.pop_byte_list()?;
+                let signatures = self.pop_byte_list()?;
+                if signatures.is_empty() || signatures.len() > pubkeys.len() {
+                    return Err(VMError::InvalidOperation);
+                }
+                self.charge(self.gas_schedule.check_multisig_price(pubkeys.len()))?;
+                let sign_data = self.sign_data.as_ref().ok_or(VMError::NoScriptContainer)?;
+
+                // Signatures must appear in the same order as their keys
+                let (mut i, mut j) = (0, 0);
+                while i < signatures.len() && signatures.len() - i <= pubkeys.len() - j {
+                    if verify_signature(&pubkeys[j], &signatures[i], sign_data)? {
+                        i += 1;
+                    }
+                    j += 1;
+                }
+                self.push(StackItem::Boolean(i == signatures.len()))
+            }
+            syscall::SYSTEM_CONTRACT_CALL => self.call_contract(),
             _ => Err(VMError::UnknownSyscall(id)),
         }
     }
 }
 
+/// Verify an ECDSA signature over SHA-256 of `message`
+///
+/// The key is tried on secp256r1, the curve of Neo N3 accounts, and on
+/// secp256k1; the signature verifies if either curve accepts it. A key that
+/// is a point on neither curve, or a signature that is malformed for every
+/// curve the key is on, is an error. The guest program does the same.
+fn verify_signature(pubkey: &[u8], signature: &[u8], message: &[u8]) -> Result<bool, VMError> {
+    let r1 = p256::ecdsa::VerifyingKey::from_sec1_bytes(pubkey).ok();
+    let k1 = k256::ecdsa::VerifyingKey::from_sec1_bytes(pubkey).ok();
+    if r1.is_none() && k1.is_none() {
+        return Err(VMError::InvalidPublicKey);
+    }
+    let r1 = r1.zip(p256::ecdsa::Signature::from_slice(signature).ok());
+    let k1 = k1.zip(k256::ecdsa::Signature::from_slice(signature).ok());
+    if r1.is_none() && k1.is_none() {
+        return Err(VMError::InvalidSignature);
+    }
+    Ok(
+        r1.is_some_and(|(key, signature)| key.verify(message, &signature).is_ok())
+            || k1.is_some_and(|(key, signature)| key.verify(message, &signature).is_ok()),
+    )
+}
+
+/// `base^exp % modulus` by square-and-multiply, reducing after every
+/// product; the result takes the sign of `base^exp`, like `BigInteger.ModPow`
+///
+/// `None` for a negative exponent, a zero modulus or a product that
+/// overflows. The guest program uses the same algorithm.
+fn mod_pow(base: i128, exp: i128, modulus: i128) -> Option<i128> {
+    if exp < 0 {
+        return None;
+    }
+    let mut result = 1i128.checked_rem(modulus)?;
+    let mut base = base.checked_rem(modulus)?;
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)?.checked_rem(modulus)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)?.checked_rem(modulus)?;
+        }
+    }
+    Some(result)
+}
+
+/// Inverse of `value` modulo `modulus` by the extended Euclidean algorithm,
+/// in `0..modulus`
+///
+/// `None` unless `value` is positive, `modulus` at least 2 and the two
+/// coprime, as in Neo's `ModInverse`. The guest program uses the same
+/// algorithm.
+fn mod_inverse(value: i128, modulus: i128) -> Option<i128> {
+    if value <= 0 || modulus < 2 {
+        return None;
+    }
+    let (mut r, mut old_r) = (value, modulus);
+    let (mut s, mut old_s) = (1i128, 0i128);
+    while r > 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r % r);
+        (old_s, s) = (s, old_s.checked_sub(q.checked_mul(s)?)?);
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(modulus))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1496,6 +2500,45 @@ mod tests {
         assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(12)));
     }
 
+    #[test]
+    fn test_call_shares_script_with_caller() {
+        let mut vm = NeoVM::new(1_000_000);
+        // CALL +3, RET, PUSH1, RET
+        let _ = vm.load_script(vec![0x34, 0x03, 0x40, 0x11, 0x40]);
+        vm.execute_next().unwrap();
+
+        assert_eq!(vm.invocation_stack.len(), 2);
+        let caller = &vm.invocation_stack[0];
+        let callee = &vm.invocation_stack[1];
+        assert!(Arc::ptr_eq(&caller.script, &callee.script));
+        assert!(Arc::ptr_eq(&caller.instructions, &callee.instructions));
+        assert_eq!(callee.ip, 3);
+    }
+
+    #[test]
+    fn test_trace_sinks_share_rolling_hash() {
+        let script = vec![0x12, 0x13, 0x9E, 0x40];
+
+        let mut buffered = NeoVM::new(1_000_000);
+        buffered.load_script(script.clone()).unwrap();
+        buffered.enable_tracing();
+        buffered.run();
+
+        let mut chained = NeoVM::new(1_000_000);
+        chained.load_script(script).unwrap();
+        chained.enable_tracing_with(Box::new(crate::trace::HashChainSink));
+        chained.run();
+
+        assert_eq!(buffered.trace.steps.len(), 4);
+        assert!(chained.trace.steps.is_empty());
+        assert_eq!(chained.trace.step_count, 4);
+        assert_eq!(buffered.trace.rolling_hash, chained.trace.rolling_hash);
+        assert_eq!(
+            buffered.trace.final_state_hash,
+            chained.trace.final_state_hash
+        );
+    }
+
     #[test]
     fn test_comparison_lt() {
         let mut vm = NeoVM::new(1_000_000);
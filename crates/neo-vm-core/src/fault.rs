@@ -0,0 +1,52 @@
+//! Fault diagnostics
+//!
+//! When execution faults, [`NeoVM::fault_info`](crate::NeoVM::fault_info)
+//! records where: the instruction, its opcode, the error and how deep the
+//! invocation stack was. [`FaultCode`] is the same information in a fixed-size
+//! form for `ProofOutput` and other serialized results.
+
+use crate::engine::VMError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where and why execution faulted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultInfo {
+    /// Offset of the faulting instruction in its script
+    pub ip: usize,
+    pub opcode: u8,
+    pub error: VMError,
+    /// Contexts on the invocation stack, 1 for the loaded script
+    pub invocation_depth: usize,
+}
+
+/// `Division by zero at 0x0002 (opcode 0xA1, depth 1)`
+impl fmt::Display for FaultInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at 0x{:04X} (opcode 0x{:02X}, depth {})",
+            self.error, self.ip, self.opcode, self.invocation_depth
+        )
+    }
+}
+
+/// Compact form of a [`FaultInfo`], with the error as [`VMError::code`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultCode {
+    pub ip: u32,
+    pub opcode: u8,
+    pub error: u8,
+    pub invocation_depth: u32,
+}
+
+impl From<&FaultInfo> for FaultCode {
+    fn from(info: &FaultInfo) -> Self {
+        Self {
+            ip: info.ip as u32,
+            opcode: info.opcode,
+            error: info.error.code(),
+            invocation_depth: info.invocation_depth as u32,
+        }
+    }
+}
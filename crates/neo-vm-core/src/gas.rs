@@ -0,0 +1,505 @@
+//! Gas schedules
+//!
+//! A [`GasSchedule`] prices every opcode and syscall. [`GasSchedule::Legacy`]
+//! is the flat table neo-zkvm has always used and stays the default.
+//! [`GasSchedule::NeoN3`] charges what a Neo N3 node charges, in datoshi: the
+//! reference `OpCodePrices` and syscall prices times the default execution
+//! fee factor.
+//!
+//! Work that grows with its input is charged on top of the flat price, once
+//! the operands are known: [`GasSchedule::data_price`] for opcodes that copy
+//! or hash bytes, [`GasSchedule::storage_put_price`] for storage writes and
+//! [`GasSchedule::check_multisig_price`] for the keys a multi-signature check
+//! walks. Without it a single `SHA256` hashes a megabyte for the price of a
+//! byte.
+//!
+//! Proofs commit to the schedule by its [`id`](GasSchedule::id), which is also
+//! how it serializes, so a verifier can tell which prices `gas_consumed` was
+//! measured in.
+//!
+//! Every instruction's gas also lands in one [`OpCategory`], following the
+//! opcode groups of Neo's instruction set. A `SYSCALL` counts as storage,
+//! crypto or a plain syscall depending on what it calls, and includes any
+//! gas the syscall charges on top of its opcode. [`GasBreakdown`] sums the
+//! categories, which always add up to the gas consumed.
+
+use crate::engine::syscall;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Neo N3's default `ExecFeeFactor`, datoshi per fee unit
+pub const DEFAULT_EXEC_FEE_FACTOR: u64 = 30;
+
+/// Neo N3's default `StoragePrice`, datoshi per stored byte
+pub const DEFAULT_STORAGE_PRICE: u64 = 100_000;
+
+/// Bytes per word for [`GasSchedule::Legacy`] data charges
+const WORD_SIZE: usize = 32;
+
+/// How the VM prices execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum GasSchedule {
+    /// Flat per-class prices: 1 for pushes, 2 for flow and stack operations,
+    /// 8 for arithmetic and compound types, 512 for hashes and 32768 for
+    /// `CHECKSIG`, which the signature syscalls also pay per key; other
+    /// syscalls cost only their opcode. Data is charged per 32-byte word and
+    /// storage per byte written
+    #[default]
+    Legacy,
+    /// Neo N3 opcode and syscall prices at the default execution fee factor
+    NeoN3,
+}
+
+impl GasSchedule {
+    /// Every schedule, in identifier order
+    pub const ALL: [GasSchedule; 2] = [GasSchedule::Legacy, GasSchedule::NeoN3];
+
+    /// Identifier committed in proofs
+    pub const fn id(self) -> u8 {
+        match self {
+            GasSchedule::Legacy => 0,
+            GasSchedule::NeoN3 => 1,
+        }
+    }
+
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(GasSchedule::Legacy),
+            1 => Some(GasSchedule::NeoN3),
+            _ => None,
+        }
+    }
+
+    /// Name accepted by [`FromStr`], e.g. on the command line
+    pub const fn name(self) -> &'static str {
+        match self {
+            GasSchedule::Legacy => "legacy",
+            GasSchedule::NeoN3 => "neo-n3",
+        }
+    }
+
+    /// Gas charged before executing `op`
+    #[inline]
+    pub fn opcode_price(self, op: u8) -> u64 {
+        match self {
+            GasSchedule::Legacy => LEGACY_OPCODE_PRICES[op as usize] as u64,
+            GasSchedule::NeoN3 => {
+                NEO_N3_OPCODE_PRICES[op as usize] as u64 * DEFAULT_EXEC_FEE_FACTOR
+            }
+        }
+    }
+
+    /// Gas charged for syscall `id` on top of the `SYSCALL` opcode
+    pub fn syscall_price(self, id: u32) -> u64 {
+        match self {
+            GasSchedule::Legacy => match id {
+                syscall::SYSTEM_CRYPTO_CHECKSIG => self.opcode_price(0xF3),
+                _ => 0,
+            },
+            GasSchedule::NeoN3 => {
+                let units = match id {
+                    syscall::SYSTEM_RUNTIME_GETTIME
+                    | syscall::SYSTEM_RUNTIME_GETTRIGGER
+                    | syscall::SYSTEM_RUNTIME_GETPLATFORM
+                    | syscall::SYSTEM_RUNTIME_GETNETWORK => 1 << 3,
+                    syscall::SYSTEM_RUNTIME_GETINVOCATIONCOUNTER
+                    | syscall::SYSTEM_RUNTIME_BURNGAS => 1 << 4,
+                    syscall::SYSTEM_RUNTIME_GETRANDOM => 1 << 13,
+                    syscall::SYSTEM_RUNTIME_LOG
+                    | syscall::SYSTEM_RUNTIME_NOTIFY
+                    | syscall::SYSTEM_STORAGE_GET
+                    | syscall::SYSTEM_STORAGE_PUT
+                    | syscall::SYSTEM_STORAGE_DELETE
+                    | syscall::SYSTEM_CRYPTO_CHECKSIG
+                    | syscall::SYSTEM_CONTRACT_CALL => 1 << 15,
+                    _ => 0,
+                };
+                units * DEFAULT_EXEC_FEE_FACTOR
+            }
+        }
+    }
+
+    /// Gas charged for `System.Crypto.CheckMultisig` over `keys` public keys,
+    /// on top of its syscall price
+    ///
+    /// Like Neo, every key costs one `System.Crypto.CheckSig`, whether or not
+    /// the check reaches it.
+    pub fn check_multisig_price(self, keys: usize) -> u64 {
+        self.syscall_price(syscall::SYSTEM_CRYPTO_CHECKSIG) * keys as u64
+    }
+
+    /// Gas charged for the `len` bytes `op` pushes, copies or hashes, on top
+    /// of its opcode price
+    ///
+    /// Neo N3 bounds these opcodes by item size rather than pricing their
+    /// input, so [`GasSchedule::NeoN3`] charges nothing here.
+    pub fn data_price(self, op: u8, len: usize) -> u64 {
+        match self {
+            GasSchedule::Legacy => {
+                let per_word = match op {
+                    // PUSHDATA1-4, NEWBUFFER, MEMCPY, CAT, SUBSTR, LEFT, RIGHT, CONVERT
+                    0x0C..=0x0E | 0x88 | 0x89 | 0x8B..=0x8E | 0xDB => 1,
+                    // SHA256, RIPEMD160, CHECKSIG
+                    0xF0 | 0xF1 | 0xF3 => 8,
+                    // HASH160
+                    0xF2 => 16,
+                    _ => 0,
+                };
+                per_word * len.div_ceil(WORD_SIZE) as u64
+            }
+            GasSchedule::NeoN3 => 0,
+        }
+    }
+
+    /// Gas charged for `System.Storage.Put` on top of its syscall price
+    ///
+    /// `old_len` is the length of the value being replaced, if the key
+    /// exists. [`GasSchedule::NeoN3`] follows Neo: a new entry pays for its
+    /// key and value, an overwrite for the bytes it adds plus a quarter of
+    /// those it reuses, at the default storage price.
+    pub fn storage_put_price(
+        self,
+        key_len: usize,
+        value_len: usize,
+        old_len: Option<usize>,
+    ) -> u64 {
+        match self {
+            GasSchedule::Legacy => (key_len + value_len) as u64,
+            GasSchedule::NeoN3 => {
+                let new_bytes = match old_len {
+                    None => key_len + value_len,
+                    Some(_) if value_len == 0 => 0,
+                    Some(old) if value_len <= old => (value_len - 1) / 4 + 1,
+                    Some(0) => value_len,
+                    Some(old) => (old - 1) / 4 + 1 + value_len - old,
+                };
+                new_bytes as u64 * DEFAULT_STORAGE_PRICE
+            }
+        }
+    }
+}
+
+impl From<GasSchedule> for u8 {
+    fn from(schedule: GasSchedule) -> u8 {
+        schedule.id()
+    }
+}
+
+impl TryFrom<u8> for GasSchedule {
+    type Error = String;
+
+    fn try_from(id: u8) -> Result<Self, String> {
+        GasSchedule::from_id(id).ok_or_else(|| format!("Unknown gas schedule {id}"))
+    }
+}
+
+impl fmt::Display for GasSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for GasSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        GasSchedule::ALL
+            .into_iter()
+            .find(|schedule| schedule.name() == s)
+            .ok_or_else(|| format!("Unknown gas schedule '{s}', expected legacy or neo-n3"))
+    }
+}
+
+// `LEGACY_OPCODE_PRICES` and `NEO_N3_OPCODE_PRICES`, generated from
+// `opcodes.toml` by the build script, as they are for the guest program
+include!(concat!(env!("OUT_DIR"), "/gas_prices.rs"));
+
+/// Kind of work an instruction's gas pays for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpCategory {
+    /// Pushes, 0x00-0x20
+    Constants,
+    /// Jumps, calls, exceptions and `RET`, 0x21-0x42, and `ABORTMSG` and
+    /// `ASSERTMSG`
+    Flow,
+    /// 0x43-0x55
+    Stack,
+    /// Slot initialization, loads and stores, 0x56-0x87
+    Slot,
+    /// Buffers and byte string slicing, 0x88-0x8F
+    Splice,
+    /// `INVERT` through `NOTEQUAL`, 0x90-0x98
+    Bitwise,
+    /// 0x99-0xBD
+    Arithmetic,
+    /// Arrays, maps and structs, 0xBE-0xD7
+    Compound,
+    /// `ISNULL`, `ISTYPE` and `CONVERT`, 0xD8-0xDF
+    Types,
+    /// Hash and signature opcodes 0xF0-0xFF, and the signature syscalls
+    Crypto,
+    /// `System.Storage` syscalls
+    Storage,
+    /// Every other syscall
+    Syscalls,
+}
+
+impl OpCategory {
+    /// Every category, in order
+    pub const ALL: [OpCategory; 12] = [
+        OpCategory::Constants,
+        OpCategory::Flow,
+        OpCategory::Stack,
+        OpCategory::Slot,
+        OpCategory::Splice,
+        OpCategory::Bitwise,
+        OpCategory::Arithmetic,
+        OpCategory::Compound,
+        OpCategory::Types,
+        OpCategory::Crypto,
+        OpCategory::Storage,
+        OpCategory::Syscalls,
+    ];
+
+    /// Category of `op`; `SYSCALL` counts as [`OpCategory::Syscalls`], use
+    /// [`OpCategory::of_syscall`] once the syscall is known
+    pub const fn of_opcode(op: u8) -> Self {
+        match op {
+            0x41 => OpCategory::Syscalls,
+            0x00..=0x20 => OpCategory::Constants,
+            0x21..=0x42 | 0xE0..=0xEF => OpCategory::Flow,
+            0x43..=0x55 => OpCategory::Stack,
+            0x56..=0x87 => OpCategory::Slot,
+            0x88..=0x8F => OpCategory::Splice,
+            0x90..=0x98 => OpCategory::Bitwise,
+            0x99..=0xBD => OpCategory::Arithmetic,
+            0xBE..=0xD7 => OpCategory::Compound,
+            0xD8..=0xDF => OpCategory::Types,
+            0xF0..=0xFF => OpCategory::Crypto,
+        }
+    }
+
+    /// Category of a `SYSCALL` to `id`
+    pub const fn of_syscall(id: u32) -> Self {
+        match id {
+            syscall::SYSTEM_STORAGE_GET
+            | syscall::SYSTEM_STORAGE_PUT
+            | syscall::SYSTEM_STORAGE_DELETE => OpCategory::Storage,
+            syscall::SYSTEM_CRYPTO_CHECKSIG | syscall::SYSTEM_CRYPTO_CHECKMULTISIG => {
+                OpCategory::Crypto
+            }
+            _ => OpCategory::Syscalls,
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Gas consumed per [`OpCategory`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasBreakdown([u64; OpCategory::ALL.len()]);
+
+impl GasBreakdown {
+    /// Gas charged to `category`
+    pub fn get(&self, category: OpCategory) -> u64 {
+        self.0[category.index()]
+    }
+
+    #[inline]
+    pub(crate) fn add(&mut self, category: OpCategory, gas: u64) {
+        let total = &mut self.0[category.index()];
+        *total = total.saturating_add(gas);
+    }
+
+    /// Categories that were charged, with their gas
+    pub fn to_map(&self) -> BTreeMap<OpCategory, u64> {
+        OpCategory::ALL
+            .into_iter()
+            .map(|category| (category, self.get(category)))
+            .filter(|&(_, gas)| gas > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpCode;
+
+    #[test]
+    fn test_neo_n3_prices_in_datoshi() {
+        let n3 = GasSchedule::NeoN3;
+        assert_eq!(n3.opcode_price(OpCode::PUSH1 as u8), 30);
+        assert_eq!(n3.opcode_price(OpCode::ADD as u8), 240);
+        assert_eq!(n3.opcode_price(OpCode::PUSHDATA4 as u8), 4096 * 30);
+        assert_eq!(n3.opcode_price(OpCode::RET as u8), 0);
+        assert_eq!(n3.syscall_price(syscall::SYSTEM_STORAGE_PUT), 32768 * 30);
+        assert_eq!(GasSchedule::Legacy.opcode_price(OpCode::ADD as u8), 8);
+        assert_eq!(
+            GasSchedule::Legacy.syscall_price(syscall::SYSTEM_STORAGE_PUT),
+            0
+        );
+    }
+
+    #[test]
+    fn test_signature_syscall_prices() {
+        let legacy = GasSchedule::Legacy;
+        let checksig = legacy.opcode_price(0xF3);
+        assert_eq!(checksig, 32768);
+        assert_eq!(
+            legacy.syscall_price(syscall::SYSTEM_CRYPTO_CHECKSIG),
+            checksig
+        );
+        assert_eq!(
+            legacy.syscall_price(syscall::SYSTEM_CRYPTO_CHECKMULTISIG),
+            0
+        );
+        assert_eq!(legacy.check_multisig_price(3), 3 * checksig);
+
+        let n3 = GasSchedule::NeoN3;
+        assert_eq!(n3.syscall_price(syscall::SYSTEM_CRYPTO_CHECKMULTISIG), 0);
+        assert_eq!(n3.check_multisig_price(0), 0);
+        assert_eq!(n3.check_multisig_price(3), 3 * 32768 * 30);
+    }
+
+    #[test]
+    fn test_check_multisig_charges_per_key() {
+        use crate::engine::{NeoVM, VMState};
+        use crate::ScriptBuilder;
+        use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let keys: Vec<SigningKey> = (1..=3)
+            .map(|seed| SigningKey::from_slice(&[seed; 32]).unwrap())
+            .collect();
+        let signature: Signature = keys[0].sign(b"container");
+        // One signature against the first `n` keys
+        let crypto_gas = |schedule: GasSchedule, n: usize| {
+            let mut builder = ScriptBuilder::new();
+            builder
+                .emit_push_bytes(&signature.to_bytes())
+                .emit_push_int(1);
+            for key in keys[..n].iter().rev() {
+                builder.emit_push_bytes(key.verifying_key().to_encoded_point(true).as_bytes());
+            }
+            builder
+                .emit_push_int(n as i128)
+                .emit_syscall(syscall::SYSTEM_CRYPTO_CHECKMULTISIG);
+            let mut vm = NeoVM::new(100_000_000);
+            vm.gas_schedule = schedule;
+            vm.sign_data = Some(b"container".to_vec());
+            vm.load_script(builder.into_bytes()).unwrap();
+            vm.run();
+            assert!(matches!(vm.state, VMState::Halt));
+            vm.gas_breakdown.get(OpCategory::Crypto)
+        };
+        for schedule in GasSchedule::ALL {
+            let checksig = schedule.syscall_price(syscall::SYSTEM_CRYPTO_CHECKSIG);
+            assert_eq!(
+                crypto_gas(schedule, 1),
+                schedule.opcode_price(OpCode::SYSCALL as u8) + checksig
+            );
+            assert_eq!(
+                crypto_gas(schedule, 3) - crypto_gas(schedule, 1),
+                2 * checksig
+            );
+        }
+    }
+
+    #[test]
+    fn test_data_prices_grow_with_length() {
+        let legacy = GasSchedule::Legacy;
+        let sha256 = 0xF0;
+        assert_eq!(legacy.data_price(sha256, 0), 0);
+        assert_eq!(legacy.data_price(sha256, 1), 8);
+        assert_eq!(legacy.data_price(sha256, 1 << 20), 8 << 15);
+        assert_eq!(legacy.data_price(OpCode::PUSHDATA1 as u8, 33), 2);
+        assert_eq!(legacy.data_price(OpCode::ADD as u8, 64), 0);
+        assert_eq!(GasSchedule::NeoN3.data_price(sha256, 1 << 20), 0);
+    }
+
+    #[test]
+    fn test_neo_n3_storage_put_price() {
+        let put = |key, value, old| GasSchedule::NeoN3.storage_put_price(key, value, old);
+        // New entry: key and value
+        assert_eq!(put(4, 10, None), 14 * DEFAULT_STORAGE_PRICE);
+        // Shrinking or same size: a quarter of the reused bytes, rounded up
+        assert_eq!(put(4, 8, Some(10)), 2 * DEFAULT_STORAGE_PRICE);
+        assert_eq!(put(4, 0, Some(10)), 0);
+        // Growing: the added bytes plus a quarter of the old ones
+        assert_eq!(put(4, 12, Some(10)), (3 + 2) * DEFAULT_STORAGE_PRICE);
+        assert_eq!(put(4, 3, Some(0)), 3 * DEFAULT_STORAGE_PRICE);
+        assert_eq!(GasSchedule::Legacy.storage_put_price(4, 10, Some(10)), 14);
+    }
+
+    #[test]
+    fn test_identifiers_round_trip() {
+        for schedule in GasSchedule::ALL {
+            assert_eq!(GasSchedule::from_id(schedule.id()), Some(schedule));
+            assert_eq!(schedule.name().parse::<GasSchedule>(), Ok(schedule));
+            let json = serde_json::to_string(&schedule).unwrap();
+            assert_eq!(json, schedule.id().to_string());
+            assert_eq!(
+                serde_json::from_str::<GasSchedule>(&json).unwrap(),
+                schedule
+            );
+        }
+        assert!(serde_json::from_str::<GasSchedule>("7").is_err());
+        assert!("neo2".parse::<GasSchedule>().is_err());
+    }
+
+    #[test]
+    fn test_breakdown_adds_up_to_gas_consumed() {
+        use crate::engine::{NeoVM, VMState};
+        use crate::ScriptBuilder;
+
+        assert_eq!(
+            OpCategory::of_opcode(OpCode::PUSH1 as u8),
+            OpCategory::Constants
+        );
+        assert_eq!(
+            OpCategory::of_opcode(OpCode::ABORTMSG as u8),
+            OpCategory::Flow
+        );
+        assert_eq!(OpCategory::of_opcode(0xF0), OpCategory::Crypto);
+        assert_eq!(
+            OpCategory::of_syscall(syscall::SYSTEM_CRYPTO_CHECKSIG),
+            OpCategory::Crypto
+        );
+
+        // PUSH1, PUSH2, ADD, DROP, Storage.Put(0x01, 0x02)
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_push_int(1)
+            .emit_push_int(2)
+            .emit(OpCode::ADD)
+            .emit(OpCode::DROP)
+            .emit_push_bytes(&[2])
+            .emit_push_bytes(&[1])
+            .emit_syscall(syscall::SYSTEM_STORAGE_PUT);
+        let mut vm = NeoVM::new(100_000_000);
+        vm.gas_schedule = GasSchedule::NeoN3;
+        vm.load_script(builder.into_bytes()).unwrap();
+        vm.run();
+        assert!(matches!(vm.state, VMState::Halt));
+
+        let breakdown = vm.gas_breakdown.to_map();
+        assert_eq!(breakdown.values().sum::<u64>(), vm.gas_consumed);
+        assert_eq!(
+            breakdown.keys().copied().collect::<Vec<_>>(),
+            [
+                OpCategory::Constants,
+                OpCategory::Stack,
+                OpCategory::Arithmetic,
+                OpCategory::Storage
+            ]
+        );
+        assert_eq!(breakdown[&OpCategory::Arithmetic], 8 * 30);
+        assert!(breakdown[&OpCategory::Storage] > 32768 * 30);
+    }
+}
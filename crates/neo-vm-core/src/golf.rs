@@ -0,0 +1,565 @@
+//! Gas golf
+//!
+//! [`suggest_optimizations`] looks for instruction sequences with a cheaper
+//! equivalent: arithmetic with its identity element, `PUSH1 ADD` for `INC`,
+//! values pushed or duplicated only to be dropped, wide `PUSHINT`s of small
+//! constants and jumps that land on another jump. Each [`Suggestion`]
+//! carries the replacement bytes and what it saves per execution, and
+//! [`apply_suggestions`] rewrites the script, relocating every relative
+//! jump, call, `TRY` and `PUSHA` offset.
+//!
+//! Like the [lints](crate::lint), suggestions are local rewrites checked
+//! against the instruction stream only. Sequences that a jump lands inside
+//! are left alone, but the identity rewrites assume integer operands: `ADD`
+//! of a byte string and zero converts it to an integer, which dropping the
+//! pair does not.
+
+use crate::gas::GasSchedule;
+use crate::instruction::{decode_script, DecodedScript, Instruction};
+use crate::opcode::OpcodeInfo;
+use crate::script_builder::ScriptBuilder;
+use crate::validate::relative;
+use thiserror::Error;
+
+/// Kind of rewrite a [`Suggestion`] makes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuggestionId {
+    /// `PUSH0 ADD`, `PUSH0 SUB`, `PUSH1 MUL` or `PUSH1 DIV`, which leave the
+    /// top item unchanged
+    IdentityArithmetic,
+    /// `PUSH1 ADD` or `PUSH1 SUB`, for `INC` or `DEC`
+    IncrementDecrement,
+    /// A value pushed or duplicated and dropped straight away, or `SWAP SWAP`
+    RedundantStackOp,
+    /// A `PUSHINT` wider than its value needs
+    WidePush,
+    /// A jump to an unconditional jump, or to the next instruction
+    JumpChain,
+}
+
+impl SuggestionId {
+    /// Stable identifier, e.g. `NZG001`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::IdentityArithmetic => "NZG001",
+            Self::IncrementDecrement => "NZG002",
+            Self::RedundantStackOp => "NZG003",
+            Self::WidePush => "NZG004",
+            Self::JumpChain => "NZG005",
+        }
+    }
+
+    /// Short kebab-case name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IdentityArithmetic => "identity-arithmetic",
+            Self::IncrementDecrement => "increment-decrement",
+            Self::RedundantStackOp => "redundant-stack-op",
+            Self::WidePush => "wide-push",
+            Self::JumpChain => "jump-chain",
+        }
+    }
+}
+
+/// A cheaper replacement for `len` bytes at `offset`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub id: SuggestionId,
+    pub offset: usize,
+    /// Bytes replaced, whole instructions starting at `offset`
+    pub len: usize,
+    /// Bytes to put in their place, possibly none; a jump in them is
+    /// relative to `offset`
+    pub replacement: Vec<u8>,
+    /// Gas saved each time the sequence runs
+    pub gas_saved: u64,
+    pub message: String,
+}
+
+impl Suggestion {
+    /// Bytes the script shrinks by
+    pub fn bytes_saved(&self) -> usize {
+        self.len - self.replacement.len()
+    }
+}
+
+/// Why [`apply_suggestions`] could not rewrite a script
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GolfError {
+    #[error("suggestion at 0x{0:04X} overlaps the one before it")]
+    Overlapping(usize),
+    #[error("suggestion at 0x{0:04X} does not cover whole instructions")]
+    NotAnInstruction(usize),
+    #[error("suggestion at 0x{0:04X} grows the script")]
+    Grows(usize),
+    #[error("instruction at 0x{0:04X} jumps into a replaced sequence")]
+    TargetReplaced(usize),
+}
+
+const PUSHINT8: u8 = 0x00;
+const PUSHINT256: u8 = 0x05;
+const PUSHA: u8 = 0x0A;
+const PUSH0: u8 = 0x10;
+const PUSH1: u8 = 0x11;
+const JMP: u8 = 0x22;
+const JMP_L: u8 = 0x23;
+const TRY: u8 = 0x3B;
+const TRY_L: u8 = 0x3C;
+const ENDTRY: u8 = 0x3D;
+const ENDTRY_L: u8 = 0x3E;
+const DROP: u8 = 0x45;
+const DUP: u8 = 0x4A;
+const SWAP: u8 = 0x50;
+const INC: u8 = 0x9C;
+const DEC: u8 = 0x9D;
+const ADD: u8 = 0x9E;
+const SUB: u8 = 0x9F;
+const MUL: u8 = 0xA0;
+const DIV: u8 = 0xA1;
+
+/// Find cheaper sequences in `script`, ordered by offset, with savings
+/// priced under `schedule`
+///
+/// Decoding stops at the first malformed instruction, see
+/// [`validate_script`](crate::validate_script).
+pub fn suggest_optimizations(script: &[u8], schedule: GasSchedule) -> Vec<Suggestion> {
+    let decoded = decode_script(script);
+    let targets = jump_targets(script, &decoded);
+    let instructions = decoded.instructions();
+
+    let mut suggestions = Vec::new();
+    let mut i = 0;
+    while i < instructions.len() {
+        let first = &instructions[i];
+        let pair = instructions
+            .get(i + 1)
+            .filter(|second| !targets[second.offset]);
+
+        let found = match pair {
+            Some(second) => pair_suggestion(first, second, schedule),
+            None => None,
+        }
+        .or_else(|| wide_push(script, first, schedule))
+        .or_else(|| jump_chain(script, &decoded, first, schedule));
+
+        match found {
+            Some(suggestion) => {
+                // Resume after the sequence so suggestions never overlap
+                let end = suggestion.offset + suggestion.len;
+                suggestions.push(suggestion);
+                while i < instructions.len() && instructions[i].offset < end {
+                    i += 1;
+                }
+            }
+            None => i += 1,
+        }
+    }
+    suggestions
+}
+
+/// Two-instruction patterns
+fn pair_suggestion(
+    first: &Instruction,
+    second: &Instruction,
+    schedule: GasSchedule,
+) -> Option<Suggestion> {
+    let price = |op: u8| schedule.opcode_price(op);
+    let both = price(first.opcode)
+        + schedule.data_price(first.opcode, first.operand_len)
+        + price(second.opcode);
+    let suggestion = |id, replacement: Vec<u8>, message: String| {
+        let replaced = replacement.first().map_or(0, |&op| price(op));
+        Some(Suggestion {
+            id,
+            offset: first.offset,
+            len: first.size + second.size,
+            replacement,
+            gas_saved: both.saturating_sub(replaced),
+            message,
+        })
+    };
+
+    match (first.opcode, second.opcode) {
+        (PUSH0, ADD | SUB) | (PUSH1, MUL | DIV) => suggestion(
+            SuggestionId::IdentityArithmetic,
+            Vec::new(),
+            format!(
+                "{} {} leaves an integer unchanged, remove both",
+                opcode_name(first.opcode),
+                opcode_name(second.opcode)
+            ),
+        ),
+        (PUSH1, ADD) => suggestion(
+            SuggestionId::IncrementDecrement,
+            vec![INC],
+            "PUSH1 ADD is INC".to_string(),
+        ),
+        (PUSH1, SUB) => suggestion(
+            SuggestionId::IncrementDecrement,
+            vec![DEC],
+            "PUSH1 SUB is DEC".to_string(),
+        ),
+        (DUP, DROP) | (SWAP, SWAP) => suggestion(
+            SuggestionId::RedundantStackOp,
+            Vec::new(),
+            format!(
+                "{} {} leaves the stack unchanged, remove both",
+                opcode_name(first.opcode),
+                opcode_name(second.opcode)
+            ),
+        ),
+        (op, DROP) if is_plain_push(op) => suggestion(
+            SuggestionId::RedundantStackOp,
+            Vec::new(),
+            "value is pushed and dropped straight away, remove both".to_string(),
+        ),
+        _ => None,
+    }
+}
+
+/// `PUSHINT*` of a value `PUSHM1`..`PUSH16` or a narrower `PUSHINT` holds
+fn wide_push(
+    script: &[u8],
+    instruction: &Instruction,
+    schedule: GasSchedule,
+) -> Option<Suggestion> {
+    if !(PUSHINT8..=PUSHINT256).contains(&instruction.opcode) {
+        return None;
+    }
+    let value = signed_le(instruction.operand(script))?;
+    let mut builder = ScriptBuilder::new();
+    builder.emit_push_int(value);
+    let replacement = builder.into_bytes();
+    if replacement.len() >= instruction.size {
+        return None;
+    }
+    Some(Suggestion {
+        id: SuggestionId::WidePush,
+        offset: instruction.offset,
+        len: instruction.size,
+        gas_saved: schedule
+            .opcode_price(instruction.opcode)
+            .saturating_sub(schedule.opcode_price(replacement[0])),
+        message: format!(
+            "{} {} fits in {}",
+            opcode_name(instruction.opcode),
+            value,
+            opcode_name(replacement[0])
+        ),
+        replacement,
+    })
+}
+
+/// A jump to an unconditional jump, retargeted to where that one goes, or
+/// a `JMP` to the next instruction, removed
+fn jump_chain(
+    script: &[u8],
+    decoded: &DecodedScript,
+    instruction: &Instruction,
+    schedule: GasSchedule,
+) -> Option<Suggestion> {
+    if !(JMP..=0x33).contains(&instruction.opcode) {
+        return None;
+    }
+    let target = jump_target(instruction, script)?;
+    if matches!(instruction.opcode, JMP | JMP_L) && target == instruction.next_offset() {
+        return Some(Suggestion {
+            id: SuggestionId::JumpChain,
+            offset: instruction.offset,
+            len: instruction.size,
+            replacement: Vec::new(),
+            gas_saved: schedule.opcode_price(instruction.opcode),
+            message: "jump to the next instruction, remove it".to_string(),
+        });
+    }
+
+    // Follow the chain, stopping at a cycle
+    let mut end = target;
+    let mut hops = 0;
+    let mut gas_saved = 0;
+    while let Some(next) = decoded.get(end) {
+        if !matches!(next.opcode, JMP | JMP_L) || hops == decoded.len() {
+            break;
+        }
+        let Some(after) = jump_target(next, script) else {
+            break;
+        };
+        gas_saved += schedule.opcode_price(next.opcode);
+        end = after;
+        hops += 1;
+    }
+    if hops == 0 || end == instruction.offset {
+        return None;
+    }
+
+    let delta = end as isize - instruction.offset as isize;
+    let operand = match instruction.operand_len {
+        1 => vec![i8::try_from(delta).ok()? as u8],
+        _ => i32::try_from(delta).ok()?.to_le_bytes().to_vec(),
+    };
+    let mut replacement = vec![instruction.opcode];
+    replacement.extend(operand);
+    Some(Suggestion {
+        id: SuggestionId::JumpChain,
+        offset: instruction.offset,
+        len: instruction.size,
+        replacement,
+        gas_saved,
+        message: format!(
+            "jumps to 0x{:04X}, which jumps on to 0x{:04X}; jump there directly",
+            target, end
+        ),
+    })
+}
+
+/// Rewrite `script` with `suggestions`, which must be ordered by offset and
+/// not overlap, as [`suggest_optimizations`] returns them
+///
+/// Relative offsets of jumps, calls, `TRY`, `ENDTRY` and `PUSHA` are
+/// updated for the bytes removed; a target at a replaced sequence moves to
+/// its replacement.
+pub fn apply_suggestions(script: &[u8], suggestions: &[Suggestion]) -> Result<Vec<u8>, GolfError> {
+    let decoded = decode_script(script);
+
+    // Pieces of the output, each with the offset it had in `script`
+    let mut pieces: Vec<(usize, &[u8])> = Vec::new();
+    // New offset of each old instruction boundary; None inside replacements
+    let mut moved = vec![None; script.len() + 1];
+    let mut position = 0;
+    let mut next = 0;
+    let mut pending = suggestions.iter().peekable();
+    for instruction in decoded.instructions() {
+        if instruction.offset < next {
+            continue;
+        }
+        moved[instruction.offset] = Some(position);
+        match pending.next_if(|s| s.offset == instruction.offset) {
+            Some(suggestion) => {
+                let end = suggestion.offset + suggestion.len;
+                if suggestion.replacement.len() > suggestion.len {
+                    return Err(GolfError::Grows(suggestion.offset));
+                }
+                if end > script.len() || (end < script.len() && decoded.get(end).is_none()) {
+                    return Err(GolfError::NotAnInstruction(suggestion.offset));
+                }
+                pieces.push((suggestion.offset, &suggestion.replacement));
+                position += suggestion.replacement.len();
+                next = end;
+            }
+            None => {
+                let bytes = &script[instruction.offset..instruction.next_offset()];
+                pieces.push((instruction.offset, bytes));
+                position += bytes.len();
+            }
+        }
+        match pending.peek() {
+            Some(suggestion) if suggestion.offset < next => {
+                return Err(GolfError::Overlapping(suggestion.offset));
+            }
+            Some(suggestion) if suggestion.offset < instruction.next_offset() => {
+                return Err(GolfError::NotAnInstruction(suggestion.offset));
+            }
+            _ => {}
+        }
+    }
+    if let Some(suggestion) = pending.next() {
+        return Err(GolfError::NotAnInstruction(suggestion.offset));
+    }
+    // Bytes after the last whole instruction are kept as they are
+    let tail = decoded
+        .instructions()
+        .last()
+        .map_or(0, Instruction::next_offset)
+        .max(next);
+    moved[tail] = Some(position);
+
+    let mut output = Vec::with_capacity(position);
+    for (old_offset, bytes) in pieces {
+        let start = output.len();
+        output.extend_from_slice(bytes);
+        let piece = decode_script(bytes);
+        for instruction in piece.instructions() {
+            let old = old_offset + instruction.offset;
+            let new = start + instruction.offset;
+            for (at, width) in relative_operands(instruction) {
+                let delta = relative(&bytes[at..at + width]);
+                if delta == 0 && matches!(instruction.opcode, TRY | TRY_L) {
+                    continue;
+                }
+                let target = usize::try_from(old as isize + delta)
+                    .ok()
+                    .and_then(|target| moved.get(target).copied().flatten())
+                    .ok_or(GolfError::TargetReplaced(old))?;
+                let delta = target as isize - new as isize;
+                let operand = &mut output[start + at..start + at + width];
+                match width {
+                    1 => operand[0] = i8::try_from(delta).expect("offsets only shrink") as u8,
+                    _ => operand.copy_from_slice(
+                        &i32::try_from(delta)
+                            .expect("offsets only shrink")
+                            .to_le_bytes(),
+                    ),
+                }
+            }
+        }
+    }
+    output.extend_from_slice(&script[tail..]);
+    Ok(output)
+}
+
+/// Position within the instruction's bytes and width of each relative
+/// offset operand
+fn relative_operands(instruction: &Instruction) -> Vec<(usize, usize)> {
+    let start = instruction.operand_start - instruction.offset;
+    match instruction.opcode {
+        PUSHA | JMP..=0x35 | ENDTRY | ENDTRY_L => vec![(start, instruction.operand_len)],
+        TRY | TRY_L => {
+            let half = instruction.operand_len / 2;
+            vec![(start, half), (start + half, half)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Whether each offset of `script` is the target of a relative operand
+fn jump_targets(script: &[u8], decoded: &DecodedScript) -> Vec<bool> {
+    let mut targets = vec![false; script.len() + 1];
+    for instruction in decoded.instructions() {
+        for (at, width) in relative_operands(instruction) {
+            let start = instruction.offset + at;
+            let delta = relative(&script[start..start + width]);
+            if let Ok(target) = usize::try_from(instruction.offset as isize + delta) {
+                if let Some(slot) = targets.get_mut(target) {
+                    *slot = true;
+                }
+            }
+        }
+    }
+    targets
+}
+
+fn jump_target(instruction: &Instruction, script: &[u8]) -> Option<usize> {
+    usize::try_from(instruction.offset as isize + relative(instruction.operand(script))).ok()
+}
+
+/// Opcodes that only push a constant: PUSHINT*, PUSHT, PUSHF, PUSHNULL,
+/// PUSHDATA*, PUSHM1 and PUSH0..PUSH16
+fn is_plain_push(op: u8) -> bool {
+    matches!(op, 0x00..=0x05 | 0x08 | 0x09 | 0x0B..=0x20)
+}
+
+/// Little-endian two's complement integer, if it fits in an `i128`
+fn signed_le(bytes: &[u8]) -> Option<i128> {
+    let negative = bytes.last().is_some_and(|b| b & 0x80 != 0);
+    let fill = if negative { 0xFF } else { 0x00 };
+    let mut buf = [fill; 16];
+    let (low, high) = bytes.split_at(bytes.len().min(16));
+    if high.iter().any(|&b| b != fill) {
+        return None;
+    }
+    buf[..low.len()].copy_from_slice(low);
+    let value = i128::from_le_bytes(buf);
+    // The value's sign must survive the truncation
+    (value.is_negative() == negative).then_some(value)
+}
+
+fn opcode_name(op: u8) -> &'static str {
+    OpcodeInfo::of(op).map_or("?", |info| info.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(script: &[u8]) -> Vec<(&'static str, usize)> {
+        suggest_optimizations(script, GasSchedule::Legacy)
+            .iter()
+            .map(|s| (s.id.code(), s.offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_patterns() {
+        let script = [
+            0x12, // PUSH2
+            0x10, 0x9E, // PUSH0 ADD
+            0x11, 0x9E, // PUSH1 ADD
+            0x4A, 0x45, // DUP DROP
+            0x01, 0x07, 0x00, // PUSHINT16 7
+            0x02, 0x2C, 0x01, 0x00, 0x00, // PUSHINT32 300
+            0x40, // RET
+        ];
+        assert_eq!(
+            ids(&script),
+            [
+                ("NZG001", 1),
+                ("NZG002", 3),
+                ("NZG003", 5),
+                ("NZG004", 7),
+                ("NZG004", 10)
+            ]
+        );
+
+        let suggestions = suggest_optimizations(&script, GasSchedule::Legacy);
+        assert_eq!(suggestions[0].gas_saved, 9);
+        assert_eq!(suggestions[1].replacement, [INC]);
+        assert_eq!(suggestions[3].replacement, [0x17]);
+        assert_eq!(suggestions[4].replacement, [0x01, 0x2C, 0x01]);
+        assert_eq!(
+            apply_suggestions(&script, &suggestions).unwrap(),
+            [0x12, 0x9C, 0x17, 0x01, 0x2C, 0x01, 0x40]
+        );
+    }
+
+    #[test]
+    fn test_jump_chain_is_retargeted_and_offsets_relocated() {
+        let script = [
+            0x22, 0x04, // 0: JMP +4 -> 4
+            0x10, 0x9E, // 2: PUSH0 ADD
+            0x22, 0x03, // 4: JMP +3 -> 7
+            0x21, // 6: NOP
+            0x24, 0xFB, // 7: JMPIF -5 -> 2
+            0x40, // 9: RET
+        ];
+        assert_eq!(ids(&script), [("NZG005", 0), ("NZG001", 2)]);
+        let suggestions = suggest_optimizations(&script, GasSchedule::Legacy);
+        assert_eq!(suggestions[0].replacement, [0x22, 0x07]);
+
+        // JMPIF targeted the removed pair, so it now lands after it
+        let golfed = apply_suggestions(&script, &suggestions).unwrap();
+        assert_eq!(golfed, [0x22, 0x05, 0x22, 0x03, 0x21, 0x24, 0xFD, 0x40]);
+
+        let removal = Suggestion {
+            id: SuggestionId::RedundantStackOp,
+            offset: 4,
+            len: 1,
+            replacement: Vec::new(),
+            gas_saved: 0,
+            message: String::new(),
+        };
+        assert_eq!(
+            apply_suggestions(&golfed, &[removal]).unwrap(),
+            [0x22, 0x04, 0x22, 0x02, 0x24, 0xFE, 0x40]
+        );
+    }
+
+    #[test]
+    fn test_sequences_a_jump_lands_inside_are_kept() {
+        // JMPIF +3 lands on the ADD of PUSH0 ADD
+        let script = [0x24, 0x03, 0x10, 0x9E, 0x40];
+        assert!(ids(&script).is_empty());
+    }
+
+    #[test]
+    fn test_jump_to_next_instruction_and_overlap() {
+        let script = [0x22, 0x02, 0x40]; // JMP +2, RET
+        let suggestions = suggest_optimizations(&script, GasSchedule::Legacy);
+        assert_eq!(apply_suggestions(&script, &suggestions).unwrap(), [0x40]);
+
+        let twice = [suggestions[0].clone(), suggestions[0].clone()];
+        assert_eq!(
+            apply_suggestions(&script, &twice),
+            Err(GolfError::Overlapping(0))
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Execution hooks
+//!
+//! Hooks observe execution without modifying the engine: profilers, coverage
+//! tools, debuggers and custom gas meters install one with
+//! [`NeoVM::set_hook`](crate::NeoVM::set_hook) and receive a callback around
+//! every instruction, syscall and fault.
+
+use crate::engine::VMError;
+
+/// What the VM should do after [`ExecutionHook::before_op`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HookAction {
+    /// Execute the instruction
+    #[default]
+    Continue,
+    /// Stop before the instruction and enter `VMState::Break`
+    ///
+    /// The next call to `execute_next` executes the instruction without
+    /// consulting the hook again.
+    Break,
+}
+
+/// Callbacks invoked by the VM while it executes
+///
+/// All methods have no-op defaults, so implementors only override the events
+/// they care about.
+pub trait ExecutionHook: Send {
+    /// Called before the instruction at `ip` is charged gas and executed
+    fn before_op(&mut self, _ip: usize, _opcode: u8) -> HookAction {
+        HookAction::Continue
+    }
+
+    /// Called after the instruction at `ip` executed successfully
+    fn after_op(&mut self, _ip: usize, _opcode: u8, _gas_consumed: u64) {}
+
+    /// Called when a syscall is dispatched, before it runs
+    fn on_syscall(&mut self, _id: u32) {}
+
+    /// Called when the instruction at `ip` faults the VM
+    fn on_fault(&mut self, _ip: usize, _error: &VMError) {}
+}
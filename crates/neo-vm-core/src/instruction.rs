@@ -0,0 +1,185 @@
+//! Pre-decoded instruction stream
+//!
+//! Scripts are decoded once when they are loaded so the engine can dispatch on
+//! opcode/operand pairs instead of re-reading and bounds-checking raw bytes on
+//! every step. The decoded form is shared between execution contexts.
+
+use crate::engine::VMError;
+use crate::opcode::OpcodeInfo;
+
+/// Operand encoding of an opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandSize {
+    /// Fixed number of operand bytes following the opcode
+    Fixed(usize),
+    /// Little-endian length prefix of the given width, followed by that many bytes
+    Prefix(usize),
+}
+
+/// Operand layout for an opcode, from the opcode table; undefined opcodes
+/// have none
+#[inline]
+pub fn operand_size(op: u8) -> OperandSize {
+    OpcodeInfo::of(op).map_or(OperandSize::Fixed(0), |info| info.operand)
+}
+
+/// A single decoded instruction
+///
+/// Operands are referenced by position rather than copied so instructions stay
+/// `Copy` and cheap to fetch on every step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// Byte offset of the opcode within the script
+    pub offset: usize,
+    /// Raw opcode byte
+    pub opcode: u8,
+    /// Byte offset of the operand (after any length prefix)
+    pub operand_start: usize,
+    /// Operand length in bytes
+    pub operand_len: usize,
+    /// Total encoded size including opcode and length prefix
+    pub size: usize,
+}
+
+impl Instruction {
+    /// Decode the instruction starting at `offset`
+    pub fn decode(script: &[u8], offset: usize) -> Result<Self, VMError> {
+        let opcode = *script.get(offset).ok_or(VMError::InvalidScript)?;
+        let (prefix_len, operand_len) = match operand_size(opcode) {
+            OperandSize::Fixed(n) => (0, n),
+            OperandSize::Prefix(width) => {
+                let start = offset + 1;
+                let prefix = script
+                    .get(start..start + width)
+                    .ok_or(VMError::InvalidScript)?;
+                let mut buf = [0u8; 4];
+                buf[..width].copy_from_slice(prefix);
+                (width, u32::from_le_bytes(buf) as usize)
+            }
+        };
+
+        let operand_start = offset + 1 + prefix_len;
+        let end = operand_start
+            .checked_add(operand_len)
+            .ok_or(VMError::InvalidScript)?;
+        if end > script.len() {
+            return Err(VMError::InvalidScript);
+        }
+
+        Ok(Self {
+            offset,
+            opcode,
+            operand_start,
+            operand_len,
+            size: end - offset,
+        })
+    }
+
+    /// Offset of the next sequential instruction
+    #[inline]
+    pub fn next_offset(&self) -> usize {
+        self.offset + self.size
+    }
+
+    /// Operand bytes within `script`
+    #[inline]
+    pub fn operand<'a>(&self, script: &'a [u8]) -> &'a [u8] {
+        &script[self.operand_start..self.operand_start + self.operand_len]
+    }
+}
+
+/// Sentinel in the offset index for bytes that do not start an instruction
+const NOT_AN_INSTRUCTION: u32 = u32::MAX;
+
+/// A script decoded into its instruction sequence
+#[derive(Debug, Clone, Default)]
+pub struct DecodedScript {
+    instructions: Vec<Instruction>,
+    /// Maps each byte offset to its instruction index
+    index: Vec<u32>,
+}
+
+impl DecodedScript {
+    /// Instruction starting at `offset`, if `offset` is an instruction boundary
+    #[inline]
+    pub fn get(&self, offset: usize) -> Option<&Instruction> {
+        match self.index.get(offset) {
+            Some(&idx) if idx != NOT_AN_INSTRUCTION => self.instructions.get(idx as usize),
+            _ => None,
+        }
+    }
+
+    /// All decoded instructions in script order
+    #[inline]
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+}
+
+/// Decode a script into its instruction sequence
+///
+/// Decoding stops at the first malformed instruction (e.g. a truncated operand);
+/// executing that offset faults with `InvalidScript` just as it would without
+/// the cache.
+pub fn decode_script(script: &[u8]) -> DecodedScript {
+    let mut instructions = Vec::new();
+    let mut index = vec![NOT_AN_INSTRUCTION; script.len()];
+    let mut offset = 0;
+
+    while offset < script.len() {
+        let Ok(instruction) = Instruction::decode(script, offset) else {
+            break;
+        };
+        index[offset] = instructions.len() as u32;
+        instructions.push(instruction);
+        offset = instruction.next_offset();
+    }
+
+    DecodedScript {
+        instructions,
+        index,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_simple_script() {
+        // PUSH2, PUSH3, ADD, RET
+        let decoded = decode_script(&[0x12, 0x13, 0x9E, 0x40]);
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(decoded.get(2).map(|i| i.opcode), Some(0x9E));
+    }
+
+    #[test]
+    fn test_decode_pushdata_operand() {
+        // PUSHDATA1 3 "abc", RET
+        let script = [0x0C, 0x03, b'a', b'b', b'c', 0x40];
+        let decoded = decode_script(&script);
+        let push = decoded.get(0).unwrap();
+        assert_eq!(push.size, 5);
+        assert_eq!(push.operand(&script), b"abc");
+        assert!(decoded.get(2).is_none());
+        assert_eq!(decoded.get(5).map(|i| i.opcode), Some(0x40));
+    }
+
+    #[test]
+    fn test_decode_stops_at_truncated_operand() {
+        // PUSH1, PUSHDATA1 with length 5 but only 1 byte of data
+        let decoded = decode_script(&[0x11, 0x0C, 0x05, 0x01]);
+        assert_eq!(decoded.len(), 1);
+        assert!(decoded.get(1).is_none());
+    }
+}
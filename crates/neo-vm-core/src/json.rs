@@ -0,0 +1,142 @@
+//! JSON representation of VM values
+//!
+//! Human-readable serializers (`serde_json`, YAML, TOML) get a stable format
+//! shared by the CLI, RPC server and language bindings; binary serializers
+//! such as bincode keep the derived layout, so proofs and public values
+//! encode exactly as before.
+//!
+//! - Stack items are objects tagged by type, in the shape Neo RPC returns
+//!   for `invokescript` stacks: `{"type":"Integer","value":"5"}`. Integers
+//!   are decimal strings, byte strings and buffers base64, maps a list of
+//!   `{"key":..,"value":..}` entries and `Any` (null) has no value.
+//! - Byte fields such as storage keys and values are base64 strings.
+//! - Hashes, 32-byte hashes and 20-byte script hashes alike, are lowercase
+//!   hex strings without a `0x` prefix.
+//!
+//! The field modules below are for `#[serde(with = "...")]`.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// `Vec<u8>` as a base64 string
+pub mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            BASE64.encode(bytes).serialize(serializer)
+        } else {
+            bytes.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            decode_base64::<D>(&String::deserialize(deserializer)?)
+        } else {
+            Vec::deserialize(deserializer)
+        }
+    }
+}
+
+/// `[u8; 32]` or `[u8; 20]` as a hex string
+pub mod hex_hash {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        hash: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        [u8; N]: Serialize,
+    {
+        if serializer.is_human_readable() {
+            hex::encode(hash).serialize(serializer)
+        } else {
+            hash.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error>
+    where
+        [u8; N]: Deserialize<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            hex::decode(&text)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| de::Error::custom(format!("invalid {N}-byte hex hash '{text}'")))
+        } else {
+            <[u8; N]>::deserialize(deserializer)
+        }
+    }
+}
+
+/// Storage write sets as `[{"key": base64, "value": base64 or null}]`
+pub mod storage_writes {
+    use super::*;
+
+    type Writes = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+    #[derive(Serialize, Deserialize)]
+    struct Write {
+        #[serde(with = "base64_bytes")]
+        key: Vec<u8>,
+        #[serde(with = "option_base64_bytes")]
+        value: Option<Vec<u8>>,
+    }
+
+    pub fn serialize<S: Serializer>(writes: &Writes, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_seq(writes.iter().map(|(key, value)| Write {
+                key: key.clone(),
+                value: value.clone(),
+            }))
+        } else {
+            writes.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Writes, D::Error> {
+        if deserializer.is_human_readable() {
+            Ok(Vec::<Write>::deserialize(deserializer)?
+                .into_iter()
+                .map(|write| (write.key, write.value))
+                .collect())
+        } else {
+            Writes::deserialize(deserializer)
+        }
+    }
+
+    mod option_base64_bytes {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            bytes: &Option<Vec<u8>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            bytes
+                .as_ref()
+                .map(|bytes| BASE64.encode(bytes))
+                .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Vec<u8>>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|text| decode_base64::<D>(&text))
+                .transpose()
+        }
+    }
+}
+
+pub(crate) fn decode_base64<'de, D: Deserializer<'de>>(text: &str) -> Result<Vec<u8>, D::Error> {
+    BASE64
+        .decode(text)
+        .map_err(|_| de::Error::custom(format!("invalid base64 '{text}'")))
+}
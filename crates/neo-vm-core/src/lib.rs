@@ -127,14 +127,75 @@
 //! ```
 //!
 
+pub mod bench;
+pub mod conformance;
+pub mod contract;
+pub mod contract_parameter;
+pub mod coverage;
+pub mod disassembler;
 pub mod engine;
+pub mod fault;
+pub mod gas;
+pub mod golf;
+pub mod hook;
+pub mod instruction;
+pub mod json;
+pub mod limits;
+pub mod lint;
 pub mod native;
+pub mod neo_types;
 pub mod opcode;
+pub mod oracle;
+pub mod replay;
+pub mod runtime;
+pub mod script_builder;
+pub mod session;
 pub mod stack_item;
 pub mod storage;
+pub mod trace;
+pub mod trie;
+pub mod validate;
+pub mod watch;
+pub mod witness;
 
-pub use engine::{NeoVM, VMError, VMState};
-pub use native::{CryptoLib, NativeContract, NativeRegistry, StdLib};
-pub use opcode::OpCode;
+/// Binary SHA-256 Merkle trees hashed like Neo's, from the `no_std`
+/// `neo-merkle` crate the guest program can also use
+pub use neo_merkle as merkle;
+
+pub use bench::{bench_script, BenchResult};
+/// Payload of [`StackItem::ByteString`]
+pub use bytes::Bytes;
+pub use contract::{CallFlags, Contract, ContractMethod};
+pub use contract_parameter::{ContractParameter, ContractParameterError};
+pub use coverage::Coverage;
+pub use disassembler::Disassembler;
+pub use engine::{CancellationToken, NeoVM, RunOutcome, VMError, VMState, VmSnapshot};
+pub use fault::{FaultCode, FaultInfo};
+pub use gas::{GasBreakdown, GasSchedule, OpCategory};
+pub use golf::{apply_suggestions, suggest_optimizations, GolfError, Suggestion, SuggestionId};
+pub use hook::{ExecutionHook, HookAction};
+pub use instruction::{decode_script, DecodedScript, Instruction};
+pub use limits::VMLimits;
+pub use lint::{lint_script, Lint, LintId};
+pub use native::{
+    CryptoLib, LedgerContract, NativeContract, NativeRegistry, OracleContract, StdLib,
+};
+pub use neo_types::{AddressError, UInt160, UInt256};
+pub use opcode::{OpCode, OpcodeInfo};
+pub use oracle::{OracleResponse, OracleResponses};
+pub use replay::{ReplayEvent, ReplayLog};
+pub use runtime::{BlockContext, NotificationEvent, RuntimeConfig, TimeProvider, TriggerType};
+pub use script_builder::ScriptBuilder;
+pub use session::{ExecutionResult, ExecutionSession};
 pub use stack_item::StackItem;
-pub use storage::{MemoryStorage, StorageBackend, StorageContext, TrackedStorage};
+#[cfg(feature = "persistent")]
+pub use storage::PersistentStorage;
+pub use storage::{MemoryStorage, StorageBackend, StorageContext, StorageError, TrackedStorage};
+pub use trace::{
+    verify_trace, verify_trace_with, ExecutionTrace, FileTraceSink, HashChainSink, TraceDiff,
+    TraceSink, TraceStep,
+};
+pub use trie::{TrieProof, TrieStorage};
+pub use validate::{validate_script, ValidationIssue};
+pub use watch::{WatchHit, WatchKind, Watchpoint};
+pub use witness::{verify_witness, verify_witness_with_gas, WitnessError, MAX_VERIFICATION_GAS};
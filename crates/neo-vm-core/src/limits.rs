@@ -0,0 +1,85 @@
+//! Execution limits
+//!
+//! [`VMLimits`] bounds what one execution may use besides gas: how deep the
+//! evaluation and invocation stacks grow, how large a script and a single
+//! byte string or buffer may be, and how many instructions run. A
+//! `neo_vm_guest::ProofInput` carries its limits to the guest program, which
+//! enforces the same ones, so a script faults at the same point whether it
+//! is simulated or proven.
+
+use crate::engine::{DEFAULT_MAX_INVOCATION_DEPTH, DEFAULT_MAX_STACK_DEPTH, MAX_SCRIPT_SIZE};
+use serde::{Deserialize, Serialize};
+
+/// Neo's `MaxItemSize`, bytes in one byte string or buffer
+pub const DEFAULT_MAX_ITEM_SIZE: usize = u16::MAX as usize * 2;
+
+/// Limits of one execution, see [`NeoVM::with_limits`](crate::NeoVM::with_limits)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VMLimits {
+    /// Items on the evaluation stack
+    pub max_stack: usize,
+    /// Contexts on the invocation stack
+    pub max_invocation_depth: usize,
+    /// Bytes in a loaded script
+    pub max_script_size: usize,
+    /// Bytes in one byte string or buffer
+    pub max_item_size: usize,
+    /// Instructions one execution may run, `u64::MAX` for no limit
+    pub max_trace_steps: u64,
+}
+
+impl Default for VMLimits {
+    fn default() -> Self {
+        Self {
+            max_stack: DEFAULT_MAX_STACK_DEPTH,
+            max_invocation_depth: DEFAULT_MAX_INVOCATION_DEPTH,
+            max_script_size: MAX_SCRIPT_SIZE,
+            max_item_size: DEFAULT_MAX_ITEM_SIZE,
+            max_trace_steps: u64::MAX,
+        }
+    }
+}
+
+impl VMLimits {
+    /// Encoding hashed into a proof's input hash: the four sizes as `u32`s,
+    /// saturating, then `max_trace_steps` as a `u64`, all little-endian
+    pub fn encode(&self) -> [u8; 24] {
+        let mut out = [0u8; 24];
+        let sizes = [
+            self.max_stack,
+            self.max_invocation_depth,
+            self.max_script_size,
+            self.max_item_size,
+        ];
+        for (chunk, size) in out.chunks_exact_mut(4).zip(sizes) {
+            chunk.copy_from_slice(&saturating_u32(size).to_le_bytes());
+        }
+        out[16..].copy_from_slice(&self.max_trace_steps.to_le_bytes());
+        out
+    }
+}
+
+/// `value`, or `u32::MAX` if it does not fit
+fn saturating_u32(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_json_keeps_defaults() {
+        let limits: VMLimits = serde_json::from_str(r#"{"max_stack": 16}"#).unwrap();
+        assert_eq!(
+            limits,
+            VMLimits {
+                max_stack: 16,
+                ..VMLimits::default()
+            }
+        );
+        assert_eq!(&limits.encode()[..4], &16u32.to_le_bytes());
+        assert_eq!(&limits.encode()[16..], &u64::MAX.to_le_bytes());
+    }
+}
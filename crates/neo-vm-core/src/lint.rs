@@ -0,0 +1,274 @@
+//! Security lints
+//!
+//! [`lint_script`] looks for patterns that usually mean a contract bug. The
+//! checks are heuristics over the instruction stream and control flow graph,
+//! so a lint is a prompt to review the code rather than proof of a
+//! vulnerability. Every lint has a stable [`LintId`] for tooling.
+
+use crate::engine::syscall;
+use crate::instruction::{decode_script, DecodedScript, Instruction};
+use crate::validate::relative;
+
+/// Kind of problem a [`Lint`] reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintId {
+    /// A signature check whose result is dropped straight away
+    CheckSigResultDropped,
+    /// A storage write reachable without passing a signature check
+    UnauthorizedStorageWrite,
+    /// A loop that walks an array taken from the caller's arguments
+    UnboundedLoop,
+    /// A syscall whose result comes from the execution environment
+    NondeterministicSyscall,
+}
+
+impl LintId {
+    /// Stable identifier, e.g. `NZL001`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::CheckSigResultDropped => "NZL001",
+            Self::UnauthorizedStorageWrite => "NZL002",
+            Self::UnboundedLoop => "NZL003",
+            Self::NondeterministicSyscall => "NZL004",
+        }
+    }
+
+    /// Short kebab-case name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CheckSigResultDropped => "checksig-result-dropped",
+            Self::UnauthorizedStorageWrite => "unauthorized-storage-write",
+            Self::UnboundedLoop => "unbounded-loop",
+            Self::NondeterministicSyscall => "nondeterministic-syscall",
+        }
+    }
+}
+
+/// A suspicious pattern found by [`lint_script`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub id: LintId,
+    /// Offset of the instruction the lint points at
+    pub offset: usize,
+    pub message: String,
+}
+
+const DROP: u8 = 0x45;
+const CHECKSIG: u8 = 0xF3;
+const SYSCALL: u8 = 0x41;
+const SIZE: u8 = 0xCA;
+const PICKITEM: u8 = 0xCE;
+
+/// Run every lint over `script`, returning findings ordered by offset
+///
+/// Decoding stops at the first malformed instruction, see
+/// [`validate_script`](crate::validate_script).
+pub fn lint_script(script: &[u8]) -> Vec<Lint> {
+    let decoded = decode_script(script);
+    let mut lints = Vec::new();
+    checksig_result_dropped(script, &decoded, &mut lints);
+    unauthorized_storage_writes(script, &decoded, &mut lints);
+    unbounded_loops(script, &decoded, &mut lints);
+    nondeterministic_syscalls(script, &decoded, &mut lints);
+    lints.sort_by_key(|lint| lint.offset);
+    lints
+}
+
+fn syscall_id(script: &[u8], instruction: &Instruction) -> Option<u32> {
+    (instruction.opcode == SYSCALL)
+        .then(|| u32::from_le_bytes(instruction.operand(script).try_into().unwrap()))
+}
+
+fn is_signature_check(script: &[u8], instruction: &Instruction) -> bool {
+    instruction.opcode == CHECKSIG
+        || matches!(
+            syscall_id(script, instruction),
+            Some(syscall::SYSTEM_CRYPTO_CHECKSIG | syscall::SYSTEM_CRYPTO_CHECKMULTISIG)
+        )
+}
+
+fn checksig_result_dropped(script: &[u8], decoded: &DecodedScript, lints: &mut Vec<Lint>) {
+    for pair in decoded.instructions().windows(2) {
+        if is_signature_check(script, &pair[0]) && pair[1].opcode == DROP {
+            lints.push(Lint {
+                id: LintId::CheckSigResultDropped,
+                offset: pair[1].offset,
+                message: "signature check result is dropped without being tested".to_string(),
+            });
+        }
+    }
+}
+
+/// Offsets execution can continue at after `instruction`
+fn successors(instruction: &Instruction, script: &[u8]) -> Vec<usize> {
+    let operand = instruction.operand(script);
+    let target = |delta: isize| usize::try_from(instruction.offset as isize + delta).ok();
+    let next = instruction.next_offset();
+    match instruction.opcode {
+        // ABORT, THROW, RET, ABORTMSG
+        0x38 | 0x3A | 0x40 | 0xE0 => vec![],
+        // JMP, JMP_L, ENDTRY, ENDTRY_L
+        0x22 | 0x23 | 0x3D | 0x3E => target(relative(operand)).into_iter().collect(),
+        // Conditional jumps and calls fall through as well
+        0x24..=0x35 => target(relative(operand))
+            .into_iter()
+            .chain([next])
+            .collect(),
+        // TRY, TRY_L: the catch and finally blocks, zero when absent
+        0x3B | 0x3C => {
+            let (catch, finally) = operand.split_at(operand.len() / 2);
+            let mut offsets = vec![next];
+            for handler in [catch, finally] {
+                let delta = relative(handler);
+                if delta != 0 {
+                    offsets.extend(target(delta));
+                }
+            }
+            offsets
+        }
+        _ => vec![next],
+    }
+}
+
+/// Storage writes reachable from the entry point along a path with no
+/// signature check before them
+fn unauthorized_storage_writes(script: &[u8], decoded: &DecodedScript, lints: &mut Vec<Lint>) {
+    // Visited (offset, authorized) pairs
+    let mut visited = vec![[false; 2]; script.len()];
+    let mut reported = vec![false; script.len()];
+    let mut pending = vec![(0usize, false)];
+    while let Some((offset, authorized)) = pending.pop() {
+        let Some(instruction) = decoded.get(offset) else {
+            continue;
+        };
+        if std::mem::replace(&mut visited[offset][authorized as usize], true) {
+            continue;
+        }
+        let is_write = matches!(
+            syscall_id(script, instruction),
+            Some(syscall::SYSTEM_STORAGE_PUT | syscall::SYSTEM_STORAGE_DELETE)
+        );
+        if is_write && !authorized && !std::mem::replace(&mut reported[offset], true) {
+            lints.push(Lint {
+                id: LintId::UnauthorizedStorageWrite,
+                offset,
+                message: "storage write is reachable without a signature check".to_string(),
+            });
+        }
+        let authorized = authorized || is_signature_check(script, instruction);
+        for next in successors(instruction, script) {
+            pending.push((next, authorized));
+        }
+    }
+}
+
+/// Backward jumps whose loop body indexes or sizes an argument
+fn unbounded_loops(script: &[u8], decoded: &DecodedScript, lints: &mut Vec<Lint>) {
+    let instructions = decoded.instructions();
+    for (end, jump) in instructions.iter().enumerate() {
+        if !(0x22..=0x33).contains(&jump.opcode) {
+            continue;
+        }
+        let Some(&target) = successors(jump, script).first() else {
+            continue;
+        };
+        if target > jump.offset {
+            continue;
+        }
+        let body = instructions[..=end]
+            .iter()
+            .filter(|instruction| instruction.offset >= target);
+        let (mut loads_argument, mut walks_array) = (false, false);
+        for instruction in body {
+            // LDARG0..LDARG6, LDARG
+            loads_argument |= (0x78..=0x7F).contains(&instruction.opcode);
+            walks_array |= matches!(instruction.opcode, SIZE | PICKITEM);
+        }
+        if loads_argument && walks_array {
+            lints.push(Lint {
+                id: LintId::UnboundedLoop,
+                offset: jump.offset,
+                message: "loop iterates over caller-supplied data without a fixed bound"
+                    .to_string(),
+            });
+        }
+    }
+}
+
+fn nondeterministic_syscalls(script: &[u8], decoded: &DecodedScript, lints: &mut Vec<Lint>) {
+    for instruction in decoded.instructions() {
+        let name = match syscall_id(script, instruction) {
+            Some(syscall::SYSTEM_RUNTIME_GETTIME) => "System.Runtime.GetTime",
+            Some(syscall::SYSTEM_RUNTIME_GETRANDOM) => "System.Runtime.GetRandom",
+            _ => continue,
+        };
+        lints.push(Lint {
+            id: LintId::NondeterministicSyscall,
+            offset: instruction.offset,
+            message: format!("{name} depends on the execution environment, not the inputs"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OpCode, ScriptBuilder};
+
+    fn ids(script: &[u8]) -> Vec<(&'static str, usize)> {
+        lint_script(script)
+            .iter()
+            .map(|lint| (lint.id.code(), lint.offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_dropped_checksig_and_unauthorized_write() {
+        let mut script = ScriptBuilder::new();
+        script
+            .emit_syscall(syscall::SYSTEM_CRYPTO_CHECKSIG)
+            .emit(OpCode::DROP)
+            .emit_push_bytes(b"v")
+            .emit_push_bytes(b"k")
+            .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+            .emit(OpCode::RET);
+        // The write follows a signature check, even though its result is lost
+        assert_eq!(ids(&script.into_bytes()), [("NZL001", 5)]);
+
+        let mut script = ScriptBuilder::new();
+        script
+            .emit_push_bytes(b"k")
+            .emit_syscall(syscall::SYSTEM_STORAGE_DELETE)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETTIME)
+            .emit(OpCode::RET);
+        assert_eq!(ids(&script.into_bytes()), [("NZL002", 3), ("NZL004", 8)]);
+    }
+
+    #[test]
+    fn test_write_on_unchecked_branch() {
+        // The entry jumps over the signature check straight to the write
+        let script = [
+            0x22, 0x04, // JMP +4
+            0xF3, // CHECKSIG
+            0x40, // RET
+            0x0C, 0x01, b'v', // PUSHDATA1 "v"
+            0x0C, 0x01, b'k', // PUSHDATA1 "k"
+            0x41, 0x11, 0x00, 0x00, 0x00, // SYSCALL Storage.Put
+            0x40, // RET
+        ];
+        assert_eq!(ids(&script), [("NZL002", 10)]);
+    }
+
+    #[test]
+    fn test_loop_over_argument() {
+        let script = [
+            0x57, 0x01, 0x01, // INITSLOT 1 local, 1 argument
+            0x10, 0x70, // PUSH0, STLOC0
+            0x78, 0x68, 0xCE, 0x45, // loop: LDARG0, LDLOC0, PICKITEM, DROP
+            0x68, 0x9C, 0x4A, 0x70, // LDLOC0, INC, DUP, STLOC0
+            0x78, 0xCA, 0x30, 0xF6, // LDARG0, SIZE, JMPLT loop
+            0x40, // RET
+        ];
+        assert_eq!(ids(&script), [("NZL003", 15)]);
+    }
+}
@@ -2,8 +2,11 @@
 //!
 //! Built-in contracts that provide core blockchain functionality.
 
+use crate::oracle::OracleResponses;
+use crate::runtime::{BlockContext, TimeProvider};
 use crate::stack_item::StackItem;
 use sha2::{Digest, Sha256};
+use std::sync::Arc;
 
 /// Maximum input size for native contract functions (1MB)
 const MAX_INPUT_SIZE: usize = 1024 * 1024;
@@ -30,7 +33,7 @@ impl StdLib {
             return Err("serialize requires 1 argument".to_string());
         }
         let bytes = bincode::serialize(&args[0]).map_err(|e| e.to_string())?;
-        Ok(StackItem::ByteString(bytes))
+        Ok(StackItem::ByteString(bytes.into()))
     }
 
     fn deserialize(&self, args: Vec<StackItem>) -> Result<StackItem, String> {
@@ -59,7 +62,7 @@ impl StdLib {
                 MAX_INPUT_SIZE
             ));
         }
-        Ok(StackItem::ByteString(json.into_bytes()))
+        Ok(StackItem::ByteString(json.into()))
     }
 }
 
@@ -75,7 +78,7 @@ impl StdLib {
             }
             use base64::{engine::general_purpose::STANDARD, Engine};
             let encoded = STANDARD.encode(bytes);
-            Ok(StackItem::ByteString(encoded.into_bytes()))
+            Ok(StackItem::ByteString(encoded.into()))
         } else {
             Err("base64Encode requires ByteString".to_string())
         }
@@ -93,7 +96,7 @@ impl StdLib {
             use base64::{engine::general_purpose::STANDARD, Engine};
             let s = String::from_utf8_lossy(bytes);
             let decoded = STANDARD.decode(s.as_ref()).map_err(|e| e.to_string())?;
-            Ok(StackItem::ByteString(decoded))
+            Ok(StackItem::ByteString(decoded.into()))
         } else {
             Err("base64Decode requires ByteString".to_string())
         }
@@ -126,7 +129,7 @@ impl StdLib {
                 16 => format!("{:x}", n),
                 _ => unreachable!(),
             };
-            Ok(StackItem::ByteString(s.into_bytes()))
+            Ok(StackItem::ByteString(s.into()))
         } else {
             Err("itoa requires Integer".to_string())
         }
@@ -231,7 +234,7 @@ impl CryptoLib {
                 ));
             }
             let hash = Sha256::digest(data);
-            Ok(StackItem::ByteString(hash.to_vec()))
+            Ok(StackItem::ByteString(hash.to_vec().into()))
         } else {
             Err("sha256 requires ByteString".to_string())
         }
@@ -248,7 +251,7 @@ impl CryptoLib {
             }
             use ripemd::Ripemd160;
             let hash = Ripemd160::digest(data);
-            Ok(StackItem::ByteString(hash.to_vec()))
+            Ok(StackItem::ByteString(hash.to_vec().into()))
         } else {
             Err("ripemd160 requires ByteString".to_string())
         }
@@ -263,18 +266,18 @@ impl CryptoLib {
         }
 
         let message = match &args[0] {
-            StackItem::ByteString(msg) => msg.as_slice(),
+            StackItem::ByteString(msg) => msg.as_ref(),
             _ => return Err("verify_ecdsa: first argument must be ByteString".to_string()),
         };
 
         let signature = match &args[1] {
-            StackItem::ByteString(sig) => sig.as_slice(),
+            StackItem::ByteString(sig) => sig.as_ref(),
             _ => return Err("verify_ecdsa: second argument must be ByteString".to_string()),
         };
 
         let pubkey = if args.len() >= 3 {
             match &args[2] {
-                StackItem::ByteString(pk) => pk.as_slice(),
+                StackItem::ByteString(pk) => pk.as_ref(),
                 _ => return Err("verify_ecdsa: third argument must be ByteString".to_string()),
             }
         } else {
@@ -299,11 +302,109 @@ impl CryptoLib {
     }
 }
 
-/// Native contract registry
+/// LedgerContract native contract - the chain's current block
+///
+/// Reads the same [`TimeProvider`] as `System.Runtime.GetTime`, so both
+/// agree on the block a script runs in.
+pub struct LedgerContract {
+    time: Arc<dyn TimeProvider>,
+}
+
+impl LedgerContract {
+    pub fn new(time: Arc<dyn TimeProvider>) -> Self {
+        Self { time }
+    }
+}
+
+impl Default for LedgerContract {
+    /// Block 0 at the Unix epoch, as a new VM reports
+    fn default() -> Self {
+        Self::new(Arc::new(BlockContext::default()))
+    }
+}
+
+impl NativeContract for LedgerContract {
+    #[inline]
+    fn hash(&self) -> [u8; 20] {
+        [
+            0xda, 0x65, 0xb6, 0x00, 0xf7, 0x12, 0x4c, 0xe6, 0xc7, 0x99, 0x50, 0xc1, 0x77, 0x2a,
+            0x36, 0x40, 0x31, 0x04, 0xf2, 0xbe,
+        ]
+    }
+
+    #[inline]
+    fn invoke(&self, method: &str, _args: Vec<StackItem>) -> Result<StackItem, String> {
+        match method {
+            "currentIndex" => Ok(StackItem::Integer(self.time.current_block().index as i128)),
+            _ => Err(format!("Unknown method: {}", method)),
+        }
+    }
+}
+
+/// OracleContract native contract - responses to oracle requests
+///
+/// Serves the [`OracleResponses`] an execution was given instead of asking
+/// oracle nodes: `getResponse(url, filter)` returns the response bytes, and
+/// fails for a request with no response. A null filter is the empty one.
 #[derive(Default)]
+pub struct OracleContract {
+    responses: Arc<OracleResponses>,
+}
+
+impl OracleContract {
+    pub fn new(responses: Arc<OracleResponses>) -> Self {
+        Self { responses }
+    }
+
+    fn get_response(&self, args: Vec<StackItem>) -> Result<StackItem, String> {
+        let url = match args.first() {
+            Some(StackItem::ByteString(url)) => String::from_utf8_lossy(url).into_owned(),
+            _ => return Err("getResponse requires a url ByteString".to_string()),
+        };
+        let filter = match args.get(1) {
+            Some(StackItem::ByteString(filter)) => String::from_utf8_lossy(filter).into_owned(),
+            Some(StackItem::Null) | None => String::new(),
+            _ => return Err("getResponse: filter must be ByteString or Null".to_string()),
+        };
+        self.responses
+            .get(&url, &filter)
+            .map(|response| StackItem::ByteString(response.to_vec().into()))
+            .ok_or_else(|| format!("No oracle response for {} with filter {:?}", url, filter))
+    }
+}
+
+impl NativeContract for OracleContract {
+    #[inline]
+    fn hash(&self) -> [u8; 20] {
+        [
+            0xfe, 0x92, 0x4b, 0x7c, 0xfe, 0x89, 0xdd, 0xd2, 0x71, 0xab, 0xaf, 0x72, 0x10, 0xa8,
+            0x0a, 0x7e, 0x11, 0x17, 0x87, 0x58,
+        ]
+    }
+
+    #[inline]
+    fn invoke(&self, method: &str, args: Vec<StackItem>) -> Result<StackItem, String> {
+        match method {
+            "getResponse" => self.get_response(args),
+            _ => Err(format!("Unknown method: {}", method)),
+        }
+    }
+}
+
+/// Native contract registry
 pub struct NativeRegistry {
     stdlib: StdLib,
     cryptolib: CryptoLib,
+    ledger: LedgerContract,
+    oracle: OracleContract,
+    /// Whether StdLib and CryptoLib are served
+    host_only: bool,
+}
+
+impl Default for NativeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NativeRegistry {
@@ -312,9 +413,54 @@ impl NativeRegistry {
         Self {
             stdlib: StdLib::new(),
             cryptolib: CryptoLib::new(),
+            ledger: LedgerContract::default(),
+            oracle: OracleContract::default(),
+            host_only: true,
+        }
+    }
+
+    /// Registry whose Ledger reports the block of `time`
+    pub fn with_time_provider(time: Arc<dyn TimeProvider>) -> Self {
+        Self {
+            ledger: LedgerContract::new(time),
+            ..Self::new()
         }
     }
 
+    /// This registry with its Oracle serving `responses`
+    pub fn with_oracle_responses(self, responses: Arc<OracleResponses>) -> Self {
+        Self {
+            oracle: OracleContract::new(responses),
+            ..self
+        }
+    }
+
+    /// This registry without StdLib and CryptoLib, which the guest program
+    /// does not serve; calls to them fail as calls to unknown contracts
+    pub fn without_host_only(self) -> Self {
+        Self {
+            host_only: false,
+            ..self
+        }
+    }
+
+    /// Block the Ledger reports
+    pub fn time(&self) -> Arc<dyn TimeProvider> {
+        Arc::clone(&self.ledger.time)
+    }
+
+    /// Have the Ledger report the block of `time`
+    pub fn set_time(&mut self, time: Arc<dyn TimeProvider>) {
+        self.ledger = LedgerContract::new(time);
+    }
+
+    /// Whether `hash` is one of the registered natives
+    pub fn contains(&self, hash: &[u8; 20]) -> bool {
+        *hash == self.ledger.hash()
+            || *hash == self.oracle.hash()
+            || self.host_only && (*hash == self.stdlib.hash() || *hash == self.cryptolib.hash())
+    }
+
     #[inline]
     pub fn invoke(
         &self,
@@ -322,10 +468,16 @@ impl NativeRegistry {
         method: &str,
         args: Vec<StackItem>,
     ) -> Result<StackItem, String> {
-        if *hash == self.stdlib.hash() {
+        if !self.contains(hash) {
+            Err("Unknown native contract".to_string())
+        } else if *hash == self.stdlib.hash() {
             self.stdlib.invoke(method, args)
         } else if *hash == self.cryptolib.hash() {
             self.cryptolib.invoke(method, args)
+        } else if *hash == self.ledger.hash() {
+            self.ledger.invoke(method, args)
+        } else if *hash == self.oracle.hash() {
+            self.oracle.invoke(method, args)
         } else {
             Err("Unknown native contract".to_string())
         }
@@ -0,0 +1,290 @@
+//! Neo hash types and addresses
+//!
+//! [`UInt160`] and [`UInt256`] hold hashes little-endian, as they are on the
+//! stack, and print big-endian with a `0x` prefix, as Neo tools show them.
+//! A contract's script hash is the RIPEMD-160 of the SHA-256 of its script,
+//! and its N3 address the Base58Check encoding of [`ADDRESS_VERSION`]
+//! followed by the script hash.
+
+use ripemd::Ripemd160;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Version byte of Neo N3 addresses, which makes them start with `N`
+pub const ADDRESS_VERSION: u8 = 0x35;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Why a hash or address could not be parsed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("Invalid hex hash '{0}'")]
+    InvalidHash(String),
+    #[error("Invalid Base58 character '{0}'")]
+    InvalidCharacter(char),
+    #[error("Base58Check checksum mismatch")]
+    Checksum,
+    #[error("Address payload is {0} bytes, expected 21")]
+    Length(usize),
+    #[error("Address version is 0x{0:02x}, expected 0x35")]
+    Version(u8),
+}
+
+macro_rules! hash_type {
+    ($name:ident, $len:literal) => {
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            pub const ZERO: Self = Self([0; $len]);
+
+            /// Hash from its little-endian bytes
+            pub const fn new(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+
+            /// Little-endian bytes
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+
+            /// Hash from little-endian bytes, `None` for the wrong length
+            pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+                bytes.try_into().ok().map(Self)
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$name> for [u8; $len] {
+            fn from(hash: $name) -> Self {
+                hash.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("0x")?;
+                self.0.iter().rev().try_for_each(|b| write!(f, "{b:02x}"))
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({self})", stringify!($name))
+            }
+        }
+
+        /// Big-endian hex; the `0x` prefix is optional
+        impl FromStr for $name {
+            type Err = AddressError;
+
+            fn from_str(text: &str) -> Result<Self, AddressError> {
+                let invalid = || AddressError::InvalidHash(text.to_string());
+                let mut bytes =
+                    hex::decode(text.strip_prefix("0x").unwrap_or(text)).map_err(|_| invalid())?;
+                bytes.reverse();
+                Self::from_slice(&bytes).ok_or_else(invalid)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+hash_type!(UInt160, 20);
+hash_type!(UInt256, 32);
+
+impl UInt160 {
+    /// Script hash of `script`: RIPEMD-160 of its SHA-256
+    pub fn from_script(script: &[u8]) -> Self {
+        Self(Ripemd160::digest(Sha256::digest(script)).into())
+    }
+
+    /// N3 address of this script hash
+    pub fn to_address(&self) -> String {
+        let mut payload = Vec::with_capacity(21);
+        payload.push(ADDRESS_VERSION);
+        payload.extend_from_slice(&self.0);
+        base58check_encode(&payload)
+    }
+
+    /// Script hash of an N3 address
+    pub fn from_address(address: &str) -> Result<Self, AddressError> {
+        let payload = base58check_decode(address)?;
+        match payload.split_first() {
+            Some((&ADDRESS_VERSION, hash)) if hash.len() == 20 => {
+                Ok(Self::from_slice(hash).expect("20 bytes"))
+            }
+            Some((&version, hash)) if hash.len() == 20 => Err(AddressError::Version(version)),
+            _ => Err(AddressError::Length(payload.len())),
+        }
+    }
+}
+
+impl UInt256 {
+    /// Double SHA-256 of `data`, Neo's `Hash256`
+    pub fn hash(data: &[u8]) -> Self {
+        Self(Sha256::digest(Sha256::digest(data)).into())
+    }
+}
+
+/// Base58 with the Bitcoin alphabet; leading zero bytes become `1`s
+pub fn base58_encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    // Little-endian base-58 digits of the rest
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&d| BASE58_ALPHABET[d as usize] as char),
+        )
+        .collect()
+}
+
+/// Inverse of [`base58_encode`]
+pub fn base58_decode(text: &str) -> Result<Vec<u8>, AddressError> {
+    let zeros = text.chars().take_while(|&c| c == '1').count();
+    // Little-endian bytes of the rest
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len());
+    for c in text.chars().skip(zeros) {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or(AddressError::InvalidCharacter(c))? as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    Ok(std::iter::repeat_n(0, zeros)
+        .chain(bytes.into_iter().rev())
+        .collect())
+}
+
+/// Base58 of `payload` followed by the first 4 bytes of its double SHA-256
+pub fn base58check_encode(payload: &[u8]) -> String {
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&UInt256::hash(payload).0[..4]);
+    base58_encode(&data)
+}
+
+/// Payload of a Base58Check string, with the checksum verified
+pub fn base58check_decode(text: &str) -> Result<Vec<u8>, AddressError> {
+    let mut data = base58_decode(text)?;
+    if data.len() < 4 {
+        return Err(AddressError::Checksum);
+    }
+    let checksum = data.split_off(data.len() - 4);
+    if UInt256::hash(&data).0[..4] != checksum[..] {
+        return Err(AddressError::Checksum);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base58() {
+        assert_eq!(base58_encode(b"Hello World!"), "2NEpo7TZRRrLZSi2U");
+        assert_eq!(base58_encode(&[0, 0, 1]), "112");
+        assert_eq!(base58_decode("112").unwrap(), [0, 0, 1]);
+        assert_eq!(base58_decode("").unwrap(), Vec::<u8>::new());
+        assert_eq!(base58_decode("2NEpo7TZRRrLZSi2U").unwrap(), b"Hello World!");
+        assert_eq!(
+            base58_decode("0OIl"),
+            Err(AddressError::InvalidCharacter('0'))
+        );
+    }
+
+    #[test]
+    fn test_address_round_trip() {
+        // PUSH1, RET
+        let hash = UInt160::from_script(&[0x11, 0x40]);
+        assert_eq!(
+            hash.to_string(),
+            "0x7fe124b7e83f8a0e191b08851137c02e85660de8"
+        );
+        let address = hash.to_address();
+        assert_eq!(address, "Nh4x1LmHEMAEiiaeodvLoy5jN3HGAYPR3k");
+        assert_eq!(UInt160::from_address(&address), Ok(hash));
+
+        // Flip the last character to break the checksum
+        let mut broken = address.clone();
+        let last = broken.pop().unwrap();
+        broken.push(if last == '1' { '2' } else { '1' });
+        assert_eq!(UInt160::from_address(&broken), Err(AddressError::Checksum));
+
+        let mut payload = vec![0x17];
+        payload.extend_from_slice(hash.as_bytes());
+        assert_eq!(
+            UInt160::from_address(&base58check_encode(&payload)),
+            Err(AddressError::Version(0x17))
+        );
+        assert_eq!(
+            UInt160::from_address(&base58check_encode(&[ADDRESS_VERSION, 1, 2])),
+            Err(AddressError::Length(3))
+        );
+    }
+
+    #[test]
+    fn test_hash_display_is_big_endian() {
+        let mut bytes = [0u8; 20];
+        bytes[0] = 0xcf;
+        bytes[19] = 0xd2;
+        let hash = UInt160::new(bytes);
+        let text = "0xd2000000000000000000000000000000000000cf";
+        assert_eq!(hash.to_string(), text);
+        assert_eq!(text.parse::<UInt160>(), Ok(hash));
+        assert_eq!(text[2..].parse::<UInt160>(), Ok(hash));
+        assert!("0x1234".parse::<UInt160>().is_err());
+
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(json, format!("\"{text}\""));
+        assert_eq!(serde_json::from_str::<UInt160>(&json).unwrap(), hash);
+        assert_eq!(
+            format!("{:?}", UInt256::ZERO),
+            format!("UInt256(0x{})", "0".repeat(64))
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Oracle responses
+//!
+//! On Neo, a contract asks the Oracle native for a URL and a JSONPath filter
+//! and oracle nodes later answer with the filtered response. A zkVM cannot
+//! fetch anything, so the responses an execution may read are supplied up
+//! front as [`OracleResponses`], keyed by URL and filter, and served by the
+//! [`OracleContract`](crate::OracleContract) native.
+//!
+//! Proofs commit to the responses through [`OracleResponses::commitment`]:
+//!
+//! ```text
+//! commitment = SHA256("neo-zkvm/Oracle/v1" || count || entry...)
+//! entry      = url_len || url || filter_len || filter || SHA256(response)
+//! ```
+//!
+//! where the count and lengths are little-endian `u32`s and entries are in
+//! URL, then filter, order. A verifier holding the responses recomputes the
+//! commitment and so learns exactly which off-chain data the execution
+//! relied on.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Domain tag prefixed to the [`OracleResponses::commitment`] preimage
+pub const ORACLE_DOMAIN: &[u8] = b"neo-zkvm/Oracle/v1";
+
+/// One response, as serialized
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleResponse {
+    pub url: String,
+    /// JSONPath filter; empty for the unfiltered response
+    #[serde(default)]
+    pub filter: String,
+    #[serde(with = "crate::json::base64_bytes")]
+    pub response: Vec<u8>,
+}
+
+/// Responses to oracle requests, keyed by URL and filter
+///
+/// Serializes as a list of [`OracleResponse`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<OracleResponse>", into = "Vec<OracleResponse>")]
+pub struct OracleResponses {
+    responses: BTreeMap<(String, String), Vec<u8>>,
+}
+
+impl OracleResponses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the response to `url` with `filter`, returning the one it
+    /// replaces
+    pub fn insert(
+        &mut self,
+        url: impl Into<String>,
+        filter: impl Into<String>,
+        response: impl Into<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        self.responses
+            .insert((url.into(), filter.into()), response.into())
+    }
+
+    /// Response to `url` with `filter`
+    pub fn get(&self, url: &str, filter: &str) -> Option<&[u8]> {
+        self.responses
+            .get(&(url.to_string(), filter.to_string()))
+            .map(Vec::as_slice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// `(url, filter, response)` entries in commitment order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, &[u8])> {
+        self.responses
+            .iter()
+            .map(|((url, filter), response)| (url.as_str(), filter.as_str(), response.as_slice()))
+    }
+
+    /// Digest of the URLs, filters and response hashes, as specified in the
+    /// [module docs](self)
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(ORACLE_DOMAIN);
+        hasher.update((self.responses.len() as u32).to_le_bytes());
+        for (url, filter, response) in self.iter() {
+            hasher.update((url.len() as u32).to_le_bytes());
+            hasher.update(url);
+            hasher.update((filter.len() as u32).to_le_bytes());
+            hasher.update(filter);
+            hasher.update(Sha256::digest(response));
+        }
+        hasher.finalize().into()
+    }
+}
+
+impl From<Vec<OracleResponse>> for OracleResponses {
+    fn from(entries: Vec<OracleResponse>) -> Self {
+        let mut responses = Self::new();
+        for entry in entries {
+            responses.insert(entry.url, entry.filter, entry.response);
+        }
+        responses
+    }
+}
+
+impl From<OracleResponses> for Vec<OracleResponse> {
+    fn from(responses: OracleResponses) -> Self {
+        responses
+            .responses
+            .into_iter()
+            .map(|((url, filter), response)| OracleResponse {
+                url,
+                filter,
+                response,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_covers_every_entry() {
+        let mut responses = OracleResponses::new();
+        let empty = responses.commitment();
+        responses.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+        let one = responses.commitment();
+        assert_ne!(one, empty);
+
+        // Insertion order does not matter, contents do
+        let mut other = OracleResponses::new();
+        other.insert("https://api.example/time", "", b"0".to_vec());
+        other.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+        responses.insert("https://api.example/time", "", b"0".to_vec());
+        assert_eq!(responses.commitment(), other.commitment());
+
+        other.insert("https://api.example/price", "$.neo", b"13".to_vec());
+        assert_ne!(responses.commitment(), other.commitment());
+        assert_eq!(
+            other.get("https://api.example/price", "$.neo"),
+            Some(&b"13"[..])
+        );
+        assert_eq!(other.get("https://api.example/price", ""), None);
+    }
+
+    #[test]
+    fn test_json_is_a_list() {
+        let mut responses = OracleResponses::new();
+        responses.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+        let json = serde_json::to_value(&responses).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "url": "https://api.example/price",
+                "filter": "$.neo",
+                "response": "MTIuNQ=="
+            }])
+        );
+        let parsed: OracleResponses = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, responses);
+    }
+}
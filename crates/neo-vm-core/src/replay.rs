@@ -0,0 +1,222 @@
+//! Record and replay of nondeterministic inputs
+//!
+//! A script's result depends on more than its bytes and arguments: the time
+//! and random numbers the runtime reports, and the storage values it reads.
+//! [`NeoVM::record_replay`](crate::NeoVM::record_replay) logs each of these
+//! as the VM is fed them, and [`NeoVM::replay`](crate::NeoVM::replay) feeds
+//! a later run the logged values instead, so an execution reported from
+//! elsewhere can be reproduced exactly without its storage or clock.
+//!
+//! Storage is logged as the value a key held before the execution first
+//! touched it; later reads see the script's own writes. Those values are the
+//! whole pre-state the execution depended on, so [`ReplayLog::storage`],
+//! with the same `RuntimeConfig`, can serve as the storage of a
+//! `ProofInput` to reproduce the run in the guest program.
+
+use crate::storage::{make_key, StorageContext};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A value the VM was fed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    /// Returned by `System.Runtime.GetTime`
+    Time(u64),
+    /// Returned by `System.Runtime.GetRandom`
+    Random(i128),
+    /// Value of a full storage key (`script_hash || key`) before the
+    /// execution first touched it
+    Storage {
+        key: Vec<u8>,
+        value: Option<Vec<u8>>,
+    },
+}
+
+impl ReplayEvent {
+    /// Whether a replayed event stands in for `live`: same kind and, for
+    /// storage, same key
+    fn replaces(&self, live: &ReplayEvent) -> bool {
+        match (self, live) {
+            (ReplayEvent::Time(_), ReplayEvent::Time(_))
+            | (ReplayEvent::Random(_), ReplayEvent::Random(_)) => true,
+            (ReplayEvent::Storage { key, .. }, ReplayEvent::Storage { key: live, .. }) => {
+                key == live
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Nondeterministic values of one execution, in the order it was fed them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayLog {
+    pub events: Vec<ReplayEvent>,
+}
+
+impl ReplayLog {
+    /// Storage entries the execution found, by full key
+    ///
+    /// Keys that were absent are left out.
+    pub fn storage(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                ReplayEvent::Storage {
+                    key,
+                    value: Some(value),
+                } => Some((key.clone(), value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Recording or replaying state of a VM
+#[derive(Debug, Clone)]
+pub(crate) struct Replay {
+    log: ReplayLog,
+    /// Next event to replay, `None` while recording
+    position: Option<usize>,
+    /// Full storage keys touched so far
+    touched: BTreeSet<Vec<u8>>,
+}
+
+impl Replay {
+    pub(crate) fn record() -> Self {
+        Self {
+            log: ReplayLog::default(),
+            position: None,
+            touched: BTreeSet::new(),
+        }
+    }
+
+    pub(crate) fn play(log: ReplayLog) -> Self {
+        Self {
+            log,
+            position: Some(0),
+            touched: BTreeSet::new(),
+        }
+    }
+
+    pub(crate) fn log(&self) -> &ReplayLog {
+        &self.log
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.position.is_some()
+    }
+
+    /// The value to use in place of `live`: `live` itself when recording,
+    /// after logging it, or the next logged event when replaying
+    ///
+    /// `None` if the replayed execution asked for something else than the
+    /// logged one did at this point.
+    pub(crate) fn pass(&mut self, live: ReplayEvent) -> Option<ReplayEvent> {
+        match &mut self.position {
+            None => {
+                self.log.events.push(live.clone());
+                Some(live)
+            }
+            Some(position) => {
+                let event = self.log.events.get(*position)?;
+                if !event.replaces(&live) {
+                    return None;
+                }
+                *position += 1;
+                Some(event.clone())
+            }
+        }
+    }
+
+    /// Whether `key` is touched for the first time in this execution
+    pub(crate) fn first_touch(&mut self, context: &StorageContext, key: &[u8]) -> bool {
+        self.touched.insert(make_key(context, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::syscall;
+    use crate::{NeoVM, ScriptBuilder, StackItem, StorageBackend, VMError, VMState};
+
+    /// `GetRandom`, `Get(key)`, `Put(key, "new")`, `Get(key)`, `GetTime`
+    fn script(key: &[u8]) -> Vec<u8> {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETRANDOM)
+            .emit_push_bytes(key)
+            .emit_syscall(syscall::SYSTEM_STORAGE_GET)
+            .emit_push_bytes(b"new")
+            .emit_push_bytes(key)
+            .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+            .emit_push_bytes(key)
+            .emit_syscall(syscall::SYSTEM_STORAGE_GET)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETTIME);
+        builder.into_bytes()
+    }
+
+    fn run(vm: &mut NeoVM, script: Vec<u8>) {
+        vm.load_script(script).unwrap();
+        vm.run();
+    }
+
+    #[test]
+    fn test_replay_reproduces_execution() {
+        let mut recorded = NeoVM::new(10_000_000);
+        recorded.runtime.random_seed = [7; 32];
+        let context = StorageContext::default();
+        recorded.storage.put(&context, b"k", b"old");
+        recorded.record_replay();
+        run(&mut recorded, script(b"k"));
+        assert!(matches!(recorded.state, VMState::Halt));
+
+        let log = recorded.replay_log().unwrap().clone();
+        assert!(matches!(log.events[0], ReplayEvent::Random(_)));
+        // The second read sees the script's own write
+        assert_eq!(log.storage(), [(make_key(&context, b"k"), b"old".to_vec())]);
+        assert_eq!(log.events.last(), Some(&ReplayEvent::Time(0)));
+
+        // A different seed and no storage, but the same values fed in
+        let mut replayed = NeoVM::new(10_000_000);
+        replayed.replay(log);
+        run(&mut replayed, script(b"k"));
+        assert!(matches!(replayed.state, VMState::Halt));
+        assert_eq!(replayed.eval_stack, recorded.eval_stack);
+        assert_eq!(replayed.gas_consumed, recorded.gas_consumed);
+        assert_eq!(replayed.storage.write_set(), recorded.storage.write_set());
+        assert_eq!(
+            replayed.eval_stack[1],
+            StackItem::ByteString(b"old".to_vec().into())
+        );
+        assert_eq!(
+            replayed.eval_stack[2],
+            StackItem::ByteString(b"new".to_vec().into())
+        );
+    }
+
+    #[test]
+    fn test_replay_divergence_faults() {
+        let mut recorded = NeoVM::new(10_000_000);
+        recorded.record_replay();
+        run(&mut recorded, script(b"a"));
+        let log = recorded.replay_log().unwrap().clone();
+
+        let mut replayed = NeoVM::new(10_000_000);
+        replayed.replay(log.clone());
+        run(&mut replayed, script(b"b"));
+        assert!(matches!(replayed.state, VMState::Fault));
+        assert_eq!(
+            replayed.fault_info.map(|fault| fault.error),
+            Some(VMError::ReplayDiverged)
+        );
+
+        // Running past the end of the log
+        let mut truncated = log;
+        truncated.events.pop();
+        let mut replayed = NeoVM::new(10_000_000);
+        replayed.replay(truncated);
+        run(&mut replayed, script(b"a"));
+        assert!(matches!(replayed.state, VMState::Fault));
+    }
+}
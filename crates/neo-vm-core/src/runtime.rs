@@ -0,0 +1,224 @@
+//! Runtime environment
+//!
+//! What the `System.Runtime` syscalls report about the chain a script runs
+//! on. A zkVM has no node to ask, so the network, trigger and random seed
+//! come from the [`RuntimeConfig`] an execution is given, and proofs commit
+//! to it: a verifier sees which network a proof claims and which seed its
+//! random numbers were drawn from.
+//!
+//! # Time
+//!
+//! `System.Runtime.GetTime` returns the timestamp of the block the script
+//! runs in, and the Ledger native reports its index. Both read the VM's
+//! [`TimeProvider`]; the guest program takes them from the input's
+//! [`BlockContext`], which proofs commit to, so a time lock checked by a
+//! proven script holds for the block the proof claims.
+//!
+//! # Random numbers
+//!
+//! `System.Runtime.GetRandom` is deterministic, so lottery-style contracts
+//! can be proven and anyone can check the draw. The `i`th call in an
+//! execution, counting from zero, returns
+//!
+//! ```text
+//! digest = SHA256("neo-zkvm/GetRandom/v1" || network || random_seed || i)
+//! value  = u128_le(digest[0..16]) >> 1
+//! ```
+//!
+//! where `network` and `i` are little-endian `u32`s. The domain tag keeps
+//! the values apart from every other hash of the seed, and the network magic
+//! gives the same seed different draws on MainNet and TestNet. Shifting off
+//! the top bit keeps values non-negative. Proofs commit to the seed and the
+//! number of draws, so a verifier can recompute every value the script saw
+//! with [`RuntimeConfig::random`].
+//!
+//! The seed should be fixed before the script's outcome is known, such as a
+//! future block hash or a commit-reveal value, or the prover can pick a
+//! favourable one.
+
+use crate::StackItem;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Network magic of Neo N3 MainNet
+pub const MAINNET_MAGIC: u32 = 860_833_102;
+
+/// Network magic of Neo N3 TestNet
+pub const TESTNET_MAGIC: u32 = 894_710_606;
+
+/// Returned by `System.Runtime.GetPlatform`
+pub const PLATFORM: &str = "NEO";
+
+/// Domain tag prefixed to every `System.Runtime.GetRandom` hash
+pub const RANDOM_DOMAIN: &[u8] = b"neo-zkvm/GetRandom/v1";
+
+/// Longest event name `System.Runtime.Notify` accepts, as on Neo
+pub const MAX_EVENT_NAME: usize = 32;
+
+/// Why a script runs, returned by `System.Runtime.GetTrigger`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum TriggerType {
+    OnPersist,
+    PostPersist,
+    /// Checking a witness
+    Verification,
+    /// Running a transaction script
+    #[default]
+    Application,
+}
+
+impl TriggerType {
+    /// Neo's byte value
+    pub const fn id(self) -> u8 {
+        match self {
+            TriggerType::OnPersist => 0x01,
+            TriggerType::PostPersist => 0x02,
+            TriggerType::Verification => 0x20,
+            TriggerType::Application => 0x40,
+        }
+    }
+
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0x01 => Some(TriggerType::OnPersist),
+            0x02 => Some(TriggerType::PostPersist),
+            0x20 => Some(TriggerType::Verification),
+            0x40 => Some(TriggerType::Application),
+            _ => None,
+        }
+    }
+}
+
+impl From<TriggerType> for u8 {
+    fn from(trigger: TriggerType) -> u8 {
+        trigger.id()
+    }
+}
+
+impl TryFrom<u8> for TriggerType {
+    type Error = String;
+
+    fn try_from(id: u8) -> Result<Self, String> {
+        TriggerType::from_id(id).ok_or_else(|| format!("unknown trigger type {id:#04x}"))
+    }
+}
+
+/// Environment reported by the `System.Runtime` syscalls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    /// Network magic returned by `System.Runtime.GetNetwork`
+    pub network: u32,
+    pub trigger: TriggerType,
+    /// Seed `System.Runtime.GetRandom` draws from
+    #[serde(with = "crate::json::hex_hash")]
+    pub random_seed: [u8; 32],
+}
+
+/// An event sent with `System.Runtime.Notify`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationEvent {
+    /// Contract that sent the event, the script's Hash160 for a loaded script
+    #[serde(with = "crate::json::hex_hash")]
+    pub script_hash: [u8; 20],
+    pub event_name: String,
+    /// Array of event arguments
+    pub state: StackItem,
+}
+
+impl Default for RuntimeConfig {
+    /// An application script on MainNet with a zero seed
+    fn default() -> Self {
+        Self {
+            network: MAINNET_MAGIC,
+            trigger: TriggerType::Application,
+            random_seed: [0; 32],
+        }
+    }
+}
+
+impl RuntimeConfig {
+    /// Value of the `index`th `System.Runtime.GetRandom` call, counting
+    /// from zero, as specified in the [module docs](self)
+    pub fn random(&self, index: u32) -> i128 {
+        let mut hasher = Sha256::new();
+        hasher.update(RANDOM_DOMAIN);
+        hasher.update(self.network.to_le_bytes());
+        hasher.update(self.random_seed);
+        hasher.update(index.to_le_bytes());
+        let digest = hasher.finalize();
+        let value = u128::from_le_bytes(digest[..16].try_into().expect("16 bytes"));
+        (value >> 1) as i128
+    }
+}
+
+/// Block a script runs in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockContext {
+    /// Height of the block, reported by `Ledger.currentIndex`
+    pub index: u32,
+    /// Block time in milliseconds since the Unix epoch, reported by
+    /// `System.Runtime.GetTime`
+    pub timestamp: u64,
+}
+
+/// Source of the current block for `System.Runtime.GetTime` and the Ledger
+/// native
+///
+/// Proven executions always use the input's [`BlockContext`]; other
+/// providers are for hosts simulating a live chain.
+pub trait TimeProvider: Send + Sync {
+    /// Block scripts currently run in
+    fn current_block(&self) -> BlockContext;
+}
+
+impl TimeProvider for BlockContext {
+    fn current_block(&self) -> BlockContext {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_ids_round_trip() {
+        for trigger in [
+            TriggerType::OnPersist,
+            TriggerType::PostPersist,
+            TriggerType::Verification,
+            TriggerType::Application,
+        ] {
+            assert_eq!(TriggerType::from_id(trigger.id()), Some(trigger));
+        }
+        assert_eq!(TriggerType::from_id(0x41), None);
+    }
+
+    #[test]
+    fn test_random_depends_on_seed_and_index() {
+        let config = RuntimeConfig::default();
+        let other = RuntimeConfig {
+            random_seed: [1; 32],
+            ..config
+        };
+        assert!(config.random(0) >= 0);
+        assert_eq!(config.random(0), config.random(0));
+        assert_ne!(config.random(0), config.random(1));
+        assert_ne!(config.random(0), other.random(0));
+
+        let testnet = RuntimeConfig {
+            network: TESTNET_MAGIC,
+            ..config
+        };
+        assert_ne!(config.random(0), testnet.random(0));
+    }
+
+    #[test]
+    fn test_random_vectors() {
+        // MainNet, zero seed; also listed in docs/api-reference.md
+        let config = RuntimeConfig::default();
+        assert_eq!(config.random(0), 61269504908215047548563118693369197863);
+        assert_eq!(config.random(1), 70310567794312504906811165498659629388);
+    }
+}
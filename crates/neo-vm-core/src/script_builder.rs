@@ -0,0 +1,101 @@
+//! Script builder
+//!
+//! Emits Neo VM bytecode like the C# `ScriptBuilder`: integers and data are
+//! pushed with the shortest encoding that holds them.
+
+use crate::opcode::OpCode;
+
+/// Incrementally builds a script
+///
+/// ```rust
+/// use neo_vm_core::{OpCode, ScriptBuilder};
+///
+/// let script = ScriptBuilder::new()
+///     .emit_push_int(2)
+///     .emit_push_int(3)
+///     .emit(OpCode::ADD)
+///     .emit(OpCode::RET)
+///     .to_bytes();
+/// assert_eq!(script, vec![0x12, 0x13, 0x9E, 0x40]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScriptBuilder {
+    script: Vec<u8>,
+}
+
+impl ScriptBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emit an opcode without operand
+    pub fn emit(&mut self, op: OpCode) -> &mut Self {
+        self.script.push(op as u8);
+        self
+    }
+
+    /// Emit an opcode followed by its raw operand bytes
+    pub fn emit_with_operand(&mut self, op: OpCode, operand: &[u8]) -> &mut Self {
+        self.script.push(op as u8);
+        self.script.extend_from_slice(operand);
+        self
+    }
+
+    /// Push an integer: `PUSHM1`..`PUSH16` for small values, otherwise the
+    /// narrowest `PUSHINT*` holding it
+    pub fn emit_push_int(&mut self, value: i128) -> &mut Self {
+        if (-1..=16).contains(&value) {
+            self.script.push((OpCode::PUSH0 as i128 + value) as u8);
+            return self;
+        }
+        if let Ok(v) = i8::try_from(value) {
+            self.emit_with_operand(OpCode::PUSHINT8, &v.to_le_bytes())
+        } else if let Ok(v) = i16::try_from(value) {
+            self.emit_with_operand(OpCode::PUSHINT16, &v.to_le_bytes())
+        } else if let Ok(v) = i32::try_from(value) {
+            self.emit_with_operand(OpCode::PUSHINT32, &v.to_le_bytes())
+        } else if let Ok(v) = i64::try_from(value) {
+            self.emit_with_operand(OpCode::PUSHINT64, &v.to_le_bytes())
+        } else {
+            self.emit_with_operand(OpCode::PUSHINT128, &value.to_le_bytes())
+        }
+    }
+
+    /// Push a byte string with `PUSHDATA1`, `PUSHDATA2` or `PUSHDATA4`
+    pub fn emit_push_bytes(&mut self, data: &[u8]) -> &mut Self {
+        if let Ok(len) = u8::try_from(data.len()) {
+            self.emit_with_operand(OpCode::PUSHDATA1, &[len]);
+        } else if let Ok(len) = u16::try_from(data.len()) {
+            self.emit_with_operand(OpCode::PUSHDATA2, &len.to_le_bytes());
+        } else {
+            self.emit_with_operand(OpCode::PUSHDATA4, &(data.len() as u32).to_le_bytes());
+        }
+        self.script.extend_from_slice(data);
+        self
+    }
+
+    pub fn emit_push_null(&mut self) -> &mut Self {
+        self.emit(OpCode::PUSHNULL)
+    }
+
+    /// Emit `SYSCALL` with an interop id, e.g. from [`crate::engine::syscall`]
+    pub fn emit_syscall(&mut self, id: u32) -> &mut Self {
+        self.emit_with_operand(OpCode::SYSCALL, &id.to_le_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.script.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.script.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.script.clone()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.script
+    }
+}
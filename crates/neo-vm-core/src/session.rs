@@ -0,0 +1,137 @@
+//! Multi-execution sessions
+//!
+//! An [`ExecutionSession`] runs several scripts one after another against the
+//! same VM, the way a block executes a sequence of transactions: storage,
+//! logs and notifications carry over from one call to the next, while stacks,
+//! slots and gas start fresh for every call.
+
+use crate::engine::{NeoVM, VMError, VMState};
+use crate::runtime::NotificationEvent;
+use crate::stack_item::StackItem;
+use crate::storage::TrackedStorage;
+
+/// Outcome of one [`ExecutionSession::execute`] call
+#[derive(Debug)]
+pub struct ExecutionResult {
+    /// `VMState::Halt` or `VMState::Fault`
+    pub state: VMState,
+    /// Evaluation stack left by the script, bottom first
+    pub stack: Vec<StackItem>,
+    /// Gas consumed by this call alone
+    pub gas_consumed: u64,
+    /// Why the call faulted, if it did
+    pub error: Option<VMError>,
+}
+
+impl ExecutionResult {
+    #[inline]
+    pub fn is_halt(&self) -> bool {
+        matches!(self.state, VMState::Halt)
+    }
+}
+
+/// A VM whose state persists across script executions
+///
+/// Writes made by a call that faults are reverted, so later calls only see
+/// the effects of calls that halted.
+pub struct ExecutionSession {
+    vm: NeoVM,
+}
+
+impl ExecutionSession {
+    /// Create a session with empty storage and a per-call gas limit
+    pub fn new(gas_limit: u64) -> Self {
+        Self {
+            vm: NeoVM::new(gas_limit),
+        }
+    }
+
+    /// Create a session on top of existing storage
+    pub fn with_storage(gas_limit: u64, storage: TrackedStorage) -> Self {
+        let mut session = Self::new(gas_limit);
+        session.vm.storage = storage;
+        session
+    }
+
+    /// Run `script` to completion with `args` pushed onto a fresh stack
+    pub fn execute(&mut self, script: Vec<u8>, args: Vec<StackItem>) -> ExecutionResult {
+        self.reset();
+
+        if let Err(e) = self.load(script, args) {
+            self.vm.state = VMState::Fault;
+            return self.result(Some(e));
+        }
+
+        let mut error = None;
+        while !matches!(self.vm.state, VMState::Halt | VMState::Fault) {
+            if let Err(e) = self.vm.execute_next() {
+                self.vm.state = VMState::Fault;
+                error = Some(e);
+            }
+        }
+        self.result(error)
+    }
+
+    /// Storage as left by the calls so far
+    pub fn storage(&self) -> &TrackedStorage {
+        &self.vm.storage
+    }
+
+    /// Logs collected across all calls
+    pub fn logs(&self) -> &[String] {
+        &self.vm.logs
+    }
+
+    /// Notifications collected across all calls
+    pub fn notifications(&self) -> &[NotificationEvent] {
+        &self.vm.notifications
+    }
+
+    /// The underlying VM, e.g. to install a hook or enable tracing
+    pub fn vm_mut(&mut self) -> &mut NeoVM {
+        &mut self.vm
+    }
+
+    /// End the session, keeping its storage
+    pub fn into_storage(self) -> TrackedStorage {
+        self.vm.storage
+    }
+
+    /// Clear per-call state, keeping storage, logs and notifications
+    fn reset(&mut self) {
+        let vm = &mut self.vm;
+        vm.state = VMState::None;
+        vm.eval_stack.clear();
+        vm.invocation_stack.clear();
+        vm.local_slots.clear();
+        vm.argument_slots.clear();
+        vm.static_slots.clear();
+        vm.gas_consumed = 0;
+        vm.gas_breakdown = Default::default();
+        vm.steps = 0;
+        vm.fault_info = None;
+        vm.random_draws = 0;
+        vm.invocation_counters.clear();
+    }
+
+    fn load(&mut self, script: Vec<u8>, args: Vec<StackItem>) -> Result<(), VMError> {
+        self.vm.load_script(script)?;
+        for arg in args {
+            self.vm.push(arg)?;
+        }
+        Ok(())
+    }
+
+    fn result(&mut self, error: Option<VMError>) -> ExecutionResult {
+        // A call that faulted before running leaves its checkpoint open
+        while self.vm.storage.checkpoint_depth() > 0 {
+            self.vm.storage.revert();
+        }
+        ExecutionResult {
+            state: self.vm.state.clone(),
+            stack: std::mem::take(&mut self.vm.eval_stack),
+            gas_consumed: self.vm.gas_consumed,
+            error,
+        }
+    }
+}
@@ -1,17 +1,29 @@
 //! Neo VM Stack Item types
 
-use serde::{Deserialize, Serialize};
+use crate::json::decode_base64;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bytes::Bytes;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Stack item types in Neo VM (simplified for zkVM)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Human-readable formats use the tagged JSON described in [`crate::json`];
+/// binary formats use the plain enum layout.
+#[derive(Debug, Clone, PartialEq)]
 pub enum StackItem {
     Null,
     Boolean(bool),
     Integer(i128),
-    ByteString(Vec<u8>),
+    /// Immutable bytes; clones share the underlying storage, so copying a
+    /// byte string with `DUP` or a slot load does not copy its contents
+    ByteString(Bytes),
     Buffer(Vec<u8>),
     Array(Vec<StackItem>),
     Struct(Vec<StackItem>),
+    /// Entries in insertion order, as Neo's ordered dictionary keeps them;
+    /// `KEYS`, `VALUES` and the canonical encoding all follow it. Keys are
+    /// [primitive](StackItem::is_primitive) and unique
     Map(Vec<(StackItem, StackItem)>),
     Pointer(u32),
 }
@@ -23,20 +35,191 @@ pub enum StackItem {
 unsafe impl Send for StackItem {}
 unsafe impl Sync for StackItem {}
 
+/// Neo's `MaxComparableSize`: bytes of byte strings one `EQUAL` may compare
+pub const MAX_COMPARABLE_SIZE: usize = 65536;
+
+/// Type tags used by the canonical encoding (Neo `StackItemType` values)
+pub mod type_tag {
+    pub const ANY: u8 = 0x00;
+    pub const POINTER: u8 = 0x10;
+    pub const BOOLEAN: u8 = 0x20;
+    pub const INTEGER: u8 = 0x21;
+    pub const BYTE_STRING: u8 = 0x28;
+    pub const BUFFER: u8 = 0x30;
+    pub const ARRAY: u8 = 0x40;
+    pub const STRUCT: u8 = 0x41;
+    pub const MAP: u8 = 0x48;
+}
+
 impl StackItem {
+    /// Canonical binary encoding used for state hashes and commitments
+    ///
+    /// Every item starts with its type tag. Integers are 16 bytes little-endian,
+    /// booleans one byte, pointers 4 bytes little-endian; byte strings, buffers
+    /// and containers carry a `u32` little-endian length followed by their
+    /// bytes or recursively encoded elements (map entries as key then value).
+    /// Unlike `Debug` output or bincode, this layout is fixed and documented.
+    pub fn serialize_canonical(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_canonical(&mut out);
+        out
+    }
+
+    /// Append the canonical encoding of this item to `out`
+    pub fn write_canonical(&self, out: &mut Vec<u8>) {
+        match self {
+            StackItem::Null => out.push(type_tag::ANY),
+            StackItem::Boolean(b) => {
+                out.push(type_tag::BOOLEAN);
+                out.push(*b as u8);
+            }
+            StackItem::Integer(i) => {
+                out.push(type_tag::INTEGER);
+                out.extend_from_slice(&i.to_le_bytes());
+            }
+            StackItem::ByteString(b) => {
+                out.push(type_tag::BYTE_STRING);
+                write_bytes(out, b);
+            }
+            StackItem::Buffer(b) => {
+                out.push(type_tag::BUFFER);
+                write_bytes(out, b);
+            }
+            StackItem::Array(items) => {
+                out.push(type_tag::ARRAY);
+                write_items(out, items);
+            }
+            StackItem::Struct(items) => {
+                out.push(type_tag::STRUCT);
+                write_items(out, items);
+            }
+            StackItem::Map(entries) => {
+                out.push(type_tag::MAP);
+                out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+                for (key, value) in entries {
+                    key.write_canonical(out);
+                    value.write_canonical(out);
+                }
+            }
+            StackItem::Pointer(p) => {
+                out.push(type_tag::POINTER);
+                out.extend_from_slice(&p.to_le_bytes());
+            }
+        }
+    }
+
     #[inline]
     pub fn to_bool(&self) -> bool {
         match self {
             StackItem::Null => false,
             StackItem::Boolean(b) => *b,
             StackItem::Integer(i) => *i != 0,
-            StackItem::ByteString(b) | StackItem::Buffer(b) => b.iter().any(|&x| x != 0),
+            StackItem::ByteString(b) => b.iter().any(|&x| x != 0),
+            StackItem::Buffer(b) => b.iter().any(|&x| x != 0),
             StackItem::Array(a) | StackItem::Struct(a) => !a.is_empty(),
             StackItem::Map(m) => !m.is_empty(),
             _ => true,
         }
     }
 
+    /// Whether the item can key a map: a boolean, integer or byte string
+    ///
+    /// Compound keys would make lookups depend on contents that can change
+    /// after insertion, and buffers are mutable, so Neo rejects both.
+    #[inline]
+    pub fn is_primitive(&self) -> bool {
+        matches!(
+            self,
+            StackItem::Boolean(_) | StackItem::Integer(_) | StackItem::ByteString(_)
+        )
+    }
+
+    /// Neo's `EQUAL`
+    ///
+    /// Items of different types are never equal, so the integer 1 equals
+    /// neither `true` nor the byte string `0x01`; `NUMEQUAL` is the
+    /// comparison that converts. Byte strings compare by contents, at most
+    /// [`MAX_COMPARABLE_SIZE`] bytes over the whole comparison. Structs are
+    /// values and compare element by element. Arrays, maps and buffers
+    /// compare by reference in Neo; this VM copies them instead of sharing
+    /// them, so a copy stands in for the same reference and they compare by
+    /// contents as well.
+    ///
+    /// `None` when the comparison would visit more than `max_items` items or
+    /// compare too many bytes, which `EQUAL` turns into a fault.
+    pub fn equals(&self, other: &StackItem, max_items: usize) -> Option<bool> {
+        let mut budget = (max_items, MAX_COMPARABLE_SIZE);
+        self.equals_within(other, &mut budget)
+    }
+
+    /// [`StackItem::equals`] with the items and bytes left to compare
+    fn equals_within(&self, other: &StackItem, budget: &mut (usize, usize)) -> Option<bool> {
+        budget.0 = budget.0.checked_sub(1)?;
+        match (self, other) {
+            (StackItem::ByteString(a), StackItem::ByteString(b)) => {
+                budget.1 = budget.1.checked_sub(a.len().max(b.len()))?;
+                Some(a == b)
+            }
+            (StackItem::Struct(a), StackItem::Struct(b))
+            | (StackItem::Array(a), StackItem::Array(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                for (x, y) in a.iter().zip(b) {
+                    if !x.equals_within(y, budget)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            (StackItem::Map(a), StackItem::Map(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                for ((ka, va), (kb, vb)) in a.iter().zip(b) {
+                    if !ka.equals_within(kb, budget)? || !va.equals_within(vb, budget)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            _ => Some(self == other),
+        }
+    }
+
+    /// Copy of a struct as Neo makes it when the struct is stored into an
+    /// array, struct or map
+    ///
+    /// Structs nested in the struct are copied too and count against
+    /// `max_items` with their elements; `None` when there are more. Other
+    /// items are returned as they are.
+    pub fn clone_struct(&self, max_items: usize) -> Option<StackItem> {
+        fn count(items: &[StackItem], budget: &mut usize) -> Option<()> {
+            for item in items {
+                *budget = budget.checked_sub(1)?;
+                if let StackItem::Struct(inner) = item {
+                    count(inner, budget)?;
+                }
+            }
+            Some(())
+        }
+        if let StackItem::Struct(items) = self {
+            let mut budget = max_items.checked_sub(1)?;
+            count(items, &mut budget)?;
+        }
+        Some(self.clone())
+    }
+
+    /// Contents of a byte string or buffer
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            StackItem::ByteString(b) => Some(b),
+            StackItem::Buffer(b) => Some(b),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn to_integer(&self) -> Option<i128> {
         match self {
@@ -46,3 +229,169 @@ impl StackItem {
         }
     }
 }
+
+/// Binary layout, as the enum would derive it
+#[derive(Serialize)]
+#[serde(rename = "StackItem")]
+enum BinaryRef<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i128),
+    ByteString(&'a Bytes),
+    Buffer(&'a Vec<u8>),
+    Array(&'a Vec<StackItem>),
+    Struct(&'a Vec<StackItem>),
+    Map(&'a Vec<(StackItem, StackItem)>),
+    Pointer(u32),
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "StackItem")]
+enum Binary {
+    Null,
+    Boolean(bool),
+    Integer(i128),
+    ByteString(Bytes),
+    Buffer(Vec<u8>),
+    Array(Vec<StackItem>),
+    Struct(Vec<StackItem>),
+    Map(Vec<(StackItem, StackItem)>),
+    Pointer(u32),
+}
+
+/// Tagged JSON layout, see [`crate::json`]
+#[derive(Serialize)]
+#[serde(tag = "type", content = "value")]
+enum JsonRef<'a> {
+    Any,
+    Boolean(bool),
+    Integer(String),
+    ByteString(String),
+    Buffer(String),
+    Array(&'a [StackItem]),
+    Struct(&'a [StackItem]),
+    Map(Vec<JsonEntryRef<'a>>),
+    Pointer(u32),
+}
+
+#[derive(Serialize)]
+struct JsonEntryRef<'a> {
+    key: &'a StackItem,
+    value: &'a StackItem,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "value")]
+enum Json {
+    Any,
+    Boolean(bool),
+    Integer(JsonInteger),
+    ByteString(String),
+    Buffer(String),
+    Array(Vec<StackItem>),
+    Struct(Vec<StackItem>),
+    Map(Vec<JsonEntry>),
+    Pointer(u32),
+}
+
+/// Integers are written as strings but also read from JSON numbers
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonInteger {
+    Text(String),
+    Number(i64),
+}
+
+#[derive(Deserialize)]
+struct JsonEntry {
+    key: StackItem,
+    value: StackItem,
+}
+
+impl Serialize for StackItem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return match self {
+                StackItem::Null => BinaryRef::Null,
+                StackItem::Boolean(b) => BinaryRef::Boolean(*b),
+                StackItem::Integer(i) => BinaryRef::Integer(*i),
+                StackItem::ByteString(b) => BinaryRef::ByteString(b),
+                StackItem::Buffer(b) => BinaryRef::Buffer(b),
+                StackItem::Array(items) => BinaryRef::Array(items),
+                StackItem::Struct(items) => BinaryRef::Struct(items),
+                StackItem::Map(entries) => BinaryRef::Map(entries),
+                StackItem::Pointer(p) => BinaryRef::Pointer(*p),
+            }
+            .serialize(serializer);
+        }
+        match self {
+            StackItem::Null => JsonRef::Any,
+            StackItem::Boolean(b) => JsonRef::Boolean(*b),
+            StackItem::Integer(i) => JsonRef::Integer(i.to_string()),
+            StackItem::ByteString(b) => JsonRef::ByteString(BASE64.encode(b)),
+            StackItem::Buffer(b) => JsonRef::Buffer(BASE64.encode(b)),
+            StackItem::Array(items) => JsonRef::Array(items),
+            StackItem::Struct(items) => JsonRef::Struct(items),
+            StackItem::Map(entries) => JsonRef::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| JsonEntryRef { key, value })
+                    .collect(),
+            ),
+            StackItem::Pointer(p) => JsonRef::Pointer(*p),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StackItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Ok(match Binary::deserialize(deserializer)? {
+                Binary::Null => StackItem::Null,
+                Binary::Boolean(b) => StackItem::Boolean(b),
+                Binary::Integer(i) => StackItem::Integer(i),
+                Binary::ByteString(b) => StackItem::ByteString(b),
+                Binary::Buffer(b) => StackItem::Buffer(b),
+                Binary::Array(items) => StackItem::Array(items),
+                Binary::Struct(items) => StackItem::Struct(items),
+                Binary::Map(entries) => StackItem::Map(entries),
+                Binary::Pointer(p) => StackItem::Pointer(p),
+            });
+        }
+        Ok(match Json::deserialize(deserializer)? {
+            Json::Any => StackItem::Null,
+            Json::Boolean(b) => StackItem::Boolean(b),
+            Json::Integer(JsonInteger::Text(text)) => StackItem::Integer(
+                text.parse()
+                    .map_err(|_| de::Error::custom(format!("invalid integer '{text}'")))?,
+            ),
+            Json::Integer(JsonInteger::Number(n)) => StackItem::Integer(n.into()),
+            Json::ByteString(text) => StackItem::ByteString(decode_base64::<D>(&text)?.into()),
+            Json::Buffer(text) => StackItem::Buffer(decode_base64::<D>(&text)?),
+            Json::Array(items) => StackItem::Array(items),
+            Json::Struct(items) => StackItem::Struct(items),
+            Json::Map(entries) => StackItem::Map(
+                entries
+                    .into_iter()
+                    .map(|entry| (entry.key, entry.value))
+                    .collect(),
+            ),
+            Json::Pointer(p) => StackItem::Pointer(p),
+        })
+    }
+}
+
+#[inline]
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+#[inline]
+fn write_items(out: &mut Vec<u8>, items: &[StackItem]) {
+    out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+    for item in items {
+        item.write_canonical(out);
+    }
+}
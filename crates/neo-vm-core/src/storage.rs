@@ -4,6 +4,25 @@
 
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
+#[cfg(feature = "persistent")]
+use std::path::Path;
+use thiserror::Error;
+
+/// Storage backend errors
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+    #[error("Invalid storage fixture: {0}")]
+    InvalidFixture(String),
+}
+
+#[cfg(feature = "persistent")]
+impl From<sled::Error> for StorageError {
+    fn from(e: sled::Error) -> Self {
+        StorageError::Backend(e.to_string())
+    }
+}
 
 /// Storage context for a contract
 #[derive(Debug, Clone, Default)]
@@ -22,6 +41,14 @@ pub trait StorageBackend {
     fn find(&self, context: &StorageContext, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)>;
 }
 
+/// Backend key for `key` in `context`: script hash followed by the key
+#[inline]
+pub(crate) fn make_key(context: &StorageContext, key: &[u8]) -> Vec<u8> {
+    let mut full_key = context.script_hash.to_vec();
+    full_key.extend_from_slice(key);
+    full_key
+}
+
 /// In-memory storage implementation
 #[derive(Debug, Clone, Default)]
 pub struct MemoryStorage {
@@ -34,11 +61,55 @@ impl MemoryStorage {
         Self::default()
     }
 
-    #[inline]
-    fn make_key(context: &StorageContext, key: &[u8]) -> Vec<u8> {
-        let mut full_key = context.script_hash.to_vec();
-        full_key.extend_from_slice(key);
-        full_key
+    /// Load storage from a JSON fixture
+    ///
+    /// The fixture maps hex script hashes to objects of hex key/value pairs:
+    /// `{"<script hash>": {"<key>": "<value>"}}`. A `0x` prefix is accepted.
+    pub fn from_json(json: &str) -> Result<Self, StorageError> {
+        let fixture: BTreeMap<String, BTreeMap<String, String>> =
+            serde_json::from_str(json).map_err(|e| StorageError::InvalidFixture(e.to_string()))?;
+
+        let decode = |s: &str| {
+            hex::decode(s.trim_start_matches("0x"))
+                .map_err(|e| StorageError::InvalidFixture(format!("'{}': {}", s, e)))
+        };
+
+        let mut storage = Self::new();
+        for (script_hash, entries) in &fixture {
+            let context = StorageContext {
+                script_hash: decode(script_hash)?.try_into().map_err(|_| {
+                    StorageError::InvalidFixture(format!(
+                        "script hash '{}' is not 20 bytes",
+                        script_hash
+                    ))
+                })?,
+                read_only: false,
+            };
+            for (key, value) in entries {
+                storage.put(&context, &decode(key)?, &decode(value)?);
+            }
+        }
+        Ok(storage)
+    }
+
+    /// Export storage as a JSON fixture readable by [`MemoryStorage::from_json`]
+    pub fn to_json(&self) -> String {
+        let mut fixture = serde_json::Map::new();
+        for (full_key, value) in &self.data {
+            let (script_hash, key) = full_key.split_at(20);
+            let entries = fixture
+                .entry(hex::encode(script_hash))
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+            if let serde_json::Value::Object(entries) = entries {
+                entries.insert(hex::encode(key), hex::encode(value).into());
+            }
+        }
+        format!("{:#}", serde_json::Value::Object(fixture))
+    }
+
+    /// All entries as full backend keys (script hash followed by the key)
+    pub fn into_entries(self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.data.into_iter().collect()
     }
 
     /// Compute Merkle root of storage
@@ -95,9 +166,18 @@ impl MemoryStorage {
     }
 }
 
+impl FromIterator<(Vec<u8>, Vec<u8>)> for MemoryStorage {
+    /// Build storage from full backend keys (script hash followed by the key)
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(iter: I) -> Self {
+        Self {
+            data: iter.into_iter().collect(),
+        }
+    }
+}
+
 impl StorageBackend for MemoryStorage {
     fn get(&self, context: &StorageContext, key: &[u8]) -> Option<Vec<u8>> {
-        let full_key = Self::make_key(context, key);
+        let full_key = make_key(context, key);
         self.data.get(&full_key).cloned()
     }
 
@@ -105,7 +185,7 @@ impl StorageBackend for MemoryStorage {
         if context.read_only {
             return;
         }
-        let full_key = Self::make_key(context, key);
+        let full_key = make_key(context, key);
         self.data.insert(full_key, value.to_vec());
     }
 
@@ -113,12 +193,12 @@ impl StorageBackend for MemoryStorage {
         if context.read_only {
             return;
         }
-        let full_key = Self::make_key(context, key);
+        let full_key = make_key(context, key);
         self.data.remove(&full_key);
     }
 
     fn find(&self, context: &StorageContext, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
-        let full_prefix = Self::make_key(context, prefix);
+        let full_prefix = make_key(context, prefix);
         self.data
             .range(full_prefix.clone()..)
             .take_while(|(k, _)| k.starts_with(&full_prefix))
@@ -130,6 +210,109 @@ impl StorageBackend for MemoryStorage {
     }
 }
 
+/// Durable storage backed by a sled database
+///
+/// Writes are staged in memory and only reach disk on [`commit`](Self::commit);
+/// [`rollback`](Self::rollback) discards them. Read errors from the database are
+/// treated as missing keys, since `StorageBackend` reads are infallible.
+#[cfg(feature = "persistent")]
+pub struct PersistentStorage {
+    db: sled::Db,
+    /// Uncommitted writes; `None` marks a deletion
+    pending: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+#[cfg(feature = "persistent")]
+impl PersistentStorage {
+    /// Open (or create) the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Ok(Self {
+            db: sled::open(path)?,
+            pending: BTreeMap::new(),
+        })
+    }
+
+    /// Open a database that is deleted when dropped
+    pub fn temporary() -> Result<Self, StorageError> {
+        Ok(Self {
+            db: sled::Config::new().temporary(true).open()?,
+            pending: BTreeMap::new(),
+        })
+    }
+
+    /// Atomically write all pending changes and flush them to disk
+    pub fn commit(&mut self) -> Result<(), StorageError> {
+        let mut batch = sled::Batch::default();
+        for (key, value) in &self.pending {
+            match value {
+                Some(value) => batch.insert(key.as_slice(), value.as_slice()),
+                None => batch.remove(key.as_slice()),
+            }
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Discard all changes since the last commit
+    pub fn rollback(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Whether there are uncommitted changes
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+#[cfg(feature = "persistent")]
+impl StorageBackend for PersistentStorage {
+    fn get(&self, context: &StorageContext, key: &[u8]) -> Option<Vec<u8>> {
+        let full_key = make_key(context, key);
+        match self.pending.get(&full_key) {
+            Some(value) => value.clone(),
+            None => self.db.get(&full_key).ok().flatten().map(|v| v.to_vec()),
+        }
+    }
+
+    fn put(&mut self, context: &StorageContext, key: &[u8], value: &[u8]) {
+        if context.read_only {
+            return;
+        }
+        self.pending
+            .insert(make_key(context, key), Some(value.to_vec()));
+    }
+
+    fn delete(&mut self, context: &StorageContext, key: &[u8]) {
+        if context.read_only {
+            return;
+        }
+        self.pending.insert(make_key(context, key), None);
+    }
+
+    fn find(&self, context: &StorageContext, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let full_prefix = make_key(context, prefix);
+        let mut merged: BTreeMap<Vec<u8>, Option<Vec<u8>>> = self
+            .db
+            .scan_prefix(&full_prefix)
+            .filter_map(Result::ok)
+            .map(|(k, v)| (k.to_vec(), Some(v.to_vec())))
+            .collect();
+        for (k, v) in self
+            .pending
+            .range(full_prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&full_prefix))
+        {
+            merged.insert(k.clone(), v.clone());
+        }
+        merged
+            .into_iter()
+            .filter_map(|(k, v)| Some((k[context.script_hash.len()..].to_vec(), v?)))
+            .collect()
+    }
+}
+
 /// Storage proof for ZK verification
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StorageProof {
@@ -191,10 +374,16 @@ pub struct StorageChange {
 }
 
 /// Tracked storage with change log
+///
+/// The change log doubles as an undo journal: [`checkpoint`](Self::checkpoint)
+/// marks a position in it, and [`revert`](Self::revert) undoes every change
+/// made since the most recent mark.
 #[derive(Debug, Clone, Default)]
 pub struct TrackedStorage {
     inner: MemoryStorage,
     changes: Vec<StorageChange>,
+    /// Change log lengths at each open checkpoint
+    checkpoints: Vec<usize>,
 }
 
 impl TrackedStorage {
@@ -206,9 +395,85 @@ impl TrackedStorage {
         &self.changes
     }
 
+    /// Current contents, without the change log
+    pub fn inner(&self) -> &MemoryStorage {
+        &self.inner
+    }
+
     pub fn merkle_root(&self) -> [u8; 32] {
         self.inner.merkle_root()
     }
+
+    /// Open a checkpoint that can later be committed or reverted
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(self.changes.len());
+    }
+
+    /// Close the most recent checkpoint, keeping its changes
+    ///
+    /// The changes become part of the enclosing checkpoint, if any.
+    pub fn commit(&mut self) {
+        self.checkpoints.pop();
+    }
+
+    /// Close the most recent checkpoint, undoing every change made since it
+    pub fn revert(&mut self) {
+        let Some(mark) = self.checkpoints.pop() else {
+            return;
+        };
+        for change in self.changes.drain(mark..).rev() {
+            let context = StorageContext {
+                script_hash: change.script_hash,
+                read_only: false,
+            };
+            match change.old_value {
+                Some(value) => self.inner.put(&context, &change.key, &value),
+                None => self.inner.delete(&context, &change.key),
+            }
+        }
+    }
+
+    /// Set the value `key` held before execution, outside the change log
+    pub(crate) fn preload(&mut self, context: &StorageContext, key: &[u8], value: Option<&[u8]>) {
+        match value {
+            Some(value) => self.inner.put(context, key, value),
+            None => self.inner.delete(context, key),
+        }
+    }
+
+    /// Number of open checkpoints
+    pub fn checkpoint_depth(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Net effect of the change log, sorted by full backend key
+    ///
+    /// Each entry holds the final value of a key, or `None` if it was
+    /// deleted. Keys whose final value equals their original one are omitted.
+    pub fn write_set(&self) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+        let mut net = BTreeMap::new();
+        for change in &self.changes {
+            let mut full_key = change.script_hash.to_vec();
+            full_key.extend_from_slice(&change.key);
+            net.entry(full_key)
+                .and_modify(|(_, new)| *new = &change.new_value)
+                .or_insert((&change.old_value, &change.new_value));
+        }
+        net.into_iter()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(key, (_, new))| (key, new.clone()))
+            .collect()
+    }
+}
+
+impl From<MemoryStorage> for TrackedStorage {
+    /// Track changes on top of existing contents
+    fn from(inner: MemoryStorage) -> Self {
+        Self {
+            inner,
+            ..Self::default()
+        }
+    }
 }
 
 impl StorageBackend for TrackedStorage {
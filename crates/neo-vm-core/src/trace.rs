@@ -0,0 +1,442 @@
+//! Execution tracing
+//!
+//! Every traced step extends a rolling SHA-256 hash chain held in
+//! [`ExecutionTrace`], so the full trace is committed to without having to keep
+//! it in memory. Where the steps themselves go is decided by a [`TraceSink`]:
+//! by default they are buffered in [`ExecutionTrace::steps`], but long runs can
+//! stream them to a file with [`FileTraceSink`] or drop them entirely with
+//! [`HashChainSink`].
+//!
+//! [`ExecutionTrace::diff`] finds the first step where two traces part, and
+//! [`verify_trace`] re-executes a script and checks it reproduces a recorded
+//! trace step for step, a cheap check of a claimed execution that needs no
+//! proof, and a way to find where the host stops matching a trace recorded
+//! elsewhere.
+
+use crate::engine::{NeoVM, VMState};
+use crate::stack_item::StackItem;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Execution trace step for proof generation
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TraceStep {
+    pub ip: usize,
+    pub opcode: u8,
+    pub stack_hash: [u8; 32],
+    pub gas_consumed: u64,
+}
+
+impl TraceStep {
+    /// Size of the binary encoding produced by [`TraceStep::to_bytes`]
+    pub const ENCODED_SIZE: usize = 8 + 1 + 32 + 8;
+
+    /// Fixed-size binary encoding: ip (u64 LE), opcode, stack hash, gas (u64 LE)
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_SIZE] {
+        let mut out = [0u8; Self::ENCODED_SIZE];
+        out[..8].copy_from_slice(&(self.ip as u64).to_le_bytes());
+        out[8] = self.opcode;
+        out[9..41].copy_from_slice(&self.stack_hash);
+        out[41..].copy_from_slice(&self.gas_consumed.to_le_bytes());
+        out
+    }
+
+    /// Decode a step from its binary encoding
+    pub fn from_bytes(bytes: &[u8; Self::ENCODED_SIZE]) -> Self {
+        let mut ip = [0u8; 8];
+        ip.copy_from_slice(&bytes[..8]);
+        let mut stack_hash = [0u8; 32];
+        stack_hash.copy_from_slice(&bytes[9..41]);
+        let mut gas = [0u8; 8];
+        gas.copy_from_slice(&bytes[41..]);
+        Self {
+            ip: u64::from_le_bytes(ip) as usize,
+            opcode: bytes[8],
+            stack_hash,
+            gas_consumed: u64::from_le_bytes(gas),
+        }
+    }
+}
+
+/// Full execution trace
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionTrace {
+    /// Buffered steps; only populated when no [`TraceSink`] is installed
+    pub steps: Vec<TraceStep>,
+    pub initial_state_hash: [u8; 32],
+    pub final_state_hash: [u8; 32],
+    /// Number of steps recorded, regardless of where they were sent
+    #[serde(default)]
+    pub step_count: u64,
+    /// Rolling hash chain over all recorded steps, seeded with the initial state hash
+    #[serde(default)]
+    pub rolling_hash: [u8; 32],
+}
+
+impl ExecutionTrace {
+    /// Reset the trace and seed the hash chain with the initial state
+    pub fn start(&mut self, initial_state_hash: [u8; 32]) {
+        *self = Self {
+            initial_state_hash,
+            rolling_hash: initial_state_hash,
+            ..Self::default()
+        };
+    }
+
+    /// Extend the hash chain with `step`
+    #[inline]
+    pub fn chain(&mut self, step: &TraceStep) {
+        self.rolling_hash = chain_hash(&self.rolling_hash, step);
+        self.step_count += 1;
+    }
+
+    /// First point where this trace and `other` diverge
+    ///
+    /// Steps match while they run the same opcode on the same stack and gas,
+    /// so two scripts that compute the same way compare equal even where
+    /// their offsets differ. Traces whose steps went to a [`TraceSink`] can
+    /// only be compared through their rolling hashes.
+    pub fn diff(&self, other: &ExecutionTrace) -> TraceDiff {
+        let same =
+            |a: &TraceStep, b: &TraceStep| a.opcode == b.opcode && a.stack_hash == b.stack_hash;
+        let buffered = |trace: &ExecutionTrace| trace.steps.len() as u64 == trace.step_count;
+        if buffered(self) && buffered(other) {
+            let (left, right) = (&self.steps, &other.steps);
+            let divergence = (0..left.len().max(right.len())).find(
+                |&i| !matches!((left.get(i), right.get(i)), (Some(a), Some(b)) if same(a, b)),
+            );
+            if let Some(index) = divergence {
+                return TraceDiff::Step {
+                    index,
+                    left: left.get(index).cloned(),
+                    right: right.get(index).cloned(),
+                };
+            }
+        } else if (self.step_count, self.rolling_hash) != (other.step_count, other.rolling_hash) {
+            return TraceDiff::Unbuffered;
+        }
+        if self.final_state_hash != other.final_state_hash {
+            return TraceDiff::FinalState {
+                left: self.final_state_hash,
+                right: other.final_state_hash,
+            };
+        }
+        TraceDiff::Identical
+    }
+}
+
+/// Result of [`ExecutionTrace::diff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceDiff {
+    Identical,
+    /// The first step that differs, `None` on the side whose trace ended
+    Step {
+        index: usize,
+        left: Option<TraceStep>,
+        right: Option<TraceStep>,
+    },
+    /// Every step matches, but the executions ended in different states;
+    /// a zero hash is an execution that did not halt
+    FinalState {
+        left: [u8; 32],
+        right: [u8; 32],
+    },
+    /// The hash chains differ, and a side without buffered steps leaves no
+    /// way to tell where
+    Unbuffered,
+}
+
+/// Next link of the rolling hash chain: `SHA256(previous || step)`
+#[inline]
+pub fn chain_hash(previous: &[u8; 32], step: &TraceStep) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    hasher.update(step.to_bytes());
+    hasher.finalize().into()
+}
+
+/// Destination for recorded trace steps
+pub trait TraceSink: Send {
+    /// Record a single step
+    fn record(&mut self, step: &TraceStep);
+
+    /// Flush buffered steps, reporting any error hit while recording
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps every step in memory
+impl TraceSink for Vec<TraceStep> {
+    #[inline]
+    fn record(&mut self, step: &TraceStep) {
+        self.push(step.clone());
+    }
+}
+
+/// Discards steps, leaving only the rolling hash in [`ExecutionTrace`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashChainSink;
+
+impl TraceSink for HashChainSink {
+    #[inline]
+    fn record(&mut self, _step: &TraceStep) {}
+}
+
+/// Streams steps to a file as fixed-size [`TraceStep::to_bytes`] records
+pub struct FileTraceSink {
+    writer: BufWriter<File>,
+    error: Option<io::Error>,
+}
+
+impl FileTraceSink {
+    /// Create (or truncate) the trace file at `path`
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            error: None,
+        })
+    }
+
+    /// Read back all steps from a trace file
+    pub fn read_steps(path: impl AsRef<Path>) -> io::Result<Vec<TraceStep>> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut steps = Vec::new();
+        let mut buf = [0u8; TraceStep::ENCODED_SIZE];
+        loop {
+            match reader.read_exact(&mut buf) {
+                Ok(()) => steps.push(TraceStep::from_bytes(&buf)),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(steps)
+    }
+}
+
+impl TraceSink for FileTraceSink {
+    fn record(&mut self, step: &TraceStep) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.writer.write_all(&step.to_bytes()) {
+            self.error = Some(e);
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()
+    }
+}
+
+/// Re-execute `script` on `arguments` and check it produces `trace`
+///
+/// The replay runs on a fresh [`NeoVM`] with unlimited gas; use
+/// [`verify_trace_with`] for executions that had storage, a gas schedule or
+/// a runtime configured. The trace must have been recorded the same way:
+/// script loaded, arguments pushed, then tracing enabled. Every step's ip,
+/// opcode, stack hash and gas must match, as must the rolling hash and, for
+/// an execution that halted, the final state hash. Traces kept with
+/// [`HashChainSink`] are checked through their rolling hash alone.
+///
+/// Gas limits are not part of a trace, so a trace that ends without halting,
+/// out of gas or on a fault, verifies as long as its steps match.
+pub fn verify_trace(script: &[u8], arguments: &[StackItem], trace: &ExecutionTrace) -> bool {
+    verify_trace_with(NeoVM::new(u64::MAX), script, arguments, trace)
+}
+
+/// [`verify_trace`] on `vm`, set up like the VM that recorded `trace`
+pub fn verify_trace_with(
+    mut vm: NeoVM,
+    script: &[u8],
+    arguments: &[StackItem],
+    trace: &ExecutionTrace,
+) -> bool {
+    let buffered = !trace.steps.is_empty();
+    if buffered && trace.steps.len() as u64 != trace.step_count {
+        return false;
+    }
+    if vm.load_script(script.to_vec()).is_err() {
+        return false;
+    }
+    vm.eval_stack.extend(arguments.iter().cloned());
+    vm.trace_sink = None;
+    if buffered {
+        vm.enable_tracing();
+    } else {
+        vm.enable_tracing_with(Box::new(HashChainSink));
+    }
+
+    // A halted execution ends with one more call that records no step
+    let halted = trace.final_state_hash != [0; 32];
+    while !matches!(vm.state, VMState::Halt | VMState::Fault) {
+        if vm.trace.step_count == trace.step_count && !halted {
+            break;
+        }
+        let result = vm.execute_next();
+        if vm.trace.step_count > trace.step_count {
+            return false;
+        }
+        // Stop at the first step that differs
+        let index = vm.trace.steps.len().wrapping_sub(1);
+        if buffered
+            && vm
+                .trace
+                .steps
+                .last()
+                .is_some_and(|step| trace.steps.get(index) != Some(step))
+        {
+            return false;
+        }
+        if result.is_err() || matches!(vm.state, VMState::Break) {
+            break;
+        }
+    }
+
+    vm.trace.step_count == trace.step_count
+        && vm.trace.initial_state_hash == trace.initial_state_hash
+        && vm.trace.rolling_hash == trace.rolling_hash
+        && vm.trace.final_state_hash == trace.final_state_hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(ip: usize) -> TraceStep {
+        TraceStep {
+            ip,
+            opcode: 0x11,
+            stack_hash: [ip as u8; 32],
+            gas_consumed: ip as u64 * 2,
+        }
+    }
+
+    #[test]
+    fn test_step_encoding_roundtrip() {
+        let original = step(42);
+        assert_eq!(TraceStep::from_bytes(&original.to_bytes()), original);
+    }
+
+    #[test]
+    fn test_rolling_hash_depends_on_order() {
+        let mut a = ExecutionTrace::default();
+        a.chain(&step(0));
+        a.chain(&step(1));
+
+        let mut b = ExecutionTrace::default();
+        b.chain(&step(1));
+        b.chain(&step(0));
+
+        assert_eq!(a.step_count, 2);
+        assert_ne!(a.rolling_hash, b.rolling_hash);
+    }
+
+    /// Trace of `script` run on `arguments`, recorded the way
+    /// [`verify_trace`] replays it
+    fn record(script: &[u8], arguments: &[StackItem], gas_limit: u64) -> ExecutionTrace {
+        let mut vm = NeoVM::new(gas_limit);
+        vm.load_script(script.to_vec()).unwrap();
+        vm.eval_stack.extend(arguments.iter().cloned());
+        vm.enable_tracing();
+        vm.run();
+        vm.trace
+    }
+
+    #[test]
+    fn test_verify_trace() {
+        // PUSH2, ADD, RET
+        let script = [0x12, 0x9E, 0x40];
+        let arguments = [StackItem::Integer(3)];
+        let trace = record(&script, &arguments, 1_000_000);
+        assert_eq!(trace.step_count, 3);
+        assert!(verify_trace(&script, &arguments, &trace));
+        assert!(!verify_trace(&script, &[StackItem::Integer(4)], &trace));
+        assert!(!verify_trace(&[0x13, 0x9E, 0x40], &arguments, &trace));
+
+        let mut tampered = trace.clone();
+        tampered.steps[1].gas_consumed += 1;
+        assert!(!verify_trace(&script, &arguments, &tampered));
+
+        let mut truncated = trace.clone();
+        truncated.steps.pop();
+        truncated.step_count -= 1;
+        assert!(!verify_trace(&script, &arguments, &truncated));
+
+        // Only the hash chain is left when steps went to a sink
+        let mut chained = trace.clone();
+        chained.steps.clear();
+        assert!(verify_trace(&script, &arguments, &chained));
+        chained.rolling_hash[0] ^= 1;
+        assert!(!verify_trace(&script, &arguments, &chained));
+    }
+
+    #[test]
+    fn test_verify_trace_without_halt() {
+        // PUSH1, PUSH0, DIV faults; the out-of-gas run stops after PUSH1
+        let script = [0x11, 0x10, 0xA1, 0x40];
+        let faulted = record(&script, &[], 1_000_000);
+        assert_eq!(faulted.step_count, 3);
+        assert!(verify_trace(&script, &[], &faulted));
+
+        let out_of_gas = record(&script, &[], 1);
+        assert_eq!(out_of_gas.step_count, 1);
+        assert!(verify_trace(&script, &[], &out_of_gas));
+    }
+
+    #[test]
+    fn test_diff() {
+        // PUSH2, PUSH3, ADD, RET against PUSH2, PUSH4, ADD, RET
+        let a = record(&[0x12, 0x13, 0x9E, 0x40], &[], 1_000_000);
+        let b = record(&[0x12, 0x14, 0x9E, 0x40], &[], 1_000_000);
+        assert_eq!(a.diff(&a), TraceDiff::Identical);
+        let TraceDiff::Step { index, left, right } = a.diff(&b) else {
+            panic!("traces should diverge at a step");
+        };
+        assert_eq!(index, 1);
+        let (left, right) = (left.unwrap(), right.unwrap());
+        assert_eq!((left.ip, left.opcode), (1, 0x13));
+        assert_eq!((right.ip, right.opcode), (1, 0x14));
+
+        // A trace that stopped early ends first
+        let short = record(&[0x12, 0x13, 0x9E, 0x40], &[], 2);
+        assert!(matches!(
+            a.diff(&short),
+            TraceDiff::Step {
+                index: 2,
+                left: Some(_),
+                right: None
+            }
+        ));
+
+        let mut tampered = a.clone();
+        tampered.final_state_hash = [1; 32];
+        assert!(matches!(a.diff(&tampered), TraceDiff::FinalState { .. }));
+
+        let mut chained = b.clone();
+        chained.steps.clear();
+        assert_eq!(a.diff(&chained), TraceDiff::Unbuffered);
+        let mut same_chain = a.clone();
+        same_chain.steps.clear();
+        assert_eq!(a.diff(&same_chain), TraceDiff::Identical);
+    }
+
+    #[test]
+    fn test_file_sink_roundtrip() {
+        let path = std::env::temp_dir().join(format!("neo-trace-{}.bin", std::process::id()));
+        let mut sink = FileTraceSink::create(&path).unwrap();
+        for ip in 0..3 {
+            sink.record(&step(ip));
+        }
+        sink.flush().unwrap();
+
+        let steps = FileTraceSink::read_steps(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(steps, vec![step(0), step(1), step(2)]);
+    }
+}
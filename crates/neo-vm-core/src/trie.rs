@@ -0,0 +1,487 @@
+//! Merkle Patricia trie storage
+//!
+//! A hexary Patricia trie over `script_hash || key`, hashed with SHA-256. The
+//! root hash commits to the whole storage state, and [`TrieProof`]s show that
+//! a key holds a value (inclusion) or is absent (exclusion) against a root.
+
+use crate::storage::{make_key, StorageBackend, StorageContext};
+use sha2::{Digest, Sha256};
+
+/// Root hash of an empty trie
+pub const EMPTY_ROOT: [u8; 32] = [0u8; 32];
+
+const TAG_LEAF: u8 = 0x00;
+const TAG_EXTENSION: u8 = 0x01;
+const TAG_BRANCH: u8 = 0x02;
+
+/// Trie node; paths are stored as nibbles, one per byte
+#[derive(Debug, Clone, Default)]
+enum Node {
+    #[default]
+    Empty,
+    Leaf {
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        path: Vec<u8>,
+        child: Box<Node>,
+    },
+    Branch {
+        children: Box<[Node; 16]>,
+        value: Option<Vec<u8>>,
+    },
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0F]).collect()
+}
+
+fn from_nibbles(nibbles: &[u8]) -> Vec<u8> {
+    nibbles.chunks(2).map(|c| (c[0] << 4) | c[1]).collect()
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+fn write_path(out: &mut Vec<u8>, path: &[u8]) {
+    out.extend_from_slice(&(path.len() as u16).to_le_bytes());
+    out.extend_from_slice(path);
+}
+
+fn write_value(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+fn new_branch() -> Node {
+    Node::Branch {
+        children: Box::default(),
+        value: None,
+    }
+}
+
+/// Prefix `node` with `path`, merging into its own path where possible
+fn with_prefix(path: Vec<u8>, node: Node) -> Node {
+    if path.is_empty() {
+        return node;
+    }
+    match node {
+        Node::Empty => Node::Empty,
+        Node::Leaf { path: rest, value } => Node::Leaf {
+            path: [path, rest].concat(),
+            value,
+        },
+        Node::Extension { path: rest, child } => Node::Extension {
+            path: [path, rest].concat(),
+            child,
+        },
+        branch => Node::Extension {
+            path,
+            child: Box::new(branch),
+        },
+    }
+}
+
+impl Node {
+    fn is_empty(&self) -> bool {
+        matches!(self, Node::Empty)
+    }
+
+    /// Canonical encoding, with children referenced by hash
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Node::Empty => {}
+            Node::Leaf { path, value } => {
+                out.push(TAG_LEAF);
+                write_path(&mut out, path);
+                write_value(&mut out, value);
+            }
+            Node::Extension { path, child } => {
+                out.push(TAG_EXTENSION);
+                write_path(&mut out, path);
+                out.extend_from_slice(&child.hash());
+            }
+            Node::Branch { children, value } => {
+                out.push(TAG_BRANCH);
+                for child in children.iter() {
+                    out.extend_from_slice(&child.hash());
+                }
+                match value {
+                    Some(value) => {
+                        out.push(1);
+                        write_value(&mut out, value);
+                    }
+                    None => out.push(0),
+                }
+            }
+        }
+        out
+    }
+
+    fn hash(&self) -> [u8; 32] {
+        match self {
+            Node::Empty => EMPTY_ROOT,
+            node => Sha256::digest(node.encode()).into(),
+        }
+    }
+
+    fn get(&self, path: &[u8]) -> Option<&[u8]> {
+        match self {
+            Node::Empty => None,
+            Node::Leaf { path: leaf, value } => {
+                (leaf.as_slice() == path).then_some(value.as_slice())
+            }
+            Node::Extension { path: ext, child } => path
+                .strip_prefix(ext.as_slice())
+                .and_then(|rest| child.get(rest)),
+            Node::Branch { children, value } => match path.split_first() {
+                None => value.as_deref(),
+                Some((&nibble, rest)) => children[nibble as usize].get(rest),
+            },
+        }
+    }
+
+    fn insert(self, path: &[u8], value: Vec<u8>) -> Node {
+        match self {
+            Node::Empty => Node::Leaf {
+                path: path.to_vec(),
+                value,
+            },
+            Node::Leaf {
+                path: leaf,
+                value: old,
+            } => {
+                if leaf == path {
+                    return Node::Leaf { path: leaf, value };
+                }
+                let c = common_prefix(&leaf, path);
+                let branch = new_branch()
+                    .insert(&leaf[c..], old)
+                    .insert(&path[c..], value);
+                with_prefix(path[..c].to_vec(), branch)
+            }
+            Node::Extension { path: ext, child } => {
+                let c = common_prefix(&ext, path);
+                if c == ext.len() {
+                    return Node::Extension {
+                        child: Box::new(child.insert(&path[c..], value)),
+                        path: ext,
+                    };
+                }
+                let mut branch = new_branch();
+                if let Node::Branch { children, .. } = &mut branch {
+                    children[ext[c] as usize] = with_prefix(ext[c + 1..].to_vec(), *child);
+                }
+                with_prefix(path[..c].to_vec(), branch.insert(&path[c..], value))
+            }
+            Node::Branch {
+                mut children,
+                value: own,
+            } => match path.split_first() {
+                None => Node::Branch {
+                    children,
+                    value: Some(value),
+                },
+                Some((&nibble, rest)) => {
+                    let slot = &mut children[nibble as usize];
+                    *slot = std::mem::take(slot).insert(rest, value);
+                    Node::Branch {
+                        children,
+                        value: own,
+                    }
+                }
+            },
+        }
+    }
+
+    fn delete(self, path: &[u8]) -> Node {
+        match self {
+            Node::Empty => Node::Empty,
+            Node::Leaf { path: leaf, value } => {
+                if leaf == path {
+                    Node::Empty
+                } else {
+                    Node::Leaf { path: leaf, value }
+                }
+            }
+            Node::Extension { path: ext, child } => match path.strip_prefix(ext.as_slice()) {
+                Some(rest) => with_prefix(ext, child.delete(rest)),
+                None => Node::Extension { path: ext, child },
+            },
+            Node::Branch {
+                mut children,
+                mut value,
+            } => {
+                match path.split_first() {
+                    None => value = None,
+                    Some((&nibble, rest)) => {
+                        let slot = &mut children[nibble as usize];
+                        *slot = std::mem::take(slot).delete(rest);
+                    }
+                }
+                Self::normalize_branch(children, value)
+            }
+        }
+    }
+
+    /// Collapse a branch left with a single entry
+    fn normalize_branch(mut children: Box<[Node; 16]>, value: Option<Vec<u8>>) -> Node {
+        let mut occupied = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| !child.is_empty())
+            .map(|(i, _)| i);
+        let first = occupied.next();
+        let single = first.is_some() && occupied.next().is_none();
+
+        match (first, value) {
+            (None, None) => Node::Empty,
+            (None, Some(value)) => Node::Leaf {
+                path: Vec::new(),
+                value,
+            },
+            (Some(i), None) if single => {
+                with_prefix(vec![i as u8], std::mem::take(&mut children[i]))
+            }
+            (_, value) => Node::Branch { children, value },
+        }
+    }
+
+    /// Append all `(nibble path, value)` pairs below this node in key order
+    fn collect(&self, prefix: &mut Vec<u8>, out: &mut Vec<(Vec<u8>, Vec<u8>)>) {
+        match self {
+            Node::Empty => {}
+            Node::Leaf { path, value } => {
+                out.push(([prefix.as_slice(), path].concat(), value.clone()));
+            }
+            Node::Extension { path, child } => {
+                let len = prefix.len();
+                prefix.extend_from_slice(path);
+                child.collect(prefix, out);
+                prefix.truncate(len);
+            }
+            Node::Branch { children, value } => {
+                if let Some(value) = value {
+                    out.push((prefix.clone(), value.clone()));
+                }
+                for (i, child) in children.iter().enumerate() {
+                    prefix.push(i as u8);
+                    child.collect(prefix, out);
+                    prefix.pop();
+                }
+            }
+        }
+    }
+
+    /// Encodings of the nodes visited while looking up `path`
+    fn proof_path(&self, path: &[u8], out: &mut Vec<Vec<u8>>) {
+        if self.is_empty() {
+            return;
+        }
+        out.push(self.encode());
+        match self {
+            Node::Extension { path: ext, child } => {
+                if let Some(rest) = path.strip_prefix(ext.as_slice()) {
+                    child.proof_path(rest, out);
+                }
+            }
+            Node::Branch { children, .. } => {
+                if let Some((&nibble, rest)) = path.split_first() {
+                    children[nibble as usize].proof_path(rest, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Inclusion or exclusion proof for a single key
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrieProof {
+    /// Full trie key (`script_hash || key`)
+    pub key: Vec<u8>,
+    /// Value held by the key, or `None` for an exclusion proof
+    pub value: Option<Vec<u8>>,
+    /// Encoded nodes from the root along the key's path
+    pub nodes: Vec<Vec<u8>>,
+}
+
+/// Reads from an encoded proof node
+struct NodeReader<'a>(&'a [u8]);
+
+impl<'a> NodeReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn path(&mut self) -> Option<&'a [u8]> {
+        let len = self.take(2)?;
+        self.take(u16::from_le_bytes([len[0], len[1]]) as usize)
+    }
+
+    fn value(&mut self) -> Option<&'a [u8]> {
+        let len = self.take(4)?;
+        self.take(u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+    }
+
+    fn hash(&mut self) -> Option<[u8; 32]> {
+        self.take(32)?.try_into().ok()
+    }
+}
+
+impl TrieProof {
+    /// Check the proof against `root`
+    pub fn verify(&self, root: [u8; 32]) -> bool {
+        self.walk(root).unwrap_or(false)
+    }
+
+    fn walk(&self, root: [u8; 32]) -> Option<bool> {
+        let path = to_nibbles(&self.key);
+        let mut remaining = path.as_slice();
+        let mut expected = root;
+
+        for encoded in &self.nodes {
+            if expected == EMPTY_ROOT || <[u8; 32]>::from(Sha256::digest(encoded)) != expected {
+                return Some(false);
+            }
+            let mut reader = NodeReader(encoded);
+            let found = match *reader.take(1)?.first()? {
+                TAG_LEAF => {
+                    let leaf = reader.path()?;
+                    let value = reader.value()?;
+                    (leaf == remaining).then_some(value)
+                }
+                TAG_EXTENSION => {
+                    let ext = reader.path()?;
+                    let child = reader.hash()?;
+                    match remaining.strip_prefix(ext) {
+                        Some(rest) => {
+                            remaining = rest;
+                            expected = child;
+                            continue;
+                        }
+                        None => None,
+                    }
+                }
+                TAG_BRANCH => {
+                    let mut children = [[0u8; 32]; 16];
+                    for child in children.iter_mut() {
+                        *child = reader.hash()?;
+                    }
+                    let value = match reader.take(1)?[0] {
+                        0 => None,
+                        _ => Some(reader.value()?),
+                    };
+                    match remaining.split_first() {
+                        None => value,
+                        Some((&nibble, rest)) => {
+                            expected = *children.get(nibble as usize)?;
+                            remaining = rest;
+                            if expected == EMPTY_ROOT {
+                                None
+                            } else {
+                                continue;
+                            }
+                        }
+                    }
+                }
+                _ => return Some(false),
+            };
+            return Some(found == self.value.as_deref());
+        }
+
+        // Only an empty trie ends without reaching a terminal node
+        Some(self.nodes.is_empty() && root == EMPTY_ROOT && self.value.is_none())
+    }
+}
+
+/// Storage backed by a Merkle Patricia trie
+#[derive(Debug, Clone, Default)]
+pub struct TrieStorage {
+    root: Node,
+}
+
+impl TrieStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Root hash committing to every key and value
+    pub fn root_hash(&self) -> [u8; 32] {
+        self.root.hash()
+    }
+
+    /// Build an inclusion or exclusion proof for `key`
+    pub fn prove(&self, context: &StorageContext, key: &[u8]) -> TrieProof {
+        self.prove_full_key(make_key(context, key))
+    }
+
+    /// [`prove`](Self::prove) for a full backend key (script hash followed
+    /// by the key)
+    pub fn prove_full_key(&self, full_key: Vec<u8>) -> TrieProof {
+        let path = to_nibbles(&full_key);
+        let mut nodes = Vec::new();
+        self.root.proof_path(&path, &mut nodes);
+        TrieProof {
+            value: self.root.get(&path).map(<[u8]>::to_vec),
+            key: full_key,
+            nodes,
+        }
+    }
+}
+
+impl FromIterator<(Vec<u8>, Vec<u8>)> for TrieStorage {
+    /// Build a trie from full backend keys (script hash followed by the key)
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        for (key, value) in iter {
+            trie.root = std::mem::take(&mut trie.root).insert(&to_nibbles(&key), value);
+        }
+        trie
+    }
+}
+
+impl StorageBackend for TrieStorage {
+    fn get(&self, context: &StorageContext, key: &[u8]) -> Option<Vec<u8>> {
+        let path = to_nibbles(&make_key(context, key));
+        self.root.get(&path).map(<[u8]>::to_vec)
+    }
+
+    fn put(&mut self, context: &StorageContext, key: &[u8], value: &[u8]) {
+        if context.read_only {
+            return;
+        }
+        let path = to_nibbles(&make_key(context, key));
+        self.root = std::mem::take(&mut self.root).insert(&path, value.to_vec());
+    }
+
+    fn delete(&mut self, context: &StorageContext, key: &[u8]) {
+        if context.read_only {
+            return;
+        }
+        let path = to_nibbles(&make_key(context, key));
+        self.root = std::mem::take(&mut self.root).delete(&path);
+    }
+
+    fn find(&self, context: &StorageContext, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let full_prefix = to_nibbles(&make_key(context, prefix));
+        let mut entries = Vec::new();
+        self.root.collect(&mut Vec::new(), &mut entries);
+        entries
+            .into_iter()
+            .filter(|(path, _)| path.starts_with(&full_prefix))
+            .map(|(path, value)| {
+                let key = from_nibbles(&path);
+                (key[context.script_hash.len()..].to_vec(), value)
+            })
+            .collect()
+    }
+}
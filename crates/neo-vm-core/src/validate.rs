@@ -0,0 +1,169 @@
+//! Static script validation
+//!
+//! [`validate_script`] finds structural problems without executing a script:
+//! truncated operands, jumps into the middle of an instruction, calls to
+//! method tokens that do not exist and slot initialisations the engine would
+//! reject. A script that passes may still fault at run time, but one that
+//! fails is malformed and not worth executing or proving.
+
+use crate::engine::MAX_SCRIPT_SIZE;
+use crate::instruction::Instruction;
+use thiserror::Error;
+
+/// A structural problem found by [`validate_script`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    #[error("Script is {size} bytes, limit is {limit}")]
+    ScriptTooLarge { size: usize, limit: usize },
+    /// The instruction's operand runs past the end of the script
+    ///
+    /// Nothing after this offset can be decoded, so it is the last issue
+    /// reported for the instruction stream.
+    #[error("Truncated operand for opcode 0x{opcode:02X} at 0x{offset:04X}")]
+    TruncatedOperand { offset: usize, opcode: u8 },
+    /// A jump, call, `PUSHA` or try offset that does not land on an
+    /// instruction boundary or the end of the script
+    #[error("Jump at 0x{offset:04X} targets {target}, which is not an instruction boundary")]
+    InvalidJumpTarget { offset: usize, target: isize },
+    /// `CALLT` with a token index outside the script's method tokens
+    ///
+    /// Scripts run by this VM carry no method token table, so every `CALLT`
+    /// is reported.
+    #[error("CALLT at 0x{offset:04X} uses method token {token}, which does not exist")]
+    MissingCallToken { offset: usize, token: u16 },
+    /// `INITSLOT` with neither locals nor arguments, or `INITSSLOT` with no
+    /// static fields
+    #[error("Slot initialisation at 0x{offset:04X} declares no slots")]
+    EmptySlots { offset: usize },
+}
+
+/// Check `script` for structural problems, returning them in script order
+///
+/// An empty result means the script is well formed.
+pub fn validate_script(script: &[u8]) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    if script.len() > MAX_SCRIPT_SIZE {
+        issues.push(ValidationIssue::ScriptTooLarge {
+            size: script.len(),
+            limit: MAX_SCRIPT_SIZE,
+        });
+    }
+
+    // Instruction starts, plus the end of the script where jumps may halt
+    let mut boundaries = vec![false; script.len() + 1];
+    boundaries[script.len()] = true;
+    let mut instructions = Vec::new();
+    let mut truncated = None;
+    let mut offset = 0;
+    while offset < script.len() {
+        match Instruction::decode(script, offset) {
+            Ok(instruction) => {
+                boundaries[offset] = true;
+                instructions.push(instruction);
+                offset = instruction.next_offset();
+            }
+            Err(_) => {
+                truncated = Some(ValidationIssue::TruncatedOperand {
+                    offset,
+                    opcode: script[offset],
+                });
+                break;
+            }
+        }
+    }
+
+    for instruction in &instructions {
+        let offset = instruction.offset;
+        let operand = instruction.operand(script);
+        match instruction.opcode {
+            // JMP..JMPLE_L, CALL, CALL_L, ENDTRY, ENDTRY_L, PUSHA
+            0x22..=0x35 | 0x3D | 0x3E | 0x0A => {
+                check_target(&mut issues, &boundaries, offset, relative(operand), false);
+            }
+            // TRY, TRY_L: catch and finally offsets, zero when absent
+            0x3B | 0x3C => {
+                let (catch, finally) = operand.split_at(operand.len() / 2);
+                check_target(&mut issues, &boundaries, offset, relative(catch), true);
+                check_target(&mut issues, &boundaries, offset, relative(finally), true);
+            }
+            // CALLT
+            0x37 => issues.push(ValidationIssue::MissingCallToken {
+                offset,
+                token: u16::from_le_bytes([operand[0], operand[1]]),
+            }),
+            // INITSSLOT, INITSLOT
+            0x56 | 0x57 if operand.iter().all(|&count| count == 0) => {
+                issues.push(ValidationIssue::EmptySlots { offset });
+            }
+            _ => {}
+        }
+    }
+
+    issues.extend(truncated);
+    issues
+}
+
+/// Signed little-endian offset from a 1- or 4-byte operand
+pub(crate) fn relative(operand: &[u8]) -> isize {
+    match *operand {
+        [byte] => byte as i8 as isize,
+        [a, b, c, d] => i32::from_le_bytes([a, b, c, d]) as isize,
+        _ => 0,
+    }
+}
+
+fn check_target(
+    issues: &mut Vec<ValidationIssue>,
+    boundaries: &[bool],
+    offset: usize,
+    delta: isize,
+    zero_is_absent: bool,
+) {
+    if zero_is_absent && delta == 0 {
+        return;
+    }
+    let target = offset as isize + delta;
+    let valid = usize::try_from(target)
+        .ok()
+        .and_then(|target| boundaries.get(target))
+        .is_some_and(|&boundary| boundary);
+    if !valid {
+        issues.push(ValidationIssue::InvalidJumpTarget { offset, target });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_script_has_no_issues() {
+        // PUSH1, JMPIF +3, PUSH2, RET
+        assert!(validate_script(&[0x11, 0x24, 0x03, 0x12, 0x40]).is_empty());
+        assert!(validate_script(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_reports_each_issue() {
+        // JMP into its own operand, CALLT 1, INITSLOT 0 0, PUSHDATA1 past the end
+        let script = [0x22, 0x01, 0x37, 0x01, 0x00, 0x57, 0x00, 0x00, 0x0C, 0x05];
+        assert_eq!(
+            validate_script(&script),
+            vec![
+                ValidationIssue::InvalidJumpTarget {
+                    offset: 0,
+                    target: 1
+                },
+                ValidationIssue::MissingCallToken {
+                    offset: 2,
+                    token: 1
+                },
+                ValidationIssue::EmptySlots { offset: 5 },
+                ValidationIssue::TruncatedOperand {
+                    offset: 8,
+                    opcode: 0x0C
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! Watchpoints
+//!
+//! A watchpoint pauses execution when a value changes. Add one with
+//! [`NeoVM::add_watch`](crate::NeoVM::add_watch): after every instruction the
+//! VM compares each watched value with the one it saw last and, on a change,
+//! enters `VMState::Break` before the next instruction and records the
+//! change in [`NeoVM::watch_hit`](crate::NeoVM::watch_hit). Resuming
+//! continues from there, like a break requested by the execution hook.
+
+use crate::stack_item::StackItem;
+
+/// Value a watchpoint observes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchKind {
+    /// Storage key of the loaded script, in
+    /// [`NeoVM::storage_context`](crate::NeoVM::storage_context)
+    StorageKey(Vec<u8>),
+    /// Evaluation stack item at this position, counted from the bottom
+    StackValue(usize),
+}
+
+/// A watched value and the value last seen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watchpoint {
+    pub kind: WatchKind,
+    /// `None` while the key is unset or the stack is shallower
+    pub value: Option<StackItem>,
+}
+
+/// Change that triggered a watchpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchHit {
+    pub kind: WatchKind,
+    pub old: Option<StackItem>,
+    pub new: Option<StackItem>,
+    /// Offset of the instruction that made the change
+    pub ip: usize,
+}
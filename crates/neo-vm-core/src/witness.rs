@@ -0,0 +1,216 @@
+//! Witness verification
+//!
+//! A Neo witness is a pair of scripts. The invocation script only pushes
+//! data, typically signatures; the verification script, whose hash is the
+//! account's script hash, then runs on those items and must leave exactly
+//! `true`. `System.Crypto.CheckSig` and `CheckMultisig` check signatures
+//! against the signed payload, the "script container" of the verification.
+
+use crate::contract::CallFlags;
+use crate::engine::{NeoVM, VMState};
+use crate::instruction::decode_script;
+use crate::opcode::OpCode;
+use crate::stack_item::StackItem;
+use thiserror::Error;
+
+/// Gas a witness may use, Neo's 1.5 GAS verification limit
+pub const MAX_VERIFICATION_GAS: u64 = 150_000_000;
+
+/// Why a witness did not verify
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WitnessError {
+    #[error("Verification script is empty")]
+    EmptyVerification,
+    #[error("Invocation script may only push data")]
+    NotPushOnly,
+    #[error("Invocation script faulted: {0}")]
+    InvocationFault(String),
+    #[error("Verification script faulted: {0}")]
+    VerificationFault(String),
+    #[error("Verification script did not leave exactly true")]
+    Rejected,
+}
+
+/// Verify a witness over `signed_payload` within [`MAX_VERIFICATION_GAS`]
+///
+/// Returns the gas both scripts used.
+pub fn verify_witness(
+    verification_script: &[u8],
+    invocation_script: &[u8],
+    signed_payload: &[u8],
+) -> Result<u64, WitnessError> {
+    verify_witness_with_gas(
+        verification_script,
+        invocation_script,
+        signed_payload,
+        MAX_VERIFICATION_GAS,
+    )
+}
+
+/// [`verify_witness`] with `gas_limit` shared by both scripts
+pub fn verify_witness_with_gas(
+    verification_script: &[u8],
+    invocation_script: &[u8],
+    signed_payload: &[u8],
+    gas_limit: u64,
+) -> Result<u64, WitnessError> {
+    if verification_script.is_empty() {
+        return Err(WitnessError::EmptyVerification);
+    }
+    let (arguments, invocation_gas) = invocation_arguments(invocation_script, gas_limit)?;
+
+    let mut vm = NeoVM::new(gas_limit - invocation_gas);
+    vm.sign_data = Some(signed_payload.to_vec());
+    vm.load_script(verification_script.to_vec())
+        .map_err(|e| WitnessError::VerificationFault(e.to_string()))?;
+    vm.eval_stack = arguments;
+    vm.run();
+
+    if !matches!(vm.state, VMState::Halt) {
+        return Err(WitnessError::VerificationFault(fault_message(&vm)));
+    }
+    if vm.eval_stack != [StackItem::Boolean(true)] {
+        return Err(WitnessError::Rejected);
+    }
+    Ok(invocation_gas + vm.gas_consumed)
+}
+
+/// Items the invocation script pushes, bottom first, and the gas it used
+pub fn invocation_arguments(
+    invocation_script: &[u8],
+    gas_limit: u64,
+) -> Result<(Vec<StackItem>, u64), WitnessError> {
+    if invocation_script.is_empty() {
+        return Ok((Vec::new(), 0));
+    }
+    if !is_push_only(invocation_script) {
+        return Err(WitnessError::NotPushOnly);
+    }
+    let mut vm = NeoVM::new(gas_limit);
+    vm.load_script_with_flags(invocation_script.to_vec(), CallFlags::NONE)
+        .map_err(|e| WitnessError::InvocationFault(e.to_string()))?;
+    vm.run();
+    if !matches!(vm.state, VMState::Halt) {
+        return Err(WitnessError::InvocationFault(fault_message(&vm)));
+    }
+    Ok((std::mem::take(&mut vm.eval_stack), vm.gas_consumed))
+}
+
+/// Whether `script` decodes entirely into data pushes
+pub fn is_push_only(script: &[u8]) -> bool {
+    let decoded = decode_script(script);
+    let complete = decoded
+        .instructions()
+        .last()
+        .is_some_and(|last| last.next_offset() == script.len());
+    complete
+        && decoded.instructions().iter().all(|instruction| {
+            instruction.opcode <= OpCode::PUSH16 as u8 && instruction.opcode != OpCode::PUSHA as u8
+        })
+}
+
+fn fault_message(vm: &NeoVM) -> String {
+    vm.fault_info.as_ref().map_or_else(
+        || "out of gas or step limit".to_string(),
+        |fault| fault.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::signature::Signer;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    const PAYLOAD: &[u8] = b"aggregate me";
+
+    /// PUSHDATA1 <public key>, SYSCALL System.Crypto.CheckSig
+    fn verification_script(key: &SigningKey) -> Vec<u8> {
+        let pubkey = key.verifying_key().to_encoded_point(true);
+        let pubkey = pubkey.as_bytes();
+        let mut script = vec![0x0C, pubkey.len() as u8];
+        script.extend_from_slice(pubkey);
+        script.extend_from_slice(&[0x41, 0x20, 0x00, 0x00, 0x00]);
+        script
+    }
+
+    /// PUSHDATA1 <signature of `payload`>
+    fn invocation_script(key: &SigningKey, payload: &[u8]) -> Vec<u8> {
+        let signature: Signature = key.sign(payload);
+        let mut script = vec![0x0C, 0x40];
+        script.extend_from_slice(&signature.to_bytes());
+        script
+    }
+
+    #[test]
+    fn test_signature_witness() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let verification = verification_script(&key);
+        let invocation = invocation_script(&key, PAYLOAD);
+
+        let gas = verify_witness(&verification, &invocation, PAYLOAD).unwrap();
+        assert!(gas > 0);
+        assert_eq!(
+            verify_witness(&verification, &invocation, b"something else"),
+            Err(WitnessError::Rejected)
+        );
+        assert!(matches!(
+            verify_witness_with_gas(&verification, &invocation, PAYLOAD, gas - 1),
+            Err(WitnessError::VerificationFault(_))
+        ));
+    }
+
+    #[test]
+    fn test_secp256r1_witness() {
+        // Neo N3 accounts use secp256r1 keys
+        let key = p256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let pubkey = key.verifying_key().to_encoded_point(true);
+        let mut verification = vec![0x0C, 33];
+        verification.extend_from_slice(pubkey.as_bytes());
+        verification.extend_from_slice(&[0x41, 0x20, 0x00, 0x00, 0x00]);
+        let signature: p256::ecdsa::Signature = key.sign(PAYLOAD);
+        let mut invocation = vec![0x0C, 0x40];
+        invocation.extend_from_slice(&signature.to_bytes());
+
+        assert!(verify_witness(&verification, &invocation, PAYLOAD).is_ok());
+        assert_eq!(
+            verify_witness(&verification, &invocation, b"something else"),
+            Err(WitnessError::Rejected)
+        );
+    }
+
+    #[test]
+    fn test_witness_rules() {
+        // PUSH1, PUSH1, EQUAL verifies anything, unless the invocation
+        // leaves an extra item; a truthy integer is not `true`
+        assert!(verify_witness(&[0x11, 0x11, 0x97], &[], PAYLOAD).is_ok());
+        assert_eq!(
+            verify_witness(&[0x11, 0x11, 0x97], &[0x11], PAYLOAD),
+            Err(WitnessError::Rejected)
+        );
+        assert_eq!(
+            verify_witness(&[0x11], &[], PAYLOAD),
+            Err(WitnessError::Rejected)
+        );
+        assert_eq!(
+            verify_witness(&[], &[0x11], PAYLOAD),
+            Err(WitnessError::EmptyVerification)
+        );
+
+        // The invocation script may not compute, call or be truncated
+        for invocation in [vec![0x11, 0x11, 0x9E], vec![0x0C, 0x05, 0x01]] {
+            assert_eq!(
+                verify_witness(&[0x11], &invocation, PAYLOAD),
+                Err(WitnessError::NotPushOnly)
+            );
+        }
+        assert!(is_push_only(&[0x00, 0x7F, 0x0B, 0x0F, 0x20]));
+
+        // Verification runs on what the invocation pushed: PUSH5, PUSH5 | EQUAL
+        assert!(verify_witness(&[0x97], &[0x15, 0x15], PAYLOAD).is_ok());
+        assert_eq!(
+            verify_witness(&[0x97], &[0x15, 0x16], PAYLOAD),
+            Err(WitnessError::Rejected)
+        );
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Tests edge cases and boundary conditions for all VM operations.
 
-use neo_vm_core::{NeoVM, StackItem, VMState};
+use neo_vm_core::{Bytes, NeoVM, StackItem, VMError, VMLimits, VMState};
 
 // Helper to run VM until completion
 fn run_vm(vm: &mut NeoVM) {
@@ -14,6 +14,20 @@ fn run_vm(vm: &mut NeoVM) {
     }
 }
 
+fn stack_limit(max_stack: usize) -> VMLimits {
+    VMLimits {
+        max_stack,
+        ..VMLimits::default()
+    }
+}
+
+fn invocation_limit(max_invocation_depth: usize) -> VMLimits {
+    VMLimits {
+        max_invocation_depth,
+        ..VMLimits::default()
+    }
+}
+
 // ============================================================================
 // Integer Boundary Tests
 // ============================================================================
@@ -154,6 +168,72 @@ fn test_pow_one_exp() {
     assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(5)));
 }
 
+#[test]
+fn test_sqrt_rounds_down() {
+    for (value, root) in [(0u8, 0), (1, 1), (15, 3), (16, 4), (127, 11)] {
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(vec![0x00, value, 0xA4, 0x40]); // PUSHINT8 value, SQRT
+        run_vm(&mut vm);
+        assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(root)));
+    }
+}
+
+#[test]
+fn test_sqrt_negative_faults() {
+    let mut vm = NeoVM::new(1_000_000);
+    let _ = vm.load_script(vec![0x0F, 0xA4, 0x40]); // PUSHM1, SQRT
+    run_vm(&mut vm);
+    assert!(matches!(vm.state, VMState::Fault));
+}
+
+#[test]
+fn test_modmul() {
+    let mut vm = NeoVM::new(1_000_000);
+    let script = vec![0x00, 0xF9, 0x14, 0x15, 0xA5, 0x40]; // -7 * 4 % 5
+    let _ = vm.load_script(script);
+    run_vm(&mut vm);
+    assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(-3)));
+
+    let mut vm = NeoVM::new(1_000_000);
+    let _ = vm.load_script(vec![0x17, 0x14, 0x10, 0xA5, 0x40]); // 7 * 4 % 0
+    run_vm(&mut vm);
+    assert!(matches!(vm.state, VMState::Fault));
+}
+
+#[test]
+fn test_modpow() {
+    // (base, exponent, modulus, result); a result of None faults
+    let cases: [(i8, i8, i8, Option<i128>); 7] = [
+        (4, 13, 97, Some(93)),
+        (-2, 3, 5, Some(-3)),
+        (5, 0, 1, Some(0)),
+        // Exponent -1 is the modular inverse
+        (3, -1, 11, Some(4)),
+        (4, -1, 8, None),
+        (3, -2, 11, None),
+        (3, 2, 0, None),
+    ];
+    for (base, exponent, modulus, result) in cases {
+        let mut vm = NeoVM::new(1_000_000);
+        let script = vec![
+            0x00,
+            base as u8,
+            0x00,
+            exponent as u8,
+            0x00,
+            modulus as u8,
+            0xA6,
+            0x40,
+        ];
+        let _ = vm.load_script(script);
+        run_vm(&mut vm);
+        match result {
+            Some(result) => assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(result))),
+            None => assert!(matches!(vm.state, VMState::Fault)),
+        }
+    }
+}
+
 // ============================================================================
 // Comparison Edge Cases
 // ============================================================================
@@ -320,6 +400,32 @@ fn test_shl_zero() {
     assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(5)));
 }
 
+#[test]
+fn test_shl_overflow_faults() {
+    // 1 << 126 still fits in an i128
+    let mut vm = NeoVM::new(1_000_000);
+    let _ = vm.load_script(vec![0x11, 0x00, 0x7E, 0xA8, 0x40]);
+    run_vm(&mut vm);
+    assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(1 << 126)));
+
+    // 1 << 127 would wrap to i128::MIN, 3 << 126 would lose its top bit
+    for script in [
+        vec![0x11, 0x00, 0x7F, 0xA8, 0x40],
+        vec![0x13, 0x00, 0x7E, 0xA8, 0x40],
+    ] {
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(script);
+        run_vm(&mut vm);
+        assert!(matches!(vm.state, VMState::Fault));
+    }
+
+    // Negative values keep their sign: -1 << 127 is i128::MIN
+    let mut vm = NeoVM::new(1_000_000);
+    let _ = vm.load_script(vec![0x0F, 0x00, 0x7F, 0xA8, 0x40]);
+    run_vm(&mut vm);
+    assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(i128::MIN)));
+}
+
 #[test]
 fn test_shr_zero() {
     let mut vm = NeoVM::new(1_000_000);
@@ -747,7 +853,7 @@ fn test_stack_depth_limit() {
 #[allow(clippy::same_item_push)]
 fn test_stack_overflow_protection() {
     // Create VM with small stack limit to test overflow protection
-    let mut vm = NeoVM::with_limits(1_000_000, 10, 1024); // max_stack_depth = 10
+    let mut vm = NeoVM::with_limits(1_000_000, stack_limit(10));
     
     // Try to push 15 items (exceeds limit of 10)
     let mut script = Vec::new();
@@ -766,7 +872,7 @@ fn test_stack_overflow_protection() {
 #[test]
 fn test_stack_exactly_at_limit() {
     // Create VM with stack limit of 5
-    let mut vm = NeoVM::with_limits(1_000_000, 5, 1024);
+    let mut vm = NeoVM::with_limits(1_000_000, stack_limit(5));
     
     // Push exactly 5 items (at limit)
     let script = vec![0x11, 0x11, 0x11, 0x11, 0x11, 0x40];
@@ -786,7 +892,7 @@ fn test_stack_exactly_at_limit() {
 #[test]
 fn test_invocation_depth_protection() {
     // Create VM with small invocation limit
-    let mut vm = NeoVM::with_limits(1_000_000, 2048, 2); // max_invocation_depth = 2
+    let mut vm = NeoVM::with_limits(1_000_000, invocation_limit(2));
     
     // Script that calls itself (recursion)
     // PUSH0, CALL +0 (calls itself), RET
@@ -806,7 +912,7 @@ fn test_invocation_depth_protection() {
 #[test]
 fn test_multiple_load_script_exceeds_limit() {
     // Create VM with invocation limit of 3
-    let mut vm = NeoVM::with_limits(1_000_000, 2048, 3);
+    let mut vm = NeoVM::with_limits(1_000_000, invocation_limit(3));
     
     // Load first script
     let script1 = vec![0x11, 0x40]; // PUSH1, RET
@@ -849,25 +955,19 @@ fn test_gas_exhaustion() {
 // Arithmetic Overflow Tests
 // ============================================================================
 
+/// PUSHINT128 `value`
+fn pushint128(value: i128) -> Vec<u8> {
+    let mut script = vec![0x04];
+    script.extend_from_slice(&value.to_le_bytes());
+    script
+}
+
 #[test]
 fn test_add_overflow_detection() {
     let mut vm = NeoVM::new(1_000_000);
     // i128::MAX + 1 should overflow
-    let max_val = i128::MAX;
-    let script = vec![
-        0x02, // PUSHINT32
-        (max_val & 0xFF) as u8,
-        ((max_val >> 8) & 0xFF) as u8,
-        ((max_val >> 16) & 0xFF) as u8,
-        ((max_val >> 24) & 0xFF) as u8,
-        0x02, // PUSHINT32
-        1u8,
-        0u8,
-        0u8,
-        0u8,  // 1
-        0x9E, // ADD
-        0x40, // RET
-    ];
+    let mut script = pushint128(i128::MAX);
+    script.extend_from_slice(&[0x11, 0x9E, 0x40]); // PUSH1, ADD, RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
@@ -877,46 +977,19 @@ fn test_add_overflow_detection() {
 fn test_sub_underflow_detection() {
     let mut vm = NeoVM::new(1_000_000);
     // i128::MIN - 1 should overflow
-    let min_val = i128::MIN;
-    let script = vec![
-        0x02, // PUSHINT32
-        (min_val & 0xFF) as u8,
-        ((min_val >> 8) & 0xFF) as u8,
-        ((min_val >> 16) & 0xFF) as u8,
-        ((min_val >> 24) & 0xFF) as u8,
-        0x02, // PUSHINT32
-        1u8,
-        0u8,
-        0u8,
-        0u8,  // 1
-        0x9F, // SUB
-        0x40, // RET
-    ];
+    let mut script = pushint128(i128::MIN);
+    script.extend_from_slice(&[0x11, 0x9F, 0x40]); // PUSH1, SUB, RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
 }
 
 #[test]
-#[allow(clippy::erasing_op)]
 fn test_mul_overflow_detection() {
     let mut vm = NeoVM::new(1_000_000);
-    // i128::MAX * 2 should overflow
-    let max_val = i128::MAX / 2 + 1;
-    let script = vec![
-        0x02, // PUSHINT32
-        (max_val & 0xFF) as u8,
-        ((max_val >> 8) & 0xFF) as u8,
-        ((max_val >> 16) & 0xFF) as u8,
-        ((max_val >> 24) & 0xFF) as u8,
-        0x02, // PUSHINT32
-        (2i128 & 0xFF) as u8,
-        ((2i128 >> 8) & 0xFF) as u8,
-        ((2i128 >> 16) & 0xFF) as u8,
-        ((2i128 >> 24) & 0xFF) as u8,
-        0xA0, // MUL
-        0x40, // RET
-    ];
+    // (i128::MAX / 2 + 1) * 2 should overflow
+    let mut script = pushint128(i128::MAX / 2 + 1);
+    script.extend_from_slice(&[0x12, 0xA0, 0x40]); // PUSH2, MUL, RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
@@ -926,16 +999,8 @@ fn test_mul_overflow_detection() {
 fn test_negate_overflow_detection() {
     let mut vm = NeoVM::new(1_000_000);
     // NEGATE i128::MIN should overflow
-    let min_val = i128::MIN;
-    let script = vec![
-        0x02, // PUSHINT32
-        (min_val & 0xFF) as u8,
-        ((min_val >> 8) & 0xFF) as u8,
-        ((min_val >> 16) & 0xFF) as u8,
-        ((min_val >> 24) & 0xFF) as u8,
-        0x9B, // NEGATE
-        0x40, // RET
-    ];
+    let mut script = pushint128(i128::MIN);
+    script.extend_from_slice(&[0x9B, 0x40]); // NEGATE, RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
@@ -945,16 +1010,8 @@ fn test_negate_overflow_detection() {
 fn test_abs_overflow_detection() {
     let mut vm = NeoVM::new(1_000_000);
     // ABS of i128::MIN should overflow
-    let min_val = i128::MIN;
-    let script = vec![
-        0x02, // PUSHINT32
-        (min_val & 0xFF) as u8,
-        ((min_val >> 8) & 0xFF) as u8,
-        ((min_val >> 16) & 0xFF) as u8,
-        ((min_val >> 24) & 0xFF) as u8,
-        0x9A, // ABS
-        0x40, // RET
-    ];
+    let mut script = pushint128(i128::MIN);
+    script.extend_from_slice(&[0x9A, 0x40]); // ABS, RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
@@ -989,7 +1046,10 @@ fn test_pushdata1_empty() {
     let script = vec![0x0C, 0x00, 0x40]; // PUSHDATA1 0 bytes, RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
-    assert_eq!(vm.eval_stack.pop(), Some(StackItem::ByteString(vec![])));
+    assert_eq!(
+        vm.eval_stack.pop(),
+        Some(StackItem::ByteString(Bytes::new()))
+    );
 }
 
 #[test]
@@ -998,7 +1058,10 @@ fn test_pushdata1_single() {
     let script = vec![0x0C, 0x01, 0xFF, 0x40]; // PUSHDATA1 1 byte (0xFF), RET
     let _ = vm.load_script(script).ok();
     run_vm(&mut vm);
-    assert_eq!(vm.eval_stack.pop(), Some(StackItem::ByteString(vec![0xFF])));
+    assert_eq!(
+        vm.eval_stack.pop(),
+        Some(StackItem::ByteString(vec![0xFF].into()))
+    );
 }
 
 #[test]
@@ -1068,3 +1131,50 @@ fn test_isnull_false() {
     assert!(matches!(vm.state, VMState::Halt));
     assert_eq!(vm.eval_stack.pop(), Some(StackItem::Boolean(false)));
 }
+
+#[test]
+fn test_item_size_limit() {
+    let limits = VMLimits {
+        max_item_size: 4,
+        ..VMLimits::default()
+    };
+    // PUSHDATA1 of 4 bytes fits, 5 bytes does not
+    let mut vm = NeoVM::with_limits(1_000_000, limits);
+    let _ = vm.load_script(vec![0x0C, 0x04, 1, 2, 3, 4, 0x40]);
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+
+    let mut vm = NeoVM::with_limits(1_000_000, limits);
+    let _ = vm.load_script(vec![0x0C, 0x05, 1, 2, 3, 4, 5, 0x40]);
+    vm.run();
+    assert!(matches!(vm.state, VMState::Fault));
+    assert_eq!(
+        vm.fault_info.map(|fault| fault.error),
+        Some(VMError::ItemTooLarge(5))
+    );
+}
+
+#[test]
+fn test_step_and_script_size_limits() {
+    let limits = VMLimits {
+        max_trace_steps: 3,
+        max_script_size: 4,
+        ..VMLimits::default()
+    };
+    // PUSH1, PUSH2, ADD, RET needs four steps
+    let mut vm = NeoVM::with_limits(1_000_000, limits);
+    let _ = vm.load_script(vec![0x11, 0x12, 0x9E, 0x40]);
+    vm.run();
+    assert!(matches!(vm.state, VMState::Fault));
+    assert_eq!(vm.steps, 3);
+    assert_eq!(
+        vm.fault_info.map(|fault| fault.error),
+        Some(VMError::StepLimitExceeded(3))
+    );
+
+    let mut vm = NeoVM::with_limits(1_000_000, limits);
+    assert_eq!(
+        vm.load_script(vec![0x11, 0x12, 0x9E, 0x40, 0x40]),
+        Err(VMError::InvalidScript)
+    );
+}
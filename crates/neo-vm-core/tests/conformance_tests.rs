@@ -0,0 +1,87 @@
+//! Conformance against neo-vm JSON test vectors
+//!
+//! The bundled vectors under `tests/vectors` must all pass. Point
+//! `NEO_VM_TEST_VECTORS` at a checkout of the reference
+//! `tests/Neo.VM.Tests/Tests` directory and run the ignored test to get a
+//! report for the full suite:
+//!
+//! ```text
+//! NEO_VM_TEST_VECTORS=../neo-vm/tests/Neo.VM.Tests/Tests \
+//!     cargo test --workspace --test conformance_tests -- --ignored --nocapture
+//! ```
+
+use neo_vm_core::conformance::{assemble, ConformanceReport, VectorFile};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn vector_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(vector_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+fn run_dir(dir: &Path) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+    for path in vector_files(dir) {
+        let json = fs::read_to_string(&path).unwrap();
+        match VectorFile::from_json(&json) {
+            Ok(file) => report.run_file(&file),
+            Err(e) => panic!("{}: {e}", path.display()),
+        }
+    }
+    report
+}
+
+#[test]
+fn test_bundled_vectors() {
+    let report = run_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors"));
+    assert!(report.passed() > 0);
+    assert!(report.is_conformant(), "{report}");
+}
+
+#[test]
+fn test_mismatch_is_reported() {
+    let file = VectorFile::from_json(
+        r#"{
+            "name": "ADD",
+            "tests": [{
+                "name": "Wrong sum",
+                "script": ["PUSH1", "PUSH2", "ADD"],
+                "steps": [{
+                    "actions": ["execute"],
+                    "result": {"state": "HALT", "resultStack": [{"type": "Integer", "value": 4}]}
+                }]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let mut report = ConformanceReport::default();
+    report.run_file(&file);
+
+    assert_eq!(report.failed(), 1);
+    let (test, reason) = &report.opcodes["ADD"].failed[0];
+    assert_eq!(test, "Wrong sum");
+    assert!(reason.contains("result stack"));
+}
+
+#[test]
+fn test_assemble_uses_reference_numbering() {
+    let script = assemble(&["LDARG0".into(), "PUSHDATA1".into(), "0x01ff".into()]).unwrap();
+    assert_eq!(script, vec![0x78, 0x0C, 0x01, 0xFF]);
+    assert!(assemble(&["NOSUCHOP".into()]).is_err());
+}
+
+#[test]
+#[ignore = "needs NEO_VM_TEST_VECTORS pointing at the reference test vectors"]
+fn test_reference_vectors() {
+    let dir = std::env::var("NEO_VM_TEST_VECTORS").expect("NEO_VM_TEST_VECTORS is not set");
+    println!("{}", run_dir(Path::new(&dir)));
+}
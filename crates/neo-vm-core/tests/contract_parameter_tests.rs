@@ -0,0 +1,147 @@
+//! Contract parameter JSON and stack item conversion tests
+
+use neo_vm_core::contract_parameter::parse_arguments;
+use neo_vm_core::{ContractParameter, ContractParameterError, StackItem};
+use serde_json::json;
+
+#[test]
+fn test_rpc_json_round_trip() {
+    let cases = [
+        (ContractParameter::Any, json!({"type": "Any"})),
+        (
+            ContractParameter::Boolean(true),
+            json!({"type": "Boolean", "value": true}),
+        ),
+        (
+            ContractParameter::Integer(-5),
+            json!({"type": "Integer", "value": "-5"}),
+        ),
+        (
+            ContractParameter::ByteArray(b"neo".to_vec()),
+            json!({"type": "ByteArray", "value": "bmVv"}),
+        ),
+        (
+            ContractParameter::String("hello".to_string()),
+            json!({"type": "String", "value": "hello"}),
+        ),
+        (
+            ContractParameter::Array(vec![ContractParameter::Integer(1)]),
+            json!({"type": "Array", "value": [{"type": "Integer", "value": "1"}]}),
+        ),
+        (
+            ContractParameter::Map(vec![(
+                ContractParameter::String("k".to_string()),
+                ContractParameter::Boolean(false),
+            )]),
+            json!({"type": "Map", "value": [{
+                "key": {"type": "String", "value": "k"},
+                "value": {"type": "Boolean", "value": false},
+            }]}),
+        ),
+    ];
+    for (param, expected) in cases {
+        assert_eq!(serde_json::to_value(&param).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<ContractParameter>(expected).unwrap(),
+            param
+        );
+    }
+}
+
+#[test]
+fn test_hashes_are_big_endian_in_json() {
+    // NEO token contract hash as shown by explorers
+    let text = "0xef4073a0f2b305a38ec4050e4d3d28bc40ea63f5";
+    let param = ContractParameter::from_json(&json!({"type": "Hash160", "value": text})).unwrap();
+    let ContractParameter::Hash160(hash) = param else {
+        panic!("expected Hash160, got {param:?}");
+    };
+    assert_eq!(hash[0], 0xf5);
+    assert_eq!(hash[19], 0xef);
+    assert_eq!(param.to_json()["value"], text);
+
+    // Stack items hold the little-endian bytes
+    assert_eq!(
+        StackItem::from_contract_parameter(&param).as_bytes(),
+        Some(&hash[..])
+    );
+}
+
+#[test]
+fn test_integer_accepts_numbers() {
+    let param = ContractParameter::from_json(&json!({"type": "Integer", "value": 42})).unwrap();
+    assert_eq!(param, ContractParameter::Integer(42));
+}
+
+#[test]
+fn test_rejects_invalid_parameters() {
+    for json in [
+        json!({"value": "1"}),
+        json!({"type": "Integer", "value": "one"}),
+        json!({"type": "Boolean", "value": "true"}),
+        json!({"type": "Hash160", "value": "0x1234"}),
+        json!({"type": "PublicKey", "value": format!("04{}", "00".repeat(32))}),
+        json!({"type": "Signature", "value": "AAAA"}),
+    ] {
+        assert!(
+            matches!(
+                ContractParameter::from_json(&json),
+                Err(ContractParameterError::Invalid(_))
+            ),
+            "accepted {json}"
+        );
+    }
+    assert_eq!(
+        ContractParameter::from_json(&json!({"type": "InteropInterface"})),
+        Err(ContractParameterError::UnsupportedType(
+            "InteropInterface".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_stack_item_conversion() {
+    let param = ContractParameter::Array(vec![
+        ContractParameter::Any,
+        ContractParameter::String("hi".to_string()),
+        ContractParameter::Integer(7),
+    ]);
+    let item = StackItem::from_contract_parameter(&param);
+    assert_eq!(
+        item,
+        StackItem::Array(vec![
+            StackItem::Null,
+            StackItem::ByteString(b"hi".to_vec().into()),
+            StackItem::Integer(7),
+        ])
+    );
+
+    // Strings come back as byte arrays
+    assert_eq!(
+        item.to_contract_parameter().unwrap(),
+        ContractParameter::Array(vec![
+            ContractParameter::Any,
+            ContractParameter::ByteArray(b"hi".to_vec()),
+            ContractParameter::Integer(7),
+        ])
+    );
+    assert_eq!(
+        StackItem::Pointer(3).to_contract_parameter(),
+        Err(ContractParameterError::Unrepresentable("Pointer"))
+    );
+}
+
+#[test]
+fn test_parse_arguments() {
+    let args =
+        parse_arguments(r#"[{"type":"Integer","value":"5"},{"type":"ByteArray","value":"AQI="}]"#)
+            .unwrap();
+    assert_eq!(
+        args,
+        vec![
+            StackItem::Integer(5),
+            StackItem::ByteString(vec![1, 2].into()),
+        ]
+    );
+    assert!(parse_arguments(r#"{"type":"Integer"}"#).is_err());
+}
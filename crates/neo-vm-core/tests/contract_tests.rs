@@ -0,0 +1,191 @@
+//! Contract call tests for Neo VM Core
+//!
+//! Tests `System.Contract.Call` and call flag enforcement.
+
+use neo_vm_core::engine::syscall;
+use neo_vm_core::{
+    CallFlags, Contract, ContractMethod, NeoVM, NotificationEvent, OpCode, ScriptBuilder,
+    StackItem, StorageBackend, StorageContext, VMError, VMState,
+};
+
+const HASH: [u8; 20] = [7; 20];
+
+fn method(name: &str, parameters: usize, offset: usize, safe: bool) -> ContractMethod {
+    ContractMethod {
+        name: name.to_string(),
+        parameters,
+        offset,
+        safe,
+    }
+}
+
+/// `sub(a, b)` at 0: SWAP, SUB, RET
+/// `store(v)` at 3: PUSHDATA1 "k", SYSCALL System.Storage.Put, RET
+/// `notify(v)` at 12: NEWARRAY0, SWAP, APPEND, PUSHDATA1 "evt",
+/// SYSCALL System.Runtime.Notify, RET
+/// `peek(v)` at 3: `store` declared safe
+fn contract() -> Contract {
+    let mut script = ScriptBuilder::new();
+    script
+        .emit(OpCode::SWAP)
+        .emit(OpCode::SUB)
+        .emit(OpCode::RET)
+        .emit_push_bytes(b"k")
+        .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+        .emit(OpCode::RET)
+        .emit(OpCode::NEWARRAY0)
+        .emit(OpCode::SWAP)
+        .emit(OpCode::APPEND)
+        .emit_push_bytes(b"evt")
+        .emit_syscall(syscall::SYSTEM_RUNTIME_NOTIFY)
+        .emit(OpCode::RET);
+    Contract::new(
+        HASH,
+        script.into_bytes(),
+        vec![
+            method("sub", 2, 0, false),
+            method("store", 1, 3, false),
+            method("notify", 1, 12, false),
+            method("peek", 1, 3, true),
+        ],
+    )
+}
+
+/// Script calling `method` on [`HASH`] with `flags`, passing `args`
+fn call_script(method: &str, flags: CallFlags, args: &[StackItem]) -> Vec<u8> {
+    let mut script = ScriptBuilder::new();
+    script.emit(OpCode::NEWARRAY0);
+    for arg in args {
+        match arg {
+            StackItem::Integer(n) => script.emit_push_int(*n),
+            StackItem::ByteString(b) => script.emit_push_bytes(b),
+            _ => unreachable!(),
+        };
+        script.emit(OpCode::APPEND);
+    }
+    script
+        .emit_push_int(flags.bits() as i128)
+        .emit_push_bytes(method.as_bytes())
+        .emit_push_bytes(&HASH)
+        .emit_syscall(syscall::SYSTEM_CONTRACT_CALL)
+        .emit(OpCode::RET);
+    script.into_bytes()
+}
+
+fn run(script: Vec<u8>, flags: CallFlags) -> (NeoVM, Result<(), VMError>) {
+    let mut vm = NeoVM::new(1_000_000);
+    vm.contracts.insert(HASH, contract());
+    vm.load_script_with_flags(script, flags).unwrap();
+    let mut result = Ok(());
+    while !matches!(vm.state, VMState::Halt | VMState::Fault) {
+        result = vm.execute_next();
+        if result.is_err() {
+            break;
+        }
+    }
+    (vm, result)
+}
+
+fn stored(vm: &NeoVM) -> Option<Vec<u8>> {
+    let context = StorageContext {
+        script_hash: HASH,
+        read_only: false,
+    };
+    vm.storage.get(&context, b"k")
+}
+
+#[test]
+fn test_call_returns_result() {
+    let args = [StackItem::Integer(5), StackItem::Integer(3)];
+    let (vm, result) = run(call_script("sub", CallFlags::ALL, &args), CallFlags::ALL);
+    result.unwrap();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(vm.eval_stack, vec![StackItem::Integer(2)]);
+}
+
+#[test]
+fn test_call_writes_callee_storage() {
+    let args = [StackItem::ByteString(b"v".to_vec().into())];
+    let (vm, result) = run(call_script("store", CallFlags::ALL, &args), CallFlags::ALL);
+    result.unwrap();
+    assert_eq!(stored(&vm), Some(b"v".to_vec()));
+}
+
+#[test]
+fn test_read_only_call_cannot_write() {
+    let args = [StackItem::ByteString(b"v".to_vec().into())];
+    let (vm, result) = run(
+        call_script("store", CallFlags::READ_ONLY, &args),
+        CallFlags::ALL,
+    );
+    assert!(matches!(
+        result,
+        Err(VMError::MissingCallFlags(CallFlags::WRITE_STATES))
+    ));
+    assert!(matches!(vm.state, VMState::Fault));
+    assert_eq!(stored(&vm), None);
+
+    // Safe methods are read-only whatever the caller passes
+    let (_, result) = run(call_script("peek", CallFlags::ALL, &args), CallFlags::ALL);
+    assert!(matches!(result, Err(VMError::MissingCallFlags(_))));
+}
+
+#[test]
+fn test_call_cannot_notify_without_flag() {
+    let args = [StackItem::Integer(1)];
+    let (vm, result) = run(
+        call_script("notify", CallFlags::STATES, &args),
+        CallFlags::ALL,
+    );
+    assert!(matches!(
+        result,
+        Err(VMError::MissingCallFlags(CallFlags::ALLOW_NOTIFY))
+    ));
+    assert!(vm.notifications.is_empty());
+
+    let (vm, result) = run(call_script("notify", CallFlags::ALL, &args), CallFlags::ALL);
+    result.unwrap();
+    assert_eq!(
+        vm.notifications,
+        vec![NotificationEvent {
+            script_hash: HASH,
+            event_name: "evt".to_string(),
+            state: StackItem::Array(vec![StackItem::Integer(1)]),
+        }]
+    );
+}
+
+#[test]
+fn test_callee_flags_narrowed_to_caller() {
+    // A read-only script cannot grant write access to the contract it calls
+    let args = [StackItem::ByteString(b"v".to_vec().into())];
+    let (_, result) = run(
+        call_script("store", CallFlags::ALL, &args),
+        CallFlags::READ_ONLY,
+    );
+    assert!(matches!(
+        result,
+        Err(VMError::MissingCallFlags(CallFlags::WRITE_STATES))
+    ));
+
+    // Calling needs AllowCall
+    let (_, result) = run(call_script("sub", CallFlags::ALL, &[]), CallFlags::STATES);
+    assert!(matches!(result, Err(VMError::MissingCallFlags(_))));
+}
+
+#[test]
+fn test_call_unknown_contract_or_method() {
+    let (_, result) = run(call_script("missing", CallFlags::ALL, &[]), CallFlags::ALL);
+    assert!(matches!(result, Err(VMError::MethodNotFound { .. })));
+
+    // `sub` takes two arguments
+    let args = [StackItem::Integer(1)];
+    let (_, result) = run(call_script("sub", CallFlags::ALL, &args), CallFlags::ALL);
+    assert!(matches!(result, Err(VMError::MethodNotFound { .. })));
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(call_script("sub", CallFlags::ALL, &[]))
+        .unwrap();
+    vm.run();
+    assert!(matches!(vm.state, VMState::Fault));
+}
@@ -2,7 +2,7 @@
 //!
 //! Tests error conditions and fault states.
 
-use neo_vm_core::{NeoVM, VMError, VMState};
+use neo_vm_core::{FaultCode, FaultInfo, NeoVM, StackItem, VMError, VMState};
 
 // Helper to run VM until completion
 fn run_vm(vm: &mut NeoVM) {
@@ -175,6 +175,23 @@ fn test_pow_negative_exponent() {
     assert!(matches!(vm.state, VMState::Fault));
 }
 
+#[test]
+fn test_pow_overflow() {
+    let mut vm = NeoVM::new(1_000_000);
+    let _ = vm.load_script(vec![0x12, 0x00, 0x7F, 0xA3, 0x40]); // 2 ^ 127 - overflows i128
+    run_vm(&mut vm);
+    assert!(matches!(vm.state, VMState::Fault));
+}
+
+#[test]
+fn test_newarray_too_large() {
+    let mut vm = NeoVM::new(1_000_000);
+    // PUSHINT32 0x7FFFFFFF, NEWARRAY
+    let _ = vm.load_script(vec![0x02, 0xFF, 0xFF, 0xFF, 0x7F, 0xC3, 0x40]);
+    run_vm(&mut vm);
+    assert!(matches!(vm.state, VMState::Fault));
+}
+
 #[test]
 fn test_shl_negative_shift() {
     let mut vm = NeoVM::new(1_000_000);
@@ -254,7 +271,7 @@ fn test_reversen_out_of_bounds() {
 #[test]
 fn test_ldloc_without_initslot() {
     let mut vm = NeoVM::new(1_000_000);
-    let _ = vm.load_script(vec![0x66, 0x40]); // LDLOC0 without INITSLOT
+    let _ = vm.load_script(vec![0x68, 0x40]); // LDLOC0 without INITSLOT
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
 }
@@ -262,7 +279,7 @@ fn test_ldloc_without_initslot() {
 #[test]
 fn test_ldarg_without_initslot() {
     let mut vm = NeoVM::new(1_000_000);
-    let _ = vm.load_script(vec![0x74, 0x40]); // LDARG0 without INITSLOT
+    let _ = vm.load_script(vec![0x78, 0x40]); // LDARG0 without INITSLOT
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
 }
@@ -270,7 +287,7 @@ fn test_ldarg_without_initslot() {
 #[test]
 fn test_stloc_without_initslot() {
     let mut vm = NeoVM::new(1_000_000);
-    let _ = vm.load_script(vec![0x15, 0x6D, 0x40]); // PUSH5, STLOC0 without INITSLOT
+    let _ = vm.load_script(vec![0x15, 0x70, 0x40]); // PUSH5, STLOC0 without INITSLOT
     run_vm(&mut vm);
     assert!(matches!(vm.state, VMState::Fault));
 }
@@ -359,6 +376,46 @@ fn test_jmp_missing_offset_faults() {
     assert!(matches!(err, VMError::InvalidScript));
 }
 
+#[test]
+fn test_jump_into_operand_faults() {
+    // PUSHINT16 0x4011 hides PUSH1, RET in its operand; JMP -2 targets it
+    let script = vec![0x01, 0x11, 0x40, 0x22, 0xFE];
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script).unwrap();
+    vm.run();
+    assert!(matches!(vm.state, VMState::Fault));
+    assert_eq!(
+        vm.fault_info.map(|fault| fault.error),
+        Some(VMError::InvalidJumpTarget(1))
+    );
+
+    // CALL_L and PUSHA are checked the same way, and so are targets
+    // outside the script
+    for script in [
+        vec![0x01, 0x11, 0x40, 0x35, 0xFE, 0xFF, 0xFF, 0xFF],
+        vec![0x01, 0x11, 0x40, 0x0A, 0xFE, 0xFF, 0xFF, 0xFF],
+        vec![0x22, 0x7F],
+    ] {
+        let mut vm = NeoVM::new(1_000_000);
+        vm.load_script(script).unwrap();
+        vm.run();
+        assert!(matches!(
+            vm.fault_info.map(|fault| fault.error),
+            Some(VMError::InvalidJumpTarget(_))
+        ));
+    }
+}
+
+#[test]
+fn test_jump_to_end_of_script_halts() {
+    // PUSH1, JMP +3, PUSH2
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(vec![0x11, 0x22, 0x03, 0x12]).unwrap();
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(vm.eval_stack, [StackItem::Integer(1)]);
+}
+
 #[test]
 fn test_syscall_missing_bytes_faults() {
     let mut vm = NeoVM::new(1_000_000);
@@ -371,6 +428,73 @@ fn test_syscall_missing_bytes_faults() {
 fn test_newarray_negative_size_faults() {
     let mut vm = NeoVM::new(1_000_000);
     vm.load_script(vec![0x0F, 0xC3]).unwrap(); // PUSHM1, NEWARRAY
-    let err = vm.execute_next().and_then(|_| vm.execute_next()).unwrap_err();
+    let err = vm
+        .execute_next()
+        .and_then(|_| vm.execute_next())
+        .unwrap_err();
     assert!(matches!(err, VMError::InvalidOperation));
 }
+
+// ============================================================================
+// Fault Diagnostics Tests
+// ============================================================================
+
+#[test]
+fn test_fault_info_locates_fault() {
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(vec![0x15, 0x10, 0xA1, 0x40]).unwrap(); // 5 / 0
+    run_vm(&mut vm);
+
+    let fault = vm.fault_info.clone().unwrap();
+    assert_eq!(
+        fault,
+        FaultInfo {
+            ip: 2,
+            opcode: 0xA1,
+            error: VMError::DivisionByZero,
+            invocation_depth: 1,
+        }
+    );
+    assert_eq!(
+        fault.to_string(),
+        "Division by zero at 0x0002 (opcode 0xA1, depth 1)"
+    );
+    assert_eq!(
+        FaultCode::from(&fault),
+        FaultCode {
+            ip: 2,
+            opcode: 0xA1,
+            error: VMError::DivisionByZero.code(),
+            invocation_depth: 1,
+        }
+    );
+}
+
+#[test]
+fn test_fault_info_in_called_context() {
+    // CALL +3, RET, PUSH0, PUSH0, DIV: faults one context down
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(vec![0x34, 0x03, 0x40, 0x10, 0x10, 0xA1])
+        .unwrap();
+    run_vm(&mut vm);
+
+    let fault = vm.fault_info.as_ref().unwrap();
+    assert_eq!(fault.ip, 5);
+    assert_eq!(fault.invocation_depth, 2);
+
+    // Halting leaves no fault behind
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+    run_vm(&mut vm);
+    assert!(vm.fault_info.is_none());
+}
+
+#[test]
+fn test_out_of_gas_fault_info() {
+    let mut vm = NeoVM::new(0);
+    vm.load_script(vec![0x11, 0x40]).unwrap();
+    run_vm(&mut vm);
+    let fault = vm.fault_info.as_ref().unwrap();
+    assert_eq!((fault.ip, fault.opcode), (0, 0x11));
+    assert_eq!(fault.error, VMError::OutOfGas);
+}
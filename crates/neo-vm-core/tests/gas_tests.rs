@@ -2,7 +2,8 @@
 //!
 //! Tests gas metering and limits.
 
-use neo_vm_core::{NeoVM, StackItem, VMState};
+use neo_vm_core::engine::syscall;
+use neo_vm_core::{GasSchedule, NeoVM, StackItem, VMState};
 
 // Helper to run VM until completion
 fn run_vm(vm: &mut NeoVM) {
@@ -257,3 +258,75 @@ fn test_gas_consumed_equals_limit() {
     assert!(matches!(vm2.state, VMState::Halt));
     assert_eq!(vm2.gas_consumed, exact_gas);
 }
+
+// ============================================================================
+// Gas Schedule Tests
+// ============================================================================
+
+#[test]
+fn test_neo_n3_schedule_prices() {
+    let mut vm = NeoVM::new(1_000_000);
+    vm.gas_schedule = GasSchedule::NeoN3;
+    let _ = vm.load_script(vec![0x15, 0x13, 0x9E, 0x40]); // 5 + 3
+    run_vm(&mut vm);
+    assert!(matches!(vm.state, VMState::Halt));
+    // PUSH5, PUSH3: 1 each, ADD: 8, RET: 0, at the default fee factor of 30
+    assert_eq!(vm.gas_consumed, 10 * 30);
+}
+
+#[test]
+fn test_neo_n3_schedule_charges_syscalls() {
+    let mut script = vec![0x41];
+    script.extend_from_slice(&syscall::SYSTEM_RUNTIME_GETTIME.to_le_bytes());
+    script.push(0x40);
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.gas_schedule = GasSchedule::NeoN3;
+    let _ = vm.load_script(script.clone());
+    run_vm(&mut vm);
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(vm.gas_consumed, 8 * 30);
+
+    // The legacy schedule only charges the SYSCALL opcode
+    let mut legacy = NeoVM::new(1_000_000);
+    let _ = legacy.load_script(script);
+    run_vm(&mut legacy);
+    assert!(legacy.gas_consumed < vm.gas_consumed);
+}
+
+#[test]
+fn test_hash_gas_grows_with_input() {
+    let hash_gas = |len: usize| {
+        let mut script = vec![0x0D];
+        script.extend_from_slice(&(len as u16).to_le_bytes());
+        script.extend(std::iter::repeat_n(0xAB, len));
+        script.extend_from_slice(&[0xF0, 0x40]); // SHA256, RET
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(script);
+        run_vm(&mut vm);
+        assert!(matches!(vm.state, VMState::Halt));
+        vm.gas_consumed
+    };
+    // PUSHDATA2 pays 1 and SHA256 8 per 32-byte word
+    assert_eq!(hash_gas(64_000) - hash_gas(32), 9 * (2000 - 1));
+}
+
+#[test]
+fn test_storage_put_gas_grows_with_value() {
+    let put_gas = |value: &[u8]| {
+        let mut builder = neo_vm_core::ScriptBuilder::new();
+        builder
+            .emit_push_bytes(value)
+            .emit_push_bytes(b"key")
+            .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+            .emit(neo_vm_core::OpCode::RET);
+        let mut vm = NeoVM::new(10_000_000_000);
+        vm.gas_schedule = GasSchedule::NeoN3;
+        let _ = vm.load_script(builder.into_bytes());
+        run_vm(&mut vm);
+        assert!(matches!(vm.state, VMState::Halt));
+        vm.gas_consumed
+    };
+    // A new entry pays Neo's storage price for every byte
+    assert_eq!(put_gas(&[1; 10]) - put_gas(&[1; 4]), 6 * 100_000);
+}
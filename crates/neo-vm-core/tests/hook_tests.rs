@@ -0,0 +1,190 @@
+//! Execution hook tests
+
+use neo_vm_core::engine::syscall;
+use neo_vm_core::{
+    ExecutionHook, HookAction, NeoVM, OpCode, ScriptBuilder, StackItem, VMError, VMState, WatchKind,
+};
+use std::sync::{Arc, Mutex};
+
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+    break_at: Option<usize>,
+}
+
+impl ExecutionHook for Recorder {
+    fn before_op(&mut self, ip: usize, opcode: u8) -> HookAction {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("before {ip} {opcode:02X}"));
+        if self.break_at == Some(ip) {
+            HookAction::Break
+        } else {
+            HookAction::Continue
+        }
+    }
+
+    fn after_op(&mut self, ip: usize, opcode: u8, _gas_consumed: u64) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("after {ip} {opcode:02X}"));
+    }
+
+    fn on_syscall(&mut self, id: u32) {
+        self.events.lock().unwrap().push(format!("syscall {id}"));
+    }
+
+    fn on_fault(&mut self, ip: usize, error: &VMError) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("fault {ip} {error}"));
+    }
+}
+
+#[test]
+fn test_hook_observes_each_instruction() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut vm = NeoVM::new(1_000_000);
+    vm.set_hook(Recorder {
+        events: events.clone(),
+        break_at: None,
+    });
+    // PUSH2, PUSH3, ADD, RET
+    vm.load_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+    vm.run();
+
+    assert!(matches!(vm.state, VMState::Halt));
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 8);
+    assert_eq!(events[0], "before 0 12");
+    assert_eq!(events[5], "after 2 9E");
+}
+
+#[test]
+fn test_hook_sees_syscalls() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut vm = NeoVM::new(1_000_000);
+    vm.set_hook(Recorder {
+        events: events.clone(),
+        break_at: None,
+    });
+    // SYSCALL GETTIME, RET
+    vm.load_script(vec![0x41, 0x03, 0x00, 0x00, 0x00, 0x40])
+        .unwrap();
+    vm.run();
+
+    assert!(events.lock().unwrap().contains(&"syscall 3".to_string()));
+}
+
+#[test]
+fn test_hook_break_pauses_and_resumes() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut vm = NeoVM::new(1_000_000);
+    vm.set_hook(Recorder {
+        events: events.clone(),
+        break_at: Some(2),
+    });
+    vm.load_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+
+    vm.run();
+    assert!(matches!(vm.state, VMState::Break));
+    assert_eq!(vm.eval_stack.len(), 2);
+    let gas_at_break = vm.gas_consumed;
+
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert!(vm.gas_consumed > gas_at_break);
+    assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(5)));
+}
+
+#[test]
+fn test_hook_notified_on_fault() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut vm = NeoVM::new(1_000_000);
+    vm.set_hook(Recorder {
+        events: events.clone(),
+        break_at: None,
+    });
+    // PUSH5, PUSH0, DIV
+    vm.load_script(vec![0x15, 0x10, 0xA1]).unwrap();
+    vm.run();
+
+    assert!(matches!(vm.state, VMState::Fault));
+    assert_eq!(
+        events.lock().unwrap().last().map(String::as_str),
+        Some("fault 2 Division by zero")
+    );
+    assert!(vm.take_hook().is_some());
+}
+
+#[test]
+fn test_coverage_counts_loaded_script() {
+    // PUSH3, loop: DEC, DUP, JMPIF loop, DROP, RET, PUSH0 (never reached)
+    let script = vec![0x13, 0x9D, 0x4A, 0x24, 0xFE, 0x45, 0x40, 0x10];
+    let mut vm = NeoVM::new(1_000_000);
+    vm.enable_coverage();
+    vm.load_script(script.clone()).unwrap();
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+
+    let coverage = vm.coverage.as_ref().unwrap();
+    assert_eq!(coverage.hits(0), 1);
+    assert_eq!(coverage.hits(1), 3);
+    assert_eq!(coverage.hits(3), 3);
+    assert_eq!(coverage.hits(7), 0);
+    assert_eq!(coverage.summary(&script), (6, 7));
+}
+
+#[test]
+fn test_watch_storage_key_breaks_on_change() {
+    let mut script = ScriptBuilder::new();
+    script
+        .emit_push_bytes(b"v1")
+        .emit_push_bytes(b"key")
+        .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+        .emit_push_bytes(b"other")
+        .emit_push_bytes(b"key2")
+        .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+        .emit(OpCode::RET);
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script.into_bytes()).unwrap();
+    vm.add_watch(WatchKind::StorageKey(b"key".to_vec()));
+
+    vm.run();
+    assert!(matches!(vm.state, VMState::Break));
+    let hit = vm.watch_hit.take().unwrap();
+    assert_eq!(hit.kind, WatchKind::StorageKey(b"key".to_vec()));
+    assert_eq!(hit.old, None);
+    assert_eq!(hit.new, Some(StackItem::ByteString(b"v1".to_vec().into())));
+
+    // Writing another key does not trigger the watch
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert!(vm.watch_hit.is_none());
+}
+
+#[test]
+fn test_watch_stack_value_breaks_on_change() {
+    // PUSH1, PUSH2, DROP, INC, RET
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(vec![0x11, 0x12, 0x45, 0x9C, 0x40]).unwrap();
+    vm.add_watch(WatchKind::StackValue(0));
+
+    vm.run();
+    assert!(matches!(vm.state, VMState::Break));
+    assert_eq!(vm.watch_hit.as_ref().unwrap().ip, 0);
+
+    // Pushing and dropping above the watched item leaves it unchanged
+    vm.run();
+    assert!(matches!(vm.state, VMState::Break));
+    let hit = vm.watch_hit.take().unwrap();
+    assert_eq!(hit.ip, 3);
+    assert_eq!(hit.old, Some(StackItem::Integer(1)));
+    assert_eq!(hit.new, Some(StackItem::Integer(2)));
+
+    assert!(vm.remove_watch(&WatchKind::StackValue(0)));
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+}
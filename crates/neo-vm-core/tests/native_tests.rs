@@ -1,8 +1,12 @@
 //! Native contract tests for Neo VM Core
 //!
-//! Tests StdLib and CryptoLib native contracts.
+//! Tests the StdLib, CryptoLib and Oracle native contracts.
 
-use neo_vm_core::{CryptoLib, NativeContract, NativeRegistry, StackItem, StdLib};
+use neo_vm_core::{
+    Bytes, CryptoLib, NativeContract, NativeRegistry, OracleContract, OracleResponses, StackItem,
+    StdLib,
+};
+use std::sync::Arc;
 
 // ============================================================================
 // StdLib Tests
@@ -22,7 +26,7 @@ fn test_stdlib_itoa_decimal() {
 
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "42");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "42");
     }
 }
 
@@ -36,7 +40,7 @@ fn test_stdlib_itoa_hex() {
 
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "ff");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "ff");
     }
 }
 
@@ -47,14 +51,14 @@ fn test_stdlib_itoa_binary() {
 
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "101");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "101");
     }
 }
 
 #[test]
 fn test_stdlib_atoi_decimal() {
     let stdlib = StdLib::new();
-    let result = stdlib.invoke("atoi", vec![StackItem::ByteString(b"42".to_vec())]);
+    let result = stdlib.invoke("atoi", vec![StackItem::ByteString(b"42".to_vec().into())]);
 
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), StackItem::Integer(42));
@@ -66,7 +70,7 @@ fn test_stdlib_atoi_hex() {
     let result = stdlib.invoke(
         "atoi",
         vec![
-            StackItem::ByteString(b"ff".to_vec()),
+            StackItem::ByteString(b"ff".to_vec().into()),
             StackItem::Integer(16),
         ],
     );
@@ -95,12 +99,12 @@ fn test_stdlib_base64_encode() {
     let stdlib = StdLib::new();
     let result = stdlib.invoke(
         "base64Encode",
-        vec![StackItem::ByteString(b"hello".to_vec())],
+        vec![StackItem::ByteString(b"hello".to_vec().into())],
     );
 
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "aGVsbG8=");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "aGVsbG8=");
     }
 }
 
@@ -109,7 +113,7 @@ fn test_stdlib_base64_decode() {
     let stdlib = StdLib::new();
     let result = stdlib.invoke(
         "base64Decode",
-        vec![StackItem::ByteString(b"aGVsbG8=".to_vec())],
+        vec![StackItem::ByteString(b"aGVsbG8=".to_vec().into())],
     );
 
     assert!(result.is_ok());
@@ -147,7 +151,10 @@ fn test_cryptolib_hash() {
 #[test]
 fn test_cryptolib_sha256() {
     let cryptolib = CryptoLib::new();
-    let result = cryptolib.invoke("sha256", vec![StackItem::ByteString(b"hello".to_vec())]);
+    let result = cryptolib.invoke(
+        "sha256",
+        vec![StackItem::ByteString(b"hello".to_vec().into())],
+    );
 
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(hash)) = result {
@@ -158,8 +165,14 @@ fn test_cryptolib_sha256() {
 #[test]
 fn test_cryptolib_sha256_deterministic() {
     let cryptolib = CryptoLib::new();
-    let result1 = cryptolib.invoke("sha256", vec![StackItem::ByteString(b"test".to_vec())]);
-    let result2 = cryptolib.invoke("sha256", vec![StackItem::ByteString(b"test".to_vec())]);
+    let result1 = cryptolib.invoke(
+        "sha256",
+        vec![StackItem::ByteString(b"test".to_vec().into())],
+    );
+    let result2 = cryptolib.invoke(
+        "sha256",
+        vec![StackItem::ByteString(b"test".to_vec().into())],
+    );
 
     assert_eq!(result1, result2);
 }
@@ -167,7 +180,10 @@ fn test_cryptolib_sha256_deterministic() {
 #[test]
 fn test_cryptolib_ripemd160() {
     let cryptolib = CryptoLib::new();
-    let result = cryptolib.invoke("ripemd160", vec![StackItem::ByteString(b"hello".to_vec())]);
+    let result = cryptolib.invoke(
+        "ripemd160",
+        vec![StackItem::ByteString(b"hello".to_vec().into())],
+    );
 
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(hash)) = result {
@@ -204,12 +220,51 @@ fn test_registry_invoke_cryptolib() {
     let result = registry.invoke(
         &cryptolib.hash(),
         "sha256",
-        vec![StackItem::ByteString(b"test".to_vec())],
+        vec![StackItem::ByteString(b"test".to_vec().into())],
     );
 
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_registry_invoke_oracle() {
+    let mut responses = OracleResponses::new();
+    responses.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+    responses.insert("https://api.example/time", "", b"0".to_vec());
+    let registry = NativeRegistry::new().with_oracle_responses(Arc::new(responses));
+    let oracle = OracleContract::default();
+    let url = |url: &str| StackItem::ByteString(url.as_bytes().to_vec().into());
+
+    let result = registry.invoke(
+        &oracle.hash(),
+        "getResponse",
+        vec![url("https://api.example/price"), url("$.neo")],
+    );
+    assert_eq!(result, Ok(StackItem::ByteString(b"12.5".to_vec().into())));
+
+    // A null filter is the unfiltered response
+    let result = registry.invoke(
+        &oracle.hash(),
+        "getResponse",
+        vec![url("https://api.example/time"), StackItem::Null],
+    );
+    assert_eq!(result, Ok(StackItem::ByteString(b"0".to_vec().into())));
+
+    // Requests without a response fail
+    let result = registry.invoke(
+        &oracle.hash(),
+        "getResponse",
+        vec![url("https://api.example/price")],
+    );
+    assert!(result.is_err());
+    assert!(oracle
+        .invoke(
+            "getResponse",
+            vec![url("https://api.example/price"), url("$.neo")]
+        )
+        .is_err());
+}
+
 #[test]
 fn test_registry_unknown_contract() {
     let registry = NativeRegistry::new();
@@ -227,7 +282,7 @@ fn test_registry_unknown_contract() {
 fn test_stdlib_serialize_large_input() {
     let stdlib = StdLib::new();
     let large_data = vec![0xFFu8; 1024 * 1024 + 1];
-    let result = stdlib.invoke("serialize", vec![StackItem::ByteString(large_data)]);
+    let result = stdlib.invoke("serialize", vec![StackItem::ByteString(large_data.into())]);
     assert!(result.is_ok());
 }
 
@@ -235,7 +290,10 @@ fn test_stdlib_serialize_large_input() {
 fn test_stdlib_base64_encode_large_input() {
     let stdlib = StdLib::new();
     let large_data = vec![0xFFu8; 1024 * 1024 + 1];
-    let result = stdlib.invoke("base64Encode", vec![StackItem::ByteString(large_data)]);
+    let result = stdlib.invoke(
+        "base64Encode",
+        vec![StackItem::ByteString(large_data.into())],
+    );
     assert!(result.is_err());
 }
 
@@ -243,7 +301,10 @@ fn test_stdlib_base64_encode_large_input() {
 fn test_stdlib_base64_decode_large_input() {
     let stdlib = StdLib::new();
     let large_data = vec![0x41u8; 1024 * 1024 + 1];
-    let result = stdlib.invoke("base64Decode", vec![StackItem::ByteString(large_data)]);
+    let result = stdlib.invoke(
+        "base64Decode",
+        vec![StackItem::ByteString(large_data.into())],
+    );
     assert!(result.is_err());
 }
 
@@ -251,7 +312,7 @@ fn test_stdlib_base64_decode_large_input() {
 fn test_stdlib_atoi_large_input() {
     let stdlib = StdLib::new();
     let large_data = vec![0x41u8; 1024 * 1024 + 1];
-    let result = stdlib.invoke("atoi", vec![StackItem::ByteString(large_data)]);
+    let result = stdlib.invoke("atoi", vec![StackItem::ByteString(large_data.into())]);
     assert!(result.is_err());
 }
 
@@ -259,7 +320,7 @@ fn test_stdlib_atoi_large_input() {
 fn test_cryptolib_sha256_large_input() {
     let cryptolib = CryptoLib::new();
     let large_data = vec![0xFFu8; 1024 * 1024 + 1];
-    let result = cryptolib.invoke("sha256", vec![StackItem::ByteString(large_data)]);
+    let result = cryptolib.invoke("sha256", vec![StackItem::ByteString(large_data.into())]);
     assert!(result.is_err());
 }
 
@@ -267,7 +328,7 @@ fn test_cryptolib_sha256_large_input() {
 fn test_cryptolib_ripemd160_large_input() {
     let cryptolib = CryptoLib::new();
     let large_data = vec![0xFFu8; 1024 * 1024 + 1];
-    let result = cryptolib.invoke("ripemd160", vec![StackItem::ByteString(large_data)]);
+    let result = cryptolib.invoke("ripemd160", vec![StackItem::ByteString(large_data.into())]);
     assert!(result.is_err());
 }
 
@@ -290,7 +351,10 @@ fn test_stdlib_atoi_invalid_base() {
     let stdlib = StdLib::new();
     let result = stdlib.invoke(
         "atoi",
-        vec![StackItem::ByteString(b"42".to_vec()), StackItem::Integer(8)],
+        vec![
+            StackItem::ByteString(b"42".to_vec().into()),
+            StackItem::Integer(8),
+        ],
     );
     assert!(result.is_err());
     if let Err(e) = result {
@@ -303,7 +367,7 @@ fn test_stdlib_base64_decode_invalid() {
     let stdlib = StdLib::new();
     let result = stdlib.invoke(
         "base64Decode",
-        vec![StackItem::ByteString(b"!!!invalid!!!".to_vec())],
+        vec![StackItem::ByteString(b"!!!invalid!!!".to_vec().into())],
     );
     assert!(result.is_err());
 }
@@ -314,9 +378,9 @@ fn test_cryptolib_ecdsa_invalid_signature() {
     let result = cryptolib.invoke(
         "verifyWithECDsa",
         vec![
-            StackItem::ByteString(b"message".to_vec()),
-            StackItem::ByteString(b"invalid-signature".to_vec()),
-            StackItem::ByteString(vec![0x04u8; 65]),
+            StackItem::ByteString(b"message".to_vec().into()),
+            StackItem::ByteString(b"invalid-signature".to_vec().into()),
+            StackItem::ByteString(vec![0x04u8; 65].into()),
         ],
     );
     assert!(result.is_err());
@@ -328,9 +392,9 @@ fn test_cryptolib_ecdsa_invalid_public_key() {
     let result = cryptolib.invoke(
         "verifyWithECDsa",
         vec![
-            StackItem::ByteString(b"message".to_vec()),
-            StackItem::ByteString(vec![0u8; 64]),
-            StackItem::ByteString(b"invalid-key".to_vec()),
+            StackItem::ByteString(b"message".to_vec().into()),
+            StackItem::ByteString(vec![0u8; 64].into()),
+            StackItem::ByteString(b"invalid-key".to_vec().into()),
         ],
     );
     assert!(result.is_err());
@@ -349,8 +413,8 @@ fn test_cryptolib_ecdsa_no_public_key() {
     let result = cryptolib.invoke(
         "verifyWithECDsa",
         vec![
-            StackItem::ByteString(b"message".to_vec()),
-            StackItem::ByteString(vec![0u8; 64]),
+            StackItem::ByteString(b"message".to_vec().into()),
+            StackItem::ByteString(vec![0u8; 64].into()),
         ],
     );
     assert!(result.is_err());
@@ -366,7 +430,7 @@ fn test_stdlib_itoa_negative() {
     let result = stdlib.invoke("itoa", vec![StackItem::Integer(-42)]);
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "-42");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "-42");
     }
 }
 
@@ -376,14 +440,14 @@ fn test_stdlib_itoa_zero() {
     let result = stdlib.invoke("itoa", vec![StackItem::Integer(0)]);
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "0");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "0");
     }
 }
 
 #[test]
 fn test_stdlib_atoi_negative() {
     let stdlib = StdLib::new();
-    let result = stdlib.invoke("atoi", vec![StackItem::ByteString(b"-42".to_vec())]);
+    let result = stdlib.invoke("atoi", vec![StackItem::ByteString(b"-42".to_vec().into())]);
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), StackItem::Integer(-42));
 }
@@ -391,17 +455,17 @@ fn test_stdlib_atoi_negative() {
 #[test]
 fn test_stdlib_base64_encode_empty() {
     let stdlib = StdLib::new();
-    let result = stdlib.invoke("base64Encode", vec![StackItem::ByteString(vec![])]);
+    let result = stdlib.invoke("base64Encode", vec![StackItem::ByteString(Bytes::new())]);
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
-        assert_eq!(String::from_utf8(bytes).unwrap(), "");
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), "");
     }
 }
 
 #[test]
 fn test_stdlib_base64_decode_empty() {
     let stdlib = StdLib::new();
-    let result = stdlib.invoke("base64Decode", vec![StackItem::ByteString(vec![])]);
+    let result = stdlib.invoke("base64Decode", vec![StackItem::ByteString(Bytes::new())]);
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(bytes)) = result {
         assert!(bytes.is_empty());
@@ -411,7 +475,7 @@ fn test_stdlib_base64_decode_empty() {
 #[test]
 fn test_cryptolib_sha256_empty() {
     let cryptolib = CryptoLib::new();
-    let result = cryptolib.invoke("sha256", vec![StackItem::ByteString(vec![])]);
+    let result = cryptolib.invoke("sha256", vec![StackItem::ByteString(Bytes::new())]);
     assert!(result.is_ok());
     if let Ok(StackItem::ByteString(hash)) = result {
         assert_eq!(hash.len(), 32);
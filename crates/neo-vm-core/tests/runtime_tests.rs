@@ -0,0 +1,222 @@
+//! Runtime syscall tests for Neo VM Core
+//!
+//! Tests the `System.Runtime` syscalls: the execution environment, block
+//! time, random numbers, gas burning and notifications.
+
+use neo_vm_core::engine::syscall;
+use neo_vm_core::runtime::TESTNET_MAGIC;
+use neo_vm_core::{
+    BlockContext, Contract, ContractMethod, LedgerContract, NativeContract, NeoVM,
+    NotificationEvent, OpCode, RuntimeConfig, ScriptBuilder, StackItem, TriggerType, VMState,
+};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+fn run_with(script: &mut ScriptBuilder, runtime: RuntimeConfig) -> NeoVM {
+    script.emit(OpCode::RET);
+    let mut vm = NeoVM::new(1_000_000);
+    vm.runtime = runtime;
+    vm.load_script(script.clone().into_bytes()).unwrap();
+    vm.run();
+    vm
+}
+
+fn run(script: &mut ScriptBuilder) -> NeoVM {
+    run_with(script, RuntimeConfig::default())
+}
+
+#[test]
+fn test_environment_syscalls() {
+    let runtime = RuntimeConfig {
+        network: TESTNET_MAGIC,
+        trigger: TriggerType::Verification,
+        random_seed: [0; 32],
+    };
+    let vm = run_with(
+        ScriptBuilder::new()
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETTRIGGER)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETPLATFORM)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETNETWORK),
+        runtime,
+    );
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(
+        vm.eval_stack,
+        vec![
+            StackItem::Integer(0x20),
+            StackItem::ByteString(b"NEO".to_vec().into()),
+            StackItem::Integer(TESTNET_MAGIC as i128),
+        ]
+    );
+}
+
+#[test]
+fn test_get_time_reads_block_context() {
+    let vm = run(ScriptBuilder::new().emit_syscall(syscall::SYSTEM_RUNTIME_GETTIME));
+    assert_eq!(vm.eval_stack, vec![StackItem::Integer(0)]);
+
+    let block = BlockContext {
+        index: 5_000_000,
+        timestamp: 1_700_000_000_000,
+    };
+    // The Ledger native sees the same block
+    let mut vm = NeoVM::new(1_000_000);
+    vm.set_time(Arc::new(block));
+    vm.load_script(
+        ScriptBuilder::new()
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETTIME)
+            .emit(OpCode::NEWARRAY0)
+            .emit_push_int(0x0F)
+            .emit_push_bytes(b"currentIndex")
+            .emit_push_bytes(&LedgerContract::default().hash())
+            .emit_syscall(syscall::SYSTEM_CONTRACT_CALL)
+            .emit(OpCode::RET)
+            .to_bytes(),
+    )
+    .unwrap();
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(
+        vm.eval_stack,
+        vec![
+            StackItem::Integer(block.timestamp as i128),
+            StackItem::Integer(block.index as i128),
+        ]
+    );
+}
+
+#[test]
+fn test_get_random_draws_in_sequence() {
+    let runtime = RuntimeConfig {
+        random_seed: [9; 32],
+        ..RuntimeConfig::default()
+    };
+    let vm = run_with(
+        ScriptBuilder::new()
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETRANDOM)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETRANDOM),
+        runtime,
+    );
+    assert_eq!(vm.random_draws, 2);
+    assert_eq!(
+        vm.eval_stack,
+        vec![
+            StackItem::Integer(runtime.random(0)),
+            StackItem::Integer(runtime.random(1)),
+        ]
+    );
+}
+
+#[test]
+fn test_invocation_counter_counts_calls() {
+    const HASH: [u8; 20] = [3; 20];
+    let mut callee = ScriptBuilder::new();
+    callee
+        .emit_syscall(syscall::SYSTEM_RUNTIME_GETINVOCATIONCOUNTER)
+        .emit(OpCode::RET);
+    let contract = Contract::new(
+        HASH,
+        callee.into_bytes(),
+        vec![ContractMethod {
+            name: "count".to_string(),
+            parameters: 0,
+            offset: 0,
+            safe: true,
+        }],
+    );
+
+    let mut script = ScriptBuilder::new();
+    script.emit_syscall(syscall::SYSTEM_RUNTIME_GETINVOCATIONCOUNTER);
+    for _ in 0..2 {
+        script
+            .emit(OpCode::NEWARRAY0)
+            .emit_push_int(0x0F)
+            .emit_push_bytes(b"count")
+            .emit_push_bytes(&HASH)
+            .emit_syscall(syscall::SYSTEM_CONTRACT_CALL);
+    }
+    script.emit(OpCode::RET);
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.contracts.insert(HASH, contract);
+    vm.load_script(script.into_bytes()).unwrap();
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(
+        vm.eval_stack,
+        vec![
+            StackItem::Integer(1),
+            StackItem::Integer(1),
+            StackItem::Integer(2),
+        ]
+    );
+}
+
+#[test]
+fn test_burn_gas() {
+    let baseline = run(ScriptBuilder::new().emit_push_int(1).emit(OpCode::DROP));
+    let vm = run(ScriptBuilder::new()
+        .emit_push_int(5_000)
+        .emit_syscall(syscall::SYSTEM_RUNTIME_BURNGAS));
+    assert!(matches!(vm.state, VMState::Halt));
+    assert!(vm.gas_consumed >= baseline.gas_consumed + 5_000);
+
+    // Burning nothing, or more than is left, faults
+    let vm = run(ScriptBuilder::new()
+        .emit_push_int(0)
+        .emit_syscall(syscall::SYSTEM_RUNTIME_BURNGAS));
+    assert!(matches!(vm.state, VMState::Fault));
+    let vm = run(ScriptBuilder::new()
+        .emit_push_int(2_000_000)
+        .emit_syscall(syscall::SYSTEM_RUNTIME_BURNGAS));
+    assert!(matches!(vm.state, VMState::Fault));
+}
+
+/// Script sending `event_name` with a one-item state
+fn notify(event_name: &[u8]) -> ScriptBuilder {
+    let mut script = ScriptBuilder::new();
+    script
+        .emit(OpCode::NEWARRAY0)
+        .emit_push_int(7)
+        .emit(OpCode::APPEND)
+        .emit_push_bytes(event_name)
+        .emit_syscall(syscall::SYSTEM_RUNTIME_NOTIFY);
+    script
+}
+
+#[test]
+fn test_notify_records_event() {
+    let mut script = notify(b"Transfer");
+    let vm = run(&mut script);
+    assert!(matches!(vm.state, VMState::Halt));
+
+    // A loaded script is identified by its Hash160
+    let bytes = script.into_bytes();
+    let hash: [u8; 20] = Ripemd160::digest(Sha256::digest(&bytes)).into();
+    assert_eq!(
+        vm.notifications,
+        vec![NotificationEvent {
+            script_hash: hash,
+            event_name: "Transfer".to_string(),
+            state: StackItem::Array(vec![StackItem::Integer(7)]),
+        }]
+    );
+}
+
+#[test]
+fn test_notify_rejects_bad_events() {
+    // Names are at most 32 bytes of UTF-8
+    let vm = run(&mut notify(&[b'a'; 33]));
+    assert!(matches!(vm.state, VMState::Fault));
+    let vm = run(&mut notify(&[0xFF]));
+    assert!(matches!(vm.state, VMState::Fault));
+
+    // The state must be an array
+    let vm = run(ScriptBuilder::new()
+        .emit_push_int(7)
+        .emit_push_bytes(b"Transfer")
+        .emit_syscall(syscall::SYSTEM_RUNTIME_NOTIFY));
+    assert!(matches!(vm.state, VMState::Fault));
+    assert!(vm.notifications.is_empty());
+}
@@ -0,0 +1,55 @@
+//! Script builder tests
+
+use neo_vm_core::engine::syscall;
+use neo_vm_core::{NeoVM, OpCode, ScriptBuilder, StackItem};
+
+#[test]
+fn test_push_int_uses_shortest_encoding() {
+    let mut builder = ScriptBuilder::new();
+    builder
+        .emit_push_int(-1)
+        .emit_push_int(16)
+        .emit_push_int(17)
+        .emit_push_int(-200)
+        .emit_push_int(70_000)
+        .emit_push_int(1 << 40)
+        .emit_push_int(i128::MAX);
+
+    let mut expected = vec![0x0F, 0x20, 0x00, 17, 0x01, 0x38, 0xFF, 0x02];
+    expected.extend_from_slice(&70_000i32.to_le_bytes());
+    expected.push(0x03);
+    expected.extend_from_slice(&(1i64 << 40).to_le_bytes());
+    expected.push(0x04);
+    expected.extend_from_slice(&i128::MAX.to_le_bytes());
+    assert_eq!(builder.to_bytes(), expected);
+}
+
+#[test]
+fn test_push_bytes_length_prefix() {
+    let short = ScriptBuilder::new().emit_push_bytes(b"abc").to_bytes();
+    assert_eq!(short, vec![0x0C, 3, b'a', b'b', b'c']);
+
+    let long = ScriptBuilder::new().emit_push_bytes(&[7u8; 300]).to_bytes();
+    assert_eq!(&long[..3], &[0x0D, 0x2C, 0x01]);
+    assert_eq!(long.len(), 303);
+}
+
+#[test]
+fn test_built_script_runs() {
+    let script = ScriptBuilder::new()
+        .emit_push_bytes(b"v")
+        .emit_push_bytes(b"k")
+        .emit_syscall(syscall::SYSTEM_STORAGE_PUT)
+        .emit_push_bytes(b"k")
+        .emit_syscall(syscall::SYSTEM_STORAGE_GET)
+        .emit(OpCode::RET)
+        .to_bytes();
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script).unwrap();
+    vm.run();
+    assert_eq!(
+        vm.eval_stack,
+        vec![StackItem::ByteString(b"v".to_vec().into())]
+    );
+}
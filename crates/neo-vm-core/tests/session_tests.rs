@@ -0,0 +1,73 @@
+//! Execution session tests
+
+use neo_vm_core::{ExecutionSession, StackItem, VMError, VMState};
+
+/// PUSHDATA1 value, PUSHDATA1 key, SYSCALL System.Storage.Put
+fn storage_put(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut script = vec![0x0C, value.len() as u8];
+    script.extend_from_slice(value);
+    script.extend_from_slice(&[0x0C, key.len() as u8]);
+    script.extend_from_slice(key);
+    script.extend_from_slice(&[0x41, 0x11, 0x00, 0x00, 0x00]);
+    script
+}
+
+/// PUSHDATA1 key, SYSCALL System.Storage.Get, RET
+fn storage_get(key: &[u8]) -> Vec<u8> {
+    let mut script = vec![0x0C, key.len() as u8];
+    script.extend_from_slice(key);
+    script.extend_from_slice(&[0x41, 0x10, 0x00, 0x00, 0x00, 0x40]);
+    script
+}
+
+#[test]
+fn test_storage_persists_across_calls() {
+    let mut session = ExecutionSession::new(1_000_000);
+
+    let mut deploy = storage_put(b"owner", b"alice");
+    deploy.push(0x40);
+    assert!(session.execute(deploy, vec![]).is_halt());
+
+    let result = session.execute(storage_get(b"owner"), vec![]);
+    assert!(result.is_halt());
+    assert_eq!(
+        result.stack,
+        vec![StackItem::ByteString(b"alice".to_vec().into())]
+    );
+}
+
+#[test]
+fn test_faulted_call_is_reverted() {
+    let mut session = ExecutionSession::new(1_000_000);
+
+    // Write, then divide by zero: PUSH1, PUSH0, DIV
+    let mut script = storage_put(b"k", b"v");
+    script.extend_from_slice(&[0x11, 0x10, 0xA1]);
+    let result = session.execute(script, vec![]);
+    assert!(matches!(result.state, VMState::Fault));
+    assert!(matches!(result.error, Some(VMError::DivisionByZero)));
+
+    let result = session.execute(storage_get(b"k"), vec![]);
+    assert_eq!(result.stack, vec![StackItem::Null]);
+    assert!(session.storage().write_set().is_empty());
+}
+
+#[test]
+fn test_gas_resets_and_logs_accumulate() {
+    let mut session = ExecutionSession::new(1_000_000);
+    // PUSHDATA1 "hi", SYSCALL System.Runtime.Log, ADD, RET
+    let script = vec![
+        0x0C, 0x02, b'h', b'i', 0x41, 0x01, 0x00, 0x00, 0x00, 0x9E, 0x40,
+    ];
+
+    let first = session.execute(
+        script.clone(),
+        vec![StackItem::Integer(2), StackItem::Integer(3)],
+    );
+    let second = session.execute(script, vec![StackItem::Integer(4), StackItem::Integer(5)]);
+
+    assert_eq!(first.stack, vec![StackItem::Integer(5)]);
+    assert_eq!(second.stack, vec![StackItem::Integer(9)]);
+    assert_eq!(first.gas_consumed, second.gas_consumed);
+    assert_eq!(session.logs(), ["hi", "hi"]);
+}
@@ -0,0 +1,115 @@
+//! Stack item encoding and semantics tests
+
+use neo_vm_core::{NeoVM, OpCode, ScriptBuilder, StackItem};
+
+// === Canonical Encoding Tests ===
+
+#[test]
+fn test_canonical_primitives() {
+    assert_eq!(StackItem::Null.serialize_canonical(), vec![0x00]);
+    assert_eq!(
+        StackItem::Boolean(true).serialize_canonical(),
+        vec![0x20, 0x01]
+    );
+
+    let mut expected = vec![0x21];
+    expected.extend_from_slice(&(-2i128).to_le_bytes());
+    assert_eq!(StackItem::Integer(-2).serialize_canonical(), expected);
+
+    assert_eq!(
+        StackItem::ByteString(b"ab".to_vec().into()).serialize_canonical(),
+        vec![0x28, 0x02, 0x00, 0x00, 0x00, b'a', b'b']
+    );
+    assert_eq!(
+        StackItem::Pointer(7).serialize_canonical(),
+        vec![0x10, 0x07, 0x00, 0x00, 0x00]
+    );
+}
+
+#[test]
+fn test_canonical_distinguishes_bytestring_and_buffer() {
+    let bytes = StackItem::ByteString(vec![1, 2, 3].into());
+    let buffer = StackItem::Buffer(vec![1, 2, 3]);
+    assert_ne!(bytes.serialize_canonical(), buffer.serialize_canonical());
+}
+
+#[test]
+fn test_canonical_nested_containers() {
+    let item = StackItem::Array(vec![
+        StackItem::Boolean(false),
+        StackItem::Map(vec![(StackItem::Integer(1), StackItem::Null)]),
+    ]);
+    let encoded = item.serialize_canonical();
+
+    // Array tag + count, Boolean, Map tag + count, Integer key, Null value
+    assert_eq!(encoded.len(), 5 + 2 + 5 + 17 + 1);
+    assert_eq!(&encoded[..5], &[0x40, 0x02, 0x00, 0x00, 0x00]);
+    assert_eq!(encoded[7], 0x48);
+}
+
+// === Byte String Sharing Tests ===
+
+#[test]
+fn test_dup_shares_byte_string() {
+    let script = ScriptBuilder::new()
+        .emit_push_bytes(&[0xAB; 64])
+        .emit(OpCode::DUP)
+        .emit(OpCode::RET)
+        .to_bytes();
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script).unwrap();
+    vm.run();
+
+    match (&vm.eval_stack[0], &vm.eval_stack[1]) {
+        (StackItem::ByteString(a), StackItem::ByteString(b)) => {
+            assert_eq!(a, b);
+            assert_eq!(a.as_ptr(), b.as_ptr());
+        }
+        other => panic!("expected two byte strings, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_byte_string_bincode_layout() {
+    // Same layout as the former `Vec<u8>` payload: variant, u64 length, bytes
+    let encoded = bincode::serialize(&StackItem::ByteString(b"ab".to_vec().into())).unwrap();
+    assert_eq!(
+        encoded,
+        vec![3, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']
+    );
+    let decoded: StackItem = bincode::deserialize(&encoded).unwrap();
+    assert_eq!(decoded, StackItem::ByteString(b"ab".to_vec().into()));
+}
+
+// === JSON Tests ===
+
+#[test]
+fn test_json_format() {
+    let item = StackItem::Array(vec![
+        StackItem::Null,
+        StackItem::Integer(-170141183460469231731687303715884105728),
+        StackItem::ByteString(b"neo".to_vec().into()),
+        StackItem::Map(vec![(StackItem::Boolean(true), StackItem::Pointer(4))]),
+    ]);
+    let json = serde_json::to_value(&item).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"type": "Array", "value": [
+            {"type": "Any"},
+            {"type": "Integer", "value": "-170141183460469231731687303715884105728"},
+            {"type": "ByteString", "value": "bmVv"},
+            {"type": "Map", "value": [{
+                "key": {"type": "Boolean", "value": true},
+                "value": {"type": "Pointer", "value": 4},
+            }]},
+        ]})
+    );
+    assert_eq!(serde_json::from_value::<StackItem>(json).unwrap(), item);
+}
+
+#[test]
+fn test_json_reads_integer_numbers() {
+    let item: StackItem = serde_json::from_str(r#"{"type":"Integer","value":5}"#).unwrap();
+    assert_eq!(item, StackItem::Integer(5));
+    assert!(serde_json::from_str::<StackItem>(r#"{"type":"Buffer","value":"!"}"#).is_err());
+}
@@ -2,7 +2,10 @@
 //!
 //! Tests storage operations and Merkle proof generation.
 
-use neo_vm_core::{MemoryStorage, StorageBackend, StorageContext, TrackedStorage};
+use neo_vm_core::{
+    MemoryStorage, NeoVM, StackItem, StorageBackend, StorageContext, TrackedStorage, TrieStorage,
+    VMState,
+};
 
 // ============================================================================
 // Basic Storage Operations
@@ -433,3 +436,346 @@ fn test_merkle_root_1000_items() {
     let root2 = storage.merkle_root();
     assert_eq!(root, root2);
 }
+
+// ============================================================================
+// JSON Fixtures
+// ============================================================================
+
+#[test]
+fn test_storage_json_roundtrip() {
+    let mut storage = MemoryStorage::new();
+    let a = StorageContext {
+        script_hash: [0xAA; 20],
+        read_only: false,
+    };
+    let b = StorageContext {
+        script_hash: [0xBB; 20],
+        read_only: false,
+    };
+    storage.put(&a, b"name", b"token");
+    storage.put(&b, &[0x01, 0x02], &[0xFF]);
+
+    let json = storage.to_json();
+    let restored = MemoryStorage::from_json(&json).unwrap();
+    assert_eq!(restored.get(&a, b"name"), Some(b"token".to_vec()));
+    assert_eq!(restored.get(&b, &[0x01, 0x02]), Some(vec![0xFF]));
+    assert_eq!(restored.merkle_root(), storage.merkle_root());
+}
+
+#[test]
+fn test_storage_json_fixture_format() {
+    let json = r#"{ "0x0000000000000000000000000000000000000001": { "6b6579": "0x76616c7565" } }"#;
+    let storage = MemoryStorage::from_json(json).unwrap();
+
+    let mut script_hash = [0u8; 20];
+    script_hash[19] = 1;
+    let ctx = StorageContext {
+        script_hash,
+        read_only: false,
+    };
+    assert_eq!(storage.get(&ctx, b"key"), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_storage_json_rejects_bad_script_hash() {
+    assert!(MemoryStorage::from_json(r#"{ "abcd": {} }"#).is_err());
+    assert!(MemoryStorage::from_json("not json").is_err());
+}
+
+// ============================================================================
+// Journaling
+// ============================================================================
+
+#[test]
+fn test_journal_revert_undoes_nested_changes() {
+    let ctx = StorageContext::default();
+    let mut storage = TrackedStorage::new();
+    storage.put(&ctx, b"a", b"1");
+
+    storage.checkpoint();
+    storage.put(&ctx, b"a", b"2");
+    storage.checkpoint();
+    storage.put(&ctx, b"b", b"3");
+    storage.delete(&ctx, b"a");
+    storage.revert();
+
+    assert_eq!(storage.get(&ctx, b"a"), Some(b"2".to_vec()));
+    assert_eq!(storage.get(&ctx, b"b"), None);
+
+    storage.revert();
+    assert_eq!(storage.get(&ctx, b"a"), Some(b"1".to_vec()));
+    assert_eq!(storage.changes().len(), 1);
+    assert_eq!(storage.checkpoint_depth(), 0);
+}
+
+#[test]
+fn test_journal_commit_merges_into_parent() {
+    let ctx = StorageContext::default();
+    let mut storage = TrackedStorage::new();
+
+    storage.checkpoint();
+    storage.checkpoint();
+    storage.put(&ctx, b"k", b"v");
+    storage.commit();
+    assert_eq!(storage.get(&ctx, b"k"), Some(b"v".to_vec()));
+
+    // Reverting the parent also undoes the committed child
+    storage.revert();
+    assert_eq!(storage.get(&ctx, b"k"), None);
+}
+
+/// PUSHDATA1 value, PUSHDATA1 key, SYSCALL System.Storage.Put
+fn storage_put(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut script = vec![0x0C, value.len() as u8];
+    script.extend_from_slice(value);
+    script.extend_from_slice(&[0x0C, key.len() as u8]);
+    script.extend_from_slice(key);
+    script.extend_from_slice(&[0x41, 0x11, 0x00, 0x00, 0x00]);
+    script
+}
+
+#[test]
+fn test_write_set_is_net_effect() {
+    let ctx = StorageContext::default();
+    let mut storage: TrackedStorage = [(vec![0u8; 21], b"x".to_vec())]
+        .into_iter()
+        .collect::<MemoryStorage>()
+        .into();
+    storage.put(&ctx, &[0], b"y");
+    storage.put(&ctx, &[0], b"x");
+    storage.put(&ctx, b"a", b"1");
+    storage.put(&ctx, b"a", b"2");
+    storage.put(&ctx, b"b", b"3");
+    storage.delete(&ctx, b"b");
+
+    let mut key_a = vec![0u8; 20];
+    key_a.push(b'a');
+    assert_eq!(storage.write_set(), vec![(key_a, Some(b"2".to_vec()))]);
+}
+
+#[test]
+fn test_vm_storage_syscalls() {
+    let mut script = storage_put(b"k", b"v");
+    // PUSHDATA1 "k", SYSCALL System.Storage.Get, RET
+    script.extend_from_slice(&[0x0C, 0x01, b'k', 0x41, 0x10, 0x00, 0x00, 0x00, 0x40]);
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script).unwrap();
+    vm.run();
+
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(
+        vm.eval_stack.pop(),
+        Some(StackItem::ByteString(b"v".to_vec().into()))
+    );
+    assert_eq!(vm.storage.checkpoint_depth(), 0);
+    assert_eq!(vm.storage.changes().len(), 1);
+}
+
+#[test]
+fn test_vm_fault_reverts_storage() {
+    let mut script = storage_put(b"k", b"v");
+    // PUSH1, PUSH0, DIV
+    script.extend_from_slice(&[0x11, 0x10, 0xA1]);
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script).unwrap();
+    vm.run();
+
+    assert!(matches!(vm.state, VMState::Fault));
+    assert_eq!(vm.storage.get(&vm.storage_context, b"k"), None);
+    assert!(vm.storage.changes().is_empty());
+}
+
+#[test]
+fn test_vm_call_commits_callee_storage() {
+    // CALL +3, RET, <callee: put k=v, RET>
+    let mut script = vec![0x34, 0x03, 0x40];
+    script.extend(storage_put(b"k", b"v"));
+    script.push(0x40);
+
+    let mut vm = NeoVM::new(1_000_000);
+    vm.load_script(script).unwrap();
+    vm.execute_next().unwrap();
+    assert_eq!(vm.storage.checkpoint_depth(), 2);
+
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt));
+    assert_eq!(vm.storage.checkpoint_depth(), 0);
+    assert_eq!(
+        vm.storage.get(&vm.storage_context, b"k"),
+        Some(b"v".to_vec())
+    );
+}
+
+// ============================================================================
+// Trie Storage
+// ============================================================================
+
+#[test]
+fn test_trie_root_is_order_independent() {
+    let ctx = StorageContext::default();
+    let entries: [(&[u8], &[u8]); 4] = [(b"a", b"1"), (b"ab", b"2"), (b"abc", b"3"), (b"b", b"4")];
+
+    let mut forward = TrieStorage::new();
+    for (k, v) in entries {
+        forward.put(&ctx, k, v);
+    }
+    let mut backward = TrieStorage::new();
+    for (k, v) in entries.iter().rev() {
+        backward.put(&ctx, k, v);
+    }
+
+    assert_ne!(forward.root_hash(), [0u8; 32]);
+    assert_eq!(forward.root_hash(), backward.root_hash());
+}
+
+#[test]
+fn test_trie_delete_restores_root() {
+    let ctx = StorageContext::default();
+    let mut storage = TrieStorage::new();
+    storage.put(&ctx, b"key1", b"value1");
+    let root = storage.root_hash();
+
+    storage.put(&ctx, b"key2", b"value2");
+    assert_ne!(storage.root_hash(), root);
+
+    storage.delete(&ctx, b"key2");
+    assert_eq!(storage.root_hash(), root);
+
+    storage.delete(&ctx, b"key1");
+    assert_eq!(storage.root_hash(), [0u8; 32]);
+}
+
+#[test]
+fn test_trie_root_matches_after_mixed_updates() {
+    let ctx = StorageContext::default();
+    let mut updated = TrieStorage::new();
+    let mut fresh = TrieStorage::new();
+    for i in 0u16..200 {
+        let key = (i * 37).to_be_bytes();
+        updated.put(&ctx, &key, &i.to_le_bytes());
+        if i % 3 == 0 {
+            fresh.put(&ctx, &key, &i.to_le_bytes());
+        }
+    }
+    for i in 0u16..200 {
+        if i % 3 != 0 {
+            updated.delete(&ctx, &(i * 37).to_be_bytes());
+        }
+    }
+
+    assert_eq!(updated.root_hash(), fresh.root_hash());
+    assert_eq!(updated.find(&ctx, &[]), fresh.find(&ctx, &[]));
+}
+
+#[test]
+fn test_trie_get_and_find() {
+    let ctx = StorageContext::default();
+    let mut storage = TrieStorage::new();
+    storage.put(&ctx, b"user:2", b"bob");
+    storage.put(&ctx, b"user:1", b"alice");
+    storage.put(&ctx, b"admin", b"root");
+
+    assert_eq!(storage.get(&ctx, b"user:1"), Some(b"alice".to_vec()));
+    assert_eq!(storage.get(&ctx, b"user:3"), None);
+    assert_eq!(
+        storage.find(&ctx, b"user:"),
+        vec![
+            (b"user:1".to_vec(), b"alice".to_vec()),
+            (b"user:2".to_vec(), b"bob".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn test_trie_inclusion_and_exclusion_proofs() {
+    let ctx = StorageContext::default();
+    let mut storage = TrieStorage::new();
+    for i in 0u8..20 {
+        storage.put(&ctx, &[i, i], &[i]);
+    }
+    let root = storage.root_hash();
+
+    let inclusion = storage.prove(&ctx, &[5, 5]);
+    assert_eq!(inclusion.value, Some(vec![5]));
+    assert!(inclusion.verify(root));
+
+    let exclusion = storage.prove(&ctx, &[5, 6]);
+    assert_eq!(exclusion.value, None);
+    assert!(exclusion.verify(root));
+
+    let mut forged = inclusion.clone();
+    forged.value = Some(vec![6]);
+    assert!(!forged.verify(root));
+
+    let mut hidden = inclusion;
+    hidden.value = None;
+    assert!(!hidden.verify(root));
+}
+
+// ============================================================================
+// Persistent Storage
+// ============================================================================
+
+#[cfg(feature = "persistent")]
+mod persistent {
+    use neo_vm_core::{PersistentStorage, StorageBackend, StorageContext};
+
+    fn ctx() -> StorageContext {
+        StorageContext {
+            script_hash: [7u8; 20],
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_persistent_commit_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("neo-storage-{}", std::process::id()));
+        {
+            let mut storage = PersistentStorage::open(&dir).unwrap();
+            storage.put(&ctx(), b"balance", b"100");
+            assert!(storage.has_pending());
+            storage.commit().unwrap();
+        }
+
+        let storage = PersistentStorage::open(&dir).unwrap();
+        assert_eq!(storage.get(&ctx(), b"balance"), Some(b"100".to_vec()));
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_persistent_rollback_discards_changes() {
+        let mut storage = PersistentStorage::temporary().unwrap();
+        storage.put(&ctx(), b"a", b"1");
+        storage.commit().unwrap();
+
+        storage.put(&ctx(), b"a", b"2");
+        storage.delete(&ctx(), b"a");
+        assert_eq!(storage.get(&ctx(), b"a"), None);
+
+        storage.rollback();
+        assert_eq!(storage.get(&ctx(), b"a"), Some(b"1".to_vec()));
+    }
+
+    #[test]
+    fn test_persistent_find_merges_pending() {
+        let mut storage = PersistentStorage::temporary().unwrap();
+        storage.put(&ctx(), b"user:1", b"alice");
+        storage.put(&ctx(), b"user:2", b"bob");
+        storage.commit().unwrap();
+
+        storage.delete(&ctx(), b"user:1");
+        storage.put(&ctx(), b"user:3", b"carol");
+
+        let found = storage.find(&ctx(), b"user:");
+        assert_eq!(
+            found,
+            vec![
+                (b"user:2".to_vec(), b"bob".to_vec()),
+                (b"user:3".to_vec(), b"carol".to_vec()),
+            ]
+        );
+    }
+}
@@ -277,6 +277,67 @@ mod comparison_tests {
 
         assert_eq!(vm.eval_stack.pop(), Some(StackItem::Boolean(true)));
     }
+
+    /// Two operand pushes and the expected `EQUAL` and `NUMEQUAL` results
+    type Vector<'a> = (&'a [u8], &'a [u8], Option<bool>, Option<bool>);
+
+    /// Result of `a, b, op` on its own, `None` if it faults
+    fn compare(a: &[u8], b: &[u8], op: u8) -> Option<bool> {
+        let mut vm = NeoVM::new(10_000_000);
+        let _ = vm.load_script([a, b, &[op, 0x40]].concat());
+        vm.run();
+        match (&vm.state, vm.eval_stack.as_slice()) {
+            (VMState::Halt, [StackItem::Boolean(result)]) => Some(*result),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_equal_vs_numequal() {
+        const PUSH0: &[u8] = &[0x10];
+        const PUSH1: &[u8] = &[0x11];
+        const PUSHT: &[u8] = &[0x08];
+        const PUSHF: &[u8] = &[0x09];
+        const PUSHNULL: &[u8] = &[0x0B];
+        const BYTES_01: &[u8] = &[0x0C, 0x01, 0x01];
+        const BYTES_AB: &[u8] = &[0x0C, 0x02, b'a', b'b'];
+
+        let vectors: &[Vector] = &[
+            (PUSH1, PUSH1, Some(true), Some(true)),
+            (PUSH1, PUSH0, Some(false), Some(false)),
+            // EQUAL never converts between types, NUMEQUAL does
+            (PUSH1, PUSHT, Some(false), Some(true)),
+            (PUSH0, PUSHF, Some(false), Some(true)),
+            (PUSHT, PUSHT, Some(true), Some(true)),
+            // Byte strings compare by contents, but are not integers
+            (PUSH1, BYTES_01, Some(false), None),
+            (BYTES_01, BYTES_01, Some(true), None),
+            (BYTES_AB, BYTES_01, Some(false), None),
+            (PUSHNULL, PUSHNULL, Some(true), None),
+            (PUSHNULL, PUSH0, Some(false), None),
+        ];
+        for &(a, b, equal, numequal) in vectors {
+            assert_eq!(compare(a, b, 0x97), equal, "{a:02x?} EQUAL {b:02x?}");
+            assert_eq!(compare(a, b, 0xB3), numequal, "{a:02x?} NUMEQUAL {b:02x?}");
+            assert_eq!(compare(a, b, 0x98), equal.map(|e| !e));
+            assert_eq!(compare(a, b, 0xB4), numequal.map(|e| !e));
+        }
+    }
+
+    #[test]
+    fn test_equal_comparable_size() {
+        // PUSHDATA4 <len bytes>, DUP
+        let push = |len: usize| {
+            let mut script = vec![0x0E];
+            script.extend_from_slice(&(len as u32).to_le_bytes());
+            script.resize(script.len() + len, 0xAB);
+            script.push(0x4A);
+            script
+        };
+        let max = neo_vm_core::stack_item::MAX_COMPARABLE_SIZE;
+        assert_eq!(compare(&push(max), &[], 0x97), Some(true));
+        assert_eq!(compare(&push(max + 1), &[], 0x97), None);
+    }
 }
 
 #[cfg(test)]
@@ -325,7 +386,14 @@ mod bitwise_tests {
 
 #[cfg(test)]
 mod array_tests {
-    use neo_vm_core::{NeoVM, StackItem, VMState};
+    use neo_vm_core::{NeoVM, StackItem, VMLimits, VMState};
+
+    fn stack_limit(max_stack: usize) -> VMLimits {
+        VMLimits {
+            max_stack,
+            ..VMLimits::default()
+        }
+    }
 
     #[test]
     fn test_newarray0() {
@@ -351,6 +419,93 @@ mod array_tests {
         assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(3)));
     }
 
+    fn run(script: Vec<u8>) -> NeoVM {
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(script);
+        vm.run();
+        vm
+    }
+
+    #[test]
+    fn test_map_keeps_insertion_order() {
+        // NEWMAP, {3: 30}, {1: 10}, {3: 33}, DUP, KEYS, SWAP, VALUES, RET
+        let vm = run(vec![
+            0xC8, 0x13, 0x00, 30, 0xD0, 0x11, 0x00, 10, 0xD0, 0x13, 0x00, 33, 0xD0, 0x4A, 0xCC,
+            0x50, 0xCD, 0x40,
+        ]);
+        assert!(matches!(vm.state, VMState::Halt));
+        // Overwriting a key keeps its position
+        assert_eq!(
+            vm.eval_stack,
+            vec![
+                StackItem::Array(vec![StackItem::Integer(3), StackItem::Integer(1)]),
+                StackItem::Array(vec![StackItem::Integer(33), StackItem::Integer(10)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_haskey() {
+        // NEWMAP, {1: 2}, DUP, PUSH1, HASKEY
+        let vm = run(vec![0xC8, 0x11, 0x12, 0xD0, 0x4A, 0x11, 0xCB, 0x40]);
+        assert_eq!(vm.eval_stack.last(), Some(&StackItem::Boolean(true)));
+
+        // The byte string 0x01 is a different key from the integer 1
+        let vm = run(vec![0xC8, 0x11, 0x12, 0xD0, 0x0C, 0x01, 0x01, 0xCB, 0x40]);
+        assert_eq!(vm.eval_stack.last(), Some(&StackItem::Boolean(false)));
+
+        // PUSH2, NEWARRAY, PUSH1, HASKEY
+        let vm = run(vec![0x12, 0xC3, 0x11, 0xCB, 0x40]);
+        assert_eq!(vm.eval_stack.last(), Some(&StackItem::Boolean(true)));
+    }
+
+    #[test]
+    fn test_compound_map_key_faults() {
+        // NEWMAP, NEWARRAY0, PUSH1, SETITEM
+        let vm = run(vec![0xC8, 0xC2, 0x11, 0xD0, 0x40]);
+        assert!(matches!(vm.state, VMState::Fault));
+        // NEWMAP, PUSHNULL, HASKEY
+        let vm = run(vec![0xC8, 0x0B, 0xCB, 0x40]);
+        assert!(matches!(vm.state, VMState::Fault));
+    }
+
+    #[test]
+    fn test_struct_equality() {
+        // Two structs built apart are equal, element by element
+        let vm = run(vec![0x12, 0xC6, 0x12, 0xC6, 0x97, 0x40]);
+        assert_eq!(vm.eval_stack, vec![StackItem::Boolean(true)]);
+
+        // A struct never equals an array with the same elements
+        let vm = run(vec![0x12, 0xC6, 0x12, 0xC3, 0x97, 0x40]);
+        assert_eq!(vm.eval_stack, vec![StackItem::Boolean(false)]);
+
+        // PUSH3, NEWSTRUCT, DUP, EQUAL visits four items, more than the limit
+        let mut vm = NeoVM::with_limits(1_000_000, stack_limit(3));
+        let _ = vm.load_script(vec![0x13, 0xC6, 0x4A, 0x97, 0x40]);
+        vm.run();
+        assert!(matches!(vm.state, VMState::Fault));
+    }
+
+    #[test]
+    fn test_struct_copied_on_append() {
+        // NEWARRAY0, PUSH2, NEWSTRUCT, APPEND
+        let script = vec![0xC2, 0x12, 0xC6, 0xCF, 0x40];
+        let vm = run(script.clone());
+        assert_eq!(
+            vm.eval_stack,
+            vec![StackItem::Array(vec![StackItem::Struct(vec![
+                StackItem::Null,
+                StackItem::Null
+            ])])]
+        );
+
+        // The copy needs room for the struct and both of its elements
+        let mut vm = NeoVM::with_limits(1_000_000, stack_limit(2));
+        let _ = vm.load_script(script);
+        vm.run();
+        assert!(matches!(vm.state, VMState::Fault));
+    }
+
     #[test]
     fn test_isnull() {
         let mut vm = NeoVM::new(1_000_000);
@@ -391,6 +546,66 @@ mod control_flow_tests {
 
         assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(5)));
     }
+
+    /// `op` with a 4-byte `offset`
+    fn long(op: u8, offset: i32) -> Vec<u8> {
+        let mut bytes = vec![op];
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes
+    }
+
+    fn run(script: Vec<u8>) -> NeoVM {
+        let mut vm = NeoVM::new(1_000_000);
+        vm.load_script(script).unwrap();
+        vm.run();
+        vm
+    }
+
+    #[test]
+    fn test_jmp_l_beyond_short_range() {
+        // JMP_L over 200 PUSH1s, PUSH5, RET
+        let mut script = long(0x23, 205);
+        script.extend_from_slice(&[0x11; 200]);
+        script.extend_from_slice(&[0x15, 0x40]);
+
+        let vm = run(script);
+        assert!(matches!(vm.state, VMState::Halt));
+        assert_eq!(vm.eval_stack, [StackItem::Integer(5)]);
+    }
+
+    #[test]
+    fn test_conditional_long_jumps() {
+        // PUSH3, loop: DEC, DUP, JMPIF_L loop, RET
+        let mut script = vec![0x13, 0x9D, 0x4A];
+        script.extend(long(0x25, -2));
+        script.push(0x40);
+        let vm = run(script);
+        assert_eq!(vm.eval_stack, [StackItem::Integer(0)]);
+
+        // PUSH1, PUSH2, JMPLT_L +6 over PUSH9, PUSH5, RET
+        let mut script = vec![0x11, 0x12];
+        script.extend(long(0x31, 6));
+        script.extend_from_slice(&[0x19, 0x15, 0x40]);
+        let vm = run(script);
+        assert_eq!(vm.eval_stack, [StackItem::Integer(5)]);
+    }
+
+    #[test]
+    fn test_call_l() {
+        // CALL_L +6, RET, PUSH7, RET
+        let mut script = long(0x35, 6);
+        script.extend_from_slice(&[0x40, 0x17, 0x40]);
+        let vm = run(script);
+        assert!(matches!(vm.state, VMState::Halt));
+        assert_eq!(vm.eval_stack.last(), Some(&StackItem::Integer(7)));
+    }
+
+    #[test]
+    fn test_long_jump_out_of_bounds_faults() {
+        assert!(matches!(run(long(0x23, 100)).state, VMState::Fault));
+        assert!(matches!(run(long(0x35, -1)).state, VMState::Fault));
+        assert!(matches!(run(long(0x23, i32::MIN)).state, VMState::Fault));
+    }
 }
 
 #[cfg(test)]
@@ -408,6 +623,78 @@ mod pushdata_tests {
 
         assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(127)));
     }
+
+    fn run(script: Vec<u8>) -> NeoVM {
+        let mut vm = NeoVM::new(1_000_000);
+        vm.load_script(script).unwrap();
+        vm.run();
+        vm
+    }
+
+    #[test]
+    fn test_pushint_family() {
+        let mut script = vec![0x02];
+        script.extend_from_slice(&(-100_000i32).to_le_bytes());
+        script.push(0x03);
+        script.extend_from_slice(&i64::MIN.to_le_bytes());
+        script.push(0x04);
+        script.extend_from_slice(&i128::MAX.to_le_bytes());
+        // PUSHINT256 -2, sign-extended to 32 bytes
+        script.push(0x05);
+        script.extend_from_slice(&[0xFE; 1]);
+        script.extend_from_slice(&[0xFF; 31]);
+        script.extend_from_slice(&[0x08, 0x09]); // PUSHT, PUSHF
+
+        let vm = run(script);
+        assert!(matches!(vm.state, VMState::Halt));
+        assert_eq!(
+            vm.eval_stack,
+            [
+                StackItem::Integer(-100_000),
+                StackItem::Integer(i64::MIN as i128),
+                StackItem::Integer(i128::MAX),
+                StackItem::Integer(-2),
+                StackItem::Boolean(true),
+                StackItem::Boolean(false),
+            ]
+        );
+
+        // 2^128 does not fit the VM's integers
+        let mut script = vec![0x05];
+        script.extend_from_slice(&[0; 16]);
+        script.push(0x01);
+        script.extend_from_slice(&[0; 15]);
+        assert!(matches!(run(script).state, VMState::Fault));
+    }
+
+    #[test]
+    fn test_pushdata4() {
+        let mut script = vec![0x0E];
+        script.extend_from_slice(&300u32.to_le_bytes());
+        script.extend_from_slice(&[0xAB; 300]);
+
+        let vm = run(script);
+        assert!(matches!(vm.state, VMState::Halt));
+        assert_eq!(
+            vm.eval_stack,
+            [StackItem::ByteString(vec![0xAB; 300].into())]
+        );
+    }
+
+    #[test]
+    fn test_pusha() {
+        // NOP, PUSHA -1, RET: points back at the NOP
+        let mut script = vec![0x21, 0x0A];
+        script.extend_from_slice(&(-1i32).to_le_bytes());
+        script.push(0x40);
+        let vm = run(script);
+        assert_eq!(vm.eval_stack, [StackItem::Pointer(0)]);
+
+        // Pointing past the end of the script
+        let mut script = vec![0x0A];
+        script.extend_from_slice(&6i32.to_le_bytes());
+        assert!(matches!(run(script).state, VMState::Fault));
+    }
 }
 
 #[cfg(test)]
@@ -418,7 +705,7 @@ mod slot_tests {
     fn test_initslot() {
         let mut vm = NeoVM::new(1_000_000);
         // PUSH5, INITSLOT(1 local, 1 arg), LDARG0, RET
-        let _ = vm.load_script(vec![0x15, 0x57, 0x01, 0x01, 0x74, 0x40]);
+        let _ = vm.load_script(vec![0x15, 0x57, 0x01, 0x01, 0x78, 0x40]);
 
         while !matches!(vm.state, VMState::Halt | VMState::Fault) {
             vm.execute_next().unwrap();
@@ -427,3 +714,101 @@ mod slot_tests {
         assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(5)));
     }
 }
+
+#[cfg(test)]
+mod snapshot_tests {
+    use neo_vm_core::{NeoVM, StackItem, VMState};
+
+    #[test]
+    fn test_restore_rewinds_execution() {
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(vec![0x12, 0x13, 0x9E, 0x40]); // PUSH2, PUSH3, ADD, RET
+
+        vm.execute_next().unwrap();
+        let snapshot = vm.snapshot();
+        vm.run();
+        assert!(matches!(vm.state, VMState::Halt));
+
+        vm.restore(&snapshot);
+        assert!(matches!(vm.state, VMState::None));
+        assert_eq!(vm.eval_stack, vec![StackItem::Integer(2)]);
+        assert_eq!(vm.gas_consumed, snapshot.gas_consumed);
+        assert_eq!(vm.invocation_stack[0].ip, 1);
+
+        vm.run();
+        assert_eq!(vm.eval_stack.pop(), Some(StackItem::Integer(5)));
+    }
+
+    #[test]
+    fn test_snapshot_explores_alternative_branch() {
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(vec![0x12, 0x13, 0x40]); // PUSH2, PUSH3, RET
+
+        vm.execute_next().unwrap();
+        vm.execute_next().unwrap();
+        let checkpoint = vm.snapshot();
+
+        vm.eval_stack.push(StackItem::Integer(10));
+        vm.run();
+        assert_eq!(vm.eval_stack.len(), 3);
+
+        vm.restore(&checkpoint);
+        vm.run();
+        assert_eq!(vm.eval_stack.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod limit_tests {
+    use neo_vm_core::{CancellationToken, NeoVM, RunOutcome, VMState};
+    use std::time::{Duration, Instant};
+
+    // JMP 0: loops forever within any gas budget
+    const INFINITE_LOOP: [u8; 2] = [0x22, 0x00];
+
+    #[test]
+    fn test_run_with_step_limit() {
+        let mut vm = NeoVM::new(u64::MAX);
+        let _ = vm.load_script(INFINITE_LOOP.to_vec());
+
+        assert_eq!(vm.run_with_limits(100, None), RunOutcome::StepLimit);
+        assert!(matches!(vm.state, VMState::None));
+        let gas = vm.gas_consumed;
+
+        // Resumable after hitting the limit
+        assert_eq!(vm.run_with_limits(100, None), RunOutcome::StepLimit);
+        assert_eq!(vm.gas_consumed, gas * 2);
+    }
+
+    #[test]
+    fn test_run_with_deadline() {
+        let mut vm = NeoVM::new(u64::MAX);
+        let _ = vm.load_script(INFINITE_LOOP.to_vec());
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        assert_eq!(
+            vm.run_with_limits(u64::MAX, Some(deadline)),
+            RunOutcome::Deadline
+        );
+    }
+
+    #[test]
+    fn test_run_with_cancellation() {
+        let mut vm = NeoVM::new(u64::MAX);
+        let _ = vm.load_script(INFINITE_LOOP.to_vec());
+        let token = CancellationToken::new();
+        vm.cancellation = Some(token.clone());
+
+        token.cancel();
+        assert_eq!(vm.run_with_limits(u64::MAX, None), RunOutcome::Cancelled);
+        assert_eq!(vm.gas_consumed, 0);
+    }
+
+    #[test]
+    fn test_run_with_limits_halts() {
+        let mut vm = NeoVM::new(1_000_000);
+        let _ = vm.load_script(vec![0x12, 0x13, 0x9E, 0x40]);
+
+        assert_eq!(vm.run_with_limits(100, None), RunOutcome::Halt);
+    }
+}
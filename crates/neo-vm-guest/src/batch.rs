@@ -0,0 +1,84 @@
+//! Parallel execution of independent scripts
+//!
+//! Meant for pre-simulating many inputs before choosing which to prove. Each
+//! input runs on its own VM and storage, so the scripts must not depend on
+//! each other's writes.
+
+use crate::{execute_with_step_limit, ProofInput, ProofOutput};
+use rayon::prelude::*;
+
+/// Execute every input on the rayon thread pool
+///
+/// Outputs are in input order, each bounded by its input's gas limit.
+pub fn execute_batch(inputs: Vec<ProofInput>) -> Vec<ProofOutput> {
+    execute_batch_with_step_limit(inputs, u64::MAX)
+}
+
+/// Like [`execute_batch`], also faulting each script after `max_steps`
+/// instructions
+pub fn execute_batch_with_step_limit(inputs: Vec<ProofInput>, max_steps: u64) -> Vec<ProofOutput> {
+    inputs
+        .into_par_iter()
+        .map(|input| execute_with_step_limit(input, max_steps))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute;
+    use neo_vm_core::{
+        BlockContext, GasSchedule, OracleResponses, RuntimeConfig, StackItem, VMLimits,
+    };
+
+    fn input(script: Vec<u8>, gas_limit: u64) -> ProofInput {
+        ProofInput {
+            script,
+            arguments: vec![],
+            gas_limit,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        }
+    }
+
+    #[test]
+    fn test_batch_matches_sequential() {
+        let inputs: Vec<ProofInput> = (0..64u8)
+            .map(|i| input(vec![0x00, i, 0x13, 0x9E, 0x40], 1_000_000)) // i + 3
+            .collect();
+        let outputs = execute_batch(inputs.clone());
+
+        assert_eq!(outputs.len(), inputs.len());
+        for (i, (input, output)) in inputs.into_iter().zip(&outputs).enumerate() {
+            assert_eq!(output.result, Some(StackItem::Integer(i as i128 + 3)));
+            assert_eq!(output.output_hash(), execute(input).output_hash());
+        }
+    }
+
+    #[test]
+    fn test_batch_limits_per_task() {
+        // JMP 0: loops forever
+        let endless = vec![0x22, 0x00];
+        let outputs = execute_batch_with_step_limit(
+            vec![
+                input(vec![0x12, 0x13, 0x9E, 0x40], 1_000_000),
+                input(endless.clone(), 1_000_000_000),
+                input(endless, 1_000),
+            ],
+            10_000,
+        );
+
+        assert_eq!(outputs[0].state, 0);
+        assert_eq!(outputs[1].state, 1);
+        assert_eq!(outputs[1].error.as_deref(), Some("Step limit exceeded"));
+        assert_eq!(outputs[2].state, 1);
+        assert_eq!(outputs[2].error, None);
+    }
+}
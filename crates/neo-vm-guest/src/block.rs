@@ -0,0 +1,67 @@
+//! Neo N3 block headers
+//!
+//! Only the hashed part of the header is modelled; the consensus witness is
+//! not part of the hash and is checked by [`crate::light_client`].
+
+use neo_vm_core::BlockContext;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub version: u32,
+    pub prev_hash: [u8; 32],
+    /// [`merkle_root`] of the hashes of the block's transactions
+    pub merkle_root: [u8; 32],
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub index: u32,
+    pub primary_index: u8,
+    pub next_consensus: [u8; 20],
+}
+
+impl BlockHeader {
+    /// Index and timestamp the block's transactions run at
+    pub fn block_context(&self) -> BlockContext {
+        BlockContext {
+            index: self.index,
+            timestamp: self.timestamp,
+        }
+    }
+
+    /// Serialization without the witness, the data the hash covers
+    pub fn unsigned_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(105);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.prev_hash);
+        out.extend_from_slice(&self.merkle_root);
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&self.index.to_le_bytes());
+        out.push(self.primary_index);
+        out.extend_from_slice(&self.next_consensus);
+        out
+    }
+
+    /// Block hash: SHA-256 of the unsigned serialization
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.unsigned_bytes()).into()
+    }
+
+    /// Message signed by the consensus nodes on `network`: the network magic
+    /// followed by the block hash
+    pub fn sign_data(&self, network: u32) -> Vec<u8> {
+        let mut data = network.to_le_bytes().to_vec();
+        data.extend_from_slice(&self.hash());
+        data
+    }
+}
+
+/// Merkle root of transaction hashes, as computed by Neo
+///
+/// Parents are the double SHA-256 of their concatenated children, and the
+/// last node of an odd level is paired with itself. An empty list has a zero
+/// root. See [`neo_vm_core::merkle`] for proofs.
+pub fn merkle_root(hashes: &[[u8; 32]]) -> [u8; 32] {
+    neo_vm_core::merkle::root(hashes)
+}
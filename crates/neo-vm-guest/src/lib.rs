@@ -1,7 +1,25 @@
 //! Neo VM Guest Program for zkVM proving
 
-use neo_vm_core::{NeoVM, StackItem, VMState};
+#[cfg(feature = "parallel")]
+mod batch;
+pub mod block;
+pub mod light_client;
+pub mod storage_witness;
+pub mod tx;
+
+#[cfg(feature = "parallel")]
+pub use batch::{execute_batch, execute_batch_with_step_limit};
+pub use storage_witness::{StorageWitness, StorageWitnessError, WitnessedStorage};
+
+use neo_vm_core::{
+    BlockContext, FaultCode, GasSchedule, MemoryStorage, NativeRegistry, NeoVM, NotificationEvent,
+    OpCategory, OracleResponses, RunOutcome, RuntimeConfig, StackItem, TrackedStorage, VMLimits,
+    VMState,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 /// Input for zkVM proving
 #[derive(Serialize, Deserialize, Clone)]
@@ -9,48 +27,241 @@ pub struct ProofInput {
     pub script: Vec<u8>,
     pub arguments: Vec<StackItem>,
     pub gas_limit: u64,
+    /// Initial storage as full backend keys (script hash followed by the key)
+    #[serde(default)]
+    pub storage: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Caller-chosen value the proof commits to as `context`, such as a
+    /// request nonce or domain separator; zero when unused
+    #[serde(default)]
+    pub context: [u8; 32],
+    /// Prices execution is charged at, committed in the public inputs
+    #[serde(default)]
+    pub gas_schedule: GasSchedule,
+    /// Network, trigger and random seed seen by the `System.Runtime`
+    /// syscalls, committed in the public inputs
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+    /// State root the storage witnesses are proven against; when set,
+    /// `storage` must be empty and only witnessed keys can be touched, see
+    /// [`storage_witness`]
+    #[serde(default)]
+    pub pre_state_root: Option<[u8; 32]>,
+    #[serde(default)]
+    pub storage_witnesses: Vec<StorageWitness>,
+    /// Stack, script, item size and step limits, enforced the same way by
+    /// the guest program
+    #[serde(default)]
+    pub limits: VMLimits,
+    /// Block whose timestamp `System.Runtime.GetTime` returns, committed in
+    /// the public inputs
+    #[serde(default)]
+    pub block_context: BlockContext,
+    /// Responses the Oracle native serves, committed in the public inputs
+    #[serde(default)]
+    pub oracle_responses: OracleResponses,
+}
+
+impl ProofInput {
+    /// Hash committed as `input_hash` in the public inputs
+    ///
+    /// SHA-256 over, in order: the `u32` script length and script,
+    /// [`hash_arguments`], the `u64` gas limit, the `u32` storage entry count
+    /// and each entry as `u32` key length, key, `u32` value length and value,
+    /// then `tx_hash`, `context`, the gas schedule id byte, the `u32` network
+    /// magic, the trigger id byte and the random seed, then a byte that is 1
+    /// when there is a pre-state root followed by the root, or 0 without
+    /// one, [`VMLimits::encode`], the `u32` block index and `u64` timestamp
+    /// and [`OracleResponses::commitment`]. Every field but the root always
+    /// has the same size, so no two inputs share a preimage. Integers are
+    /// little-endian. The guest program computes the same value.
+    ///
+    /// Storage witnesses are not hashed: they can only prove the values the
+    /// root commits to.
+    pub fn input_hash(&self, tx_hash: [u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update((self.script.len() as u32).to_le_bytes());
+        hasher.update(&self.script);
+        hasher.update(hash_arguments(&self.arguments));
+        hasher.update(self.gas_limit.to_le_bytes());
+        hasher.update((self.storage.len() as u32).to_le_bytes());
+        for (key, value) in &self.storage {
+            hasher.update((key.len() as u32).to_le_bytes());
+            hasher.update(key);
+            hasher.update((value.len() as u32).to_le_bytes());
+            hasher.update(value);
+        }
+        hasher.update(tx_hash);
+        hasher.update(self.context);
+        hasher.update([self.gas_schedule.id()]);
+        hasher.update(self.runtime.network.to_le_bytes());
+        hasher.update([self.runtime.trigger.id()]);
+        hasher.update(self.runtime.random_seed);
+        match self.pre_state_root {
+            Some(root) => {
+                hasher.update([1]);
+                hasher.update(root);
+            }
+            None => hasher.update([0]),
+        }
+        hasher.update(self.limits.encode());
+        hasher.update(self.block_context.index.to_le_bytes());
+        hasher.update(self.block_context.timestamp.to_le_bytes());
+        hasher.update(self.oracle_responses.commitment());
+        hasher.finalize().into()
+    }
+
+    /// Native contracts as this input configures them: the Ledger reports
+    /// [`block_context`](Self::block_context) and the Oracle serves
+    /// [`oracle_responses`](Self::oracle_responses)
+    ///
+    /// StdLib and CryptoLib are left out, as the guest program cannot prove
+    /// calls to them.
+    pub fn natives(&self) -> NativeRegistry {
+        NativeRegistry::with_time_provider(Arc::new(self.block_context))
+            .with_oracle_responses(Arc::new(self.oracle_responses.clone()))
+            .without_host_only()
+    }
+}
+
+/// Canonical encoding of script arguments
+///
+/// The little-endian `u32` argument count followed by each argument's
+/// [`StackItem::serialize_canonical`] encoding: a type tag, then the value,
+/// with byte strings and containers prefixed by their `u32` length. Unlike
+/// bincode the layout is fixed, so other implementations can reproduce it.
+pub fn encode_arguments(arguments: &[StackItem]) -> Vec<u8> {
+    let mut out = (arguments.len() as u32).to_le_bytes().to_vec();
+    for argument in arguments {
+        argument.write_canonical(&mut out);
+    }
+    out
+}
+
+/// SHA-256 of [`encode_arguments`]
+pub fn hash_arguments(arguments: &[StackItem]) -> [u8; 32] {
+    Sha256::digest(encode_arguments(arguments)).into()
 }
 
 /// Output from zkVM execution
+///
+/// Serializes to the JSON format described in [`neo_vm_core::json`].
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ProofOutput {
     pub state: u8,
     pub result: Option<StackItem>,
     pub gas_consumed: u64,
     pub error: Option<String>,
+    /// Where the VM faulted, when it did
+    #[serde(default)]
+    pub fault: Option<FaultCode>,
+    /// Net storage writes, sorted by full key; `None` marks a deletion
+    #[serde(default, with = "neo_vm_core::json::storage_writes")]
+    pub storage_writes: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    /// Events sent with `System.Runtime.Notify`, in order
+    ///
+    /// Reported by the host run; the public inputs do not commit to them.
+    #[serde(default)]
+    pub notifications: Vec<NotificationEvent>,
+    /// `System.Runtime.GetRandom` calls made, see [`neo_vm_core::runtime`]
+    #[serde(default)]
+    pub random_draws: u32,
+    /// `gas_consumed` by [`OpCategory`], leaving out categories that used
+    /// none; `None` when the script did not run
+    ///
+    /// Reported by the host run; the public inputs do not commit to it.
+    #[serde(default)]
+    pub gas_breakdown: Option<BTreeMap<OpCategory, u64>>,
+}
+
+impl ProofOutput {
+    /// Hash committed as `output_hash` in the public inputs
+    ///
+    /// SHA-256 of the canonical encoding of the result item, or of the empty
+    /// string when execution left nothing on the stack. The guest program
+    /// computes the same value from its final stack.
+    pub fn output_hash(&self) -> [u8; 32] {
+        let bytes = self
+            .result
+            .as_ref()
+            .map(StackItem::serialize_canonical)
+            .unwrap_or_default();
+        Sha256::digest(&bytes).into()
+    }
+
+    /// Hash committed as `write_set_hash` in the public inputs
+    ///
+    /// SHA-256 over each write in order: the little-endian `u32` key length and
+    /// key, then `0x00` for a deletion or `0x01` followed by the `u32` value
+    /// length and value.
+    pub fn write_set_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for (key, value) in &self.storage_writes {
+            hasher.update((key.len() as u32).to_le_bytes());
+            hasher.update(key);
+            match value {
+                Some(value) => {
+                    hasher.update([0x01]);
+                    hasher.update((value.len() as u32).to_le_bytes());
+                    hasher.update(value);
+                }
+                None => hasher.update([0x00]),
+            }
+        }
+        hasher.finalize().into()
+    }
 }
 
 /// Execute Neo VM and return proof output
 pub fn execute(input: ProofInput) -> ProofOutput {
-    let mut vm = NeoVM::new(input.gas_limit);
+    execute_with_step_limit(input, u64::MAX)
+}
+
+/// Like [`execute`], but fault once `max_steps` instructions have run
+///
+/// The step limit only bounds host-side simulation; the guest program has no
+/// such limit, so outputs cut short by it cannot be proven.
+pub fn execute_with_step_limit(input: ProofInput, max_steps: u64) -> ProofOutput {
+    run(input, max_steps, None)
+}
+
+/// Like [`execute`], with `sign_data` as the script container that
+/// `System.Crypto.CheckSig` and `CheckMultisig` check signatures against
+pub fn execute_signed(input: ProofInput, sign_data: &[u8]) -> ProofOutput {
+    run(input, u64::MAX, Some(sign_data.to_vec()))
+}
+
+fn run(input: ProofInput, max_steps: u64, sign_data: Option<Vec<u8>>) -> ProofOutput {
+    let mut vm = NeoVM::with_limits(input.gas_limit, input.limits);
+    vm.sign_data = sign_data;
+    vm.gas_schedule = input.gas_schedule;
+    vm.runtime = input.runtime;
+    vm.natives = input.natives();
+    let storage = match input.witnessed_storage() {
+        Ok(Some(witnessed)) => {
+            vm.witnessed_keys = Some(witnessed.keys);
+            witnessed.entries
+        }
+        Ok(None) => input.storage,
+        Err(e) => return rejected(e.to_string()),
+    };
+    vm.storage = TrackedStorage::from(storage.into_iter().collect::<MemoryStorage>());
     if let Err(e) = vm.load_script(input.script) {
-        return ProofOutput {
-            state: 1,
-            gas_consumed: vm.gas_consumed,
-            result: Some(StackItem::Boolean(false)),
-            error: Some(e.to_string()),
-        };
+        return rejected(e.to_string());
     }
 
     // Push arguments (bypassing depth check for initial args - they should fit)
     for arg in input.arguments {
-        if vm.eval_stack.len() >= 2048 {
-            return ProofOutput {
-                state: 1,
-                gas_consumed: vm.gas_consumed,
-                result: Some(StackItem::Boolean(false)),
-                error: Some("Stack overflow".to_string()),
-            };
+        if vm.eval_stack.len() >= input.limits.max_stack {
+            return rejected("Stack overflow".to_string());
         }
         vm.eval_stack.push(arg);
     }
 
-    // Execute until halt or fault
-    while !matches!(vm.state, VMState::Halt | VMState::Fault) {
-        if vm.execute_next().is_err() {
-            vm.state = VMState::Fault;
-            break;
-        }
+    // Execute until halt, fault or the step limit
+    let mut error = None;
+    if vm.run_with_limits(max_steps, None) == RunOutcome::StepLimit {
+        vm.state = VMState::Fault;
+        error = Some("Step limit exceeded".to_string());
     }
 
     let state = match vm.state {
@@ -63,6 +274,176 @@ pub fn execute(input: ProofInput) -> ProofOutput {
         state,
         result: vm.eval_stack.pop(),
         gas_consumed: vm.gas_consumed,
-        error: None,
+        error,
+        fault: vm.fault_info.as_ref().map(FaultCode::from),
+        storage_writes: vm.storage.write_set(),
+        notifications: vm.notifications,
+        random_draws: vm.random_draws,
+        gas_breakdown: Some(vm.gas_breakdown.to_map()),
+    }
+}
+
+/// Output of an input that faulted before its script ran
+fn rejected(error: String) -> ProofOutput {
+    ProofOutput {
+        state: 1,
+        gas_consumed: 0,
+        result: Some(StackItem::Boolean(false)),
+        error: Some(error),
+        fault: None,
+        storage_writes: Vec::new(),
+        notifications: Vec::new(),
+        random_draws: 0,
+        gas_breakdown: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo_vm_core::engine::syscall;
+    use neo_vm_core::{LedgerContract, NativeContract, OpCode, OracleContract, ScriptBuilder};
+
+    #[test]
+    fn test_encode_arguments() {
+        let encoded = encode_arguments(&[
+            StackItem::Integer(1),
+            StackItem::ByteString(b"ab".to_vec().into()),
+        ]);
+        let mut expected = vec![2, 0, 0, 0, 0x21, 1];
+        expected.extend([0; 15]);
+        expected.extend([0x28, 2, 0, 0, 0, b'a', b'b']);
+        assert_eq!(encoded, expected);
+        assert_eq!(encode_arguments(&[]), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_natives_follow_input() {
+        let input: ProofInput = serde_json::from_value(serde_json::json!({
+            "script": [0x40],
+            "arguments": [],
+            "gas_limit": 1000,
+            "block_context": {"index": 7, "timestamp": 9},
+            "oracle_responses": [
+                {"url": "https://api.example/price", "filter": "$.neo", "response": "MTIuNQ=="}
+            ],
+        }))
+        .unwrap();
+        let natives = input.natives();
+        let ledger = LedgerContract::default().hash();
+        let oracle = OracleContract::default().hash();
+        let bytes = |s: &str| StackItem::ByteString(s.as_bytes().to_vec().into());
+
+        assert_eq!(
+            natives.invoke(&ledger, "currentIndex", vec![]),
+            Ok(StackItem::Integer(7))
+        );
+        assert_eq!(
+            natives.invoke(
+                &oracle,
+                "getResponse",
+                vec![bytes("https://api.example/price"), bytes("$.neo")]
+            ),
+            Ok(bytes("12.5"))
+        );
+        assert!(!natives.contains(&neo_vm_core::StdLib.hash()));
+        assert!(!natives.contains(&neo_vm_core::CryptoLib.hash()));
+    }
+
+    #[test]
+    fn test_execute_reports_input_block() {
+        let mut script = ScriptBuilder::new();
+        script
+            .emit(OpCode::PUSH15)
+            .emit_push_bytes(b"currentIndex")
+            .emit_push_bytes(&LedgerContract::default().hash())
+            .emit_syscall(syscall::SYSTEM_CONTRACT_CALL)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_GETTIME)
+            .emit(OpCode::ADD)
+            .emit(OpCode::RET);
+        let input: ProofInput = serde_json::from_value(serde_json::json!({
+            "script": script.into_bytes(),
+            "arguments": [{"type": "Array", "value": []}],
+            "gas_limit": 1_000_000,
+            "block_context": {"index": 7, "timestamp": 1000},
+        }))
+        .unwrap();
+        // Both the Ledger and GetTime see the input's block
+        let output = execute(input);
+        assert_eq!(output.result, Some(StackItem::Integer(1007)));
+    }
+
+    #[test]
+    fn test_input_hash_separates_optional_fields() {
+        let base: ProofInput = serde_json::from_value(serde_json::json!({
+            "script": [0x40],
+            "arguments": [],
+            "gas_limit": 1000,
+        }))
+        .unwrap();
+        let mut with_oracle = base.clone();
+        with_oracle
+            .oracle_responses
+            .insert("https://a", "", b"1".to_vec());
+        // Appended bare, a root equal to the oracle commitment would hash
+        // the same bytes as the commitment
+        let with_root = ProofInput {
+            pre_state_root: Some(with_oracle.oracle_responses.commitment()),
+            ..base.clone()
+        };
+        let hashes = [&base, &with_oracle, &with_root].map(|input| input.input_hash([0; 32]));
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_ne!(hashes[1], hashes[2]);
+    }
+
+    #[test]
+    fn test_proof_output_json() {
+        let output = ProofOutput {
+            state: 0,
+            result: Some(StackItem::Integer(5)),
+            gas_consumed: 42,
+            error: None,
+            fault: None,
+            storage_writes: vec![(vec![1, 2], Some(vec![3])), (vec![4], None)],
+            notifications: vec![NotificationEvent {
+                script_hash: [0xAB; 20],
+                event_name: "Transfer".to_string(),
+                state: StackItem::Array(vec![StackItem::Integer(7)]),
+            }],
+            random_draws: 2,
+            gas_breakdown: Some(BTreeMap::from([(OpCategory::Arithmetic, 40)])),
+        };
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "state": 0,
+                "result": {"type": "Integer", "value": "5"},
+                "gas_consumed": 42,
+                "error": null,
+                "fault": null,
+                "storage_writes": [
+                    {"key": "AQI=", "value": "Aw=="},
+                    {"key": "BA==", "value": null},
+                ],
+                "notifications": [{
+                    "script_hash": "ab".repeat(20),
+                    "event_name": "Transfer",
+                    "state": {"type": "Array", "value": [{"type": "Integer", "value": "7"}]},
+                }],
+                "random_draws": 2,
+                "gas_breakdown": {"arithmetic": 40},
+            })
+        );
+        let decoded: ProofOutput = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.storage_writes, output.storage_writes);
+        assert_eq!(decoded.notifications, output.notifications);
+        assert_eq!(decoded.gas_breakdown, output.gas_breakdown);
+
+        // Binary encodings keep the plain layout
+        let encoded = bincode::serialize(&output).unwrap();
+        let decoded: ProofOutput = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.storage_writes, output.storage_writes);
     }
 }
@@ -0,0 +1,200 @@
+//! Header chain verification for light clients
+//!
+//! Starting from a trusted header, every following header must link to its
+//! predecessor and carry a witness from the consensus nodes the predecessor
+//! named in `next_consensus`: a multi-signature over the header's sign data.
+//! The same check runs on the host and in the light-client guest program.
+
+use crate::block::BlockHeader;
+use crate::tx::{script_hash, Witness};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Gas available to each header witness
+pub use neo_vm_core::witness::MAX_VERIFICATION_GAS;
+
+/// A block header with its consensus witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedHeader {
+    pub header: BlockHeader,
+    pub witness: Witness,
+}
+
+/// Input of the light-client guest program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LightClientInput {
+    /// Network magic the headers were signed for
+    pub network: u32,
+    /// Header the client already trusts
+    pub trusted: BlockHeader,
+    /// Headers following `trusted`, in order
+    pub headers: Vec<SignedHeader>,
+}
+
+/// Values committed by the light-client guest program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightClientOutput {
+    pub network: u32,
+    pub trusted_hash: [u8; 32],
+    pub latest_hash: [u8; 32],
+    pub latest_index: u32,
+    /// Consensus script hash that must sign the header after the latest one
+    pub next_consensus: [u8; 20],
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum LightClientError {
+    #[error("Header {0} does not follow its predecessor")]
+    BrokenLink(u32),
+    #[error("Header {0} is not witnessed by the expected consensus nodes")]
+    WrongConsensus(u32),
+    #[error("Header {0} failed witness verification")]
+    VerificationFailed(u32),
+}
+
+/// Check that `input.headers` extend `input.trusted`
+pub fn verify_header_chain(
+    input: &LightClientInput,
+) -> Result<LightClientOutput, LightClientError> {
+    let mut latest = &input.trusted;
+    for signed in &input.headers {
+        let header = &signed.header;
+        if header.prev_hash != latest.hash() || Some(header.index) != latest.index.checked_add(1) {
+            return Err(LightClientError::BrokenLink(header.index));
+        }
+        if script_hash(&signed.witness.verification_script) != latest.next_consensus {
+            return Err(LightClientError::WrongConsensus(header.index));
+        }
+        signed
+            .witness
+            .verify(&header.sign_data(input.network), MAX_VERIFICATION_GAS)
+            .ok_or(LightClientError::VerificationFailed(header.index))?;
+        latest = header;
+    }
+
+    Ok(LightClientOutput {
+        network: input.network,
+        trusted_hash: input.trusted.hash(),
+        latest_hash: latest.hash(),
+        latest_index: latest.index,
+        next_consensus: latest.next_consensus,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::{signature::Signer as _, Signature, SigningKey};
+
+    const NETWORK: u32 = 860833102;
+
+    fn keys() -> Vec<SigningKey> {
+        (1..=3u8)
+            .map(|i| SigningKey::from_slice(&[i; 32]).unwrap())
+            .collect()
+    }
+
+    /// PUSH2, PUSHDATA1 <key> x3, PUSH3, SYSCALL System.Crypto.CheckMultisig
+    fn multisig_script(keys: &[SigningKey]) -> Vec<u8> {
+        let mut script = vec![0x12];
+        for key in keys {
+            let pubkey = key.verifying_key().to_sec1_bytes();
+            script.extend_from_slice(&[0x0C, pubkey.len() as u8]);
+            script.extend_from_slice(&pubkey);
+        }
+        script.extend_from_slice(&[0x13, 0x41, 0x21, 0x00, 0x00, 0x00]);
+        script
+    }
+
+    fn genesis(keys: &[SigningKey]) -> BlockHeader {
+        BlockHeader {
+            version: 0,
+            prev_hash: [0u8; 32],
+            merkle_root: [0u8; 32],
+            timestamp: 0,
+            nonce: 0,
+            index: 0,
+            primary_index: 0,
+            next_consensus: script_hash(&multisig_script(keys)),
+        }
+    }
+
+    /// Header after `prev`, signed by the first and last of `keys`
+    fn next(prev: &BlockHeader, keys: &[SigningKey]) -> SignedHeader {
+        let header = BlockHeader {
+            prev_hash: prev.hash(),
+            index: prev.index + 1,
+            timestamp: prev.timestamp + 15_000,
+            ..prev.clone()
+        };
+        let sign_data = header.sign_data(NETWORK);
+        let mut invocation = Vec::new();
+        for key in [&keys[0], &keys[2]] {
+            let signature: Signature = key.sign(&sign_data);
+            invocation.extend_from_slice(&[0x0C, 0x40]);
+            invocation.extend_from_slice(&signature.to_bytes());
+        }
+        SignedHeader {
+            header,
+            witness: Witness {
+                invocation_script: invocation,
+                verification_script: multisig_script(keys),
+            },
+        }
+    }
+
+    #[test]
+    fn test_valid_chain() {
+        let keys = keys();
+        let trusted = genesis(&keys);
+        let first = next(&trusted, &keys);
+        let second = next(&first.header, &keys);
+        let input = LightClientInput {
+            network: NETWORK,
+            trusted: trusted.clone(),
+            headers: vec![first, second.clone()],
+        };
+
+        let output = verify_header_chain(&input).unwrap();
+        assert_eq!(output.trusted_hash, trusted.hash());
+        assert_eq!(output.latest_hash, second.header.hash());
+        assert_eq!(output.latest_index, 2);
+    }
+
+    #[test]
+    fn test_tampered_header_rejected() {
+        let keys = keys();
+        let trusted = genesis(&keys);
+        let mut first = next(&trusted, &keys);
+        first.header.timestamp += 1;
+
+        let input = LightClientInput {
+            network: NETWORK,
+            trusted,
+            headers: vec![first],
+        };
+        assert_eq!(
+            verify_header_chain(&input),
+            Err(LightClientError::VerificationFailed(1))
+        );
+    }
+
+    #[test]
+    fn test_foreign_consensus_rejected() {
+        let keys = keys();
+        let trusted = genesis(&keys);
+        let other: Vec<SigningKey> = (4..=6u8)
+            .map(|i| SigningKey::from_slice(&[i; 32]).unwrap())
+            .collect();
+
+        let input = LightClientInput {
+            network: NETWORK,
+            headers: vec![next(&trusted, &other)],
+            trusted,
+        };
+        assert_eq!(
+            verify_header_chain(&input),
+            Err(LightClientError::WrongConsensus(1))
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Storage supplied as Merkle witnesses
+//!
+//! Instead of the full storage, a [`ProofInput`] can declare a pre-state
+//! root and carry a [`StorageWitness`] for each key the script touches: a
+//! [`TrieProof`] that the key holds a value, or is absent, under that root.
+//! The script starts with the proven values, and a storage syscall on a key
+//! no witness covers faults with [`VMError::UnwitnessedStorage`]. Input
+//! stays small for scripts that touch a few keys of a large state, and the
+//! guest program checks the same witnesses.
+//!
+//! [`VMError::UnwitnessedStorage`]: neo_vm_core::VMError::UnwitnessedStorage
+
+use crate::ProofInput;
+use neo_vm_core::TrieProof;
+use neo_vm_core::TrieStorage;
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// Proof that a full storage key holds a value, or none, under a state root
+pub type StorageWitness = TrieProof;
+
+/// Why the storage witnesses of an input were rejected
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StorageWitnessError {
+    #[error("Storage witnesses need a pre-state root")]
+    MissingRoot,
+    #[error("Storage must be empty when it is supplied as witnesses")]
+    StorageWithWitnesses,
+    #[error("Storage witness for key {0} does not match the pre-state root")]
+    InvalidWitness(String),
+}
+
+/// Initial storage and the keys the script may touch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WitnessedStorage {
+    /// Proven entries holding a value
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Every witnessed key, including proven absent ones
+    pub keys: BTreeSet<Vec<u8>>,
+}
+
+impl ProofInput {
+    /// Check the storage witnesses against the pre-state root
+    ///
+    /// `None` when the input carries its full storage instead.
+    pub fn witnessed_storage(&self) -> Result<Option<WitnessedStorage>, StorageWitnessError> {
+        let Some(root) = self.pre_state_root else {
+            if !self.storage_witnesses.is_empty() {
+                return Err(StorageWitnessError::MissingRoot);
+            }
+            return Ok(None);
+        };
+        if !self.storage.is_empty() {
+            return Err(StorageWitnessError::StorageWithWitnesses);
+        }
+        let mut storage = WitnessedStorage::default();
+        for witness in &self.storage_witnesses {
+            if !witness.verify(root) {
+                return Err(StorageWitnessError::InvalidWitness(hex::encode(
+                    &witness.key,
+                )));
+            }
+            if storage.keys.insert(witness.key.clone()) {
+                if let Some(value) = &witness.value {
+                    storage.entries.push((witness.key.clone(), value.clone()));
+                }
+            }
+        }
+        Ok(Some(storage))
+    }
+}
+
+/// Witnesses for full storage `keys` against `state`'s root
+pub fn storage_witnesses(state: &TrieStorage, keys: &[Vec<u8>]) -> Vec<StorageWitness> {
+    keys.iter()
+        .map(|key| state.prove_full_key(key.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute;
+    use neo_vm_core::engine::syscall;
+    use neo_vm_core::{
+        BlockContext, GasSchedule, OracleResponses, RuntimeConfig, ScriptBuilder, StackItem,
+        VMError, VMLimits,
+    };
+
+    fn full_key(key: &[u8]) -> Vec<u8> {
+        [&[0u8; 20][..], key].concat()
+    }
+
+    /// `Storage.Get(key)`
+    fn get(key: &[u8]) -> Vec<u8> {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_push_bytes(key)
+            .emit_syscall(syscall::SYSTEM_STORAGE_GET);
+        builder.into_bytes()
+    }
+
+    fn witnessed_input(script: Vec<u8>, state: &TrieStorage, keys: &[&[u8]]) -> ProofInput {
+        let keys: Vec<_> = keys.iter().map(|key| full_key(key)).collect();
+        ProofInput {
+            script,
+            arguments: Vec::new(),
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::default(),
+            runtime: RuntimeConfig::default(),
+            pre_state_root: Some(state.root_hash()),
+            storage_witnesses: storage_witnesses(state, &keys),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        }
+    }
+
+    #[test]
+    fn test_witnessed_reads() {
+        let state: TrieStorage = [
+            (full_key(b"a"), b"1".to_vec()),
+            (full_key(b"b"), b"2".to_vec()),
+        ]
+        .into_iter()
+        .collect();
+
+        let output = execute(witnessed_input(get(b"a"), &state, &[b"a", b"c"]));
+        assert_eq!(output.state, 0);
+        assert_eq!(
+            output.result,
+            Some(StackItem::ByteString(b"1".to_vec().into()))
+        );
+
+        // Proven absent
+        let output = execute(witnessed_input(get(b"c"), &state, &[b"a", b"c"]));
+        assert_eq!(output.state, 0);
+        assert_eq!(output.result, Some(StackItem::Null));
+
+        // Present under the root, but without a witness
+        let output = execute(witnessed_input(get(b"b"), &state, &[b"a", b"c"]));
+        assert_eq!(output.state, 1);
+        assert_eq!(
+            output.fault.map(|fault| fault.error),
+            Some(VMError::UnwitnessedStorage.code())
+        );
+    }
+
+    #[test]
+    fn test_witnesses_bound_to_root() {
+        let state: TrieStorage = [(full_key(b"a"), b"1".to_vec())].into_iter().collect();
+        let input = witnessed_input(get(b"a"), &state, &[b"a"]);
+        assert!(input.witnessed_storage().is_ok());
+
+        let mut wrong_root = input.clone();
+        wrong_root.pre_state_root = Some([1; 32]);
+        assert_eq!(
+            wrong_root.witnessed_storage(),
+            Err(StorageWitnessError::InvalidWitness(hex::encode(full_key(
+                b"a"
+            ))))
+        );
+        assert_eq!(execute(wrong_root.clone()).state, 1);
+        assert_ne!(input.input_hash([0; 32]), wrong_root.input_hash([0; 32]));
+
+        let mut forged = input.clone();
+        forged.storage_witnesses[0].value = Some(b"2".to_vec());
+        assert!(forged.witnessed_storage().is_err());
+
+        let mut with_storage = input.clone();
+        with_storage.storage = vec![(full_key(b"a"), b"1".to_vec())];
+        assert_eq!(
+            with_storage.witnessed_storage(),
+            Err(StorageWitnessError::StorageWithWitnesses)
+        );
+
+        let mut without_root = input;
+        without_root.pre_state_root = None;
+        assert_eq!(
+            without_root.witnessed_storage(),
+            Err(StorageWitnessError::MissingRoot)
+        );
+    }
+}
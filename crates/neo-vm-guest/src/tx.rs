@@ -0,0 +1,568 @@
+//! Neo N3 transactions
+//!
+//! Parses the Neo N3 transaction wire format and checks witnesses the way a
+//! node does before running a transaction: every signer needs a witness whose
+//! verification script hashes to the signer's account, and running the
+//! invocation script and then the verification script must leave exactly
+//! `true` on the stack.
+
+use crate::ProofInput;
+use neo_vm_core::witness::verify_witness_with_gas;
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, UInt160, VMLimits};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Maximum size of a serialized transaction
+pub const MAX_TRANSACTION_SIZE: usize = 102_400;
+
+/// Maximum number of signers, attributes, and entries in each signer list
+const MAX_SUBITEMS: u64 = 16;
+
+/// Maximum nesting of witness rule conditions
+const MAX_CONDITION_DEPTH: usize = 3;
+
+/// Witness scope flags of a [`Signer`]
+pub mod witness_scope {
+    pub const NONE: u8 = 0x00;
+    pub const CALLED_BY_ENTRY: u8 = 0x01;
+    pub const CUSTOM_CONTRACTS: u8 = 0x10;
+    pub const CUSTOM_GROUPS: u8 = 0x20;
+    pub const WITNESS_RULES: u8 = 0x40;
+    pub const GLOBAL: u8 = 0x80;
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TxError {
+    #[error("Unexpected end of transaction data")]
+    UnexpectedEof,
+    #[error("Invalid transaction: {0}")]
+    Invalid(&'static str),
+    #[error("Transaction has {0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("Expected {signers} witnesses, found {witnesses}")]
+    WitnessCount { signers: usize, witnesses: usize },
+    #[error("Witness {0} does not match its signer's account")]
+    WitnessMismatch(usize),
+    #[error("Witness {0} has no verification script; contract verification is not supported")]
+    UnsupportedWitness(usize),
+    #[error("Witness {0} failed verification")]
+    VerificationFailed(usize),
+}
+
+/// Account that authorizes a transaction, and where its witness applies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    pub account: [u8; 20],
+    /// Combination of [`witness_scope`] flags
+    pub scopes: u8,
+    pub allowed_contracts: Vec<[u8; 20]>,
+    pub allowed_groups: Vec<[u8; 33]>,
+    /// Witness rules in wire encoding
+    pub rules: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionAttribute {
+    HighPriority,
+    OracleResponse { id: u64, code: u8, result: Vec<u8> },
+    NotValidBefore { height: u32 },
+    Conflicts { hash: [u8; 32] },
+    NotaryAssisted { n_keys: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Witness {
+    pub invocation_script: Vec<u8>,
+    pub verification_script: Vec<u8>,
+}
+
+impl Witness {
+    /// Run the invocation script and then the verification script against
+    /// `sign_data`, within `gas_limit`
+    ///
+    /// Returns the gas used if verification left exactly `true` on the stack.
+    /// See [`neo_vm_core::witness`] for why a witness is rejected.
+    pub fn verify(&self, sign_data: &[u8], gas_limit: u64) -> Option<u64> {
+        verify_witness_with_gas(
+            &self.verification_script,
+            &self.invocation_script,
+            sign_data,
+            gas_limit,
+        )
+        .ok()
+    }
+}
+
+/// A Neo N3 transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: u8,
+    pub nonce: u32,
+    pub system_fee: i64,
+    pub network_fee: i64,
+    pub valid_until_block: u32,
+    pub signers: Vec<Signer>,
+    pub attributes: Vec<TransactionAttribute>,
+    pub script: Vec<u8>,
+    pub witnesses: Vec<Witness>,
+}
+
+impl Transaction {
+    /// Deserialize a transaction from its wire format
+    pub fn from_bytes(data: &[u8]) -> Result<Self, TxError> {
+        if data.len() > MAX_TRANSACTION_SIZE {
+            return Err(TxError::Invalid("transaction too large"));
+        }
+        let mut reader = Reader { data, pos: 0 };
+        let tx = Self {
+            version: reader.u8()?,
+            nonce: reader.u32()?,
+            system_fee: reader.u64()? as i64,
+            network_fee: reader.u64()? as i64,
+            valid_until_block: reader.u32()?,
+            signers: reader.list(MAX_SUBITEMS, Reader::signer)?,
+            attributes: reader.list(MAX_SUBITEMS, Reader::attribute)?,
+            script: reader.var_bytes(u16::MAX as u64)?,
+            witnesses: reader.list(MAX_SUBITEMS, Reader::witness)?,
+        };
+        if reader.pos != data.len() {
+            return Err(TxError::TrailingBytes(data.len() - reader.pos));
+        }
+        tx.validate()?;
+        Ok(tx)
+    }
+
+    /// Serialize the transaction, including witnesses
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.unsigned_bytes();
+        write_var_int(&mut out, self.witnesses.len() as u64);
+        for witness in &self.witnesses {
+            write_var_bytes(&mut out, &witness.invocation_script);
+            write_var_bytes(&mut out, &witness.verification_script);
+        }
+        out
+    }
+
+    /// Serialization without witnesses, the data the hash covers
+    pub fn unsigned_bytes(&self) -> Vec<u8> {
+        let mut out = vec![self.version];
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&self.system_fee.to_le_bytes());
+        out.extend_from_slice(&self.network_fee.to_le_bytes());
+        out.extend_from_slice(&self.valid_until_block.to_le_bytes());
+
+        write_var_int(&mut out, self.signers.len() as u64);
+        for signer in &self.signers {
+            out.extend_from_slice(&signer.account);
+            out.push(signer.scopes);
+            if signer.scopes & witness_scope::CUSTOM_CONTRACTS != 0 {
+                write_var_int(&mut out, signer.allowed_contracts.len() as u64);
+                signer
+                    .allowed_contracts
+                    .iter()
+                    .for_each(|c| out.extend_from_slice(c));
+            }
+            if signer.scopes & witness_scope::CUSTOM_GROUPS != 0 {
+                write_var_int(&mut out, signer.allowed_groups.len() as u64);
+                signer
+                    .allowed_groups
+                    .iter()
+                    .for_each(|g| out.extend_from_slice(g));
+            }
+            if signer.scopes & witness_scope::WITNESS_RULES != 0 {
+                write_var_int(&mut out, signer.rules.len() as u64);
+                signer.rules.iter().for_each(|r| out.extend_from_slice(r));
+            }
+        }
+
+        write_var_int(&mut out, self.attributes.len() as u64);
+        for attribute in &self.attributes {
+            match attribute {
+                TransactionAttribute::HighPriority => out.push(0x01),
+                TransactionAttribute::OracleResponse { id, code, result } => {
+                    out.push(0x11);
+                    out.extend_from_slice(&id.to_le_bytes());
+                    out.push(*code);
+                    write_var_bytes(&mut out, result);
+                }
+                TransactionAttribute::NotValidBefore { height } => {
+                    out.push(0x20);
+                    out.extend_from_slice(&height.to_le_bytes());
+                }
+                TransactionAttribute::Conflicts { hash } => {
+                    out.push(0x21);
+                    out.extend_from_slice(hash);
+                }
+                TransactionAttribute::NotaryAssisted { n_keys } => {
+                    out.extend_from_slice(&[0x22, *n_keys]);
+                }
+            }
+        }
+
+        write_var_bytes(&mut out, &self.script);
+        out
+    }
+
+    /// Transaction hash: SHA-256 of the unsigned serialization
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.unsigned_bytes()).into()
+    }
+
+    /// Message signed by the witnesses on `network`: the network magic
+    /// followed by the transaction hash
+    pub fn sign_data(&self, network: u32) -> Vec<u8> {
+        let mut data = network.to_le_bytes().to_vec();
+        data.extend_from_slice(&self.hash());
+        data
+    }
+
+    /// Run every witness against its signer
+    ///
+    /// Verification is charged against the network fee, shared by all
+    /// witnesses.
+    pub fn verify_witnesses(&self, network: u32) -> Result<(), TxError> {
+        if self.witnesses.len() != self.signers.len() {
+            return Err(TxError::WitnessCount {
+                signers: self.signers.len(),
+                witnesses: self.witnesses.len(),
+            });
+        }
+
+        let sign_data = self.sign_data(network);
+        let mut gas_left = self.network_fee as u64;
+        for (i, (signer, witness)) in self.signers.iter().zip(&self.witnesses).enumerate() {
+            if witness.verification_script.is_empty() {
+                return Err(TxError::UnsupportedWitness(i));
+            }
+            if script_hash(&witness.verification_script) != signer.account {
+                return Err(TxError::WitnessMismatch(i));
+            }
+            let gas_used = witness
+                .verify(&sign_data, gas_left)
+                .ok_or(TxError::VerificationFailed(i))?;
+            gas_left -= gas_used;
+        }
+        Ok(())
+    }
+
+    /// Input that executes the transaction script against `storage`, with
+    /// the system fee as gas limit
+    pub fn proof_input(&self, storage: Vec<(Vec<u8>, Vec<u8>)>) -> ProofInput {
+        ProofInput {
+            script: self.script.clone(),
+            arguments: vec![],
+            gas_limit: self.system_fee as u64,
+            storage,
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), TxError> {
+        if self.version != 0 {
+            return Err(TxError::Invalid("unknown version"));
+        }
+        if self.system_fee < 0 || self.network_fee < 0 {
+            return Err(TxError::Invalid("negative fee"));
+        }
+        if self.signers.is_empty() {
+            return Err(TxError::Invalid("no signers"));
+        }
+        for (i, signer) in self.signers.iter().enumerate() {
+            if self.signers[..i]
+                .iter()
+                .any(|s| s.account == signer.account)
+            {
+                return Err(TxError::Invalid("duplicate signer"));
+            }
+        }
+        if self.script.is_empty() {
+            return Err(TxError::Invalid("empty script"));
+        }
+        Ok(())
+    }
+}
+
+/// Script hash of `script`: RIPEMD-160 of its SHA-256
+pub fn script_hash(script: &[u8]) -> [u8; 20] {
+    UInt160::from_script(script).into()
+}
+
+fn write_var_int(out: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0xFC => out.push(value as u8),
+        0xFD..=0xFFFF => {
+            out.push(0xFD);
+            out.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        0x1_0000..=0xFFFF_FFFF => {
+            out.push(0xFE);
+            out.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xFF);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+fn write_var_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_var_int(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8], TxError> {
+        let end = self.pos.checked_add(len).ok_or(TxError::UnexpectedEof)?;
+        let bytes = self.data.get(self.pos..end).ok_or(TxError::UnexpectedEof)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], TxError> {
+        Ok(self.bytes(N)?.try_into().expect("slice has length N"))
+    }
+
+    fn u8(&mut self) -> Result<u8, TxError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, TxError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, TxError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn var_int(&mut self, max: u64) -> Result<u64, TxError> {
+        let value = match self.u8()? {
+            0xFD => u16::from_le_bytes(self.array()?) as u64,
+            0xFE => u32::from_le_bytes(self.array()?) as u64,
+            0xFF => self.u64()?,
+            n => n as u64,
+        };
+        if value > max {
+            return Err(TxError::Invalid("length out of range"));
+        }
+        Ok(value)
+    }
+
+    fn var_bytes(&mut self, max: u64) -> Result<Vec<u8>, TxError> {
+        let len = self.var_int(max)? as usize;
+        Ok(self.bytes(len)?.to_vec())
+    }
+
+    fn list<T>(
+        &mut self,
+        max: u64,
+        item: impl Fn(&mut Self) -> Result<T, TxError>,
+    ) -> Result<Vec<T>, TxError> {
+        let count = self.var_int(max)?;
+        (0..count).map(|_| item(self)).collect()
+    }
+
+    fn signer(&mut self) -> Result<Signer, TxError> {
+        let account = self.array()?;
+        let scopes = self.u8()?;
+        if scopes & witness_scope::GLOBAL != 0 && scopes != witness_scope::GLOBAL {
+            return Err(TxError::Invalid("global scope combined with other scopes"));
+        }
+        let mut signer = Signer {
+            account,
+            scopes,
+            allowed_contracts: Vec::new(),
+            allowed_groups: Vec::new(),
+            rules: Vec::new(),
+        };
+        if scopes & witness_scope::CUSTOM_CONTRACTS != 0 {
+            signer.allowed_contracts = self.list(MAX_SUBITEMS, Self::array)?;
+        }
+        if scopes & witness_scope::CUSTOM_GROUPS != 0 {
+            signer.allowed_groups = self.list(MAX_SUBITEMS, Self::array)?;
+        }
+        if scopes & witness_scope::WITNESS_RULES != 0 {
+            signer.rules = self.list(MAX_SUBITEMS, Self::witness_rule)?;
+        }
+        Ok(signer)
+    }
+
+    /// Rule action followed by its condition, returned as raw bytes
+    fn witness_rule(&mut self) -> Result<Vec<u8>, TxError> {
+        let start = self.pos;
+        if self.u8()? > 1 {
+            return Err(TxError::Invalid("unknown witness rule action"));
+        }
+        self.witness_condition(0)?;
+        Ok(self.data[start..self.pos].to_vec())
+    }
+
+    fn witness_condition(&mut self, depth: usize) -> Result<(), TxError> {
+        if depth >= MAX_CONDITION_DEPTH {
+            return Err(TxError::Invalid("witness condition nested too deeply"));
+        }
+        match self.u8()? {
+            // Boolean
+            0x00 => {
+                self.u8()?;
+            }
+            // Not
+            0x01 => self.witness_condition(depth + 1)?,
+            // And, Or
+            0x02 | 0x03 => {
+                let count = self.var_int(MAX_SUBITEMS)?;
+                for _ in 0..count {
+                    self.witness_condition(depth + 1)?;
+                }
+            }
+            // ScriptHash, CalledByContract
+            0x18 | 0x28 => {
+                self.bytes(20)?;
+            }
+            // Group, CalledByGroup
+            0x19 | 0x29 => {
+                self.bytes(33)?;
+            }
+            // CalledByEntry
+            0x20 => {}
+            _ => return Err(TxError::Invalid("unknown witness condition")),
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self) -> Result<TransactionAttribute, TxError> {
+        Ok(match self.u8()? {
+            0x01 => TransactionAttribute::HighPriority,
+            0x11 => TransactionAttribute::OracleResponse {
+                id: self.u64()?,
+                code: self.u8()?,
+                result: self.var_bytes(u16::MAX as u64)?,
+            },
+            0x20 => TransactionAttribute::NotValidBefore {
+                height: self.u32()?,
+            },
+            0x21 => TransactionAttribute::Conflicts {
+                hash: self.array()?,
+            },
+            0x22 => TransactionAttribute::NotaryAssisted { n_keys: self.u8()? },
+            _ => return Err(TxError::Invalid("unknown attribute type")),
+        })
+    }
+
+    fn witness(&mut self) -> Result<Witness, TxError> {
+        Ok(Witness {
+            invocation_script: self.var_bytes(1024)?,
+            verification_script: self.var_bytes(1024)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::{signature::Signer as _, Signature, SigningKey};
+
+    const NETWORK: u32 = 860833102;
+
+    /// PUSHDATA1 <pubkey>, SYSCALL System.Crypto.CheckSig
+    fn verification_script(key: &SigningKey) -> Vec<u8> {
+        let pubkey = key.verifying_key().to_sec1_bytes();
+        let mut script = vec![0x0C, pubkey.len() as u8];
+        script.extend_from_slice(&pubkey);
+        script.extend_from_slice(&[0x41, 0x20, 0x00, 0x00, 0x00]);
+        script
+    }
+
+    fn signed_tx(key: &SigningKey) -> Transaction {
+        let verification = verification_script(key);
+        let mut tx = Transaction {
+            version: 0,
+            nonce: 42,
+            system_fee: 1_000_000,
+            network_fee: 1_000_000,
+            valid_until_block: 100,
+            signers: vec![Signer {
+                account: script_hash(&verification),
+                scopes: witness_scope::CALLED_BY_ENTRY,
+                allowed_contracts: vec![],
+                allowed_groups: vec![],
+                rules: vec![],
+            }],
+            attributes: vec![TransactionAttribute::HighPriority],
+            // PUSH2, PUSH3, ADD, RET
+            script: vec![0x12, 0x13, 0x9E, 0x40],
+            witnesses: vec![],
+        };
+        let signature: Signature = key.sign(&tx.sign_data(NETWORK));
+        let mut invocation = vec![0x0C, 0x40];
+        invocation.extend_from_slice(&signature.to_bytes());
+        tx.witnesses.push(Witness {
+            invocation_script: invocation,
+            verification_script: verification,
+        });
+        tx
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut tx = signed_tx(&key());
+        tx.signers[0].scopes = witness_scope::CUSTOM_CONTRACTS | witness_scope::WITNESS_RULES;
+        tx.signers[0].allowed_contracts = vec![[9u8; 20]];
+        // Allow, And [CalledByEntry, Not Boolean false]
+        tx.signers[0].rules = vec![vec![0x01, 0x02, 0x02, 0x20, 0x01, 0x00, 0x00]];
+        tx.attributes
+            .push(TransactionAttribute::Conflicts { hash: [1u8; 32] });
+
+        let bytes = tx.to_bytes();
+        assert_eq!(Transaction::from_bytes(&bytes), Ok(tx));
+        assert_eq!(
+            Transaction::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(TxError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_valid_witness_verifies() {
+        let tx = signed_tx(&key());
+        assert_eq!(tx.verify_witnesses(NETWORK), Ok(()));
+        assert_eq!(
+            tx.verify_witnesses(NETWORK + 1),
+            Err(TxError::VerificationFailed(0))
+        );
+    }
+
+    #[test]
+    fn test_witness_must_match_signer() {
+        let mut tx = signed_tx(&key());
+        tx.signers[0].account = [0u8; 20];
+        assert_eq!(
+            tx.verify_witnesses(NETWORK),
+            Err(TxError::WitnessMismatch(0))
+        );
+
+        tx.witnesses.clear();
+        assert_eq!(
+            tx.verify_witnesses(NETWORK),
+            Err(TxError::WitnessCount {
+                signers: 1,
+                witnesses: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! JavaScript bindings for in-browser script simulation
+//!
+//! Build with `wasm-pack build crates/neo-vm-wasm --target web`, then:
+//!
+//! ```js
+//! import init, { run, disassemble } from "./pkg/neo_vm_wasm.js";
+//!
+//! await init();
+//! const script = Uint8Array.from([0x12, 0x13, 0x9e, 0x40]);
+//! const result = JSON.parse(run(script, null, 1_000_000n));
+//! console.log(result.state, result.stack, disassemble(script));
+//! ```
+//!
+//! Scripts run on the host VM from `neo-vm-core`, without proving.
+
+use neo_vm_core::contract_parameter::parse_arguments;
+use neo_vm_core::{ContractParameterError, Disassembler, NeoVM, VMState};
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+/// Execute `script` and return the result as JSON
+///
+/// `args_json` is an optional JSON array of Neo contract parameters, pushed
+/// in order before execution. The result has the shape of a Neo RPC
+/// `invokescript` response: `state` (`"HALT"` or `"FAULT"`), `gasconsumed`,
+/// `exception`, `stack` (bottom first, in the stack item JSON format of
+/// `neo_vm_core::json`) and the script's `logs`.
+#[wasm_bindgen]
+pub fn run(script: &[u8], args_json: Option<String>, gas_limit: u64) -> Result<String, JsError> {
+    Ok(simulate(script, args_json.as_deref(), gas_limit)?)
+}
+
+/// Disassemble `script` into one line per instruction: offset, bytes and
+/// mnemonic with decoded operands
+#[wasm_bindgen]
+pub fn disassemble(script: &[u8]) -> String {
+    Disassembler::new(script).disassemble()
+}
+
+// `JsError` can only be built on wasm32, so the logic lives here where host
+// tests can reach it
+fn simulate(
+    script: &[u8],
+    args_json: Option<&str>,
+    gas_limit: u64,
+) -> Result<String, ContractParameterError> {
+    let arguments = match args_json {
+        Some(json) => parse_arguments(json)?,
+        None => Vec::new(),
+    };
+
+    let mut vm = NeoVM::new(gas_limit);
+    let mut exception = vm.load_script(script.to_vec()).err().map(|e| e.to_string());
+    if exception.is_some() {
+        vm.state = VMState::Fault;
+    }
+    vm.eval_stack.extend(arguments);
+    while !matches!(vm.state, VMState::Halt | VMState::Fault) {
+        if let Err(e) = vm.execute_next() {
+            vm.state = VMState::Fault;
+            exception = Some(e.to_string());
+        }
+    }
+
+    let result = json!({
+        "state": if matches!(vm.state, VMState::Halt) { "HALT" } else { "FAULT" },
+        "gasconsumed": vm.gas_consumed.to_string(),
+        "exception": exception,
+        "stack": vm.eval_stack,
+        "logs": vm.logs,
+    });
+    Ok(result.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn simulate_json(script: &[u8], args_json: Option<&str>) -> Value {
+        serde_json::from_str(&simulate(script, args_json, 1_000_000).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_simulate_halt() {
+        // PUSH2 PUSH3 ADD RET
+        let result = simulate_json(&[0x12, 0x13, 0x9E, 0x40], None);
+        assert_eq!(result["state"], "HALT");
+        assert_eq!(result["exception"], Value::Null);
+        assert_eq!(result["stack"], json!([{"type": "Integer", "value": "5"}]));
+        assert_ne!(result["gasconsumed"], "0");
+    }
+
+    #[test]
+    fn test_simulate_arguments_and_fault() {
+        // ADD RET over the arguments
+        let args = r#"[{"type":"Integer","value":"40"},{"type":"Integer","value":"2"}]"#;
+        let result = simulate_json(&[0x9E, 0x40], Some(args));
+        assert_eq!(result["stack"], json!([{"type": "Integer", "value": "42"}]));
+
+        // ADD with nothing to add
+        let result = simulate_json(&[0x9E, 0x40], None);
+        assert_eq!(result["state"], "FAULT");
+        assert!(result["exception"].is_string());
+
+        assert!(simulate(&[0x40], Some("{}"), 1_000_000).is_err());
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&[0x12, 0x13, 0x9E, 0x40]);
+        assert!(listing.contains("ADD"), "{listing}");
+        assert_eq!(listing.lines().count(), 4);
+    }
+}
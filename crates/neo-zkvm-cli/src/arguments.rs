@@ -0,0 +1,122 @@
+//! Typed script arguments for `--arg`
+//!
+//! Each value names its type before a colon:
+//! - `int:<n>`: decimal integer, e.g. `int:-5`
+//! - `bool:true` or `bool:false`
+//! - `hex:<bytes>`: byte string as hex, the `0x` prefix is optional
+//! - `str:<text>`: byte string of the text's UTF-8
+//! - `null`
+//! - `array:[...]`: JSON array whose numbers, booleans, nulls and nested
+//!   arrays map to the matching items and whose strings are typed values
+//!   themselves, e.g. `array:[1, true, "hex:01ff", [2, 3]]`
+
+use neo_vm_core::StackItem;
+use serde_json::Value;
+
+/// Parse one typed argument
+pub fn parse_typed_argument(text: &str) -> Result<StackItem, String> {
+    if text == "null" {
+        return Ok(StackItem::Null);
+    }
+    let (kind, value) = text
+        .split_once(':')
+        .ok_or_else(|| format!("Argument '{}' has no type, e.g. int:5", text))?;
+    match kind {
+        "int" => value
+            .parse()
+            .map(StackItem::Integer)
+            .map_err(|_| format!("Invalid integer '{}'", value)),
+        "bool" => match value {
+            "true" => Ok(StackItem::Boolean(true)),
+            "false" => Ok(StackItem::Boolean(false)),
+            _ => Err(format!("Invalid boolean '{}'", value)),
+        },
+        "hex" => hex::decode(value.trim_start_matches("0x"))
+            .map(|bytes| StackItem::ByteString(bytes.into()))
+            .map_err(|e| format!("Invalid hex '{}': {}", value, e)),
+        "str" => Ok(StackItem::ByteString(value.as_bytes().to_vec().into())),
+        "array" => {
+            let json: Value =
+                serde_json::from_str(value).map_err(|e| format!("Invalid array: {}", e))?;
+            match json {
+                Value::Array(_) => json_item(&json),
+                _ => Err(format!("Invalid array '{}': not a JSON array", value)),
+            }
+        }
+        _ => Err(format!(
+            "Unknown argument type '{}', expected int, bool, hex, str, null or array",
+            kind
+        )),
+    }
+}
+
+/// Stack item for an element of an `array:` argument
+fn json_item(value: &Value) -> Result<StackItem, String> {
+    match value {
+        Value::Null => Ok(StackItem::Null),
+        Value::Bool(b) => Ok(StackItem::Boolean(*b)),
+        Value::Number(n) => n
+            .as_i64()
+            .map(|n| StackItem::Integer(n.into()))
+            .or_else(|| n.as_u64().map(|n| StackItem::Integer(n.into())))
+            .ok_or_else(|| format!("Invalid integer {} in array, use \"int:...\"", n)),
+        Value::String(text) => parse_typed_argument(text),
+        Value::Array(items) => items
+            .iter()
+            .map(json_item)
+            .collect::<Result<_, _>>()
+            .map(StackItem::Array),
+        Value::Object(_) => Err("Objects are not supported in array arguments".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(data: &[u8]) -> StackItem {
+        StackItem::ByteString(data.to_vec().into())
+    }
+
+    #[test]
+    fn test_scalar_arguments() {
+        assert_eq!(parse_typed_argument("int:-5"), Ok(StackItem::Integer(-5)));
+        assert_eq!(
+            parse_typed_argument("bool:true"),
+            Ok(StackItem::Boolean(true))
+        );
+        assert_eq!(parse_typed_argument("hex:0x01ff"), Ok(bytes(&[1, 0xff])));
+        assert_eq!(parse_typed_argument("str:a:b"), Ok(bytes(b"a:b")));
+        assert_eq!(parse_typed_argument("null"), Ok(StackItem::Null));
+    }
+
+    #[test]
+    fn test_array_argument() {
+        assert_eq!(
+            parse_typed_argument(r#"array:[1, false, null, "str:hi", [2, "int:-3"]]"#),
+            Ok(StackItem::Array(vec![
+                StackItem::Integer(1),
+                StackItem::Boolean(false),
+                StackItem::Null,
+                bytes(b"hi"),
+                StackItem::Array(vec![StackItem::Integer(2), StackItem::Integer(-3)]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        for text in [
+            "5",
+            "int:five",
+            "bool:yes",
+            "hex:0g",
+            "map:{}",
+            "array:{}",
+            r#"array:["plain"]"#,
+            "array:[1.5]",
+        ] {
+            assert!(parse_typed_argument(text).is_err(), "{}", text);
+        }
+    }
+}
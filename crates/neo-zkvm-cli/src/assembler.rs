@@ -6,11 +6,18 @@
 //! - Labels and symbolic jumps
 //! - Syntax sugar for common patterns
 //! - Comprehensive error messages
+//! - Debug info: `.func`, `.args`, `.locals` and `.statics` name functions
+//!   and slots, see [`Assembler::debug_info`]
 
 #![allow(dead_code)]
 
 use std::collections::HashMap;
 
+use neo_vm_core::instruction::OperandSize;
+use neo_vm_core::{OpCode, OpcodeInfo};
+
+use crate::debug_info::{self, DebugInfo, Function, SequencePoint};
+
 #[derive(Debug, Clone)]
 pub enum AssemblerError {
     UnknownOpcode(String, usize),
@@ -54,12 +61,18 @@ struct Macro {
 
 const MAX_MACRO_DEPTH: usize = 100;
 
+/// Maximum repeat count of sugar like `INC3` or `NOP16`
+const MAX_REPEAT: usize = 1024;
+
 pub struct Assembler {
     labels: HashMap<String, usize>,
     macros: HashMap<String, Macro>,
     pending_labels: Vec<(usize, String, usize, bool)>,
     warnings: Vec<String>,
     macro_depth: usize,
+    /// Source line of each preprocessed line
+    source_lines: Vec<usize>,
+    debug_info: DebugInfo,
 }
 
 impl Assembler {
@@ -70,6 +83,11 @@ impl Assembler {
             pending_labels: Vec::new(),
             warnings: Vec::new(),
             macro_depth: 0,
+            source_lines: Vec::new(),
+            debug_info: DebugInfo {
+                version: debug_info::VERSION,
+                ..DebugInfo::default()
+            },
         }
     }
 
@@ -77,6 +95,12 @@ impl Assembler {
         &self.warnings
     }
 
+    /// Functions, slot names and source lines of the last assembled source,
+    /// without document names
+    pub fn debug_info(&self) -> &DebugInfo {
+        &self.debug_info
+    }
+
     pub fn assemble(&mut self, source: &str) -> Result<Vec<u8>, String> {
         // First pass: collect macros and labels
         let expanded = self.preprocess(source)?;
@@ -90,6 +114,11 @@ impl Assembler {
                 continue;
             }
 
+            let source_line = self.source_lines[line_num];
+            if line.starts_with('.') && self.directive(line, bytecode.len(), line_num + 1)? {
+                continue;
+            }
+
             // Handle labels
             if line.ends_with(':') {
                 let label = line.trim_end_matches(':').to_string();
@@ -100,8 +129,18 @@ impl Assembler {
                 continue;
             }
 
+            let offset = bytecode.len();
             self.assemble_line(line, &mut bytecode, line_num + 1)?;
+            let points = &mut self.debug_info.sequence_points;
+            if bytecode.len() > offset && points.last().is_none_or(|p| p.line != source_line) {
+                points.push(SequencePoint {
+                    offset,
+                    document: 0,
+                    line: source_line,
+                });
+            }
         }
+        self.end_function(bytecode.len());
 
         // Resolve pending label references
         self.resolve_labels(&mut bytecode)?;
@@ -109,7 +148,63 @@ impl Assembler {
         Ok(bytecode)
     }
 
+    /// Handle a debug info directive, returning whether `line` was one
+    fn directive(&mut self, line: &str, offset: usize, line_num: usize) -> Result<bool, String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let names = || parts[1..].iter().map(|s| s.to_string()).collect();
+        match parts[0].to_lowercase().as_str() {
+            ".func" => {
+                let name = parts.get(1).ok_or_else(|| {
+                    AssemblerError::SyntaxError("Missing function name".to_string(), line_num)
+                        .to_string()
+                })?;
+                self.end_function(offset);
+                self.debug_info.functions.push(Function {
+                    name: name.to_string(),
+                    start: offset,
+                    end: usize::MAX,
+                    ..Function::default()
+                });
+            }
+            ".endfunc" => self.end_function(offset),
+            ".statics" => self.debug_info.statics = names(),
+            directive @ (".args" | ".locals") => {
+                let function = self
+                    .debug_info
+                    .functions
+                    .last_mut()
+                    .filter(|f| f.end == usize::MAX)
+                    .ok_or_else(|| {
+                        AssemblerError::SyntaxError(
+                            format!("{} outside .func", directive),
+                            line_num,
+                        )
+                        .to_string()
+                    })?;
+                if directive == ".args" {
+                    function.arguments = names();
+                } else {
+                    function.locals = names();
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// End the open `.func`, if any, at `offset`
+    fn end_function(&mut self, offset: usize) {
+        if let Some(function) = self.debug_info.functions.last_mut() {
+            if function.end == usize::MAX {
+                function.end = offset;
+            }
+        }
+    }
+
     fn preprocess(&mut self, source: &str) -> Result<Vec<String>, String> {
+        self.source_lines.clear();
+        self.debug_info.functions.clear();
+        self.debug_info.sequence_points.clear();
         let mut result = Vec::new();
         let mut in_macro = false;
         let mut current_macro_name = String::new();
@@ -157,12 +252,16 @@ impl Assembler {
             // Macro invocation
             if trimmed.starts_with('%') && !trimmed.starts_with("%macro") {
                 let expanded = self.expand_macro(trimmed, line_num + 1)?;
+                self.source_lines
+                    .extend(std::iter::repeat_n(line_num + 1, expanded.len()));
                 result.extend(expanded);
                 continue;
             }
 
             // Syntax sugar expansion
             let expanded = self.expand_sugar(trimmed, line_num + 1)?;
+            self.source_lines
+                .extend(std::iter::repeat_n(line_num + 1, expanded.len()));
             result.extend(expanded);
         }
 
@@ -216,7 +315,7 @@ impl Assembler {
         Ok(result)
     }
 
-    fn expand_sugar(&self, line: &str, _line_num: usize) -> Result<Vec<String>, String> {
+    fn expand_sugar(&self, line: &str, line_num: usize) -> Result<Vec<String>, String> {
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.is_empty() {
             return Ok(vec![line.to_string()]);
@@ -241,31 +340,31 @@ impl Assembler {
             // INC2, INC3, etc. - multiple increments
             s if s.starts_with("INC") && s.len() > 3 => {
                 if let Ok(n) = s[3..].parse::<usize>() {
-                    return Ok(vec!["INC".to_string(); n]);
+                    return Self::repeat("INC", n, line_num);
                 }
             }
             // DEC2, DEC3, etc. - multiple decrements
             s if s.starts_with("DEC") && s.len() > 3 => {
                 if let Ok(n) = s[3..].parse::<usize>() {
-                    return Ok(vec!["DEC".to_string(); n]);
+                    return Self::repeat("DEC", n, line_num);
                 }
             }
             // DUP2, DUP3, etc. - multiple duplicates
             s if s.starts_with("DUP") && s.len() > 3 => {
                 if let Ok(n) = s[3..].parse::<usize>() {
-                    return Ok(vec!["DUP".to_string(); n]);
+                    return Self::repeat("DUP", n, line_num);
                 }
             }
             // DROP2, DROP3, etc. - multiple drops
             s if s.starts_with("DROP") && s.len() > 4 => {
                 if let Ok(n) = s[4..].parse::<usize>() {
-                    return Ok(vec!["DROP".to_string(); n]);
+                    return Self::repeat("DROP", n, line_num);
                 }
             }
             // NOP2, NOP3, etc. - multiple nops
             s if s.starts_with("NOP") && s.len() > 3 => {
                 if let Ok(n) = s[3..].parse::<usize>() {
-                    return Ok(vec!["NOP".to_string(); n]);
+                    return Self::repeat("NOP", n, line_num);
                 }
             }
             _ => {}
@@ -274,127 +373,22 @@ impl Assembler {
         Ok(vec![line.to_string()])
     }
 
+    /// `op` repeated `n` times, for sugar like `INC3`
+    fn repeat(op: &str, n: usize, line_num: usize) -> Result<Vec<String>, String> {
+        if n > MAX_REPEAT {
+            return Err(AssemblerError::InvalidOperand(
+                format!("repeat count {} exceeds maximum {}", n, MAX_REPEAT),
+                line_num,
+            )
+            .to_string());
+        }
+        Ok(vec![op.to_string(); n])
+    }
+
     fn is_simple_opcode(&self, s: &str) -> bool {
         let op = s.to_uppercase();
-        matches!(
-            op.as_str(),
-            "PUSH0"
-                | "PUSH1"
-                | "PUSH2"
-                | "PUSH3"
-                | "PUSH4"
-                | "PUSH5"
-                | "PUSH6"
-                | "PUSH7"
-                | "PUSH8"
-                | "PUSH9"
-                | "PUSH10"
-                | "PUSH11"
-                | "PUSH12"
-                | "PUSH13"
-                | "PUSH14"
-                | "PUSH15"
-                | "PUSH16"
-                | "PUSHM1"
-                | "PUSHNULL"
-                | "TRUE"
-                | "FALSE"
-                | "NOP"
-                | "RET"
-                | "ABORT"
-                | "ASSERT"
-                | "THROW"
-                | "DEPTH"
-                | "DROP"
-                | "NIP"
-                | "CLEAR"
-                | "DUP"
-                | "OVER"
-                | "PICK"
-                | "TUCK"
-                | "SWAP"
-                | "ROT"
-                | "ROLL"
-                | "REVERSE3"
-                | "REVERSE4"
-                | "REVERSEN"
-                | "XDROP"
-                | "ADD"
-                | "SUB"
-                | "MUL"
-                | "DIV"
-                | "MOD"
-                | "POW"
-                | "SQRT"
-                | "SHL"
-                | "SHR"
-                | "INC"
-                | "DEC"
-                | "SIGN"
-                | "ABS"
-                | "NEGATE"
-                | "NEG"
-                | "INVERT"
-                | "AND"
-                | "OR"
-                | "XOR"
-                | "EQUAL"
-                | "NOTEQUAL"
-                | "NOT"
-                | "BOOLAND"
-                | "BOOLOR"
-                | "NZ"
-                | "LT"
-                | "LE"
-                | "GT"
-                | "GE"
-                | "MIN"
-                | "MAX"
-                | "WITHIN"
-                | "NUMEQUAL"
-                | "NUMNOTEQUAL"
-                | "NEWARRAY0"
-                | "NEWARRAY"
-                | "NEWSTRUCT0"
-                | "NEWSTRUCT"
-                | "NEWMAP"
-                | "SIZE"
-                | "HASKEY"
-                | "KEYS"
-                | "VALUES"
-                | "PICKITEM"
-                | "APPEND"
-                | "SETITEM"
-                | "REVERSEITEMS"
-                | "REMOVE"
-                | "CLEARITEMS"
-                | "POPITEM"
-                | "PACK"
-                | "UNPACK"
-                | "ISNULL"
-                | "SHA256"
-                | "RIPEMD160"
-                | "HASH160"
-                | "CHECKSIG"
-                | "LDLOC0"
-                | "LDLOC1"
-                | "LDLOC2"
-                | "LDLOC3"
-                | "LDLOC4"
-                | "LDLOC5"
-                | "STLOC0"
-                | "STLOC1"
-                | "STLOC2"
-                | "STLOC3"
-                | "STLOC4"
-                | "STLOC5"
-                | "LDARG0"
-                | "LDARG1"
-                | "LDARG2"
-                | "LDARG3"
-                | "LDARG4"
-                | "LDARG5"
-        )
+        matches!(op.as_str(), "TRUE" | "FALSE" | "NEG")
+            || OpcodeInfo::by_name(&op).is_some_and(|info| info.operand == OperandSize::Fixed(0))
     }
 
     fn optimal_push(&self, n: i128) -> String {
@@ -422,30 +416,34 @@ impl Assembler {
         let operands = &parts[1..];
 
         match op.as_str() {
+            // Aliases
+            "TRUE" => bytecode.push(OpCode::PUSH1 as u8),
+            "FALSE" => bytecode.push(OpCode::PUSH0 as u8),
+            "NEG" => bytecode.push(OpCode::NEGATE as u8),
+
             // Constants
             "PUSHINT8" => {
-                bytecode.push(0x00);
+                bytecode.push(OpCode::PUSHINT8 as u8);
                 let val = self.parse_int(operands, line_num)? as i8;
                 bytecode.push(val as u8);
             }
             "PUSHINT16" => {
-                bytecode.push(0x01);
+                bytecode.push(OpCode::PUSHINT16 as u8);
                 let val = self.parse_int(operands, line_num)? as i16;
                 bytecode.extend_from_slice(&val.to_le_bytes());
             }
             "PUSHINT32" => {
-                bytecode.push(0x02);
+                bytecode.push(OpCode::PUSHINT32 as u8);
                 let val = self.parse_int(operands, line_num)? as i32;
                 bytecode.extend_from_slice(&val.to_le_bytes());
             }
             "PUSHINT64" => {
-                bytecode.push(0x03);
+                bytecode.push(OpCode::PUSHINT64 as u8);
                 let val = self.parse_int(operands, line_num)?;
                 bytecode.extend_from_slice(&val.to_le_bytes());
             }
-            "PUSHNULL" => bytecode.push(0x0B),
             "PUSHDATA1" => {
-                bytecode.push(0x0C);
+                bytecode.push(OpCode::PUSHDATA1 as u8);
                 let data = self.parse_data(operands, line_num)?;
                 let len = data.len();
                 if len > 255 {
@@ -459,7 +457,7 @@ impl Assembler {
                 bytecode.extend_from_slice(&data);
             }
             "PUSHDATA2" => {
-                bytecode.push(0x0D);
+                bytecode.push(OpCode::PUSHDATA2 as u8);
                 let data = self.parse_data(operands, line_num)?;
                 let len = data.len();
                 if len > u16::MAX as usize {
@@ -474,206 +472,18 @@ impl Assembler {
                 bytecode.extend_from_slice(&(len as u16).to_le_bytes());
                 bytecode.extend_from_slice(&data);
             }
-            "PUSHM1" => bytecode.push(0x0F),
-            "PUSH0" | "PUSHF" | "FALSE" => bytecode.push(0x10),
-            "PUSH1" | "PUSHT" | "TRUE" => bytecode.push(0x11),
-            "PUSH2" => bytecode.push(0x12),
-            "PUSH3" => bytecode.push(0x13),
-            "PUSH4" => bytecode.push(0x14),
-            "PUSH5" => bytecode.push(0x15),
-            "PUSH6" => bytecode.push(0x16),
-            "PUSH7" => bytecode.push(0x17),
-            "PUSH8" => bytecode.push(0x18),
-            "PUSH9" => bytecode.push(0x19),
-            "PUSH10" => bytecode.push(0x1A),
-            "PUSH11" => bytecode.push(0x1B),
-            "PUSH12" => bytecode.push(0x1C),
-            "PUSH13" => bytecode.push(0x1D),
-            "PUSH14" => bytecode.push(0x1E),
-            "PUSH15" => bytecode.push(0x1F),
-            "PUSH16" => bytecode.push(0x20),
-
-            // Flow control
-            "NOP" => bytecode.push(0x21),
-            "JMP" => {
-                bytecode.push(0x22);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMP_L" => {
-                bytecode.push(0x23);
-                self.emit_jump_offset_long(bytecode, operands, line_num)?;
-            }
-            "JMPIF" => {
-                bytecode.push(0x24);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPIFNOT" => {
-                bytecode.push(0x26);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPEQ" => {
-                bytecode.push(0x28);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPNE" => {
-                bytecode.push(0x2A);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPGT" => {
-                bytecode.push(0x2C);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPGE" => {
-                bytecode.push(0x2E);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPLT" => {
-                bytecode.push(0x30);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "JMPLE" => {
-                bytecode.push(0x32);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "CALL" => {
-                bytecode.push(0x34);
-                self.emit_jump_offset(bytecode, operands, line_num)?;
-            }
-            "ABORT" => bytecode.push(0x38),
-            "ASSERT" => bytecode.push(0x39),
-            "THROW" => bytecode.push(0x3A),
-            "RET" => bytecode.push(0x40),
+
             "SYSCALL" => {
-                bytecode.push(0x41);
+                bytecode.push(OpCode::SYSCALL as u8);
                 let id = self.parse_syscall_id(operands, line_num)?;
                 bytecode.extend_from_slice(&id.to_le_bytes());
             }
-
-            // Stack operations
-            "DEPTH" => bytecode.push(0x43),
-            "DROP" => bytecode.push(0x45),
-            "NIP" => bytecode.push(0x46),
-            "XDROP" => bytecode.push(0x48),
-            "CLEAR" => bytecode.push(0x49),
-            "DUP" => bytecode.push(0x4A),
-            "OVER" => bytecode.push(0x4B),
-            "PICK" => bytecode.push(0x4D),
-            "TUCK" => bytecode.push(0x4E),
-            "SWAP" => bytecode.push(0x50),
-            "ROT" => bytecode.push(0x51),
-            "ROLL" => bytecode.push(0x52),
-            "REVERSE3" => bytecode.push(0x53),
-            "REVERSE4" => bytecode.push(0x54),
-            "REVERSEN" => bytecode.push(0x55),
-
-            // Slot operations
             "INITSLOT" => {
-                bytecode.push(0x57);
+                bytecode.push(OpCode::INITSLOT as u8);
                 let (locals, args) = self.parse_slot_args(operands, line_num)?;
                 bytecode.push(locals);
                 bytecode.push(args);
             }
-            "LDLOC0" => bytecode.push(0x66),
-            "LDLOC1" => bytecode.push(0x67),
-            "LDLOC2" => bytecode.push(0x68),
-            "LDLOC3" => bytecode.push(0x69),
-            "LDLOC4" => bytecode.push(0x6A),
-            "LDLOC5" => bytecode.push(0x6B),
-            "LDLOC" => {
-                bytecode.push(0x6C);
-                let idx = self.parse_u8(operands, line_num)?;
-                bytecode.push(idx);
-            }
-            "STLOC0" => bytecode.push(0x6D),
-            "STLOC1" => bytecode.push(0x6E),
-            "STLOC2" => bytecode.push(0x6F),
-            "STLOC3" => bytecode.push(0x70),
-            "STLOC4" => bytecode.push(0x71),
-            "STLOC5" => bytecode.push(0x72),
-            "STLOC" => {
-                bytecode.push(0x73);
-                let idx = self.parse_u8(operands, line_num)?;
-                bytecode.push(idx);
-            }
-            "LDARG0" => bytecode.push(0x74),
-            "LDARG1" => bytecode.push(0x75),
-            "LDARG2" => bytecode.push(0x76),
-            "LDARG3" => bytecode.push(0x77),
-            "LDARG4" => bytecode.push(0x78),
-            "LDARG5" => bytecode.push(0x79),
-            "LDARG" => {
-                bytecode.push(0x7A);
-                let idx = self.parse_u8(operands, line_num)?;
-                bytecode.push(idx);
-            }
-
-            // Bitwise operations
-            "INVERT" => bytecode.push(0x90),
-            "AND" => bytecode.push(0x91),
-            "OR" => bytecode.push(0x92),
-            "XOR" => bytecode.push(0x93),
-            "EQUAL" => bytecode.push(0x97),
-            "NOTEQUAL" => bytecode.push(0x98),
-
-            // Arithmetic
-            "SIGN" => bytecode.push(0x99),
-            "ABS" => bytecode.push(0x9A),
-            "NEGATE" | "NEG" => bytecode.push(0x9B),
-            "INC" => bytecode.push(0x9C),
-            "DEC" => bytecode.push(0x9D),
-            "ADD" => bytecode.push(0x9E),
-            "SUB" => bytecode.push(0x9F),
-            "MUL" => bytecode.push(0xA0),
-            "DIV" => bytecode.push(0xA1),
-            "MOD" => bytecode.push(0xA2),
-            "POW" => bytecode.push(0xA3),
-            "SQRT" => bytecode.push(0xA4),
-            "SHL" => bytecode.push(0xA8),
-            "SHR" => bytecode.push(0xA9),
-            "NOT" => bytecode.push(0xAA),
-            "BOOLAND" => bytecode.push(0xAB),
-            "BOOLOR" => bytecode.push(0xAC),
-            "NZ" => bytecode.push(0xB1),
-            "NUMEQUAL" => bytecode.push(0xB3),
-            "NUMNOTEQUAL" => bytecode.push(0xB4),
-            "LT" => bytecode.push(0xB5),
-            "LE" => bytecode.push(0xB6),
-            "GT" => bytecode.push(0xB7),
-            "GE" => bytecode.push(0xB8),
-            "MIN" => bytecode.push(0xB9),
-            "MAX" => bytecode.push(0xBA),
-            "WITHIN" => bytecode.push(0xBB),
-
-            // Compound types
-            "PACK" => bytecode.push(0xC0),
-            "UNPACK" => bytecode.push(0xC1),
-            "NEWARRAY0" => bytecode.push(0xC2),
-            "NEWARRAY" => bytecode.push(0xC3),
-            "NEWSTRUCT0" => bytecode.push(0xC5),
-            "NEWSTRUCT" => bytecode.push(0xC6),
-            "NEWMAP" => bytecode.push(0xC8),
-            "SIZE" => bytecode.push(0xCA),
-            "HASKEY" => bytecode.push(0xCB),
-            "KEYS" => bytecode.push(0xCC),
-            "VALUES" => bytecode.push(0xCD),
-            "PICKITEM" => bytecode.push(0xCE),
-            "APPEND" => bytecode.push(0xCF),
-            "SETITEM" => bytecode.push(0xD0),
-            "REVERSEITEMS" => bytecode.push(0xD1),
-            "REMOVE" => bytecode.push(0xD2),
-            "CLEARITEMS" => bytecode.push(0xD3),
-            "POPITEM" => bytecode.push(0xD4),
-
-            // Types
-            "ISNULL" => bytecode.push(0xD8),
-            "ISTYPE" => bytecode.push(0xD9),
-            "CONVERT" => bytecode.push(0xDB),
-
-            // Crypto
-            "SHA256" => bytecode.push(0xF0),
-            "RIPEMD160" => bytecode.push(0xF1),
-            "HASH160" => bytecode.push(0xF2),
-            "CHECKSIG" => bytecode.push(0xF3),
 
             // Raw byte emission
             "DB" | ".BYTE" => {
@@ -683,8 +493,33 @@ impl Assembler {
                 }
             }
 
+            // Everything else by its operand encoding in the opcode table
             _ => {
-                return Err(AssemblerError::UnknownOpcode(op, line_num).to_string());
+                let info = OpcodeInfo::by_name(&op).ok_or_else(|| {
+                    AssemblerError::UnknownOpcode(op.clone(), line_num).to_string()
+                })?;
+                let jump = (OpCode::JMP as u8..=OpCode::CALL_L as u8).contains(&info.code)
+                    || info.code == OpCode::ENDTRY as u8
+                    || info.code == OpCode::ENDTRY_L as u8;
+                bytecode.push(info.code);
+                match info.operand {
+                    OperandSize::Fixed(0) => {}
+                    OperandSize::Fixed(1) if jump => {
+                        self.emit_jump_offset(bytecode, operands, line_num)?
+                    }
+                    OperandSize::Fixed(4) if jump => {
+                        self.emit_jump_offset_long(bytecode, operands, line_num)?
+                    }
+                    // Slot counts and indexes, and stack item types
+                    OperandSize::Fixed(1) => bytecode.push(self.parse_u8(operands, line_num)?),
+                    _ => {
+                        return Err(AssemblerError::InvalidOperand(
+                            format!("{} is not supported by the assembler, emit it with DB", op),
+                            line_num,
+                        )
+                        .to_string());
+                    }
+                }
             }
         }
 
@@ -896,3 +731,40 @@ impl Assembler {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo_vm_core::Disassembler;
+
+    #[test]
+    fn test_opcode_table_round_trip() {
+        for info in OpcodeInfo::all() {
+            let source = match info.operand {
+                OperandSize::Fixed(0) => info.name.to_string(),
+                OperandSize::Fixed(1) => format!("{} 3", info.name),
+                OperandSize::Fixed(4) if info.name.ends_with("_L") => format!("{} 5", info.name),
+                _ => continue,
+            };
+            let script = Assembler::new().assemble(&source).unwrap();
+            assert_eq!(script[0], info.code, "{source}");
+
+            let (text, size) = Disassembler::new(&script).decode_instruction(0);
+            assert_eq!(size, script.len(), "{source}");
+            assert_eq!(text.split_whitespace().next(), Some(info.name));
+        }
+
+        assert_eq!(
+            Assembler::new().assemble("PUSHT FALSE").unwrap(),
+            [0x08, 0x10]
+        );
+        assert_eq!(
+            Assembler::new().assemble("ISTYPE 0x21").unwrap(),
+            [0xD9, 0x21]
+        );
+        assert!(Assembler::new()
+            .assemble("PUSHA 4")
+            .unwrap_err()
+            .contains("not supported"));
+    }
+}
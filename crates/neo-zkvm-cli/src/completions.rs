@@ -0,0 +1,268 @@
+//! Shell completions and man pages
+//!
+//! The CLI parses its own arguments; [`command`] describes the same commands
+//! and flags as a clap `Command` so clap_complete and clap_mangen can
+//! generate shell integration from it. Keep it in step with `print_help`.
+
+use clap::{Arg, ArgAction, Command, ValueHint};
+
+/// The `neo-zkvm` command line
+pub fn command() -> Command {
+    Command::new("neo-zkvm")
+        .version(crate::VERSION)
+        .about("A comprehensive toolkit for Neo zkVM development")
+        .disable_help_subcommand(true)
+        .disable_version_flag(true)
+        .subcommand_required(true)
+        .arg(
+            Arg::new("version")
+                .short('v')
+                .long("version")
+                .action(ArgAction::Version)
+                .help("Show version information"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .value_name("NAME")
+                .global(true)
+                .help("Default flags from a profile in ~/.config/neo-zkvm/config.toml"),
+        )
+        .subcommands([
+            subcommand("run", "Execute a script and show results")
+                .arg(script())
+                .args(execution_flags())
+                .arg(file_flag(
+                    "dump-storage",
+                    "Write final storage as a JSON fixture",
+                ))
+                .arg(file_flag("trace", "Write the execution trace as JSON")),
+            subcommand("prove", "Generate ZK proof for script execution")
+                .arg(script())
+                .args(execution_flags())
+                .arg(
+                    Arg::new("mode")
+                        .long("mode")
+                        .value_parser(["execute", "mock", "sp1", "plonk", "groth16"])
+                        .help("Proof type (default: sp1)"),
+                )
+                .arg(
+                    Arg::new("backend")
+                        .long("backend")
+                        .value_parser(["env", "cpu", "cuda", "network"])
+                        .help("Where SP1 proves (default: env)"),
+                )
+                .arg(json_flag("Print output and public inputs as JSON")),
+            subcommand("asm", "Assemble source code to bytecode")
+                .arg(
+                    Arg::new("source")
+                        .required(true)
+                        .value_hint(ValueHint::FilePath)
+                        .help("Assembly source or a .neoasm file"),
+                )
+                .arg(file_flag("debug-info", "Write .neodbg debug info")),
+            subcommand("disasm", "Disassemble bytecode to readable format")
+                .arg(script())
+                .arg(debug_info_flag()),
+            subcommand("debug", "Interactive step-by-step debugger")
+                .arg(script())
+                .arg(debug_info_flag())
+                .arg(file_flag("batch", "Run the debugger commands in a file"))
+                .arg(
+                    Arg::new("mi")
+                        .long("mi")
+                        .action(ArgAction::SetTrue)
+                        .help("JSON requests and responses, one per line"),
+                ),
+            subcommand("dap", "Debug Adapter Protocol server on stdio, for editors"),
+            subcommand("inspect", "Analyze and display script information")
+                .arg(script())
+                .arg(json_flag(
+                    "Print security lints and gas golf suggestions as JSON",
+                ))
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .action(ArgAction::SetTrue)
+                        .help("Apply the gas golf suggestions"),
+                )
+                .arg(file_flag("out", "With --fix, write the optimized bytes")),
+            subcommand(
+                "coverage",
+                "Run a script and show which instructions executed",
+            )
+            .arg(script())
+            .args(execution_flags()),
+            subcommand(
+                "diff",
+                "Run two scripts and compare their traces, stacks and gas",
+            )
+            .arg(script().id("a"))
+            .arg(script().id("b"))
+            .args(execution_flags()),
+            subcommand(
+                "trace-diff",
+                "Find the first step where two JSON traces diverge",
+            )
+            .arg(file("a"))
+            .arg(file("b")),
+            subcommand("watch", "Re-assemble and re-run a file whenever it changes")
+                .arg(file("source").help("A .neoasm, .bin or .nef file"))
+                .arg(
+                    Arg::new("run")
+                        .long("run")
+                        .action(ArgAction::SetTrue)
+                        .help("Run the script on each change (default)"),
+                )
+                .arg(file_flag("test", "Run a test file on each change instead"))
+                .args(execution_flags()),
+            subcommand(
+                "test",
+                "Run declarative TOML test files or directories of them",
+            )
+            .arg(
+                Arg::new("files")
+                    .required(true)
+                    .num_args(1..)
+                    .value_hint(ValueHint::AnyPath),
+            )
+            .arg(
+                Arg::new("sp1")
+                    .long("sp1")
+                    .action(ArgAction::SetTrue)
+                    .help("Also run each test in SP1 execute mode"),
+            )
+            .arg(
+                Arg::new("bless")
+                    .long("bless")
+                    .action(ArgAction::SetTrue)
+                    .help("Write snapshots instead of checking them"),
+            )
+            .arg(file_flag("junit", "Write a JUnit XML report")),
+            subcommand(
+                "hash",
+                "Show a script's SHA-256, Neo script hash and address",
+            )
+            .arg(script())
+            .arg(json_flag("Print as JSON")),
+            subcommand(
+                "encode-args",
+                "Show the canonical encoding of arguments and its SHA-256",
+            )
+            .arg(Arg::new("json").help("Arguments as contract parameter JSON"))
+            .arg(typed_arg_flag())
+            .arg(json_flag("Print as JSON").id("json-output")),
+            subcommand("vkey", "Verifying key of the guest program").subcommand(
+                subcommand("export", "Write the guest program's SP1 verifying key")
+                    .arg(file_flag("out", "Output file (default: <elf sha256>.vkey)")),
+            ),
+            subcommand("elf", "Guest program ELF").subcommand(
+                subcommand(
+                    "info",
+                    "Show the guest ELF's size, SHA-256 and SP1 vkey hash",
+                )
+                .arg(json_flag("Print as JSON")),
+            ),
+            subcommand("completions", "Print a shell completion script").arg(
+                Arg::new("shell").required(true).value_parser([
+                    "bash",
+                    "elvish",
+                    "fish",
+                    "powershell",
+                    "zsh",
+                ]),
+            ),
+            subcommand("man", "Print the man page").arg(
+                Arg::new("out")
+                    .long("out")
+                    .value_name("DIR")
+                    .value_hint(ValueHint::DirPath)
+                    .help("Write a page per command into a directory instead"),
+            ),
+            subcommand("version", "Show version information"),
+            subcommand("help", "Show the help message"),
+        ])
+}
+
+fn subcommand(name: &'static str, about: &'static str) -> Command {
+    Command::new(name).about(about).disable_help_flag(true)
+}
+
+fn script() -> Arg {
+    Arg::new("script")
+        .required(true)
+        .value_hint(ValueHint::FilePath)
+        .help("Hex bytecode, a .bin or .nef file, or - for standard input")
+}
+
+fn file(id: &'static str) -> Arg {
+    Arg::new(id).required(true).value_hint(ValueHint::FilePath)
+}
+
+fn file_flag(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .help(help)
+}
+
+fn debug_info_flag() -> Arg {
+    file_flag(
+        "debug-info",
+        ".neodbg or nccs debug info (default: <script>.neodbg if present)",
+    )
+}
+
+fn json_flag(help: &'static str) -> Arg {
+    Arg::new("json")
+        .long("json")
+        .action(ArgAction::SetTrue)
+        .help(help)
+}
+
+fn typed_arg_flag() -> Arg {
+    Arg::new("arg")
+        .long("arg")
+        .value_name("VALUE")
+        .action(ArgAction::Append)
+        .help("Typed argument: int:, bool:, hex:, str:, null or array:[...]")
+}
+
+/// Flags of the commands that run a script
+fn execution_flags() -> [Arg; 4] {
+    [
+        Arg::new("gas")
+            .short('g')
+            .long("gas")
+            .value_name("LIMIT")
+            .help("Gas limit (default: 1000000)"),
+        typed_arg_flag(),
+        Arg::new("args")
+            .long("args")
+            .value_name("JSON")
+            .help("Arguments as contract parameter JSON"),
+        file_flag("storage", "Load initial storage from a JSON fixture"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_complete::Shell;
+
+    #[test]
+    fn test_command_is_consistent() {
+        command().debug_assert();
+    }
+
+    #[test]
+    fn test_completions_list_commands() {
+        let mut script = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut command(), "neo-zkvm", &mut script);
+        let script = String::from_utf8(script).unwrap();
+        for name in ["trace-diff", "encode-args", "--dump-storage", "groth16"] {
+            assert!(script.contains(name), "{}", name);
+        }
+    }
+}
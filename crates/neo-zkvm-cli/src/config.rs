@@ -0,0 +1,214 @@
+//! Config file and named profiles
+//!
+//! `~/.config/neo-zkvm/config.toml` (or `$XDG_CONFIG_HOME/neo-zkvm/`, or
+//! the file named by `NEO_ZKVM_CONFIG`) holds profiles of default flags:
+//!
+//! ```toml
+//! default_profile = "dev"
+//!
+//! [profiles.dev]
+//! gas_limit = 5000000
+//! proof_mode = "mock"
+//! output = "json"
+//!
+//! [profiles.release]
+//! proof_mode = "groth16"
+//! backend = "network"
+//! vkey = "keys/neo-zkvm.vkey"
+//! ```
+//!
+//! `--profile <name>` selects a profile, `default_profile` otherwise. Its
+//! settings become the flags they stand for on the commands that take them,
+//! unless the command line already gives that flag.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profile used when `--profile` is not given
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// `--gas` of `run`, `prove`, `coverage`, `diff` and `watch`
+    pub gas_limit: Option<u64>,
+    /// `--mode` of `prove`: execute, mock, sp1, plonk or groth16
+    pub proof_mode: Option<String>,
+    /// `--backend` of `prove`: env, cpu, cuda or network
+    pub backend: Option<String>,
+    /// `json` adds `--json` to the commands that print JSON
+    pub output: Option<OutputFormat>,
+    /// `--out` of `vkey export`
+    pub vkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file '{}': {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid config file '{}': {}", path.display(), e))
+    }
+}
+
+impl Profile {
+    /// Flags, with their values, this profile gives `command`
+    pub fn flags(&self, command: &str) -> Vec<(&'static str, Option<String>)> {
+        let mut flags = Vec::new();
+        if let Some(gas) = self.gas_limit {
+            if matches!(command, "run" | "prove" | "coverage" | "diff" | "watch") {
+                flags.push(("--gas", Some(gas.to_string())));
+            }
+        }
+        if command == "prove" {
+            if let Some(mode) = &self.proof_mode {
+                flags.push(("--mode", Some(mode.clone())));
+            }
+            if let Some(backend) = &self.backend {
+                flags.push(("--backend", Some(backend.clone())));
+            }
+        }
+        if self.output == Some(OutputFormat::Json)
+            && matches!(
+                command,
+                "prove" | "inspect" | "hash" | "encode-args" | "elf"
+            )
+        {
+            flags.push(("--json", None));
+        }
+        if let Some(vkey) = &self.vkey {
+            if command == "vkey" {
+                flags.push(("--out", Some(vkey.clone())));
+            }
+        }
+        flags
+    }
+}
+
+/// `NEO_ZKVM_CONFIG`, or `neo-zkvm/config.toml` in the user's config
+/// directory
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("NEO_ZKVM_CONFIG") {
+        return Some(path.into());
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(base.join("neo-zkvm").join("config.toml"))
+}
+
+/// Remove `--profile <name>` from the command line `args` (program name,
+/// command, then its arguments) and append the selected profile's flags
+pub fn apply_profile(args: &mut Vec<String>) -> Result<(), String> {
+    let requested = match args.iter().position(|arg| arg == "--profile") {
+        Some(i) if i + 1 < args.len() => {
+            let name = args.remove(i + 1);
+            args.remove(i);
+            Some(name)
+        }
+        Some(_) => return Err("Missing profile name after --profile".to_string()),
+        None => None,
+    };
+
+    let path = config_path().filter(|path| path.exists());
+    let config = match &path {
+        Some(path) => Config::load(path)?,
+        None if requested.is_some() => {
+            return Err("--profile given but no config file was found".to_string())
+        }
+        None => return Ok(()),
+    };
+    let Some(name) = requested.or(config.default_profile.clone()) else {
+        return Ok(());
+    };
+    let profile = config
+        .profiles
+        .get(&name)
+        .ok_or_else(|| format!("Unknown profile '{}'", name))?;
+
+    let command = args.get(1).cloned().unwrap_or_default();
+    extend_with_profile(args, profile, &command);
+    Ok(())
+}
+
+/// Append the flags `profile` gives `command` that `args` does not set
+fn extend_with_profile(args: &mut Vec<String>, profile: &Profile, command: &str) {
+    for (flag, value) in profile.flags(command) {
+        let given = args
+            .iter()
+            .any(|arg| arg == flag || (flag == "--gas" && arg == "-g"));
+        if !given {
+            args.push(flag.to_string());
+            args.extend(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        default_profile = "dev"
+
+        [profiles.dev]
+        gas_limit = 5000000
+        proof_mode = "mock"
+        output = "json"
+        vkey = "neo.vkey"
+    "#;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.default_profile.as_deref(), Some("dev"));
+        let profile = &config.profiles["dev"];
+        assert_eq!(profile.gas_limit, Some(5_000_000));
+        assert_eq!(profile.output, Some(OutputFormat::Json));
+        assert_eq!(profile.backend, None);
+
+        assert!(Config::parse("[profiles.dev]\ngas = 5").is_err());
+        assert!(Config::parse("[profiles.dev]\noutput = \"yaml\"").is_err());
+    }
+
+    #[test]
+    fn test_profile_flags() {
+        let config = Config::parse(CONFIG).unwrap();
+        let profile = &config.profiles["dev"];
+
+        let mut prove = args("neo-zkvm prove 40 --gas 7");
+        extend_with_profile(&mut prove, profile, "prove");
+        assert_eq!(prove, args("neo-zkvm prove 40 --gas 7 --mode mock --json"));
+
+        let mut run = args("neo-zkvm run 40");
+        extend_with_profile(&mut run, profile, "run");
+        assert_eq!(run, args("neo-zkvm run 40 --gas 5000000"));
+
+        let mut vkey = args("neo-zkvm vkey export");
+        extend_with_profile(&mut vkey, profile, "vkey");
+        assert_eq!(vkey, args("neo-zkvm vkey export --out neo.vkey"));
+    }
+}
@@ -0,0 +1,394 @@
+//! Debug info
+//!
+//! A `.neodbg` file maps a script back to its source: function ranges with
+//! the names of their argument and local slots, static slot names and the
+//! source line of each instruction. `neo-zkvm asm --debug-info` writes one;
+//! `debug` and `disasm` read it, from `--debug-info` or from a `.neodbg` file
+//! next to the script, to show functions, lines and slots by name.
+//!
+//! The debug info Neo compilers write (nccs `*.debug.json`, the JSON inside
+//! a `.nefdbgnfo`) is read as well.
+
+use neo_vm_core::Disassembler;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Version of the `.neodbg` format written by [`DebugInfo::write`]
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugInfo {
+    pub version: u32,
+    /// Source files, referred to by index from sequence points
+    #[serde(default)]
+    pub documents: Vec<String>,
+    #[serde(default)]
+    pub functions: Vec<Function>,
+    /// Static slot names by index
+    #[serde(default)]
+    pub statics: Vec<String>,
+    /// Source line of the instructions from each offset on, by offset
+    #[serde(default)]
+    pub sequence_points: Vec<SequencePoint>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Function {
+    pub name: String,
+    /// First offset of the function
+    pub start: usize,
+    /// Offset just past the function
+    pub end: usize,
+    /// Argument slot names by index
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    /// Local slot names by index
+    #[serde(default)]
+    pub locals: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequencePoint {
+    pub offset: usize,
+    #[serde(default)]
+    pub document: usize,
+    pub line: usize,
+}
+
+/// Kind of slot an instruction loads or stores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Static,
+    Local,
+    Argument,
+}
+
+impl DebugInfo {
+    /// Read a `.neodbg` file or nccs debug info JSON
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid debug info {}: {}", path.display(), e))
+    }
+
+    /// The `.neodbg` file next to `script`, e.g. `contract.neodbg` for
+    /// `contract.nef`, if there is one
+    pub fn find_for(script: &str) -> Result<Option<Self>, String> {
+        let path = Path::new(script).with_extension("neodbg");
+        if script == "-" || !path.is_file() {
+            return Ok(None);
+        }
+        Self::load(&path).map(Some)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if value.get("methods").is_some() {
+            return from_nccs(&value);
+        }
+        let info: Self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        if info.version != VERSION {
+            return Err(format!("unsupported version {}", info.version));
+        }
+        Ok(info)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Innermost function containing `offset`
+    pub fn function_at(&self, offset: usize) -> Option<&Function> {
+        self.functions
+            .iter()
+            .filter(|f| f.start <= offset && offset < f.end)
+            .min_by_key(|f| f.end - f.start)
+    }
+
+    /// Source line of the instruction at `offset`, as `file:line`, or
+    /// `line N` without a document name
+    pub fn location(&self, offset: usize) -> Option<String> {
+        let point = self.sequence_point_at(offset)?;
+        Some(match self.documents.get(point.document) {
+            Some(document) => format!("{}:{}", document, point.line),
+            None => format!("line {}", point.line),
+        })
+    }
+
+    /// Sequence point covering the instruction at `offset`
+    pub fn sequence_point_at(&self, offset: usize) -> Option<&SequencePoint> {
+        self.sequence_points
+            .iter()
+            .filter(|p| p.offset <= offset)
+            .max_by_key(|p| p.offset)
+    }
+
+    /// Index of the document at `path`, comparing canonical paths
+    pub fn document_index(&self, path: &Path) -> Option<usize> {
+        let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.into());
+        let path = canonical(path);
+        self.documents
+            .iter()
+            .position(|document| canonical(Path::new(document)) == path)
+    }
+
+    /// Offset and line of a breakpoint on `line` of `document`: the first
+    /// instruction of that line, or of the next line with code
+    pub fn breakpoint_offset(&self, document: usize, line: usize) -> Option<(usize, usize)> {
+        self.sequence_points
+            .iter()
+            .filter(|p| p.document == document && p.line >= line)
+            .min_by_key(|p| (p.line, p.offset))
+            .map(|p| (p.offset, p.line))
+    }
+
+    /// Where `offset` is, like `main+0x3 (program.neoasm:7)`
+    pub fn describe(&self, offset: usize) -> Option<String> {
+        let function = self.function_at(offset).map(|f| match offset - f.start {
+            0 => f.name.clone(),
+            n => format!("{}+0x{:X}", f.name, n),
+        });
+        match (function, self.location(offset)) {
+            (Some(function), Some(location)) => Some(format!("{} ({})", function, location)),
+            (function, location) => function.or(location),
+        }
+    }
+
+    /// Disassembly of `script` in the [`Disassembler`] layout, with a
+    /// header line per function and slot names and source lines as comments
+    pub fn disassemble(&self, script: &[u8]) -> String {
+        let disassembler = Disassembler::new(script);
+        let mut output = String::new();
+        let mut ip = 0;
+        while ip < script.len() {
+            let (name, size) = disassembler.decode_instruction(ip);
+            let bytes = &script[ip..ip + size.min(script.len() - ip)];
+            let hex_bytes = bytes
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            if let Some(function) = self.functions.iter().find(|f| f.start == ip) {
+                output.push_str(&format!("{}:\n", function.name));
+            }
+            let mut comments: Vec<String> = slot_operand(&name)
+                .and_then(|(slot, index)| self.slot_name(ip, slot, index))
+                .map(str::to_string)
+                .into_iter()
+                .collect();
+            if self.sequence_points.iter().any(|p| p.offset == ip) {
+                comments.extend(self.location(ip));
+            }
+            if comments.is_empty() {
+                output.push_str(&format!("{:04X}:  {:16}  {}\n", ip, hex_bytes, name));
+            } else {
+                output.push_str(&format!(
+                    "{:04X}:  {:16}  {:24}  ; {}\n",
+                    ip,
+                    hex_bytes,
+                    name,
+                    comments.join(", ")
+                ));
+            }
+
+            ip += size;
+        }
+        output
+    }
+
+    /// Name of slot `index` as seen from the instruction at `offset`
+    pub fn slot_name(&self, offset: usize, slot: Slot, index: usize) -> Option<&str> {
+        let names = match slot {
+            Slot::Static => &self.statics,
+            Slot::Local => &self.function_at(offset)?.locals,
+            Slot::Argument => &self.function_at(offset)?.arguments,
+        };
+        names
+            .get(index)
+            .map(String::as_str)
+            .filter(|n| !n.is_empty())
+    }
+}
+
+/// Slot a disassembled instruction such as `LDLOC2` or `STARG 7` loads or
+/// stores
+pub fn slot_operand(instruction: &str) -> Option<(Slot, usize)> {
+    let (mnemonic, operand) = instruction
+        .split_once(' ')
+        .map_or((instruction, None), |(m, o)| (m, Some(o)));
+    let (slot, rest) = [
+        ("LDSFLD", Slot::Static),
+        ("STSFLD", Slot::Static),
+        ("LDLOC", Slot::Local),
+        ("STLOC", Slot::Local),
+        ("LDARG", Slot::Argument),
+        ("STARG", Slot::Argument),
+    ]
+    .into_iter()
+    .find_map(|(prefix, slot)| mnemonic.strip_prefix(prefix).map(|rest| (slot, rest)))?;
+    let index = if rest.is_empty() { operand? } else { rest };
+    Some((slot, index.trim().parse().ok()?))
+}
+
+/// Convert nccs debug info: `methods` with `range` (inclusive), `params`,
+/// `variables` and `sequence-points`, and `static-variables`
+fn from_nccs(value: &Value) -> Result<DebugInfo, String> {
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut info = DebugInfo {
+        version: VERSION,
+        documents: strings(value.get("documents")),
+        statics: slot_names(&strings(value.get("static-variables"))),
+        ..DebugInfo::default()
+    };
+    for method in value["methods"]
+        .as_array()
+        .ok_or("methods is not an array")?
+    {
+        let range = method["range"].as_str().ok_or("method without a range")?;
+        let (start, end) = range
+            .split_once('-')
+            .and_then(|(s, e)| Some((s.parse::<usize>().ok()?, e.parse::<usize>().ok()?)))
+            .ok_or_else(|| format!("invalid range {}", range))?;
+        // Names are `Namespace.Contract,method`
+        let name = method["name"].as_str().unwrap_or_default();
+        info.functions.push(Function {
+            name: name.rsplit(',').next().unwrap_or(name).to_string(),
+            start,
+            end: end + 1,
+            arguments: slot_names(&strings(method.get("params"))),
+            locals: slot_names(&strings(method.get("variables"))),
+        });
+        for point in strings(method.get("sequence-points")) {
+            info.sequence_points.push(
+                sequence_point(&point)
+                    .ok_or_else(|| format!("invalid sequence point {}", point))?,
+            );
+        }
+    }
+    info.sequence_points.sort_by_key(|p| p.offset);
+    Ok(info)
+}
+
+/// Names from nccs `name,type` or `name,type,index` entries, placed at
+/// their index when one is given
+fn slot_names(entries: &[String]) -> Vec<String> {
+    let mut names = Vec::new();
+    for (position, entry) in entries.iter().enumerate() {
+        let mut fields = entry.split(',');
+        let name = fields.next().unwrap_or_default().to_string();
+        let index = fields
+            .nth(1)
+            .and_then(|i| i.trim().parse().ok())
+            .unwrap_or(position);
+        if names.len() <= index {
+            names.resize(index + 1, String::new());
+        }
+        names[index] = name;
+    }
+    names
+}
+
+/// nccs `offset[document]line:column-line:column`
+fn sequence_point(text: &str) -> Option<SequencePoint> {
+    let (offset, rest) = text.split_once('[')?;
+    let (document, rest) = rest.split_once(']')?;
+    let line = rest.split(':').next()?;
+    Some(SequencePoint {
+        offset: offset.parse().ok()?,
+        document: document.parse().ok()?,
+        line: line.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nccs_debug_info() {
+        let info = DebugInfo::parse(
+            r#"{
+                "hash": "0x0000000000000000000000000000000000000000",
+                "documents": ["Contract.cs"],
+                "static-variables": ["owner,Hash160,0"],
+                "methods": [{
+                    "id": "Contract.Transfer",
+                    "name": "Contract,transfer",
+                    "range": "0-9",
+                    "params": ["from,Hash160", "amount,Integer"],
+                    "return": "Boolean",
+                    "variables": ["ok,Boolean,1"],
+                    "sequence-points": ["0[0]12:9-12:30", "4[0]13:9-13:20"]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.functions[0].name, "transfer");
+        assert_eq!(info.functions[0].end, 10);
+        assert_eq!(info.functions[0].locals, ["", "ok"]);
+        assert_eq!(info.describe(5).unwrap(), "transfer+0x5 (Contract.cs:13)");
+        assert_eq!(info.slot_name(5, Slot::Argument, 1), Some("amount"));
+        assert_eq!(info.slot_name(5, Slot::Local, 0), None);
+        assert_eq!(info.slot_name(5, Slot::Static, 0), Some("owner"));
+        assert!(info.function_at(10).is_none());
+    }
+
+    #[test]
+    fn test_assembler_debug_info() {
+        let mut assembler = crate::assembler::Assembler::new();
+        let script = assembler
+            .assemble(
+                ".statics total\n\
+                 .func main\n\
+                 .locals x\n\
+                 INITSLOT 1 0\n\
+                 PUSH5\n\
+                 STLOC0\n\
+                 .endfunc\n\
+                 RET\n",
+            )
+            .unwrap();
+        let info = assembler.debug_info();
+
+        assert_eq!(info.functions.len(), 1);
+        assert_eq!((info.functions[0].start, info.functions[0].end), (0, 5));
+        assert_eq!(info.describe(3).unwrap(), "main+0x3 (line 5)");
+        assert_eq!(info.location(5).unwrap(), "line 8");
+        assert!(info.function_at(5).is_none());
+        assert!(info.disassemble(&script).contains("main:\n"));
+        // Directives have no code, so breakpoints on them move down
+        assert_eq!(info.breakpoint_offset(0, 2), Some((0, 4)));
+        assert_eq!(info.breakpoint_offset(0, 7), Some((5, 8)));
+        assert_eq!(info.breakpoint_offset(0, 9), None);
+        assert_eq!(
+            DebugInfo::parse(&serde_json::to_string(info).unwrap()).unwrap(),
+            *info
+        );
+    }
+
+    #[test]
+    fn test_slot_operand() {
+        assert_eq!(slot_operand("LDLOC2"), Some((Slot::Local, 2)));
+        assert_eq!(slot_operand("STARG 7"), Some((Slot::Argument, 7)));
+        assert_eq!(slot_operand("STSFLD0"), Some((Slot::Static, 0)));
+        assert_eq!(slot_operand("ADD"), None);
+    }
+}
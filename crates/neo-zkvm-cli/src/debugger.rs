@@ -0,0 +1,834 @@
+//! Interactive debugger
+//!
+//! `neo-zkvm debug` steps a script with breakpoints, storage watchpoints,
+//! undo and checkpoints, naming functions and slots from [debug
+//! info](crate::debug_info). A fault leaves the VM as it was for a
+//! post-mortem. Commands can also come from a `--batch` file, or as JSON
+//! requests through the [machine interface](mi) or the [Debug Adapter
+//! Protocol](dap).
+
+use neo_vm_core::{
+    Disassembler, ExecutionHook, HookAction, NeoVM, VMState, VmSnapshot, WatchHit, WatchKind,
+};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex};
+
+use crate::debug_info::{DebugInfo, Slot};
+use crate::{describe_fault, VERSION};
+
+pub mod dap;
+mod mi;
+
+/// Steps kept for the post-mortem shown when the program faults
+const RECENT_STEPS: usize = 256;
+
+/// Steps the post-mortem shows unless `postmortem <n>` asks for more
+const POST_MORTEM_STEPS: usize = 10;
+
+/// An executed instruction: offset, opcode and gas consumed after it
+type RecentStep = (usize, u8, u64);
+
+/// Pauses the VM before any instruction at a breakpoint address and keeps
+/// the last [`RECENT_STEPS`] executed instructions
+struct DebuggerHook {
+    breakpoints: Arc<Mutex<Vec<usize>>>,
+    recent: Arc<Mutex<VecDeque<RecentStep>>>,
+}
+
+impl ExecutionHook for DebuggerHook {
+    fn before_op(&mut self, ip: usize, _opcode: u8) -> HookAction {
+        if self.breakpoints.lock().unwrap().contains(&ip) {
+            HookAction::Break
+        } else {
+            HookAction::Continue
+        }
+    }
+
+    fn after_op(&mut self, ip: usize, opcode: u8, gas_consumed: u64) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_STEPS {
+            recent.pop_front();
+        }
+        recent.push_back((ip, opcode, gas_consumed));
+    }
+}
+
+/// How far [`Debugger::resume`] runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resume {
+    /// One instruction
+    Step,
+    /// To the next breakpoint or watchpoint
+    Continue,
+    /// To the end, ignoring breakpoints and watchpoints
+    Run,
+}
+
+/// Why [`Debugger::resume`] stopped
+#[derive(Debug)]
+enum Stop {
+    Step,
+    Breakpoint,
+    Watch(WatchHit),
+    Halt,
+    Fault,
+    /// The program had already halted or faulted
+    Terminated,
+    Error(String),
+}
+
+pub struct Debugger {
+    vm: NeoVM,
+    script: Vec<u8>,
+    breakpoints: Arc<Mutex<Vec<usize>>>,
+    /// Last executed instructions, for the post-mortem
+    recent: Arc<Mutex<VecDeque<RecentStep>>>,
+    history: Vec<String>,
+    /// State before each step/continue/run, for `back`
+    undo: Vec<VmSnapshot>,
+    checkpoint: Option<VmSnapshot>,
+    /// Function, slot and source names
+    debug_info: Option<DebugInfo>,
+}
+
+impl Debugger {
+    pub fn new(script: Vec<u8>, gas_limit: u64, debug_info: Option<DebugInfo>) -> Self {
+        let breakpoints = Arc::new(Mutex::new(Vec::new()));
+        let recent = Arc::new(Mutex::new(VecDeque::new()));
+        let vm = Self::load_vm(&script, gas_limit, &breakpoints, &recent);
+        Self {
+            vm,
+            script,
+            breakpoints,
+            recent,
+            history: Vec::new(),
+            undo: Vec::new(),
+            checkpoint: None,
+            debug_info,
+        }
+    }
+
+    fn load_vm(
+        script: &[u8],
+        gas_limit: u64,
+        breakpoints: &Arc<Mutex<Vec<usize>>>,
+        recent: &Arc<Mutex<VecDeque<RecentStep>>>,
+    ) -> NeoVM {
+        recent.lock().unwrap().clear();
+        let mut vm = NeoVM::new(gas_limit);
+        vm.set_hook(DebuggerHook {
+            breakpoints: breakpoints.clone(),
+            recent: recent.clone(),
+        });
+        let _ = vm.load_script(script.to_vec());
+        vm
+    }
+
+    /// Execute exactly one instruction, stepping over a breakpoint at the current address
+    fn step_once(&mut self) -> Result<(), neo_vm_core::VMError> {
+        self.vm.watch_hit = None;
+        self.vm.execute_next()?;
+        // A watchpoint breaks after the instruction, a breakpoint before it
+        if matches!(self.vm.state, VMState::Break) && self.vm.watch_hit.is_none() {
+            self.vm.execute_next()?;
+        }
+        Ok(())
+    }
+
+    fn is_terminated(&self) -> bool {
+        matches!(self.vm.state, VMState::Halt | VMState::Fault)
+    }
+
+    /// Step, continue or run, saving the state first for `back`
+    fn resume(&mut self, how: Resume) -> Stop {
+        if self.is_terminated() {
+            return Stop::Terminated;
+        }
+        self.undo.push(self.vm.snapshot());
+
+        let result = match how {
+            Resume::Step => self.step_once(),
+            Resume::Continue => {
+                // Leave the current breakpoint before running on to the next one
+                let mut result = self.step_once();
+                while result.is_ok() && matches!(self.vm.state, VMState::None) {
+                    result = self.vm.execute_next();
+                }
+                result
+            }
+            Resume::Run => {
+                // Breaks are resumed immediately, so breakpoints are ignored
+                let mut result = Ok(());
+                while result.is_ok() && !self.is_terminated() {
+                    result = self.vm.execute_next();
+                }
+                self.vm.watch_hit = None;
+                result
+            }
+        };
+
+        match (result, &self.vm.state) {
+            (_, VMState::Halt) => Stop::Halt,
+            (_, VMState::Fault) => Stop::Fault,
+            (Err(e), _) => Stop::Error(e.to_string()),
+            _ => match self.vm.watch_hit.take() {
+                Some(hit) => Stop::Watch(hit),
+                None if matches!(how, Resume::Continue) => Stop::Breakpoint,
+                None => Stop::Step,
+            },
+        }
+    }
+
+    /// Read commands from `input` until `quit` or the end of input. With
+    /// `echo`, as for `--batch`, each command is printed after the prompt
+    /// and blank lines and `#` comments are skipped rather than repeating
+    /// the last command.
+    pub fn run(&mut self, mut input: impl BufRead, echo: bool) -> Result<(), String> {
+        println!("Neo zkVM Debugger v{}", VERSION);
+        if !echo {
+            println!("Type 'help' for available commands.\n");
+        }
+
+        self.print_current_state();
+
+        let mut stdout = io::stdout();
+
+        loop {
+            let prompt = if matches!(self.vm.state, VMState::Fault) {
+                "(neodbg post-mortem) "
+            } else {
+                "(neodbg) "
+            };
+
+            if !echo {
+                print!("{}", prompt);
+                stdout.flush().unwrap();
+            }
+
+            let mut line = String::new();
+            match input.read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    if !echo {
+                        println!();
+                    }
+                    break;
+                }
+                Ok(_) => {}
+            }
+
+            let line = line.trim();
+            if echo {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                println!("{}{}", prompt, line);
+            }
+            if line.is_empty() {
+                // Repeat last command
+                if let Some(last) = self.history.last().cloned() {
+                    self.execute_command(&last)?;
+                }
+                continue;
+            }
+
+            self.history.push(line.to_string());
+
+            if self.execute_command(line)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute_command(&mut self, cmd: &str) -> Result<bool, String> {
+        let parts: Vec<&str> = cmd.split_whitespace().collect();
+        if parts.is_empty() {
+            return Ok(false);
+        }
+
+        match parts[0] {
+            "help" | "h" => self.cmd_help(),
+            "step" | "s" | "n" => self.cmd_resume(Resume::Step),
+            "continue" | "c" => self.cmd_resume(Resume::Continue),
+            "run" | "r" => self.cmd_resume(Resume::Run),
+            "back" => self.cmd_back(),
+            "checkpoint" | "cp" => self.cmd_checkpoint(),
+            "restore" => self.cmd_restore(),
+            "break" | "b" => self.cmd_breakpoint(&parts[1..]),
+            "delete" | "d" => self.cmd_delete_breakpoint(&parts[1..]),
+            "watch" | "w" => self.cmd_watch(&parts[1..]),
+            "unwatch" => self.cmd_unwatch(&parts[1..]),
+            "info" | "i" => self.cmd_info(&parts[1..]),
+            "print" | "p" => self.cmd_print(&parts[1..]),
+            "stack" => self.cmd_stack(),
+            "slots" => self.cmd_slots(),
+            "backtrace" | "bt" => self.cmd_backtrace(),
+            "postmortem" | "pm" => self.cmd_post_mortem(&parts[1..]),
+            "disasm" => self.cmd_disasm(),
+            "reset" => self.cmd_reset(),
+            "quit" | "q" | "exit" => return Ok(true),
+            _ => {
+                println!(
+                    "Unknown command: '{}'. Type 'help' for available commands.",
+                    parts[0]
+                );
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn cmd_help(&self) {
+        println!(
+            r#"
+Available commands:
+  step, s, n          Execute next instruction
+  continue, c         Continue until breakpoint or halt
+  run, r              Run to completion
+  back                Undo the last step/continue/run
+  checkpoint, cp      Save the current VM state
+  restore             Return to the saved checkpoint
+  break <addr>, b     Set breakpoint at address (hex)
+  delete <addr>, d    Delete breakpoint
+  watch <key>, w      Break when a storage key (hex) changes
+  unwatch <key>       Delete watchpoint
+  info breakpoints    List all breakpoints
+  info watches        List all watchpoints
+  info registers      Show VM state
+  print <n>, p        Print stack item at index n
+  stack               Show full stack
+  slots               Show argument, local and static slots
+  backtrace, bt       Show the invocation stack
+  postmortem [n], pm  After a fault, show it with the last n steps (default: 10)
+  disasm              Disassemble current script
+  reset               Reset VM to initial state
+  quit, q, exit       Exit debugger
+"#
+        );
+    }
+
+    fn cmd_resume(&mut self, how: Resume) {
+        match self.resume(how) {
+            Stop::Terminated => {
+                println!("Program has terminated. Use 'reset' to restart.");
+                return;
+            }
+            Stop::Watch(hit) => print_watch_hit(&hit),
+            Stop::Breakpoint => println!("Breakpoint hit at 0x{:04X}", self.get_current_ip()),
+            Stop::Error(e) => println!("Error: {}", e),
+            // A fault is reported by the post-mortem
+            Stop::Step | Stop::Halt | Stop::Fault => {}
+        }
+
+        self.print_current_state();
+    }
+
+    /// Undo the last step, continue or run
+    fn back(&mut self) -> bool {
+        let Some(snapshot) = self.undo.pop() else {
+            return false;
+        };
+        self.vm.restore(&snapshot);
+        true
+    }
+
+    fn cmd_back(&mut self) {
+        if self.back() {
+            self.print_current_state();
+        } else {
+            println!("Nothing to undo.");
+        }
+    }
+
+    fn cmd_checkpoint(&mut self) {
+        self.checkpoint = Some(self.vm.snapshot());
+        println!("Checkpoint saved at 0x{:04X}", self.get_current_ip());
+    }
+
+    /// Return to the saved checkpoint, if there is one
+    fn restore_checkpoint(&mut self) -> bool {
+        let Some(snapshot) = &self.checkpoint else {
+            return false;
+        };
+        self.vm.restore(snapshot);
+        self.undo.clear();
+        true
+    }
+
+    fn cmd_restore(&mut self) {
+        if self.restore_checkpoint() {
+            println!("Restored checkpoint.");
+            self.print_current_state();
+        } else {
+            println!("No checkpoint saved. Use 'checkpoint' first.");
+        }
+    }
+
+    /// Add a breakpoint, returning false if there already is one at `addr`
+    fn add_breakpoint(&self, addr: usize) -> bool {
+        let mut breakpoints = self.breakpoints.lock().unwrap();
+        if breakpoints.contains(&addr) {
+            return false;
+        }
+        breakpoints.push(addr);
+        true
+    }
+
+    fn remove_breakpoint(&self, addr: usize) -> bool {
+        let mut breakpoints = self.breakpoints.lock().unwrap();
+        let len = breakpoints.len();
+        breakpoints.retain(|&bp| bp != addr);
+        breakpoints.len() != len
+    }
+
+    fn cmd_breakpoint(&mut self, args: &[&str]) {
+        if args.is_empty() {
+            println!("Usage: break <address>");
+            return;
+        }
+
+        match parse_address(args[0]) {
+            Some(addr) if self.add_breakpoint(addr) => {
+                println!("Breakpoint set at 0x{:04X}", addr)
+            }
+            Some(addr) => println!("Breakpoint already exists at 0x{:04X}", addr),
+            None => println!("Invalid address: {}", args[0]),
+        }
+    }
+
+    fn cmd_delete_breakpoint(&mut self, args: &[&str]) {
+        if args.is_empty() {
+            println!("Usage: delete <address>");
+            return;
+        }
+
+        match parse_address(args[0]) {
+            Some(addr) if self.remove_breakpoint(addr) => {
+                println!("Breakpoint removed at 0x{:04X}", addr)
+            }
+            Some(addr) => println!("No breakpoint at 0x{:04X}", addr),
+            None => println!("Invalid address: {}", args[0]),
+        }
+    }
+
+    /// Watch a storage key, returning false if it is already watched
+    fn watch(&mut self, key: Vec<u8>) -> bool {
+        let kind = WatchKind::StorageKey(key);
+        if self.vm.watches.iter().any(|watch| watch.kind == kind) {
+            return false;
+        }
+        self.vm.add_watch(kind);
+        true
+    }
+
+    fn cmd_watch(&mut self, args: &[&str]) {
+        let Some(key) = args.first() else {
+            println!("Usage: watch <key>");
+            return;
+        };
+
+        match hex::decode(key.trim_start_matches("0x")) {
+            Ok(key) => {
+                let kind = WatchKind::StorageKey(key.clone());
+                if self.watch(key) {
+                    println!("Watching {}", describe_watch(&kind));
+                } else {
+                    println!("Already watching {}", describe_watch(&kind));
+                }
+            }
+            Err(_) => println!("Invalid key: {}", key),
+        }
+    }
+
+    fn cmd_unwatch(&mut self, args: &[&str]) {
+        let Some(key) = args.first() else {
+            println!("Usage: unwatch <key>");
+            return;
+        };
+
+        match hex::decode(key.trim_start_matches("0x")) {
+            Ok(key) => {
+                let kind = WatchKind::StorageKey(key);
+                if self.vm.remove_watch(&kind) {
+                    println!("Stopped watching {}", describe_watch(&kind));
+                } else {
+                    println!("Not watching {}", describe_watch(&kind));
+                }
+            }
+            Err(_) => println!("Invalid key: {}", key),
+        }
+    }
+
+    fn cmd_info(&self, args: &[&str]) {
+        if args.is_empty() {
+            println!("Usage: info <breakpoints|watches|registers>");
+            return;
+        }
+
+        match args[0] {
+            "breakpoints" | "b" => {
+                let breakpoints = self.breakpoints.lock().unwrap();
+                if breakpoints.is_empty() {
+                    println!("No breakpoints set.");
+                } else {
+                    println!("Breakpoints:");
+                    for (i, bp) in breakpoints.iter().enumerate() {
+                        println!("  {}: 0x{:04X}", i + 1, bp);
+                    }
+                }
+            }
+            "watches" | "w" => {
+                if self.vm.watches.is_empty() {
+                    println!("No watchpoints set.");
+                } else {
+                    println!("Watchpoints:");
+                    for (i, watch) in self.vm.watches.iter().enumerate() {
+                        println!(
+                            "  {}: {} = {}",
+                            i + 1,
+                            describe_watch(&watch.kind),
+                            describe_watched_value(&watch.value)
+                        );
+                    }
+                }
+            }
+            "registers" | "r" => {
+                println!("VM State:");
+                println!("  State:        {:?}", self.vm.state);
+                println!("  IP:           0x{:04X}", self.get_current_ip());
+                println!("  Gas consumed: {}", self.vm.gas_consumed);
+                println!("  Gas limit:    {}", self.vm.gas_limit);
+                println!("  Stack depth:  {}", self.vm.eval_stack.len());
+            }
+            _ => println!("Unknown info type: {}", args[0]),
+        }
+    }
+
+    fn cmd_print(&self, args: &[&str]) {
+        if args.is_empty() {
+            if let Some(top) = self.vm.eval_stack.last() {
+                println!("Top: {:?}", top);
+            } else {
+                println!("Stack is empty.");
+            }
+            return;
+        }
+
+        match args[0].parse::<usize>() {
+            Ok(idx) => {
+                let len = self.vm.eval_stack.len();
+                if idx < len {
+                    println!("[{}]: {:?}", idx, self.vm.eval_stack[len - 1 - idx]);
+                } else {
+                    println!("Index out of range (stack depth: {})", len);
+                }
+            }
+            Err(_) => println!("Invalid index: {}", args[0]),
+        }
+    }
+
+    fn cmd_stack(&self) {
+        if self.vm.eval_stack.is_empty() {
+            println!("Stack is empty.");
+        } else {
+            println!("Stack (top → bottom):");
+            for (i, item) in self.vm.eval_stack.iter().rev().enumerate() {
+                println!("  [{}] {:?}", i, item);
+            }
+        }
+    }
+
+    fn cmd_slots(&self) {
+        let ip = self.get_current_ip();
+        for (title, slot, items) in [
+            ("Arguments", Slot::Argument, &self.vm.argument_slots),
+            ("Locals", Slot::Local, &self.vm.local_slots),
+            ("Statics", Slot::Static, &self.vm.static_slots),
+        ] {
+            if items.is_empty() {
+                println!("{}: none", title);
+                continue;
+            }
+            println!("{}:", title);
+            for (i, item) in items.iter().enumerate() {
+                match self
+                    .debug_info
+                    .as_ref()
+                    .and_then(|info| info.slot_name(ip, slot, i))
+                {
+                    Some(name) => println!("  [{}] {} = {:?}", i, name, item),
+                    None => println!("  [{}] {:?}", i, item),
+                }
+            }
+        }
+    }
+
+    /// Offsets of the invocation stack's frames, innermost first
+    fn frames(&self) -> Vec<usize> {
+        // A faulted frame has moved past the instruction that faulted
+        let faulted_at = self
+            .vm
+            .fault_info
+            .as_ref()
+            .filter(|_| matches!(self.vm.state, VMState::Fault))
+            .map(|fault| fault.ip);
+        self.vm
+            .invocation_stack
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, context)| faulted_at.filter(|_| i == 0).unwrap_or(context.ip))
+            .collect()
+    }
+
+    fn cmd_backtrace(&self) {
+        let frames = self.frames();
+        if frames.is_empty() {
+            println!("No frames.");
+            return;
+        }
+        for (i, ip) in frames.into_iter().enumerate() {
+            match self.describe_offset(ip) {
+                Some(location) => println!("  #{} 0x{:04X} in {}", i, ip, location),
+                None => println!("  #{} 0x{:04X}", i, ip),
+            }
+        }
+    }
+
+    fn cmd_post_mortem(&self, args: &[&str]) {
+        match self.post_mortem_steps(args) {
+            Ok(steps) => {
+                if steps > RECENT_STEPS {
+                    println!("Only the last {} steps are kept.", RECENT_STEPS);
+                }
+                self.print_post_mortem(steps);
+            }
+            Err(message) => println!("{}", message),
+        }
+    }
+
+    /// Steps `postmortem` should show, or why it cannot
+    fn post_mortem_steps(&self, args: &[&str]) -> Result<usize, String> {
+        if !matches!(self.vm.state, VMState::Fault) {
+            return Err("The program has not faulted.".to_string());
+        }
+        match args.first() {
+            None => Ok(POST_MORTEM_STEPS),
+            Some(n) => n.parse().map_err(|_| format!("Invalid step count: {}", n)),
+        }
+    }
+
+    /// Where and why the program faulted, the last `steps` instructions
+    /// before it, the stack, slots and invocation stack
+    fn print_post_mortem(&self, steps: usize) {
+        let Some(fault) = &self.vm.fault_info else {
+            println!("Program faulted!");
+            return;
+        };
+        println!("Program faulted: {}", describe_fault(&self.script, fault));
+        if let Some(location) = self.describe_offset(fault.ip) {
+            println!("  in {}", location);
+        }
+
+        let (skipped, recent) = self.recent_steps(steps.saturating_sub(1));
+        println!("\nLast steps:");
+        if skipped > 0 {
+            println!("    ... {} earlier", skipped);
+        }
+        for (ip, opcode, gas) in recent {
+            println!(
+                "    0x{:04X}  {:<28} gas {}",
+                ip,
+                self.instruction_name(ip, opcode),
+                gas
+            );
+        }
+        println!(
+            "  ✗ 0x{:04X}  {:<28} {}",
+            fault.ip,
+            self.instruction_name(fault.ip, fault.opcode),
+            fault.error
+        );
+
+        println!();
+        self.cmd_stack();
+        self.cmd_slots();
+        println!("Backtrace:");
+        self.cmd_backtrace();
+        println!(
+            "\nPost-mortem: inspect with stack, print, slots and bt; 'back' undoes the last \
+             command, 'reset' restarts."
+        );
+    }
+
+    /// The last `steps` executed instructions and how many came before them
+    fn recent_steps(&self, steps: usize) -> (usize, Vec<RecentStep>) {
+        let recent = self.recent.lock().unwrap();
+        let skipped = recent.len().saturating_sub(steps);
+        (skipped, recent.iter().skip(skipped).copied().collect())
+    }
+
+    /// Disassembled instruction at `ip`, or just its opcode if `ip` is in
+    /// another script, such as a called contract
+    fn instruction_name(&self, ip: usize, opcode: u8) -> String {
+        if self.script.get(ip) == Some(&opcode) {
+            Disassembler::new(&self.script).decode_instruction(ip).0
+        } else {
+            format!("opcode 0x{:02X}", opcode)
+        }
+    }
+
+    fn cmd_disasm(&self) {
+        match &self.debug_info {
+            Some(debug_info) => println!("{}", debug_info.disassemble(&self.script)),
+            None => println!("{}", Disassembler::new(&self.script).disassemble()),
+        }
+    }
+
+    /// Function and source line of `ip`, from the debug info
+    fn describe_offset(&self, ip: usize) -> Option<String> {
+        self.debug_info.as_ref()?.describe(ip)
+    }
+
+    /// Reload the script, keeping breakpoints and watchpoints
+    fn reset(&mut self) {
+        let watches = std::mem::take(&mut self.vm.watches);
+        self.vm = Self::load_vm(
+            &self.script,
+            self.vm.gas_limit,
+            &self.breakpoints,
+            &self.recent,
+        );
+        for watch in watches {
+            self.vm.add_watch(watch.kind);
+        }
+        self.undo.clear();
+    }
+
+    fn cmd_reset(&mut self) {
+        self.reset();
+        println!("VM reset to initial state.");
+        self.print_current_state();
+    }
+
+    fn get_current_ip(&self) -> usize {
+        self.vm
+            .invocation_stack
+            .last()
+            .map(|ctx| ctx.ip)
+            .unwrap_or(0)
+    }
+
+    fn print_current_state(&self) {
+        if matches!(self.vm.state, VMState::Halt) {
+            println!("Program halted. Gas consumed: {}", self.vm.gas_consumed);
+            return;
+        }
+
+        if matches!(self.vm.state, VMState::Fault) {
+            self.print_post_mortem(POST_MORTEM_STEPS);
+            return;
+        }
+
+        let ip = self.get_current_ip();
+        if ip < self.script.len() {
+            let op = self.script[ip];
+            let disasm = Disassembler::new(&self.script);
+            let (name, _) = disasm.decode_instruction(ip);
+            let location = self
+                .describe_offset(ip)
+                .map(|location| format!("  in {}", location))
+                .unwrap_or_default();
+            println!(
+                "→ 0x{:04X}: {:02X}  {}    [gas: {}]{}",
+                ip, op, name, self.vm.gas_consumed, location
+            );
+        }
+    }
+}
+
+/// A hex address, with or without `0x`
+fn parse_address(text: &str) -> Option<usize> {
+    usize::from_str_radix(text.trim_start_matches("0x"), 16).ok()
+}
+
+fn describe_watch(kind: &WatchKind) -> String {
+    match kind {
+        WatchKind::StorageKey(key) => format!("storage key 0x{}", hex::encode(key)),
+        WatchKind::StackValue(index) => format!("stack item {}", index),
+    }
+}
+
+fn describe_watched_value(value: &Option<neo_vm_core::StackItem>) -> String {
+    match value {
+        Some(item) => format!("{:?}", item),
+        None => "<unset>".to_string(),
+    }
+}
+
+fn print_watch_hit(hit: &WatchHit) {
+    println!(
+        "Watchpoint hit at 0x{:04X}: {} changed from {} to {}",
+        hit.ip,
+        describe_watch(&hit.kind),
+        describe_watched_value(&hit.old),
+        describe_watched_value(&hit.new)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PUSH2 PUSH0 DIV RET, which faults dividing by zero at 0x02
+    fn faulting() -> Debugger {
+        Debugger::new(hex::decode("1210A140").unwrap(), 1_000_000, None)
+    }
+
+    #[test]
+    fn test_recent_steps_keep_the_last_ones() {
+        let recent = Arc::new(Mutex::new(VecDeque::new()));
+        let mut hook = DebuggerHook {
+            breakpoints: Arc::new(Mutex::new(Vec::new())),
+            recent: recent.clone(),
+        };
+        for ip in 0..RECENT_STEPS + 44 {
+            hook.after_op(ip, 0x21, ip as u64);
+        }
+        let recent = recent.lock().unwrap();
+        assert_eq!(recent.len(), RECENT_STEPS);
+        assert_eq!(recent.front(), Some(&(44, 0x21, 44)));
+        assert_eq!(
+            recent.back(),
+            Some(&(RECENT_STEPS + 43, 0x21, RECENT_STEPS as u64 + 43))
+        );
+    }
+
+    #[test]
+    fn test_post_mortem_after_fault() {
+        let mut debugger = faulting();
+        assert_eq!(
+            debugger.post_mortem_steps(&[]),
+            Err("The program has not faulted.".to_string())
+        );
+
+        assert!(matches!(debugger.resume(Resume::Run), Stop::Fault));
+        // The faulted frame has moved past DIV, the backtrace names DIV
+        assert_eq!(debugger.vm.fault_info.as_ref().unwrap().ip, 2);
+        assert_eq!(debugger.frames(), vec![2]);
+
+        // PUSH2 and PUSH0 ran; DIV faulted before it was recorded
+        let (skipped, recent) = debugger.recent_steps(1);
+        assert_eq!(skipped, 1);
+        assert_eq!(recent.iter().map(|step| step.0).collect::<Vec<_>>(), [1]);
+
+        assert_eq!(debugger.post_mortem_steps(&[]), Ok(POST_MORTEM_STEPS));
+        assert_eq!(debugger.post_mortem_steps(&["300"]), Ok(300));
+        assert_eq!(
+            debugger.post_mortem_steps(&["x"]),
+            Err("Invalid step count: x".to_string())
+        );
+    }
+}
@@ -0,0 +1,698 @@
+//! Debug Adapter Protocol server
+//!
+//! `neo-zkvm dap` speaks the [Debug Adapter
+//! Protocol](https://microsoft.github.io/debug-adapter-protocol/) over
+//! stdio, so VS Code and other DAP clients can debug `.neoasm` files and
+//! scripts with debug info at the source level. A `launch` request names
+//! the program; `.neoasm` files are assembled with their debug info, other
+//! scripts use `debugInfo` or the `.neodbg` file next to them.
+//!
+//! Source breakpoints are mapped to offsets through the debug info's
+//! sequence points; instruction breakpoints work without it. The single
+//! thread's stack frames are the invocation stack, and the innermost frame
+//! has argument, local, static and evaluation stack scopes.
+
+use neo_vm_core::{StackItem, VMState};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use super::{parse_address, Debugger, Resume, Stop};
+use crate::assembler::Assembler;
+use crate::debug_info::{DebugInfo, Slot};
+use crate::describe_fault;
+
+/// The only thread
+const THREAD_ID: u64 = 1;
+
+/// Variable references of the innermost frame's scopes
+const ARGUMENTS: u64 = 1;
+const LOCALS: u64 = 2;
+const STATICS: u64 = 3;
+const EVALUATION_STACK: u64 = 4;
+
+/// An event's name and body
+type Event = (&'static str, Value);
+
+/// How far a stepping request goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepKind {
+    /// To the next source line, stepping over calls
+    Over,
+    /// To the next source line, into calls
+    In,
+    /// Until the current frame returns
+    Out,
+}
+
+/// One debug session
+#[derive(Default)]
+pub struct DapServer {
+    seq: u64,
+    debugger: Option<Debugger>,
+    stop_on_entry: bool,
+    /// Breakpoint lines by source path
+    source_breakpoints: HashMap<String, Vec<usize>>,
+    instruction_breakpoints: Vec<usize>,
+    /// Responses and events to send
+    outgoing: Vec<Value>,
+}
+
+impl DapServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests from `input` on `output` until the client
+    /// disconnects or closes the input
+    pub fn serve(&mut self, mut input: impl BufRead, mut output: impl Write) -> Result<(), String> {
+        while let Some(message) = read_message(&mut input)? {
+            let done = self.handle(&message);
+            for message in self.outgoing.drain(..) {
+                write_message(&mut output, &message)?;
+            }
+            if done {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a request, queueing its response and any events, and return
+    /// whether the session is over
+    fn handle(&mut self, request: &Value) -> bool {
+        let command = request["command"].as_str().unwrap_or_default();
+        let arguments = &request["arguments"];
+        let (result, events) = match self.request(command, arguments) {
+            Ok((body, events)) => (Ok(body), events),
+            Err(message) => (Err(message), Vec::new()),
+        };
+
+        self.seq += 1;
+        let mut response = json!({
+            "seq": self.seq,
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        self.outgoing.push(response);
+
+        // Events follow the response that caused them
+        for (event, body) in events {
+            self.seq += 1;
+            self.outgoing.push(json!({
+                "seq": self.seq,
+                "type": "event",
+                "event": event,
+                "body": body,
+            }));
+        }
+
+        matches!(command, "disconnect" | "terminate")
+    }
+
+    /// The body of the response to `command` and the events to send after
+    /// it
+    fn request(&mut self, command: &str, arguments: &Value) -> Result<(Value, Vec<Event>), String> {
+        let events = match command {
+            "initialize" => {
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsInstructionBreakpoints": true,
+                    "supportsStepBack": true,
+                    "supportsRestartRequest": true,
+                    "supportsTerminateRequest": true,
+                });
+                return Ok((capabilities, Vec::new()));
+            }
+            "launch" => {
+                self.launch(arguments)?;
+                vec![("initialized", json!({}))]
+            }
+            "setBreakpoints" => return self.set_breakpoints(arguments).map(|b| (b, Vec::new())),
+            "setInstructionBreakpoints" => {
+                return self
+                    .set_instruction_breakpoints(arguments)
+                    .map(|b| (b, Vec::new()))
+            }
+            "setExceptionBreakpoints" => {
+                // Faults always stop, there are no exception filters
+                return Ok((json!({}), Vec::new()));
+            }
+            "configurationDone" => {
+                if self.stop_on_entry {
+                    vec![stopped("entry", None)]
+                } else {
+                    let stop = self.debugger()?.resume(Resume::Continue);
+                    self.stop_events(stop)
+                }
+            }
+            "threads" => {
+                let threads = json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] });
+                return Ok((threads, Vec::new()));
+            }
+            "stackTrace" => return Ok((self.stack_trace()?, Vec::new())),
+            "scopes" => return Ok((self.scopes(arguments)?, Vec::new())),
+            "variables" => return Ok((self.variables(arguments)?, Vec::new())),
+            "continue" => {
+                let stop = self.debugger()?.resume(Resume::Continue);
+                let events = self.stop_events(stop);
+                return Ok((json!({ "allThreadsContinued": true }), events));
+            }
+            "next" => self.step(StepKind::Over)?,
+            "stepIn" => self.step(StepKind::In)?,
+            "stepOut" => self.step(StepKind::Out)?,
+            "stepBack" => {
+                if !self.debugger()?.back() {
+                    return Err("Nothing to step back to".to_string());
+                }
+                vec![stopped("step", None)]
+            }
+            "restart" => {
+                let debugger = self.debugger()?;
+                debugger.reset();
+                if self.stop_on_entry {
+                    vec![stopped("entry", None)]
+                } else {
+                    let stop = self.debugger()?.resume(Resume::Continue);
+                    self.stop_events(stop)
+                }
+            }
+            // Requests run to completion, so the program is never running
+            "pause" => vec![stopped("pause", None)],
+            "disconnect" | "terminate" => Vec::new(),
+            _ => return Err(format!("Unsupported request: {}", command)),
+        };
+        Ok((json!({}), events))
+    }
+
+    fn debugger(&mut self) -> Result<&mut Debugger, String> {
+        self.debugger
+            .as_mut()
+            .ok_or_else(|| "No program launched".to_string())
+    }
+
+    /// Load the `program`, a `.neoasm` file or any script argument
+    fn launch(&mut self, arguments: &Value) -> Result<(), String> {
+        let program = arguments["program"]
+            .as_str()
+            .ok_or("Missing program argument")?;
+        let gas_limit = match &arguments["gasLimit"] {
+            Value::Null => 1_000_000, // Default gas limit
+            limit => limit.as_u64().ok_or("gasLimit must be a number")?,
+        };
+        self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+
+        let (script, debug_info) = if program.ends_with(".neoasm") {
+            let source = std::fs::read_to_string(program)
+                .map_err(|e| format!("Failed to read {}: {}", program, e))?;
+            let mut assembler = Assembler::new();
+            let script = assembler.assemble(&source)?;
+            let mut debug_info = assembler.debug_info().clone();
+            debug_info.documents.push(program.to_string());
+            (script, Some(debug_info))
+        } else {
+            let script = crate::parse_script(program)?;
+            let debug_info = match arguments["debugInfo"].as_str() {
+                Some(path) => Some(DebugInfo::load(Path::new(path))?),
+                None => DebugInfo::find_for(program)?,
+            };
+            (script, debug_info)
+        };
+
+        self.debugger = Some(Debugger::new(script, gas_limit, debug_info));
+        self.sync_breakpoints();
+        Ok(())
+    }
+
+    /// Replace the breakpoints in a source file
+    fn set_breakpoints(&mut self, arguments: &Value) -> Result<Value, String> {
+        let path = arguments["source"]["path"]
+            .as_str()
+            .ok_or("Missing source path")?
+            .to_string();
+        let lines: Vec<usize> = match arguments["breakpoints"].as_array() {
+            Some(breakpoints) => breakpoints
+                .iter()
+                .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                .map(|line| line as usize)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let breakpoints: Vec<Value> = lines
+            .iter()
+            .map(|&line| match self.resolve(&path, line) {
+                Some((offset, line)) => json!({
+                    "verified": true,
+                    "line": line,
+                    "instructionReference": format!("0x{:04X}", offset),
+                }),
+                None => json!({
+                    "verified": false,
+                    "line": line,
+                    "message": "No code at this line",
+                }),
+            })
+            .collect();
+
+        self.source_breakpoints.insert(path, lines);
+        self.sync_breakpoints();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    /// Offset and line of a breakpoint on `line` of `path`: the first
+    /// instruction on that line or on the next line with code
+    fn resolve(&self, path: &str, line: usize) -> Option<(usize, usize)> {
+        let info = self.debugger.as_ref()?.debug_info.as_ref()?;
+        let document = info.document_index(Path::new(path))?;
+        info.breakpoint_offset(document, line)
+    }
+
+    fn set_instruction_breakpoints(&mut self, arguments: &Value) -> Result<Value, String> {
+        let references = arguments["breakpoints"]
+            .as_array()
+            .ok_or("Missing breakpoints")?;
+        self.instruction_breakpoints.clear();
+        let breakpoints: Vec<Value> = references
+            .iter()
+            .map(|breakpoint| {
+                let reference = breakpoint["instructionReference"].as_str().unwrap_or("");
+                let offset = breakpoint["offset"].as_i64().unwrap_or(0);
+                let address = parse_address(reference)
+                    .and_then(|address| address.checked_add_signed(offset as isize));
+                match address {
+                    Some(address) => {
+                        self.instruction_breakpoints.push(address);
+                        json!({
+                            "verified": true,
+                            "instructionReference": format!("0x{:04X}", address),
+                        })
+                    }
+                    None => json!({ "verified": false, "message": "Invalid address" }),
+                }
+            })
+            .collect();
+        self.sync_breakpoints();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    /// Give the debugger the source and instruction breakpoints; source
+    /// breakpoints set before the launch are resolved here
+    fn sync_breakpoints(&mut self) {
+        let Some(debugger) = &self.debugger else {
+            return;
+        };
+        let mut offsets: Vec<usize> = self
+            .source_breakpoints
+            .iter()
+            .flat_map(|(path, lines)| {
+                lines
+                    .iter()
+                    .filter_map(|&line| self.resolve(path, line).map(|(offset, _)| offset))
+            })
+            .chain(self.instruction_breakpoints.iter().copied())
+            .collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        *debugger.breakpoints.lock().unwrap() = offsets;
+    }
+
+    /// Step by source line, or by instruction without debug info; `back`
+    /// undoes the whole step
+    fn step(&mut self, kind: StepKind) -> Result<Vec<Event>, String> {
+        let debugger = self.debugger()?;
+        let depth = debugger.vm.invocation_stack.len();
+        let line = debugger.current_sequence_point();
+        let undo = debugger.undo.len();
+
+        let stop = loop {
+            let stop = debugger.resume(Resume::Step);
+            if !matches!(stop, Stop::Step) {
+                break stop;
+            }
+            let current_depth = debugger.vm.invocation_stack.len();
+            let done = match kind {
+                StepKind::Out => current_depth < depth,
+                _ if debugger.debug_info.is_none() => true,
+                StepKind::Over if current_depth > depth => false,
+                _ => current_depth < depth || debugger.current_sequence_point() != line,
+            };
+            if done {
+                break stop;
+            }
+            if debugger.at_breakpoint() {
+                break Stop::Breakpoint;
+            }
+        };
+        debugger.undo.truncate(undo + 1);
+        Ok(self.stop_events(stop))
+    }
+
+    /// Events telling the client why the program stopped, or that it ended
+    fn stop_events(&self, stop: Stop) -> Vec<Event> {
+        let Some(debugger) = &self.debugger else {
+            return Vec::new();
+        };
+        match stop {
+            Stop::Step => vec![stopped("step", None)],
+            Stop::Breakpoint => vec![stopped("breakpoint", None)],
+            Stop::Watch(hit) => vec![stopped(
+                "data breakpoint",
+                Some(format!("Watchpoint hit at 0x{:04X}", hit.ip)),
+            )],
+            Stop::Fault => {
+                let text = debugger
+                    .vm
+                    .fault_info
+                    .as_ref()
+                    .map(|fault| describe_fault(&debugger.script, fault))
+                    .unwrap_or_else(|| "Program faulted".to_string());
+                vec![
+                    output(format!("Program faulted: {}\n", text)),
+                    stopped("exception", Some(text)),
+                ]
+            }
+            Stop::Error(message) => vec![
+                output(format!("Error: {}\n", message)),
+                stopped("exception", Some(message)),
+            ],
+            // Continuing after a fault ends the session
+            Stop::Terminated if !matches!(debugger.vm.state, VMState::Halt) => vec![
+                ("exited", json!({ "exitCode": 1 })),
+                ("terminated", json!({})),
+            ],
+            Stop::Halt | Stop::Terminated => {
+                let result: Vec<String> = debugger
+                    .vm
+                    .eval_stack
+                    .iter()
+                    .rev()
+                    .map(|item| format!("{:?}", item))
+                    .collect();
+                vec![
+                    output(format!(
+                        "Program halted. Gas consumed: {}\nResult stack: [{}]\n",
+                        debugger.vm.gas_consumed,
+                        result.join(", ")
+                    )),
+                    ("exited", json!({ "exitCode": 0 })),
+                    ("terminated", json!({})),
+                ]
+            }
+        }
+    }
+
+    fn stack_trace(&mut self) -> Result<Value, String> {
+        let debugger = self.debugger()?;
+        let frames: Vec<Value> = debugger
+            .frames()
+            .into_iter()
+            .enumerate()
+            .map(|(index, ip)| {
+                let info = debugger.debug_info.as_ref();
+                let name = info
+                    .and_then(|info| info.function_at(ip))
+                    .map(|function| function.name.clone())
+                    .unwrap_or_else(|| format!("0x{:04X}", ip));
+                let mut frame = json!({
+                    "id": index,
+                    "name": name,
+                    "line": 0,
+                    "column": 0,
+                    "instructionPointerReference": format!("0x{:04X}", ip),
+                });
+                let point = info.and_then(|info| info.sequence_point_at(ip));
+                if let Some((info, point)) = info.zip(point) {
+                    frame["line"] = json!(point.line);
+                    frame["column"] = json!(1);
+                    if let Some(document) = info.documents.get(point.document) {
+                        frame["source"] = json!({
+                            "name": Path::new(document).file_name().map(|name| name.to_string_lossy()),
+                            "path": document,
+                        });
+                    }
+                }
+                frame
+            })
+            .collect();
+        Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+    }
+
+    /// Slots belong to the innermost frame, so outer frames have no scopes
+    fn scopes(&mut self, arguments: &Value) -> Result<Value, String> {
+        self.debugger()?;
+        if arguments["frameId"].as_u64().unwrap_or(0) != 0 {
+            return Ok(json!({ "scopes": [] }));
+        }
+        let scope = |name: &str, reference: u64| json!({ "name": name, "variablesReference": reference, "expensive": false });
+        Ok(json!({
+            "scopes": [
+                scope("Arguments", ARGUMENTS),
+                scope("Locals", LOCALS),
+                scope("Statics", STATICS),
+                scope("Evaluation Stack", EVALUATION_STACK),
+            ]
+        }))
+    }
+
+    fn variables(&mut self, arguments: &Value) -> Result<Value, String> {
+        let debugger = self.debugger()?;
+        let ip = debugger.get_current_ip();
+        let slot = |slot, items: &[StackItem]| -> Vec<Value> {
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let name = debugger
+                        .debug_info
+                        .as_ref()
+                        .and_then(|info| info.slot_name(ip, slot, index))
+                        .filter(|name| !name.is_empty())
+                        .map(|name| name.to_string())
+                        .unwrap_or_else(|| format!("[{}]", index));
+                    variable(name, item)
+                })
+                .collect()
+        };
+        let variables = match arguments["variablesReference"].as_u64() {
+            Some(ARGUMENTS) => slot(Slot::Argument, &debugger.vm.argument_slots),
+            Some(LOCALS) => slot(Slot::Local, &debugger.vm.local_slots),
+            Some(STATICS) => slot(Slot::Static, &debugger.vm.static_slots),
+            Some(EVALUATION_STACK) => debugger
+                .vm
+                .eval_stack
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(index, item)| variable(format!("[{}]", index), item))
+                .collect(),
+            _ => return Err("Unknown variables reference".to_string()),
+        };
+        Ok(json!({ "variables": variables }))
+    }
+}
+
+impl Debugger {
+    /// Offset of the sequence point the current instruction belongs to
+    fn current_sequence_point(&self) -> Option<usize> {
+        let info = self.debug_info.as_ref()?;
+        info.sequence_point_at(self.get_current_ip())
+            .map(|point| point.offset)
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        self.breakpoints
+            .lock()
+            .unwrap()
+            .contains(&self.get_current_ip())
+    }
+}
+
+fn stopped(reason: &str, text: Option<String>) -> Event {
+    let mut body = json!({
+        "reason": reason,
+        "threadId": THREAD_ID,
+        "allThreadsStopped": true,
+    });
+    if let Some(text) = text {
+        body["text"] = json!(text);
+    }
+    ("stopped", body)
+}
+
+fn output(text: String) -> Event {
+    ("output", json!({ "category": "console", "output": text }))
+}
+
+fn variable(name: String, item: &StackItem) -> Value {
+    json!({ "name": name, "value": format!("{:?}", item), "variablesReference": 0 })
+}
+
+/// Read a `Content-Length` framed message, or `None` at the end of input
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        let read = input
+            .read_line(&mut header)
+            .map_err(|e| format!("Failed to read message: {}", e))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or("Message without Content-Length")?;
+    let mut body = vec![0; length];
+    input
+        .read_exact(&mut body)
+        .map_err(|e| format!("Failed to read message: {}", e))?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| format!("Invalid message: {}", e))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> Result<(), String> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| output.flush())
+        .map_err(|e| format!("Failed to write message: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(server: &mut DapServer, command: &str, arguments: Value) -> Vec<Value> {
+        server.handle(&json!({
+            "seq": 1,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        }));
+        server.outgoing.drain(..).collect()
+    }
+
+    fn events(messages: &[Value]) -> Vec<&str> {
+        messages
+            .iter()
+            .filter_map(|message| message["event"].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_framing() {
+        let body = r#"{"seq":1,"type":"request","command":"initialize"}"#;
+        let input = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let mut input = input.as_bytes();
+        let message = read_message(&mut input).unwrap().unwrap();
+        assert_eq!(message["command"], "initialize");
+        assert!(read_message(&mut input).unwrap().is_none());
+
+        let mut output = Vec::new();
+        write_message(&mut output, &message).unwrap();
+        assert_eq!(read_message(&mut output.as_slice()).unwrap(), Some(message));
+    }
+
+    #[test]
+    fn test_instruction_breakpoints_and_stepping() {
+        let mut server = DapServer::new();
+        // PUSH2 PUSH3 ADD RET
+        let messages = request(
+            &mut server,
+            "launch",
+            json!({ "program": "12139E40", "stopOnEntry": true }),
+        );
+        assert_eq!(messages[0]["success"], true);
+        assert_eq!(events(&messages), ["initialized"]);
+
+        let messages = request(
+            &mut server,
+            "setInstructionBreakpoints",
+            json!({ "breakpoints": [{ "instructionReference": "0x0002" }] }),
+        );
+        assert_eq!(messages[0]["body"]["breakpoints"][0]["verified"], true);
+
+        let messages = request(&mut server, "configurationDone", json!({}));
+        assert_eq!(messages[1]["body"]["reason"], "entry");
+
+        let messages = request(&mut server, "continue", json!({ "threadId": 1 }));
+        assert_eq!(messages[1]["body"]["reason"], "breakpoint");
+        let messages = request(&mut server, "stackTrace", json!({ "threadId": 1 }));
+        let frame = &messages[0]["body"]["stackFrames"][0];
+        assert_eq!(frame["instructionPointerReference"], "0x0002");
+
+        let messages = request(&mut server, "variables", json!({ "variablesReference": 4 }));
+        let stack = &messages[0]["body"]["variables"];
+        assert_eq!(stack[0]["value"], "Integer(3)");
+        assert_eq!(stack[1]["value"], "Integer(2)");
+
+        let messages = request(&mut server, "next", json!({ "threadId": 1 }));
+        assert_eq!(messages[1]["body"]["reason"], "step");
+        let messages = request(&mut server, "continue", json!({ "threadId": 1 }));
+        assert_eq!(events(&messages), ["output", "exited", "terminated"]);
+    }
+
+    #[test]
+    fn test_source_breakpoints() {
+        let path = std::env::temp_dir().join(format!("neo-zkvm-dap-{}.neoasm", std::process::id()));
+        std::fs::write(
+            &path,
+            ".func main\n\
+             PUSH2\n\
+             PUSH3\n\
+             \n\
+             ADD\n\
+             RET\n\
+             .endfunc\n",
+        )
+        .unwrap();
+        let program = path.to_string_lossy().to_string();
+
+        let mut server = DapServer::new();
+        request(&mut server, "launch", json!({ "program": program }));
+        let messages = request(
+            &mut server,
+            "setBreakpoints",
+            json!({ "source": { "path": program }, "breakpoints": [{ "line": 4 }, { "line": 9 }] }),
+        );
+        let breakpoints = &messages[0]["body"]["breakpoints"];
+        assert_eq!(breakpoints[0]["verified"], true);
+        assert_eq!(breakpoints[0]["line"], 5);
+        assert_eq!(breakpoints[1]["verified"], false);
+
+        let messages = request(&mut server, "configurationDone", json!({}));
+        assert_eq!(messages[1]["body"]["reason"], "breakpoint");
+        let messages = request(&mut server, "stackTrace", json!({ "threadId": 1 }));
+        let frame = &messages[0]["body"]["stackFrames"][0];
+        assert_eq!(frame["name"], "main");
+        assert_eq!(frame["line"], 5);
+        assert_eq!(frame["source"]["path"], program.as_str());
+
+        let messages = request(&mut server, "next", json!({ "threadId": 1 }));
+        assert_eq!(messages[1]["body"]["reason"], "step");
+        let messages = request(&mut server, "stackTrace", json!({ "threadId": 1 }));
+        assert_eq!(messages[0]["body"]["stackFrames"][0]["line"], 6);
+
+        request(&mut server, "stepBack", json!({ "threadId": 1 }));
+        let messages = request(&mut server, "stackTrace", json!({ "threadId": 1 }));
+        assert_eq!(messages[0]["body"]["stackFrames"][0]["line"], 5);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,433 @@
+//! Machine interface
+//!
+//! `neo-zkvm debug --mi` drives the debugger with one JSON request per line
+//! and answers each with one JSON response per line, so an editor can run
+//! it as a child process:
+//!
+//! ```text
+//! → {"id": 1, "command": "break", "arguments": {"address": "0x10"}}
+//! ← {"id": 1, "success": true, "body": {"breakpoints": [16]}}
+//! → {"id": 2, "command": "continue"}
+//! ← {"id": 2, "success": true, "body": {"stop": "breakpoint", "state": {...}}}
+//! ```
+//!
+//! A failed request gets `"success": false` and a `message`. Stack items
+//! use the RPC JSON format.
+
+use neo_vm_core::{Disassembler, StackItem, VMState, WatchHit, WatchKind};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+use super::{parse_address, Debugger, Resume, Stop, POST_MORTEM_STEPS};
+use crate::debug_info::Slot;
+use crate::describe_fault;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    command: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+impl Debugger {
+    /// Answer requests from `input` on `output` until `quit` or the end of
+    /// input
+    pub fn serve_mi(&mut self, input: impl BufRead, mut output: impl Write) -> Result<(), String> {
+        for line in input.lines() {
+            let line = line.map_err(|e| format!("Failed to read request: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (response, quit) = self.handle_mi(&line);
+            writeln!(output, "{}", response)
+                .and_then(|_| output.flush())
+                .map_err(|e| format!("Failed to write response: {}", e))?;
+            if quit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The response to one request line and whether it asked to quit
+    fn handle_mi(&mut self, line: &str) -> (Value, bool) {
+        let request: Request = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                let response = json!({
+                    "id": Value::Null,
+                    "success": false,
+                    "message": format!("Invalid request: {}", e),
+                });
+                return (response, false);
+            }
+        };
+
+        let quit = matches!(request.command.as_str(), "quit" | "exit");
+        let response = match self.mi_command(&request.command, &request.arguments) {
+            Ok(body) => json!({ "id": request.id, "success": true, "body": body }),
+            Err(message) => json!({ "id": request.id, "success": false, "message": message }),
+        };
+        (response, quit)
+    }
+
+    fn mi_command(&mut self, command: &str, arguments: &Value) -> Result<Value, String> {
+        match command {
+            "state" => Ok(self.mi_state()),
+            "step" => Ok(self.mi_resume(Resume::Step)),
+            "continue" => Ok(self.mi_resume(Resume::Continue)),
+            "run" => Ok(self.mi_resume(Resume::Run)),
+            "back" => {
+                if !self.back() {
+                    return Err("Nothing to undo".to_string());
+                }
+                Ok(json!({ "state": self.mi_state() }))
+            }
+            "reset" => {
+                self.reset();
+                Ok(json!({ "state": self.mi_state() }))
+            }
+            "checkpoint" => {
+                self.checkpoint = Some(self.vm.snapshot());
+                Ok(json!({ "ip": self.get_current_ip() }))
+            }
+            "restore" => {
+                if !self.restore_checkpoint() {
+                    return Err("No checkpoint saved".to_string());
+                }
+                Ok(json!({ "state": self.mi_state() }))
+            }
+            "break" => {
+                self.add_breakpoint(address(arguments)?);
+                Ok(self.mi_breakpoints())
+            }
+            "delete" => {
+                let addr = address(arguments)?;
+                if !self.remove_breakpoint(addr) {
+                    return Err(format!("No breakpoint at 0x{:04X}", addr));
+                }
+                Ok(self.mi_breakpoints())
+            }
+            "breakpoints" => Ok(self.mi_breakpoints()),
+            "watch" => {
+                self.watch(storage_key(arguments)?);
+                Ok(self.mi_watches())
+            }
+            "unwatch" => {
+                let key = storage_key(arguments)?;
+                if !self.vm.remove_watch(&WatchKind::StorageKey(key.clone())) {
+                    return Err(format!("Not watching storage key 0x{}", hex::encode(key)));
+                }
+                Ok(self.mi_watches())
+            }
+            "watches" => Ok(self.mi_watches()),
+            "stack" => {
+                let items: Vec<&StackItem> = self.vm.eval_stack.iter().rev().collect();
+                Ok(json!({ "items": items }))
+            }
+            "slots" => Ok(self.mi_slots()),
+            "backtrace" => Ok(self.mi_backtrace()),
+            "disassemble" => Ok(self.mi_disassemble()),
+            "postmortem" => {
+                let steps = match arguments.get("steps") {
+                    None => POST_MORTEM_STEPS,
+                    Some(steps) => steps
+                        .as_u64()
+                        .ok_or("steps must be a number")?
+                        .try_into()
+                        .map_err(|_| "steps is too large")?,
+                };
+                self.mi_post_mortem(steps)
+            }
+            "quit" | "exit" => Ok(json!({})),
+            _ => Err(format!("Unknown command: {}", command)),
+        }
+    }
+
+    /// Where the VM is and why it stopped, if it has terminated
+    fn mi_state(&self) -> Value {
+        let ip = self.get_current_ip();
+        let mut state = json!({
+            "state": format!("{:?}", self.vm.state).to_uppercase(),
+            "ip": ip,
+            "gas_consumed": self.vm.gas_consumed,
+            "gas_limit": self.vm.gas_limit,
+            "stack_depth": self.vm.eval_stack.len(),
+        });
+        if !self.is_terminated() && ip < self.script.len() {
+            state["instruction"] = json!(Disassembler::new(&self.script).decode_instruction(ip).0);
+        }
+        self.add_location(&mut state, ip);
+        if let Some(fault) = self
+            .vm
+            .fault_info
+            .as_ref()
+            .filter(|_| matches!(self.vm.state, VMState::Fault))
+        {
+            state["fault"] = json!({
+                "ip": fault.ip,
+                "opcode": fault.opcode,
+                "message": describe_fault(&self.script, fault),
+            });
+        }
+        state
+    }
+
+    fn mi_resume(&mut self, how: Resume) -> Value {
+        let mut body = match self.resume(how) {
+            Stop::Step => json!({ "stop": "step" }),
+            Stop::Breakpoint => json!({ "stop": "breakpoint" }),
+            Stop::Watch(hit) => json!({ "stop": "watch", "watch": watch_hit(&hit) }),
+            Stop::Halt => json!({ "stop": "halt" }),
+            Stop::Fault => json!({ "stop": "fault" }),
+            Stop::Terminated => json!({ "stop": "terminated" }),
+            Stop::Error(message) => json!({ "stop": "error", "message": message }),
+        };
+        body["state"] = self.mi_state();
+        body
+    }
+
+    fn mi_breakpoints(&self) -> Value {
+        json!({ "breakpoints": *self.breakpoints.lock().unwrap() })
+    }
+
+    fn mi_watches(&self) -> Value {
+        let watches: Vec<Value> = self
+            .vm
+            .watches
+            .iter()
+            .map(|watch| {
+                let WatchKind::StorageKey(key) = &watch.kind else {
+                    return json!({ "value": watch.value });
+                };
+                json!({ "key": hex::encode(key), "value": watch.value })
+            })
+            .collect();
+        json!({ "watches": watches })
+    }
+
+    fn mi_slots(&self) -> Value {
+        let ip = self.get_current_ip();
+        let slots = |slot: Slot, items: &[StackItem]| -> Vec<Value> {
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    let mut entry = json!({ "index": index, "value": value });
+                    if let Some(name) = self
+                        .debug_info
+                        .as_ref()
+                        .and_then(|info| info.slot_name(ip, slot, index))
+                    {
+                        entry["name"] = json!(name);
+                    }
+                    entry
+                })
+                .collect()
+        };
+        json!({
+            "arguments": slots(Slot::Argument, &self.vm.argument_slots),
+            "locals": slots(Slot::Local, &self.vm.local_slots),
+            "statics": slots(Slot::Static, &self.vm.static_slots),
+        })
+    }
+
+    fn mi_backtrace(&self) -> Value {
+        let frames: Vec<Value> = self
+            .frames()
+            .into_iter()
+            .enumerate()
+            .map(|(index, ip)| {
+                let mut frame = json!({ "index": index, "ip": ip });
+                self.add_location(&mut frame, ip);
+                frame
+            })
+            .collect();
+        json!({ "frames": frames })
+    }
+
+    fn mi_disassemble(&self) -> Value {
+        let disassembler = Disassembler::new(&self.script);
+        let mut instructions = Vec::new();
+        let mut offset = 0;
+        while offset < self.script.len() {
+            let (name, size) = disassembler.decode_instruction(offset);
+            let end = (offset + size.max(1)).min(self.script.len());
+            let mut instruction = json!({
+                "offset": offset,
+                "bytes": hex::encode(&self.script[offset..end]),
+                "instruction": name,
+            });
+            self.add_location(&mut instruction, offset);
+            instructions.push(instruction);
+            offset = end;
+        }
+        json!({ "instructions": instructions })
+    }
+
+    fn mi_post_mortem(&self, steps: usize) -> Result<Value, String> {
+        let fault = self
+            .vm
+            .fault_info
+            .as_ref()
+            .filter(|_| matches!(self.vm.state, VMState::Fault))
+            .ok_or("The program has not faulted")?;
+        let (skipped, recent) = self.recent_steps(steps.saturating_sub(1));
+        let recent: Vec<Value> = recent
+            .into_iter()
+            .map(|(ip, opcode, gas)| {
+                json!({
+                    "ip": ip,
+                    "instruction": self.instruction_name(ip, opcode),
+                    "gas_consumed": gas,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "fault": {
+                "ip": fault.ip,
+                "instruction": self.instruction_name(fault.ip, fault.opcode),
+                "error": fault.error.to_string(),
+            },
+            "skipped": skipped,
+            "steps": recent,
+            "state": self.mi_state(),
+        }))
+    }
+
+    /// Add the function and source location of `ip` to `value`, when the
+    /// debug info has them
+    fn add_location(&self, value: &mut Value, ip: usize) {
+        let Some(info) = &self.debug_info else {
+            return;
+        };
+        if let Some(function) = info.function_at(ip) {
+            value["function"] = json!(function.name);
+        }
+        if let Some(location) = info.location(ip) {
+            value["location"] = json!(location);
+        }
+    }
+}
+
+/// The `address` argument, a number or a hex string
+fn address(arguments: &Value) -> Result<usize, String> {
+    match arguments.get("address") {
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .and_then(|n| n.try_into().ok())
+            .ok_or_else(|| format!("Invalid address: {}", n)),
+        Some(Value::String(text)) => {
+            parse_address(text).ok_or_else(|| format!("Invalid address: {}", text))
+        }
+        _ => Err("Missing address argument".to_string()),
+    }
+}
+
+/// The `key` argument, a hex storage key
+fn storage_key(arguments: &Value) -> Result<Vec<u8>, String> {
+    let key = arguments
+        .get("key")
+        .and_then(Value::as_str)
+        .ok_or("Missing key argument")?;
+    hex::decode(key.trim_start_matches("0x")).map_err(|_| format!("Invalid key: {}", key))
+}
+
+fn watch_hit(hit: &WatchHit) -> Value {
+    let mut value = json!({ "ip": hit.ip, "old": hit.old, "new": hit.new });
+    if let WatchKind::StorageKey(key) = &hit.kind {
+        value["key"] = json!(hex::encode(key));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PUSH2 PUSH3 ADD RET
+    fn debugger() -> Debugger {
+        Debugger::new(hex::decode("12139E40").unwrap(), 1_000_000, None)
+    }
+
+    fn request(debugger: &mut Debugger, line: &str) -> Value {
+        let (response, _) = debugger.handle_mi(line);
+        response
+    }
+
+    #[test]
+    fn test_step_and_state() {
+        let mut debugger = debugger();
+        let response = request(&mut debugger, r#"{"id": 1, "command": "step"}"#);
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["success"], true);
+        assert_eq!(response["body"]["stop"], "step");
+        assert_eq!(response["body"]["state"]["ip"], 1);
+        assert_eq!(response["body"]["state"]["instruction"], "PUSH3");
+
+        let response = request(&mut debugger, r#"{"id": 2, "command": "stack"}"#);
+        assert_eq!(
+            response["body"]["items"],
+            json!([{ "type": "Integer", "value": "2" }])
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_and_run() {
+        let mut debugger = debugger();
+        let response = request(
+            &mut debugger,
+            r#"{"id": "a", "command": "break", "arguments": {"address": "0x02"}}"#,
+        );
+        assert_eq!(response["body"]["breakpoints"], json!([2]));
+
+        let response = request(&mut debugger, r#"{"id": "b", "command": "continue"}"#);
+        assert_eq!(response["body"]["stop"], "breakpoint");
+        assert_eq!(response["body"]["state"]["ip"], 2);
+        assert_eq!(response["body"]["state"]["instruction"], "ADD");
+
+        let response = request(&mut debugger, r#"{"id": "c", "command": "run"}"#);
+        assert_eq!(response["body"]["stop"], "halt");
+        assert_eq!(response["body"]["state"]["state"], "HALT");
+
+        let response = request(&mut debugger, r#"{"id": "d", "command": "step"}"#);
+        assert_eq!(response["body"]["stop"], "terminated");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut debugger = debugger();
+        let response = request(&mut debugger, "not json");
+        assert_eq!(response["success"], false);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = request(&mut debugger, r#"{"id": 1, "command": "frobnicate"}"#);
+        assert_eq!(response["success"], false);
+        assert_eq!(response["message"], "Unknown command: frobnicate");
+
+        let response = request(&mut debugger, r#"{"id": 2, "command": "delete"}"#);
+        assert_eq!(response["message"], "Missing address argument");
+
+        let (_, quit) = debugger.handle_mi(r#"{"id": 3, "command": "quit"}"#);
+        assert!(quit);
+    }
+
+    #[test]
+    fn test_post_mortem() {
+        // PUSH1 PUSH0 DIV RET
+        let mut debugger = Debugger::new(hex::decode("1110A140").unwrap(), 1_000_000, None);
+        let response = request(&mut debugger, r#"{"id": 1, "command": "postmortem"}"#);
+        assert_eq!(response["success"], false);
+
+        let response = request(&mut debugger, r#"{"id": 2, "command": "run"}"#);
+        assert_eq!(response["body"]["stop"], "fault");
+        assert_eq!(response["body"]["state"]["fault"]["ip"], 2);
+
+        let response = request(&mut debugger, r#"{"id": 3, "command": "postmortem"}"#);
+        assert_eq!(response["body"]["fault"]["instruction"], "DIV");
+        assert_eq!(response["body"]["steps"].as_array().unwrap().len(), 2);
+    }
+}
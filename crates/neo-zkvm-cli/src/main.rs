@@ -5,25 +5,46 @@
 //! A comprehensive command-line interface for Neo zkVM development,
 //! including execution, debugging, assembly, and proof generation.
 
-use neo_vm_core::{NeoVM, VMState};
-use neo_vm_guest::ProofInput;
-use neo_zkvm_prover::{NeoProver, ProverConfig};
-use neo_zkvm_verifier::verify;
+use neo_vm_core::contract_parameter::parse_arguments;
+use neo_vm_core::{
+    apply_suggestions, lint_script, suggest_optimizations, BlockContext, Disassembler,
+    ExecutionTrace, FaultInfo, GasSchedule, MemoryStorage, NeoVM, OracleResponses, RuntimeConfig,
+    Suggestion, TraceDiff, TrackedStorage, UInt160, VMLimits, VMState,
+};
+use neo_vm_guest::{encode_arguments, ProofInput};
+use neo_zkvm_prover::{NeoProver, ProofMode, ProverBackend, ProverConfig};
+use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::io::{self, BufRead, Write};
+use std::io::{self, Read, Write};
+use std::path::Path;
 
+mod arguments;
 mod assembler;
-mod disassembler;
-
+mod completions;
+mod config;
+mod debug_info;
+mod debugger;
+mod snapshot;
+mod test_runner;
+mod watch;
+
+use arguments::parse_typed_argument;
 use assembler::Assembler;
-use disassembler::Disassembler;
+use debug_info::DebugInfo;
+use debugger::dap::DapServer;
+use debugger::Debugger;
 
 const VERSION: &str = "0.2.0";
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    if let Err(e) = config::apply_profile(&mut args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 
     if args.len() < 2 {
         print_help();
@@ -36,7 +57,19 @@ fn main() {
         "asm" => cmd_assemble(&args[2..]),
         "disasm" => cmd_disassemble(&args[2..]),
         "debug" => cmd_debug(&args[2..]),
+        "dap" => cmd_dap(),
         "inspect" => cmd_inspect(&args[2..]),
+        "coverage" => cmd_coverage(&args[2..]),
+        "diff" => cmd_diff(&args[2..]),
+        "trace-diff" => cmd_trace_diff(&args[2..]),
+        "watch" => cmd_watch(&args[2..]),
+        "test" => cmd_test(&args[2..]),
+        "hash" => cmd_hash(&args[2..]),
+        "encode-args" => cmd_encode_args(&args[2..]),
+        "vkey" => cmd_vkey(&args[2..]),
+        "elf" => cmd_elf(&args[2..]),
+        "completions" => cmd_completions(&args[2..]),
+        "man" => cmd_man(&args[2..]),
         "version" | "-v" | "--version" => {
             println!("neo-zkvm v{}", VERSION);
             Ok(())
@@ -67,25 +100,103 @@ A comprehensive toolkit for Neo zkVM development.
 USAGE:
     neo-zkvm <COMMAND> [OPTIONS] [ARGS]
 
+OPTIONS:
+    --profile <name>    Default flags from a profile in ~/.config/neo-zkvm/config.toml
+
 COMMANDS:
     run <script>        Execute a script and show results
+                          --gas <limit>          Gas limit (default: 1000000)
+                          --arg <value>          Typed argument, repeatable (see ARGUMENTS)
+                          --args <json>          Arguments as contract parameter JSON
+                          --storage <file>       Load initial storage from a JSON fixture
+                          --dump-storage <file>  Write final storage as a JSON fixture
+                          --trace <file>         Write the execution trace as JSON
     prove <script>      Generate ZK proof for script execution
+                          --gas <limit>          Gas limit (default: 1000000)
+                          --arg <value>          Typed argument, repeatable (see ARGUMENTS)
+                          --args <json>          Arguments as contract parameter JSON
+                          --storage <file>       Load initial storage from a JSON fixture
+                          --mode <mode>          execute, mock, sp1, plonk or groth16 (default: sp1)
+                          --backend <backend>    env, cpu, cuda or network (default: env)
+                          --json                 Print output and public inputs as JSON
     asm <source>        Assemble source code to bytecode
+                          --debug-info <file>    Write .neodbg debug info
     disasm <hex>        Disassemble bytecode to readable format
+                          --debug-info <file>    .neodbg or nccs debug info (default:
+                                                 <script>.neodbg if present)
     debug <script>      Interactive step-by-step debugger
+                          --debug-info <file>    .neodbg or nccs debug info (default:
+                                                 <script>.neodbg if present)
+                          --batch <file>         Run the debugger commands in a file
+                          --mi                   JSON requests and responses, one per line
+    dap                 Debug Adapter Protocol server on stdio, for editors
     inspect <script>    Analyze and display script information
+                          --json                 Print security lints and gas golf
+                                                 suggestions as JSON
+                          --fix                  Apply the gas golf suggestions and
+                                                 print the optimized script
+                          --out <file>           With --fix, write the optimized bytes
+    coverage <script>   Run a script and show which instructions executed
+                          --gas <limit>          Gas limit (default: 1000000)
+                          --storage <file>       Load initial storage from a JSON fixture
+                          --arg <value>          Typed argument, repeatable (see ARGUMENTS)
+                          --args <json>          Arguments as contract parameter JSON
+    diff <a> <b>        Run two scripts and compare their traces, stacks and gas
+                          --gas <limit>          Gas limit (default: 1000000)
+                          --storage <file>       Load initial storage from a JSON fixture
+                          --arg <value>          Typed argument, repeatable (see ARGUMENTS)
+                          --args <json>          Arguments passed to both scripts
+    trace-diff <a> <b>  Find the first step where two JSON traces diverge
+    watch <source>      Re-assemble and re-run a .neoasm (or .bin/.nef) file
+                        whenever it changes and show how the stack and gas moved
+                          --run                  Run the script on each change (default)
+                          --test <file>          Run a test file on each change instead
+                          --gas <limit>          Gas limit (default: 1000000)
+                          --storage <file>       Load initial storage from a JSON fixture
+                          --arg <value>          Typed argument, repeatable (see ARGUMENTS)
+                          --args <json>          Arguments as contract parameter JSON
+    test <files>        Run declarative TOML test files or directories of them
+                          --sp1                  Also run each test in SP1 execute mode
+                          --bless                Write snapshots instead of checking them
+                          --junit <file>         Write a JUnit XML report
+    hash <script>       Show a script's SHA-256, Neo script hash and address
+                          --json                 Print as JSON
+    encode-args <json>  Show the canonical encoding of contract parameter
+                        arguments and its SHA-256, as hashed into input_hash
+                          --arg <value>          Typed argument, repeatable (see ARGUMENTS)
+                          --json                 Print as JSON
+    vkey export         Write the guest program's SP1 verifying key
+                          --out <file>           Output file (default: <elf sha256>.vkey)
+    elf info            Show the guest ELF's size, SHA-256 and SP1 vkey hash
+                          --json                 Print as JSON
+    completions <shell> Print a completion script for bash, elvish, fish,
+                        powershell or zsh
+    man                 Print the man page in roff format
+                          --out <dir>            Write a page per command into a directory
     version             Show version information
     help                Show this help message
 
 SCRIPT INPUT FORMATS:
     - Hex string:       12139E40 or 0x12139E40
     - Binary file:      script.bin or script.nef
+    - Standard input:   - (hex or raw bytes)
     - Assembly file:    script.neoasm (for asm command)
 
+ARGUMENTS:
+    --arg values are pushed in order after any --args, the last one on top:
+    int:-5  bool:true  hex:01ff  str:hello  null
+    array:[1, true, "hex:01ff", [2, 3]]   JSON; strings are typed values
+
 EXAMPLES:
     # Execute a simple addition (PUSH2 PUSH3 ADD RET)
     neo-zkvm run 12139E40
 
+    # Execute a script read from a pipeline with two arguments (ADD RET)
+    echo 9E40 | neo-zkvm run - --arg int:5 --arg int:3
+
+    # Execute against a storage fixture and save the resulting state
+    neo-zkvm run script.bin --storage pre.json --dump-storage post.json
+
     # Assemble source code
     neo-zkvm asm "PUSH2 PUSH3 ADD RET"
     neo-zkvm asm program.neoasm
@@ -99,9 +210,37 @@ EXAMPLES:
     # Inspect script structure
     neo-zkvm inspect 12139E40
 
+    # Show instruction hit counts for a run with two arguments
+    neo-zkvm coverage contract.bin --args '[{{"type":"Integer","value":"5"}},{{"type":"Integer","value":"3"}}]'
+
+    # Check that an optimized script behaves like the original
+    neo-zkvm diff original.bin optimized.bin --args '[{{"type":"Integer","value":"5"}}]'
+
+    # Find where two recorded executions part
+    neo-zkvm run script.bin --trace a.json
+    neo-zkvm trace-diff a.json b.json
+
+    # Re-run an assembly file on every save
+    neo-zkvm watch program.neoasm --run --arg int:5
+
+    # Run a test suite and keep a JUnit report for CI
+    neo-zkvm test tests/*.toml --junit report.xml
+
     # Generate ZK proof
     neo-zkvm prove 12139E40
 
+    # Recompute the hashes a proof's public inputs commit to
+    neo-zkvm hash 12139E40
+    neo-zkvm encode-args '[{{"type":"Integer","value":"5"}}]'
+
+    # Install bash completions and the man pages
+    neo-zkvm completions bash > /etc/bash_completion.d/neo-zkvm
+    neo-zkvm man --out /usr/local/share/man/man1
+
+    # Pin the guest program an on-chain verifier accepts
+    neo-zkvm elf info
+    neo-zkvm vkey export --out vkey.bin
+
 For more information, visit: https://github.com/neonlabsorg/neo-zkvm"#,
         VERSION
     );
@@ -111,25 +250,51 @@ fn cmd_run(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err(
             "Missing script argument.\n\nUsage: neo-zkvm run <script>\n\nExamples:\n  \
-             neo-zkvm run 12139E40\n  neo-zkvm run script.bin"
+             neo-zkvm run 12139E40\n  neo-zkvm run script.bin\n  \
+             echo 9E40 | neo-zkvm run - --arg int:5 --arg int:3"
                 .to_string(),
         );
     }
 
     let script = parse_script(&args[0])?;
     let gas_limit = parse_gas_limit(args)?;
+    let arguments = parse_script_arguments(args)?;
 
     let mut vm = NeoVM::new(gas_limit);
-    let _ = vm.load_script(script);
+    if let Some(storage) = load_storage_fixture(args)? {
+        vm.storage = TrackedStorage::from(storage);
+    }
+    let _ = vm.load_script(script.clone());
+    vm.eval_stack.extend(arguments);
+    let trace_path = parse_option(args, "--trace");
+    if trace_path.is_some() {
+        vm.enable_tracing();
+    }
 
     println!("Executing script...\n");
 
     while !matches!(vm.state, VMState::Halt | VMState::Fault) {
         if let Err(e) = vm.execute_next() {
-            return Err(format!("Execution failed: {}", e));
+            if let Some(path) = trace_path {
+                write_trace(path, &vm.trace)?;
+            }
+            let reason = match &vm.fault_info {
+                Some(fault) => describe_fault(&script, fault),
+                None => e.to_string(),
+            };
+            return Err(format!("Execution failed: {}", reason));
         }
     }
 
+    if let Some(path) = trace_path {
+        write_trace(path, &vm.trace)?;
+    }
+
+    if let Some(path) = parse_option(args, "--dump-storage") {
+        fs::write(path, vm.storage.inner().to_json())
+            .map_err(|e| format!("Failed to write storage file '{}': {}", path, e))?;
+    }
+
     println!("═══════════════════════════════════════");
     println!("  EXECUTION RESULT");
     println!("═══════════════════════════════════════");
@@ -164,30 +329,97 @@ fn cmd_prove(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err(
             "Missing script argument.\n\nUsage: neo-zkvm prove <script>\n\nExamples:\n  \
-             neo-zkvm prove 12139E40\n  neo-zkvm prove script.bin"
+             neo-zkvm prove 12139E40\n  neo-zkvm prove script.bin --arg int:5"
                 .to_string(),
         );
     }
 
     let script = parse_script(&args[0])?;
     let gas_limit = parse_gas_limit(args)?;
+    let arguments = parse_script_arguments(args)?;
+    let json = args.iter().any(|arg| arg == "--json");
+
+    let mut config = ProverConfig::builder();
+    if let Some(mode) = parse_option(args, "--mode") {
+        config = config.proof_mode(
+            mode.parse::<ProofMode>()
+                .map_err(|e| format!("Invalid --mode: {}", e))?,
+        );
+    }
+    if let Some(backend) = parse_option(args, "--backend") {
+        config = config.backend(
+            backend
+                .parse::<ProverBackend>()
+                .map_err(|e| format!("Invalid --backend: {}", e))?,
+        );
+    }
+    let config = config.build().map_err(|e| e.to_string())?;
 
-    println!("Generating ZK proof...\n");
+    if !json {
+        println!("Generating ZK proof...\n");
+    }
 
     let input = ProofInput {
         script,
-        arguments: vec![],
+        arguments,
         gas_limit,
+        storage: load_storage_fixture(args)?
+            .map(MemoryStorage::into_entries)
+            .unwrap_or_default(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
-    let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let prover = NeoProver::new(config);
+    let proof = prover
+        .prove(input)
+        .map_err(|e| format!("Proof generation failed: {}", e))?;
+
+    // Without the SP1 toolchain the prover falls back to a mock proof
+    let mock = proof.proof_mode == ProofMode::Mock;
+    let verifier = if mock {
+        NeoVerifier::new().with_policy(VerifyPolicy::allow_mock_proofs())
+    } else {
+        NeoVerifier::new()
+    };
+    let verified = verifier.verify(&proof);
+
+    if json {
+        let result = serde_json::json!({
+            "output": proof.output,
+            "public_inputs": proof.public_inputs,
+            "proof_mode": proof.proof_mode,
+            "metadata": proof.metadata,
+            "verified": verified,
+        });
+        println!("{:#}", result);
+        return Ok(());
+    }
 
     println!("═══════════════════════════════════════");
     println!("  PROOF GENERATION RESULT");
     println!("═══════════════════════════════════════");
     println!("  Result:   {:?}", proof.output.result);
-    println!("  Verified: {}", verify(&proof));
+    println!("  Writes:   {}", proof.output.storage_writes.len());
+    println!("  Verified: {}", verified);
+    if mock {
+        println!("  Warning:  mock proof, install the SP1 toolchain for real proofs");
+    }
+    if let Some(report) = &proof.execution_report {
+        println!("  Cycles:   {}", report.total_cycles);
+        for (category, cycles) in &report.cycles_by_category {
+            println!("    {:?}: {}", category, cycles);
+        }
+        for (precompile, calls) in &report.precompile_calls {
+            println!("    {} calls: {}", precompile, calls);
+        }
+    }
     println!("═══════════════════════════════════════");
 
     Ok(())
@@ -196,8 +428,9 @@ fn cmd_prove(args: &[String]) -> Result<(), String> {
 fn cmd_assemble(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err(
-            "Missing source argument.\n\nUsage: neo-zkvm asm <source>\n\nExamples:\n  \
-             neo-zkvm asm \"PUSH2 PUSH3 ADD RET\"\n  neo-zkvm asm program.neoasm"
+            "Missing source argument.\n\nUsage: neo-zkvm asm <source> [--debug-info <file>]\n\n\
+             Examples:\n  neo-zkvm asm \"PUSH2 PUSH3 ADD RET\"\n  neo-zkvm asm program.neoasm\n  \
+             neo-zkvm asm program.neoasm --debug-info program.neodbg"
                 .to_string(),
         );
     }
@@ -211,6 +444,14 @@ fn cmd_assemble(args: &[String]) -> Result<(), String> {
     let mut assembler = Assembler::new();
     let bytecode = assembler.assemble(&source)?;
 
+    if let Some(path) = parse_option(args, "--debug-info") {
+        let mut debug_info = assembler.debug_info().clone();
+        if args[0].ends_with(".neoasm") {
+            debug_info.documents.push(args[0].clone());
+        }
+        debug_info.write(Path::new(path))?;
+    }
+
     println!("{}", hex::encode(&bytecode));
 
     // Show warnings if any
@@ -224,16 +465,18 @@ fn cmd_assemble(args: &[String]) -> Result<(), String> {
 fn cmd_disassemble(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err(
-            "Missing bytecode argument.\n\nUsage: neo-zkvm disasm <hex>\n\nExamples:\n  \
-             neo-zkvm disasm 12139E40\n  neo-zkvm disasm script.bin"
+            "Missing bytecode argument.\n\nUsage: neo-zkvm disasm <hex> [--debug-info <file>]\n\n\
+             Examples:\n  neo-zkvm disasm 12139E40\n  neo-zkvm disasm script.bin\n  \
+             neo-zkvm disasm script.bin --debug-info script.debug.json"
                 .to_string(),
         );
     }
 
     let script = parse_script(&args[0])?;
-    let disasm = Disassembler::new(&script);
-
-    println!("{}", disasm.disassemble());
+    match load_debug_info(args)? {
+        Some(debug_info) => println!("{}", debug_info.disassemble(&script)),
+        None => println!("{}", Disassembler::new(&script).disassemble()),
+    }
 
     Ok(())
 }
@@ -241,395 +484,761 @@ fn cmd_disassemble(args: &[String]) -> Result<(), String> {
 fn cmd_debug(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err(
-            "Missing script argument.\n\nUsage: neo-zkvm debug <script>\n\nExamples:\n  \
-             neo-zkvm debug 12139E40\n  neo-zkvm debug script.bin"
+            "Missing script argument.\n\nUsage: neo-zkvm debug <script> [--debug-info <file>] \
+             [--batch <file>] [--mi]\n\n\
+             Examples:\n  neo-zkvm debug 12139E40\n  neo-zkvm debug script.bin\n  \
+             neo-zkvm debug script.bin --debug-info script.neodbg\n  \
+             neo-zkvm debug script.bin --batch commands.txt\n  \
+             neo-zkvm debug script.bin --mi"
                 .to_string(),
         );
     }
 
     let script = parse_script(&args[0])?;
     let gas_limit = parse_gas_limit(args)?;
-
-    let mut debugger = Debugger::new(script, gas_limit);
-    debugger.run()?;
+    let debug_info = load_debug_info(args)?;
+
+    let mut debugger = Debugger::new(script, gas_limit, debug_info);
+    let mi = args.iter().any(|arg| arg == "--mi");
+    match parse_option(args, "--batch") {
+        Some(path) => {
+            let file =
+                fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+            let input = io::BufReader::new(file);
+            if mi {
+                debugger.serve_mi(input, io::stdout().lock())?;
+            } else {
+                debugger.run(input, true)?;
+            }
+        }
+        None if mi => debugger.serve_mi(io::stdin().lock(), io::stdout().lock())?,
+        None => debugger.run(io::stdin().lock(), false)?,
+    }
 
     Ok(())
 }
 
+fn cmd_dap() -> Result<(), String> {
+    DapServer::new().serve(io::stdin().lock(), io::stdout().lock())
+}
+
 fn cmd_inspect(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err(
-            "Missing script argument.\n\nUsage: neo-zkvm inspect <script>\n\nExamples:\n  \
-             neo-zkvm inspect 12139E40\n  neo-zkvm inspect script.bin"
+            "Missing script argument.\n\nUsage: neo-zkvm inspect <script> [--json] [--fix [--out <file>]]\n\n\
+             Examples:\n  neo-zkvm inspect 12139E40\n  neo-zkvm inspect script.bin --json\n  \
+             neo-zkvm inspect script.bin --fix --out golfed.bin"
                 .to_string(),
         );
     }
 
     let script = parse_script(&args[0])?;
-    let inspector = Inspector::new(&script);
+    let suggestions = suggest_optimizations(&script, GasSchedule::default());
+    let fixed = if args.iter().any(|arg| arg == "--fix") {
+        Some(apply_suggestions(&script, &suggestions).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    if let (Some(fixed), Some(out)) = (&fixed, parse_option(args, "--out")) {
+        fs::write(out, fixed).map_err(|e| format!("Failed to write {}: {}", out, e))?;
+    }
+
+    if args.iter().any(|arg| arg == "--json") {
+        let lints: Vec<_> = lint_script(&script)
+            .into_iter()
+            .map(|lint| {
+                serde_json::json!({
+                    "id": lint.id.code(),
+                    "name": lint.id.name(),
+                    "offset": lint.offset,
+                    "message": lint.message,
+                })
+            })
+            .collect();
+        let suggestions: Vec<_> = suggestions
+            .iter()
+            .map(|suggestion| {
+                serde_json::json!({
+                    "id": suggestion.id.code(),
+                    "name": suggestion.id.name(),
+                    "offset": suggestion.offset,
+                    "length": suggestion.len,
+                    "replacement": hex::encode(&suggestion.replacement),
+                    "gas_saved": suggestion.gas_saved,
+                    "message": suggestion.message,
+                })
+            })
+            .collect();
+        let script_hash = UInt160::from_script(&script);
+        let mut report = serde_json::json!({
+            "script_hash": script_hash.to_string(),
+            "address": script_hash.to_address(),
+            "lints": lints,
+            "suggestions": suggestions,
+        });
+        if let Some(fixed) = &fixed {
+            report["fixed"] = hex::encode(fixed).into();
+        }
+        println!("{:#}", report);
+        return Ok(());
+    }
 
-    println!("{}", inspector.analyze());
+    match fixed {
+        Some(fixed) => {
+            for suggestion in &suggestions {
+                println!("{}", describe_suggestion(suggestion));
+            }
+            println!(
+                "Applied {}: {} -> {} bytes, {} gas less per pass",
+                match suggestions.len() {
+                    1 => "1 suggestion".to_string(),
+                    n => format!("{} suggestions", n),
+                },
+                script.len(),
+                fixed.len(),
+                suggestions.iter().map(|s| s.gas_saved).sum::<u64>()
+            );
+            match parse_option(args, "--out") {
+                Some(out) => println!("Wrote {}", out),
+                None => println!("{}", hex::encode(&fixed)),
+            }
+        }
+        None => println!("{}", Inspector::new(&script).analyze()),
+    }
 
     Ok(())
 }
 
-const MAX_SCRIPT_SIZE: usize = 1024 * 1024; // 1MB
-
-fn parse_script(input: &str) -> Result<Vec<u8>, String> {
-    if input.ends_with(".nef") || input.ends_with(".bin") {
-        let metadata =
-            fs::metadata(input).map_err(|e| format!("Failed to read file '{}': {}", input, e))?;
-        if metadata.len() > MAX_SCRIPT_SIZE as u64 {
-            return Err(format!(
-                "Script file exceeds maximum size of {} bytes",
-                MAX_SCRIPT_SIZE
-            ));
-        }
-        let content =
-            fs::read(input).map_err(|e| format!("Failed to read file '{}': {}", input, e))?;
-        if content.len() > MAX_SCRIPT_SIZE {
-            return Err(format!(
-                "Script content exceeds maximum size of {} bytes",
-                MAX_SCRIPT_SIZE
-            ));
-        }
-        Ok(content)
+/// One line for a gas golf suggestion: where, what and what it saves
+fn describe_suggestion(suggestion: &Suggestion) -> String {
+    let replacement = if suggestion.replacement.is_empty() {
+        "remove".to_string()
     } else {
-        let hex_str = input.trim_start_matches("0x");
-        let decoded = hex::decode(hex_str).map_err(|e| format!("Invalid hex string: {}", e))?;
-        if decoded.len() > MAX_SCRIPT_SIZE {
-            return Err(format!(
-                "Script exceeds maximum size of {} bytes",
-                MAX_SCRIPT_SIZE
-            ));
-        }
-        Ok(decoded)
-    }
+        format!("replace with {}", hex::encode(&suggestion.replacement))
+    };
+    format!(
+        "    0x{:04X}  {} {}: {} ({}; saves {} gas, {} bytes)",
+        suggestion.offset,
+        suggestion.id.code(),
+        suggestion.id.name(),
+        suggestion.message,
+        replacement,
+        suggestion.gas_saved,
+        suggestion.bytes_saved()
+    )
 }
 
-fn parse_gas_limit(args: &[String]) -> Result<u64, String> {
-    for (i, arg) in args.iter().enumerate() {
-        if (arg == "--gas" || arg == "-g") && i + 1 < args.len() {
-            return args[i + 1]
-                .parse()
-                .map_err(|_| "Invalid gas limit value".to_string());
+fn cmd_coverage(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(
+            "Missing script argument.\n\nUsage: neo-zkvm coverage <script> [--args <json>]\n\n\
+             Examples:\n  neo-zkvm coverage 12139E40\n  \
+             neo-zkvm coverage script.bin --args '[{\"type\":\"Integer\",\"value\":\"5\"}]'"
+                .to_string(),
+        );
+    }
+
+    let script = parse_script(&args[0])?;
+    let gas_limit = parse_gas_limit(args)?;
+    let arguments = parse_script_arguments(args)?;
+
+    let mut vm = NeoVM::new(gas_limit);
+    if let Some(storage) = load_storage_fixture(args)? {
+        vm.storage = TrackedStorage::from(storage);
+    }
+    vm.enable_coverage();
+    vm.load_script(script.clone()).map_err(|e| e.to_string())?;
+    vm.eval_stack.extend(arguments);
+    vm.run();
+
+    let coverage = vm.coverage.take().unwrap_or_default();
+    let disasm = Disassembler::new(&script);
+    let mut ip = 0;
+    while ip < script.len() {
+        let (name, size) = disasm.decode_instruction(ip);
+        match coverage.hits(ip) {
+            0 => println!("     -  {:04X}:  {}", ip, name),
+            hits => println!("{:>6}  {:04X}:  {}", hits, ip, name),
         }
+        ip += size.max(1);
     }
-    Ok(1_000_000) // Default gas limit
-}
 
-// ============================================================================
-// Debugger
-// ============================================================================
+    let (covered, total) = coverage.summary(&script);
+    let percent = if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    };
+    println!();
+    println!("State:    {:?}", vm.state);
+    if let Some(fault) = &vm.fault_info {
+        println!("Fault:    {}", describe_fault(&script, fault));
+    }
+    println!(
+        "Coverage: {}/{} instructions ({:.1}%)",
+        covered, total, percent
+    );
 
-struct Debugger {
-    vm: NeoVM,
-    script: Vec<u8>,
-    breakpoints: Vec<usize>,
-    history: Vec<String>,
+    Ok(())
 }
 
-impl Debugger {
-    fn new(script: Vec<u8>, gas_limit: u64) -> Self {
+fn cmd_diff(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 {
+        return Err(
+            "Missing script arguments.\n\nUsage: neo-zkvm diff <scriptA> <scriptB> [--args <json>]\n\n\
+             Examples:\n  neo-zkvm diff 12139E40 1540\n  \
+             neo-zkvm diff original.bin optimized.bin --args '[{\"type\":\"Integer\",\"value\":\"5\"}]'"
+                .to_string(),
+        );
+    }
+
+    let scripts = [parse_script(&args[0])?, parse_script(&args[1])?];
+    let gas_limit = parse_gas_limit(args)?;
+    let arguments = parse_script_arguments(args)?;
+    let storage = load_storage_fixture(args)?;
+
+    let mut vms = Vec::with_capacity(2);
+    for script in &scripts {
         let mut vm = NeoVM::new(gas_limit);
-        let _ = vm.load_script(script.clone());
-        Self {
-            vm,
-            script,
-            breakpoints: Vec::new(),
-            history: Vec::new(),
+        if let Some(storage) = &storage {
+            vm.storage = TrackedStorage::from(storage.clone());
         }
+        vm.enable_tracing();
+        vm.load_script(script.clone()).map_err(|e| e.to_string())?;
+        vm.eval_stack.extend(arguments.iter().cloned());
+        vm.run();
+        vms.push(vm);
     }
+    let (a, b) = (&vms[0], &vms[1]);
 
-    fn run(&mut self) -> Result<(), String> {
-        println!("Neo zkVM Debugger v{}", VERSION);
-        println!("Type 'help' for available commands.\n");
+    for (label, vm, script) in [("A", a, &scripts[0]), ("B", b, &scripts[1])] {
+        println!(
+            "Script {}: {:?}, gas {}, {} steps",
+            label,
+            vm.state,
+            vm.gas_consumed,
+            vm.trace.steps.len()
+        );
+        if let Some(fault) = &vm.fault_info {
+            println!("  Fault: {}", describe_fault(script, fault));
+        }
+    }
 
-        self.print_current_state();
+    println!();
+    match a.trace.diff(&b.trace) {
+        TraceDiff::Step { index, left, right } => {
+            println!("First divergence at step {}:", index);
+            for (label, step, script) in [("A", left, &scripts[0]), ("B", right, &scripts[1])] {
+                match step {
+                    Some(step) => {
+                        let (name, _) = Disassembler::new(script).decode_instruction(step.ip);
+                        println!(
+                            "  {}: 0x{:04X}  {:<24} [gas: {}]",
+                            label, step.ip, name, step.gas_consumed
+                        );
+                    }
+                    None => println!("  {}: <ended>", label),
+                }
+            }
+        }
+        TraceDiff::FinalState { .. } => println!("Steps are identical, final states differ."),
+        TraceDiff::Identical => println!("Traces are identical."),
+        TraceDiff::Unbuffered => println!("Traces differ."),
+    }
 
-        let stdin = io::stdin();
-        let mut stdout = io::stdout();
+    println!();
+    let depth = a.eval_stack.len().max(b.eval_stack.len());
+    let item = |stack: &[neo_vm_core::StackItem], i: usize| {
+        stack
+            .len()
+            .checked_sub(i + 1)
+            .map(|index| format!("{:?}", stack[index]))
+            .unwrap_or_else(|| "<none>".to_string())
+    };
+    let differences: Vec<_> = (0..depth)
+        .map(|i| (i, item(&a.eval_stack, i), item(&b.eval_stack, i)))
+        .filter(|(_, x, y)| x != y)
+        .collect();
+    let stacks_match = differences.is_empty();
+    if stacks_match {
+        println!("Final stacks match.");
+    } else {
+        println!("Final stack differences (top → bottom):");
+        for (i, x, y) in differences {
+            println!("  [{}] A: {}  B: {}", i, x, y);
+        }
+    }
 
-        loop {
-            print!("(neodbg) ");
-            stdout.flush().unwrap();
+    let writes_match = a.storage.write_set() == b.storage.write_set();
+    if !writes_match {
+        println!("Storage writes differ.");
+    }
 
-            let mut line = String::new();
-            if stdin.lock().read_line(&mut line).is_err() {
-                break;
-            }
+    let delta = b.gas_consumed as i128 - a.gas_consumed as i128;
+    println!("Gas delta: {:+} (B - A)", delta);
 
-            let line = line.trim();
-            if line.is_empty() {
-                // Repeat last command
-                if let Some(last) = self.history.last().cloned() {
-                    self.execute_command(&last)?;
-                }
-                continue;
-            }
+    // Gas and the steps taken may differ; what the scripts produce may not
+    let same_state = std::mem::discriminant(&a.state) == std::mem::discriminant(&b.state);
+    if !same_state || !stacks_match || !writes_match {
+        return Err("Scripts A and B produce different results".to_string());
+    }
+    Ok(())
+}
 
-            self.history.push(line.to_string());
+fn cmd_trace_diff(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 {
+        return Err(
+            "Missing trace arguments.\n\nUsage: neo-zkvm trace-diff <a.json> <b.json>\n\n\
+             Example:\n  neo-zkvm run script.bin --trace host.json\n  \
+             neo-zkvm trace-diff host.json guest.json"
+                .to_string(),
+        );
+    }
 
-            if self.execute_command(line)? {
-                break;
+    let load = |path: &str| -> Result<ExecutionTrace, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read trace '{}': {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid trace '{}': {}", path, e))
+    };
+    let (a, b) = (load(&args[0])?, load(&args[1])?);
+    println!("Trace A: {} steps", a.step_count);
+    println!("Trace B: {} steps", b.step_count);
+    println!();
+
+    match a.diff(&b) {
+        TraceDiff::Identical => println!("Traces are identical."),
+        TraceDiff::Step { index, left, right } => {
+            println!("First divergence at step {}:", index);
+            for (label, step) in [("A", left), ("B", right)] {
+                match step {
+                    Some(step) => println!(
+                        "  {}: 0x{:04X}  opcode 0x{:02X}  stack {}  [gas: {}]",
+                        label,
+                        step.ip,
+                        step.opcode,
+                        hex::encode(step.stack_hash),
+                        step.gas_consumed
+                    ),
+                    None => println!("  {}: <ended>", label),
+                }
             }
         }
-
-        Ok(())
-    }
-
-    fn execute_command(&mut self, cmd: &str) -> Result<bool, String> {
-        let parts: Vec<&str> = cmd.split_whitespace().collect();
-        if parts.is_empty() {
-            return Ok(false);
-        }
-
-        match parts[0] {
-            "help" | "h" => self.cmd_help(),
-            "step" | "s" | "n" => self.cmd_step(),
-            "continue" | "c" => self.cmd_continue(),
-            "run" | "r" => self.cmd_run_to_end(),
-            "break" | "b" => self.cmd_breakpoint(&parts[1..]),
-            "delete" | "d" => self.cmd_delete_breakpoint(&parts[1..]),
-            "info" | "i" => self.cmd_info(&parts[1..]),
-            "print" | "p" => self.cmd_print(&parts[1..]),
-            "stack" => self.cmd_stack(),
-            "disasm" => self.cmd_disasm(),
-            "reset" => self.cmd_reset(),
-            "quit" | "q" | "exit" => return Ok(true),
-            _ => {
-                println!(
-                    "Unknown command: '{}'. Type 'help' for available commands.",
-                    parts[0]
-                );
-            }
+        TraceDiff::FinalState { left, right } => {
+            println!("Steps are identical, final states differ:");
+            println!("  A: {}", hex::encode(left));
+            println!("  B: {}", hex::encode(right));
+        }
+        TraceDiff::Unbuffered => {
+            println!("Traces differ, but a trace without recorded steps cannot show where.")
         }
-
-        Ok(false)
     }
 
-    fn cmd_help(&self) {
-        println!(
-            r#"
-Available commands:
-  step, s, n          Execute next instruction
-  continue, c         Continue until breakpoint or halt
-  run, r              Run to completion
-  break <addr>, b     Set breakpoint at address (hex)
-  delete <addr>, d    Delete breakpoint
-  info breakpoints    List all breakpoints
-  info registers      Show VM state
-  print <n>, p        Print stack item at index n
-  stack               Show full stack
-  disasm              Disassemble current script
-  reset               Reset VM to initial state
-  quit, q, exit       Exit debugger
-"#
+    Ok(())
+}
+
+fn cmd_watch(args: &[String]) -> Result<(), String> {
+    let Some(path) = args.first().filter(|arg| !arg.starts_with("--")) else {
+        return Err(
+            "Missing source argument.\n\nUsage: neo-zkvm watch <source> [--run | --test <tests.toml>]\n\n\
+             Examples:\n  neo-zkvm watch program.neoasm --run --arg int:5\n  \
+             neo-zkvm watch program.neoasm --test tests/program.toml"
+                .to_string(),
         );
+    };
+    if let Some(tests) = parse_option(args, "--test") {
+        println!("Watching {}, testing {} (Ctrl-C to stop)\n", path, tests);
+        let run_tests = || {
+            test_runner::print_reports(&[test_runner::run_file(
+                std::path::Path::new(tests),
+                test_runner::RunOptions::default(),
+            )]);
+        };
+        run_tests();
+        return watch::watch_file(std::path::Path::new(path), || {
+            println!("\n── {} changed", path);
+            run_tests();
+        });
+    }
+
+    let gas_limit = parse_gas_limit(args)?;
+    let arguments = parse_script_arguments(args)?;
+    let storage = load_storage_fixture(args)?;
+    let run = || watch::run_once(path, gas_limit, &arguments, storage.as_ref());
+
+    println!("Watching {} (Ctrl-C to stop)\n", path);
+    let mut last = run();
+    for line in watch::render(&last) {
+        println!("{}", line);
     }
 
-    fn cmd_step(&mut self) {
-        if matches!(self.vm.state, VMState::Halt | VMState::Fault) {
-            println!("Program has terminated. Use 'reset' to restart.");
-            return;
+    watch::watch_file(std::path::Path::new(path), || {
+        let outcome = run();
+        println!("\n── {} changed", path);
+        for line in watch::render_diff(&last, &outcome) {
+            println!("{}", line);
         }
+        last = outcome;
+    })
+}
 
-        if let Err(e) = self.vm.execute_next() {
-            println!("Error: {}", e);
+fn cmd_test(args: &[String]) -> Result<(), String> {
+    let mut files = Vec::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--junit" => {
+                rest.next();
+            }
+            "--sp1" | "--bless" => {}
+            path if fs::metadata(path).is_ok_and(|m| m.is_dir()) => {
+                let mut entries: Vec<_> = fs::read_dir(path)
+                    .map_err(|e| format!("Failed to read directory '{}': {}", path, e))?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                    .collect();
+                entries.sort();
+                files.extend(entries);
+            }
+            path => files.push(path.into()),
         }
+    }
+    if files.is_empty() {
+        return Err(
+            "Missing test files.\n\nUsage: neo-zkvm test <files or directories> [--sp1] \
+             [--bless] [--junit <file>]\n\nExamples:\n  neo-zkvm test tests/*.toml\n  \
+             neo-zkvm test tests --sp1 --junit report.xml\n  neo-zkvm test tests --bless"
+                .to_string(),
+        );
+    }
+
+    let options = test_runner::RunOptions {
+        sp1: args.iter().any(|arg| arg == "--sp1"),
+        bless: args.iter().any(|arg| arg == "--bless"),
+    };
+    if options.sp1 && !NeoProver::is_elf_available() {
+        return Err(
+            "--sp1 needs the SP1 guest ELF, install the SP1 toolchain and rebuild".to_string(),
+        );
+    }
 
-        self.print_current_state();
+    let reports: Vec<_> = files
+        .iter()
+        .map(|path| test_runner::run_file(path, options))
+        .collect();
+    let failed = test_runner::print_reports(&reports);
+    if let Some(path) = parse_option(args, "--junit") {
+        fs::write(path, test_runner::junit_xml(&reports))
+            .map_err(|e| format!("Failed to write JUnit report '{}': {}", path, e))?;
     }
 
-    fn cmd_continue(&mut self) {
-        while !matches!(self.vm.state, VMState::Halt | VMState::Fault) {
-            let ip = self.get_current_ip();
-            if self.breakpoints.contains(&ip)
-                && !self
-                    .history
-                    .last()
-                    .map(|s| s.starts_with("continue"))
-                    .unwrap_or(false)
-            {
-                println!("Breakpoint hit at 0x{:04X}", ip);
-                break;
-            }
+    match failed {
+        0 => Ok(()),
+        1 => Err("1 test failed".to_string()),
+        n => Err(format!("{} tests failed", n)),
+    }
+}
 
-            if let Err(e) = self.vm.execute_next() {
-                println!("Error: {}", e);
-                break;
-            }
+fn cmd_hash(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(
+            "Missing script argument.\n\nUsage: neo-zkvm hash <script> [--json]\n\nExamples:\n  \
+             neo-zkvm hash 12139E40\n  neo-zkvm hash script.bin --json"
+                .to_string(),
+        );
+    }
 
-            // Check breakpoint after execution
-            let new_ip = self.get_current_ip();
-            if self.breakpoints.contains(&new_ip) {
-                println!("Breakpoint hit at 0x{:04X}", new_ip);
-                self.print_current_state();
-                return;
-            }
-        }
+    let script = parse_script(&args[0])?;
+    let sha256 = hex::encode(Sha256::digest(&script));
+    let script_hash = UInt160::from_script(&script);
 
-        self.print_current_state();
+    if args.iter().any(|arg| arg == "--json") {
+        println!(
+            "{:#}",
+            serde_json::json!({
+                "sha256": sha256,
+                "script_hash": script_hash.to_string(),
+                "address": script_hash.to_address(),
+            })
+        );
+        return Ok(());
     }
 
-    fn cmd_run_to_end(&mut self) {
-        while !matches!(self.vm.state, VMState::Halt | VMState::Fault) {
-            if let Err(e) = self.vm.execute_next() {
-                println!("Error: {}", e);
-                break;
-            }
-        }
+    println!("SHA-256:     {}", sha256);
+    println!("Script hash: {}", script_hash);
+    println!("Address:     {}", script_hash.to_address());
+    Ok(())
+}
 
-        self.print_current_state();
+fn cmd_encode_args(args: &[String]) -> Result<(), String> {
+    let (json, options) = match args.split_first() {
+        Some((first, rest)) if !first.starts_with("--") => (Some(first), rest),
+        _ => (None, args),
+    };
+    if json.is_none() && !options.iter().any(|arg| arg == "--arg" || arg == "--args") {
+        return Err(
+            "Missing arguments.\n\nUsage: neo-zkvm encode-args <json> [--arg <value>]... [--json]\n\n\
+             Examples:\n  neo-zkvm encode-args '[{\"type\":\"Integer\",\"value\":\"5\"}]'\n  \
+             neo-zkvm encode-args --arg int:5 --arg str:alice"
+                .to_string(),
+        );
     }
 
-    fn cmd_breakpoint(&mut self, args: &[&str]) {
-        if args.is_empty() {
-            println!("Usage: break <address>");
-            return;
-        }
+    // Positional contract parameters first, then any --args and --arg
+    let mut arguments = match json {
+        Some(json) => parse_arguments(json).map_err(|e| format!("Invalid arguments: {}", e))?,
+        None => Vec::new(),
+    };
+    arguments.extend(parse_script_arguments(options)?);
 
-        let addr_str = args[0].trim_start_matches("0x");
-        match usize::from_str_radix(addr_str, 16) {
-            Ok(addr) => {
-                if !self.breakpoints.contains(&addr) {
-                    self.breakpoints.push(addr);
-                    println!("Breakpoint set at 0x{:04X}", addr);
-                } else {
-                    println!("Breakpoint already exists at 0x{:04X}", addr);
-                }
-            }
-            Err(_) => println!("Invalid address: {}", args[0]),
-        }
+    let encoded = encode_arguments(&arguments);
+    let hash = hex::encode(Sha256::digest(&encoded));
+
+    if args.iter().any(|arg| arg == "--json") {
+        println!(
+            "{:#}",
+            serde_json::json!({
+                "encoded": hex::encode(&encoded),
+                "sha256": hash,
+            })
+        );
+        return Ok(());
     }
 
-    fn cmd_delete_breakpoint(&mut self, args: &[&str]) {
-        if args.is_empty() {
-            println!("Usage: delete <address>");
-            return;
-        }
+    println!("Encoded: {}", hex::encode(&encoded));
+    println!("SHA-256: {}", hash);
+    Ok(())
+}
 
-        let addr_str = args[0].trim_start_matches("0x");
-        match usize::from_str_radix(addr_str, 16) {
-            Ok(addr) => {
-                if let Some(pos) = self.breakpoints.iter().position(|&x| x == addr) {
-                    self.breakpoints.remove(pos);
-                    println!("Breakpoint removed at 0x{:04X}", addr);
-                } else {
-                    println!("No breakpoint at 0x{:04X}", addr);
-                }
-            }
-            Err(_) => println!("Invalid address: {}", args[0]),
-        }
+fn cmd_vkey(args: &[String]) -> Result<(), String> {
+    if args.first().map(String::as_str) != Some("export") {
+        return Err(
+            "Usage: neo-zkvm vkey export [--out <file>]\n\nExamples:\n  \
+             neo-zkvm vkey export\n  neo-zkvm vkey export --out vkey.bin"
+                .to_string(),
+        );
     }
 
-    fn cmd_info(&self, args: &[&str]) {
-        if args.is_empty() {
-            println!("Usage: info <breakpoints|registers>");
-            return;
-        }
+    let verifier = NeoVerifier::new();
+    let bytes = verifier
+        .vkey_bytes()
+        .ok_or("No verifying key: the SP1 guest ELF was not built")?;
+    // Named for the ELF by default, ready for a verifying key registry
+    let out = parse_option(args, "--out").map_or_else(
+        || {
+            format!(
+                "{}.vkey",
+                hex::encode(ProverConfig::default().guest_elf_hash())
+            )
+        },
+        str::to_string,
+    );
+    fs::write(&out, &bytes).map_err(|e| format!("Failed to write '{}': {}", out, e))?;
 
-        match args[0] {
-            "breakpoints" | "b" => {
-                if self.breakpoints.is_empty() {
-                    println!("No breakpoints set.");
-                } else {
-                    println!("Breakpoints:");
-                    for (i, bp) in self.breakpoints.iter().enumerate() {
-                        println!("  {}: 0x{:04X}", i + 1, bp);
-                    }
-                }
-            }
-            "registers" | "r" => {
-                println!("VM State:");
-                println!("  State:        {:?}", self.vm.state);
-                println!("  IP:           0x{:04X}", self.get_current_ip());
-                println!("  Gas consumed: {}", self.vm.gas_consumed);
-                println!("  Gas limit:    {}", self.vm.gas_limit);
-                println!("  Stack depth:  {}", self.vm.eval_stack.len());
-            }
-            _ => println!("Unknown info type: {}", args[0]),
-        }
+    println!("Wrote {} byte verifying key to {}", bytes.len(), out);
+    if let Some(hash) = verifier.vkey_hash() {
+        println!("  SP1 vkey hash: {}", hash);
     }
+    Ok(())
+}
 
-    fn cmd_print(&self, args: &[&str]) {
-        if args.is_empty() {
-            if let Some(top) = self.vm.eval_stack.last() {
-                println!("Top: {:?}", top);
-            } else {
-                println!("Stack is empty.");
-            }
-            return;
-        }
+fn cmd_elf(args: &[String]) -> Result<(), String> {
+    if args.first().map(String::as_str) != Some("info") {
+        return Err("Usage: neo-zkvm elf info [--json]".to_string());
+    }
 
-        match args[0].parse::<usize>() {
-            Ok(idx) => {
-                let len = self.vm.eval_stack.len();
-                if idx < len {
-                    println!("[{}]: {:?}", idx, self.vm.eval_stack[len - 1 - idx]);
-                } else {
-                    println!("Index out of range (stack depth: {})", len);
-                }
-            }
-            Err(_) => println!("Invalid index: {}", args[0]),
-        }
+    let config = ProverConfig::default();
+    let elf_hash = hex::encode(config.guest_elf_hash());
+    let vkey_hash = NeoVerifier::new().vkey_hash();
+
+    if args.iter().any(|arg| arg == "--json") {
+        let info = serde_json::json!({
+            "size": config.guest_elf().len(),
+            "sha256": elf_hash,
+            "built": NeoProver::is_elf_available(),
+            "vkey_hash": vkey_hash,
+        });
+        println!("{:#}", info);
+        return Ok(());
     }
 
-    fn cmd_stack(&self) {
-        if self.vm.eval_stack.is_empty() {
-            println!("Stack is empty.");
-        } else {
-            println!("Stack (top → bottom):");
-            for (i, item) in self.vm.eval_stack.iter().rev().enumerate() {
-                println!("  [{}] {:?}", i, item);
-            }
+    println!("Guest ELF");
+    println!("  Size:          {} bytes", config.guest_elf().len());
+    println!("  SHA-256:       {}", elf_hash);
+    match vkey_hash {
+        Some(hash) => println!("  SP1 vkey hash: {}", hash),
+        None => println!("  SP1 vkey hash: unavailable, the SP1 guest ELF was not built"),
+    }
+    Ok(())
+}
+
+fn cmd_completions(args: &[String]) -> Result<(), String> {
+    let Some(shell) = args.first() else {
+        return Err(
+            "Missing shell argument.\n\nUsage: neo-zkvm completions <shell>\n\n\
+             Shells: bash, elvish, fish, powershell, zsh\n\nExamples:\n  \
+             neo-zkvm completions bash > /etc/bash_completion.d/neo-zkvm\n  \
+             neo-zkvm completions zsh > ~/.zfunc/_neo-zkvm"
+                .to_string(),
+        );
+    };
+    let shell: clap_complete::Shell = shell.parse().map_err(|_| {
+        format!(
+            "Unknown shell '{}', expected bash, elvish, fish, powershell or zsh",
+            shell
+        )
+    })?;
+    // Generated into a buffer, as clap_complete panics on write errors
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut completions::command(), "neo-zkvm", &mut script);
+    io::stdout()
+        .write_all(&script)
+        .map_err(|e| format!("Failed to write completions: {}", e))?;
+    Ok(())
+}
+
+fn cmd_man(args: &[String]) -> Result<(), String> {
+    let command = completions::command();
+    match parse_option(args, "--out") {
+        Some(dir) => {
+            fs::create_dir_all(dir)
+                .and_then(|()| clap_mangen::generate_to(command, dir))
+                .map_err(|e| format!("Failed to write man pages to '{}': {}", dir, e))?;
+            println!("Wrote man pages to {}", dir);
         }
+        None => clap_mangen::Man::new(command)
+            .render(&mut io::stdout())
+            .map_err(|e| e.to_string())?,
     }
+    Ok(())
+}
 
-    fn cmd_disasm(&self) {
-        let disasm = Disassembler::new(&self.script);
-        println!("{}", disasm.disassemble());
+const MAX_SCRIPT_SIZE: usize = 1024 * 1024; // 1MB
+
+/// A fault with the faulting instruction of `script` disassembled
+fn describe_fault(script: &[u8], fault: &FaultInfo) -> String {
+    let mut text = fault.to_string();
+    if fault.ip < script.len() {
+        let (name, _) = Disassembler::new(script).decode_instruction(fault.ip);
+        text.push_str(&format!(": {}", name));
     }
+    text
+}
 
-    fn cmd_reset(&mut self) {
-        self.vm = NeoVM::new(self.vm.gas_limit);
-        let _ = self.vm.load_script(self.script.clone());
-        println!("VM reset to initial state.");
-        self.print_current_state();
+fn parse_script(input: &str) -> Result<Vec<u8>, String> {
+    if input == "-" {
+        read_script_from_stdin()
+    } else if input.ends_with(".nef") || input.ends_with(".bin") {
+        let metadata =
+            fs::metadata(input).map_err(|e| format!("Failed to read file '{}': {}", input, e))?;
+        if metadata.len() > MAX_SCRIPT_SIZE as u64 {
+            return Err(format!(
+                "Script file exceeds maximum size of {} bytes",
+                MAX_SCRIPT_SIZE
+            ));
+        }
+        let content =
+            fs::read(input).map_err(|e| format!("Failed to read file '{}': {}", input, e))?;
+        if content.len() > MAX_SCRIPT_SIZE {
+            return Err(format!(
+                "Script content exceeds maximum size of {} bytes",
+                MAX_SCRIPT_SIZE
+            ));
+        }
+        Ok(content)
+    } else {
+        let hex_str = input.trim_start_matches("0x");
+        let decoded = hex::decode(hex_str).map_err(|e| format!("Invalid hex string: {}", e))?;
+        if decoded.len() > MAX_SCRIPT_SIZE {
+            return Err(format!(
+                "Script exceeds maximum size of {} bytes",
+                MAX_SCRIPT_SIZE
+            ));
+        }
+        Ok(decoded)
     }
+}
 
-    fn get_current_ip(&self) -> usize {
-        self.vm
-            .invocation_stack
-            .last()
-            .map(|ctx| ctx.ip)
-            .unwrap_or(0)
+/// Script from standard input, as hex text or else as raw bytes
+fn read_script_from_stdin() -> Result<Vec<u8>, String> {
+    let mut content = Vec::new();
+    io::stdin()
+        .take(MAX_SCRIPT_SIZE as u64 * 2 + 3)
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read script from stdin: {}", e))?;
+    let script = std::str::from_utf8(&content)
+        .ok()
+        .and_then(|text| hex::decode(text.trim().trim_start_matches("0x")).ok())
+        .unwrap_or(content);
+    if script.len() > MAX_SCRIPT_SIZE {
+        return Err(format!(
+            "Script exceeds maximum size of {} bytes",
+            MAX_SCRIPT_SIZE
+        ));
     }
+    Ok(script)
+}
 
-    fn print_current_state(&self) {
-        if matches!(self.vm.state, VMState::Halt) {
-            println!("Program halted. Gas consumed: {}", self.vm.gas_consumed);
-            return;
+/// Arguments from `--args` followed by each `--arg`, bottom of the stack
+/// first
+fn parse_script_arguments(args: &[String]) -> Result<Vec<neo_vm_core::StackItem>, String> {
+    let mut arguments = match parse_option(args, "--args") {
+        Some(json) => parse_arguments(json).map_err(|e| format!("Invalid --args: {}", e))?,
+        None => Vec::new(),
+    };
+    for (flag, value) in args.iter().zip(args.iter().skip(1)) {
+        if flag == "--arg" {
+            arguments
+                .push(parse_typed_argument(value).map_err(|e| format!("Invalid --arg: {}", e))?);
         }
+    }
+    Ok(arguments)
+}
 
-        if matches!(self.vm.state, VMState::Fault) {
-            println!("Program faulted!");
-            return;
+fn parse_gas_limit(args: &[String]) -> Result<u64, String> {
+    for (i, arg) in args.iter().enumerate() {
+        if (arg == "--gas" || arg == "-g") && i + 1 < args.len() {
+            return args[i + 1]
+                .parse()
+                .map_err(|_| "Invalid gas limit value".to_string());
         }
+    }
+    Ok(1_000_000) // Default gas limit
+}
 
-        let ip = self.get_current_ip();
-        if ip < self.script.len() {
-            let op = self.script[ip];
-            let disasm = Disassembler::new(&self.script);
-            let (name, _) = disasm.decode_instruction(ip);
-            println!(
-                "→ 0x{:04X}: {:02X}  {}    [gas: {}]",
-                ip, op, name, self.vm.gas_consumed
-            );
-        }
+/// Value following `flag`, if present
+/// Storage fixture named by `--storage`, if given
+fn load_storage_fixture(args: &[String]) -> Result<Option<MemoryStorage>, String> {
+    let Some(path) = parse_option(args, "--storage") else {
+        return Ok(None);
+    };
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read storage file '{}': {}", path, e))?;
+    MemoryStorage::from_json(&json)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+fn write_trace(path: &str, trace: &ExecutionTrace) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(trace).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write trace file '{}': {}", path, e))
+}
+
+/// Debug info from `--debug-info <file>`, or the `.neodbg` file next to the
+/// script in `args[0]`
+fn load_debug_info(args: &[String]) -> Result<Option<DebugInfo>, String> {
+    match parse_option(args, "--debug-info") {
+        Some(path) => DebugInfo::load(Path::new(path)).map(Some),
+        None => DebugInfo::find_for(&args[0]),
     }
 }
 
+fn parse_option<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
 // ============================================================================
 // Inspector
 // ============================================================================
@@ -652,7 +1261,9 @@ impl<'a> Inspector<'a> {
 
         // Basic info
         output.push_str(&format!("  Size:         {} bytes\n", self.script.len()));
-        output.push_str(&format!("  Hash (hex):   {}\n", hex::encode(self.script)));
+        let script_hash = UInt160::from_script(self.script);
+        output.push_str(&format!("  Script hash:  {}\n", script_hash));
+        output.push_str(&format!("  Address:      {}\n", script_hash.to_address()));
 
         // Opcode statistics
         let stats = self.collect_opcode_stats();
@@ -686,6 +1297,40 @@ impl<'a> Inspector<'a> {
         output.push_str(&format!("    Minimum:    {}\n", estimated_gas.0));
         output.push_str(&format!("    Maximum:    {}\n", estimated_gas.1));
 
+        // Security lints
+        output.push_str("\n───────────────────────────────────────────────────────────────\n");
+        output.push_str("  SECURITY LINTS\n");
+        output.push_str("───────────────────────────────────────────────────────────────\n");
+        let lints = lint_script(self.script);
+        if lints.is_empty() {
+            output.push_str("    None\n");
+        }
+        for lint in &lints {
+            output.push_str(&format!(
+                "    0x{:04X}  {} {}: {}\n",
+                lint.offset,
+                lint.id.code(),
+                lint.id.name(),
+                lint.message
+            ));
+        }
+
+        // Gas golf
+        output.push_str("\n───────────────────────────────────────────────────────────────\n");
+        output.push_str("  GAS GOLF\n");
+        output.push_str("───────────────────────────────────────────────────────────────\n");
+        let suggestions = suggest_optimizations(self.script, GasSchedule::default());
+        if suggestions.is_empty() {
+            output.push_str("    None\n");
+        }
+        for suggestion in &suggestions {
+            output.push_str(&describe_suggestion(suggestion));
+            output.push('\n');
+        }
+        if !suggestions.is_empty() {
+            output.push_str("    Apply with --fix\n");
+        }
+
         // Disassembly
         output.push_str("\n───────────────────────────────────────────────────────────────\n");
         output.push_str("  DISASSEMBLY\n");
@@ -0,0 +1,216 @@
+//! Snapshots of test cases
+//!
+//! A test case with `snapshot = ["disassembly", "stack", "trace"]` (any of
+//! them) is checked against a text file under `snapshots/<file stem>/`
+//! next to its test file, holding the final state and gas followed by the
+//! chosen sections. `neo-zkvm test --bless` writes the files; without it a
+//! missing or different snapshot fails the case with a line diff.
+
+use neo_vm_core::{Disassembler, NeoVM};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Lines of context kept around each change in a diff
+const CONTEXT: usize = 2;
+
+/// Above this many cells the diff compares lines by position instead of
+/// aligning them
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Section of a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+    Disassembly,
+    /// Final evaluation stack, bottom first
+    Stack,
+    /// Offset, instruction, gas and stack hash of each executed step
+    Trace,
+}
+
+/// File holding the snapshot of case `name` from the test file `test_file`
+pub fn path(test_file: &Path, name: &str) -> PathBuf {
+    let stem = test_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    test_file
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("snapshots")
+        .join(stem)
+        .join(format!("{}.snap", slug))
+}
+
+/// Snapshot of `script` after `vm` finished running it; `vm` must have
+/// traced the run if `parts` has [`Part::Trace`]
+pub fn render(parts: &[Part], script: &[u8], vm: &NeoVM) -> String {
+    let mut text = format!("{:?}, gas {}\n", vm.state, vm.gas_consumed);
+    for part in parts {
+        match part {
+            Part::Disassembly => {
+                text.push_str("\n-- disassembly --\n");
+                text.push_str(&Disassembler::new(script).disassemble());
+            }
+            Part::Stack => {
+                text.push_str("\n-- stack --\n");
+                for item in &vm.eval_stack {
+                    text.push_str(&format!("{:?}\n", item));
+                }
+            }
+            Part::Trace => {
+                text.push_str("\n-- trace --\n");
+                let disassembler = Disassembler::new(script);
+                for step in &vm.trace.steps {
+                    let (name, _) = disassembler.decode_instruction(step.ip);
+                    text.push_str(&format!(
+                        "{:04X}:  {:<24} gas {:<8} stack {}\n",
+                        step.ip,
+                        name,
+                        step.gas_consumed,
+                        hex::encode(&step.stack_hash[..4])
+                    ));
+                }
+            }
+        }
+    }
+    text
+}
+
+/// `expected` and `actual` lines that differ, `-` for expected and `+` for
+/// actual, with a little context and `@@ line <n> @@` before each hunk
+pub fn diff(expected: &str, actual: &str) -> Vec<String> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    let ops = align(&old, &new);
+
+    // Line of the expected text each op sits at
+    let mut line = 1;
+    let mut lines_at = Vec::with_capacity(ops.len());
+    for op in &ops {
+        lines_at.push(line);
+        if !matches!(op, Line::Added(_)) {
+            line += 1;
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut shown_until = 0;
+    for (i, op) in ops.iter().enumerate() {
+        if matches!(op, Line::Same(_)) {
+            continue;
+        }
+        let from = i.saturating_sub(CONTEXT);
+        if lines.is_empty() || from > shown_until {
+            lines.push(format!("@@ line {} @@", lines_at[from]));
+        }
+        let to = (i + CONTEXT + 1).min(ops.len());
+        for op in &ops[from.max(shown_until)..to] {
+            lines.push(match op {
+                Line::Same(text) => format!("  {}", text),
+                Line::Removed(text) => format!("- {}", text),
+                Line::Added(text) => format!("+ {}", text),
+            });
+        }
+        shown_until = to;
+    }
+    lines
+}
+
+/// A line of an alignment of the expected and actual text
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Align the lines along a longest common subsequence
+fn align<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        let mut ops = Vec::new();
+        for i in 0..old.len().max(new.len()) {
+            match (old.get(i), new.get(i)) {
+                (Some(a), Some(b)) if a == b => ops.push(Line::Same(a)),
+                (a, b) => {
+                    ops.extend(a.copied().map(Line::Removed));
+                    ops.extend(b.copied().map(Line::Added));
+                }
+            }
+        }
+        return ops;
+    }
+
+    // lengths[i][j]: common subsequence length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len()
+            && (j == new.len() || lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            ops.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            ops.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_path() {
+        assert_eq!(
+            path(Path::new("tests/math.toml"), "Adds 2 + 3"),
+            Path::new("tests/snapshots/math/adds_2___3.snap")
+        );
+    }
+
+    #[test]
+    fn test_diff_shows_changed_lines_with_context() {
+        let expected = "Halt, gas 12\n\n-- stack --\nInteger(5)\n";
+        let actual = "Halt, gas 13\n\n-- stack --\nInteger(5)\nNull\n";
+        assert_eq!(
+            diff(expected, actual),
+            vec![
+                "@@ line 1 @@",
+                "- Halt, gas 12",
+                "+ Halt, gas 13",
+                "  ",
+                "  -- stack --",
+                "  Integer(5)",
+                "+ Null",
+            ]
+        );
+        assert!(diff(expected, expected).is_empty());
+    }
+}
@@ -0,0 +1,566 @@
+//! Declarative script tests
+//!
+//! `neo-zkvm test` runs TOML files of test cases, each a script with its
+//! arguments and initial storage and what running it must produce:
+//!
+//! ```toml
+//! [[test]]
+//! name = "adds its arguments"
+//! script = "9E40"              # hex, or a .neoasm, .bin or .nef file
+//! arguments = ["int:5", "int:3"]
+//! storage = "fixtures/pre.json"
+//!
+//! [test.expect]
+//! state = "halt"               # the default
+//! stack = ["int:8"]            # bottom first, like arguments
+//! gas = { min = 1, max = 100 }
+//! events = [{ name = "Added", state = "array:[8]" }]
+//! ```
+//!
+//! `asm` gives assembly inline instead of `script`. Arguments and stack
+//! items are typed values as taken by `--arg`; paths are relative to the
+//! test file. Expectations left out are not checked. `snapshot` compares
+//! the run against a stored snapshot, see [`crate::snapshot`].
+
+use neo_vm_core::{
+    BlockContext, GasSchedule, MemoryStorage, NeoVM, OracleResponses, RuntimeConfig, StackItem,
+    TrackedStorage,
+};
+use neo_vm_core::{VMLimits, VMState};
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::{NeoProver, ProofMode, ProverConfig};
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::arguments::parse_typed_argument;
+use crate::assembler::Assembler;
+use crate::snapshot::{self, Part};
+
+const DEFAULT_GAS_LIMIT: u64 = 1_000_000;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestFile {
+    #[serde(default)]
+    pub test: Vec<TestCase>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    pub name: String,
+    /// Hex bytecode, or a `.neoasm`, `.bin` or `.nef` file
+    pub script: Option<String>,
+    /// Assembly source, instead of `script`
+    pub asm: Option<String>,
+    /// Typed values pushed before the script runs, the last one on top
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    /// Storage fixture as written by `run --dump-storage`
+    pub storage: Option<String>,
+    pub gas_limit: Option<u64>,
+    #[serde(default)]
+    pub expect: Expectation,
+    /// Sections of the run to check against the case's snapshot file
+    #[serde(default)]
+    pub snapshot: Vec<Part>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    /// `halt` or `fault`, `halt` if not given
+    pub state: Option<String>,
+    /// Typed values, bottom of the stack first
+    pub stack: Option<Vec<String>>,
+    pub gas: Option<GasRange>,
+    /// Every notification sent, in order
+    pub events: Option<Vec<ExpectedEvent>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GasRange {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedEvent {
+    pub name: String,
+    /// Typed value of the event's arguments, not checked if left out
+    pub state: Option<String>,
+}
+
+/// How `neo-zkvm test` runs cases
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunOptions {
+    /// Also execute each case in SP1 execute mode
+    pub sp1: bool,
+    /// Write snapshots instead of comparing against them
+    pub bless: bool,
+}
+
+/// Result of one test case
+#[derive(Debug)]
+pub struct CaseResult {
+    pub name: String,
+    /// Why the case failed, empty if it passed
+    pub failures: Vec<String>,
+    /// Whether `--bless` wrote a new or changed snapshot
+    pub blessed: bool,
+    pub time: Duration,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Results of the cases in one test file
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: String,
+    pub cases: Vec<CaseResult>,
+}
+
+impl FileReport {
+    pub fn failed(&self) -> usize {
+        self.cases.iter().filter(|case| !case.passed()).count()
+    }
+
+    fn time(&self) -> Duration {
+        self.cases.iter().map(|case| case.time).sum()
+    }
+}
+
+/// Run every case in the test file at `path`; a file that cannot be read
+/// or parsed is reported as a single failing case named after it
+pub fn run_file(path: &Path, options: RunOptions) -> FileReport {
+    let display = path.display().to_string();
+    let cases = match load_file(path) {
+        Ok(file) => file
+            .test
+            .iter()
+            .map(|case| run_case(case, path, options))
+            .collect(),
+        Err(e) => vec![CaseResult {
+            name: display.clone(),
+            failures: vec![e],
+            blessed: false,
+            time: Duration::ZERO,
+        }],
+    };
+    FileReport {
+        path: display,
+        cases,
+    }
+}
+
+fn load_file(path: &Path) -> Result<TestFile, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read test file '{}': {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| format!("Invalid test file '{}': {}", path.display(), e))
+}
+
+/// Run one case of the test file at `file` on the host VM and, with
+/// `sp1`, in SP1 execute mode
+pub fn run_case(case: &TestCase, file: &Path, options: RunOptions) -> CaseResult {
+    let start = Instant::now();
+    let mut blessed = false;
+    let failures = check_case(case, file, options, &mut blessed).unwrap_or_else(|e| vec![e]);
+    CaseResult {
+        name: case.name.clone(),
+        failures,
+        blessed,
+        time: start.elapsed(),
+    }
+}
+
+fn check_case(
+    case: &TestCase,
+    file: &Path,
+    options: RunOptions,
+    blessed: &mut bool,
+) -> Result<Vec<String>, String> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let script = load_script(case, dir)?;
+    let arguments = parse_items(&case.arguments, "argument")?;
+    let storage = match &case.storage {
+        Some(path) => {
+            let path = dir.join(path);
+            let json = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read storage file '{}': {}", path.display(), e))?;
+            Some(MemoryStorage::from_json(&json).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+    let gas_limit = case.gas_limit.unwrap_or(DEFAULT_GAS_LIMIT);
+
+    let mut vm = NeoVM::new(gas_limit);
+    if let Some(storage) = &storage {
+        vm.storage = TrackedStorage::from(storage.clone());
+    }
+    vm.load_script(script.clone()).map_err(|e| e.to_string())?;
+    vm.eval_stack.extend(arguments.iter().cloned());
+    if case.snapshot.contains(&Part::Trace) {
+        vm.enable_tracing();
+    }
+    vm.run();
+
+    let mut failures = check_expectation(&case.expect, &vm, &script)?;
+    if !case.snapshot.is_empty() {
+        let actual = snapshot::render(&case.snapshot, &script, &vm);
+        let path = snapshot::path(file, &case.name);
+        let expected = fs::read_to_string(&path).ok();
+        if options.bless {
+            if expected.as_deref() != Some(actual.as_str()) {
+                write_snapshot(&path, &actual)?;
+                *blessed = true;
+            }
+        } else {
+            failures.extend(compare_snapshot(&path, expected.as_deref(), &actual));
+        }
+    }
+    if options.sp1 {
+        let input = ProofInput {
+            script,
+            arguments,
+            gas_limit,
+            storage: storage.map(MemoryStorage::into_entries).unwrap_or_default(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        failures.extend(check_sp1(input, &vm));
+    }
+    Ok(failures)
+}
+
+fn write_snapshot(path: &Path, text: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    fs::write(path, text)
+        .map_err(|e| format!("Failed to write snapshot '{}': {}", path.display(), e))
+}
+
+/// Failure for a snapshot at `path` that is missing or differs from `actual`
+fn compare_snapshot(path: &Path, expected: Option<&str>, actual: &str) -> Option<String> {
+    match expected {
+        None => Some(format!(
+            "snapshot {} is missing, run with --bless to write it",
+            path.display()
+        )),
+        Some(expected) if expected == actual => None,
+        Some(expected) => Some(format!(
+            "snapshot {} differs (- stored, + actual):\n    {}",
+            path.display(),
+            snapshot::diff(expected, actual).join("\n    ")
+        )),
+    }
+}
+
+fn load_script(case: &TestCase, dir: &Path) -> Result<Vec<u8>, String> {
+    match (&case.script, &case.asm) {
+        (Some(_), Some(_)) => Err("Give either script or asm, not both".to_string()),
+        (None, Some(source)) => Assembler::new().assemble(source),
+        (Some(script), None)
+            if [".neoasm", ".bin", ".nef"]
+                .iter()
+                .any(|e| script.ends_with(e)) =>
+        {
+            crate::watch::load_source(&dir.join(script).to_string_lossy())
+        }
+        (Some(script), None) => crate::parse_script(script),
+        (None, None) => Err("Missing script or asm".to_string()),
+    }
+}
+
+fn parse_items(values: &[String], what: &str) -> Result<Vec<StackItem>, String> {
+    values
+        .iter()
+        .map(|value| parse_typed_argument(value).map_err(|e| format!("Invalid {}: {}", what, e)))
+        .collect()
+}
+
+/// How the finished `vm` misses `expect`
+fn check_expectation(
+    expect: &Expectation,
+    vm: &NeoVM,
+    script: &[u8],
+) -> Result<Vec<String>, String> {
+    let mut failures = Vec::new();
+
+    let state = format!("{:?}", vm.state).to_lowercase();
+    let expected_state = expect.state.as_deref().unwrap_or("halt").to_lowercase();
+    if state != expected_state {
+        let mut failure = format!("state: expected {}, got {}", expected_state, state);
+        if let (VMState::Fault, Some(fault)) = (&vm.state, &vm.fault_info) {
+            failure.push_str(&format!(" ({})", crate::describe_fault(script, fault)));
+        }
+        failures.push(failure);
+    }
+
+    if let Some(stack) = &expect.stack {
+        let expected = parse_items(stack, "stack item")?;
+        if expected != vm.eval_stack {
+            failures.push(format!(
+                "stack: expected {:?}, got {:?}",
+                expected, vm.eval_stack
+            ));
+        }
+    }
+
+    if let Some(gas) = &expect.gas {
+        let used = vm.gas_consumed;
+        if gas.min.is_some_and(|min| used < min) || gas.max.is_some_and(|max| used > max) {
+            let bound = |b: Option<u64>| b.map_or("_".to_string(), |b| b.to_string());
+            failures.push(format!(
+                "gas: expected {}..={}, got {}",
+                bound(gas.min),
+                bound(gas.max),
+                used
+            ));
+        }
+    }
+
+    if let Some(events) = &expect.events {
+        let sent: Vec<&str> = vm
+            .notifications
+            .iter()
+            .map(|event| event.event_name.as_str())
+            .collect();
+        let names: Vec<&str> = events.iter().map(|event| event.name.as_str()).collect();
+        if sent != names {
+            failures.push(format!("events: expected {:?}, got {:?}", names, sent));
+        } else {
+            for (expected, event) in events.iter().zip(&vm.notifications) {
+                let Some(state) = &expected.state else {
+                    continue;
+                };
+                let state = parse_typed_argument(state)
+                    .map_err(|e| format!("Invalid event state: {}", e))?;
+                if state != event.state {
+                    failures.push(format!(
+                        "event {}: expected state {:?}, got {:?}",
+                        expected.name, state, event.state
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Run `input` in SP1 execute mode and check it agrees with the host `vm`
+fn check_sp1(input: ProofInput, vm: &NeoVM) -> Option<String> {
+    let config = match ProverConfig::builder()
+        .proof_mode(ProofMode::Execute)
+        .build()
+    {
+        Ok(config) => config,
+        Err(e) => return Some(format!("sp1: {}", e)),
+    };
+    let proof = match NeoProver::new(config).prove(input) {
+        Ok(proof) => proof,
+        Err(e) => return Some(format!("sp1: {}", e)),
+    };
+    let halted = matches!(vm.state, VMState::Halt);
+    if proof.public_inputs.execution_success != halted
+        || proof.public_inputs.gas_consumed != vm.gas_consumed
+    {
+        return Some(format!(
+            "sp1: guest {} with gas {}, host {:?} with gas {}",
+            if proof.public_inputs.execution_success {
+                "halted"
+            } else {
+                "faulted"
+            },
+            proof.public_inputs.gas_consumed,
+            vm.state,
+            vm.gas_consumed
+        ));
+    }
+    None
+}
+
+/// Print a line per case, the failures and a summary line; returns how
+/// many cases failed
+pub fn print_reports(reports: &[FileReport]) -> usize {
+    let tests: usize = reports.iter().map(|report| report.cases.len()).sum();
+    println!(
+        "running {} from {}",
+        count(tests, "test"),
+        count(reports.len(), "file")
+    );
+    for report in reports {
+        for case in &report.cases {
+            let status = match (case.passed(), case.blessed) {
+                (true, false) => "ok",
+                (true, true) => "ok (blessed)",
+                (false, _) => "FAILED",
+            };
+            println!("test {} :: {} ... {}", report.path, case.name, status);
+        }
+    }
+
+    let failed: usize = reports.iter().map(FileReport::failed).sum();
+    if failed > 0 {
+        println!("\nfailures:");
+        for report in reports {
+            for case in report.cases.iter().filter(|case| !case.passed()) {
+                println!("\n---- {} :: {} ----", report.path, case.name);
+                for failure in &case.failures {
+                    println!("  {}", failure);
+                }
+            }
+        }
+    }
+
+    let time: Duration = reports.iter().map(FileReport::time).sum();
+    println!(
+        "\ntest result: {}. {} passed; {} failed; finished in {:.2}s",
+        if failed == 0 { "ok" } else { "FAILED" },
+        tests - failed,
+        failed,
+        time.as_secs_f64()
+    );
+    failed
+}
+
+/// `n` followed by `noun`, plural unless `n` is one
+fn count(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {}", noun),
+        n => format!("{} {}s", n, noun),
+    }
+}
+
+/// JUnit XML of the reports, a test suite per file
+pub fn junit_xml(reports: &[FileReport]) -> String {
+    let tests: usize = reports.iter().map(|report| report.cases.len()).sum();
+    let failures: usize = reports.iter().map(FileReport::failed).sum();
+    let time: Duration = reports.iter().map(FileReport::time).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        tests,
+        failures,
+        time.as_secs_f64()
+    );
+    for report in reports {
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+            escape(&report.path),
+            report.cases.len(),
+            report.failed(),
+            report.time().as_secs_f64()
+        );
+        for case in &report.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape(&case.name),
+                escape(&report.path),
+                case.time.as_secs_f64()
+            );
+            match case.failures.first() {
+                None => xml.push_str("/>\n"),
+                Some(first) => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                        escape(first),
+                        escape(&case.failures.join("\n"))
+                    );
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        [[test]]
+        name = "adds its arguments"
+        script = "9E40"
+        arguments = ["int:5", "int:3"]
+
+        [test.expect]
+        stack = ["int:8"]
+        gas = { max = 100 }
+
+        [[test]]
+        name = "underflows"
+        asm = "ADD RET"
+
+        [test.expect]
+        state = "halt"
+        gas = { min = 1000 }
+    "#;
+
+    fn run(text: &str) -> Vec<CaseResult> {
+        let file: TestFile = toml::from_str(text).unwrap();
+        file.test
+            .iter()
+            .map(|case| run_case(case, Path::new("test.toml"), RunOptions::default()))
+            .collect()
+    }
+
+    #[test]
+    fn test_cases_pass_and_fail() {
+        let results = run(FILE);
+        assert!(results[0].passed(), "{:?}", results[0].failures);
+        assert_eq!(results[1].failures.len(), 2);
+        assert!(results[1].failures[0].starts_with("state: expected halt, got fault"));
+        assert!(results[1].failures[1].starts_with("gas: expected 1000..=_"));
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        let text = "[[test]]\nname = \"x\"\nasm = \"RET\"\n[test.expect]\nresult = 1";
+        assert!(toml::from_str::<TestFile>(text).is_err());
+    }
+
+    #[test]
+    fn test_junit_xml() {
+        let report = FileReport {
+            path: "tests/a.toml".to_string(),
+            cases: run(FILE),
+        };
+        let xml = junit_xml(&[report]);
+        assert!(xml.contains("<testsuites tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testcase name=\"adds its arguments\" classname=\"tests/a.toml\""));
+        assert!(xml.contains("<failure message=\"state: expected halt, got fault"));
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
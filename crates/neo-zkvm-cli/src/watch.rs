@@ -0,0 +1,236 @@
+//! Watch mode
+//!
+//! `neo-zkvm watch <source>` re-assembles and re-runs a script whenever its
+//! file changes and prints how the result moved: state, gas and the stack
+//! items that differ from the previous run.
+
+use neo_vm_core::{MemoryStorage, NeoVM, StackItem, TrackedStorage, VMState};
+use notify::{RecursiveMode, Watcher};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::assembler::Assembler;
+
+/// Events closer together than this count as one change, editors often
+/// write a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// What one run of the watched script ended with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub state: String,
+    pub gas: u64,
+    /// Stack items, top first
+    pub stack: Vec<String>,
+    /// Why the script did not assemble or run
+    pub error: Option<String>,
+}
+
+impl Outcome {
+    fn failed(error: String) -> Self {
+        Self {
+            state: "Error".to_string(),
+            gas: 0,
+            stack: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Script in `path`: assembled if it is a `.neoasm` file, otherwise read
+/// like any script argument
+pub fn load_source(path: &str) -> Result<Vec<u8>, String> {
+    if path.ends_with(".neoasm") {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+        Assembler::new().assemble(&source)
+    } else {
+        crate::parse_script(path)
+    }
+}
+
+/// Assemble and run the script in `path` on a fresh VM
+pub fn run_once(
+    path: &str,
+    gas_limit: u64,
+    arguments: &[StackItem],
+    storage: Option<&MemoryStorage>,
+) -> Outcome {
+    let script = match load_source(path) {
+        Ok(script) => script,
+        Err(e) => return Outcome::failed(e),
+    };
+    let mut vm = NeoVM::new(gas_limit);
+    if let Some(storage) = storage {
+        vm.storage = TrackedStorage::from(storage.clone());
+    }
+    if let Err(e) = vm.load_script(script.clone()) {
+        return Outcome::failed(e.to_string());
+    }
+    vm.eval_stack.extend(arguments.iter().cloned());
+    vm.run();
+
+    let error = match (&vm.state, &vm.fault_info) {
+        (VMState::Fault, Some(fault)) => Some(crate::describe_fault(&script, fault)),
+        _ => None,
+    };
+    Outcome {
+        state: format!("{:?}", vm.state),
+        gas: vm.gas_consumed,
+        stack: vm
+            .eval_stack
+            .iter()
+            .rev()
+            .map(|i| format!("{:?}", i))
+            .collect(),
+        error,
+    }
+}
+
+/// Lines summarising an outcome on its own
+pub fn render(outcome: &Outcome) -> Vec<String> {
+    let mut lines = vec![format!("{}, gas {}", outcome.state, outcome.gas)];
+    if let Some(error) = &outcome.error {
+        lines.push(format!("  {}", error));
+    }
+    for (i, item) in outcome.stack.iter().enumerate() {
+        lines.push(format!("  [{}] {}", i, item));
+    }
+    lines
+}
+
+/// Lines showing how `new` differs from `old`: the state, the gas and each
+/// stack slot (top first) that changed
+pub fn render_diff(old: &Outcome, new: &Outcome) -> Vec<String> {
+    if old == new {
+        return vec![format!(
+            "unchanged: {}, gas {}, stack depth {}",
+            new.state,
+            new.gas,
+            new.stack.len()
+        )];
+    }
+
+    let mut lines = Vec::new();
+    if old.state != new.state {
+        lines.push(format!("state {} → {}", old.state, new.state));
+    }
+    if let Some(error) = &new.error {
+        lines.push(format!("  {}", error));
+    }
+    if old.gas != new.gas {
+        let delta = new.gas as i128 - old.gas as i128;
+        lines.push(format!("gas {} → {} ({:+})", old.gas, new.gas, delta));
+    }
+    for i in 0..old.stack.len().max(new.stack.len()) {
+        let (before, after) = (old.stack.get(i), new.stack.get(i));
+        if before == after {
+            continue;
+        }
+        if let Some(item) = before {
+            lines.push(format!("- [{}] {}", i, item));
+        }
+        if let Some(item) = after {
+            lines.push(format!("+ [{}] {}", i, item));
+        }
+    }
+    lines
+}
+
+/// Call `on_change` each time the file at `path` is written, created or
+/// replaced, until the watcher fails
+pub fn watch_file(path: &Path, mut on_change: impl FnMut()) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("'{}' is not a file", path.display()))?
+        .to_owned();
+    // Watch the directory: editors that save by renaming a new file over
+    // the old one would otherwise end the watch
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("Failed to start watcher: {}", e))?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch '{}': {}", dir.display(), e))?;
+
+    let touches_file = |event: &notify::Result<notify::Event>| match event {
+        Ok(event) => {
+            !event.kind.is_access()
+                && event
+                    .paths
+                    .iter()
+                    .any(|p| p.file_name() == Some(name.as_os_str()))
+        }
+        Err(_) => false,
+    };
+
+    while let Ok(event) = rx.recv() {
+        if let Err(e) = &event {
+            eprintln!("Warning: {}", e);
+        }
+        if !touches_file(&event) {
+            continue;
+        }
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+        on_change();
+    }
+    Err("File watcher stopped".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(state: &str, gas: u64, stack: &[&str]) -> Outcome {
+        Outcome {
+            state: state.to_string(),
+            gas,
+            stack: stack.iter().map(|s| s.to_string()).collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_of_identical_runs() {
+        let run = outcome("Halt", 120, &["Integer(5)"]);
+        assert_eq!(
+            render_diff(&run, &run),
+            vec!["unchanged: Halt, gas 120, stack depth 1"]
+        );
+    }
+
+    #[test]
+    fn test_diff_shows_changed_slots() {
+        let old = outcome("Halt", 120, &["Integer(5)", "Integer(1)"]);
+        let new = outcome("Halt", 150, &["Integer(6)", "Integer(1)", "Null"]);
+        assert_eq!(
+            render_diff(&old, &new),
+            vec![
+                "gas 120 → 150 (+30)",
+                "- [0] Integer(5)",
+                "+ [0] Integer(6)",
+                "+ [2] Null",
+            ]
+        );
+
+        let fault = Outcome {
+            error: Some("Stack underflow at 0x0002".to_string()),
+            ..outcome("Fault", 90, &[])
+        };
+        assert_eq!(
+            render_diff(&new, &fault)[..3],
+            [
+                "state Halt → Fault",
+                "  Stack underflow at 0x0002",
+                "gas 150 → 90 (-60)"
+            ]
+        );
+    }
+}
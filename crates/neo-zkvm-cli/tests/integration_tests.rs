@@ -1,9 +1,17 @@
 //! Integration tests for Neo zkVM
 
-use neo_vm_core::StackItem;
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, StackItem, VMLimits};
 use neo_vm_guest::{execute, ProofInput};
-use neo_zkvm_prover::{NeoProver, ProverConfig};
-use neo_zkvm_verifier::verify;
+use neo_zkvm_prover::{NeoProof, NeoProver, ProverConfig};
+use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
+use std::process::Command;
+
+/// Without the SP1 toolchain the prover falls back to mock proofs
+fn verify(proof: &NeoProof) -> bool {
+    NeoVerifier::new()
+        .with_policy(VerifyPolicy::allow_mock_proofs())
+        .verify(proof)
+}
 
 #[test]
 fn test_full_prove_verify_cycle() {
@@ -18,10 +26,19 @@ fn test_full_prove_verify_cycle() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let proof = prover.prove(input).unwrap();
 
     assert_eq!(proof.output.state, 0);
     assert!(verify(&proof));
@@ -42,6 +59,15 @@ fn test_complex_arithmetic() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let output = execute(input);
@@ -62,6 +88,15 @@ fn test_comparison_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let output = execute(input);
@@ -76,8 +111,8 @@ fn test_comparison_operations() {
 fn test_prove_verify_with_arguments() {
     let script = vec![
         0x57, 0x00, 0x02, // INITSLOT 0 locals, 2 args
-        0x74, // LDARG0
-        0x75, // LDARG1
+        0x78, // LDARG0
+        0x79, // LDARG1
         0x9E, // ADD
         0x40, // RET
     ];
@@ -86,10 +121,19 @@ fn test_prove_verify_with_arguments() {
         script,
         arguments: vec![StackItem::Integer(10), StackItem::Integer(20)],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let proof = prover.prove(input).unwrap();
 
     assert_eq!(proof.output.state, 0);
     assert_eq!(proof.output.result, Some(StackItem::Integer(30)));
@@ -107,10 +151,19 @@ fn test_prove_verify_hash_operation() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let proof = prover.prove(input).unwrap();
 
     assert_eq!(proof.output.state, 0);
     assert!(verify(&proof));
@@ -129,10 +182,19 @@ fn test_prove_verify_array_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let proof = prover.prove(input).unwrap();
 
     assert_eq!(proof.output.state, 0);
     assert_eq!(proof.output.result, Some(StackItem::Integer(3)));
@@ -156,10 +218,19 @@ fn test_prove_verify_control_flow() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let proof = prover.prove(input).unwrap();
 
     assert_eq!(proof.output.state, 0);
     assert!(verify(&proof));
@@ -178,10 +249,21 @@ fn test_execute_faulted_script() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let output = execute(input);
     assert_eq!(output.state, 1); // Fault state
+    let fault = output.fault.unwrap();
+    assert_eq!((fault.ip, fault.opcode), (2, 0xA1));
 }
 
 #[test]
@@ -196,10 +278,19 @@ fn test_gas_tracking_in_proof() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     let prover = NeoProver::new(ProverConfig::default());
-    let proof = prover.prove(input);
+    let proof = prover.prove(input).unwrap();
 
     assert!(proof.output.gas_consumed > 0);
     assert!(proof.public_inputs.gas_consumed > 0);
@@ -216,6 +307,15 @@ fn test_script_size_limit() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault - script too large
@@ -228,6 +328,15 @@ fn test_stack_underflow_handling() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault - stack underflow
@@ -240,6 +349,15 @@ fn test_division_by_zero() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault - division by zero
@@ -252,6 +370,15 @@ fn test_gas_exhaustion() {
         script,
         arguments: vec![],
         gas_limit: 10, // Very low gas limit
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault - out of gas
@@ -267,6 +394,15 @@ fn test_pushdata_boundary() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0); // Should succeed
@@ -280,6 +416,15 @@ fn test_pushdata_truncated() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault - truncated data
@@ -293,6 +438,15 @@ fn test_loop_detection_by_gas() {
         script,
         arguments: vec![],
         gas_limit: 100,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     // Should either fault (out of gas) or halt after some iterations
@@ -311,6 +465,15 @@ fn test_control_flow_jump_valid() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -328,6 +491,15 @@ fn test_control_flow_abort() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault
@@ -345,6 +517,15 @@ fn test_control_flow_assert() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 1); // Should fault
@@ -365,6 +546,15 @@ fn test_control_flow_jump_backward() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -382,6 +572,15 @@ fn test_bitwise_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -400,6 +599,15 @@ fn test_shift_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -418,6 +626,15 @@ fn test_modulo_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -436,6 +653,15 @@ fn test_power_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -454,6 +680,15 @@ fn test_min_max_operations() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -473,6 +708,15 @@ fn test_within_range_check() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -500,6 +744,15 @@ fn test_native_crypto_sha256() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -517,6 +770,15 @@ fn test_native_crypto_ripemd160() {
         script,
         arguments: vec![],
         gas_limit: 1_000_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
     let output = execute(input);
     assert_eq!(output.state, 0);
@@ -526,3 +788,139 @@ fn test_native_crypto_ripemd160() {
         panic!("Expected ByteString result");
     }
 }
+
+/// Run `neo-zkvm` with `args`, returning whether it exited successfully
+/// and its standard output
+fn neo_zkvm(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_neo-zkvm"))
+        .args(args)
+        // Keep a user's config profile out of the run
+        .env(
+            "NEO_ZKVM_CONFIG",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/no-config.toml"),
+        )
+        .output()
+        .expect("failed to run neo-zkvm");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    (output.status.success(), stdout)
+}
+
+/// Run `neo-zkvm diff` on two hex scripts, returning whether it exited
+/// successfully and its report
+fn diff(a: &str, b: &str) -> (bool, String) {
+    neo_zkvm(&["diff", a, b])
+}
+
+#[test]
+fn test_diff_divergence_at_same_step() {
+    // PUSH2 PUSH3 ADD RET against PUSH2 PUSH3 MUL RET
+    let (ok, report) = diff("12139E40", "1213A040");
+    assert!(!ok);
+    assert!(report.contains("First divergence at step 2:"));
+    assert!(report.contains("A: 0x0002  ADD"));
+    assert!(report.contains("B: 0x0002  MUL"));
+    assert!(report.contains("[0] A: Integer(5)  B: Integer(6)"));
+    assert!(report.contains("Gas delta: +0 (B - A)"));
+}
+
+#[test]
+fn test_diff_traces_of_different_length() {
+    // JMP out of the script faults after one step; JMP +2 reaches RET
+    let (ok, report) = diff("226440", "220240");
+    assert!(!ok);
+    assert!(report.contains("Script A: Fault, gas 2, 1 steps"));
+    assert!(report.contains("Script B: Halt, gas 4, 2 steps"));
+    assert!(report.contains("First divergence at step 1:"));
+    assert!(report.contains("A: <ended>"));
+    assert!(report.contains("B: 0x0002  RET"));
+    assert!(report.contains("Gas delta: +2 (B - A)"));
+}
+
+#[test]
+fn test_diff_stack_differences() {
+    // Same steps, different pushed bytes
+    let (ok, report) = diff("0C0161", "0C0162");
+    assert!(!ok);
+    assert!(report.contains("Steps are identical, final states differ."));
+    assert!(report.contains("Final stack differences (top → bottom):"));
+    assert!(report.contains("[0] A: ByteString("));
+}
+
+#[test]
+fn test_diff_equivalent_scripts() {
+    // PUSH2 PUSH3 ADD RET computes what PUSH5 RET pushes, for more gas
+    let (ok, report) = diff("12139E40", "1540");
+    assert!(ok, "{report}");
+    assert!(report.contains("First divergence at step 0:"));
+    assert!(report.contains("Final stacks match."));
+    assert!(report.contains("Gas delta: -9 (B - A)"));
+
+    let (ok, report) = diff("1540", "1540");
+    assert!(ok, "{report}");
+    assert!(report.contains("Traces are identical."));
+    assert!(report.contains("Gas delta: +0 (B - A)"));
+}
+
+#[test]
+fn test_hash_known_script() {
+    // PUSH2 PUSH3 ADD RET
+    let (ok, output) = neo_zkvm(&["hash", "12139E40"]);
+    assert!(ok, "{output}");
+    assert!(output
+        .contains("SHA-256:     a2f75d4d5bf94361deebbd9a1ccc059da8eee9f23dc0d4e98762f5c33cdb02e9"));
+    // Hash160, RIPEMD-160 of the SHA-256, shown big-endian
+    assert!(output.contains("Script hash: 0xb9403b5565f84aa628ed4e87d37a8a83f6140030"));
+    assert!(output.contains("Address:     NQHmierEnoJGXKVBxtJrkvXPnuUizzjn6F"));
+
+    let (ok, output) = neo_zkvm(&["hash", "12139E40", "--json"]);
+    assert!(ok, "{output}");
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        json["sha256"],
+        "a2f75d4d5bf94361deebbd9a1ccc059da8eee9f23dc0d4e98762f5c33cdb02e9"
+    );
+    assert_eq!(
+        json["script_hash"],
+        "0xb9403b5565f84aa628ed4e87d37a8a83f6140030"
+    );
+    assert_eq!(json["address"], "NQHmierEnoJGXKVBxtJrkvXPnuUizzjn6F");
+}
+
+#[test]
+fn test_encode_args_matches_guest_encoding() {
+    use sha2::{Digest, Sha256};
+
+    let arguments = [
+        StackItem::Integer(5),
+        StackItem::ByteString(b"alice".to_vec().into()),
+    ];
+    let expected = neo_vm_guest::encode_arguments(&arguments);
+
+    // The same arguments as contract parameters and as --arg values
+    for args in [
+        &[
+            "encode-args",
+            r#"[{"type":"Integer","value":"5"},{"type":"String","value":"alice"}]"#,
+            "--json",
+        ][..],
+        &[
+            "encode-args",
+            "--arg",
+            "int:5",
+            "--arg",
+            "str:alice",
+            "--json",
+        ],
+    ] {
+        let (ok, output) = neo_zkvm(args);
+        assert!(ok, "{output}");
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["encoded"], hex::encode(&expected));
+        assert_eq!(json["sha256"], hex::encode(Sha256::digest(&expected)));
+    }
+
+    let (ok, output) = neo_zkvm(&["encode-args", "--arg", "int:5"]);
+    assert!(ok, "{output}");
+    let encoded = neo_vm_guest::encode_arguments(&[StackItem::Integer(5)]);
+    assert!(output.contains(&format!("Encoded: {}", hex::encode(encoded))));
+}
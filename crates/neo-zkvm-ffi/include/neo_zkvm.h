@@ -0,0 +1,75 @@
+/*
+ * Neo zkVM C API
+ *
+ * Link against libneo_zkvm built from crates/neo-zkvm-ffi. See the crate
+ * documentation for ownership rules: every handle, string and buffer
+ * returned by the library is released with the matching neozk_*_free.
+ */
+
+#ifndef NEO_ZKVM_H
+#define NEO_ZKVM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes */
+#define NEOZK_OK 0
+#define NEOZK_ERR_NULL_POINTER 1
+#define NEOZK_ERR_INVALID_ARGUMENT 2
+#define NEOZK_ERR_PROVE 3
+#define NEOZK_ERR_INTERNAL 4
+
+/* Proof modes for neozk_prove */
+#define NEOZK_MODE_EXECUTE 0
+#define NEOZK_MODE_MOCK 1
+#define NEOZK_MODE_SP1 2
+#define NEOZK_MODE_PLONK 3
+#define NEOZK_MODE_GROTH16 4
+
+/* Flags for neozk_verify */
+#define NEOZK_VERIFY_ALLOW_MOCK 1
+
+typedef struct NeozkResult NeozkResult;
+typedef struct NeozkProof NeozkProof;
+
+typedef struct NeozkBytes {
+    uint8_t *data;
+    size_t len;
+} NeozkBytes;
+
+/* Message of the last failed call on this thread, or NULL. Do not free. */
+const char *neozk_last_error(void);
+
+/* args_json: JSON array of Neo contract parameters, or NULL */
+int32_t neozk_run(const uint8_t *script, size_t script_len, const char *args_json,
+                  uint64_t gas_limit, NeozkResult **out);
+int32_t neozk_prove(const uint8_t *script, size_t script_len, const char *args_json,
+                    uint64_t gas_limit, int32_t mode, NeozkProof **out);
+/* 1 if valid, 0 otherwise */
+int32_t neozk_verify(const NeozkProof *proof, uint32_t flags);
+
+/* 0 halt, 1 fault */
+uint8_t neozk_result_state(const NeozkResult *result);
+uint64_t neozk_result_gas_consumed(const NeozkResult *result);
+char *neozk_result_json(const NeozkResult *result);
+void neozk_result_free(NeozkResult *result);
+
+int32_t neozk_proof_mode(const NeozkProof *proof);
+char *neozk_proof_output_json(const NeozkProof *proof);
+char *neozk_proof_public_inputs_json(const NeozkProof *proof);
+int32_t neozk_proof_to_bytes(const NeozkProof *proof, NeozkBytes *out);
+int32_t neozk_proof_from_bytes(const uint8_t *data, size_t len, NeozkProof **out);
+void neozk_proof_free(NeozkProof *proof);
+
+void neozk_string_free(char *s);
+void neozk_bytes_free(NeozkBytes bytes);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* NEO_ZKVM_H */
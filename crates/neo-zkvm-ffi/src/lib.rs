@@ -0,0 +1,424 @@
+//! C ABI for embedding Neo zkVM
+//!
+//! Builds `libneo_zkvm` as a shared library with the functions declared in
+//! `include/neo_zkvm.h`, for C, and for .NET through P/Invoke.
+//!
+//! ## Conventions
+//!
+//! - Functions that can fail return a `NEOZK_*` status code and write their
+//!   result through an out pointer. On failure [`neozk_last_error`] describes
+//!   the error.
+//! - Arguments are a JSON array of Neo contract parameters, e.g.
+//!   `[{"type":"Integer","value":"5"}]`, or `NULL` for none.
+//! - Results and proofs are opaque handles released with
+//!   [`neozk_result_free`] and [`neozk_proof_free`]. Strings returned by the
+//!   library are released with [`neozk_string_free`], byte buffers with
+//!   [`neozk_bytes_free`]. Memory allocated by the library must not be freed
+//!   with the caller's allocator.
+//! - JSON results use the format of [`neo_vm_core::json`].
+//! - Panics are caught at the boundary and reported as
+//!   [`NEOZK_ERR_INTERNAL`].
+
+use neo_vm_core::contract_parameter::parse_arguments;
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
+use neo_vm_guest::{ProofInput, ProofOutput};
+use neo_zkvm_prover::{EncodeOptions, NeoProof, NeoProver, ProofMode, ProverConfig};
+use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+pub const NEOZK_OK: i32 = 0;
+/// A required pointer argument was null
+pub const NEOZK_ERR_NULL_POINTER: i32 = 1;
+/// An argument was malformed: arguments JSON, proof mode or proof bytes
+pub const NEOZK_ERR_INVALID_ARGUMENT: i32 = 2;
+/// Proof generation failed
+pub const NEOZK_ERR_PROVE: i32 = 3;
+/// The library panicked
+pub const NEOZK_ERR_INTERNAL: i32 = 4;
+
+/// Accept mock proofs in [`neozk_verify`]; for tests only
+pub const NEOZK_VERIFY_ALLOW_MOCK: u32 = 1;
+
+/// Execution result returned by [`neozk_run`]
+pub struct NeozkResult(ProofOutput);
+
+/// Proof returned by [`neozk_prove`] or [`neozk_proof_from_bytes`]
+pub struct NeozkProof(NeoProof);
+
+/// Byte buffer owned by the library
+#[repr(C)]
+pub struct NeozkBytes {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+struct FfiError(i32, String);
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning errors and panics into a status code
+fn guard(f: impl FnOnce() -> Result<(), FfiError>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => {
+            LAST_ERROR.with(|e| *e.borrow_mut() = None);
+            NEOZK_OK
+        }
+        Ok(Err(FfiError(code, message))) => {
+            set_last_error(message);
+            code
+        }
+        Err(_) => {
+            set_last_error("panic in neo-zkvm".to_string());
+            NEOZK_ERR_INTERNAL
+        }
+    }
+}
+
+fn null_pointer(name: &str) -> FfiError {
+    FfiError(NEOZK_ERR_NULL_POINTER, format!("{name} is null"))
+}
+
+fn invalid(message: impl Into<String>) -> FfiError {
+    FfiError(NEOZK_ERR_INVALID_ARGUMENT, message.into())
+}
+
+/// Copy `len` bytes from `data`, which may be null when `len` is zero
+unsafe fn read_bytes(data: *const u8, len: usize, name: &str) -> Result<Vec<u8>, FfiError> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if data.is_null() {
+        return Err(null_pointer(name));
+    }
+    Ok(std::slice::from_raw_parts(data, len).to_vec())
+}
+
+unsafe fn read_input(
+    script: *const u8,
+    script_len: usize,
+    args_json: *const c_char,
+    gas_limit: u64,
+) -> Result<ProofInput, FfiError> {
+    let script = read_bytes(script, script_len, "script")?;
+    let arguments = if args_json.is_null() {
+        Vec::new()
+    } else {
+        let json = CStr::from_ptr(args_json)
+            .to_str()
+            .map_err(|_| invalid("arguments are not UTF-8"))?;
+        parse_arguments(json).map_err(|e| invalid(e.to_string()))?
+    };
+    Ok(ProofInput {
+        script,
+        arguments,
+        gas_limit,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
+    })
+}
+
+fn proof_mode(mode: i32) -> Result<ProofMode, FfiError> {
+    Ok(match mode {
+        0 => ProofMode::Execute,
+        1 => ProofMode::Mock,
+        2 => ProofMode::Sp1,
+        3 => ProofMode::Plonk,
+        4 => ProofMode::Groth16,
+        _ => return Err(invalid(format!("unknown proof mode {mode}"))),
+    })
+}
+
+fn proof_mode_code(mode: ProofMode) -> i32 {
+    match mode {
+        ProofMode::Execute => 0,
+        ProofMode::Mock => 1,
+        ProofMode::Sp1 => 2,
+        ProofMode::Plonk => 3,
+        ProofMode::Groth16 => 4,
+    }
+}
+
+/// JSON as a library-owned C string, or null if it cannot be encoded
+fn json_string(value: &impl serde::Serialize) -> *mut c_char {
+    serde_json::to_string(value)
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Message of the last failed call on this thread, or null
+///
+/// The pointer stays valid until the next call into the library on the same
+/// thread; do not free it.
+#[no_mangle]
+pub extern "C" fn neozk_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Execute a script without proving it
+///
+/// # Safety
+///
+/// `script` must point to `script_len` readable bytes, `args_json` must be
+/// null or a NUL-terminated string, and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_run(
+    script: *const u8,
+    script_len: usize,
+    args_json: *const c_char,
+    gas_limit: u64,
+    out: *mut *mut NeozkResult,
+) -> i32 {
+    guard(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let input = read_input(script, script_len, args_json, gas_limit)?;
+        let output = neo_vm_guest::execute(input);
+        *out = Box::into_raw(Box::new(NeozkResult(output)));
+        Ok(())
+    })
+}
+
+/// Execute a script and prove the execution
+///
+/// `mode` is 0 for execute only, 1 mock, 2 SP1 compressed, 3 PLONK and
+/// 4 Groth16. SP1 modes fall back to a mock proof when the guest program
+/// was not built.
+///
+/// # Safety
+///
+/// As for [`neozk_run`].
+#[no_mangle]
+pub unsafe extern "C" fn neozk_prove(
+    script: *const u8,
+    script_len: usize,
+    args_json: *const c_char,
+    gas_limit: u64,
+    mode: i32,
+    out: *mut *mut NeozkProof,
+) -> i32 {
+    guard(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let input = read_input(script, script_len, args_json, gas_limit)?;
+        let config = ProverConfig::builder()
+            .proof_mode(proof_mode(mode)?)
+            .build()
+            .map_err(|e| invalid(e.to_string()))?;
+        let proof = NeoProver::new(config)
+            .prove(input)
+            .map_err(|e| FfiError(NEOZK_ERR_PROVE, e.to_string()))?;
+        *out = Box::into_raw(Box::new(NeozkProof(proof)));
+        Ok(())
+    })
+}
+
+/// Verify a proof, returning 1 if it is valid and 0 otherwise
+///
+/// Mock proofs are rejected unless `flags` contains
+/// [`NEOZK_VERIFY_ALLOW_MOCK`]. On rejection [`neozk_last_error`] gives the
+/// reason.
+///
+/// # Safety
+///
+/// `proof` must be null or a live handle from this library.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_verify(proof: *const NeozkProof, flags: u32) -> i32 {
+    let mut valid = false;
+    let status = guard(|| {
+        let proof = proof.as_ref().ok_or_else(|| null_pointer("proof"))?;
+        let mut verifier = NeoVerifier::new();
+        if flags & NEOZK_VERIFY_ALLOW_MOCK != 0 {
+            verifier = verifier.with_policy(VerifyPolicy::allow_mock_proofs());
+        }
+        let result = verifier.verify_detailed(&proof.0);
+        valid = result.valid;
+        match result.error {
+            Some(error) => Err(invalid(error)),
+            None => Ok(()),
+        }
+    });
+    (status == NEOZK_OK && valid) as i32
+}
+
+/// VM state of the run: 0 halt, 1 fault
+///
+/// # Safety
+///
+/// `result` must be a live handle from [`neozk_run`].
+#[no_mangle]
+pub unsafe extern "C" fn neozk_result_state(result: *const NeozkResult) -> u8 {
+    (*result).0.state
+}
+
+/// Gas consumed by the run
+///
+/// # Safety
+///
+/// `result` must be a live handle from [`neozk_run`].
+#[no_mangle]
+pub unsafe extern "C" fn neozk_result_gas_consumed(result: *const NeozkResult) -> u64 {
+    (*result).0.gas_consumed
+}
+
+/// The run's output as JSON, to be freed with [`neozk_string_free`]
+///
+/// # Safety
+///
+/// `result` must be a live handle from [`neozk_run`].
+#[no_mangle]
+pub unsafe extern "C" fn neozk_result_json(result: *const NeozkResult) -> *mut c_char {
+    json_string(&(*result).0)
+}
+
+/// Release a result; null is ignored
+///
+/// # Safety
+///
+/// `result` must be null or a handle from [`neozk_run`] not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_result_free(result: *mut NeozkResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// Proof mode actually used, in the numbering of [`neozk_prove`]
+///
+/// # Safety
+///
+/// `proof` must be a live proof handle.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_proof_mode(proof: *const NeozkProof) -> i32 {
+    proof_mode_code((*proof).0.proof_mode)
+}
+
+/// The proven execution output as JSON, to be freed with
+/// [`neozk_string_free`]
+///
+/// # Safety
+///
+/// `proof` must be a live proof handle.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_proof_output_json(proof: *const NeozkProof) -> *mut c_char {
+    json_string(&(*proof).0.output)
+}
+
+/// The proof's public inputs as JSON, to be freed with [`neozk_string_free`]
+///
+/// # Safety
+///
+/// `proof` must be a live proof handle.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_proof_public_inputs_json(proof: *const NeozkProof) -> *mut c_char {
+    json_string(&(*proof).0.public_inputs)
+}
+
+/// Encode a proof in the versioned wire format
+///
+/// # Safety
+///
+/// `proof` must be a live proof handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_proof_to_bytes(
+    proof: *const NeozkProof,
+    out: *mut NeozkBytes,
+) -> i32 {
+    guard(|| {
+        let proof = proof.as_ref().ok_or_else(|| null_pointer("proof"))?;
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let bytes = proof
+            .0
+            .to_bytes(EncodeOptions::default())
+            .map_err(|e| invalid(e.to_string()))?
+            .into_boxed_slice();
+        let len = bytes.len();
+        *out = NeozkBytes {
+            data: Box::into_raw(bytes).cast(),
+            len,
+        };
+        Ok(())
+    })
+}
+
+/// Decode a proof written by [`neozk_proof_to_bytes`] or any
+/// `NeoProof::to_bytes` encoding
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_proof_from_bytes(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut NeozkProof,
+) -> i32 {
+    guard(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let bytes = read_bytes(data, len, "data")?;
+        let proof = NeoProof::from_bytes(&bytes).map_err(|e| invalid(e.to_string()))?;
+        *out = Box::into_raw(Box::new(NeozkProof(proof)));
+        Ok(())
+    })
+}
+
+/// Release a proof; null is ignored
+///
+/// # Safety
+///
+/// `proof` must be null or a proof handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_proof_free(proof: *mut NeozkProof) {
+    if !proof.is_null() {
+        drop(Box::from_raw(proof));
+    }
+}
+
+/// Release a string returned by the library; null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or a string from this library not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Release a byte buffer returned by the library; empty buffers are ignored
+///
+/// # Safety
+///
+/// `bytes` must come from this library and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn neozk_bytes_free(bytes: NeozkBytes) {
+    if !bytes.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            bytes.data, bytes.len,
+        )));
+    }
+}
@@ -0,0 +1,149 @@
+//! C ABI tests, calling the exported functions as a C caller would
+
+use neo_zkvm::*;
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+const ADD: [u8; 4] = [0x12, 0x13, 0x9E, 0x40]; // PUSH2 PUSH3 ADD RET
+
+unsafe fn take_string(s: *mut std::ffi::c_char) -> String {
+    assert!(!s.is_null());
+    let text = CStr::from_ptr(s).to_str().unwrap().to_string();
+    neozk_string_free(s);
+    text
+}
+
+unsafe fn last_error() -> String {
+    CStr::from_ptr(neozk_last_error())
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_run() {
+    unsafe {
+        let mut result = ptr::null_mut();
+        assert_eq!(
+            neozk_run(ADD.as_ptr(), ADD.len(), ptr::null(), 1_000_000, &mut result),
+            NEOZK_OK
+        );
+        assert_eq!(neozk_result_state(result), 0);
+        assert!(neozk_result_gas_consumed(result) > 0);
+        let json: serde_json::Value =
+            serde_json::from_str(&take_string(neozk_result_json(result))).unwrap();
+        assert_eq!(
+            json["result"],
+            serde_json::json!({"type": "Integer", "value": "5"})
+        );
+        neozk_result_free(result);
+    }
+}
+
+#[test]
+fn test_run_with_arguments() {
+    // ADD RET over two contract parameter arguments
+    let script = [0x9E, 0x40];
+    let args = CString::new(r#"[{"type":"Integer","value":"40"},{"type":"Integer","value":"2"}]"#)
+        .unwrap();
+    unsafe {
+        let mut result = ptr::null_mut();
+        assert_eq!(
+            neozk_run(
+                script.as_ptr(),
+                script.len(),
+                args.as_ptr(),
+                1_000_000,
+                &mut result
+            ),
+            NEOZK_OK
+        );
+        let json = take_string(neozk_result_json(result));
+        assert!(json.contains(r#""value":"42""#), "{json}");
+        neozk_result_free(result);
+    }
+}
+
+#[test]
+fn test_errors() {
+    let bad_args = CString::new("[{\"type\":\"Integer\"}]").unwrap();
+    unsafe {
+        let mut result = ptr::null_mut();
+        assert_eq!(
+            neozk_run(ptr::null(), 4, ptr::null(), 1_000_000, &mut result),
+            NEOZK_ERR_NULL_POINTER
+        );
+        assert_eq!(last_error(), "script is null");
+
+        assert_eq!(
+            neozk_run(
+                ADD.as_ptr(),
+                ADD.len(),
+                bad_args.as_ptr(),
+                1_000_000,
+                &mut result
+            ),
+            NEOZK_ERR_INVALID_ARGUMENT
+        );
+        assert!(last_error().contains("Invalid contract parameter"));
+
+        let mut proof = ptr::null_mut();
+        assert_eq!(
+            neozk_prove(
+                ADD.as_ptr(),
+                ADD.len(),
+                ptr::null(),
+                1_000_000,
+                9,
+                &mut proof
+            ),
+            NEOZK_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            neozk_proof_from_bytes(b"NZKP\x09".as_ptr(), 5, &mut proof),
+            NEOZK_ERR_INVALID_ARGUMENT
+        );
+        assert!(proof.is_null());
+    }
+}
+
+#[test]
+fn test_prove_verify_round_trip() {
+    unsafe {
+        let mut proof = ptr::null_mut();
+        assert_eq!(
+            neozk_prove(
+                ADD.as_ptr(),
+                ADD.len(),
+                ptr::null(),
+                1_000_000,
+                1,
+                &mut proof
+            ),
+            NEOZK_OK
+        );
+        assert_eq!(neozk_proof_mode(proof), 1);
+        assert_eq!(neozk_verify(proof, NEOZK_VERIFY_ALLOW_MOCK), 1);
+        assert_eq!(neozk_verify(proof, 0), 0);
+        assert!(last_error().contains("rejected by policy"));
+
+        let inputs: serde_json::Value =
+            serde_json::from_str(&take_string(neozk_proof_public_inputs_json(proof))).unwrap();
+        assert_eq!(inputs["execution_success"], true);
+
+        let mut bytes = NeozkBytes {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(neozk_proof_to_bytes(proof, &mut bytes), NEOZK_OK);
+        let mut decoded = ptr::null_mut();
+        assert_eq!(
+            neozk_proof_from_bytes(bytes.data, bytes.len, &mut decoded),
+            NEOZK_OK
+        );
+        assert_eq!(neozk_verify(decoded, NEOZK_VERIFY_ALLOW_MOCK), 1);
+
+        neozk_bytes_free(bytes);
+        neozk_proof_free(decoded);
+        neozk_proof_free(proof);
+    }
+}
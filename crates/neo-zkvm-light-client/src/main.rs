@@ -0,0 +1,29 @@
+//! Neo zkVM Light-Client Guest Program
+//!
+//! Verifies a chain of Neo block headers starting from a trusted header and
+//! commits the resulting `LightClientOutput`. An invalid chain panics, so no
+//! proof can be produced for it.
+
+// No main for zkVM - SP1 provides the entrypoint
+#![cfg_attr(target_os = "zkvm", no_main)]
+
+#[cfg(target_os = "zkvm")]
+sp1_zkvm::entrypoint!(zkvm_main);
+
+/// Main entry point for SP1 zkVM
+#[cfg(target_os = "zkvm")]
+pub fn zkvm_main() {
+    use neo_vm_guest::light_client::{verify_header_chain, LightClientInput};
+
+    let input: LightClientInput = sp1_zkvm::io::read();
+    let output = verify_header_chain(&input).expect("invalid header chain");
+    sp1_zkvm::io::commit(&output);
+}
+
+/// Main function for non-zkVM targets
+#[cfg(not(target_os = "zkvm"))]
+fn main() {
+    eprintln!("Error: This program must be run in the SP1 zkVM environment.");
+    eprintln!("For local testing, use neo_vm_guest::light_client directly.");
+    std::process::exit(1);
+}
@@ -0,0 +1,15 @@
+//! Build script generating the gas price tables from neo-vm-core's
+//! `opcodes.toml`, so the guest charges exactly what `GasSchedule` does
+
+// Only the price tables are generated here
+#[allow(dead_code)]
+#[path = "../neo-vm-core/build/opcode_tables.rs"]
+mod opcode_tables;
+
+use opcode_tables::{write_out, OpcodeTable};
+
+fn main() {
+    println!("cargo:rerun-if-changed=../neo-vm-core/build/opcode_tables.rs");
+    let table = OpcodeTable::load("../neo-vm-core/opcodes.toml".as_ref());
+    write_out("gas_prices.rs", &table.gas_prices());
+}
@@ -10,7 +10,12 @@
 #[cfg(target_os = "zkvm")]
 sp1_zkvm::entrypoint!(zkvm_main);
 
+#[cfg(feature = "full")]
+use k256::ecdsa::signature::Verifier;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 
 /// Input for zkVM proving
 #[derive(Serialize, Deserialize, Clone)]
@@ -18,6 +23,206 @@ pub struct GuestInput {
     pub script: Vec<u8>,
     pub arguments: Vec<StackItem>,
     pub gas_limit: u64,
+    pub storage: Vec<(Vec<u8>, Vec<u8>)>,
+    pub tx_hash: [u8; 32],
+    pub context: [u8; 32],
+    /// `neo_vm_core::GasSchedule::id`
+    pub gas_schedule: u8,
+    pub network: u32,
+    /// `neo_vm_core::TriggerType::id`
+    pub trigger: u8,
+    pub random_seed: [u8; 32],
+    /// Payload `CheckSig` verifies signatures against; its SHA-256 must be
+    /// `tx_hash`
+    pub sign_data: Option<Vec<u8>>,
+    /// Root the storage witnesses are proven against; when set, `storage`
+    /// must be empty and only witnessed keys can be touched
+    pub pre_state_root: Option<[u8; 32]>,
+    pub storage_witnesses: Vec<StorageWitness>,
+    pub limits: GuestLimits,
+    /// `neo_vm_core::BlockContext`, reported by `System.Runtime.GetTime`
+    pub block_index: u32,
+    pub timestamp: u64,
+    /// `neo_vm_core::OracleResponses` as `(url, filter, response)`
+    pub oracle_responses: Vec<(String, String, Vec<u8>)>,
+}
+
+/// `neo_vm_core::VMLimits` with its sizes saturated to `u32`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuestLimits {
+    pub max_stack: u32,
+    pub max_invocation_depth: u32,
+    pub max_script_size: u32,
+    pub max_item_size: u32,
+    pub max_trace_steps: u64,
+}
+
+impl Default for GuestLimits {
+    fn default() -> Self {
+        Self {
+            max_stack: 2048,
+            max_invocation_depth: 1024,
+            max_script_size: 1024 * 1024,
+            max_item_size: u16::MAX as u32 * 2,
+            max_trace_steps: u64::MAX,
+        }
+    }
+}
+
+impl GuestLimits {
+    /// `neo_vm_core::VMLimits::encode`
+    fn encode(&self) -> [u8; 24] {
+        let mut out = [0u8; 24];
+        let sizes = [
+            self.max_stack,
+            self.max_invocation_depth,
+            self.max_script_size,
+            self.max_item_size,
+        ];
+        for (chunk, size) in out.chunks_exact_mut(4).zip(sizes) {
+            chunk.copy_from_slice(&size.to_le_bytes());
+        }
+        out[16..].copy_from_slice(&self.max_trace_steps.to_le_bytes());
+        out
+    }
+}
+
+/// `neo_vm_core::TrieProof` for a full storage key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageWitness {
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+    /// Encoded trie nodes from the root along the key's path
+    pub nodes: Vec<Vec<u8>>,
+}
+
+type WitnessedStorage = (Vec<(Vec<u8>, Vec<u8>)>, BTreeSet<Vec<u8>>);
+
+const TRIE_LEAF: u8 = 0x00;
+const TRIE_EXTENSION: u8 = 0x01;
+const TRIE_BRANCH: u8 = 0x02;
+const EMPTY_TRIE_ROOT: [u8; 32] = [0u8; 32];
+
+/// Reads from an encoded trie node
+struct NodeReader<'a>(&'a [u8]);
+
+impl<'a> NodeReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn path(&mut self) -> Option<&'a [u8]> {
+        let len = self.take(2)?;
+        self.take(u16::from_le_bytes([len[0], len[1]]) as usize)
+    }
+
+    fn value(&mut self) -> Option<&'a [u8]> {
+        let len = self.take(4)?;
+        self.take(u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+    }
+
+    fn hash(&mut self) -> Option<[u8; 32]> {
+        self.take(32)?.try_into().ok()
+    }
+}
+
+impl StorageWitness {
+    /// Check the witness against `root`, as `TrieProof::verify`
+    fn verify(&self, root: [u8; 32]) -> bool {
+        self.walk(root).unwrap_or(false)
+    }
+
+    fn walk(&self, root: [u8; 32]) -> Option<bool> {
+        let path: Vec<u8> = self.key.iter().flat_map(|b| [b >> 4, b & 0x0F]).collect();
+        let mut remaining = path.as_slice();
+        let mut expected = root;
+
+        for encoded in &self.nodes {
+            if expected == EMPTY_TRIE_ROOT || <[u8; 32]>::from(Sha256::digest(encoded)) != expected
+            {
+                return Some(false);
+            }
+            let mut reader = NodeReader(encoded);
+            let found = match *reader.take(1)?.first()? {
+                TRIE_LEAF => {
+                    let leaf = reader.path()?;
+                    let value = reader.value()?;
+                    (leaf == remaining).then_some(value)
+                }
+                TRIE_EXTENSION => {
+                    let ext = reader.path()?;
+                    let child = reader.hash()?;
+                    match remaining.strip_prefix(ext) {
+                        Some(rest) => {
+                            remaining = rest;
+                            expected = child;
+                            continue;
+                        }
+                        None => None,
+                    }
+                }
+                TRIE_BRANCH => {
+                    let mut children = [[0u8; 32]; 16];
+                    for child in children.iter_mut() {
+                        *child = reader.hash()?;
+                    }
+                    let value = match reader.take(1)?[0] {
+                        0 => None,
+                        _ => Some(reader.value()?),
+                    };
+                    match remaining.split_first() {
+                        None => value,
+                        Some((&nibble, rest)) => {
+                            expected = *children.get(nibble as usize)?;
+                            remaining = rest;
+                            if expected == EMPTY_TRIE_ROOT {
+                                None
+                            } else {
+                                continue;
+                            }
+                        }
+                    }
+                }
+                _ => return Some(false),
+            };
+            return Some(found == self.value.as_deref());
+        }
+
+        // Only an empty trie ends without reaching a terminal node
+        Some(self.nodes.is_empty() && root == EMPTY_TRIE_ROOT && self.value.is_none())
+    }
+}
+
+/// Initial storage and the keys a script may touch, proven by the storage
+/// witnesses; `Ok(None)` when the input carries its full storage instead
+fn witnessed_storage(input: &GuestInput) -> Result<Option<WitnessedStorage>, ()> {
+    let Some(root) = input.pre_state_root else {
+        if !input.storage_witnesses.is_empty() {
+            return Err(());
+        }
+        return Ok(None);
+    };
+    if !input.storage.is_empty() {
+        return Err(());
+    }
+    let mut entries = Vec::new();
+    let mut keys = BTreeSet::new();
+    for witness in &input.storage_witnesses {
+        if !witness.verify(root) {
+            return Err(());
+        }
+        if keys.insert(witness.key.clone()) {
+            if let Some(value) = &witness.value {
+                entries.push((witness.key.clone(), value.clone()));
+            }
+        }
+    }
+    Ok(Some((entries, keys)))
 }
 
 /// Stack item types matching Neo VM
@@ -28,6 +233,8 @@ pub enum StackItem {
     Integer(i128),
     ByteString(Vec<u8>),
     Array(Vec<StackItem>),
+    /// Entries in insertion order, encoded in that order like
+    /// `neo_vm_core::StackItem::Map`
     Map(Vec<(StackItem, StackItem)>),
     Struct(Vec<StackItem>),
 }
@@ -43,19 +250,53 @@ impl StackItem {
         }
     }
 
+    /// Same conversion as `neo_vm_core::StackItem::to_integer`
     fn to_integer(&self) -> Option<i128> {
         match self {
             StackItem::Integer(i) => Some(*i),
             StackItem::Boolean(b) => Some(*b as i128),
-            StackItem::ByteString(b) if b.len() <= 16 => {
-                let mut arr = [0u8; 16];
-                arr[..b.len()].copy_from_slice(b);
-                Some(i128::from_le_bytes(arr))
-            }
             _ => None,
         }
     }
 
+    /// Canonical encoding, identical to `neo_vm_core::StackItem::write_canonical`
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        match self {
+            StackItem::Null => out.push(0x00),
+            StackItem::Boolean(b) => {
+                out.push(0x20);
+                out.push(*b as u8);
+            }
+            StackItem::Integer(i) => {
+                out.push(0x21);
+                out.extend_from_slice(&i.to_le_bytes());
+            }
+            StackItem::ByteString(b) => {
+                out.push(0x28);
+                out.extend_from_slice(&(b.len() as u32).to_le_bytes());
+                out.extend_from_slice(b);
+            }
+            StackItem::Array(items) => Self::write_items(out, 0x40, items),
+            StackItem::Struct(items) => Self::write_items(out, 0x41, items),
+            StackItem::Map(entries) => {
+                out.push(0x48);
+                out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+                for (key, value) in entries {
+                    key.write_canonical(out);
+                    value.write_canonical(out);
+                }
+            }
+        }
+    }
+
+    fn write_items(out: &mut Vec<u8>, tag: u8, items: &[StackItem]) {
+        out.push(tag);
+        out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+        for item in items {
+            item.write_canonical(out);
+        }
+    }
+
     fn to_bytes(&self) -> Vec<u8> {
         match self {
             StackItem::ByteString(b) => b.clone(),
@@ -74,6 +315,17 @@ pub struct PublicValues {
     pub output_hash: [u8; 32],
     pub gas_consumed: u64,
     pub execution_success: bool,
+    pub write_set_hash: [u8; 32],
+    pub tx_hash: [u8; 32],
+    pub context: [u8; 32],
+    pub gas_schedule: u8,
+    pub network: u32,
+    pub trigger: u8,
+    pub random_seed: [u8; 32],
+    pub random_draws: u32,
+    pub block_index: u32,
+    pub timestamp: u64,
+    pub oracle_hash: [u8; 32],
 }
 
 /// VM execution state
@@ -85,16 +337,77 @@ enum VMState {
 }
 
 /// Execution context for call stack
+///
+/// Script bytes are shared between contexts so calls don't copy the script.
 struct ExecutionContext {
-    script: Vec<u8>,
+    script: Arc<[u8]>,
     ip: usize,
 }
 
-/// Default maximum stack depth
-const MAX_STACK_DEPTH: usize = 2048;
+/// Runtime syscall ids, matching neo-vm-core
+const SYSTEM_RUNTIME_LOG: u32 = 0x01;
+const SYSTEM_RUNTIME_NOTIFY: u32 = 0x02;
+const SYSTEM_RUNTIME_GETTIME: u32 = 0x03;
+const SYSTEM_RUNTIME_GETTRIGGER: u32 = 0x04;
+const SYSTEM_RUNTIME_GETPLATFORM: u32 = 0x05;
+const SYSTEM_RUNTIME_GETNETWORK: u32 = 0x06;
+const SYSTEM_RUNTIME_GETRANDOM: u32 = 0x07;
+const SYSTEM_RUNTIME_GETINVOCATIONCOUNTER: u32 = 0x08;
+const SYSTEM_RUNTIME_BURNGAS: u32 = 0x09;
+
+/// Longest `System.Runtime.Notify` event name, as `neo_vm_core::MAX_EVENT_NAME`
+const MAX_EVENT_NAME: usize = 32;
+
+/// Storage syscall ids, matching neo-vm-core
+const SYSTEM_STORAGE_GET: u32 = 0x10;
+const SYSTEM_STORAGE_PUT: u32 = 0x11;
+const SYSTEM_STORAGE_DELETE: u32 = 0x12;
 
-/// Default maximum invocation depth  
-const MAX_INVOCATION_DEPTH: usize = 1024;
+/// Signature syscall ids, matching neo-vm-core
+const SYSTEM_CRYPTO_CHECKSIG: u32 = 0x20;
+const SYSTEM_CRYPTO_CHECKMULTISIG: u32 = 0x21;
+
+/// Contract syscall id, matching neo-vm-core; the guest runs a single script,
+/// so only the Ledger and Oracle natives can be called
+const SYSTEM_CONTRACT_CALL: u32 = 0x30;
+
+/// `neo_vm_core::LedgerContract` hash
+const LEDGER_HASH: [u8; 20] = [
+    0xda, 0x65, 0xb6, 0x00, 0xf7, 0x12, 0x4c, 0xe6, 0xc7, 0x99, 0x50, 0xc1, 0x77, 0x2a, 0x36, 0x40,
+    0x31, 0x04, 0xf2, 0xbe,
+];
+
+/// `neo_vm_core::OracleContract` hash
+const ORACLE_HASH: [u8; 20] = [
+    0xfe, 0x92, 0x4b, 0x7c, 0xfe, 0x89, 0xdd, 0xd2, 0x71, 0xab, 0xaf, 0x72, 0x10, 0xa8, 0x0a, 0x7e,
+    0x11, 0x17, 0x87, 0x58,
+];
+
+/// Number of `neo_vm_core::OpCategory` variants
+const GAS_CATEGORIES: usize = 12;
+
+/// Index of `neo_vm_core::OpCategory::of_opcode(op)`, or of
+/// `OpCategory::of_syscall` for a `SYSCALL` to `syscall`
+fn gas_category(op: u8, syscall: Option<u32>) -> usize {
+    match (op, syscall) {
+        (0x41, Some(SYSTEM_STORAGE_GET | SYSTEM_STORAGE_PUT | SYSTEM_STORAGE_DELETE)) => 10,
+        (0x41, Some(SYSTEM_CRYPTO_CHECKSIG | SYSTEM_CRYPTO_CHECKMULTISIG)) => 9,
+        (0x41, _) => 11,
+        (0x00..=0x20, _) => 0,
+        (0x21..=0x42 | 0xE0..=0xEF, _) => 1,
+        (0x43..=0x55, _) => 2,
+        (0x56..=0x87, _) => 3,
+        (0x88..=0x8F, _) => 4,
+        (0x90..=0x98, _) => 5,
+        (0x99..=0xBD, _) => 6,
+        (0xBE..=0xD7, _) => 7,
+        (0xD8..=0xDF, _) => 8,
+        (0xF0..=0xFF, _) => 9,
+    }
+}
+
+/// Script hash of the storage context scripts run in
+const STORAGE_SCRIPT_HASH: [u8; 20] = [0u8; 20];
 
 /// Neo VM implementation for zkVM guest
 struct NeoVM {
@@ -103,28 +416,165 @@ struct NeoVM {
     invocation_stack: Vec<ExecutionContext>,
     gas_consumed: u64,
     gas_limit: u64,
+    gas_schedule: GasSchedule,
+    /// `gas_consumed` per category, in `neo_vm_core::OpCategory::ALL` order
+    gas_breakdown: [u64; GAS_CATEGORIES],
+    /// Category the running instruction's gas is charged to
+    gas_category: usize,
+    /// Storage keyed by script hash followed by the key
+    storage: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Value of each written key before its first write
+    original: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    /// Keys covered by storage witnesses, the only ones the script may
+    /// touch; `None` when the input carries its full storage
+    witnessed: Option<BTreeSet<Vec<u8>>>,
+    /// Message signed by the script container, checked by the signature
+    /// syscalls. Unset for proven scripts, like `neo_vm_guest::execute`.
+    sign_data: Option<Vec<u8>>,
+    runtime: Runtime,
+    /// Responses the Oracle native serves, as `GuestInput::oracle_responses`
+    oracle_responses: Vec<(String, String, Vec<u8>)>,
+    /// `System.Runtime.GetRandom` calls made so far
+    random_draws: u32,
+    limits: GuestLimits,
+    /// Instructions executed, checked against `limits.max_trace_steps`
+    steps: u64,
 }
 
-/// Gas cost lookup table
-const GAS_COSTS: [u16; 256] = [
-    // 0x00-0x0F
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 0x10-0x1F (PUSH0-PUSH16)
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 0x20-0x2F
-    1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x30-0x3F
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x40-0x4F
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x50-0x5F
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x60-0x6F
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x70-0x7F
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x80-0x8F
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0x90-0x9F
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xA0-0xAF (arithmetic)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xB0-0xBF (comparison)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xC0-0xCF (compound types)
-    8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, // 0xD0-0xDF
-    8, 8, 8, 8, 8, 8, 8, 8, 2, 2, 2, 2, 2, 2, 2, 2, // 0xE0-0xEF
-    2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, // 0xF0-0xFF (crypto)
-    512, 512, 512, 32768, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-];
+/// What the runtime syscalls report, as `neo_vm_core::RuntimeConfig`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Runtime {
+    network: u32,
+    trigger: u8,
+    random_seed: [u8; 32],
+    /// Block index the Ledger native reports
+    block_index: u32,
+    /// Block timestamp in milliseconds
+    timestamp: u64,
+}
+
+impl Runtime {
+    /// Triggers `neo_vm_core::TriggerType` defines
+    fn is_valid_trigger(trigger: u8) -> bool {
+        matches!(trigger, 0x01 | 0x02 | 0x20 | 0x40)
+    }
+
+    /// Same derivation as `neo_vm_core::RuntimeConfig::random`
+    fn random(&self, index: u32) -> i128 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"neo-zkvm/GetRandom/v1");
+        hasher.update(self.network.to_le_bytes());
+        hasher.update(self.random_seed);
+        hasher.update(index.to_le_bytes());
+        let digest = hasher.finalize();
+        let mut low = [0u8; 16];
+        low.copy_from_slice(&digest[..16]);
+        (u128::from_le_bytes(low) >> 1) as i128
+    }
+}
+
+/// Prices charged for opcodes and syscalls, matching
+/// `neo_vm_core::GasSchedule`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GasSchedule {
+    Legacy,
+    NeoN3,
+}
+
+impl GasSchedule {
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(GasSchedule::Legacy),
+            1 => Some(GasSchedule::NeoN3),
+            _ => None,
+        }
+    }
+
+    fn opcode_price(self, op: u8) -> u64 {
+        match self {
+            GasSchedule::Legacy => LEGACY_OPCODE_PRICES[op as usize] as u64,
+            GasSchedule::NeoN3 => NEO_N3_OPCODE_PRICES[op as usize] as u64 * EXEC_FEE_FACTOR,
+        }
+    }
+
+    fn syscall_price(self, id: u32) -> u64 {
+        match (self, id) {
+            (GasSchedule::Legacy, SYSTEM_CRYPTO_CHECKSIG) => self.opcode_price(0xF3),
+            (GasSchedule::Legacy, _) => 0,
+            (
+                GasSchedule::NeoN3,
+                SYSTEM_RUNTIME_GETTIME
+                | SYSTEM_RUNTIME_GETTRIGGER
+                | SYSTEM_RUNTIME_GETPLATFORM
+                | SYSTEM_RUNTIME_GETNETWORK,
+            ) => (1 << 3) * EXEC_FEE_FACTOR,
+            (GasSchedule::NeoN3, SYSTEM_RUNTIME_GETINVOCATIONCOUNTER | SYSTEM_RUNTIME_BURNGAS) => {
+                (1 << 4) * EXEC_FEE_FACTOR
+            }
+            (GasSchedule::NeoN3, SYSTEM_RUNTIME_GETRANDOM) => (1 << 13) * EXEC_FEE_FACTOR,
+            (
+                GasSchedule::NeoN3,
+                SYSTEM_RUNTIME_LOG
+                | SYSTEM_RUNTIME_NOTIFY
+                | SYSTEM_STORAGE_GET
+                | SYSTEM_STORAGE_PUT
+                | SYSTEM_STORAGE_DELETE
+                | SYSTEM_CRYPTO_CHECKSIG
+                | SYSTEM_CONTRACT_CALL,
+            ) => (1 << 15) * EXEC_FEE_FACTOR,
+            (GasSchedule::NeoN3, _) => 0,
+        }
+    }
+
+    /// Charge for the keys `System.Crypto.CheckMultisig` walks, one
+    /// `CheckSig` each
+    fn check_multisig_price(self, keys: usize) -> u64 {
+        self.syscall_price(SYSTEM_CRYPTO_CHECKSIG) * keys as u64
+    }
+
+    /// Charge for the `len` bytes `op` pushes or hashes
+    fn data_price(self, op: u8, len: usize) -> u64 {
+        match self {
+            GasSchedule::Legacy => {
+                let per_word = match op {
+                    0x0C..=0x0E | 0x88 | 0x89 | 0x8B..=0x8E | 0xDB => 1,
+                    0xF0 | 0xF1 | 0xF3 => 8,
+                    0xF2 => 16,
+                    _ => 0,
+                };
+                per_word * len.div_ceil(32) as u64
+            }
+            GasSchedule::NeoN3 => 0,
+        }
+    }
+
+    fn storage_put_price(self, key_len: usize, value_len: usize, old_len: Option<usize>) -> u64 {
+        match self {
+            GasSchedule::Legacy => (key_len + value_len) as u64,
+            GasSchedule::NeoN3 => {
+                let new_bytes = match old_len {
+                    None => key_len + value_len,
+                    Some(_) if value_len == 0 => 0,
+                    Some(old) if value_len <= old => (value_len - 1) / 4 + 1,
+                    Some(0) => value_len,
+                    Some(old) => (old - 1) / 4 + 1 + value_len - old,
+                };
+                new_bytes as u64 * STORAGE_PRICE
+            }
+        }
+    }
+}
+
+/// Neo N3's default execution fee factor, datoshi per fee unit
+const EXEC_FEE_FACTOR: u64 = 30;
+
+/// Neo N3's default storage price, datoshi per stored byte
+const STORAGE_PRICE: u64 = 100_000;
+
+// `LEGACY_OPCODE_PRICES` and `NEO_N3_OPCODE_PRICES`, generated by the
+// build script from the `opcodes.toml` that `neo_vm_core::GasSchedule`'s
+// tables also come from
+include!(concat!(env!("OUT_DIR"), "/gas_prices.rs"));
 
 impl NeoVM {
     fn new(gas_limit: u64) -> Self {
@@ -134,27 +584,51 @@ impl NeoVM {
             invocation_stack: Vec::with_capacity(8),
             gas_consumed: 0,
             gas_limit,
+            gas_schedule: GasSchedule::Legacy,
+            gas_breakdown: [0; GAS_CATEGORIES],
+            gas_category: 0,
+            storage: BTreeMap::new(),
+            original: BTreeMap::new(),
+            witnessed: None,
+            sign_data: None,
+            runtime: Runtime {
+                network: 860_833_102,
+                trigger: 0x40,
+                random_seed: [0; 32],
+                block_index: 0,
+                timestamp: 0,
+            },
+            oracle_responses: Vec::new(),
+            random_draws: 0,
+            limits: GuestLimits::default(),
+            steps: 0,
         }
     }
 
-    /// Push item to eval stack with depth checking
+    /// Push item to eval stack with depth and item size checking
     fn push(&mut self, item: StackItem) -> Result<(), &'static str> {
-        if self.eval_stack.len() >= MAX_STACK_DEPTH {
+        if self.eval_stack.len() >= self.limits.max_stack as usize {
             return Err("Stack overflow");
         }
+        if matches!(&item, StackItem::ByteString(b) if b.len() > self.limits.max_item_size as usize)
+        {
+            return Err("Item too large");
+        }
         self.eval_stack.push(item);
         Ok(())
     }
 
     fn load_script(&mut self, script: Vec<u8>) -> Result<(), &'static str> {
-        if script.len() > 1024 * 1024 {
+        if script.len() > self.limits.max_script_size as usize {
             return Err("Script too large");
         }
-        if self.invocation_stack.len() >= MAX_INVOCATION_DEPTH {
+        if self.invocation_stack.len() >= self.limits.max_invocation_depth as usize {
             return Err("Invocation depth exceeded");
         }
-        self.invocation_stack
-            .push(ExecutionContext { script, ip: 0 });
+        self.invocation_stack.push(ExecutionContext {
+            script: Arc::from(script),
+            ip: 0,
+        });
         Ok(())
     }
 
@@ -169,16 +643,31 @@ impl NeoVM {
         let op = ctx.script[ctx.ip];
         ctx.ip += 1;
 
+        if self.steps >= self.limits.max_trace_steps {
+            return Err("Step limit exceeded");
+        }
+        self.steps += 1;
+
         // Gas metering
-        let gas_cost = GAS_COSTS[op as usize] as u64;
-        self.gas_consumed = self.gas_consumed.saturating_add(gas_cost);
+        let id = ctx
+            .script
+            .get(ctx.ip..ctx.ip + 4)
+            .map(|id| u32::from_le_bytes([id[0], id[1], id[2], id[3]]));
+        self.gas_category = gas_category(op, id);
+        self.charge(self.gas_schedule.opcode_price(op))?;
 
+        self.execute_op(op)
+    }
+
+    fn charge(&mut self, gas: u64) -> Result<(), &'static str> {
+        self.gas_consumed = self.gas_consumed.saturating_add(gas);
+        let total = &mut self.gas_breakdown[self.gas_category];
+        *total = total.saturating_add(gas);
         if self.gas_consumed > self.gas_limit {
             self.state = VMState::Fault;
             return Err("Out of gas");
         }
-
-        self.execute_op(op)
+        Ok(())
     }
 
     fn execute_op(&mut self, op: u8) -> Result<(), &'static str> {
@@ -186,10 +675,10 @@ impl NeoVM {
             // PUSH0-PUSH16
             0x10..=0x20 => {
                 let n = (op - 0x10) as i128;
-                self.eval_stack.push(StackItem::Integer(n));
+                self.push(StackItem::Integer(n))?;
             }
-            0x0F => self.eval_stack.push(StackItem::Integer(-1)),
-            0x0B => self.eval_stack.push(StackItem::Null),
+            0x0F => self.push(StackItem::Integer(-1))?,
+            0x0B => self.push(StackItem::Null)?,
 
             // Constants with operands
             0x00 => {
@@ -197,23 +686,37 @@ impl NeoVM {
                 let ctx = self.invocation_stack.last_mut().ok_or("Stack underflow")?;
                 let val = ctx.script[ctx.ip] as i8 as i128;
                 ctx.ip += 1;
-                self.eval_stack.push(StackItem::Integer(val));
+                self.push(StackItem::Integer(val))?;
             }
             0x01 => {
                 // PUSHINT16
                 let ctx = self.invocation_stack.last_mut().ok_or("Stack underflow")?;
                 let val = i16::from_le_bytes([ctx.script[ctx.ip], ctx.script[ctx.ip + 1]]) as i128;
                 ctx.ip += 2;
-                self.eval_stack.push(StackItem::Integer(val));
+                self.push(StackItem::Integer(val))?;
+            }
+            0x02..=0x05 => {
+                // PUSHINT32, PUSHINT64, PUSHINT128, PUSHINT256
+                let len = 4 << (op - 0x02);
+                let ctx = self.invocation_stack.last_mut().ok_or("Stack underflow")?;
+                let operand = ctx
+                    .script
+                    .get(ctx.ip..ctx.ip + len)
+                    .ok_or("Script truncated")?;
+                let val = int_operand(operand)?;
+                ctx.ip += len;
+                self.push(StackItem::Integer(val))?;
             }
             0x0C => {
                 // PUSHDATA1
                 let ctx = self.invocation_stack.last_mut().ok_or("Stack underflow")?;
                 let len = ctx.script[ctx.ip] as usize;
                 ctx.ip += 1;
+                self.charge(self.gas_schedule.data_price(op, len))?;
+                let ctx = self.invocation_stack.last_mut().ok_or("Stack underflow")?;
                 let data = ctx.script[ctx.ip..ctx.ip + len].to_vec();
                 ctx.ip += len;
-                self.eval_stack.push(StackItem::ByteString(data));
+                self.push(StackItem::ByteString(data))?;
             }
 
             // Stack operations
@@ -224,7 +727,7 @@ impl NeoVM {
             0x4A => {
                 // DUP
                 let item = self.eval_stack.last().ok_or("Stack underflow")?.clone();
-                self.eval_stack.push(item);
+                self.push(item)?;
             }
 
             // Arithmetic
@@ -233,21 +736,21 @@ impl NeoVM {
                 let b = self.pop_int()?;
                 let a = self.pop_int()?;
                 let result = a.checked_add(b).ok_or("Overflow")?;
-                self.eval_stack.push(StackItem::Integer(result));
+                self.push(StackItem::Integer(result))?;
             }
             0x9F => {
                 // SUB
                 let b = self.pop_int()?;
                 let a = self.pop_int()?;
                 let result = a.checked_sub(b).ok_or("Underflow")?;
-                self.eval_stack.push(StackItem::Integer(result));
+                self.push(StackItem::Integer(result))?;
             }
             0xA0 => {
                 // MUL
                 let b = self.pop_int()?;
                 let a = self.pop_int()?;
                 let result = a.checked_mul(b).ok_or("Overflow")?;
-                self.eval_stack.push(StackItem::Integer(result));
+                self.push(StackItem::Integer(result))?;
             }
             0xA1 => {
                 // DIV
@@ -257,21 +760,199 @@ impl NeoVM {
                     return Err("Division by zero");
                 }
                 let result = a.checked_div(b).ok_or("Division error")?;
-                self.eval_stack.push(StackItem::Integer(result));
+                self.push(StackItem::Integer(result))?;
+            }
+            0xA2 => {
+                // MOD
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                if b == 0 {
+                    return Err("Division by zero");
+                }
+                let result = a.checked_rem(b).ok_or("Division error")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0xA3 => {
+                // POW
+                let exp = self.pop_int()?;
+                let base = self.pop_int()?;
+                let exp = u32::try_from(exp).map_err(|_| "Invalid exponent")?;
+                let result = base.checked_pow(exp).ok_or("Overflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0xA4 => {
+                // SQRT
+                let a = self.pop_int()?;
+                let result = a
+                    .checked_isqrt()
+                    .ok_or("Square root of a negative number")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0xA5 => {
+                // MODMUL
+                let modulus = self.pop_int()?;
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                if modulus == 0 {
+                    return Err("Division by zero");
+                }
+                let result = a
+                    .checked_mul(b)
+                    .and_then(|product| product.checked_rem(modulus))
+                    .ok_or("Overflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0xA6 => {
+                // MODPOW, or the modular inverse for an exponent of -1
+                let modulus = self.pop_int()?;
+                let exp = self.pop_int()?;
+                let base = self.pop_int()?;
+                if modulus == 0 {
+                    return Err("Division by zero");
+                }
+                let result = if exp == -1 {
+                    mod_inverse(base, modulus)
+                } else {
+                    mod_pow(base, exp, modulus)
+                };
+                self.push(StackItem::Integer(
+                    result.ok_or("Invalid modular operation")?,
+                ))?;
+            }
+            0xA8 => {
+                // SHL
+                let shift = self.pop_int()?;
+                let value = self.pop_int()?;
+                if !(0..=256).contains(&shift) {
+                    return Err("Invalid shift");
+                }
+                let result = value
+                    .checked_shl(shift as u32)
+                    .filter(|result| result >> shift as u32 == value)
+                    .ok_or("Overflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0xA9 => {
+                // SHR
+                let shift = self.pop_int()?;
+                let value = self.pop_int()?;
+                if !(0..=256).contains(&shift) {
+                    return Err("Invalid shift");
+                }
+                let result = value.checked_shr(shift as u32).ok_or("Invalid shift")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0x99 => {
+                // SIGN
+                let a = self.pop_int()?;
+                self.push(StackItem::Integer(a.signum()))?;
+            }
+            0x9A => {
+                // ABS
+                let a = self.pop_int()?;
+                let result = a.checked_abs().ok_or("Overflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0x9B => {
+                // NEGATE
+                let a = self.pop_int()?;
+                let result = a.checked_neg().ok_or("Overflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0x9C => {
+                // INC
+                let a = self.pop_int()?;
+                let result = a.checked_add(1).ok_or("Overflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0x9D => {
+                // DEC
+                let a = self.pop_int()?;
+                let result = a.checked_sub(1).ok_or("Underflow")?;
+                self.push(StackItem::Integer(result))?;
+            }
+            0xB9 => {
+                // MIN
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                self.push(StackItem::Integer(a.min(b)))?;
+            }
+            0xBA => {
+                // MAX
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                self.push(StackItem::Integer(a.max(b)))?;
+            }
+            0xBB => {
+                // WITHIN (a <= x < b)
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                let x = self.pop_int()?;
+                self.push(StackItem::Boolean(a <= x && x < b))?;
+            }
+
+            // Logic
+            0xAA => {
+                // NOT
+                let a = self.eval_stack.pop().ok_or("Stack underflow")?;
+                self.push(StackItem::Boolean(!a.to_bool()))?;
+            }
+            0xAB => {
+                // BOOLAND
+                let b = self.eval_stack.pop().ok_or("Stack underflow")?;
+                let a = self.eval_stack.pop().ok_or("Stack underflow")?;
+                self.eval_stack
+                    .push(StackItem::Boolean(a.to_bool() && b.to_bool()));
+            }
+            0xAC => {
+                // BOOLOR
+                let b = self.eval_stack.pop().ok_or("Stack underflow")?;
+                let a = self.eval_stack.pop().ok_or("Stack underflow")?;
+                self.eval_stack
+                    .push(StackItem::Boolean(a.to_bool() || b.to_bool()));
+            }
+            0xB1 => {
+                // NZ
+                let a = self.pop_int()?;
+                self.push(StackItem::Boolean(a != 0))?;
             }
 
             // Comparison
+            0xB3 => {
+                // NUMEQUAL
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                self.push(StackItem::Boolean(a == b))?;
+            }
+            0xB4 => {
+                // NUMNOTEQUAL
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                self.push(StackItem::Boolean(a != b))?;
+            }
             0xB5 => {
                 // LT
                 let b = self.pop_int()?;
                 let a = self.pop_int()?;
-                self.eval_stack.push(StackItem::Boolean(a < b));
+                self.push(StackItem::Boolean(a < b))?;
+            }
+            0xB6 => {
+                // LE
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                self.push(StackItem::Boolean(a <= b))?;
+            }
+            0xB7 => {
+                // GT
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                self.push(StackItem::Boolean(a > b))?;
             }
             0xB8 => {
                 // GE
                 let b = self.pop_int()?;
                 let a = self.pop_int()?;
-                self.eval_stack.push(StackItem::Boolean(a >= b));
+                self.push(StackItem::Boolean(a >= b))?;
             }
 
             // Flow control
@@ -294,21 +975,63 @@ impl NeoVM {
                 }
             }
 
+            #[cfg(feature = "full")]
+            0x41 => {
+                // SYSCALL
+                let ctx = self.invocation_stack.last_mut().ok_or("Stack underflow")?;
+                let id_bytes = ctx
+                    .script
+                    .get(ctx.ip..ctx.ip + 4)
+                    .ok_or("Script truncated")?;
+                let id = u32::from_le_bytes([id_bytes[0], id_bytes[1], id_bytes[2], id_bytes[3]]);
+                ctx.ip += 4;
+                self.charge(self.gas_schedule.syscall_price(id))?;
+                self.execute_syscall(id)?;
+            }
+
             // Crypto - use SP1 precompiles when available
             #[cfg(target_os = "zkvm")]
             0xF0 => {
                 // SHA256 - use SP1 precompile for better performance
                 let data = self.eval_stack.pop().ok_or("Stack underflow")?;
                 let bytes = data.to_bytes();
+                self.charge(self.gas_schedule.data_price(op, bytes.len()))?;
                 let result = sp1_zkvm::precompiles::sha256::sha256(&bytes);
-                self.eval_stack.push(StackItem::ByteString(result.to_vec()));
+                self.push(StackItem::ByteString(result.to_vec()))?;
             }
             #[cfg(not(target_os = "zkvm"))]
             0xF0 => {
                 // SHA256 - fallback implementation for testing
-                let data = self.eval_stack.pop().ok_or("Stack underflow")?;
-                let result = sha256_hash(&data.to_bytes());
-                self.eval_stack.push(StackItem::ByteString(result.to_vec()));
+                let bytes = self.eval_stack.pop().ok_or("Stack underflow")?.to_bytes();
+                self.charge(self.gas_schedule.data_price(op, bytes.len()))?;
+                let result = sha256_hash(&bytes);
+                self.push(StackItem::ByteString(result.to_vec()))?;
+            }
+
+            #[cfg(feature = "full")]
+            0xF3 => {
+                // CHECKSIG over a message from the stack
+                let pubkey = self.eval_stack.pop().ok_or("Stack underflow")?;
+                let signature = self.eval_stack.pop().ok_or("Stack underflow")?;
+                let message = self.eval_stack.pop().ok_or("Stack underflow")?;
+                let (pubkey, signature, message) = match (pubkey, signature, message) {
+                    (
+                        StackItem::ByteString(pubkey),
+                        StackItem::ByteString(signature),
+                        StackItem::ByteString(message),
+                    ) => (pubkey, signature, message),
+                    _ => return Err("Invalid type"),
+                };
+                self.charge(self.gas_schedule.data_price(op, message.len()))?;
+                let digest = Sha256::digest(&message);
+                let verified = verify_signature(&pubkey, &signature, &digest)?;
+                self.push(StackItem::Boolean(verified))?;
+            }
+
+            #[cfg(not(feature = "full"))]
+            0x41 | 0xF3 => {
+                self.state = VMState::Fault;
+                return Err("Not supported by the arithmetic program");
             }
 
             _ => {
@@ -319,6 +1042,232 @@ impl NeoVM {
         Ok(())
     }
 
+    #[cfg(feature = "full")]
+    fn execute_syscall(&mut self, id: u32) -> Result<(), &'static str> {
+        match id {
+            // Logs and notifications are not public values; the guest only
+            // pops and checks their arguments like the host
+            SYSTEM_RUNTIME_LOG => {
+                self.eval_stack.pop().ok_or("Stack underflow")?;
+                Ok(())
+            }
+            SYSTEM_RUNTIME_NOTIFY => {
+                let name = self.pop_bytes()?;
+                if name.len() > MAX_EVENT_NAME || std::str::from_utf8(&name).is_err() {
+                    return Err("Invalid operation");
+                }
+                match self.eval_stack.pop().ok_or("Stack underflow")? {
+                    StackItem::Array(_) => Ok(()),
+                    _ => Err("Invalid type"),
+                }
+            }
+            SYSTEM_RUNTIME_GETTRIGGER => {
+                self.push(StackItem::Integer(self.runtime.trigger as i128))
+            }
+            SYSTEM_RUNTIME_GETTIME => self.push(StackItem::Integer(self.runtime.timestamp as i128)),
+            SYSTEM_RUNTIME_GETPLATFORM => self.push(StackItem::ByteString(b"NEO".to_vec())),
+            SYSTEM_RUNTIME_GETNETWORK => {
+                self.push(StackItem::Integer(self.runtime.network as i128))
+            }
+            SYSTEM_RUNTIME_GETRANDOM => {
+                let value = self.runtime.random(self.random_draws);
+                self.random_draws = self
+                    .random_draws
+                    .checked_add(1)
+                    .ok_or("Invalid operation")?;
+                self.push(StackItem::Integer(value))
+            }
+            // Scripts run once, and native calls are not invocations
+            SYSTEM_RUNTIME_GETINVOCATIONCOUNTER => self.push(StackItem::Integer(1)),
+            SYSTEM_RUNTIME_BURNGAS => {
+                let datoshi = self
+                    .eval_stack
+                    .pop()
+                    .ok_or("Stack underflow")?
+                    .to_integer()
+                    .ok_or("Invalid type")?;
+                if datoshi <= 0 {
+                    return Err("Invalid operation");
+                }
+                self.charge(u64::try_from(datoshi).unwrap_or(u64::MAX))
+            }
+            SYSTEM_STORAGE_GET => {
+                let key = self.pop_storage_key()?;
+                let item = match self.storage.get(&key) {
+                    Some(value) => StackItem::ByteString(value.clone()),
+                    None => StackItem::Null,
+                };
+                self.push(item)
+            }
+            SYSTEM_STORAGE_PUT => {
+                let key = self.pop_storage_key()?;
+                let value = match self.eval_stack.pop().ok_or("Stack underflow")? {
+                    StackItem::ByteString(b) => b,
+                    _ => return Err("Invalid type"),
+                };
+                let old_len = self.storage.get(&key).map(Vec::len);
+                self.charge(self.gas_schedule.storage_put_price(
+                    key.len() - STORAGE_SCRIPT_HASH.len(),
+                    value.len(),
+                    old_len,
+                ))?;
+                self.record_original(&key);
+                self.storage.insert(key, value);
+                Ok(())
+            }
+            SYSTEM_STORAGE_DELETE => {
+                let key = self.pop_storage_key()?;
+                self.record_original(&key);
+                self.storage.remove(&key);
+                Ok(())
+            }
+            SYSTEM_CRYPTO_CHECKSIG => {
+                let pubkey = self.pop_bytes()?;
+                let signature = self.pop_bytes()?;
+                let sign_data = self.sign_data.as_ref().ok_or("No script container")?;
+                let verified = verify_signature(&pubkey, &signature, sign_data)?;
+                self.push(StackItem::Boolean(verified))
+            }
+            SYSTEM_CRYPTO_CHECKMULTISIG => {
+                let pubkeys = self.pop_byte_list()?;
+                let signatures = self.pop_byte_list()?;
+                if signatures.is_empty() || signatures.len() > pubkeys.len() {
+                    return Err("Invalid operation");
+                }
+                self.charge(self.gas_schedule.check_multisig_price(pubkeys.len()))?;
+                let sign_data = self.sign_data.as_ref().ok_or("No script container")?;
+
+                // Signatures must appear in the same order as their keys
+                let (mut i, mut j) = (0, 0);
+                while i < signatures.len() && signatures.len() - i <= pubkeys.len() - j {
+                    if verify_signature(&pubkeys[j], &signatures[i], sign_data)? {
+                        i += 1;
+                    }
+                    j += 1;
+                }
+                self.push(StackItem::Boolean(i == signatures.len()))
+            }
+            SYSTEM_CONTRACT_CALL => {
+                // Pops and checks in the order of `neo_vm_core`'s call_contract
+                let hash: [u8; 20] = self
+                    .pop_bytes()?
+                    .try_into()
+                    .map_err(|_| "Invalid operation")?;
+                let method =
+                    String::from_utf8(self.pop_bytes()?).map_err(|_| "Invalid operation")?;
+                let flags = self
+                    .eval_stack
+                    .pop()
+                    .ok_or("Stack underflow")?
+                    .to_integer()
+                    .and_then(|bits| u8::try_from(bits).ok())
+                    .ok_or("Invalid operation")?;
+                // Bits outside `neo_vm_core::CallFlags::ALL`
+                if flags & !0x0F != 0 {
+                    return Err("Invalid operation");
+                }
+                let args = match self.eval_stack.pop().ok_or("Stack underflow")? {
+                    StackItem::Array(args) => args,
+                    _ => return Err("Invalid type"),
+                };
+                if method.starts_with('_') {
+                    return Err("Invalid operation");
+                }
+                let result = match (hash, method.as_str()) {
+                    (LEDGER_HASH, "currentIndex") => {
+                        StackItem::Integer(self.runtime.block_index as i128)
+                    }
+                    (ORACLE_HASH, "getResponse") => self.oracle_response(&args)?,
+                    (LEDGER_HASH | ORACLE_HASH, _) => return Err("Native contract call failed"),
+                    _ => return Err("Contract not found"),
+                };
+                self.push(result)
+            }
+            _ => Err("Unknown syscall"),
+        }
+    }
+
+    /// `neo_vm_core::OracleContract`'s `getResponse(url, filter)`, where a
+    /// null filter is the empty one
+    fn oracle_response(&self, args: &[StackItem]) -> Result<StackItem, &'static str> {
+        let url = match args.first() {
+            Some(StackItem::ByteString(url)) => String::from_utf8_lossy(url),
+            _ => return Err("Native contract call failed"),
+        };
+        let filter = match args.get(1) {
+            Some(StackItem::ByteString(filter)) => String::from_utf8_lossy(filter),
+            Some(StackItem::Null) | None => "".into(),
+            _ => return Err("Native contract call failed"),
+        };
+        self.oracle_responses
+            .iter()
+            .find(|(u, f, _)| *u == url && *f == filter)
+            .map(|(_, _, response)| StackItem::ByteString(response.clone()))
+            .ok_or("Native contract call failed")
+    }
+
+    fn pop_bytes(&mut self) -> Result<Vec<u8>, &'static str> {
+        match self.eval_stack.pop().ok_or("Stack underflow")? {
+            StackItem::ByteString(b) => Ok(b),
+            _ => Err("Invalid type"),
+        }
+    }
+
+    /// Pop a count followed by that many byte strings, in pop order
+    fn pop_byte_list(&mut self) -> Result<Vec<Vec<u8>>, &'static str> {
+        let count = match self.eval_stack.pop().ok_or("Stack underflow")? {
+            StackItem::Integer(n) => usize::try_from(n).map_err(|_| "Invalid operation")?,
+            _ => return Err("Invalid type"),
+        };
+        if count > self.eval_stack.len() {
+            return Err("Stack underflow");
+        }
+        (0..count).map(|_| self.pop_bytes()).collect()
+    }
+
+    /// Pop a storage key and prefix it with the context script hash
+    fn pop_storage_key(&mut self) -> Result<Vec<u8>, &'static str> {
+        match self.eval_stack.pop().ok_or("Stack underflow")? {
+            StackItem::ByteString(key) => {
+                let mut full_key = STORAGE_SCRIPT_HASH.to_vec();
+                full_key.extend_from_slice(&key);
+                if self
+                    .witnessed
+                    .as_ref()
+                    .is_some_and(|keys| !keys.contains(&full_key))
+                {
+                    return Err("Storage key is not covered by a storage witness");
+                }
+                Ok(full_key)
+            }
+            _ => Err("Invalid type"),
+        }
+    }
+
+    fn record_original(&mut self, key: &[u8]) {
+        if !self.original.contains_key(key) {
+            self.original
+                .insert(key.to_vec(), self.storage.get(key).cloned());
+        }
+    }
+
+    /// Net storage writes, empty unless execution halted
+    ///
+    /// Matches `TrackedStorage::write_set` in neo-vm-core, which reverts all
+    /// writes when the VM faults.
+    fn write_set(&self) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+        if self.state != VMState::Halt {
+            return Vec::new();
+        }
+        self.original
+            .iter()
+            .filter_map(|(key, original)| {
+                let value = self.storage.get(key).cloned();
+                (value != *original).then(|| (key.clone(), value))
+            })
+            .collect()
+    }
+
     fn pop_int(&mut self) -> Result<i128, &'static str> {
         self.eval_stack
             .pop()
@@ -327,10 +1276,162 @@ impl NeoVM {
     }
 }
 
+/// `base^exp % modulus`, the same square-and-multiply as `neo_vm_core`'s
+/// MODPOW
+fn mod_pow(base: i128, exp: i128, modulus: i128) -> Option<i128> {
+    if exp < 0 {
+        return None;
+    }
+    let mut result = 1i128.checked_rem(modulus)?;
+    let mut base = base.checked_rem(modulus)?;
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)?.checked_rem(modulus)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)?.checked_rem(modulus)?;
+        }
+    }
+    Some(result)
+}
+
+/// Inverse of `value` modulo `modulus`, the same extended Euclidean
+/// algorithm as `neo_vm_core`'s MODPOW with an exponent of -1
+fn mod_inverse(value: i128, modulus: i128) -> Option<i128> {
+    if value <= 0 || modulus < 2 {
+        return None;
+    }
+    let (mut r, mut old_r) = (value, modulus);
+    let (mut s, mut old_s) = (1i128, 0i128);
+    while r > 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r % r);
+        (old_s, s) = (s, old_s.checked_sub(q.checked_mul(s)?)?);
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(modulus))
+}
+
+/// Little-endian two's complement `PUSHINT*` operand, as
+/// `neo_vm_core`'s engine reads it: faults unless the value fits in an i128
+fn int_operand(operand: &[u8]) -> Result<i128, &'static str> {
+    let (low, high) = operand.split_at(operand.len().min(16));
+    let negative = low.last().is_some_and(|&b| b & 0x80 != 0);
+    let extension = if negative { 0xFF } else { 0x00 };
+    if high.iter().any(|&b| b != extension) {
+        return Err("Integer too large");
+    }
+    let mut bytes = [extension; 16];
+    bytes[..low.len()].copy_from_slice(low);
+    Ok(i128::from_le_bytes(bytes))
+}
+
+/// Encoding hashed into `write_set_hash`, matching `ProofOutput::write_set_hash`
+fn encode_write_set(writes: &[(Vec<u8>, Option<Vec<u8>>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (key, value) in writes {
+        out.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        out.extend_from_slice(key);
+        match value {
+            Some(value) => {
+                out.push(0x01);
+                out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                out.extend_from_slice(value);
+            }
+            None => out.push(0x00),
+        }
+    }
+    out
+}
+
+/// Verify an ECDSA signature over SHA-256 of `message` on secp256r1 or
+/// secp256k1, as neo-vm-core does
+///
+/// A key on neither curve, or a signature malformed for every curve the key
+/// is on, faults; a well-formed signature that does not verify is `false`.
+/// In the zkVM, the patched p256 and k256 verify on SP1's secp256r1 and
+/// secp256k1 precompiles.
+#[cfg(feature = "full")]
+fn verify_signature(pubkey: &[u8], signature: &[u8], message: &[u8]) -> Result<bool, &'static str> {
+    let r1 = p256::ecdsa::VerifyingKey::from_sec1_bytes(pubkey).ok();
+    let k1 = k256::ecdsa::VerifyingKey::from_sec1_bytes(pubkey).ok();
+    if r1.is_none() && k1.is_none() {
+        return Err("Invalid public key");
+    }
+    let r1 = r1.zip(p256::ecdsa::Signature::from_slice(signature).ok());
+    let k1 = k1.zip(k256::ecdsa::Signature::from_slice(signature).ok());
+    if r1.is_none() && k1.is_none() {
+        return Err("Invalid signature");
+    }
+    Ok(
+        r1.is_some_and(|(key, signature)| key.verify(message, &signature).is_ok())
+            || k1.is_some_and(|(key, signature)| key.verify(message, &signature).is_ok()),
+    )
+}
+
+/// `neo_vm_guest::ProofInput::input_hash`: SHA-256 over the script, the
+/// hash of the canonically encoded arguments, the gas limit, the storage
+/// entries and the remaining input fields
+fn hash_input(input: &GuestInput) -> [u8; 32] {
+    let mut arguments = (input.arguments.len() as u32).to_le_bytes().to_vec();
+    for argument in &input.arguments {
+        argument.write_canonical(&mut arguments);
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update((input.script.len() as u32).to_le_bytes());
+    hasher.update(&input.script);
+    hasher.update(Sha256::digest(&arguments));
+    hasher.update(input.gas_limit.to_le_bytes());
+    hasher.update((input.storage.len() as u32).to_le_bytes());
+    for (key, value) in &input.storage {
+        hasher.update((key.len() as u32).to_le_bytes());
+        hasher.update(key);
+        hasher.update((value.len() as u32).to_le_bytes());
+        hasher.update(value);
+    }
+    hasher.update(input.tx_hash);
+    hasher.update(input.context);
+    hasher.update([input.gas_schedule]);
+    hasher.update(input.network.to_le_bytes());
+    hasher.update([input.trigger]);
+    hasher.update(input.random_seed);
+    match input.pre_state_root {
+        Some(root) => {
+            hasher.update([1]);
+            hasher.update(root);
+        }
+        None => hasher.update([0]),
+    }
+    hasher.update(input.limits.encode());
+    hasher.update(input.block_index.to_le_bytes());
+    hasher.update(input.timestamp.to_le_bytes());
+    hasher.update(oracle_commitment(&input.oracle_responses));
+    hasher.finalize().into()
+}
+
+/// `neo_vm_core::OracleResponses::commitment`
+fn oracle_commitment(responses: &[(String, String, Vec<u8>)]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"neo-zkvm/Oracle/v1");
+    hasher.update((responses.len() as u32).to_le_bytes());
+    for (url, filter, response) in responses {
+        hasher.update((url.len() as u32).to_le_bytes());
+        hasher.update(url);
+        hasher.update((filter.len() as u32).to_le_bytes());
+        hasher.update(filter);
+        hasher.update(Sha256::digest(response));
+    }
+    hasher.finalize().into()
+}
+
 /// SHA256 hash function (fallback for non-zkVM targets)
 #[cfg(not(target_os = "zkvm"))]
 fn sha256_hash(data: &[u8]) -> [u8; 32] {
-    use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
     hasher.update(data);
     hasher.finalize().into()
@@ -342,24 +1443,67 @@ pub fn zkvm_main() {
     // Read input from host
     let input: GuestInput = sp1_zkvm::io::read();
 
-    // Compute input hash
-    let input_bytes = bincode::serialize(&input).unwrap_or_default();
-    let input_hash = sp1_zkvm::precompiles::sha256::sha256(&input_bytes);
+    let input_hash = hash_input(&input);
+    let oracle_hash = oracle_commitment(&input.oracle_responses);
 
     // Compute script hash
     let script_hash = sp1_zkvm::precompiles::sha256::sha256(&input.script);
 
     // Create VM and execute
     let mut vm = NeoVM::new(input.gas_limit);
+    vm.limits = input.limits;
+    let gas_schedule = GasSchedule::from_id(input.gas_schedule);
+    vm.gas_schedule = gas_schedule.unwrap_or(GasSchedule::Legacy);
+    vm.runtime = Runtime {
+        network: input.network,
+        trigger: input.trigger,
+        random_seed: input.random_seed,
+        block_index: input.block_index,
+        timestamp: input.timestamp,
+    };
+    vm.oracle_responses = input.oracle_responses;
+    let witnessed_storage = witnessed_storage(&input);
+    let storage_proven = witnessed_storage.is_ok();
+    match witnessed_storage {
+        Ok(Some((entries, keys))) => {
+            vm.storage = entries.into_iter().collect();
+            vm.witnessed = Some(keys);
+        }
+        _ => vm.storage = input.storage.into_iter().collect(),
+    }
+    let empty_write_set_hash: [u8; 32] = sp1_zkvm::precompiles::sha256::sha256(&[]).into();
+
+    // Signed data is bound to the proof through `tx_hash`
+    let sign_data_bound = input.sign_data.as_ref().is_none_or(|sign_data| {
+        <[u8; 32]>::from(sp1_zkvm::precompiles::sha256::sha256(sign_data)) == input.tx_hash
+    });
+    vm.sign_data = input.sign_data;
 
-    if vm.load_script(input.script).is_err() {
+    if gas_schedule.is_none()
+        || !sign_data_bound
+        || !storage_proven
+        || !Runtime::is_valid_trigger(input.trigger)
+        || input.arguments.len() > input.limits.max_stack as usize
+        || vm.load_script(input.script).is_err()
+    {
         // Commit failure
         sp1_zkvm::io::commit(&PublicValues {
             script_hash: script_hash.into(),
-            input_hash: input_hash.into(),
+            input_hash,
             output_hash: [0u8; 32],
             gas_consumed: 0,
             execution_success: false,
+            write_set_hash: empty_write_set_hash,
+            tx_hash: input.tx_hash,
+            context: input.context,
+            gas_schedule: input.gas_schedule,
+            network: input.network,
+            trigger: input.trigger,
+            random_seed: input.random_seed,
+            random_draws: 0,
+            block_index: input.block_index,
+            timestamp: input.timestamp,
+            oracle_hash,
         });
         return;
     }
@@ -377,17 +1521,34 @@ pub fn zkvm_main() {
         }
     }
 
-    // Compute output hash
-    let result_bytes = bincode::serialize(&vm.eval_stack).unwrap_or_default();
+    // Compute output hash over the canonical encoding of the result item
+    let mut result_bytes = Vec::new();
+    if let Some(result) = vm.eval_stack.last() {
+        result.write_canonical(&mut result_bytes);
+    }
     let output_hash: [u8; 32] = sp1_zkvm::precompiles::sha256::sha256(&result_bytes).into();
 
+    let write_set_bytes = encode_write_set(&vm.write_set());
+    let write_set_hash: [u8; 32] = sp1_zkvm::precompiles::sha256::sha256(&write_set_bytes).into();
+
     // Create public values
     let public_values = PublicValues {
         script_hash: script_hash.into(),
-        input_hash: input_hash.into(),
+        input_hash,
         output_hash,
         gas_consumed: vm.gas_consumed,
         execution_success: vm.state == VMState::Halt,
+        write_set_hash,
+        tx_hash: input.tx_hash,
+        context: input.context,
+        gas_schedule: input.gas_schedule,
+        network: input.network,
+        trigger: input.trigger,
+        random_seed: input.random_seed,
+        random_draws: vm.random_draws,
+        block_index: input.block_index,
+        timestamp: input.timestamp,
+        oracle_hash,
     };
 
     // Commit public values to the proof
@@ -402,6 +1563,10 @@ fn main() {
     std::process::exit(1);
 }
 
+// Parity with neo-vm-core needs the syscalls
+#[cfg(all(test, feature = "full"))]
+mod parity_tests;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,4 +1596,61 @@ mod tests {
 
         assert_eq!(vm.eval_stack[0], StackItem::Integer(3));
     }
+
+    #[test]
+    #[cfg(feature = "full")]
+    fn test_storage_write_set() {
+        let mut vm = NeoVM::new(1_000_000);
+        vm.storage.insert(vec![0u8; 21], vec![1]);
+        // PUSHDATA1 [2], PUSHDATA1 [0], SYSCALL PUT, RET
+        vm.load_script(vec![
+            0x0C, 0x01, 0x02, 0x0C, 0x01, 0x00, 0x41, 0x11, 0x00, 0x00, 0x00, 0x40,
+        ])
+        .unwrap();
+
+        while vm.state == VMState::Running {
+            vm.execute_next().unwrap();
+        }
+
+        assert_eq!(vm.write_set(), vec![(vec![0u8; 21], Some(vec![2]))]);
+    }
+
+    #[test]
+    #[cfg(feature = "full")]
+    fn test_unwitnessed_storage_faults() {
+        // PUSHDATA1 [2], PUSHDATA1 [key], SYSCALL PUT, RET
+        let put = |key: u8| {
+            vec![
+                0x0C, 0x01, 0x02, 0x0C, 0x01, key, 0x41, 0x11, 0x00, 0x00, 0x00, 0x40,
+            ]
+        };
+        let mut vm = NeoVM::new(1_000_000);
+        vm.witnessed = Some([vec![0u8; 21]].into_iter().collect());
+        vm.load_script(put(0)).unwrap();
+        while vm.state == VMState::Running {
+            vm.execute_next().unwrap();
+        }
+        assert_eq!(vm.write_set(), vec![(vec![0u8; 21], Some(vec![2]))]);
+
+        let mut vm = NeoVM::new(1_000_000);
+        vm.witnessed = Some(BTreeSet::new());
+        vm.load_script(put(0)).unwrap();
+        vm.execute_next().unwrap();
+        vm.execute_next().unwrap();
+        assert_eq!(
+            vm.execute_next(),
+            Err("Storage key is not covered by a storage witness")
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "full"))]
+    fn test_arithmetic_program_rejects_syscalls() {
+        let mut vm = NeoVM::new(1_000_000);
+        // SYSCALL GetTrigger, RET
+        vm.load_script(vec![0x41, 0x04, 0x00, 0x00, 0x00, 0x40])
+            .unwrap();
+        assert!(vm.execute_next().is_err());
+        assert_eq!(vm.state, VMState::Fault);
+    }
 }
@@ -0,0 +1,882 @@
+//! Host/guest parity
+//!
+//! Until the guest runs `neo-vm-core` itself, this VM is a second
+//! implementation of the same semantics. These property tests build random
+//! scripts from the instructions both VMs implement and check that
+//! `neo_vm_core::NeoVM` and this VM end in the same state, with the same
+//! stack, gas, gas breakdown and storage writes.
+
+use super::{
+    hash_input, oracle_commitment, GasSchedule, GuestInput, GuestLimits, NeoVM, Runtime, StackItem,
+    StorageWitness, VMState,
+};
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use neo_vm_core::engine::syscall;
+use neo_vm_core::{NativeContract, OpCode, ScriptBuilder, VMLimits};
+use proptest::prelude::*;
+use sha2::{Digest, Sha256};
+
+/// One generated instruction
+#[derive(Debug, Clone)]
+enum Op {
+    Int(i128),
+    Bytes(Vec<u8>),
+    Null,
+    Simple(OpCode),
+    Put(Vec<u8>, Vec<u8>),
+    Get(Vec<u8>),
+    Delete(Vec<u8>),
+    Syscall(u32),
+}
+
+/// Opcodes without operands implemented by both VMs
+const SIMPLE: &[OpCode] = &[
+    OpCode::DROP,
+    OpCode::DUP,
+    OpCode::ADD,
+    OpCode::SUB,
+    OpCode::MUL,
+    OpCode::DIV,
+    OpCode::MOD,
+    OpCode::POW,
+    OpCode::SQRT,
+    OpCode::MODMUL,
+    OpCode::MODPOW,
+    OpCode::SHL,
+    OpCode::SHR,
+    OpCode::SIGN,
+    OpCode::ABS,
+    OpCode::NEGATE,
+    OpCode::INC,
+    OpCode::DEC,
+    OpCode::NOT,
+    OpCode::BOOLAND,
+    OpCode::BOOLOR,
+    OpCode::NZ,
+    OpCode::NUMEQUAL,
+    OpCode::NUMNOTEQUAL,
+    OpCode::LT,
+    OpCode::LE,
+    OpCode::GT,
+    OpCode::GE,
+    OpCode::MIN,
+    OpCode::MAX,
+    OpCode::WITHIN,
+    OpCode::NOP,
+    OpCode::ASSERT,
+];
+
+/// Integers at the edges of the i128 range both VMs compute in
+const BOUNDARY: &[i128] = &[
+    i128::MIN,
+    i128::MIN + 1,
+    i128::MAX,
+    i128::MAX - 1,
+    1 << 64,
+    -(1 << 64),
+    1 << 126,
+    i64::MAX as i128,
+    i64::MIN as i128,
+    127,
+    128,
+];
+
+/// Runtime syscalls implemented by both VMs
+const RUNTIME_SYSCALLS: &[u32] = &[
+    syscall::SYSTEM_RUNTIME_LOG,
+    syscall::SYSTEM_RUNTIME_GETTRIGGER,
+    syscall::SYSTEM_RUNTIME_GETPLATFORM,
+    syscall::SYSTEM_RUNTIME_GETNETWORK,
+    syscall::SYSTEM_RUNTIME_GETRANDOM,
+    syscall::SYSTEM_RUNTIME_GETINVOCATIONCOUNTER,
+    syscall::SYSTEM_RUNTIME_BURNGAS,
+];
+
+fn key() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(0u8..4, 1..3)
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => any::<i16>().prop_map(|n| Op::Int(n.into())),
+        2 => (-1i128..=16).prop_map(Op::Int),
+        2 => prop::sample::select(BOUNDARY).prop_map(Op::Int),
+        1 => prop::collection::vec(any::<u8>(), 0..20).prop_map(Op::Bytes),
+        1 => Just(Op::Null),
+        6 => prop::sample::select(SIMPLE).prop_map(Op::Simple),
+        1 => (key(), prop::collection::vec(any::<u8>(), 0..4)).prop_map(|(k, v)| Op::Put(k, v)),
+        1 => key().prop_map(Op::Get),
+        1 => key().prop_map(Op::Delete),
+        1 => prop::sample::select(RUNTIME_SYSCALLS).prop_map(Op::Syscall),
+    ]
+}
+
+fn build(ops: &[Op]) -> Vec<u8> {
+    let mut builder = ScriptBuilder::new();
+    for op in ops {
+        match op {
+            Op::Int(n) => builder.emit_push_int(*n),
+            Op::Bytes(data) => builder.emit_push_bytes(data),
+            Op::Null => builder.emit_push_null(),
+            Op::Simple(op) => builder.emit(*op),
+            Op::Put(key, value) => builder
+                .emit_push_bytes(value)
+                .emit_push_bytes(key)
+                .emit_syscall(syscall::SYSTEM_STORAGE_PUT),
+            Op::Get(key) => builder
+                .emit_push_bytes(key)
+                .emit_syscall(syscall::SYSTEM_STORAGE_GET),
+            Op::Delete(key) => builder
+                .emit_push_bytes(key)
+                .emit_syscall(syscall::SYSTEM_STORAGE_DELETE),
+            Op::Syscall(id) => builder.emit_syscall(*id),
+        };
+    }
+    builder.emit(OpCode::RET);
+    builder.into_bytes()
+}
+
+fn to_host(item: &StackItem) -> neo_vm_core::StackItem {
+    use neo_vm_core::StackItem as Host;
+    match item {
+        StackItem::Null => Host::Null,
+        StackItem::Boolean(b) => Host::Boolean(*b),
+        StackItem::Integer(i) => Host::Integer(*i),
+        StackItem::ByteString(b) => Host::ByteString(b.clone().into()),
+        StackItem::Array(items) => Host::Array(items.iter().map(to_host).collect()),
+        StackItem::Struct(items) => Host::Struct(items.iter().map(to_host).collect()),
+        StackItem::Map(entries) => Host::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (to_host(k), to_host(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Final state of one run, comparable across the two VMs
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    halted: bool,
+    stack: Vec<neo_vm_core::StackItem>,
+    gas: u64,
+    gas_breakdown: [u64; 12],
+    writes: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+fn run_host(
+    script: &[u8],
+    storage: &[(Vec<u8>, Vec<u8>)],
+    gas_limit: u64,
+    sign_data: Option<&[u8]>,
+    schedule: neo_vm_core::GasSchedule,
+    runtime: neo_vm_core::RuntimeConfig,
+    limits: VMLimits,
+) -> Outcome {
+    let mut vm = neo_vm_core::NeoVM::with_limits(gas_limit, limits);
+    vm.gas_schedule = schedule;
+    vm.runtime = runtime;
+    vm.sign_data = sign_data.map(<[u8]>::to_vec);
+    vm.storage = neo_vm_core::TrackedStorage::from(
+        storage
+            .iter()
+            .cloned()
+            .collect::<neo_vm_core::MemoryStorage>(),
+    );
+    if vm.load_script(script.to_vec()).is_ok() {
+        vm.run();
+    } else {
+        vm.state = neo_vm_core::VMState::Fault;
+    }
+    let halted = matches!(vm.state, neo_vm_core::VMState::Halt);
+    Outcome {
+        halted,
+        stack: if halted {
+            vm.eval_stack.clone()
+        } else {
+            Vec::new()
+        },
+        gas: vm.gas_consumed,
+        gas_breakdown: neo_vm_core::OpCategory::ALL.map(|category| vm.gas_breakdown.get(category)),
+        writes: vm.storage.write_set(),
+    }
+}
+
+fn run_guest(
+    script: &[u8],
+    storage: &[(Vec<u8>, Vec<u8>)],
+    gas_limit: u64,
+    sign_data: Option<&[u8]>,
+    schedule: neo_vm_core::GasSchedule,
+    runtime: neo_vm_core::RuntimeConfig,
+    limits: VMLimits,
+) -> Outcome {
+    let mut vm = NeoVM::new(gas_limit);
+    vm.limits = guest_limits(limits);
+    vm.gas_schedule = GasSchedule::from_id(schedule.id()).unwrap();
+    vm.runtime = Runtime {
+        network: runtime.network,
+        trigger: runtime.trigger.id(),
+        random_seed: runtime.random_seed,
+        block_index: 0,
+        timestamp: 0,
+    };
+    vm.sign_data = sign_data.map(<[u8]>::to_vec);
+    vm.storage = storage.iter().cloned().collect();
+    if vm.load_script(script.to_vec()).is_ok() {
+        while vm.state == VMState::Running {
+            if vm.execute_next().is_err() {
+                vm.state = VMState::Fault;
+            }
+        }
+    } else {
+        vm.state = VMState::Fault;
+    }
+    let halted = vm.state == VMState::Halt;
+    Outcome {
+        halted,
+        stack: if halted {
+            vm.eval_stack.iter().map(to_host).collect()
+        } else {
+            Vec::new()
+        },
+        gas: vm.gas_consumed,
+        gas_breakdown: vm.gas_breakdown,
+        writes: vm.write_set(),
+    }
+}
+
+/// The prover's conversion of `limits`
+fn guest_limits(limits: VMLimits) -> GuestLimits {
+    let size = |value: usize| u32::try_from(value).unwrap_or(u32::MAX);
+    GuestLimits {
+        max_stack: size(limits.max_stack),
+        max_invocation_depth: size(limits.max_invocation_depth),
+        max_script_size: size(limits.max_script_size),
+        max_item_size: size(limits.max_item_size),
+        max_trace_steps: limits.max_trace_steps,
+    }
+}
+
+/// Default limits, or ones low enough for generated scripts to reach
+fn limits() -> impl Strategy<Value = VMLimits> {
+    prop_oneof![
+        2 => Just(VMLimits::default()),
+        1 => (1usize..8, 0usize..64, 0usize..24, 0u64..32).prop_map(
+            |(max_stack, max_script_size, max_item_size, max_trace_steps)| VMLimits {
+                max_stack,
+                max_script_size,
+                max_item_size,
+                max_trace_steps,
+                ..VMLimits::default()
+            }
+        ),
+    ]
+}
+
+fn initial_storage() -> impl Strategy<Value = Vec<(Vec<u8>, Vec<u8>)>> {
+    prop::collection::vec((key(), prop::collection::vec(any::<u8>(), 1..4)), 0..4).prop_map(
+        |entries| {
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let mut full = vec![0u8; 20];
+                    full.extend_from_slice(&key);
+                    (full, value)
+                })
+                .collect()
+        },
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn host_and_guest_agree(
+        ops in prop::collection::vec(op(), 0..24),
+        storage in initial_storage(),
+        // Low limits exercise running out of gas mid-script
+        gas_limit in prop_oneof![0u64..200, 0u64..100_000, Just(10_000_000)],
+        schedule in prop::sample::select(neo_vm_core::GasSchedule::ALL.to_vec()),
+        network in any::<u32>(),
+        random_seed in any::<[u8; 32]>(),
+        limits in limits(),
+    ) {
+        let script = build(&ops);
+        let runtime = neo_vm_core::RuntimeConfig {
+            network,
+            trigger: neo_vm_core::TriggerType::Verification,
+            random_seed,
+        };
+        prop_assert_eq!(
+            run_host(&script, &storage, gas_limit, None, schedule, runtime, limits),
+            run_guest(&script, &storage, gas_limit, None, schedule, runtime, limits)
+        );
+    }
+}
+
+fn argument() -> impl Strategy<Value = StackItem> {
+    let leaf = prop_oneof![
+        Just(StackItem::Null),
+        any::<bool>().prop_map(StackItem::Boolean),
+        any::<i128>().prop_map(StackItem::Integer),
+        prop::collection::vec(any::<u8>(), 0..8).prop_map(StackItem::ByteString),
+    ];
+    leaf.prop_recursive(2, 8, 3, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..3).prop_map(StackItem::Array),
+            prop::collection::vec(inner.clone(), 0..3).prop_map(StackItem::Struct),
+            prop::collection::vec((inner.clone(), inner), 0..3).prop_map(StackItem::Map),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn input_hashes_agree(
+        arguments in prop::collection::vec(argument(), 0..4),
+        storage in initial_storage(),
+        gas_limit in any::<u64>(),
+        tx_hash in any::<[u8; 32]>(),
+        network in any::<u32>(),
+        pre_state_root in any::<Option<[u8; 32]>>(),
+        limits in limits(),
+        block_index in any::<u32>(),
+        timestamp in prop_oneof![Just(0), any::<u64>()],
+        oracle_responses in prop::collection::btree_map(
+            (".{0,8}", ".{0,4}"),
+            prop::collection::vec(any::<u8>(), 0..8),
+            0..3,
+        ),
+    ) {
+        let script = vec![0x11, 0x40];
+        let runtime = neo_vm_core::RuntimeConfig {
+            network,
+            ..Default::default()
+        };
+        let host = neo_vm_guest::ProofInput {
+            script: script.clone(),
+            arguments: arguments.iter().map(to_host).collect(),
+            gas_limit,
+            storage: storage.clone(),
+            context: [7; 32],
+            gas_schedule: neo_vm_core::GasSchedule::NeoN3,
+            runtime,
+            pre_state_root,
+            storage_witnesses: Vec::new(),
+            limits,
+            block_context: neo_vm_core::BlockContext {
+                index: block_index,
+                timestamp,
+            },
+            oracle_responses: oracle_responses
+                .iter()
+                .map(|((url, filter), response)| neo_vm_core::OracleResponse {
+                    url: url.clone(),
+                    filter: filter.clone(),
+                    response: response.clone(),
+                })
+                .collect::<Vec<_>>()
+                .into(),
+        };
+        let guest = GuestInput {
+            script,
+            arguments,
+            gas_limit,
+            storage,
+            tx_hash,
+            context: [7; 32],
+            gas_schedule: neo_vm_core::GasSchedule::NeoN3.id(),
+            network,
+            trigger: runtime.trigger.id(),
+            random_seed: runtime.random_seed,
+            sign_data: None,
+            pre_state_root,
+            storage_witnesses: Vec::new(),
+            limits: guest_limits(limits),
+            block_index,
+            timestamp,
+            oracle_responses: oracle_responses
+                .into_iter()
+                .map(|((url, filter), response)| (url, filter, response))
+                .collect(),
+        };
+        prop_assert_eq!(host.input_hash(tx_hash), hash_input(&guest));
+        prop_assert_eq!(
+            host.oracle_responses.commitment(),
+            oracle_commitment(&guest.oracle_responses)
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn storage_witnesses_agree(
+        storage in initial_storage(),
+        probes in prop::collection::vec(key(), 1..4),
+        flipped_byte in 0usize..32,
+    ) {
+        let trie: neo_vm_core::TrieStorage = storage.iter().cloned().collect();
+        let root = trie.root_hash();
+        let mut wrong_root = root;
+        wrong_root[flipped_byte] ^= 1;
+        let keys: Vec<Vec<u8>> = storage
+            .iter()
+            .map(|(key, _)| key.clone())
+            .chain(probes.into_iter().map(|key| [vec![0u8; 20], key].concat()))
+            .collect();
+
+        for host in neo_vm_guest::storage_witness::storage_witnesses(&trie, &keys) {
+            let guest = StorageWitness {
+                key: host.key.clone(),
+                value: host.value.clone(),
+                nodes: host.nodes.clone(),
+            };
+            prop_assert!(host.verify(root) && guest.verify(root));
+            prop_assert!(!host.verify(wrong_root) && !guest.verify(wrong_root));
+
+            // Claiming a different value breaks both
+            let mut forged = guest.clone();
+            forged.value = Some(b"forged".to_vec());
+            prop_assert!(!forged.verify(root));
+        }
+    }
+}
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_slice(&[seed; 32]).unwrap()
+}
+
+fn pubkey(key: &SigningKey) -> Vec<u8> {
+    key.verifying_key()
+        .to_encoded_point(true)
+        .as_bytes()
+        .to_vec()
+}
+
+fn sign(key: &SigningKey, message: &[u8]) -> Vec<u8> {
+    let signature: Signature = key.sign(message);
+    signature.to_bytes().to_vec()
+}
+
+/// `signatures`, `keys` in the order CHECKMULTISIG pops them
+fn multisig(signatures: &[Vec<u8>], keys: &[Vec<u8>]) -> Vec<u8> {
+    let mut builder = ScriptBuilder::new();
+    for list in [signatures, keys] {
+        for item in list.iter().rev() {
+            builder.emit_push_bytes(item);
+        }
+        builder.emit_push_int(list.len() as i128);
+    }
+    builder
+        .emit_syscall(syscall::SYSTEM_CRYPTO_CHECKMULTISIG)
+        .emit(OpCode::RET);
+    builder.into_bytes()
+}
+
+#[test]
+fn signature_checks_agree() {
+    const SIGN_DATA: &[u8] = b"script container";
+    let keys: Vec<SigningKey> = (1..=3).map(signing_key).collect();
+    let pubkeys: Vec<Vec<u8>> = keys.iter().map(pubkey).collect();
+    let signatures: Vec<Vec<u8>> = keys.iter().map(|k| sign(k, SIGN_DATA)).collect();
+
+    // Opcode 0xF3 signs the SHA-256 of a message from the stack
+    let message = b"message";
+    let digest_signature = sign(&keys[0], &Sha256::digest(message));
+    let checksig_op = |signature: &[u8], key: &[u8]| {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_push_bytes(message)
+            .emit_push_bytes(signature)
+            .emit_push_bytes(key);
+        let mut script = builder.into_bytes();
+        script.extend_from_slice(&[0xF3, 0x40]);
+        script
+    };
+    let checksig = |signature: &[u8], key: &[u8]| {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_push_bytes(signature)
+            .emit_push_bytes(key)
+            .emit_syscall(syscall::SYSTEM_CRYPTO_CHECKSIG)
+            .emit(OpCode::RET);
+        builder.into_bytes()
+    };
+
+    let cases = [
+        (checksig_op(&digest_signature, &pubkeys[0]), true),
+        (checksig_op(&digest_signature, &pubkeys[1]), false),
+        (checksig_op(&[0; 3], &pubkeys[0]), false),
+        (checksig_op(&digest_signature, &[2; 33]), false),
+        (checksig(&signatures[0], &pubkeys[0]), true),
+        (checksig(&signatures[0], &pubkeys[1]), false),
+        (multisig(&signatures[..2], &pubkeys), true),
+        (
+            multisig(&[signatures[0].clone(), signatures[2].clone()], &pubkeys),
+            true,
+        ),
+        (
+            multisig(&[signatures[1].clone(), signatures[0].clone()], &pubkeys),
+            false,
+        ),
+        (multisig(&signatures, &pubkeys[..2]), false),
+        (multisig(&[], &pubkeys), false),
+    ];
+    for (script, expect_true) in cases {
+        for sign_data in [Some(SIGN_DATA), None] {
+            let schedule = neo_vm_core::GasSchedule::NeoN3;
+            let runtime = neo_vm_core::RuntimeConfig::default();
+            let limits = VMLimits::default();
+            let host = run_host(
+                &script,
+                &[],
+                100_000_000,
+                sign_data,
+                schedule,
+                runtime,
+                limits,
+            );
+            assert_eq!(
+                host,
+                run_guest(
+                    &script,
+                    &[],
+                    100_000_000,
+                    sign_data,
+                    schedule,
+                    runtime,
+                    limits
+                )
+            );
+            if expect_true && sign_data.is_some() {
+                assert_eq!(host.stack, vec![neo_vm_core::StackItem::Boolean(true)]);
+            }
+        }
+    }
+}
+
+#[test]
+fn secp256r1_signature_checks_agree() {
+    const SIGN_DATA: &[u8] = b"script container";
+    // Neo N3 accounts sign with secp256r1 keys
+    let key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let r1_pubkey = key
+        .verifying_key()
+        .to_encoded_point(true)
+        .as_bytes()
+        .to_vec();
+    let r1_signature: p256::ecdsa::Signature = key.sign(SIGN_DATA);
+    let r1_signature = r1_signature.to_bytes().to_vec();
+    let k1_key = signing_key(2);
+    let k1_pubkey = pubkey(&k1_key);
+    let k1_signature = sign(&k1_key, SIGN_DATA);
+    let checksig = |signature: &[u8], key: &[u8]| {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_push_bytes(signature)
+            .emit_push_bytes(key)
+            .emit_syscall(syscall::SYSTEM_CRYPTO_CHECKSIG)
+            .emit(OpCode::RET);
+        builder.into_bytes()
+    };
+
+    let cases = [
+        (checksig(&r1_signature, &r1_pubkey), true),
+        (checksig(&k1_signature, &r1_pubkey), false),
+        (checksig(&r1_signature, &k1_pubkey), false),
+        (
+            multisig(
+                &[r1_signature.clone(), k1_signature.clone()],
+                &[r1_pubkey.clone(), k1_pubkey.clone()],
+            ),
+            true,
+        ),
+        (
+            multisig(&[k1_signature, r1_signature], &[r1_pubkey, k1_pubkey]),
+            false,
+        ),
+    ];
+    let schedule = neo_vm_core::GasSchedule::NeoN3;
+    let runtime = neo_vm_core::RuntimeConfig::default();
+    let limits = VMLimits::default();
+    for (script, expected) in cases {
+        let host = run_host(
+            &script,
+            &[],
+            100_000_000,
+            Some(SIGN_DATA),
+            schedule,
+            runtime,
+            limits,
+        );
+        assert_eq!(
+            host,
+            run_guest(
+                &script,
+                &[],
+                100_000_000,
+                Some(SIGN_DATA),
+                schedule,
+                runtime,
+                limits
+            )
+        );
+        assert_eq!(host.stack, vec![neo_vm_core::StackItem::Boolean(expected)]);
+    }
+}
+
+#[test]
+fn overflow_boundaries_agree() {
+    let unary = [
+        OpCode::INC,
+        OpCode::DEC,
+        OpCode::NEGATE,
+        OpCode::ABS,
+        OpCode::SQRT,
+    ];
+    let binary = [
+        OpCode::ADD,
+        OpCode::SUB,
+        OpCode::MUL,
+        OpCode::DIV,
+        OpCode::MOD,
+        OpCode::POW,
+        OpCode::SHL,
+        OpCode::SHR,
+    ];
+    let operands = BOUNDARY.iter().copied().chain([-1, 0, 1, 2, 64, 127]);
+    let schedule = neo_vm_core::GasSchedule::default();
+    let runtime = neo_vm_core::RuntimeConfig::default();
+    let limits = VMLimits::default();
+    for a in operands.clone() {
+        let mut scripts: Vec<_> = unary
+            .iter()
+            .map(|&op| build(&[Op::Int(a), Op::Simple(op)]))
+            .collect();
+        for b in operands.clone() {
+            scripts.extend(
+                binary
+                    .iter()
+                    .map(|&op| build(&[Op::Int(a), Op::Int(b), Op::Simple(op)])),
+            );
+        }
+        for script in scripts {
+            assert_eq!(
+                run_host(&script, &[], 1_000_000, None, schedule, runtime, limits),
+                run_guest(&script, &[], 1_000_000, None, schedule, runtime, limits),
+                "script {script:02x?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn log_and_notify_agree() {
+    let state = StackItem::Array(vec![StackItem::Integer(1)]);
+    let notify = |name: &[u8]| {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit_push_bytes(name)
+            .emit_syscall(syscall::SYSTEM_RUNTIME_NOTIFY)
+            .emit(OpCode::PUSH1)
+            .emit(OpCode::RET);
+        builder.into_bytes()
+    };
+    let log = |ops: &[Op]| {
+        let mut ops = ops.to_vec();
+        ops.push(Op::Syscall(syscall::SYSTEM_RUNTIME_LOG));
+        ops.push(Op::Int(1));
+        build(&ops)
+    };
+    // Arguments are pushed before the script runs; the guest has no
+    // opcodes that build arrays
+    let cases = [
+        (vec![], log(&[Op::Bytes(b"hello".to_vec())])),
+        (vec![], log(&[Op::Bytes(vec![0xFF])])),
+        (vec![], log(&[Op::Int(5)])),
+        (vec![], log(&[])),
+        (vec![state.clone()], notify(b"Transfer")),
+        (vec![state.clone()], notify(&[b'a'; 32])),
+        (vec![state.clone()], notify(&[b'a'; 33])),
+        (vec![state.clone()], notify(&[0xFF])),
+        (vec![StackItem::Integer(1)], notify(b"Transfer")),
+        (vec![], notify(b"Transfer")),
+        (
+            vec![state, StackItem::Integer(1)],
+            build(&[Op::Syscall(syscall::SYSTEM_RUNTIME_NOTIFY)]),
+        ),
+    ];
+    for (args, script) in cases {
+        let mut host = neo_vm_core::NeoVM::new(1_000_000);
+        host.load_script(script.clone()).unwrap();
+        host.eval_stack.extend(args.iter().map(to_host));
+        host.run();
+
+        let mut guest = NeoVM::new(1_000_000);
+        guest.load_script(script.clone()).unwrap();
+        guest.eval_stack.extend(args);
+        while guest.state == VMState::Running {
+            if guest.execute_next().is_err() {
+                guest.state = VMState::Fault;
+            }
+        }
+
+        let halted = matches!(host.state, neo_vm_core::VMState::Halt);
+        assert_eq!(halted, guest.state == VMState::Halt, "script {script:02x?}");
+        assert_eq!(
+            host.gas_consumed, guest.gas_consumed,
+            "script {script:02x?}"
+        );
+        if halted {
+            let stack: Vec<_> = guest.eval_stack.iter().map(to_host).collect();
+            assert_eq!(host.eval_stack, stack, "script {script:02x?}");
+        }
+    }
+}
+
+#[test]
+fn native_calls_agree() {
+    let url = b"https://api.example/price";
+    let mut responses = neo_vm_core::OracleResponses::default();
+    responses.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+    let ledger = neo_vm_core::LedgerContract::default().hash();
+    let oracle = neo_vm_core::OracleContract::default().hash();
+    let cryptolib = neo_vm_core::CryptoLib.hash();
+    let stdlib = neo_vm_core::StdLib.hash();
+    let call = |hash: [u8; 20], method: &str| {
+        let mut builder = ScriptBuilder::new();
+        builder
+            .emit(OpCode::PUSH15)
+            .emit_push_bytes(method.as_bytes())
+            .emit_push_bytes(&hash)
+            .emit_syscall(syscall::SYSTEM_CONTRACT_CALL)
+            .emit(OpCode::RET);
+        builder.into_bytes()
+    };
+    let bytes = |value: &[u8]| StackItem::ByteString(value.to_vec());
+    // The argument array is pushed before the script runs
+    let cases = [
+        (
+            vec![bytes(url), bytes(b"$.neo")],
+            call(oracle, "getResponse"),
+        ),
+        (
+            vec![bytes(url), bytes(b"$.gas")],
+            call(oracle, "getResponse"),
+        ),
+        (vec![bytes(url)], call(oracle, "getResponse")),
+        (vec![StackItem::Integer(1)], call(oracle, "getResponse")),
+        (vec![], call(oracle, "request")),
+        (vec![], call(ledger, "currentIndex")),
+        (vec![], call(ledger, "_currentIndex")),
+        (vec![], call([0; 20], "currentIndex")),
+        // Proven runs leave out the host-only natives
+        (vec![bytes(b"abc")], call(cryptolib, "sha256")),
+        (vec![StackItem::Integer(1)], call(stdlib, "itoa")),
+    ];
+    let mut results = Vec::new();
+    for (args, script) in cases {
+        let input = neo_vm_guest::ProofInput {
+            script: script.clone(),
+            arguments: vec![neo_vm_core::StackItem::Array(
+                args.iter().map(to_host).collect(),
+            )],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: neo_vm_core::GasSchedule::NeoN3,
+            runtime: neo_vm_core::RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: neo_vm_core::BlockContext {
+                index: 7,
+                timestamp: 9,
+            },
+            oracle_responses: responses.clone(),
+        };
+        let host = neo_vm_guest::execute(input);
+
+        let mut guest = NeoVM::new(1_000_000);
+        guest.gas_schedule = GasSchedule::NeoN3;
+        guest.runtime.block_index = 7;
+        guest.oracle_responses = vec![(
+            "https://api.example/price".into(),
+            "$.neo".into(),
+            b"12.5".to_vec(),
+        )];
+        guest.load_script(script.clone()).unwrap();
+        guest.eval_stack.push(StackItem::Array(args));
+        while guest.state == VMState::Running {
+            if guest.execute_next().is_err() {
+                guest.state = VMState::Fault;
+            }
+        }
+
+        assert_eq!(
+            host.state == 0,
+            guest.state == VMState::Halt,
+            "script {script:02x?}"
+        );
+        assert_eq!(
+            host.gas_consumed, guest.gas_consumed,
+            "script {script:02x?}"
+        );
+        if host.state == 0 {
+            assert_eq!(host.result, guest.eval_stack.last().map(to_host));
+        }
+        results.push((host.state == 0).then_some(host.result).flatten());
+    }
+    // The scripts return the oracle's response and the block index
+    assert_eq!(results[0], Some(to_host(&bytes(b"12.5"))));
+    assert_eq!(results[5], Some(neo_vm_core::StackItem::Integer(7)));
+}
+
+#[test]
+fn gas_prices_agree() {
+    for host in neo_vm_core::GasSchedule::ALL {
+        let guest = GasSchedule::from_id(host.id()).unwrap();
+        for op in 0..=u8::MAX {
+            assert_eq!(
+                guest.opcode_price(op),
+                host.opcode_price(op),
+                "opcode {op:#04x}"
+            );
+            for len in [0, 1, 32, 33, 1024] {
+                assert_eq!(
+                    guest.data_price(op, len),
+                    host.data_price(op, len),
+                    "opcode {op:#04x}, {len} bytes"
+                );
+            }
+        }
+        for id in 0..=0xFF {
+            assert_eq!(
+                guest.syscall_price(id),
+                host.syscall_price(id),
+                "syscall {id:#x}"
+            );
+        }
+        for keys in [0, 1, 7] {
+            assert_eq!(
+                guest.check_multisig_price(keys),
+                host.check_multisig_price(keys)
+            );
+        }
+        for (key_len, value_len, old_len) in [
+            (1, 0, None),
+            (4, 9, None),
+            (4, 9, Some(3)),
+            (4, 3, Some(9)),
+            (4, 0, Some(9)),
+            (4, 5, Some(0)),
+        ] {
+            assert_eq!(
+                guest.storage_put_price(key_len, value_len, old_len),
+                host.storage_put_price(key_len, value_len, old_len)
+            );
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! SP1 cycle counts of the reference workloads from [`neo_vm_core::bench`]
+//!
+//! Runs each workload through the guest program in execute mode, which is
+//! cheap next to proving and deterministic, so it prints a table instead of
+//! timing anything. Needs the SP1 toolchain to build the guest ELF.
+//!
+//! Run with `cargo bench -p neo-zkvm-prover --bench sp1_cycles`.
+
+use neo_vm_core::bench::{bench_script, workloads};
+use neo_zkvm_prover::{NeoProver, ProofMode, ProverConfig};
+
+fn main() {
+    if !NeoProver::is_elf_available() {
+        println!("SP1 toolchain not installed, no guest ELF to execute");
+        return;
+    }
+
+    let prover = NeoProver::new(
+        ProverConfig::builder()
+            .proof_mode(ProofMode::Execute)
+            .build()
+            .expect("valid config"),
+    );
+
+    println!(
+        "{:<12} {:>12} {:>14} {:>12}",
+        "workload", "instructions", "sp1 cycles", "cycles/instr"
+    );
+    for workload in workloads() {
+        let host = bench_script(&workload.script, 0).expect("workload decodes");
+        match prover.prove_script(workload.script) {
+            Ok(proof) => {
+                let cycles = proof
+                    .execution_report
+                    .map_or(0, |report| report.total_cycles);
+                println!(
+                    "{:<12} {:>12} {:>14} {:>12.1}",
+                    workload.name,
+                    host.instructions,
+                    cycles,
+                    cycles as f64 / host.instructions as f64
+                );
+            }
+            Err(e) => println!("{:<12} not supported by the guest: {e}", workload.name),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Build script for SP1 integration
 //!
-//! Uses sp1-build to compile the guest program and generate the ELF binary.
+//! Uses sp1-build to compile the guest programs and generate the ELF binaries.
 //! Falls back to empty ELF if SP1 toolchain is not available.
 
 fn main() {
@@ -10,6 +10,8 @@ fn main() {
     std::fs::create_dir_all(&elf_dir).ok();
 
     let elf_path = elf_dir.join("riscv32im-succinct-zkvm-elf");
+    let light_client_elf_path = elf_dir.join("neo-zkvm-light-client-elf");
+    let arithmetic_elf_path = elf_dir.join("neo-zkvm-arithmetic-elf");
 
     // Check if SP1 toolchain is available
     let has_sp1 = std::process::Command::new("rustup")
@@ -28,13 +30,33 @@ fn main() {
         ));
 
         println!("cargo:rerun-if-changed=../neo-zkvm-program/src");
+
+        // The same guest without syscalls, for scripts that need none
+        sp1_build::build_program_with_args(
+            &format!("{}/../neo-zkvm-program", env!("CARGO_MANIFEST_DIR")),
+            sp1_build::BuildArgs {
+                no_default_features: true,
+                elf_name: Some("neo-zkvm-arithmetic-elf".to_string()),
+                output_directory: Some(elf_dir.display().to_string()),
+                ..Default::default()
+            },
+        );
+
+        sp1_build::build_program(&format!(
+            "{}/../neo-zkvm-light-client",
+            env!("CARGO_MANIFEST_DIR")
+        ));
+
+        println!("cargo:rerun-if-changed=../neo-zkvm-light-client/src");
     } else {
         println!("cargo:warning=SP1 toolchain not found, using dummy ELF");
         println!("cargo:warning=Install with: curl -L https://sp1.succinct.xyz | bash && sp1up");
 
         // Create a dummy ELF file so include_bytes! doesn't fail
-        if !elf_path.exists() {
-            std::fs::write(&elf_path, b"DUMMY_ELF_NOT_FOR_PRODUCTION").ok();
+        for path in [&elf_path, &light_client_elf_path, &arithmetic_elf_path] {
+            if !path.exists() {
+                std::fs::write(path, b"DUMMY_ELF_NOT_FOR_PRODUCTION").ok();
+            }
         }
 
         // Tell cargo we're using mock mode
@@ -0,0 +1,475 @@
+//! Proofs of executions that were already proven
+//!
+//! Sequencers prove the same view calls over and over. A [`ProofCache`]
+//! keeps the proofs a prover made, keyed by the script, its arguments and
+//! the state they ran against, so a prover with the cache set, see
+//! [`NeoProver::with_cache`](crate::NeoProver::with_cache), returns the
+//! stored proof of an identical execution instead of proving it again.
+//!
+//! Layout under the cache directory:
+//!
+//! - `<key>.proof`: the compact encoding of a cached proof, named by the hex
+//!   [`CacheKey::digest`]
+//! - `index.json`: a [`CacheEntry`] per cached proof, rewritten through a
+//!   temporary file and a rename whenever an entry is stored, served or
+//!   evicted
+//!
+//! Entries older than [`CachePolicy::max_age`] expire, and once there are
+//! more than [`CachePolicy::max_entries`] the least recently served entry is
+//! evicted.
+
+use crate::{EncodeOptions, NeoProof, ProofEncodingError, ProofMode};
+use neo_vm_guest::{hash_arguments, ProofInput};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors reading or writing a proof cache
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Corrupt proof cache {path}: {message}")]
+    Corrupt { path: PathBuf, message: String },
+    #[error(transparent)]
+    Encoding(#[from] ProofEncodingError),
+}
+
+/// What a cached proof proves
+///
+/// Two inputs with the same key produce the same public values, so either
+/// one's proof serves the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheKey {
+    /// SHA-256 of the script
+    pub script_hash: [u8; 32],
+    /// [`hash_arguments`] of the arguments
+    pub args_hash: [u8; 32],
+    /// [`ProofInput::pre_state_root`], zero for inputs with inline storage
+    pub state_root: [u8; 32],
+    /// [`ProofInput::input_hash`], which also covers the gas limit, inline
+    /// storage, context, runtime, limits and transaction hash
+    pub input_hash: [u8; 32],
+    pub mode: ProofMode,
+    /// SHA-256 of the guest ELF
+    pub elf_hash: [u8; 32],
+}
+
+impl CacheKey {
+    /// Key of proving `input`, bound to `tx_hash`, in `mode` with the guest
+    /// ELF `elf_hash`
+    pub fn new(input: &ProofInput, tx_hash: [u8; 32], mode: ProofMode, elf_hash: [u8; 32]) -> Self {
+        Self {
+            script_hash: Sha256::digest(&input.script).into(),
+            args_hash: hash_arguments(&input.arguments),
+            state_root: input.pre_state_root.unwrap_or_default(),
+            input_hash: input.input_hash(tx_hash),
+            mode,
+            elf_hash,
+        }
+    }
+
+    /// SHA-256 over every field, naming the cached proof
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.script_hash);
+        hasher.update(self.args_hash);
+        hasher.update(self.state_root);
+        hasher.update(self.input_hash);
+        hasher.update([self.mode as u8]);
+        hasher.update(self.elf_hash);
+        hasher.finalize().into()
+    }
+}
+
+/// When cached proofs are dropped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Proofs kept before the least recently served one is evicted
+    pub max_entries: usize,
+    /// Time after which a proof expires, counted from when it was stored
+    pub max_age: Option<Duration>,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            max_age: None,
+        }
+    }
+}
+
+/// A cached proof as recorded in `index.json`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub script_hash: [u8; 32],
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub args_hash: [u8; 32],
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub state_root: [u8; 32],
+    pub mode: ProofMode,
+    /// Unix time in seconds when the proof was stored
+    pub created_at: u64,
+    /// Times the proof was served from the cache
+    pub hits: u64,
+    /// Order of the last store or hit; the lowest is evicted first
+    recency: u64,
+}
+
+/// Directory of proofs by [`CacheKey`]
+///
+/// The index is kept in memory behind a lock and written through to disk,
+/// so share one cache between the provers of a process with an `Arc`.
+pub struct ProofCache {
+    dir: PathBuf,
+    policy: CachePolicy,
+    index: Mutex<BTreeMap<String, CacheEntry>>,
+}
+
+impl ProofCache {
+    /// Open `dir`, creating it if needed, and load its index
+    ///
+    /// Index entries whose proof file is missing are dropped.
+    pub fn open(dir: impl AsRef<Path>, policy: CachePolicy) -> Result<Self, CacheError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("index.json");
+        let mut index: BTreeMap<String, CacheEntry> = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| CacheError::Corrupt {
+                path: path.clone(),
+                message: e.to_string(),
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        index.retain(|name, _| dir.join(format!("{name}.proof")).exists());
+        Ok(Self {
+            dir,
+            policy,
+            index: Mutex::new(index),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn policy(&self) -> CachePolicy {
+        self.policy
+    }
+
+    /// Cached proofs, expired ones included until the next store or lookup
+    pub fn len(&self) -> usize {
+        self.index().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every cached proof's entry, by key digest
+    pub fn entries(&self) -> BTreeMap<String, CacheEntry> {
+        self.index().clone()
+    }
+
+    /// The proof cached for `key`, if any and not expired
+    pub fn get(&self, key: &CacheKey) -> Result<Option<NeoProof>, CacheError> {
+        let name = hex_name(&key.digest());
+        let mut index = self.index();
+        let expired = self.expire(&mut index);
+        let Some(recency) = next_recency(&index) else {
+            self.write_index(&index, expired)?;
+            return Ok(None);
+        };
+        let Some(entry) = index.get_mut(&name) else {
+            self.write_index(&index, expired)?;
+            return Ok(None);
+        };
+
+        let path = self.proof_path(&name);
+        let proof = match fs::read(&path) {
+            Ok(bytes) => NeoProof::from_bytes(&bytes).map_err(|e| CacheError::Corrupt {
+                path,
+                message: e.to_string(),
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                index.remove(&name);
+                self.write_index(&index, true)?;
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        entry.hits += 1;
+        entry.recency = recency;
+        self.write_index(&index, true)?;
+        Ok(Some(proof))
+    }
+
+    /// Store `proof` as the proof of `key`, evicting entries past the
+    /// policy's limits
+    pub fn insert(&self, key: &CacheKey, proof: &NeoProof) -> Result<(), CacheError> {
+        let name = hex_name(&key.digest());
+        let bytes = proof.to_bytes(EncodeOptions::compact())?;
+        write_atomic(&self.proof_path(&name), &bytes)?;
+
+        let mut index = self.index();
+        self.expire(&mut index);
+        let recency = next_recency(&index).unwrap_or(0);
+        index.insert(
+            name,
+            CacheEntry {
+                script_hash: key.script_hash,
+                args_hash: key.args_hash,
+                state_root: key.state_root,
+                mode: key.mode,
+                created_at: now(),
+                hits: 0,
+                recency,
+            },
+        );
+        while index.len() > self.policy.max_entries {
+            let Some(oldest) = index
+                .iter()
+                .min_by_key(|(_, entry)| entry.recency)
+                .map(|(name, _)| name.clone())
+            else {
+                break;
+            };
+            index.remove(&oldest);
+            self.remove_proof(&oldest)?;
+        }
+        self.write_index(&index, true)
+    }
+
+    /// Forget the proof of `key`
+    pub fn remove(&self, key: &CacheKey) -> Result<(), CacheError> {
+        let name = hex_name(&key.digest());
+        let mut index = self.index();
+        if index.remove(&name).is_some() {
+            self.write_index(&index, true)?;
+        }
+        self.remove_proof(&name)
+    }
+
+    /// Forget every cached proof
+    pub fn clear(&self) -> Result<(), CacheError> {
+        let mut index = self.index();
+        for name in std::mem::take(&mut *index).into_keys() {
+            self.remove_proof(&name)?;
+        }
+        self.write_index(&index, true)
+    }
+
+    fn index(&self) -> MutexGuard<'_, BTreeMap<String, CacheEntry>> {
+        self.index.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drop expired entries, returning whether there were any
+    fn expire(&self, index: &mut BTreeMap<String, CacheEntry>) -> bool {
+        let Some(max_age) = self.policy.max_age else {
+            return false;
+        };
+        let now = now();
+        let expired: Vec<String> = index
+            .iter()
+            .filter(|(_, entry)| now.saturating_sub(entry.created_at) >= max_age.as_secs())
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &expired {
+            index.remove(name);
+            // A proof that can't be deleted is only disk space; the index no
+            // longer serves it
+            let _ = self.remove_proof(name);
+        }
+        !expired.is_empty()
+    }
+
+    fn write_index(
+        &self,
+        index: &BTreeMap<String, CacheEntry>,
+        changed: bool,
+    ) -> Result<(), CacheError> {
+        if !changed {
+            return Ok(());
+        }
+        let json = serde_json::to_vec_pretty(index).map_err(io::Error::other)?;
+        Ok(write_atomic(&self.dir.join("index.json"), &json)?)
+    }
+
+    fn remove_proof(&self, name: &str) -> Result<(), CacheError> {
+        match fs::remove_file(self.proof_path(name)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn proof_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.proof"))
+    }
+}
+
+/// Recency for the next store or hit, `None` when the index is empty
+fn next_recency(index: &BTreeMap<String, CacheEntry>) -> Option<u64> {
+    index.values().map(|entry| entry.recency + 1).max()
+}
+
+fn hex_name(digest: &[u8; 32]) -> String {
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Write through a temporary file so readers never see a partial file
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NeoProver, ProverConfig};
+    use neo_vm_core::{
+        BlockContext, GasSchedule, OracleResponses, RuntimeConfig, StackItem, VMLimits,
+    };
+
+    fn input(argument: i64) -> ProofInput {
+        ProofInput {
+            script: vec![0x11, 0x40],
+            arguments: vec![StackItem::Integer(argument.into())],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        }
+    }
+
+    fn key(argument: i64) -> CacheKey {
+        CacheKey::new(&input(argument), [0; 32], ProofMode::Mock, [9; 32])
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("neo-proof-cache-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(key(1), key(1));
+        assert_ne!(key(1).args_hash, key(2).args_hash);
+        assert_eq!(key(1).script_hash, key(2).script_hash);
+
+        let mut rooted = input(1);
+        rooted.pre_state_root = Some([5; 32]);
+        let rooted = CacheKey::new(&rooted, [0; 32], ProofMode::Mock, [9; 32]);
+        assert_eq!(rooted.state_root, [5; 32]);
+        assert_ne!(rooted.digest(), key(1).digest());
+
+        let groth16 = CacheKey::new(&input(1), [0; 32], ProofMode::Groth16, [9; 32]);
+        assert_ne!(groth16.digest(), key(1).digest());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = temp_dir("round-trip");
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove(input(1)).unwrap();
+
+        let cache = ProofCache::open(&dir, CachePolicy::default()).unwrap();
+        assert!(cache.get(&key(1)).unwrap().is_none());
+        cache.insert(&key(1), &proof).unwrap();
+        let cached = cache.get(&key(1)).unwrap().unwrap();
+        assert_eq!(cached.proof_bytes, proof.proof_bytes);
+        assert!(cache.get(&key(2)).unwrap().is_none());
+
+        // A reopened cache serves the same proof
+        let reopened = ProofCache::open(&dir, CachePolicy::default()).unwrap();
+        assert_eq!(reopened.len(), 1);
+        let cached = reopened.get(&key(1)).unwrap().unwrap();
+        assert!(prover.verify(&cached));
+        let entries = reopened.entries();
+        let entry = entries.values().next().unwrap();
+        assert_eq!(entry.hits, 2);
+        assert_eq!(entry.args_hash, key(1).args_hash);
+
+        reopened.remove(&key(1)).unwrap();
+        assert!(reopened.is_empty());
+        assert!(ProofCache::open(&dir, CachePolicy::default())
+            .unwrap()
+            .is_empty());
+
+        std::fs::write(dir.join("index.json"), "{").unwrap();
+        assert!(matches!(
+            ProofCache::open(&dir, CachePolicy::default()),
+            Err(CacheError::Corrupt { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_eviction() {
+        let dir = temp_dir("eviction");
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove(input(1)).unwrap();
+        let policy = CachePolicy {
+            max_entries: 2,
+            max_age: None,
+        };
+        let cache = ProofCache::open(&dir, policy).unwrap();
+        cache.insert(&key(1), &proof).unwrap();
+        cache.insert(&key(2), &proof).unwrap();
+        // Serving 1 makes 2 the least recently used
+        cache.get(&key(1)).unwrap().unwrap();
+        cache.insert(&key(3), &proof).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key(2)).unwrap().is_none());
+        assert!(cache.get(&key(1)).unwrap().is_some());
+        assert!(!dir
+            .join(format!("{}.proof", hex_name(&key(2).digest())))
+            .exists());
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Proofs expire once they are max_age old
+        let dir = temp_dir("expiry");
+        let policy = CachePolicy {
+            max_entries: 2,
+            max_age: Some(Duration::ZERO),
+        };
+        let cache = ProofCache::open(&dir, policy).unwrap();
+        cache.insert(&key(1), &proof).unwrap();
+        assert!(cache.get(&key(1)).unwrap().is_none());
+        assert!(cache.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
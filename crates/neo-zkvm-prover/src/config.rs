@@ -0,0 +1,472 @@
+//! Prover configuration
+//!
+//! [`ProverConfig`] is built with [`ProverConfig::builder`], which rejects
+//! combinations that cannot work or would be silently ignored, such as a
+//! custom guest ELF together with a mode that never runs SP1.
+
+use crate::{ProofMode, NEO_ZKVM_ELF, TEST_MOCK_KEY};
+use sha2::{Digest, Sha256};
+use sp1_prover::components::CpuProverComponents;
+use sp1_sdk::{Prover, ProverClient};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Where SP1 generates proofs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProverBackend {
+    /// Chosen by the `SP1_PROVER` environment variable, CPU if unset
+    #[default]
+    Env,
+    /// Local CPU prover
+    Cpu,
+    /// Local GPU prover
+    Cuda,
+    /// Succinct prover network, authenticated by `NETWORK_PRIVATE_KEY`
+    Network,
+}
+
+impl ProverBackend {
+    /// Whether proofs are requested from the prover network, directly or
+    /// through `SP1_PROVER=network`
+    pub fn is_network(self) -> bool {
+        match self {
+            ProverBackend::Network => true,
+            ProverBackend::Env => {
+                std::env::var("SP1_PROVER").is_ok_and(|prover| prover == "network")
+            }
+            ProverBackend::Cpu | ProverBackend::Cuda => false,
+        }
+    }
+
+    pub(crate) fn client(self) -> Box<dyn Prover<CpuProverComponents>> {
+        match self {
+            ProverBackend::Env => Box::new(ProverClient::from_env()),
+            ProverBackend::Cpu => Box::new(ProverClient::builder().cpu().build()),
+            ProverBackend::Cuda => Box::new(ProverClient::builder().cuda().build()),
+            ProverBackend::Network => Box::new(ProverClient::builder().network().build()),
+        }
+    }
+}
+
+impl std::str::FromStr for ProverBackend {
+    type Err = String;
+
+    /// Lowercase variant name, e.g. `cpu`
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "env" => Ok(ProverBackend::Env),
+            "cpu" => Ok(ProverBackend::Cpu),
+            "cuda" => Ok(ProverBackend::Cuda),
+            "network" => Ok(ProverBackend::Network),
+            _ => Err(format!(
+                "Unknown prover backend '{s}', expected env, cpu, cuda or network"
+            )),
+        }
+    }
+}
+
+/// A [`ProverConfig`] limit that stopped a proof
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceLimit {
+    #[error("proving did not finish within {0:?}")]
+    ProvingTime(Duration),
+    #[error("guest program ran past {0} cycles")]
+    Cycles(u64),
+    #[error("guest program touched {used} bytes of memory, limit is {limit}")]
+    Memory { used: u64, limit: u64 },
+}
+
+/// Why a prover configuration was rejected
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("max_cycles must be greater than zero")]
+    ZeroMaxCycles,
+    #[error("Default gas limit must be greater than zero")]
+    ZeroGasLimit,
+    #[error("Maximum proving time must be greater than zero")]
+    ZeroProvingTime,
+    #[error("max_memory_bytes must be greater than zero")]
+    ZeroMaxMemory,
+    #[error("Guest program is not an ELF file")]
+    InvalidElf,
+    #[error("Failed to read guest ELF {path}: {message}")]
+    ElfRead { path: PathBuf, message: String },
+    /// A setting that only applies to SP1 proofs was combined with a mode
+    /// that does not generate them
+    #[error("{setting} has no effect in {mode:?} mode")]
+    UnusedSetting {
+        setting: &'static str,
+        mode: ProofMode,
+    },
+}
+
+/// Prover configuration
+#[derive(Clone, Debug)]
+pub struct ProverConfig {
+    max_cycles: u64,
+    proof_mode: ProofMode,
+    default_gas_limit: u64,
+    guest_elf: Option<Arc<[u8]>>,
+    backend: ProverBackend,
+    max_proving_time: Option<Duration>,
+    max_memory_bytes: Option<u64>,
+    mock_key: MockKey,
+}
+
+/// HMAC key for mock proofs, kept out of `Debug` output
+#[derive(Clone)]
+struct MockKey([u8; 32]);
+
+impl std::fmt::Debug for MockKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 == TEST_MOCK_KEY {
+            f.write_str("TEST_MOCK_KEY")
+        } else {
+            f.write_str("<redacted>")
+        }
+    }
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        Self {
+            max_cycles: 10_000_000,
+            proof_mode: ProofMode::Sp1,
+            default_gas_limit: 1_000_000,
+            guest_elf: None,
+            backend: ProverBackend::Env,
+            max_proving_time: None,
+            max_memory_bytes: None,
+            mock_key: MockKey(TEST_MOCK_KEY),
+        }
+    }
+}
+
+impl ProverConfig {
+    pub fn builder() -> ProverConfigBuilder {
+        ProverConfigBuilder::default()
+    }
+
+    /// Maximum cycles for SP1 execution
+    pub fn max_cycles(&self) -> u64 {
+        self.max_cycles
+    }
+
+    /// Proof mode (determines proof type and verification cost)
+    pub fn proof_mode(&self) -> ProofMode {
+        self.proof_mode
+    }
+
+    /// Gas limit of scripts proven with [`crate::NeoProver::prove_script`]
+    pub fn default_gas_limit(&self) -> u64 {
+        self.default_gas_limit
+    }
+
+    /// Guest program proofs are generated with
+    pub fn guest_elf(&self) -> &[u8] {
+        self.guest_elf.as_deref().unwrap_or(NEO_ZKVM_ELF)
+    }
+
+    /// SHA-256 of [`guest_elf`](Self::guest_elf), recorded in
+    /// [`crate::ProofMetadata::elf_hash`]
+    pub fn guest_elf_hash(&self) -> [u8; 32] {
+        Sha256::digest(self.guest_elf()).into()
+    }
+
+    /// Whether [`guest_elf`](Self::guest_elf) was overridden
+    pub fn has_custom_elf(&self) -> bool {
+        self.guest_elf.is_some()
+    }
+
+    pub fn backend(&self) -> ProverBackend {
+        self.backend
+    }
+
+    /// How long SP1 may take for one proof
+    pub fn max_proving_time(&self) -> Option<Duration> {
+        self.max_proving_time
+    }
+
+    /// Guest memory SP1 execution may touch, in bytes
+    pub fn max_memory_bytes(&self) -> Option<u64> {
+        self.max_memory_bytes
+    }
+
+    /// Key of the HMAC that authenticates mock proofs
+    pub fn mock_key(&self) -> &[u8; 32] {
+        &self.mock_key.0
+    }
+
+    /// Check the configuration for invalid or ineffective settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_cycles == 0 {
+            return Err(ConfigError::ZeroMaxCycles);
+        }
+        if self.default_gas_limit == 0 {
+            return Err(ConfigError::ZeroGasLimit);
+        }
+        if self.max_proving_time == Some(Duration::ZERO) {
+            return Err(ConfigError::ZeroProvingTime);
+        }
+        if self.max_memory_bytes == Some(0) {
+            return Err(ConfigError::ZeroMaxMemory);
+        }
+        if let Some(elf) = &self.guest_elf {
+            if !elf.starts_with(b"\x7fELF") {
+                return Err(ConfigError::InvalidElf);
+            }
+        }
+
+        // Execute mode runs the guest program under SP1 but proves nothing
+        if matches!(self.proof_mode, ProofMode::Execute | ProofMode::Mock) {
+            let unused = if self.guest_elf.is_some() && self.proof_mode == ProofMode::Mock {
+                Some("Custom guest ELF")
+            } else if self.backend != ProverBackend::Env {
+                Some("Prover backend")
+            } else if self.max_proving_time.is_some() {
+                Some("Maximum proving time")
+            } else if self.max_memory_bytes.is_some() && self.proof_mode == ProofMode::Mock {
+                Some("Memory limit")
+            } else {
+                None
+            };
+            if let Some(setting) = unused {
+                return Err(ConfigError::UnusedSetting {
+                    setting,
+                    mode: self.proof_mode,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`ProverConfig`]
+///
+/// ```rust
+/// use neo_zkvm_prover::{ProofMode, ProverConfig};
+///
+/// let config = ProverConfig::builder()
+///     .proof_mode(ProofMode::Mock)
+///     .default_gas_limit(500_000)
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.default_gas_limit(), 500_000);
+///
+/// // A custom guest program is never run without SP1
+/// assert!(ProverConfig::builder()
+///     .proof_mode(ProofMode::Mock)
+///     .guest_elf(b"\x7fELF...".to_vec())
+///     .build()
+///     .is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct ProverConfigBuilder {
+    config: ProverConfig,
+    elf_path: Option<PathBuf>,
+}
+
+impl ProverConfigBuilder {
+    pub fn max_cycles(mut self, max_cycles: u64) -> Self {
+        self.config.max_cycles = max_cycles;
+        self
+    }
+
+    pub fn proof_mode(mut self, mode: ProofMode) -> Self {
+        self.config.proof_mode = mode;
+        self
+    }
+
+    pub fn default_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.config.default_gas_limit = gas_limit;
+        self
+    }
+
+    /// Prove with this guest program instead of the embedded one, e.g. a
+    /// fork with extra syscalls
+    pub fn guest_elf(mut self, elf: impl Into<Vec<u8>>) -> Self {
+        self.config.guest_elf = Some(elf.into().into());
+        self.elf_path = None;
+        self
+    }
+
+    /// Like [`guest_elf`](Self::guest_elf), read from `path` on build
+    pub fn guest_elf_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.elf_path = Some(path.as_ref().to_path_buf());
+        self.config.guest_elf = None;
+        self
+    }
+
+    pub fn backend(mut self, backend: ProverBackend) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
+    /// Abort proofs SP1 has not finished after `time`
+    pub fn max_proving_time(mut self, time: Duration) -> Self {
+        self.config.max_proving_time = Some(time);
+        self
+    }
+
+    /// Abort before proving when the guest program touches more than
+    /// `bytes` of memory under SP1 execution
+    pub fn max_memory_bytes(mut self, bytes: u64) -> Self {
+        self.config.max_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Authenticate mock proofs with this key instead of the public
+    /// [`TEST_MOCK_KEY`]; verifiers need the same key
+    pub fn mock_key(mut self, key: [u8; 32]) -> Self {
+        self.config.mock_key = MockKey(key);
+        self
+    }
+
+    /// Load the guest ELF if given by path and validate the configuration
+    pub fn build(self) -> Result<ProverConfig, ConfigError> {
+        let mut config = self.config;
+        if let Some(path) = self.elf_path {
+            let elf = std::fs::read(&path).map_err(|e| ConfigError::ElfRead {
+                path,
+                message: e.to_string(),
+            })?;
+            config.guest_elf = Some(elf.into());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let config = ProverConfig::builder().build().unwrap();
+        assert_eq!(config.proof_mode(), ProofMode::Sp1);
+        assert_eq!(config.max_cycles(), 10_000_000);
+        assert_eq!(config.backend(), ProverBackend::Env);
+        assert!(!config.has_custom_elf());
+        assert_eq!(config.guest_elf(), NEO_ZKVM_ELF);
+    }
+
+    #[test]
+    fn test_validate_rejects_nonsense() {
+        let err = |builder: ProverConfigBuilder| builder.build().unwrap_err();
+
+        assert_eq!(
+            err(ProverConfig::builder().max_cycles(0)),
+            ConfigError::ZeroMaxCycles
+        );
+        assert_eq!(
+            err(ProverConfig::builder().default_gas_limit(0)),
+            ConfigError::ZeroGasLimit
+        );
+        assert_eq!(
+            err(ProverConfig::builder().max_proving_time(Duration::ZERO)),
+            ConfigError::ZeroProvingTime
+        );
+        assert_eq!(
+            err(ProverConfig::builder().max_memory_bytes(0)),
+            ConfigError::ZeroMaxMemory
+        );
+        assert_eq!(
+            err(ProverConfig::builder().guest_elf(b"DUMMY".to_vec())),
+            ConfigError::InvalidElf
+        );
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .backend(ProverBackend::Cuda)),
+            ConfigError::UnusedSetting {
+                setting: "Prover backend",
+                mode: ProofMode::Execute,
+            }
+        );
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .max_memory_bytes(1 << 20)),
+            ConfigError::UnusedSetting {
+                setting: "Memory limit",
+                mode: ProofMode::Mock,
+            }
+        );
+    }
+
+    #[test]
+    fn test_sp1_only_settings() {
+        let err = |builder: ProverConfigBuilder| builder.build().unwrap_err();
+        let unused = |setting, mode| ConfigError::UnusedSetting { setting, mode };
+
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .max_proving_time(Duration::from_secs(60))),
+            unused("Maximum proving time", ProofMode::Execute)
+        );
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .max_proving_time(Duration::from_secs(60))),
+            unused("Maximum proving time", ProofMode::Mock)
+        );
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .guest_elf(b"\x7fELF".to_vec())),
+            unused("Custom guest ELF", ProofMode::Mock)
+        );
+        // The backend is checked before the proving time
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .backend(ProverBackend::Cpu)
+                .max_proving_time(Duration::from_secs(60))),
+            unused("Prover backend", ProofMode::Mock)
+        );
+        // A zero time is rejected whatever the mode
+        assert_eq!(
+            err(ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .max_proving_time(Duration::ZERO)),
+            ConfigError::ZeroProvingTime
+        );
+
+        // Execute mode runs SP1, so its memory limit and ELF apply
+        let config = ProverConfig::builder()
+            .proof_mode(ProofMode::Execute)
+            .max_memory_bytes(1 << 20)
+            .guest_elf(b"\x7fELF".to_vec())
+            .build()
+            .unwrap();
+        assert_eq!(config.max_memory_bytes(), Some(1 << 20));
+        let config = ProverConfig::builder()
+            .backend(ProverBackend::Cpu)
+            .max_proving_time(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(config.max_proving_time(), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_guest_elf_path() {
+        let path = std::env::temp_dir().join("neo-zkvm-prover-test-elf");
+        std::fs::write(&path, b"\x7fELF\x01\x01").unwrap();
+        let config = ProverConfig::builder()
+            .guest_elf_path(&path)
+            .build()
+            .unwrap();
+        assert!(config.has_custom_elf());
+        assert_eq!(config.guest_elf(), b"\x7fELF\x01\x01");
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            ProverConfig::builder().guest_elf_path(&path).build(),
+            Err(ConfigError::ElfRead { .. })
+        ));
+    }
+}
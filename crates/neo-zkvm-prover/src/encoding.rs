@@ -0,0 +1,332 @@
+//! Versioned wire format for [`NeoProof`]
+//!
+//! An encoded proof starts with a header: the magic `NZKP`, the format
+//! version, the payload encoding and the compression applied to
+//! `proof_bytes`. Readers take both options from the header, so an archive
+//! may mix encodings. Input without the magic is read as the plain fixint
+//! bincode used before the header existed.
+
+use crate::{NeoProof, BINCODE_LIMIT};
+use bincode::Options;
+use std::io::Read;
+use thiserror::Error;
+
+/// First bytes of every encoded proof
+pub const PROOF_MAGIC: [u8; 4] = *b"NZKP";
+
+/// Current wire format version
+///
+/// Version 2 added [`crate::PublicInputs::gas_schedule`], version 3
+/// [`crate::PublicInputs::runtime`], version 4
+/// [`crate::PublicInputs::random_draws`], version 5
+/// `ProofOutput::notifications`, version 6 `ProofOutput::fault`, version 7
+/// the canonical `input_hash` of `ProofInput::input_hash`, version 8
+/// [`crate::NeoProof::metadata`], version 9
+/// [`crate::PublicInputs::block_context`], version 10
+/// [`crate::PublicInputs::oracle_hash`] and version 11 the fixed
+/// `input_hash` layout that hashes every input field.
+pub const PROOF_FORMAT_VERSION: u8 = 11;
+
+const HEADER_LEN: usize = PROOF_MAGIC.len() + 3;
+
+/// Bincode integer encoding of the proof payload
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofEncoding {
+    /// Fixed-width integers, the layout of `bincode::serialize`
+    #[default]
+    Fixint,
+    /// Variable-length integers; smaller, mostly for lengths and gas values
+    Varint,
+}
+
+/// Compression applied to [`NeoProof::proof_bytes`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofCompression {
+    #[default]
+    None,
+    Zstd,
+}
+
+/// How [`NeoProof::to_bytes`] lays out a proof
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    pub encoding: ProofEncoding,
+    pub compression: ProofCompression,
+}
+
+impl EncodeOptions {
+    /// Varint payload with zstd-compressed proof bytes, for archives and
+    /// network transfer
+    pub fn compact() -> Self {
+        Self {
+            encoding: ProofEncoding::Varint,
+            compression: ProofCompression::Zstd,
+        }
+    }
+}
+
+/// Why a proof could not be encoded or decoded
+#[derive(Error, Debug)]
+pub enum ProofEncodingError {
+    #[error("Proof header is truncated")]
+    Truncated,
+    #[error("Unsupported proof format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Unknown proof encoding {0}")]
+    UnknownEncoding(u8),
+    #[error("Unknown proof compression {0}")]
+    UnknownCompression(u8),
+    #[error("Malformed proof: {0}")]
+    Malformed(#[from] bincode::Error),
+    /// The decompressed proof bytes would exceed the size limit
+    #[error("Proof bytes are {size} bytes, limit is {limit}")]
+    ProofTooLarge { size: u64, limit: u64 },
+    /// zstd failed, or the proof bytes decompress past the size limit
+    #[error("Proof compression failed: {0}")]
+    Compression(std::io::Error),
+    #[error("Failed to read proof: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl ProofEncoding {
+    fn tag(self) -> u8 {
+        match self {
+            Self::Fixint => 0,
+            Self::Varint => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, ProofEncodingError> {
+        match tag {
+            0 => Ok(Self::Fixint),
+            1 => Ok(Self::Varint),
+            _ => Err(ProofEncodingError::UnknownEncoding(tag)),
+        }
+    }
+
+    fn serialize(self, proof: &NeoProof) -> bincode::Result<Vec<u8>> {
+        let options = bincode::DefaultOptions::new().with_limit(BINCODE_LIMIT);
+        match self {
+            Self::Fixint => options.with_fixint_encoding().serialize(proof),
+            Self::Varint => options.with_varint_encoding().serialize(proof),
+        }
+    }
+
+    fn deserialize_from(self, reader: impl Read, limit: u64) -> bincode::Result<NeoProof> {
+        let options = bincode::DefaultOptions::new().with_limit(limit);
+        match self {
+            Self::Fixint => options.with_fixint_encoding().deserialize_from(reader),
+            Self::Varint => options.with_varint_encoding().deserialize_from(reader),
+        }
+    }
+}
+
+impl ProofCompression {
+    fn tag(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zstd => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, ProofEncodingError> {
+        match tag {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Zstd),
+            _ => Err(ProofEncodingError::UnknownCompression(tag)),
+        }
+    }
+}
+
+impl NeoProof {
+    /// Encode the proof with a versioned header
+    pub fn to_bytes(&self, options: EncodeOptions) -> Result<Vec<u8>, ProofEncodingError> {
+        let payload = match options.compression {
+            ProofCompression::None => options.encoding.serialize(self)?,
+            ProofCompression::Zstd => {
+                let proof_bytes =
+                    zstd::bulk::compress(&self.proof_bytes, zstd::DEFAULT_COMPRESSION_LEVEL)
+                        .map_err(ProofEncodingError::Compression)?;
+                options.encoding.serialize(&NeoProof {
+                    proof_bytes,
+                    ..self.clone()
+                })?
+            }
+        };
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+        bytes.extend_from_slice(&PROOF_MAGIC);
+        bytes.push(PROOF_FORMAT_VERSION);
+        bytes.push(options.encoding.tag());
+        bytes.push(options.compression.tag());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Decode a proof written by [`NeoProof::to_bytes`] or as headerless
+    /// fixint bincode
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofEncodingError> {
+        Self::read_from(bytes, BINCODE_LIMIT)
+    }
+
+    /// Like [`NeoProof::from_bytes`], streaming from `reader`
+    ///
+    /// Decoding fails with [`bincode::ErrorKind::SizeLimit`] once the
+    /// payload would exceed `limit`, and with
+    /// [`ProofEncodingError::ProofTooLarge`] if the decompressed proof bytes
+    /// would.
+    pub fn read_from(mut reader: impl Read, limit: u64) -> Result<Self, ProofEncodingError> {
+        let mut magic = [0u8; PROOF_MAGIC.len()];
+        let read = read_up_to(&mut reader, &mut magic)?;
+        if magic[..read] != PROOF_MAGIC {
+            let headerless = (&magic[..read]).chain(reader);
+            return Ok(ProofEncoding::Fixint.deserialize_from(headerless, limit)?);
+        }
+
+        let mut header = [0u8; HEADER_LEN - PROOF_MAGIC.len()];
+        if read_up_to(&mut reader, &mut header)? < header.len() {
+            return Err(ProofEncodingError::Truncated);
+        }
+        let [version, encoding, compression] = header;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(ProofEncodingError::UnsupportedVersion(version));
+        }
+        let encoding = ProofEncoding::from_tag(encoding)?;
+        let compression = ProofCompression::from_tag(compression)?;
+
+        let mut proof = encoding.deserialize_from(reader, limit)?;
+        if compression == ProofCompression::Zstd {
+            // `to_bytes` records the decompressed size in the zstd frame
+            let size = zstd::zstd_safe::get_frame_content_size(&proof.proof_bytes)
+                .ok()
+                .flatten()
+                .ok_or_else(|| {
+                    ProofEncodingError::Compression(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "zstd frame has no content size",
+                    ))
+                })?;
+            if size > limit {
+                return Err(ProofEncodingError::ProofTooLarge { size, limit });
+            }
+            proof.proof_bytes = zstd::bulk::decompress(&proof.proof_bytes, size as usize)
+                .map_err(ProofEncodingError::Compression)?;
+        }
+        Ok(proof)
+    }
+}
+
+/// Fill as much of `buf` as `reader` has, returning the bytes read
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NeoProver, ProofMode, ProverConfig};
+
+    fn sample_proof() -> NeoProof {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let mut proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        // Stand-in for SP1 proof bytes, which are large and repetitive
+        proof.proof_bytes = (0..4096u32).map(|i| (i % 7) as u8).collect();
+        proof
+    }
+
+    fn assert_same(a: &NeoProof, b: &NeoProof) {
+        assert_eq!(
+            bincode::serialize(a).unwrap(),
+            bincode::serialize(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip_all_options() {
+        let proof = sample_proof();
+        for encoding in [ProofEncoding::Fixint, ProofEncoding::Varint] {
+            for compression in [ProofCompression::None, ProofCompression::Zstd] {
+                let options = EncodeOptions {
+                    encoding,
+                    compression,
+                };
+                let bytes = proof.to_bytes(options).unwrap();
+                assert_same(&NeoProof::from_bytes(&bytes).unwrap(), &proof);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_is_smaller() {
+        let proof = sample_proof();
+        let plain = proof.to_bytes(EncodeOptions::default()).unwrap();
+        let compact = proof.to_bytes(EncodeOptions::compact()).unwrap();
+        assert!(compact.len() < plain.len() / 4);
+    }
+
+    #[test]
+    fn test_headerless_bincode() {
+        let proof = sample_proof();
+        let legacy = bincode::serialize(&proof).unwrap();
+        assert_same(&NeoProof::from_bytes(&legacy).unwrap(), &proof);
+    }
+
+    #[test]
+    fn test_read_from_enforces_limit() {
+        let proof = sample_proof();
+        for options in [EncodeOptions::default(), EncodeOptions::compact()] {
+            let bytes = proof.to_bytes(options).unwrap();
+            assert_same(&NeoProof::read_from(&bytes[..], 8192).unwrap(), &proof);
+        }
+
+        let plain = proof.to_bytes(EncodeOptions::default()).unwrap();
+        assert!(matches!(
+            NeoProof::read_from(&plain[..], 1024),
+            Err(ProofEncodingError::Malformed(e)) if matches!(*e, bincode::ErrorKind::SizeLimit)
+        ));
+        let compact = proof.to_bytes(EncodeOptions::compact()).unwrap();
+        assert!(matches!(
+            NeoProof::read_from(&compact[..], 1024),
+            Err(ProofEncodingError::ProofTooLarge {
+                size: 4096,
+                limit: 1024
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_bad_header() {
+        let mut bytes = sample_proof().to_bytes(EncodeOptions::compact()).unwrap();
+        assert!(matches!(
+            NeoProof::from_bytes(&bytes[..5]),
+            Err(ProofEncodingError::Truncated)
+        ));
+
+        bytes[4] = PROOF_FORMAT_VERSION + 1;
+        assert!(matches!(
+            NeoProof::from_bytes(&bytes),
+            Err(ProofEncodingError::UnsupportedVersion(_))
+        ));
+
+        bytes[4] = PROOF_FORMAT_VERSION;
+        bytes[6] = 9;
+        assert!(matches!(
+            NeoProof::from_bytes(&bytes),
+            Err(ProofEncodingError::UnknownCompression(9))
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! Outstanding prover network requests
+//!
+//! A proof requested from the Succinct prover network keeps being generated
+//! after the process that asked for it exits. A [`ProofJobStore`] records
+//! each request as `<request id in hex>.json` in a directory, from the
+//! moment the network accepts it until its proof is fetched. A prover with
+//! the store set, see [`NeoProver::with_job_store`](crate::NeoProver::with_job_store),
+//! polls a recorded request instead of paying for a new one when asked to
+//! prove the same input again, and [`NeoProver::resume`](crate::NeoProver::resume)
+//! fetches the proof of a recorded job directly.
+
+use crate::ProofMode;
+use neo_vm_guest::ProofInput;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors reading or writing a job store
+#[derive(Debug, Error)]
+pub enum JobStoreError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Corrupt proof job {path}: {message}")]
+    Corrupt { path: PathBuf, message: String },
+}
+
+impl From<JobStoreError> for crate::ProverError {
+    fn from(e: JobStoreError) -> Self {
+        crate::ProverError::JobStore(e.to_string())
+    }
+}
+
+/// A proof requested from the prover network, as persisted in the store
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProofJob {
+    /// Network request id
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub request_id: [u8; 32],
+    pub mode: ProofMode,
+    pub input: ProofInput,
+    /// Transaction the proof is bound to, zero for plain scripts
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub tx_hash: [u8; 32],
+    /// Payload the proof checks signatures against, for witness proofs
+    #[serde(default)]
+    pub sign_data: Option<Vec<u8>>,
+    /// [`ProofInput::input_hash`] of `input` and `tx_hash`
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub input_hash: [u8; 32],
+    /// SHA-256 of the guest ELF the proof was requested for
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub elf_hash: [u8; 32],
+    /// Unix time in seconds when the proof was requested
+    pub requested_at: u64,
+}
+
+impl ProofJob {
+    /// Whether this job proves the same statement with the same program
+    pub(crate) fn matches(
+        &self,
+        input_hash: &[u8; 32],
+        mode: ProofMode,
+        elf_hash: &[u8; 32],
+    ) -> bool {
+        self.input_hash == *input_hash && self.mode == mode && self.elf_hash == *elf_hash
+    }
+}
+
+/// Directory of outstanding [`ProofJob`]s
+///
+/// Every call reads or writes the directory, so several provers, or a
+/// restarted process, share the same view of it.
+#[derive(Clone, Debug)]
+pub struct ProofJobStore {
+    dir: PathBuf,
+}
+
+impl ProofJobStore {
+    /// Open `dir`, creating it if needed
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, JobStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Record `job`, replacing a job with the same request id
+    pub fn save(&self, job: &ProofJob) -> Result<(), JobStoreError> {
+        let json = serde_json::to_vec_pretty(job).map_err(io::Error::other)?;
+        let path = self.job_path(&job.request_id);
+        // Write through a temporary file so readers never see a partial job
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        Ok(fs::rename(tmp, path)?)
+    }
+
+    pub fn get(&self, request_id: &[u8; 32]) -> Result<Option<ProofJob>, JobStoreError> {
+        let path = self.job_path(request_id);
+        match fs::read(&path) {
+            Ok(bytes) => Self::parse(&path, &bytes).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Forget a job, e.g. once its proof was fetched or the network gave up
+    /// on it
+    pub fn remove(&self, request_id: &[u8; 32]) -> Result<(), JobStoreError> {
+        match fs::remove_file(self.job_path(request_id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every recorded job, oldest request first
+    pub fn outstanding(&self) -> Result<Vec<ProofJob>, JobStoreError> {
+        let mut jobs = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            jobs.push(Self::parse(&path, &fs::read(&path)?)?);
+        }
+        jobs.sort_by_key(|job: &ProofJob| (job.requested_at, job.request_id));
+        Ok(jobs)
+    }
+
+    /// Oldest job proving `input_hash` in `mode` with the guest ELF
+    /// `elf_hash`
+    pub(crate) fn find(
+        &self,
+        input_hash: &[u8; 32],
+        mode: ProofMode,
+        elf_hash: &[u8; 32],
+    ) -> Result<Option<ProofJob>, JobStoreError> {
+        Ok(self
+            .outstanding()?
+            .into_iter()
+            .find(|job| job.matches(input_hash, mode, elf_hash)))
+    }
+
+    fn parse(path: &Path, bytes: &[u8]) -> Result<ProofJob, JobStoreError> {
+        serde_json::from_slice(bytes).map_err(|e| JobStoreError::Corrupt {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    fn job_path(&self, request_id: &[u8; 32]) -> PathBuf {
+        let name: String = request_id.iter().map(|b| format!("{b:02x}")).collect();
+        self.dir.join(format!("{name}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
+
+    fn job(request_id: u8, requested_at: u64) -> ProofJob {
+        let input = ProofInput {
+            script: vec![0x11, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        ProofJob {
+            request_id: [request_id; 32],
+            mode: ProofMode::Groth16,
+            input_hash: input.input_hash([0; 32]),
+            input,
+            tx_hash: [0; 32],
+            sign_data: None,
+            elf_hash: [9; 32],
+            requested_at,
+        }
+    }
+
+    #[test]
+    fn test_job_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("neo-proof-jobs-{}", std::process::id()));
+        let store = ProofJobStore::open(&dir).unwrap();
+        assert!(store.outstanding().unwrap().is_empty());
+
+        store.save(&job(2, 20)).unwrap();
+        store.save(&job(1, 10)).unwrap();
+        let jobs = store.outstanding().unwrap();
+        assert_eq!(
+            jobs.iter().map(|job| job.request_id[0]).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(jobs[0].input.script, [0x11, 0x40]);
+
+        // A reopened store sees the same jobs
+        let reopened = ProofJobStore::open(&dir).unwrap();
+        let input_hash = jobs[0].input_hash;
+        let found = reopened
+            .find(&input_hash, ProofMode::Groth16, &[9; 32])
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.request_id, [1; 32]);
+        assert!(reopened
+            .find(&input_hash, ProofMode::Plonk, &[9; 32])
+            .unwrap()
+            .is_none());
+
+        reopened.remove(&[1; 32]).unwrap();
+        reopened.remove(&[1; 32]).unwrap();
+        assert!(store.get(&[1; 32]).unwrap().is_none());
+        assert_eq!(store.outstanding().unwrap().len(), 1);
+
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        assert!(matches!(
+            store.outstanding(),
+            Err(JobStoreError::Corrupt { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
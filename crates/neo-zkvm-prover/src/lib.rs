@@ -6,6 +6,7 @@
 //!
 //! ```rust
 //! use neo_zkvm_prover::{NeoProver, ProverConfig, ProofMode};
+//! use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
 //! use neo_vm_guest::ProofInput;
 //!
 //! // Create prover with default config
@@ -16,17 +17,75 @@
 //!     script: vec![0x12, 0x13, 0x9E, 0x40], // 2 + 3
 //!     arguments: vec![],
 //!     gas_limit: 1_000_000,
+//!     storage: Vec::new(),
+//!     context: [0; 32],
+//!     gas_schedule: GasSchedule::Legacy,
+//!     runtime: RuntimeConfig::default(),
+//!     pre_state_root: None,
+//!     storage_witnesses: Vec::new(),
+//!     limits: VMLimits::default(),
+//!     block_context: BlockContext::default(),
+//!     oracle_responses: OracleResponses::default(),
 //! };
 //!
 //! // Generate proof
-//! let proof = prover.prove(input);
+//! let proof = prover.prove(input).unwrap();
 //! ```
 
 use bincode::Options;
-use neo_vm_guest::{execute, ProofInput, ProofOutput};
+use hmac::{Hmac, Mac};
+use neo_vm_core::engine::syscall;
+use neo_vm_core::witness::{self, WitnessError, MAX_VERIFICATION_GAS};
+use neo_vm_core::{
+    decode_script, validate_script, BlockContext, CryptoLib, GasSchedule, NativeContract,
+    OracleResponses, RuntimeConfig, StdLib, TrieStorage, VMLimits, ValidationIssue,
+};
+use neo_vm_guest::block::{merkle_root, BlockHeader};
+use neo_vm_guest::light_client::{
+    verify_header_chain, LightClientError, LightClientInput, LightClientOutput,
+};
+use neo_vm_guest::tx::{Transaction, TxError};
+use neo_vm_guest::{
+    execute, execute_signed, ProofInput, ProofOutput, StorageWitness, StorageWitnessError,
+};
+use progress::Progress;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use sp1_sdk::{ProverClient, SP1ProofMode, SP1PublicValues, SP1Stdin};
+use sp1_core_executor::ExecutionError;
+use sp1_prover::components::CpuProverComponents;
+use sp1_sdk::network::B256;
+use sp1_sdk::{
+    Prover, ProverClient, SP1Context, SP1Proof, SP1ProofMode, SP1ProofWithPublicValues,
+    SP1ProvingKey, SP1PublicValues, SP1Stdin,
+};
+use sp1_stark::SP1ProverOpts;
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{info_span, warn};
+
+pub mod cache;
+pub mod config;
+pub mod encoding;
+pub mod jobs;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod program;
+pub mod progress;
+pub mod report;
+#[cfg(feature = "neo-rpc")]
+pub mod rpc;
+
+pub use cache::{CacheEntry, CacheError, CacheKey, CachePolicy, ProofCache};
+pub use config::{ConfigError, ProverBackend, ProverConfig, ProverConfigBuilder, ResourceLimit};
+pub use encoding::{EncodeOptions, ProofCompression, ProofEncoding, ProofEncodingError};
+pub use jobs::{JobStoreError, ProofJob, ProofJobStore};
+#[cfg(feature = "metrics")]
+pub use metrics::ProverMetrics;
+pub use program::ProgramId;
+pub use progress::{ProgressCallback, ProgressEvent, ProofStage};
+pub use report::{ExecutionReport, InstructionCategory, PreflightReport};
 
 /// SP1 ELF binary - embedded at compile time
 /// This is the compiled guest program that runs inside SP1 zkVM
@@ -36,6 +95,24 @@ use sp1_sdk::{ProverClient, SP1ProofMode, SP1PublicValues, SP1Stdin};
 pub const NEO_ZKVM_ELF: &[u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/elf/riscv32im-succinct-zkvm-elf"));
 
+/// SP1 ELF of the light-client guest program, a dummy value like
+/// [`NEO_ZKVM_ELF`] when the SP1 toolchain is not installed
+pub const NEO_LIGHT_CLIENT_ELF: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/elf/neo-zkvm-light-client-elf"));
+
+/// SP1 ELF of the guest program built without syscalls, see
+/// [`ProgramId::Arithmetic`]; a dummy value like [`NEO_ZKVM_ELF`] when the
+/// SP1 toolchain is not installed
+pub const NEO_ZKVM_ARITHMETIC_ELF: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/elf/neo-zkvm-arithmetic-elf"));
+
+/// Default key of the HMAC on mock proofs
+///
+/// This key is public, so anyone can forge mock proofs made with it. Set a
+/// private key with [`ProverConfigBuilder::mock_key`] wherever a mock proof
+/// must show that it came from your prover.
+pub const TEST_MOCK_KEY: [u8; 32] = *b"neo-zkvm insecure mock proof key";
+
 const BINCODE_LIMIT: u64 = 10 * 1024 * 1024; // 10MB limit
 
 fn bincode_options() -> impl Options {
@@ -57,36 +134,225 @@ pub struct NeoProof {
     pub vkey_hash: [u8; 32],
     /// Proof mode used
     pub proof_mode: ProofMode,
+    /// SP1 cycle statistics, present when the guest program ran under SP1
+    pub execution_report: Option<ExecutionReport>,
+    /// How the proof was produced
+    #[serde(default)]
+    pub metadata: ProofMetadata,
+}
+
+/// Provenance of a proof
+///
+/// Recorded by the prover so auditors and archives can tell how a proof was
+/// made. The metadata is not part of the public values and nothing proves
+/// it, so treat it as the prover's claim rather than a verified fact.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofMetadata {
+    /// Version of `neo-zkvm-prover` that made the proof
+    pub prover_version: String,
+    /// Unix time in seconds when proving started
+    pub created_at: u64,
+    /// [`GasSchedule::id`] of the schedule the script ran under
+    pub gas_schedule: u8,
+    /// SHA-256 of the guest ELF
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub elf_hash: [u8; 32],
 }
 
 /// Public inputs for verification
+///
+/// In JSON, hashes are hex strings; see [`neo_vm_core::json`].
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PublicInputs {
     /// Hash of the executed script
+    #[serde(with = "neo_vm_core::json::hex_hash")]
     pub script_hash: [u8; 32],
     /// Hash of input arguments
+    #[serde(with = "neo_vm_core::json::hex_hash")]
     pub input_hash: [u8; 32],
     /// Hash of execution output
+    #[serde(with = "neo_vm_core::json::hex_hash")]
     pub output_hash: [u8; 32],
     /// Gas consumed during execution
     pub gas_consumed: u64,
     /// Whether execution succeeded
     pub execution_success: bool,
+    /// Hash of the storage write set
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub write_set_hash: [u8; 32],
+    /// Hash of the transaction the script came from, zero for bare scripts
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub tx_hash: [u8; 32],
+    /// [`ProofInput::context`] echoed by the guest, binding the proof to one
+    /// request
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub context: [u8; 32],
+    /// Schedule `gas_consumed` was charged under, as its identifier
+    pub gas_schedule: GasSchedule,
+    /// [`ProofInput::runtime`] the script saw
+    pub runtime: RuntimeConfig,
+    /// `System.Runtime.GetRandom` calls the script made
+    pub random_draws: u32,
+    /// [`ProofInput::block_context`] whose timestamp
+    /// `System.Runtime.GetTime` returned
+    pub block_context: BlockContext,
+    /// [`OracleResponses::commitment`] of the oracle responses the
+    /// execution was given
+    #[serde(with = "neo_vm_core::json::hex_hash")]
+    pub oracle_hash: [u8; 32],
+}
+
+impl PublicInputs {
+    /// Every value `System.Runtime.GetRandom` returned, in order
+    ///
+    /// Recomputed from the committed seed and draw count, so a verifier can
+    /// audit the randomness a proven script used.
+    pub fn random_values(&self) -> Vec<i128> {
+        (0..self.random_draws)
+            .map(|index| self.runtime.random(index))
+            .collect()
+    }
+}
+
+/// Public values of a [`BlockProof`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BlockPublicInputs {
+    /// Hash of the block header
+    pub block_hash: [u8; 32],
+    /// Trie root of storage before the first transaction
+    pub pre_state_root: [u8; 32],
+    /// Trie root of storage after the last transaction
+    pub post_state_root: [u8; 32],
+    /// Number of transactions in the block
+    pub tx_count: u32,
+    /// Gas consumed by all transaction scripts
+    pub gas_consumed: u64,
+}
+
+/// Proof that every transaction of a block executed in order, taking
+/// storage from `pre_state_root` to `post_state_root`
+///
+/// Each transaction is proven on its own; the block commitment binds their
+/// public inputs to the block's.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlockProof {
+    pub header: BlockHeader,
+    pub public_inputs: BlockPublicInputs,
+    /// One proof per transaction, in block order
+    pub transactions: Vec<NeoProof>,
+    /// Hash of the block public inputs and every transaction commitment
+    pub commitment: [u8; 32],
 }
 
-/// Prover configuration
-#[derive(Clone, Debug)]
-pub struct ProverConfig {
-    /// Maximum cycles for SP1 execution
-    pub max_cycles: u64,
-    /// Proof mode (determines proof type and verification cost)
+/// Proof that a chain of block headers extends a trusted header
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LightClientProof {
+    /// Values committed by the light-client guest program
+    pub output: LightClientOutput,
+    /// SP1 proof bytes, or the output hash for mock proofs
+    pub proof_bytes: Vec<u8>,
+    /// Verification key hash
+    pub vkey_hash: [u8; 32],
+    /// Proof mode used
     pub proof_mode: ProofMode,
 }
 
+/// Why a proof could not be generated
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ProverError {
+    #[error(transparent)]
+    Transaction(#[from] TxError),
+    #[error("Invalid witness: {0}")]
+    InvalidWitness(#[from] WitnessError),
+    #[error(transparent)]
+    InvalidStorageWitness(#[from] StorageWitnessError),
+    /// Static validation found the script malformed, so it was not proven
+    #[error("Script is malformed: {}", join_issues(.0))]
+    InvalidScript(Vec<ValidationIssue>),
+    /// The guest program committed different public values than the host
+    /// computed by running the input on `neo-vm-core`
+    #[error("Host and guest execution disagree on {field}")]
+    ConsistencyMismatch { field: &'static str },
+    /// The guest program aborted under SP1
+    #[error("Guest program failed: {0}")]
+    GuestFault(String),
+    /// SP1 setup, proving or self-verification failed
+    #[error("SP1 error: {0}")]
+    Sp1Error(String),
+    #[error("Serialized input is {size} bytes, limit is {limit}")]
+    InputTooLarge { size: u64, limit: u64 },
+    /// A limit set in [`ProverConfig`] was exceeded
+    #[error("Resource limit exceeded: {0}")]
+    ResourceLimit(#[from] ResourceLimit),
+    /// The program set with [`NeoProver::with_program`] cannot run the
+    /// script
+    #[error("The {0} guest program does not support this script")]
+    UnsupportedProgram(ProgramId),
+    /// The script calls StdLib or CryptoLib, which only the host serves, so
+    /// the call would fault in the proof
+    #[error("Script calls {contract} at offset {offset}; only the Ledger and Oracle natives can be proven")]
+    HostOnlyNative {
+        contract: &'static str,
+        offset: usize,
+    },
+    /// The [`ProofJobStore`] could not be read or written, or a job could
+    /// not be resumed
+    #[error("Proof job: {0}")]
+    JobStore(String),
+    /// [`NeoProver::wrap`] was given a proof other than an SP1 compressed
+    /// proof
+    #[error("Only SP1 compressed proofs can be wrapped, not {0:?} proofs")]
+    NotCompressed(ProofMode),
+}
+
+/// Offset and name of the first `System.Contract.Call` to StdLib or
+/// CryptoLib whose contract hash is pushed right before it
+///
+/// Hashes computed at run time are not found; the host run then faults on
+/// the call like the guest, see [`ProofInput::natives`].
+fn host_only_native_call(script: &[u8]) -> Option<(usize, &'static str)> {
+    const PUSHDATA1: u8 = 0x0C;
+    const SYSCALL: u8 = 0x41;
+    let natives = [(StdLib.hash(), "StdLib"), (CryptoLib.hash(), "CryptoLib")];
+    let decoded = decode_script(script);
+    decoded.instructions().windows(2).find_map(|pair| {
+        let (push, call) = (&pair[0], &pair[1]);
+        let calls = push.opcode == PUSHDATA1
+            && call.opcode == SYSCALL
+            && call.operand(script) == syscall::SYSTEM_CONTRACT_CALL.to_le_bytes();
+        natives
+            .iter()
+            .find(|(hash, _)| calls && push.operand(script) == hash)
+            .map(|&(_, name)| (call.offset, name))
+    })
+}
+
+fn join_issues(issues: &[ValidationIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Why a block could not be proven
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BlockError {
+    #[error("Pre-state does not match the pre-state root")]
+    PreStateRoot,
+    #[error("Transactions do not match the header's merkle root")]
+    MerkleRoot,
+    #[error("Transaction {0}: {1}")]
+    Transaction(usize, ProverError),
+}
+
 /// Proof mode - determines the type of proof generated
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProofMode {
     /// Execute only, no proof generation (fastest)
+    ///
+    /// With an SP1 ELF available the guest program is also executed under
+    /// SP1 to fill [`NeoProof::execution_report`].
     Execute,
     /// Mock proof for testing (fast, not verifiable)
     Mock,
@@ -98,48 +364,654 @@ pub enum ProofMode {
     Groth16,
 }
 
-impl Default for ProverConfig {
-    fn default() -> Self {
-        Self {
-            max_cycles: 10_000_000,
-            proof_mode: ProofMode::Sp1,
+impl std::str::FromStr for ProofMode {
+    type Err = String;
+
+    /// Lowercase variant name, e.g. `mock` or `groth16`
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "execute" => Ok(ProofMode::Execute),
+            "mock" => Ok(ProofMode::Mock),
+            "sp1" => Ok(ProofMode::Sp1),
+            "plonk" => Ok(ProofMode::Plonk),
+            "groth16" => Ok(ProofMode::Groth16),
+            _ => Err(format!(
+                "Unknown proof mode '{s}', expected execute, mock, sp1, plonk or groth16"
+            )),
+        }
+    }
+}
+
+/// On-chain proof a compressed proof is wrapped into, see
+/// [`NeoProver::wrap`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetMode {
+    Groth16,
+    Plonk,
+}
+
+impl From<TargetMode> for ProofMode {
+    fn from(target: TargetMode) -> Self {
+        match target {
+            TargetMode::Groth16 => ProofMode::Groth16,
+            TargetMode::Plonk => ProofMode::Plonk,
         }
     }
 }
 
+/// Proof bytes, verification key hash and public inputs of an SP1 proof
+type Sp1ProofParts = (Vec<u8>, [u8; 32], PublicInputs, ExecutionReport);
+
+/// Public inputs committed by the guest program and its SP1 statistics
+type Sp1Execution = (PublicInputs, ExecutionReport);
+
+/// Proof bytes, verification key hash and output of a light-client SP1 proof
+type LightClientProofParts = (Vec<u8>, [u8; 32], LightClientOutput);
+
+/// A proof fetched from the prover network and tracked in a job store
+struct RemoteJob {
+    store: Arc<ProofJobStore>,
+    /// Job to request, with its request id filled in once requested, or
+    /// the recorded job to poll
+    job: ProofJob,
+    requested: bool,
+}
+
 /// Neo zkVM Prover
 pub struct NeoProver {
     config: ProverConfig,
+    program: Option<ProgramId>,
+    job_store: Option<Arc<ProofJobStore>>,
+    cache: Option<Arc<ProofCache>>,
+    progress: Progress,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<ProverMetrics>>,
 }
 
 impl NeoProver {
     /// Check if the SP1 ELF is available and valid
     pub fn is_elf_available() -> bool {
-        !NEO_ZKVM_ELF.is_empty() && NEO_ZKVM_ELF.len() > 100 &&
+        Self::is_real_elf(NEO_ZKVM_ELF)
+    }
+
+    /// Check if the light-client SP1 ELF is available and valid
+    pub fn is_light_client_elf_available() -> bool {
+        Self::is_real_elf(NEO_LIGHT_CLIENT_ELF)
+    }
+
+    fn is_real_elf(elf: &[u8]) -> bool {
+        !elf.is_empty() && elf.len() > 100 &&
         // Check it's not our dummy marker
-        !NEO_ZKVM_ELF.starts_with(b"DUMMY")
+        !elf.starts_with(b"DUMMY")
     }
 
     /// Create a new prover with the given configuration
     ///
     /// If SP1 is not available, it will fall back to mock mode.
     pub fn new(config: ProverConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            program: None,
+            job_store: None,
+            cache: None,
+            progress: Progress::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Record proofs and verification failures in `metrics`
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<ProverMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Report the stages of every proof to `callback`, e.g. to drive a
+    /// progress bar
+    ///
+    /// The callback may run on a prover thread when a maximum proving time
+    /// is configured.
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(ProgressEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Progress::new(Arc::new(callback));
+        self
+    }
+
+    /// Prove every input with `program` instead of the smallest program
+    /// that supports it
+    ///
+    /// Inputs the program does not support fail with
+    /// [`ProverError::UnsupportedProgram`]. Ignored when the config sets a
+    /// custom guest ELF.
+    pub fn with_program(mut self, program: ProgramId) -> Self {
+        self.program = Some(program);
+        self
+    }
+
+    /// Record proofs requested from the prover network in `store` until
+    /// they are fetched
+    ///
+    /// Proving an input that already has an outstanding job in the store
+    /// polls that request instead of starting a new one, so a process that
+    /// crashed while waiting picks up where it left off. Only SP1 proofs with
+    /// a network backend, see [`ProverBackend::is_network`], are recorded.
+    pub fn with_job_store(mut self, store: Arc<ProofJobStore>) -> Self {
+        self.job_store = Some(store);
+        self
+    }
+
+    /// Serve proofs of executions already proven from `cache`, and store
+    /// every new proof in it
+    ///
+    /// Executions are the same when their input, transaction hash, proof
+    /// mode and guest program are, see [`CacheKey`]. Signed executions and
+    /// [`ProofMode::Execute`] runs are not cached, and a cached mock proof
+    /// this prover's mock key does not verify is proven again. Cache errors
+    /// are logged, never returned.
+    pub fn with_cache(mut self, cache: Arc<ProofCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn config(&self) -> &ProverConfig {
+        &self.config
+    }
+
+    /// Fetch the proof of a job recorded in the job store
+    ///
+    /// The prover must have the store set, use the network backend and make
+    /// proofs of the job's mode. As with [`prove`](Self::prove), the input
+    /// is executed on `neo-vm-core` to check the proof's public values. The
+    /// job is removed from the store once its proof arrives; if the network
+    /// gave up on it, remove it with [`ProofJobStore::remove`].
+    pub fn resume(&self, job: &ProofJob) -> Result<NeoProof, ProverError> {
+        let Some(store) = self.job_store.as_ref() else {
+            return Err(ProverError::JobStore("no job store is set".into()));
+        };
+        if !self.config.backend().is_network() {
+            return Err(ProverError::JobStore(
+                "jobs can only be resumed with the network backend".into(),
+            ));
+        }
+        if job.mode != self.config.proof_mode() {
+            return Err(ProverError::JobStore(format!(
+                "job is a {:?} proof, the prover makes {:?} proofs",
+                job.mode,
+                self.config.proof_mode()
+            )));
+        }
+        if self.elf_hash(self.program_for(&job.input)?) != job.elf_hash {
+            return Err(ProverError::JobStore(
+                "job was requested for another guest program".into(),
+            ));
+        }
+        if store.get(&job.request_id)?.is_none() {
+            return Err(ProverError::JobStore("job is not in the store".into()));
+        }
+        self.prove_bound(job.input.clone(), job.tx_hash, job.sign_data.as_deref())
+    }
+
+    /// Program that proves `input`, `None` for the config's custom ELF
+    fn program_for(&self, input: &ProofInput) -> Result<Option<ProgramId>, ProverError> {
+        if self.config.has_custom_elf() {
+            return Ok(None);
+        }
+        let program = self.program.unwrap_or_else(|| ProgramId::for_input(input));
+        if !program.supports(input) {
+            return Err(ProverError::UnsupportedProgram(program));
+        }
+        Ok(Some(program))
+    }
+
+    /// SHA-256 of the ELF that proves with `program`
+    fn elf_hash(&self, program: Option<ProgramId>) -> [u8; 32] {
+        program.map_or_else(|| self.config.guest_elf_hash(), ProgramId::elf_hash)
+    }
+
+    fn guest_elf(config: &ProverConfig, program: Option<ProgramId>) -> &[u8] {
+        match program {
+            Some(program) => program.elf(),
+            None => config.guest_elf(),
+        }
     }
 
     /// Generate a proof for the given input
     ///
     /// The proof mode in the config determines what type of proof is generated.
-    /// If SP1 is not available, automatically falls back to mock mode.
-    pub fn prove(&self, input: ProofInput) -> NeoProof {
+    /// If the SP1 ELF is not available, automatically falls back to mock mode;
+    /// SP1 failures otherwise surface as [`ProverError::GuestFault`],
+    /// [`ProverError::ResourceLimit`] or [`ProverError::Sp1Error`].
+    ///
+    /// The input is first executed on `neo-vm-core`; an SP1 proof whose
+    /// public values differ from that execution is rejected with
+    /// [`ProverError::ConsistencyMismatch`].
+    pub fn prove(&self, input: ProofInput) -> Result<NeoProof, ProverError> {
+        self.prove_bound(input, [0u8; 32], None)
+    }
+
+    /// Estimate what proving `input` would cost without proving it
+    ///
+    /// Runs the input on `neo-vm-core` and, when the guest ELF was built,
+    /// executes the guest program under SP1 to count its cycles. Both are
+    /// cheap next to proving, so callers can reject or re-budget a script
+    /// before starting a long proof. Inputs that [`prove`](Self::prove)
+    /// would refuse fail with the same error, including
+    /// [`ProverError::ResourceLimit`] past `max_cycles` or
+    /// `max_memory_bytes`.
+    pub fn preflight(&self, input: &ProofInput) -> Result<PreflightReport, ProverError> {
+        let _guard = info_span!("neo_preflight").entered();
+        Self::check_input(input, [0u8; 32], None)?;
+        let program = self.program_for(input)?;
+
+        let output = self
+            .progress
+            .stage(ProofStage::HostExecution, || execute(input.clone()));
+
+        let elf = Self::guest_elf(&self.config, program);
+        let mut guest_fault = false;
+        let mut execution_report = None;
+        if Self::is_real_elf(elf) {
+            let stdin = self.prepare_stdin(input, [0u8; 32], None);
+            let prover = self.config.backend().client();
+            let execution = self.progress.stage(ProofStage::Execute, || {
+                Self::execute_sp1(prover.as_ref(), &self.config, elf, &stdin)
+            });
+            match execution {
+                Ok((_, report)) => execution_report = Some(report),
+                Err(ProverError::GuestFault(reason)) => {
+                    tracing::debug!(reason, "guest program failed during preflight");
+                    guest_fault = true;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(PreflightReport {
+            gas: output.gas_consumed,
+            sp1_cycles: execution_report.as_ref().map(|report| report.total_cycles),
+            execution_report,
+            would_fault: output.state != 0 || guest_fault,
+            output,
+        })
+    }
+
+    /// Prove a script without arguments or storage under the configured
+    /// default gas limit
+    pub fn prove_script(&self, script: Vec<u8>) -> Result<NeoProof, ProverError> {
+        self.prove(ProofInput {
+            script,
+            arguments: vec![],
+            gas_limit: self.config.default_gas_limit(),
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        })
+    }
+
+    /// [`prove`](Self::prove), panicking on error
+    ///
+    /// Meant for examples and tests where a failed proof is a bug.
+    pub fn prove_unwrap(&self, input: ProofInput) -> NeoProof {
+        self.prove(input)
+            .unwrap_or_else(|e| panic!("proof generation failed: {e}"))
+    }
+
+    /// Verify a transaction's witnesses and prove execution of its script
+    ///
+    /// The witnesses are checked on the host before proving; the proof binds
+    /// the transaction hash into the public inputs and runs the script with
+    /// the system fee as gas limit.
+    pub fn prove_transaction(
+        &self,
+        tx: &Transaction,
+        network: u32,
+        storage: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<NeoProof, ProverError> {
+        self.prove_transaction_at(tx, network, storage, BlockContext::default())
+    }
+
+    /// [`prove_transaction`](Self::prove_transaction) in the block `block`
+    fn prove_transaction_at(
+        &self,
+        tx: &Transaction,
+        network: u32,
+        storage: Vec<(Vec<u8>, Vec<u8>)>,
+        block: BlockContext,
+    ) -> Result<NeoProof, ProverError> {
+        tx.verify_witnesses(network)?;
+        let input = ProofInput {
+            block_context: block,
+            ..tx.proof_input(storage)
+        };
+        self.prove_bound(input, tx.hash(), None)
+    }
+
+    /// Prove that a witness verifies over `signed_payload`
+    ///
+    /// The witness is checked with [`witness::verify_witness`] before
+    /// proving. The proof runs the verification script on the items the
+    /// invocation script pushes, which become the input's arguments, with
+    /// `signed_payload` as the data `CheckSig` verifies against. Its
+    /// `tx_hash` is the SHA-256 of `signed_payload`, which the guest checks,
+    /// so a verifier holding the payload checks `tx_hash`, that
+    /// `script_hash` is the SHA-256 of the verification script and that the
+    /// result is `true`.
+    pub fn prove_witness(
+        &self,
+        verification_script: &[u8],
+        invocation_script: &[u8],
+        signed_payload: &[u8],
+    ) -> Result<NeoProof, ProverError> {
+        witness::verify_witness(verification_script, invocation_script, signed_payload)?;
+        let (arguments, invocation_gas) =
+            witness::invocation_arguments(invocation_script, MAX_VERIFICATION_GAS)?;
+        let input = ProofInput {
+            script: verification_script.to_vec(),
+            arguments,
+            gas_limit: MAX_VERIFICATION_GAS - invocation_gas,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        let tx_hash = Self::hash_data(signed_payload);
+        self.prove_bound(input, tx_hash, Some(signed_payload))
+    }
+
+    /// Prove every transaction of a block in order against shared storage
+    ///
+    /// `pre_state` holds full storage keys and must hash to `pre_state_root`.
+    /// Each transaction sees the writes of the ones before it and the
+    /// header's index and timestamp; transactions that fault are still part
+    /// of the block, with their writes discarded.
+    pub fn prove_block(
+        &self,
+        header: &BlockHeader,
+        transactions: &[Transaction],
+        network: u32,
+        pre_state: Vec<(Vec<u8>, Vec<u8>)>,
+        pre_state_root: [u8; 32],
+    ) -> Result<BlockProof, BlockError> {
+        let mut state: BTreeMap<Vec<u8>, Vec<u8>> = pre_state.into_iter().collect();
+        if state_root(&state) != pre_state_root {
+            return Err(BlockError::PreStateRoot);
+        }
+        let tx_hashes: Vec<[u8; 32]> = transactions.iter().map(Transaction::hash).collect();
+        if merkle_root(&tx_hashes) != header.merkle_root {
+            return Err(BlockError::MerkleRoot);
+        }
+
+        let mut proofs = Vec::with_capacity(transactions.len());
+        for (i, tx) in transactions.iter().enumerate() {
+            let storage = state.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            let proof = self
+                .prove_transaction_at(tx, network, storage, header.block_context())
+                .map_err(|e| BlockError::Transaction(i, e))?;
+            for (key, value) in &proof.output.storage_writes {
+                match value {
+                    Some(value) => state.insert(key.clone(), value.clone()),
+                    None => state.remove(key),
+                };
+            }
+            proofs.push(proof);
+        }
+
+        let public_inputs = BlockPublicInputs {
+            block_hash: header.hash(),
+            pre_state_root,
+            post_state_root: state_root(&state),
+            tx_count: proofs.len() as u32,
+            gas_consumed: proofs.iter().map(|p| p.public_inputs.gas_consumed).sum(),
+        };
+        let commitment = Self::block_commitment(&public_inputs, &proofs);
+        Ok(BlockProof {
+            header: header.clone(),
+            public_inputs,
+            transactions: proofs,
+            commitment,
+        })
+    }
+
+    /// Verify a block proof and every transaction proof in it
+    pub fn verify_block(&self, proof: &BlockProof) -> bool {
+        let inputs = &proof.public_inputs;
+        let tx_hashes: Vec<[u8; 32]> = proof
+            .transactions
+            .iter()
+            .map(|p| p.public_inputs.tx_hash)
+            .collect();
+        let gas_consumed: u64 = proof
+            .transactions
+            .iter()
+            .map(|p| p.public_inputs.gas_consumed)
+            .sum();
+
+        inputs.block_hash == proof.header.hash()
+            && merkle_root(&tx_hashes) == proof.header.merkle_root
+            && inputs.tx_count as usize == proof.transactions.len()
+            && inputs.gas_consumed == gas_consumed
+            && proof.commitment == Self::block_commitment(inputs, &proof.transactions)
+            && proof
+                .transactions
+                .iter()
+                .all(|p| p.public_inputs.block_context == proof.header.block_context())
+            && proof.transactions.iter().all(|p| self.verify(p))
+    }
+
+    /// Verify a header chain and prove it with the light-client program
+    ///
+    /// The proof commits to the trusted and latest header hashes, so a bridge
+    /// that trusts one header can adopt the latest with a single proof.
+    pub fn prove_header_chain(
+        &self,
+        input: &LightClientInput,
+    ) -> Result<LightClientProof, LightClientError> {
+        let output = verify_header_chain(input)?;
+
+        let sp1_mode = match self.config.proof_mode() {
+            ProofMode::Sp1 => Some(SP1ProofMode::Compressed),
+            ProofMode::Plonk => Some(SP1ProofMode::Plonk),
+            ProofMode::Groth16 => Some(SP1ProofMode::Groth16),
+            ProofMode::Execute | ProofMode::Mock => None,
+        };
+        let sp1_proof = match sp1_mode {
+            Some(mode) if Self::is_light_client_elf_available() => {
+                match self.generate_light_client_sp1_proof(input, mode) {
+                    Ok(parts) => Some(parts),
+                    Err(e) => {
+                        warn!(error = %e, "light-client proof generation failed, falling back to mock");
+                        None
+                    }
+                }
+            }
+            Some(_) => {
+                warn!("light-client ELF not available, falling back to mock proof");
+                None
+            }
+            None => None,
+        };
+
+        let proof = match sp1_proof {
+            Some((proof_bytes, vkey_hash, sp1_output)) => LightClientProof {
+                output: sp1_output,
+                proof_bytes,
+                vkey_hash,
+                proof_mode: self.config.proof_mode(),
+            },
+            None if self.config.proof_mode() == ProofMode::Execute => LightClientProof {
+                output,
+                proof_bytes: vec![],
+                vkey_hash: [0u8; 32],
+                proof_mode: ProofMode::Execute,
+            },
+            None => LightClientProof {
+                proof_bytes: Self::light_client_commitment(&output).to_vec(),
+                output,
+                vkey_hash: [0u8; 32],
+                proof_mode: ProofMode::Mock,
+            },
+        };
+        Ok(proof)
+    }
+
+    /// Verify a header chain proof
+    pub fn verify_header_chain_proof(&self, proof: &LightClientProof) -> bool {
+        match proof.proof_mode {
+            ProofMode::Execute => true,
+            ProofMode::Mock => {
+                proof.proof_bytes == Self::light_client_commitment(&proof.output).as_slice()
+            }
+            ProofMode::Sp1 | ProofMode::Plonk | ProofMode::Groth16 => {
+                self.verify_light_client_sp1_proof(proof).unwrap_or(false)
+            }
+        }
+    }
+
+    fn light_client_commitment(output: &LightClientOutput) -> [u8; 32] {
+        Self::hash_data(&bincode::serialize(output).unwrap_or_default())
+    }
+
+    fn generate_light_client_sp1_proof(
+        &self,
+        input: &LightClientInput,
+        mode: SP1ProofMode,
+    ) -> Result<LightClientProofParts, Box<dyn std::error::Error>> {
+        let prover = self.config.backend().client();
+        let (pk, vk) = prover.setup(NEO_LIGHT_CLIENT_ELF);
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(input);
+
+        let proof = prover.prove(&pk, &stdin, mode)?;
+        prover.verify(&proof, &vk)?;
+
+        let output = bincode_options().deserialize(proof.public_values.as_slice())?;
+        let proof_bytes = bincode::serialize(&proof)?;
+        let vkey_hash = Self::hash_data(&bincode::serialize(&vk)?);
+
+        Ok((proof_bytes, vkey_hash, output))
+    }
+
+    fn verify_light_client_sp1_proof(
+        &self,
+        proof: &LightClientProof,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        if !Self::is_light_client_elf_available() {
+            return Ok(false);
+        }
+
+        let prover = self.config.backend().client();
+        let (_, vk) = prover.setup(NEO_LIGHT_CLIENT_ELF);
+
+        let sp1_proof: sp1_sdk::SP1ProofWithPublicValues =
+            bincode_options().deserialize(&proof.proof_bytes)?;
+        let output: LightClientOutput =
+            bincode_options().deserialize(sp1_proof.public_values.as_slice())?;
+        if output != proof.output {
+            return Ok(false);
+        }
+
+        Ok(prover.verify(&sp1_proof, &vk).is_ok())
+    }
+
+    fn block_commitment(inputs: &BlockPublicInputs, transactions: &[NeoProof]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(inputs.block_hash);
+        hasher.update(inputs.pre_state_root);
+        hasher.update(inputs.post_state_root);
+        hasher.update(inputs.tx_count.to_le_bytes());
+        hasher.update(inputs.gas_consumed.to_le_bytes());
+        for proof in transactions {
+            hasher.update(Self::compute_commitment(&proof.public_inputs));
+        }
+        hasher.finalize().into()
+    }
+
+    /// Reject inputs too large for the guest, with a malformed script or
+    /// with calls to natives the guest does not serve
+    fn check_input(
+        input: &ProofInput,
+        tx_hash: [u8; 32],
+        sign_data: Option<&[u8]>,
+    ) -> Result<(), ProverError> {
+        let size = bincode::serialized_size(&build_guest_input(input, tx_hash, sign_data))
+            .map_err(|e| ProverError::Sp1Error(e.to_string()))?;
+        if size > BINCODE_LIMIT {
+            return Err(ProverError::InputTooLarge {
+                size,
+                limit: BINCODE_LIMIT,
+            });
+        }
+
+        let issues = validate_script(&input.script);
+        if !issues.is_empty() {
+            return Err(ProverError::InvalidScript(issues));
+        }
+        if let Some((offset, contract)) = host_only_native_call(&input.script) {
+            return Err(ProverError::HostOnlyNative { contract, offset });
+        }
+        input.witnessed_storage()?;
+        Ok(())
+    }
+
+    /// Prove `input` bound to `tx_hash`, with `sign_data`, if any, as the
+    /// payload `CheckSig` verifies against
+    fn prove_bound(
+        &self,
+        input: ProofInput,
+        tx_hash: [u8; 32],
+        sign_data: Option<&[u8]>,
+    ) -> Result<NeoProof, ProverError> {
+        let _guard = info_span!("neo_prove", mode = ?self.config.proof_mode()).entered();
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+
+        Self::check_input(&input, tx_hash, sign_data)?;
+        let program = self.program_for(&input)?;
+        let cache_key = self.cache_key(&input, program, tx_hash, sign_data);
+        if let Some(proof) = self.cached_proof(cache_key.as_ref()) {
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &self.metrics {
+                metrics.observe_cache_hit(&proof);
+            }
+            return Ok(proof);
+        }
+        let elf = Self::guest_elf(&self.config, program);
+        let metadata = ProofMetadata {
+            prover_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            gas_schedule: input.gas_schedule.id(),
+            elf_hash: self.elf_hash(program),
+        };
+
         // Compute hashes for public inputs
         let script_hash = Self::hash_data(&input.script);
-        let input_hash = Self::hash_guest_input(&input);
+        let input_hash = input.input_hash(tx_hash);
 
         // Execute to get output (used for all modes)
-        let output = execute(input.clone());
-        let output_bytes = bincode::serialize(&output).unwrap_or_default();
-        let output_hash = Self::hash_data(&output_bytes);
+        let output = self
+            .progress
+            .stage(ProofStage::HostExecution, || match sign_data {
+                Some(sign_data) => execute_signed(input.clone(), sign_data),
+                None => execute(input.clone()),
+            });
+        let output_hash = output.output_hash();
 
         let mut public_inputs = PublicInputs {
             script_hash,
@@ -147,66 +1019,69 @@ impl NeoProver {
             output_hash,
             gas_consumed: output.gas_consumed,
             execution_success: output.state == 0,
+            write_set_hash: output.write_set_hash(),
+            tx_hash,
+            context: input.context,
+            gas_schedule: input.gas_schedule,
+            runtime: input.runtime,
+            random_draws: output.random_draws,
+            block_context: input.block_context,
+            oracle_hash: input.oracle_responses.commitment(),
         };
 
         // Check if SP1 is available
-        let sp1_available = Self::is_elf_available();
+        let sp1_available = Self::is_real_elf(elf);
 
         // Generate proof based on mode (fallback to mock if SP1 not available)
-        let (proof_bytes, vkey_hash, actual_mode, sp1_public_inputs) =
-            match self.config.proof_mode {
-                ProofMode::Execute => (vec![], [0u8; 32], ProofMode::Execute, None),
-                ProofMode::Mock => (
-                    self.generate_mock_proof(&public_inputs),
-                    [0u8; 32],
-                    ProofMode::Mock,
-                    None,
-                ),
+        let (proof_bytes, vkey_hash, actual_mode, sp1_execution) = match self.config.proof_mode() {
+            ProofMode::Execute if sp1_available => {
+                let stdin = self.prepare_stdin(&input, tx_hash, sign_data);
+                let prover = self.config.backend().client();
+                let execution = self.progress.stage(ProofStage::Execute, || {
+                    Self::execute_sp1(prover.as_ref(), &self.config, elf, &stdin)
+                })?;
+                (vec![], [0u8; 32], ProofMode::Execute, Some(execution))
+            }
+            ProofMode::Execute => (vec![], [0u8; 32], ProofMode::Execute, None),
+            ProofMode::Mock => (
+                self.generate_mock_proof(&public_inputs),
+                [0u8; 32],
+                ProofMode::Mock,
+                None,
+            ),
             ProofMode::Sp1 if sp1_available => {
-                match self.generate_sp1_proof(&input, SP1ProofMode::Compressed) {
-                    Ok((bytes, hash, inputs)) => (bytes, hash, ProofMode::Sp1, Some(inputs)),
-                    Err(_) => {
-                        eprintln!("Warning: SP1 proof generation failed, falling back to mock");
-                        (
-                            self.generate_mock_proof(&public_inputs),
-                            [0u8; 32],
-                            ProofMode::Mock,
-                            None,
-                        )
-                    }
-                }
+                let (bytes, hash, inputs, report) = self.generate_sp1_proof(
+                    &input,
+                    program,
+                    tx_hash,
+                    sign_data,
+                    SP1ProofMode::Compressed,
+                )?;
+                (bytes, hash, ProofMode::Sp1, Some((inputs, report)))
             }
             ProofMode::Plonk if sp1_available => {
-                match self.generate_sp1_proof(&input, SP1ProofMode::Plonk) {
-                    Ok((bytes, hash, inputs)) => (bytes, hash, ProofMode::Plonk, Some(inputs)),
-                    Err(_) => {
-                        eprintln!("Warning: PLONK proof generation failed, falling back to mock");
-                        (
-                            self.generate_mock_proof(&public_inputs),
-                            [0u8; 32],
-                            ProofMode::Mock,
-                            None,
-                        )
-                    }
-                }
+                let (bytes, hash, inputs, report) = self.generate_sp1_proof(
+                    &input,
+                    program,
+                    tx_hash,
+                    sign_data,
+                    SP1ProofMode::Plonk,
+                )?;
+                (bytes, hash, ProofMode::Plonk, Some((inputs, report)))
             }
             ProofMode::Groth16 if sp1_available => {
-                match self.generate_sp1_proof(&input, SP1ProofMode::Groth16) {
-                    Ok((bytes, hash, inputs)) => (bytes, hash, ProofMode::Groth16, Some(inputs)),
-                    Err(_) => {
-                        eprintln!("Warning: Groth16 proof generation failed, falling back to mock");
-                        (
-                            self.generate_mock_proof(&public_inputs),
-                            [0u8; 32],
-                            ProofMode::Mock,
-                            None,
-                        )
-                    }
-                }
+                let (bytes, hash, inputs, report) = self.generate_sp1_proof(
+                    &input,
+                    program,
+                    tx_hash,
+                    sign_data,
+                    SP1ProofMode::Groth16,
+                )?;
+                (bytes, hash, ProofMode::Groth16, Some((inputs, report)))
             }
             // Fallback to mock for SP1 modes when ELF not available
             _ => {
-                eprintln!("Warning: SP1 ELF not available, falling back to mock proof");
+                warn!("SP1 ELF not available, falling back to mock proof");
                 (
                     self.generate_mock_proof(&public_inputs),
                     [0u8; 32],
@@ -214,32 +1089,188 @@ impl NeoProver {
                     None,
                 )
             }
-            };
+        };
 
-        if let Some(inputs) = sp1_public_inputs {
+        let mut execution_report = None;
+        if let Some((inputs, report)) = sp1_execution {
+            if let Some(field) = first_mismatch(&public_inputs, &inputs) {
+                return Err(ProverError::ConsistencyMismatch { field });
+            }
             public_inputs = inputs;
+            execution_report = Some(report);
         }
 
-        NeoProof {
+        let proof = NeoProof {
             output,
             proof_bytes,
             public_inputs,
             vkey_hash,
             proof_mode: actual_mode,
+            execution_report,
+            metadata,
+        };
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_proof(&proof, start.elapsed());
+        }
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            if let Err(e) = cache.insert(key, &proof) {
+                warn!(error = %e, "could not cache proof");
+            }
+        }
+        Ok(proof)
+    }
+
+    /// Cache key of proving `input`, `None` when it isn't cached
+    fn cache_key(
+        &self,
+        input: &ProofInput,
+        program: Option<ProgramId>,
+        tx_hash: [u8; 32],
+        sign_data: Option<&[u8]>,
+    ) -> Option<CacheKey> {
+        let mode = self.config.proof_mode();
+        if self.cache.is_none() || sign_data.is_some() || mode == ProofMode::Execute {
+            return None;
+        }
+        Some(CacheKey::new(input, tx_hash, mode, self.elf_hash(program)))
+    }
+
+    /// Proof cached under `key` that this prover would accept
+    fn cached_proof(&self, key: Option<&CacheKey>) -> Option<NeoProof> {
+        let (cache, key) = (self.cache.as_ref()?, key?);
+        let proof = match cache.get(key) {
+            Ok(proof) => proof?,
+            Err(e) => {
+                warn!(error = %e, "could not read proof cache");
+                return None;
+            }
+        };
+        if proof.proof_mode == ProofMode::Mock && self.verify_mock_proof(&proof).is_err() {
+            return None;
         }
+        tracing::debug!("serving cached proof");
+        Some(proof)
+    }
+
+    /// Wrap an SP1 compressed proof into a Groth16 or PLONK proof
+    ///
+    /// The guest program is not run again: the compressed proof is checked
+    /// against its public values and verifying key, then shrunk and wrapped
+    /// the way a [`ProofMode::Groth16`] or [`ProofMode::Plonk`] prover would,
+    /// so a service can prove once in [`ProofMode::Sp1`] and settle the proof
+    /// to different chains later. Wrapping runs on this machine whatever the
+    /// backend, and needs the circuit artifacts SP1 downloads on first use.
+    ///
+    /// The wrapped proof keeps the output, public inputs and execution
+    /// report of `proof`; its metadata records when it was wrapped.
+    pub fn wrap(&self, proof: &NeoProof, target: TargetMode) -> Result<NeoProof, ProverError> {
+        let _guard = info_span!("neo_wrap", ?target).entered();
+        let sp1_error = |e: &dyn std::fmt::Display| ProverError::Sp1Error(e.to_string());
+        if proof.proof_mode != ProofMode::Sp1 {
+            return Err(ProverError::NotCompressed(proof.proof_mode));
+        }
+        let program = ProgramId::from_elf_hash(&proof.metadata.elf_hash)
+            .filter(|_| !self.config.has_custom_elf());
+        let elf = Self::guest_elf(&self.config, program);
+        if !Self::is_real_elf(elf) {
+            return Err(ProverError::Sp1Error("SP1 ELF not available".into()));
+        }
+
+        let sp1_proof: SP1ProofWithPublicValues = bincode_options()
+            .deserialize(&proof.proof_bytes)
+            .map_err(|e| sp1_error(&e))?;
+        let public_inputs =
+            decode_public_inputs(&sp1_proof.public_values).map_err(|e| sp1_error(&e))?;
+        if let Some(field) = first_mismatch(&proof.public_inputs, &public_inputs) {
+            return Err(ProverError::ConsistencyMismatch { field });
+        }
+
+        let prover = self.config.backend().client();
+        let (_, vk) = self.progress.stage(ProofStage::Setup, || prover.setup(elf));
+        self.progress
+            .stage(ProofStage::Verify, || prover.verify(&sp1_proof, &vk))
+            .map_err(|e| sp1_error(&e))?;
+        let SP1Proof::Compressed(compressed) = sp1_proof.proof else {
+            return Err(ProverError::Sp1Error(format!(
+                "expected a compressed SP1 proof, found a {} proof",
+                sp1_proof.proof
+            )));
+        };
+
+        let wrapped = self.progress.stage(ProofStage::Prove, || {
+            let inner = prover.inner();
+            let opts = SP1ProverOpts::default();
+            let shrunk = inner.shrink(*compressed, opts).map_err(|e| sp1_error(&e))?;
+            let outer = inner.wrap_bn254(shrunk, opts).map_err(|e| sp1_error(&e))?;
+            let dev_mode = sp1_prover::build::sp1_dev_mode();
+            let wrapped = match target {
+                TargetMode::Groth16 => {
+                    let artifacts = if dev_mode {
+                        sp1_prover::build::try_build_groth16_bn254_artifacts_dev(
+                            &outer.vk,
+                            &outer.proof,
+                        )
+                    } else {
+                        sp1_sdk::install::try_install_circuit_artifacts("groth16")
+                    };
+                    SP1Proof::Groth16(inner.wrap_groth16_bn254(outer, &artifacts))
+                }
+                TargetMode::Plonk => {
+                    let artifacts = if dev_mode {
+                        sp1_prover::build::try_build_plonk_bn254_artifacts_dev(
+                            &outer.vk,
+                            &outer.proof,
+                        )
+                    } else {
+                        sp1_sdk::install::try_install_circuit_artifacts("plonk")
+                    };
+                    SP1Proof::Plonk(inner.wrap_plonk_bn254(outer, &artifacts))
+                }
+            };
+            Ok::<_, ProverError>(SP1ProofWithPublicValues {
+                proof: wrapped,
+                public_values: sp1_proof.public_values,
+                sp1_version: prover.version().to_string(),
+                tee_proof: None,
+            })
+        })?;
+        self.progress
+            .stage(ProofStage::Verify, || prover.verify(&wrapped, &vk))
+            .map_err(|e| sp1_error(&e))?;
+
+        Ok(NeoProof {
+            proof_bytes: bincode::serialize(&wrapped).map_err(|e| sp1_error(&e))?,
+            proof_mode: target.into(),
+            metadata: ProofMetadata {
+                prover_version: env!("CARGO_PKG_VERSION").to_string(),
+                created_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs()),
+                ..proof.metadata.clone()
+            },
+            ..proof.clone()
+        })
     }
 
     /// Verify a proof
     ///
     /// Returns true if the proof is valid, false otherwise.
     pub fn verify(&self, proof: &NeoProof) -> bool {
-        match proof.proof_mode {
-            ProofMode::Execute => true,
+        let _guard = info_span!("neo_verify", mode = ?proof.proof_mode).entered();
+        let result = match proof.proof_mode {
+            ProofMode::Execute => Ok(()),
             ProofMode::Mock => self.verify_mock_proof(proof),
-            ProofMode::Sp1 | ProofMode::Plonk | ProofMode::Groth16 => {
-                self.verify_sp1_proof(proof).unwrap_or(false)
+            ProofMode::Sp1 | ProofMode::Plonk | ProofMode::Groth16 => self.verify_sp1_proof(proof),
+        };
+        if let Err(reason) = result {
+            tracing::debug!(reason, "proof rejected");
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &self.metrics {
+                metrics.observe_verify_failure(reason);
             }
         }
+        result.is_ok()
     }
 
     fn hash_data(data: &[u8]) -> [u8; 32] {
@@ -248,16 +1279,12 @@ impl NeoProver {
         hasher.finalize().into()
     }
 
-    fn hash_guest_input(input: &ProofInput) -> [u8; 32] {
-        let guest_input = build_guest_input(input);
-        let bytes = bincode::serialize(&guest_input).unwrap_or_default();
-        Self::hash_data(&bytes)
-    }
-
     fn generate_mock_proof(&self, inputs: &PublicInputs) -> Vec<u8> {
+        let commitment = Self::compute_commitment(inputs);
         let mock = MockProof {
             public_inputs: inputs.clone(),
-            commitment: Self::compute_commitment(inputs),
+            commitment,
+            mac: MockProof::compute_mac(self.config.mock_key(), &commitment),
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
@@ -266,76 +1293,240 @@ impl NeoProver {
         bincode::serialize(&mock).unwrap_or_default()
     }
 
-    fn verify_mock_proof(&self, proof: &NeoProof) -> bool {
-        match bincode::deserialize::<MockProof>(&proof.proof_bytes) {
-            Ok(mock) => {
-                let expected = Self::compute_commitment(&proof.public_inputs);
-                mock.commitment == expected
-                    && mock.public_inputs.script_hash == proof.public_inputs.script_hash
-            }
-            Err(_) => false,
+    /// Check a mock proof, naming why it was rejected
+    fn verify_mock_proof(&self, proof: &NeoProof) -> Result<(), &'static str> {
+        let mock =
+            bincode::deserialize::<MockProof>(&proof.proof_bytes).map_err(|_| "malformed_proof")?;
+        let expected = Self::compute_commitment(&proof.public_inputs);
+        if mock.commitment != expected
+            || mock.public_inputs.script_hash != proof.public_inputs.script_hash
+        {
+            return Err("commitment_mismatch");
+        }
+        if !mock.verify_mac(self.config.mock_key()) {
+            return Err("mac_mismatch");
         }
+        Ok(())
     }
 
     fn generate_sp1_proof(
         &self,
         input: &ProofInput,
-        mode: sp1_sdk::SP1ProofMode,
-    ) -> Result<(Vec<u8>, [u8; 32], PublicInputs), Box<dyn std::error::Error>> {
+        program: Option<ProgramId>,
+        tx_hash: [u8; 32],
+        sign_data: Option<&[u8]>,
+        mode: SP1ProofMode,
+    ) -> Result<Sp1ProofParts, ProverError> {
         // Only run if ELF is available
-        if !Self::is_elf_available() {
-            return Err("SP1 ELF not available".into());
+        if !Self::is_real_elf(Self::guest_elf(&self.config, program)) {
+            return Err(ProverError::Sp1Error("SP1 ELF not available".into()));
+        }
+
+        let stdin = self.prepare_stdin(input, tx_hash, sign_data);
+        let remote = self.remote_job(input, program, tx_hash, sign_data)?;
+        let config = self.config.clone();
+        let progress = self.progress.clone();
+        // Keep the prover thread's spans under the current proof
+        let span = tracing::Span::current();
+        let task = move || {
+            span.in_scope(|| Self::run_sp1(&config, program, &progress, &stdin, mode, remote))
+        };
+
+        with_time_limit(self.config.max_proving_time(), task)
+    }
+
+    /// Job tracking a network proof of `input`, `None` without a job store
+    /// or network backend
+    fn remote_job(
+        &self,
+        input: &ProofInput,
+        program: Option<ProgramId>,
+        tx_hash: [u8; 32],
+        sign_data: Option<&[u8]>,
+    ) -> Result<Option<RemoteJob>, ProverError> {
+        let Some(store) = &self.job_store else {
+            return Ok(None);
+        };
+        if !self.config.backend().is_network() {
+            return Ok(None);
         }
+        let mode = self.config.proof_mode();
+        let input_hash = input.input_hash(tx_hash);
+        let elf_hash = self.elf_hash(program);
+        let remote = match store.find(&input_hash, mode, &elf_hash)? {
+            Some(job) => RemoteJob {
+                store: store.clone(),
+                job,
+                requested: true,
+            },
+            None => RemoteJob {
+                store: store.clone(),
+                job: ProofJob {
+                    request_id: [0; 32],
+                    mode,
+                    input: input.clone(),
+                    tx_hash,
+                    sign_data: sign_data.map(<[u8]>::to_vec),
+                    input_hash,
+                    elf_hash,
+                    requested_at: 0,
+                },
+                requested: false,
+            },
+        };
+        Ok(Some(remote))
+    }
+
+    fn run_sp1(
+        config: &ProverConfig,
+        program: Option<ProgramId>,
+        progress: &Progress,
+        stdin: &SP1Stdin,
+        mode: SP1ProofMode,
+        remote: Option<RemoteJob>,
+    ) -> Result<Sp1ProofParts, ProverError> {
+        let sp1_error = |e: &dyn std::fmt::Display| ProverError::Sp1Error(e.to_string());
 
-        let prover = ProverClient::from_env();
-        let (pk, vk) = prover.setup(NEO_ZKVM_ELF);
+        let elf = Self::guest_elf(config, program);
+        let prover = config.backend().client();
+        let (pk, vk) = progress.stage(ProofStage::Setup, || prover.setup(elf));
 
-        let stdin = self.prepare_stdin(input);
+        // Execute first so guest failures are told apart from proving failures
+        let (_, report) = progress.stage(ProofStage::Execute, || {
+            Self::execute_sp1(prover.as_ref(), config, elf, stdin)
+        })?;
 
-        // Build and run the proof based on mode
-        let proof = match mode {
-            sp1_sdk::SP1ProofMode::Core => prover.prove(&pk, &stdin).core().run(),
-            sp1_sdk::SP1ProofMode::Compressed => prover.prove(&pk, &stdin).compressed().run(),
-            sp1_sdk::SP1ProofMode::Plonk => prover.prove(&pk, &stdin).plonk().run(),
-            sp1_sdk::SP1ProofMode::Groth16 => prover.prove(&pk, &stdin).groth16().run(),
-        }?;
+        let proof = progress.stage(ProofStage::Prove, || match remote {
+            Some(remote) => Self::prove_remote(config, &pk, stdin, mode, remote),
+            None => prover.prove(&pk, stdin, mode).map_err(|e| sp1_error(&e)),
+        })?;
 
         // Verify immediately to catch any issues
-        prover.verify(&proof, &vk)?;
+        progress
+            .stage(ProofStage::Verify, || prover.verify(&proof, &vk))
+            .map_err(|e| sp1_error(&e))?;
 
-        let public_inputs = decode_public_inputs(&proof.public_values)?;
-        let proof_bytes = bincode::serialize(&proof)?;
-        let vkey_hash = Self::hash_data(&bincode::serialize(&vk)?);
+        let public_inputs =
+            decode_public_inputs(&proof.public_values).map_err(|e| sp1_error(&e))?;
+        let proof_bytes = bincode::serialize(&proof).map_err(|e| sp1_error(&e))?;
+        let vkey_hash = Self::hash_data(&bincode::serialize(&vk).map_err(|e| sp1_error(&e))?);
 
-        Ok((proof_bytes, vkey_hash, public_inputs))
+        Ok((proof_bytes, vkey_hash, public_inputs, report))
     }
 
-    fn verify_sp1_proof(&self, proof: &NeoProof) -> Result<bool, Box<dyn std::error::Error>> {
-        if !Self::is_elf_available() {
-            return Ok(false);
+    /// Prove on the prover network, keeping the request in the job store
+    /// until its proof arrives
+    fn prove_remote(
+        config: &ProverConfig,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        mode: SP1ProofMode,
+        remote: RemoteJob,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        let sp1_error = |e: &dyn std::fmt::Display| ProverError::Sp1Error(e.to_string());
+        let RemoteJob {
+            store,
+            mut job,
+            requested,
+        } = remote;
+
+        let network = ProverClient::builder().network().build();
+        if requested {
+            tracing::info!(request = %B256::from(job.request_id), "polling outstanding proof request");
+        } else {
+            let request_id = network
+                .prove(pk, stdin)
+                .mode(mode)
+                .cycle_limit(config.max_cycles())
+                .request()
+                .map_err(|e| sp1_error(&e))?;
+            job.request_id = request_id.0;
+            job.requested_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            store.save(&job)?;
         }
 
-        let prover = ProverClient::from_env();
-        let (_, vk) = prover.setup(NEO_ZKVM_ELF);
+        // Wait inside the caller's Tokio runtime when there is one
+        let wait = network.wait_proof(B256::from(job.request_id), config.max_proving_time());
+        let proof = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => tokio::task::block_in_place(|| handle.block_on(wait)),
+            Err(_) => tokio::runtime::Runtime::new()
+                .map_err(|e| sp1_error(&e))?
+                .block_on(wait),
+        }
+        .map_err(|e| sp1_error(&e))?;
+        store.remove(&job.request_id)?;
+        Ok(proof)
+    }
 
-        let sp1_proof: sp1_sdk::SP1ProofWithPublicValues =
-            bincode_options().deserialize(&proof.proof_bytes)?;
-        let public_inputs = decode_public_inputs(&sp1_proof.public_values)?;
-        if !public_inputs_equal(&public_inputs, &proof.public_inputs) {
-            return Ok(false);
+    /// Execute the guest program under SP1 without proving
+    fn execute_sp1(
+        prover: &dyn Prover<CpuProverComponents>,
+        config: &ProverConfig,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<Sp1Execution, ProverError> {
+        let guest_fault = |e: &dyn std::fmt::Display| ProverError::GuestFault(e.to_string());
+        let context = SP1Context::builder()
+            .max_cycles(config.max_cycles())
+            .build();
+        let (public_values, report) =
+            prover
+                .inner()
+                .execute(elf, stdin, context)
+                .map_err(|e| match e {
+                    ExecutionError::ExceededCycleLimit(limit) => {
+                        ResourceLimit::Cycles(limit).into()
+                    }
+                    e => guest_fault(&e),
+                })?;
+        // SP1 counts 4-byte words
+        let memory = report.touched_memory_addresses.saturating_mul(4);
+        if let Some(limit) = config.max_memory_bytes().filter(|&limit| memory > limit) {
+            return Err(ResourceLimit::Memory {
+                used: memory,
+                limit,
+            }
+            .into());
+        }
+        let public_inputs = decode_public_inputs(&public_values).map_err(|e| guest_fault(&e))?;
+        Ok((public_inputs, ExecutionReport::from_sp1(&report)))
+    }
+
+    /// Check an SP1 proof, naming why it was rejected
+    fn verify_sp1_proof(&self, proof: &NeoProof) -> Result<(), &'static str> {
+        let program = ProgramId::from_elf_hash(&proof.metadata.elf_hash)
+            .filter(|_| !self.config.has_custom_elf());
+        let elf = Self::guest_elf(&self.config, program);
+        if !Self::is_real_elf(elf) {
+            return Err("elf_unavailable");
         }
 
-        match prover.verify(&sp1_proof, &vk) {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
+        let sp1_proof: sp1_sdk::SP1ProofWithPublicValues = bincode_options()
+            .deserialize(&proof.proof_bytes)
+            .map_err(|_| "malformed_proof")?;
+        let public_inputs =
+            decode_public_inputs(&sp1_proof.public_values).map_err(|_| "malformed_proof")?;
+        if first_mismatch(&public_inputs, &proof.public_inputs).is_some() {
+            return Err("public_inputs_mismatch");
         }
+
+        let prover = self.config.backend().client();
+        let (_, vk) = prover.setup(elf);
+        prover.verify(&sp1_proof, &vk).map_err(|_| "sp1_rejected")
     }
 
-    fn prepare_stdin(&self, input: &ProofInput) -> SP1Stdin {
+    fn prepare_stdin(
+        &self,
+        input: &ProofInput,
+        tx_hash: [u8; 32],
+        sign_data: Option<&[u8]>,
+    ) -> SP1Stdin {
         let mut stdin = SP1Stdin::new();
 
         // Convert to guest-compatible format
-        let guest_input = build_guest_input(input);
+        let guest_input = build_guest_input(input, tx_hash, sign_data);
 
         stdin.write(&guest_input);
         stdin
@@ -348,6 +1539,17 @@ impl NeoProver {
         hasher.update(inputs.output_hash);
         hasher.update(inputs.gas_consumed.to_le_bytes());
         hasher.update([inputs.execution_success as u8]);
+        hasher.update(inputs.write_set_hash);
+        hasher.update(inputs.tx_hash);
+        hasher.update(inputs.context);
+        hasher.update([inputs.gas_schedule.id()]);
+        hasher.update(inputs.runtime.network.to_le_bytes());
+        hasher.update([inputs.runtime.trigger.id()]);
+        hasher.update(inputs.runtime.random_seed);
+        hasher.update(inputs.random_draws.to_le_bytes());
+        hasher.update(inputs.block_context.index.to_le_bytes());
+        hasher.update(inputs.block_context.timestamp.to_le_bytes());
+        hasher.update(inputs.oracle_hash);
         hasher.finalize().into()
     }
 }
@@ -358,15 +1560,100 @@ pub struct GuestInput {
     pub script: Vec<u8>,
     pub arguments: Vec<GuestStackItem>,
     pub gas_limit: u64,
+    pub storage: Vec<(Vec<u8>, Vec<u8>)>,
+    pub tx_hash: [u8; 32],
+    pub context: [u8; 32],
+    /// [`GasSchedule::id`]
+    pub gas_schedule: u8,
+    pub network: u32,
+    /// [`neo_vm_core::TriggerType::id`]
+    pub trigger: u8,
+    pub random_seed: [u8; 32],
+    /// Payload `CheckSig` verifies signatures against; its SHA-256 must be
+    /// `tx_hash`
+    pub sign_data: Option<Vec<u8>>,
+    pub pre_state_root: Option<[u8; 32]>,
+    pub storage_witnesses: Vec<StorageWitness>,
+    pub limits: GuestLimits,
+    /// [`BlockContext::index`]
+    pub block_index: u32,
+    /// [`BlockContext::timestamp`]
+    pub timestamp: u64,
+    /// [`OracleResponses`] as `(url, filter, response)`, in commitment
+    /// order
+    pub oracle_responses: Vec<(String, String, Vec<u8>)>,
+}
+
+/// [`VMLimits`] as the guest program takes them, sizes saturated to `u32`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuestLimits {
+    pub max_stack: u32,
+    pub max_invocation_depth: u32,
+    pub max_script_size: u32,
+    pub max_item_size: u32,
+    pub max_trace_steps: u64,
+}
+
+impl From<&VMLimits> for GuestLimits {
+    fn from(limits: &VMLimits) -> Self {
+        let size = |value: usize| u32::try_from(value).unwrap_or(u32::MAX);
+        Self {
+            max_stack: size(limits.max_stack),
+            max_invocation_depth: size(limits.max_invocation_depth),
+            max_script_size: size(limits.max_script_size),
+            max_item_size: size(limits.max_item_size),
+            max_trace_steps: limits.max_trace_steps,
+        }
+    }
 }
 
 /// Simplified stack item for guest
+///
+/// Variants are in the order of the guest program's `StackItem`, since
+/// bincode encodes them by index.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GuestStackItem {
     Null,
     Boolean(bool),
     Integer(i128),
     ByteString(Vec<u8>),
+    Array(Vec<GuestStackItem>),
+    Map(Vec<(GuestStackItem, GuestStackItem)>),
+    Struct(Vec<GuestStackItem>),
+}
+
+/// Run `task`, failing with [`ResourceLimit::ProvingTime`] if it has not
+/// finished after `limit`
+///
+/// SP1 cannot be interrupted, so a timed-out proof keeps running on its
+/// thread until it finishes.
+fn with_time_limit<T: Send + 'static>(
+    limit: Option<Duration>,
+    task: impl FnOnce() -> Result<T, ProverError> + Send + 'static,
+) -> Result<T, ProverError> {
+    let Some(timeout) = limit else {
+        return task();
+    };
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // The receiver is gone once the timeout has passed
+        let _ = sender.send(task());
+    });
+    receiver.recv_timeout(timeout).map_err(|e| match e {
+        mpsc::RecvTimeoutError::Timeout => ResourceLimit::ProvingTime(timeout).into(),
+        mpsc::RecvTimeoutError::Disconnected => {
+            ProverError::Sp1Error("prover thread panicked".into())
+        }
+    })?
+}
+
+/// Trie root of storage given as full keys
+fn state_root(state: &BTreeMap<Vec<u8>, Vec<u8>>) -> [u8; 32] {
+    state
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<TrieStorage>()
+        .root_hash()
 }
 
 fn decode_public_inputs(
@@ -375,29 +1662,88 @@ fn decode_public_inputs(
     Ok(bincode_options().deserialize(values.as_slice())?)
 }
 
-fn public_inputs_equal(a: &PublicInputs, b: &PublicInputs) -> bool {
-    a.script_hash == b.script_hash
-        && a.input_hash == b.input_hash
-        && a.output_hash == b.output_hash
-        && a.gas_consumed == b.gas_consumed
-        && a.execution_success == b.execution_success
+/// Name of the first field where two sets of public inputs differ
+fn first_mismatch(a: &PublicInputs, b: &PublicInputs) -> Option<&'static str> {
+    if a.script_hash != b.script_hash {
+        Some("script_hash")
+    } else if a.input_hash != b.input_hash {
+        Some("input_hash")
+    } else if a.output_hash != b.output_hash {
+        Some("output_hash")
+    } else if a.gas_consumed != b.gas_consumed {
+        Some("gas_consumed")
+    } else if a.execution_success != b.execution_success {
+        Some("execution_success")
+    } else if a.write_set_hash != b.write_set_hash {
+        Some("write_set_hash")
+    } else if a.tx_hash != b.tx_hash {
+        Some("tx_hash")
+    } else if a.context != b.context {
+        Some("context")
+    } else if a.gas_schedule != b.gas_schedule {
+        Some("gas_schedule")
+    } else if a.runtime != b.runtime {
+        Some("runtime")
+    } else if a.random_draws != b.random_draws {
+        Some("random_draws")
+    } else if a.block_context != b.block_context {
+        Some("block_context")
+    } else if a.oracle_hash != b.oracle_hash {
+        Some("oracle_hash")
+    } else {
+        None
+    }
 }
 
-fn build_guest_input(input: &ProofInput) -> GuestInput {
+fn build_guest_input(
+    input: &ProofInput,
+    tx_hash: [u8; 32],
+    sign_data: Option<&[u8]>,
+) -> GuestInput {
     GuestInput {
         script: input.script.clone(),
-        arguments: input
-            .arguments
+        arguments: input.arguments.iter().map(guest_item).collect(),
+        gas_limit: input.gas_limit,
+        storage: input.storage.clone(),
+        tx_hash,
+        context: input.context,
+        gas_schedule: input.gas_schedule.id(),
+        network: input.runtime.network,
+        trigger: input.runtime.trigger.id(),
+        random_seed: input.runtime.random_seed,
+        sign_data: sign_data.map(<[u8]>::to_vec),
+        pre_state_root: input.pre_state_root,
+        storage_witnesses: input.storage_witnesses.clone(),
+        limits: GuestLimits::from(&input.limits),
+        block_index: input.block_context.index,
+        timestamp: input.block_context.timestamp,
+        oracle_responses: input
+            .oracle_responses
             .iter()
-            .map(|item| match item {
-                neo_vm_core::StackItem::Null => GuestStackItem::Null,
-                neo_vm_core::StackItem::Boolean(b) => GuestStackItem::Boolean(*b),
-                neo_vm_core::StackItem::Integer(i) => GuestStackItem::Integer(*i),
-                neo_vm_core::StackItem::ByteString(b) => GuestStackItem::ByteString(b.clone()),
-                _ => GuestStackItem::Null,
-            })
+            .map(|(url, filter, response)| (url.to_string(), filter.to_string(), response.to_vec()))
             .collect(),
-        gas_limit: input.gas_limit,
+    }
+}
+
+/// The guest program's form of an argument
+///
+/// The guest has no buffers or pointers; they are passed as `Null`, which
+/// makes the guest's `input_hash` differ from the host's.
+fn guest_item(item: &neo_vm_core::StackItem) -> GuestStackItem {
+    use neo_vm_core::StackItem as Host;
+    match item {
+        Host::Null | Host::Buffer(_) | Host::Pointer(_) => GuestStackItem::Null,
+        Host::Boolean(b) => GuestStackItem::Boolean(*b),
+        Host::Integer(i) => GuestStackItem::Integer(*i),
+        Host::ByteString(b) => GuestStackItem::ByteString(b.to_vec()),
+        Host::Array(items) => GuestStackItem::Array(items.iter().map(guest_item).collect()),
+        Host::Struct(items) => GuestStackItem::Struct(items.iter().map(guest_item).collect()),
+        Host::Map(entries) => GuestStackItem::Map(
+            entries
+                .iter()
+                .map(|(key, value)| (guest_item(key), guest_item(value)))
+                .collect(),
+        ),
     }
 }
 
@@ -407,6 +1753,23 @@ pub struct MockProof {
     pub public_inputs: PublicInputs,
     pub commitment: [u8; 32],
     pub timestamp: u64,
+    /// HMAC-SHA256 of `commitment` under the prover's mock key
+    pub mac: [u8; 32],
+}
+
+impl MockProof {
+    pub fn compute_mac(key: &[u8; 32], commitment: &[u8; 32]) -> [u8; 32] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(commitment);
+        mac.finalize().into_bytes().into()
+    }
+
+    /// Check [`MockProof::mac`] in constant time
+    pub fn verify_mac(&self, key: &[u8; 32]) -> bool {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(&self.commitment);
+        mac.verify_slice(&self.mac).is_ok()
+    }
 }
 
 #[cfg(test)]
@@ -416,52 +1779,986 @@ mod tests {
 
     #[test]
     fn test_mock_proof() {
-        let prover = NeoProver::new(ProverConfig {
-            proof_mode: ProofMode::Mock,
-            ..Default::default()
-        });
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
 
         let input = ProofInput {
             script: vec![0x12, 0x13, 0x9E, 0x40],
             arguments: vec![],
             gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
         };
 
-        let proof = prover.prove(input);
+        let proof = prover.prove(input).unwrap();
         assert!(proof.proof_mode == ProofMode::Mock);
         assert!(prover.verify(&proof));
     }
 
+    #[test]
+    fn test_mock_proof_key() {
+        let keyed = |key: [u8; 32]| {
+            NeoProver::new(
+                ProverConfig::builder()
+                    .proof_mode(ProofMode::Mock)
+                    .mock_key(key)
+                    .build()
+                    .unwrap(),
+            )
+        };
+        let prover = keyed([7u8; 32]);
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert!(prover.verify(&proof));
+
+        // A valid commitment is not enough without the key
+        assert!(!keyed(TEST_MOCK_KEY).verify(&proof));
+        assert!(!format!("{:?}", prover.config).contains("7, 7"));
+    }
+
+    #[test]
+    fn test_proof_cache() {
+        let dir = std::env::temp_dir().join(format!("neo-prover-cache-{}", std::process::id()));
+        let cache = Arc::new(ProofCache::open(&dir, CachePolicy::default()).unwrap());
+        let keyed = |key: [u8; 32]| {
+            NeoProver::new(
+                ProverConfig::builder()
+                    .proof_mode(ProofMode::Mock)
+                    .mock_key(key)
+                    .build()
+                    .unwrap(),
+            )
+            .with_cache(cache.clone())
+        };
+        let prover = keyed([7u8; 32]);
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let cached = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert_eq!(cached.proof_bytes, proof.proof_bytes);
+        assert_eq!(cached.metadata, proof.metadata);
+        assert_eq!(cache.entries().values().next().unwrap().hits, 1);
+
+        // A cached mock proof under another key is proven again
+        let other = keyed(TEST_MOCK_KEY);
+        let reproven = other.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert!(other.verify(&reproven));
+        assert!(prover.prove_script(vec![0x12, 0x40]).is_ok());
+        assert_eq!(cache.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wrap_requires_compressed_proof() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let mut proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert!(matches!(
+            prover.wrap(&proof, TargetMode::Groth16),
+            Err(ProverError::NotCompressed(ProofMode::Mock))
+        ));
+
+        proof.proof_mode = ProofMode::Sp1;
+        if !NeoProver::is_elf_available() {
+            assert!(matches!(
+                prover.wrap(&proof, TargetMode::Plonk),
+                Err(ProverError::Sp1Error(_))
+            ));
+        }
+        assert_eq!(ProofMode::from(TargetMode::Plonk), ProofMode::Plonk);
+    }
+
     #[test]
     fn test_execute_only() {
-        let prover = NeoProver::new(ProverConfig {
-            proof_mode: ProofMode::Execute,
-            ..Default::default()
-        });
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .build()
+                .unwrap(),
+        );
 
         let input = ProofInput {
             script: vec![0x12, 0x13, 0x9E, 0x40],
             arguments: vec![],
             gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
         };
 
-        let proof = prover.prove(input);
+        let proof = prover.prove(input).unwrap();
         assert!(proof.proof_mode == ProofMode::Execute);
         assert!(prover.verify(&proof));
     }
 
     #[test]
-    fn test_guest_input_hash_matches_serialized_guest_input() {
+    fn test_proof_metadata() {
+        let config = ProverConfig::builder()
+            .proof_mode(ProofMode::Mock)
+            .build()
+            .unwrap();
+        let elf_hash = ProgramId::Arithmetic.elf_hash();
+        let prover = NeoProver::new(config);
+        let proof = prover
+            .prove(ProofInput {
+                script: vec![0x12, 0x13, 0x9E, 0x40],
+                arguments: vec![],
+                gas_limit: 1_000_000,
+                storage: Vec::new(),
+                context: [0; 32],
+                gas_schedule: GasSchedule::NeoN3,
+                runtime: RuntimeConfig::default(),
+                pre_state_root: None,
+                storage_witnesses: Vec::new(),
+                limits: VMLimits::default(),
+                block_context: BlockContext::default(),
+                oracle_responses: OracleResponses::default(),
+            })
+            .unwrap();
+
+        let metadata = &proof.metadata;
+        assert_eq!(metadata.prover_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.gas_schedule, GasSchedule::NeoN3.id());
+        assert_eq!(metadata.elf_hash, elf_hash);
+        assert!(metadata.created_at > 0);
+
+        // Metadata is not committed to, so changing it keeps the proof valid
+        let mut relabelled = proof.clone();
+        relabelled.metadata = ProofMetadata::default();
+        assert!(prover.verify(&relabelled));
+    }
+
+    #[test]
+    fn test_program_selection() {
+        let input = |script: Vec<u8>| ProofInput {
+            script,
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        // PUSH2, PUSH3, ADD, RET and SYSCALL GetTrigger, RET
+        let arithmetic = input(vec![0x12, 0x13, 0x9E, 0x40]);
+        let syscall = input(vec![0x41, 0x04, 0x00, 0x00, 0x00, 0x40]);
+        assert_eq!(ProgramId::for_input(&arithmetic), ProgramId::Arithmetic);
+        assert_eq!(ProgramId::for_input(&syscall), ProgramId::Vm);
+        assert!(ProgramId::Vm.supports(&syscall));
+
+        let config = ProverConfig::builder()
+            .proof_mode(ProofMode::Mock)
+            .build()
+            .unwrap();
+        let pinned = NeoProver::new(config.clone()).with_program(ProgramId::Arithmetic);
+        assert!(matches!(
+            pinned.prove(syscall.clone()),
+            Err(ProverError::UnsupportedProgram(ProgramId::Arithmetic))
+        ));
+        let proof = NeoProver::new(config).prove(syscall).unwrap();
+        assert_eq!(proof.metadata.elf_hash, ProgramId::Vm.elf_hash());
+    }
+
+    #[test]
+    fn test_resume_checks_job() {
+        let input = ProofInput {
+            script: vec![0x12, 0x13, 0x9E, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        let job = ProofJob {
+            request_id: [1; 32],
+            mode: ProofMode::Groth16,
+            input_hash: input.input_hash([0; 32]),
+            input,
+            tx_hash: [0; 32],
+            sign_data: None,
+            elf_hash: ProgramId::Arithmetic.elf_hash(),
+            requested_at: 0,
+        };
+        let dir = std::env::temp_dir().join(format!("neo-resume-{}", std::process::id()));
+        let store = Arc::new(ProofJobStore::open(&dir).unwrap());
+        let network = |mode| {
+            ProverConfig::builder()
+                .proof_mode(mode)
+                .backend(ProverBackend::Network)
+                .build()
+                .unwrap()
+        };
+        let resume_error = |prover: &NeoProver, job: &ProofJob| match prover.resume(job) {
+            Err(ProverError::JobStore(message)) => message,
+            other => panic!("expected a job store error, got {:?}", other.err()),
+        };
+
+        let groth16 = NeoProver::new(network(ProofMode::Groth16));
+        assert!(resume_error(&groth16, &job).contains("no job store"));
+        let cpu = ProverConfig::builder()
+            .proof_mode(ProofMode::Groth16)
+            .backend(ProverBackend::Cpu)
+            .build()
+            .unwrap();
+        let cpu = NeoProver::new(cpu).with_job_store(store.clone());
+        assert!(resume_error(&cpu, &job).contains("network backend"));
+        let plonk = NeoProver::new(network(ProofMode::Plonk)).with_job_store(store.clone());
+        assert!(resume_error(&plonk, &job).contains("Groth16 proof"));
+        let groth16 = groth16.with_job_store(store);
+        let other_elf = ProofJob {
+            elf_hash: [7; 32],
+            ..job.clone()
+        };
+        assert!(resume_error(&groth16, &other_elf).contains("another guest program"));
+        assert!(resume_error(&groth16, &job).contains("not in the store"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_preflight() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let input = |script: Vec<u8>| ProofInput {
+            script,
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+
+        let add = input(vec![0x12, 0x13, 0x9E, 0x40]);
+        let report = prover.preflight(&add).unwrap();
+        assert_eq!(report.gas, prover.prove(add).unwrap().output.gas_consumed);
+        assert!(!report.would_fault);
+        assert_eq!(report.output.result, Some(StackItem::Integer(5)));
+        // The dummy ELF cannot run under SP1
+        assert_eq!(report.sp1_cycles, None);
+
+        // PUSH0, ASSERT
+        assert!(
+            prover
+                .preflight(&input(vec![0x10, 0x39]))
+                .unwrap()
+                .would_fault
+        );
+        assert!(matches!(
+            prover.preflight(&input(vec![0x22, 0x01])),
+            Err(ProverError::InvalidScript(_))
+        ));
+    }
+
+    #[test]
+    fn test_prove_script_uses_default_gas_limit() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .default_gas_limit(3)
+                .build()
+                .unwrap(),
+        );
+
+        // PUSH2, PUSH3, ADD, RET runs out of gas at the third instruction
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert!(!proof.public_inputs.execution_success);
+    }
+
+    #[test]
+    fn test_malformed_script_is_not_proven() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        // JMP +5 past the end of PUSH1, RET
+        let result = prover.prove_script(vec![0x22, 0x05, 0x11, 0x40]);
+        assert_eq!(
+            result.err(),
+            Some(ProverError::InvalidScript(vec![
+                ValidationIssue::InvalidJumpTarget {
+                    offset: 0,
+                    target: 5
+                }
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_host_only_natives_are_not_proven() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let call = |hash: [u8; 20]| {
+            let mut script = neo_vm_core::ScriptBuilder::new();
+            script
+                .emit_push_bytes(b"sha256")
+                .emit_push_bytes(&hash)
+                .emit_syscall(syscall::SYSTEM_CONTRACT_CALL)
+                .emit(neo_vm_core::OpCode::RET);
+            script.into_bytes()
+        };
+        assert_eq!(
+            prover.prove_script(call(CryptoLib.hash())).err(),
+            Some(ProverError::HostOnlyNative {
+                contract: "CryptoLib",
+                offset: 30
+            })
+        );
+        assert!(matches!(
+            prover.prove_script(call(StdLib.hash())),
+            Err(ProverError::HostOnlyNative {
+                contract: "StdLib",
+                ..
+            })
+        ));
+        // The Ledger is served by the guest, and the call faults on its
+        // missing arguments like any other
+        let ledger = neo_vm_core::LedgerContract::default().hash();
+        assert!(prover.prove_script(call(ledger)).is_ok());
+    }
+
+    #[test]
+    fn test_input_too_large() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let input = ProofInput {
+            script: vec![0x21; BINCODE_LIMIT as usize],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+
+        assert!(matches!(
+            prover.prove(input),
+            Err(ProverError::InputTooLarge {
+                limit: BINCODE_LIMIT,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_preflight_cycle_limit() {
+        // Counting cycles needs the guest ELF
+        if !NeoProver::is_elf_available() {
+            return;
+        }
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .max_cycles(1_000)
+                .build()
+                .unwrap(),
+        );
+        let input = ProofInput {
+            script: vec![0x12, 0x13, 0x9E, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        assert!(matches!(
+            prover.preflight(&input),
+            Err(ProverError::ResourceLimit(ResourceLimit::Cycles(_)))
+        ));
+    }
+
+    #[test]
+    fn test_proving_time_limit() {
+        let limit = Duration::from_millis(10);
+        let slow = || {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        };
+        assert!(matches!(
+            with_time_limit(Some(limit), slow),
+            Err(ProverError::ResourceLimit(ResourceLimit::ProvingTime(t))) if t == limit
+        ));
+        assert_eq!(
+            with_time_limit(Some(Duration::from_secs(60)), || Ok(7)).unwrap(),
+            7
+        );
+        assert_eq!(with_time_limit(None, || Ok(7)).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_execution_report_requires_sp1() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert!(proof.execution_report.is_none());
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        )
+        .with_progress(move |event| sink.lock().unwrap().push(event));
+        prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], ProgressEvent::Started(ProofStage::HostExecution));
+        assert!(matches!(
+            events[1],
+            ProgressEvent::Finished {
+                stage: ProofStage::HostExecution,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_consistency_check_names_field() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .build()
+                .unwrap(),
+        );
+        let input = ProofInput {
+            script: vec![0x12, 0x13, 0x9E, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+
+        let host = prover.prove(input).unwrap().public_inputs;
+        let mut guest = host.clone();
+        assert_eq!(first_mismatch(&host, &guest), None);
+
+        guest.gas_consumed += 1;
+        assert_eq!(first_mismatch(&host, &guest), Some("gas_consumed"));
+        guest.output_hash = [0u8; 32];
+        assert_eq!(first_mismatch(&host, &guest), Some("output_hash"));
+    }
+
+    #[test]
+    fn test_storage_binds_input_and_write_set() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let mut key = vec![0u8; 20];
+        key.push(b'k');
+        let input = |value: &[u8]| ProofInput {
+            // PUSHDATA1 "w", PUSHDATA1 "k", SYSCALL System.Storage.Put, RET
+            script: vec![
+                0x0C, 0x01, b'w', 0x0C, 0x01, b'k', 0x41, 0x11, 0x00, 0x00, 0x00, 0x40,
+            ],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: vec![(key.clone(), value.to_vec())],
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+
+        let changed = prover.prove(input(b"v")).unwrap();
+        assert_eq!(
+            changed.output.storage_writes,
+            vec![(key.clone(), Some(b"w".to_vec()))]
+        );
+        assert!(prover.verify(&changed));
+
+        // Writing the value already stored is not a net change
+        let unchanged = prover.prove(input(b"w")).unwrap();
+        assert!(unchanged.output.storage_writes.is_empty());
+        assert_ne!(
+            changed.public_inputs.input_hash,
+            unchanged.public_inputs.input_hash
+        );
+        assert_ne!(
+            changed.public_inputs.write_set_hash,
+            unchanged.public_inputs.write_set_hash
+        );
+    }
+
+    #[test]
+    fn test_random_values_audit_draws() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let input = ProofInput {
+            // SYSCALL System.Runtime.GetRandom twice, DROP, RET
+            script: vec![
+                0x41, 0x07, 0x00, 0x00, 0x00, 0x41, 0x07, 0x00, 0x00, 0x00, 0x45, 0x40,
+            ],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig {
+                random_seed: [5; 32],
+                ..RuntimeConfig::default()
+            },
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+
+        let proof = prover.prove(input).unwrap();
+        let values = proof.public_inputs.random_values();
+        assert_eq!(values.len(), 2);
+        assert_eq!(proof.output.result, Some(StackItem::Integer(values[0])));
+        assert!(prover.verify(&proof));
+
+        // The draw count is committed
+        let mut tampered = proof.clone();
+        tampered.public_inputs.random_draws = 1;
+        assert!(!prover.verify(&tampered));
+    }
+
+    #[test]
+    fn test_block_time_is_committed() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let block_context = BlockContext {
+            index: 42,
+            timestamp: 1_700_000_000_000,
+        };
+        let input = ProofInput {
+            // SYSCALL System.Runtime.GetTime, RET
+            script: vec![0x41, 0x03, 0x00, 0x00, 0x00, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context,
+            oracle_responses: OracleResponses::default(),
+        };
+
+        let proof = prover.prove(input.clone()).unwrap();
+        assert_eq!(
+            proof.output.result,
+            Some(StackItem::Integer(1_700_000_000_000))
+        );
+        assert_eq!(proof.public_inputs.block_context, block_context);
+        assert!(prover.verify(&proof));
+
+        // The block time is part of the input hash and committed
+        let default_time = ProofInput {
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+            ..input
+        };
+        assert_ne!(
+            proof.public_inputs.input_hash,
+            default_time.input_hash([0; 32])
+        );
+        let mut tampered = proof.clone();
+        tampered.public_inputs.block_context.timestamp += 1;
+        assert!(!prover.verify(&tampered));
+    }
+
+    #[test]
+    fn test_oracle_responses_are_committed() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let mut oracle_responses = OracleResponses::new();
+        oracle_responses.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+        let input = ProofInput {
+            script: vec![0x12, 0x13, 0x9E, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: oracle_responses.clone(),
+        };
+
+        let proof = prover.prove(input.clone()).unwrap();
+        assert_eq!(
+            proof.public_inputs.oracle_hash,
+            oracle_responses.commitment()
+        );
+        assert_ne!(
+            proof.public_inputs.oracle_hash,
+            OracleResponses::default().commitment()
+        );
+        assert_eq!(proof.public_inputs.input_hash, input.input_hash([0; 32]));
+        assert!(prover.verify(&proof));
+
+        let guest = build_guest_input(&input, [0; 32], None);
+        assert_eq!(
+            guest.oracle_responses,
+            vec![(
+                "https://api.example/price".to_string(),
+                "$.neo".to_string(),
+                b"12.5".to_vec()
+            )]
+        );
+
+        let mut tampered = proof.clone();
+        tampered.public_inputs.oracle_hash = [0; 32];
+        assert!(!prover.verify(&tampered));
+    }
+
+    /// Transaction running `script`, signed on network 0
+    fn signed_tx(script: Vec<u8>, nonce: u32) -> Transaction {
+        use k256::ecdsa::{signature::Signer as _, Signature, SigningKey};
+        use neo_vm_guest::tx::{script_hash, witness_scope, Signer, Witness};
+
+        let key = SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let pubkey = key.verifying_key().to_sec1_bytes();
+        // PUSHDATA1 <pubkey>, SYSCALL System.Crypto.CheckSig
+        let mut verification = vec![0x0C, pubkey.len() as u8];
+        verification.extend_from_slice(&pubkey);
+        verification.extend_from_slice(&[0x41, 0x20, 0x00, 0x00, 0x00]);
+
+        let mut tx = Transaction {
+            version: 0,
+            nonce,
+            system_fee: 1_000_000,
+            network_fee: 1_000_000,
+            valid_until_block: 10,
+            signers: vec![Signer {
+                account: script_hash(&verification),
+                scopes: witness_scope::CALLED_BY_ENTRY,
+                allowed_contracts: vec![],
+                allowed_groups: vec![],
+                rules: vec![],
+            }],
+            attributes: vec![],
+            script,
+            witnesses: vec![],
+        };
+        let signature: Signature = key.sign(&tx.sign_data(0));
+        let mut invocation = vec![0x0C, 0x40];
+        invocation.extend_from_slice(&signature.to_bytes());
+        tx.witnesses.push(Witness {
+            invocation_script: invocation,
+            verification_script: verification,
+        });
+        tx
+    }
+
+    #[test]
+    fn test_public_inputs_json() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        let json = serde_json::to_value(&proof.public_inputs).unwrap();
+        assert_eq!(
+            json["script_hash"],
+            hex::encode(proof.public_inputs.script_hash)
+        );
+        assert_eq!(json["tx_hash"], "00".repeat(32));
+        assert_eq!(json["gas_consumed"], proof.public_inputs.gas_consumed);
+
+        let decoded: PublicInputs = serde_json::from_value(json).unwrap();
+        assert_eq!(first_mismatch(&decoded, &proof.public_inputs), None);
+    }
+
+    #[test]
+    fn test_prove_transaction_binds_tx_hash() {
+        let tx = signed_tx(vec![0x12, 0x13, 0x9E, 0x40], 1);
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_transaction(&tx, 0, vec![]).unwrap();
+        assert_eq!(proof.public_inputs.tx_hash, tx.hash());
+        assert_eq!(proof.output.result, Some(StackItem::Integer(5)));
+        assert!(prover.verify(&proof));
+
+        assert!(matches!(
+            prover.prove_transaction(&tx, 1, vec![]),
+            Err(ProverError::Transaction(TxError::VerificationFailed(0)))
+        ));
+    }
+
+    #[test]
+    fn test_prove_witness() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let tx = signed_tx(vec![0x40], 1);
+        let witness = &tx.witnesses[0];
+        let payload = tx.sign_data(0);
+
+        let proof = prover
+            .prove_witness(
+                &witness.verification_script,
+                &witness.invocation_script,
+                &payload,
+            )
+            .unwrap();
+        assert!(prover.verify(&proof));
+        assert!(proof.public_inputs.execution_success);
+        assert_eq!(proof.public_inputs.tx_hash, NeoProver::hash_data(&payload));
+        assert_eq!(
+            proof.public_inputs.script_hash,
+            NeoProver::hash_data(&witness.verification_script)
+        );
+        assert_eq!(
+            proof.output.result,
+            Some(neo_vm_core::StackItem::Boolean(true))
+        );
+
+        assert_eq!(
+            prover
+                .prove_witness(
+                    &witness.verification_script,
+                    &witness.invocation_script,
+                    &tx.sign_data(1),
+                )
+                .err(),
+            Some(ProverError::InvalidWitness(WitnessError::Rejected))
+        );
+    }
+
+    #[test]
+    fn test_prove_block_chains_storage() {
+        // PUSHDATA1 "v", PUSHDATA1 "k", SYSCALL System.Storage.Put, RET
+        let put = signed_tx(
+            vec![
+                0x0C, 0x01, b'v', 0x0C, 0x01, b'k', 0x41, 0x11, 0x00, 0x00, 0x00, 0x40,
+            ],
+            1,
+        );
+        // PUSHDATA1 "k", SYSCALL System.Storage.Get, RET
+        let get = signed_tx(
+            vec![0x0C, 0x01, b'k', 0x41, 0x10, 0x00, 0x00, 0x00, 0x40],
+            2,
+        );
+        let transactions = vec![put, get];
+        let header = BlockHeader {
+            version: 0,
+            prev_hash: [0u8; 32],
+            merkle_root: merkle_root(&[transactions[0].hash(), transactions[1].hash()]),
+            timestamp: 0,
+            nonce: 0,
+            index: 1,
+            primary_index: 0,
+            next_consensus: [0u8; 20],
+        };
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+
+        let proof = prover
+            .prove_block(
+                &header,
+                &transactions,
+                0,
+                vec![],
+                TrieStorage::new().root_hash(),
+            )
+            .unwrap();
+        assert_eq!(
+            proof.transactions[1].output.result,
+            Some(StackItem::ByteString(b"v".to_vec().into()))
+        );
+        let mut key = vec![0u8; 20];
+        key.push(b'k');
+        let expected: TrieStorage = [(key, b"v".to_vec())].into_iter().collect();
+        assert_eq!(proof.public_inputs.post_state_root, expected.root_hash());
+        assert!(prover.verify_block(&proof));
+
+        let mut tampered = proof.clone();
+        tampered.public_inputs.post_state_root = [0u8; 32];
+        assert!(!prover.verify_block(&tampered));
+
+        assert_eq!(
+            prover
+                .prove_block(&header, &transactions[..1], 0, vec![], [0u8; 32])
+                .unwrap_err(),
+            BlockError::MerkleRoot
+        );
+    }
+
+    #[test]
+    fn test_header_chain_proof() {
+        let trusted = BlockHeader {
+            version: 0,
+            prev_hash: [0u8; 32],
+            merkle_root: [0u8; 32],
+            timestamp: 0,
+            nonce: 0,
+            index: 7,
+            primary_index: 0,
+            next_consensus: [1u8; 20],
+        };
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+
+        let proof = prover
+            .prove_header_chain(&LightClientInput {
+                network: 0,
+                trusted: trusted.clone(),
+                headers: vec![],
+            })
+            .unwrap();
+        assert_eq!(proof.output.latest_hash, trusted.hash());
+        assert_eq!(proof.output.latest_index, 7);
+        assert!(prover.verify_header_chain_proof(&proof));
+
+        let mut tampered = proof.clone();
+        tampered.output.latest_index = 8;
+        assert!(!prover.verify_header_chain_proof(&tampered));
+    }
+
+    #[test]
+    fn test_input_hash_is_canonical() {
         let input = ProofInput {
             script: vec![0x12, 0x13, 0x9E, 0x40],
-            arguments: vec![StackItem::Integer(7)],
+            arguments: vec![StackItem::Array(vec![StackItem::Integer(7)])],
             gas_limit: 123,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
         };
 
-        let guest = build_guest_input(&input);
-        let bytes = bincode::serialize(&guest).expect("serialize");
-        let hash = NeoProver::hash_data(&bytes);
+        // Nested arguments reach the guest intact, so both sides hash them
+        let guest = build_guest_input(&input, [0u8; 32], None);
+        assert_eq!(
+            guest.arguments,
+            vec![GuestStackItem::Array(vec![GuestStackItem::Integer(7)])]
+        );
 
-        assert_eq!(hash, NeoProver::hash_guest_input(&input));
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove(input.clone()).unwrap();
+        assert_eq!(proof.public_inputs.input_hash, input.input_hash([0u8; 32]));
     }
 }
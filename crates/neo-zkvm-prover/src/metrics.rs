@@ -0,0 +1,161 @@
+//! Prometheus metrics for proving services
+//!
+//! [`ProverMetrics`] is registered with any [`prometheus::Registry`] and
+//! attached to provers with [`crate::NeoProver::with_metrics`]; one instance
+//! can be shared by all provers of a service.
+
+use crate::{NeoProof, ProofMode};
+use prometheus::{
+    exponential_buckets, Histogram, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry,
+};
+use std::time::Duration;
+
+/// Proof and verification metrics
+///
+/// | Metric | Type | Labels |
+/// |--------|------|--------|
+/// | `neo_zkvm_proofs_generated_total` | counter | `mode` |
+/// | `neo_zkvm_proof_latency_seconds` | histogram | `mode` |
+/// | `neo_zkvm_verify_failures_total` | counter | `reason` |
+/// | `neo_zkvm_proof_gas_consumed` | histogram | |
+/// | `neo_zkvm_proof_cache_hits_total` | counter | `mode` |
+///
+/// `mode` is the mode of the generated proof, so SP1 requests that fell
+/// back to mock proofs count as `mock`.
+#[derive(Clone, Debug)]
+pub struct ProverMetrics {
+    proofs_generated: IntCounterVec,
+    proof_latency: HistogramVec,
+    verify_failures: IntCounterVec,
+    gas_consumed: Histogram,
+    cache_hits: IntCounterVec,
+}
+
+impl ProverMetrics {
+    pub fn new() -> prometheus::Result<Self> {
+        Ok(Self {
+            proofs_generated: IntCounterVec::new(
+                Opts::new(
+                    "neo_zkvm_proofs_generated_total",
+                    "Proofs generated, by proof mode",
+                ),
+                &["mode"],
+            )?,
+            // 1ms for mock proofs up to about an hour for Groth16
+            proof_latency: HistogramVec::new(
+                HistogramOpts::new(
+                    "neo_zkvm_proof_latency_seconds",
+                    "Time to generate a proof, by proof mode",
+                )
+                .buckets(exponential_buckets(0.001, 4.0, 12)?),
+                &["mode"],
+            )?,
+            verify_failures: IntCounterVec::new(
+                Opts::new(
+                    "neo_zkvm_verify_failures_total",
+                    "Proofs rejected by verification, by reason",
+                ),
+                &["reason"],
+            )?,
+            gas_consumed: Histogram::with_opts(
+                HistogramOpts::new(
+                    "neo_zkvm_proof_gas_consumed",
+                    "Gas consumed by proven executions",
+                )
+                .buckets(exponential_buckets(100.0, 4.0, 12)?),
+            )?,
+            cache_hits: IntCounterVec::new(
+                Opts::new(
+                    "neo_zkvm_proof_cache_hits_total",
+                    "Proofs served from the proof cache, by proof mode",
+                ),
+                &["mode"],
+            )?,
+        })
+    }
+
+    /// Register every metric with `registry`
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.proofs_generated.clone()))?;
+        registry.register(Box::new(self.proof_latency.clone()))?;
+        registry.register(Box::new(self.verify_failures.clone()))?;
+        registry.register(Box::new(self.gas_consumed.clone()))?;
+        registry.register(Box::new(self.cache_hits.clone()))?;
+        Ok(())
+    }
+
+    pub(crate) fn observe_proof(&self, proof: &NeoProof, elapsed: Duration) {
+        let mode = mode_label(proof.proof_mode);
+        self.proofs_generated.with_label_values(&[mode]).inc();
+        self.proof_latency
+            .with_label_values(&[mode])
+            .observe(elapsed.as_secs_f64());
+        self.gas_consumed
+            .observe(proof.public_inputs.gas_consumed as f64);
+    }
+
+    pub(crate) fn observe_cache_hit(&self, proof: &NeoProof) {
+        self.cache_hits
+            .with_label_values(&[mode_label(proof.proof_mode)])
+            .inc();
+    }
+
+    pub(crate) fn observe_verify_failure(&self, reason: &str) {
+        self.verify_failures.with_label_values(&[reason]).inc();
+    }
+}
+
+fn mode_label(mode: ProofMode) -> &'static str {
+    match mode {
+        ProofMode::Execute => "execute",
+        ProofMode::Mock => "mock",
+        ProofMode::Sp1 => "sp1",
+        ProofMode::Plonk => "plonk",
+        ProofMode::Groth16 => "groth16",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NeoProver, ProverConfig};
+    use std::sync::Arc;
+
+    fn sample(registry: &Registry, name: &str) -> Vec<prometheus::proto::Metric> {
+        registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == name)
+            .map(|family| family.get_metric().to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_prover_metrics() {
+        let registry = Registry::new();
+        let metrics = Arc::new(ProverMetrics::new().unwrap());
+        metrics.register(&registry).unwrap();
+
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        )
+        .with_metrics(metrics);
+        let mut proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        proof.proof_bytes.truncate(1);
+        assert!(!prover.verify(&proof));
+
+        let generated = sample(&registry, "neo_zkvm_proofs_generated_total");
+        assert_eq!(generated.len(), 1);
+        assert_eq!(generated[0].get_label()[0].get_value(), "mock");
+        assert_eq!(generated[0].get_counter().get_value(), 1.0);
+
+        let gas = sample(&registry, "neo_zkvm_proof_gas_consumed");
+        assert_eq!(gas[0].get_histogram().get_sample_count(), 1);
+
+        let failures = sample(&registry, "neo_zkvm_verify_failures_total");
+        assert_eq!(failures[0].get_label()[0].get_value(), "malformed_proof");
+    }
+}
@@ -0,0 +1,101 @@
+//! Embedded guest programs
+//!
+//! The prover embeds more than one build of the guest program. Scripts that
+//! make no syscalls and check no signatures can be proven by the smaller
+//! [`ProgramId::Arithmetic`] build. By default [`crate::NeoProver`] picks the
+//! smallest program that supports each [`ProofInput`]; use
+//! [`NeoProver::with_program`](crate::NeoProver::with_program) to pin one.
+//!
+//! Each program has its own verifying key. Proofs record the SHA-256 of the
+//! program's ELF in [`ProofMetadata::elf_hash`](crate::ProofMetadata::elf_hash),
+//! which verifiers use to pick the key.
+
+use crate::{NEO_ZKVM_ARITHMETIC_ELF, NEO_ZKVM_ELF};
+use neo_vm_core::decode_script;
+use neo_vm_guest::ProofInput;
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+
+const SYSCALL: u8 = 0x41;
+const CHECKSIG: u8 = 0xF3;
+
+static ELF_HASHES: LazyLock<[[u8; 32]; 2]> =
+    LazyLock::new(|| ProgramId::ALL.map(|program| Sha256::digest(program.elf()).into()));
+
+/// A guest program embedded in the prover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgramId {
+    /// The full VM, [`NEO_ZKVM_ELF`]
+    Vm,
+    /// The VM without `SYSCALL` and `CHECKSIG`, [`NEO_ZKVM_ARITHMETIC_ELF`]
+    ///
+    /// Without storage, runtime and crypto interop the guest is smaller,
+    /// which makes key setup and proving cheaper.
+    Arithmetic,
+}
+
+impl ProgramId {
+    /// Every program, from most to least capable
+    pub const ALL: [ProgramId; 2] = [ProgramId::Vm, ProgramId::Arithmetic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProgramId::Vm => "vm",
+            ProgramId::Arithmetic => "arithmetic",
+        }
+    }
+
+    pub fn elf(self) -> &'static [u8] {
+        match self {
+            ProgramId::Vm => NEO_ZKVM_ELF,
+            ProgramId::Arithmetic => NEO_ZKVM_ARITHMETIC_ELF,
+        }
+    }
+
+    /// SHA-256 of [`Self::elf`]
+    pub fn elf_hash(self) -> [u8; 32] {
+        ELF_HASHES[self as usize]
+    }
+
+    /// Program whose ELF has this SHA-256
+    pub fn from_elf_hash(elf_hash: &[u8; 32]) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|program| program.elf_hash() == *elf_hash)
+    }
+
+    /// Whether the ELF was built, rather than a placeholder left when the
+    /// SP1 toolchain is not installed
+    pub fn is_available(self) -> bool {
+        crate::NeoProver::is_real_elf(self.elf())
+    }
+
+    /// Whether this program runs `input` the way `neo-vm-core` does
+    ///
+    /// Only the instructions decoded from the start of the script are
+    /// checked, so data hidden after a `RET` counts as code.
+    pub fn supports(self, input: &ProofInput) -> bool {
+        match self {
+            ProgramId::Vm => true,
+            ProgramId::Arithmetic => decode_script(&input.script)
+                .instructions()
+                .iter()
+                .all(|instruction| !matches!(instruction.opcode, SYSCALL | CHECKSIG)),
+        }
+    }
+
+    /// The smallest program that supports `input`
+    pub fn for_input(input: &ProofInput) -> Self {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|program| program.supports(input))
+            .unwrap_or(ProgramId::Vm)
+    }
+}
+
+impl std::fmt::Display for ProgramId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
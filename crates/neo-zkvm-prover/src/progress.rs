@@ -0,0 +1,86 @@
+//! Proof generation progress
+//!
+//! Every stage of [`crate::NeoProver::prove`] runs inside a `tracing` span
+//! (`host_execute`, `setup`, `execute`, `prove`, `verify`). SP1's own spans,
+//! such as `prove_core`, `compress` and `wrap_groth16_bn254`, nest inside
+//! `prove`. A callback set with [`crate::NeoProver::with_progress`] receives
+//! the same stages as [`ProgressEvent`]s.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info_span;
+
+/// Stage of proof generation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProofStage {
+    /// Running the input on `neo-vm-core`
+    HostExecution,
+    /// Generating SP1 proving and verifying keys
+    Setup,
+    /// Executing the guest program under SP1
+    Execute,
+    /// Generating the SP1 proof, including compression and wrapping
+    Prove,
+    /// Verifying the fresh SP1 proof
+    Verify,
+}
+
+/// Progress reported to a callback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressEvent {
+    Started(ProofStage),
+    Finished {
+        stage: ProofStage,
+        elapsed: Duration,
+    },
+}
+
+/// Callback receiving [`ProgressEvent`]s, possibly from a prover thread
+pub type ProgressCallback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub(crate) struct Progress(Option<ProgressCallback>);
+
+impl Progress {
+    pub(crate) fn new(callback: ProgressCallback) -> Self {
+        Self(Some(callback))
+    }
+
+    /// Run `f` as `stage`, inside its span and reported to the callback
+    pub(crate) fn stage<T>(&self, stage: ProofStage, f: impl FnOnce() -> T) -> T {
+        let span = match stage {
+            ProofStage::HostExecution => info_span!("host_execute"),
+            ProofStage::Setup => info_span!("setup"),
+            ProofStage::Execute => info_span!("execute"),
+            ProofStage::Prove => info_span!("prove"),
+            ProofStage::Verify => info_span!("verify"),
+        };
+        let _guard = span.enter();
+
+        self.emit(ProgressEvent::Started(stage));
+        let start = Instant::now();
+        let result = f();
+        self.emit(ProgressEvent::Finished {
+            stage,
+            elapsed: start.elapsed(),
+        });
+        result
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.0 {
+            callback(event);
+        }
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() {
+            "Progress(callback)"
+        } else {
+            "Progress(none)"
+        })
+    }
+}
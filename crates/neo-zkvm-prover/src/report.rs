@@ -0,0 +1,121 @@
+//! SP1 execution statistics
+//!
+//! Cycle counts come from executing the guest program under SP1, which is
+//! cheap next to proving, so they estimate proving cost up front.
+
+use neo_vm_guest::ProofOutput;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Group of RISC-V instructions executed by the guest program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InstructionCategory {
+    /// Integer arithmetic, logic, shifts, comparisons and `auipc`
+    Alu,
+    Mul,
+    /// Division and remainder
+    DivRem,
+    Load,
+    Store,
+    Branch,
+    Jump,
+    /// `ecall`, including precompile invocations
+    Syscall,
+    /// `ebreak` and `unimp`
+    Other,
+}
+
+impl InstructionCategory {
+    fn of(mnemonic: &str) -> Self {
+        match mnemonic {
+            "mul" | "mulh" | "mulhu" | "mulhsu" => Self::Mul,
+            "div" | "divu" | "rem" | "remu" => Self::DivRem,
+            "lb" | "lh" | "lw" | "lbu" | "lhu" => Self::Load,
+            "sb" | "sh" | "sw" => Self::Store,
+            "beq" | "bne" | "blt" | "bge" | "bltu" | "bgeu" => Self::Branch,
+            "jal" | "jalr" => Self::Jump,
+            "ecall" => Self::Syscall,
+            "ebreak" | "unimp" => Self::Other,
+            _ => Self::Alu,
+        }
+    }
+}
+
+/// What executing the guest program cost under SP1
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionReport {
+    /// RISC-V cycles executed by the guest program
+    pub total_cycles: u64,
+    /// Cycles by instruction category, omitting categories never executed
+    pub cycles_by_category: BTreeMap<InstructionCategory, u64>,
+    /// Invocations of each SP1 precompile used, e.g. `SHA_COMPRESS`
+    pub precompile_calls: BTreeMap<String, u64>,
+}
+
+/// What proving an input would cost, from [`crate::NeoProver::preflight`]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PreflightReport {
+    /// Gas the script consumes
+    pub gas: u64,
+    /// RISC-V cycles of the guest program, `None` when the guest ELF was not
+    /// built or the guest failed
+    pub sp1_cycles: Option<u64>,
+    /// Full SP1 statistics behind [`sp1_cycles`](Self::sp1_cycles)
+    pub execution_report: Option<ExecutionReport>,
+    /// Result of running the input on `neo-vm-core`
+    pub output: ProofOutput,
+    /// Whether the script faults or the guest program aborts under SP1
+    pub would_fault: bool,
+}
+
+impl ExecutionReport {
+    pub(crate) fn from_sp1(report: &sp1_sdk::ExecutionReport) -> Self {
+        let mut cycles_by_category = BTreeMap::new();
+        for (opcode, &count) in report.opcode_counts.iter() {
+            if count > 0 {
+                *cycles_by_category
+                    .entry(InstructionCategory::of(opcode.mnemonic()))
+                    .or_insert(0) += count;
+            }
+        }
+        let precompile_calls = report
+            .syscall_counts
+            .iter()
+            .filter(|(code, &count)| count > 0 && code.as_air_id().is_some())
+            .map(|(code, &count)| (code.to_string(), count))
+            .collect();
+
+        Self {
+            total_cycles: report.total_instruction_count(),
+            cycles_by_category,
+            precompile_calls,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_categories() {
+        assert_eq!(InstructionCategory::of("add"), InstructionCategory::Alu);
+        assert_eq!(InstructionCategory::of("auipc"), InstructionCategory::Alu);
+        assert_eq!(InstructionCategory::of("mulhu"), InstructionCategory::Mul);
+        assert_eq!(InstructionCategory::of("remu"), InstructionCategory::DivRem);
+        assert_eq!(InstructionCategory::of("lbu"), InstructionCategory::Load);
+        assert_eq!(InstructionCategory::of("sw"), InstructionCategory::Store);
+        assert_eq!(InstructionCategory::of("bgeu"), InstructionCategory::Branch);
+        assert_eq!(InstructionCategory::of("jalr"), InstructionCategory::Jump);
+        assert_eq!(
+            InstructionCategory::of("ecall"),
+            InstructionCategory::Syscall
+        );
+    }
+
+    #[test]
+    fn test_empty_sp1_report() {
+        let report = ExecutionReport::from_sp1(&sp1_sdk::ExecutionReport::default());
+        assert_eq!(report, ExecutionReport::default());
+    }
+}
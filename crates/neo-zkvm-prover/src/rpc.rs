@@ -0,0 +1,408 @@
+//! Neo RPC integration
+//!
+//! Fetches a deployed contract and its storage from a Neo N3 node
+//! (`getcontractstate`, `findstorage`) and runs a method invocation through
+//! the prover: what `invokefunction` would report, but as a verifiable proof.
+//!
+//! The proven script runs in the default storage context, so the contract's
+//! entries are seeded under that context rather than under the contract hash.
+
+use crate::{NeoProof, NeoProver, ProverError};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, StackItem, VMLimits};
+use neo_vm_guest::ProofInput;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// Storage context of the proven script
+const DEFAULT_CONTEXT: [u8; 20] = [0u8; 20];
+
+/// Storage entries as (key, value) pairs
+pub type StorageEntries = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Errors talking to a Neo node
+#[derive(Debug, Error)]
+pub enum RpcError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("Contract has no method {name} taking {parameters} parameters")]
+    UnknownMethod { name: String, parameters: usize },
+    #[error("Proving failed: {0}")]
+    Prover(#[from] ProverError),
+}
+
+/// A method from a contract manifest's ABI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractMethod {
+    pub name: String,
+    pub parameters: usize,
+    /// Entry point within the NEF script
+    pub offset: usize,
+}
+
+/// A deployed contract as returned by `getcontractstate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractState {
+    /// Contract hash as displayed by the node, e.g. `0xd2a4...`
+    pub hash: String,
+    /// Manifest name
+    pub name: String,
+    /// NEF script
+    pub script: Vec<u8>,
+    pub methods: Vec<ContractMethod>,
+}
+
+impl ContractState {
+    /// Find a method by name and parameter count, as Neo resolves calls
+    pub fn method(&self, name: &str, parameters: usize) -> Result<&ContractMethod, RpcError> {
+        self.methods
+            .iter()
+            .find(|m| m.name == name && m.parameters == parameters)
+            .ok_or_else(|| RpcError::UnknownMethod {
+                name: name.to_string(),
+                parameters,
+            })
+    }
+
+    /// Build the input that invokes `method` with `args` against `storage`
+    ///
+    /// The script is the NEF script behind a jump to the method's offset, so
+    /// relative branches inside the contract keep their targets. `args` are
+    /// in declaration order; the first one ends up on top of the stack.
+    pub fn invocation(
+        &self,
+        method: &str,
+        args: Vec<StackItem>,
+        storage: StorageEntries,
+        gas_limit: u64,
+    ) -> Result<ProofInput, RpcError> {
+        let entry = self.method(method, args.len())?;
+        let storage = storage
+            .into_iter()
+            .map(|(key, value)| {
+                let mut full = DEFAULT_CONTEXT.to_vec();
+                full.extend_from_slice(&key);
+                (full, value)
+            })
+            .collect();
+
+        Ok(ProofInput {
+            script: entry_script(&self.script, entry.offset),
+            arguments: args.into_iter().rev().collect(),
+            gas_limit,
+            storage,
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        })
+    }
+}
+
+/// Prefix `script` with a jump to `offset`
+fn entry_script(script: &[u8], offset: usize) -> Vec<u8> {
+    if offset == 0 {
+        return script.to_vec();
+    }
+    let mut out = match i8::try_from(offset + 2) {
+        // JMP
+        Ok(jump) => vec![0x22, jump as u8],
+        // JMP_L
+        Err(_) => {
+            let mut jump = vec![0x23];
+            jump.extend_from_slice(&((offset + 5) as i32).to_le_bytes());
+            jump
+        }
+    };
+    out.extend_from_slice(script);
+    out
+}
+
+/// Blocking JSON-RPC client for a Neo N3 node
+pub struct RpcClient {
+    url: String,
+    http: reqwest::blocking::Client,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Fetch a contract's NEF script and manifest
+    pub fn get_contract_state(&self, hash: &str) -> Result<ContractState, RpcError> {
+        parse_contract_state(self.call("getcontractstate", json!([hash]))?)
+    }
+
+    /// Fetch all storage entries of a contract, keys relative to the contract
+    pub fn find_storage(&self, hash: &str) -> Result<StorageEntries, RpcError> {
+        let mut entries = Vec::new();
+        let mut start = 0;
+        loop {
+            let page = parse_storage_page(self.call("findstorage", json!([hash, "", start]))?)?;
+            entries.extend(page.entries);
+            match page.next {
+                Some(next) => start = next,
+                None => return Ok(entries),
+            }
+        }
+    }
+
+    /// Fetch a contract and its storage and build the input invoking `method`
+    pub fn invocation_input(
+        &self,
+        hash: &str,
+        method: &str,
+        args: Vec<StackItem>,
+        gas_limit: u64,
+    ) -> Result<ProofInput, RpcError> {
+        let contract = self.get_contract_state(hash)?;
+        let storage = self.find_storage(hash)?;
+        contract.invocation(method, args, storage, gas_limit)
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response: RawResponse = self.http.post(&self.url).json(&request).send()?.json()?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(RpcError::Rpc {
+                code: error.code,
+                message: error.message,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(RpcError::InvalidResponse("no result".into())),
+        }
+    }
+}
+
+impl NeoProver {
+    /// Prove an invocation of a deployed contract against its current storage
+    pub fn prove_invocation(
+        &self,
+        client: &RpcClient,
+        hash: &str,
+        method: &str,
+        args: Vec<StackItem>,
+        gas_limit: u64,
+    ) -> Result<NeoProof, RpcError> {
+        let input = client.invocation_input(hash, method, args, gas_limit)?;
+        Ok(self.prove(input)?)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawResponse {
+    result: Option<Value>,
+    error: Option<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RawContractState {
+    hash: String,
+    nef: RawNef,
+    manifest: RawManifest,
+}
+
+#[derive(Deserialize)]
+struct RawNef {
+    script: String,
+}
+
+#[derive(Deserialize)]
+struct RawManifest {
+    name: String,
+    abi: RawAbi,
+}
+
+#[derive(Deserialize)]
+struct RawAbi {
+    methods: Vec<RawMethod>,
+}
+
+#[derive(Deserialize)]
+struct RawMethod {
+    name: String,
+    parameters: Vec<Value>,
+    offset: usize,
+}
+
+#[derive(Deserialize)]
+struct RawStoragePage {
+    results: Vec<RawStorageEntry>,
+    next: u64,
+    truncated: bool,
+}
+
+#[derive(Deserialize)]
+struct RawStorageEntry {
+    key: String,
+    value: String,
+}
+
+struct StoragePage {
+    entries: StorageEntries,
+    /// Where the next page starts, if the result was truncated
+    next: Option<u64>,
+}
+
+fn decode(field: &str) -> Result<Vec<u8>, RpcError> {
+    STANDARD
+        .decode(field)
+        .map_err(|e| RpcError::InvalidResponse(e.to_string()))
+}
+
+fn parse_contract_state(result: Value) -> Result<ContractState, RpcError> {
+    let raw: RawContractState =
+        serde_json::from_value(result).map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+    Ok(ContractState {
+        hash: raw.hash,
+        name: raw.manifest.name,
+        script: decode(&raw.nef.script)?,
+        methods: raw
+            .manifest
+            .abi
+            .methods
+            .into_iter()
+            .map(|m| ContractMethod {
+                name: m.name,
+                parameters: m.parameters.len(),
+                offset: m.offset,
+            })
+            .collect(),
+    })
+}
+
+fn parse_storage_page(result: Value) -> Result<StoragePage, RpcError> {
+    let raw: RawStoragePage =
+        serde_json::from_value(result).map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+    let entries = raw
+        .results
+        .iter()
+        .map(|entry| Ok((decode(&entry.key)?, decode(&entry.value)?)))
+        .collect::<Result<_, RpcError>>()?;
+    Ok(StoragePage {
+        entries,
+        next: raw.truncated.then_some(raw.next),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo_vm_guest::execute;
+
+    /// `answer()` at 0: PUSH1, RET
+    /// `sub(a, b)` at 2: SWAP, SUB, RET
+    /// `get()` at 5: PUSHDATA1 "k", SYSCALL System.Storage.Get, RET
+    fn contract() -> ContractState {
+        parse_contract_state(json!({
+            "id": 5,
+            "updatecounter": 0,
+            "hash": "0x0102030405060708090a0b0c0d0e0f1011121314",
+            "nef": {
+                "magic": 860243278,
+                "compiler": "test",
+                "source": "",
+                "tokens": [],
+                "script": STANDARD.encode([
+                    0x11, 0x40, 0x50, 0x9F, 0x40, 0x0C, 0x01, b'k', 0x41, 0x10, 0x00, 0x00,
+                    0x00, 0x40,
+                ]),
+                "checksum": 0
+            },
+            "manifest": {
+                "name": "Test",
+                "abi": {
+                    "methods": [
+                        {"name": "answer", "parameters": [], "returntype": "Integer", "offset": 0, "safe": true},
+                        {"name": "sub", "parameters": [
+                            {"name": "a", "type": "Integer"},
+                            {"name": "b", "type": "Integer"}
+                        ], "returntype": "Integer", "offset": 2, "safe": true},
+                        {"name": "get", "parameters": [], "returntype": "ByteArray", "offset": 5, "safe": true}
+                    ],
+                    "events": []
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_contract_state() {
+        let contract = contract();
+        assert_eq!(contract.name, "Test");
+        assert_eq!(contract.script.len(), 14);
+        assert_eq!(contract.method("sub", 2).unwrap().offset, 2);
+        assert!(matches!(
+            contract.method("sub", 1),
+            Err(RpcError::UnknownMethod { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_storage_page() {
+        let page = parse_storage_page(json!({
+            "truncated": true,
+            "next": 2,
+            "results": [
+                {"key": STANDARD.encode(b"k"), "value": STANDARD.encode(b"v")},
+                {"key": STANDARD.encode(b"x"), "value": STANDARD.encode(b"y")}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(page.entries[0], (b"k".to_vec(), b"v".to_vec()));
+        assert_eq!(page.next, Some(2));
+
+        let last = parse_storage_page(json!({"truncated": false, "next": 2, "results": []}));
+        assert_eq!(last.unwrap().next, None);
+    }
+
+    #[test]
+    fn test_invocation_runs_method() {
+        let contract = contract();
+        let storage = vec![(b"k".to_vec(), b"v".to_vec())];
+
+        let answer = contract
+            .invocation("answer", vec![], storage.clone(), 1_000_000)
+            .unwrap();
+        assert_eq!(execute(answer).result, Some(StackItem::Integer(1)));
+
+        let args = vec![StackItem::Integer(10), StackItem::Integer(3)];
+        let sub = contract
+            .invocation("sub", args, storage.clone(), 1_000_000)
+            .unwrap();
+        assert_eq!(execute(sub).result, Some(StackItem::Integer(7)));
+
+        let get = contract
+            .invocation("get", vec![], storage, 1_000_000)
+            .unwrap();
+        assert_eq!(
+            execute(get).result,
+            Some(StackItem::ByteString(b"v".to_vec().into()))
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Neo zkVM proving service
+//!
+//! A gRPC service for running the prover as shared infrastructure: clients
+//! submit proof jobs, poll their status and download the proofs, which are
+//! kept on disk next to a persistent record of every job. The
+//! `neo-zkvm-service` binary serves [`ZkvmService`]; the protocol is defined
+//! in `proto/neo_zkvm_service.proto`. [`rpc::router`] adds Neo RPC compatible
+//! `invokescript` and `invokefunction` over JSON-RPC. A shared service can
+//! require API keys and enforce per-tenant quotas, see [`tenants`], and
+//! restrict the scripts it proves, see [`policy`].
+//!
+//! ```no_run
+//! use neo_zkvm_service::{ServiceConfig, ZkvmService};
+//!
+//! # async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+//! let service = ZkvmService::start(ServiceConfig::default())?;
+//! tonic::transport::Server::builder()
+//!     .add_service(service.into_server())
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod policy;
+pub mod rpc;
+pub mod service;
+pub mod store;
+pub mod tenants;
+
+/// Messages and service traits generated from `neo_zkvm_service.proto`
+pub mod proto {
+    tonic::include_proto!("neozkvm.v1");
+}
+
+pub use policy::{OpcodeRule, PolicyError, PolicyViolation, ScriptPolicy};
+pub use service::{ServiceConfig, ServiceError, ZkvmService};
+pub use store::{JobRecord, JobState, JobStore, StoreError};
+pub use tenants::{Priority, TenantConfig, TenantError, API_KEY_HEADER};
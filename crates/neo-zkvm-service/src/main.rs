@@ -0,0 +1,167 @@
+//! `neo-zkvm-service` - gRPC proving service with an optional Neo RPC
+//! compatible JSON-RPC endpoint
+
+use neo_zkvm_prover::{CachePolicy, ProofMode};
+use neo_zkvm_service::{rpc, ScriptPolicy, ServiceConfig, TenantConfig, ZkvmService};
+use std::env;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: neo-zkvm-service [OPTIONS]
+
+OPTIONS:
+    --listen <addr>          gRPC address (default: 127.0.0.1:50051)
+    --rpc-listen <addr>      Also serve invokescript/invokefunction JSON-RPC here
+    --neo-rpc <url>          Neo node that invokefunction fetches contracts from
+    --data-dir <dir>         Job records and proofs (default: neo-zkvm-data)
+    --max-concurrent <n>     Jobs proven at the same time (default: 1)
+    --max-pending <n>        Queued and running jobs accepted (default: 1024)
+    --mode <mode>            Default proof mode: execute, mock, sp1, plonk, groth16 (default: sp1)
+    --max-gas <limit>        Highest gas limit a job may request (default: 100000000)
+    --timeout <secs>         Time limit for SP1 proofs
+    --max-memory <bytes>     Guest memory limit for SP1 execution
+    --mock-key <hex>         32-byte key authenticating mock proofs
+    --tenants <file>         JSON array of tenants with API keys and quotas
+    --policy <file>          JSON script policy: allowed scripts, size limit, banned opcodes
+    --cache-entries <n>      Cache proofs, keeping the n most recently served (default: 1024)
+    --cache-ttl <secs>       Cache proofs, dropping them after secs
+    --allow-mock             Accept mock proofs in Verify";
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{USAGE}");
+        return;
+    }
+    if let Err(e) = serve(&args).await {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Listen addresses and service settings from the command line
+struct Options {
+    addr: SocketAddr,
+    rpc_addr: Option<SocketAddr>,
+    config: ServiceConfig,
+}
+
+async fn serve(args: &[String]) -> Result<(), String> {
+    let Options {
+        addr,
+        rpc_addr,
+        config,
+    } = parse_args(args)?;
+    let data_dir = config.data_dir.display().to_string();
+    let service = ZkvmService::start(config).map_err(|e| e.to_string())?;
+
+    if let Some(rpc_addr) = rpc_addr {
+        let listener = tokio::net::TcpListener::bind(rpc_addr)
+            .await
+            .map_err(|e| format!("Cannot listen on {rpc_addr}: {e}"))?;
+        let router = rpc::router(service.clone());
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                eprintln!("Error: JSON-RPC server stopped: {e}");
+            }
+        });
+        println!("JSON-RPC listening on {rpc_addr}");
+    }
+
+    println!("neo-zkvm-service listening on {addr}, data in {data_dir}");
+    tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve_with_shutdown(addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut addr: SocketAddr = "127.0.0.1:50051".parse().expect("valid default address");
+    let mut rpc_addr = None;
+    let mut config = ServiceConfig::default();
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        if flag == "--allow-mock" {
+            config.allow_mock_proofs = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {flag}\n\n{USAGE}"))?;
+        let invalid = |what: &str| format!("Invalid {what} '{value}'");
+        match flag.as_str() {
+            "--listen" => addr = value.parse().map_err(|_| invalid("address"))?,
+            "--rpc-listen" => rpc_addr = Some(value.parse().map_err(|_| invalid("address"))?),
+            "--neo-rpc" => config.neo_rpc_url = Some(value.clone()),
+            "--data-dir" => config.data_dir = PathBuf::from(value),
+            "--max-concurrent" => {
+                config.max_concurrent_jobs = value.parse().map_err(|_| invalid("job count"))?
+            }
+            "--max-pending" => {
+                config.max_pending_jobs = value.parse().map_err(|_| invalid("job count"))?
+            }
+            "--mode" => config.default_mode = parse_mode(value).ok_or_else(|| invalid("mode"))?,
+            "--max-gas" => {
+                config.max_gas_limit = value.parse().map_err(|_| invalid("gas limit"))?
+            }
+            "--timeout" => {
+                let secs = value.parse().map_err(|_| invalid("timeout"))?;
+                config.prove_timeout = Some(Duration::from_secs(secs));
+            }
+            "--max-memory" => {
+                config.max_memory_bytes = Some(value.parse().map_err(|_| invalid("byte count"))?)
+            }
+            "--cache-entries" => {
+                let cache = config.proof_cache.get_or_insert_with(CachePolicy::default);
+                cache.max_entries = value.parse().map_err(|_| invalid("entry count"))?;
+            }
+            "--cache-ttl" => {
+                let secs = value.parse().map_err(|_| invalid("cache TTL"))?;
+                let cache = config.proof_cache.get_or_insert_with(CachePolicy::default);
+                cache.max_age = Some(Duration::from_secs(secs));
+            }
+            "--mock-key" => {
+                config.mock_key = hex::decode(value)
+                    .ok()
+                    .and_then(|key| key.try_into().ok())
+                    .ok_or_else(|| invalid("mock key"))?
+            }
+            "--tenants" => {
+                let json = fs::read_to_string(value)
+                    .map_err(|e| format!("Cannot read tenants file {value}: {e}"))?;
+                config.tenants = serde_json::from_str::<Vec<TenantConfig>>(&json)
+                    .map_err(|e| format!("Invalid tenants file {value}: {e}"))?;
+            }
+            "--policy" => {
+                let json = fs::read_to_string(value)
+                    .map_err(|e| format!("Cannot read policy file {value}: {e}"))?;
+                config.policy = serde_json::from_str::<ScriptPolicy>(&json)
+                    .map_err(|e| format!("Invalid policy file {value}: {e}"))?;
+            }
+            _ => return Err(format!("Unknown option '{flag}'\n\n{USAGE}")),
+        }
+    }
+    Ok(Options {
+        addr,
+        rpc_addr,
+        config,
+    })
+}
+
+fn parse_mode(mode: &str) -> Option<ProofMode> {
+    Some(match mode {
+        "execute" => ProofMode::Execute,
+        "mock" => ProofMode::Mock,
+        "sp1" => ProofMode::Sp1,
+        "plonk" => ProofMode::Plonk,
+        "groth16" => ProofMode::Groth16,
+        _ => return None,
+    })
+}
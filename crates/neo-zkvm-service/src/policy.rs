@@ -0,0 +1,437 @@
+//! Restrictions on the scripts the service proves
+//!
+//! A [`ScriptPolicy`] is checked when a job is submitted and when an
+//! invocation is built, before anything is queued or proven. It can limit
+//! the script size, allow only listed scripts, and ban opcodes found in the
+//! script's decoded instruction stream:
+//!
+//! ```json
+//! {"allowed_scripts": ["9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"],
+//!  "max_script_size": 4096,
+//!  "banned_opcodes": ["ABORT", {"opcode": "SYSCALL", "syscall": 17, "unless_signed": true}]}
+//! ```
+//!
+//! Scripts are identified by their SHA-256, the `script_hash` committed in
+//! the proof's public inputs. A rule with `syscall` matches only `SYSCALL`s
+//! with that id (17 is `System.Storage.Put`, see
+//! [`syscall`](neo_vm_core::engine::syscall)); `unless_signed` lifts the ban
+//! where a signature check guards the instruction, so the example refuses
+//! storage writes nobody signed for. A guard is a `CHECKSIG` or
+//! `System.Crypto.CheckSig`/`CheckMultisig` syscall followed directly by the
+//! `ASSERT` or `JMPIFNOT` that tests its result, somewhere before the
+//! instruction, with no jump, call, `PUSHA` or exception handler landing
+//! between the two. A check whose result is dropped guards nothing.
+//!
+//! The instruction stream is decoded linearly, as the VM only jumps to
+//! instruction boundaries. When opcodes are banned, scripts that do not
+//! decode to the end are refused, since their tail cannot be checked.
+
+use neo_vm_core::engine::syscall;
+use neo_vm_core::{decode_script, Instruction, OpcodeInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const JMPIFNOT: u8 = 0x26;
+const JMPIFNOT_L: u8 = 0x27;
+const ASSERT: u8 = 0x39;
+const SYSCALL: u8 = 0x41;
+const CHECKSIG: u8 = 0xF3;
+
+/// Scripts the service agrees to prove; the default allows any script
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScriptPolicy {
+    /// SHA-256 hashes of the scripts that may be proven, any if empty
+    #[serde(with = "hex_hashes")]
+    pub allowed_scripts: Vec<[u8; 32]>,
+    /// Largest script accepted, in bytes
+    pub max_script_size: Option<usize>,
+    pub banned_opcodes: Vec<OpcodeRule>,
+}
+
+/// An opcode refused by a [`ScriptPolicy`], written as its mnemonic alone
+/// or as an object with the fields below
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RuleRepr")]
+pub struct OpcodeRule {
+    /// Mnemonic, e.g. `PUSHINT8`
+    pub opcode: String,
+    /// Only ban `SYSCALL`s with this id
+    pub syscall: Option<u32>,
+    /// Allow the opcode where a tested signature check guards it
+    pub unless_signed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleRepr {
+    Opcode(String),
+    Rule(RuleFields),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFields {
+    opcode: String,
+    #[serde(default)]
+    syscall: Option<u32>,
+    #[serde(default)]
+    unless_signed: bool,
+}
+
+impl From<RuleRepr> for OpcodeRule {
+    fn from(repr: RuleRepr) -> Self {
+        match repr {
+            RuleRepr::Opcode(opcode) => Self::new(opcode),
+            RuleRepr::Rule(fields) => Self {
+                opcode: fields.opcode,
+                syscall: fields.syscall,
+                unless_signed: fields.unless_signed,
+            },
+        }
+    }
+}
+
+impl OpcodeRule {
+    /// Ban every occurrence of `opcode`
+    pub fn new(opcode: impl Into<String>) -> Self {
+        Self {
+            opcode: opcode.into(),
+            syscall: None,
+            unless_signed: false,
+        }
+    }
+
+    /// Ban `SYSCALL`s with id `id`
+    pub fn syscall(id: u32) -> Self {
+        Self {
+            syscall: Some(id),
+            ..Self::new("SYSCALL")
+        }
+    }
+
+    /// Lift the ban where a tested signature check guards the opcode
+    pub fn unless_signed(mut self) -> Self {
+        self.unless_signed = true;
+        self
+    }
+
+    fn matches(&self, code: u8, script: &[u8], instruction: &Instruction) -> bool {
+        instruction.opcode == code
+            && self
+                .syscall
+                .is_none_or(|id| syscall_id(script, instruction) == Some(id))
+    }
+}
+
+/// Why a policy could not be loaded
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error("Script policy bans unknown opcode {0}")]
+    UnknownOpcode(String),
+    #[error("Script policy gives a syscall id for {0}, which is not SYSCALL")]
+    NotSyscall(String),
+}
+
+/// Why a script was refused
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyViolation {
+    #[error("Script is {size} bytes, the policy allows at most {max}")]
+    TooLarge { size: usize, max: usize },
+    #[error("Script {} is not on the allow-list", hex::encode(.0))]
+    NotAllowed([u8; 32]),
+    #[error("Script does not decode past offset {0}")]
+    Malformed(usize),
+    #[error("{opcode} at offset {offset} is banned by the script policy")]
+    BannedOpcode { opcode: String, offset: usize },
+}
+
+impl ScriptPolicy {
+    /// Whether every banned opcode is a known mnemonic
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        for rule in &self.banned_opcodes {
+            let info = OpcodeInfo::by_name(&rule.opcode)
+                .ok_or_else(|| PolicyError::UnknownOpcode(rule.opcode.clone()))?;
+            if rule.syscall.is_some() && info.code != SYSCALL {
+                return Err(PolicyError::NotSyscall(rule.opcode.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check `script` against the policy
+    ///
+    /// Rules naming unknown opcodes match nothing; [`validate`](Self::validate)
+    /// reports them.
+    pub fn check(&self, script: &[u8]) -> Result<(), PolicyViolation> {
+        if let Some(max) = self.max_script_size.filter(|&max| script.len() > max) {
+            return Err(PolicyViolation::TooLarge {
+                size: script.len(),
+                max,
+            });
+        }
+        if !self.allowed_scripts.is_empty() {
+            let hash: [u8; 32] = Sha256::digest(script).into();
+            if !self.allowed_scripts.contains(&hash) {
+                return Err(PolicyViolation::NotAllowed(hash));
+            }
+        }
+        if self.banned_opcodes.is_empty() {
+            return Ok(());
+        }
+
+        let decoded = decode_script(script);
+        let end = decoded
+            .instructions()
+            .last()
+            .map_or(0, Instruction::next_offset);
+        if end != script.len() {
+            return Err(PolicyViolation::Malformed(end));
+        }
+        let instructions = decoded.instructions();
+        let targets = branch_targets(script, instructions);
+        let rules: Vec<_> = self
+            .banned_opcodes
+            .iter()
+            .filter_map(|rule| Some((rule, OpcodeInfo::by_name(&rule.opcode)?.code)))
+            .collect();
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Some((rule, _)) = rules.iter().find(|(rule, code)| {
+                rule.matches(*code, script, instruction)
+                    && !(rule.unless_signed && is_guarded(script, instructions, &targets, index))
+            }) {
+                return Err(PolicyViolation::BannedOpcode {
+                    opcode: rule.opcode.clone(),
+                    offset: instruction.offset,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn syscall_id(script: &[u8], instruction: &Instruction) -> Option<u32> {
+    (instruction.opcode == SYSCALL)
+        .then(|| u32::from_le_bytes(instruction.operand(script).try_into().unwrap()))
+}
+
+fn is_signature_check(script: &[u8], instruction: &Instruction) -> bool {
+    instruction.opcode == CHECKSIG
+        || matches!(
+            syscall_id(script, instruction),
+            Some(syscall::SYSTEM_CRYPTO_CHECKSIG | syscall::SYSTEM_CRYPTO_CHECKMULTISIG)
+        )
+}
+
+/// Whether a signature check, its result tested by the `ASSERT` or
+/// `JMPIFNOT` after it, runs before `instructions[index]` on every path
+///
+/// Execution enters the span after the test only by falling through it,
+/// unless a branch lands inside, so the nearest test before the instruction
+/// decides.
+fn is_guarded(
+    script: &[u8],
+    instructions: &[Instruction],
+    targets: &[isize],
+    index: usize,
+) -> bool {
+    let Some(test) = (1..index).rev().find(|&at| {
+        matches!(instructions[at].opcode, ASSERT | JMPIFNOT | JMPIFNOT_L)
+            && is_signature_check(script, &instructions[at - 1])
+    }) else {
+        return false;
+    };
+    let span = instructions[test].offset as isize + 1..=instructions[index].offset as isize;
+    !targets.iter().any(|target| span.contains(target))
+}
+
+/// Offsets the script can branch to, other than by falling through
+fn branch_targets(script: &[u8], instructions: &[Instruction]) -> Vec<isize> {
+    let mut targets = Vec::new();
+    for instruction in instructions {
+        let offset = instruction.offset as isize;
+        let operand = instruction.operand(script);
+        match instruction.opcode {
+            // JMP..JMPLE_L, CALL, CALL_L, ENDTRY, ENDTRY_L, PUSHA
+            0x22..=0x35 | 0x3D | 0x3E | 0x0A => targets.push(offset + relative(operand)),
+            // TRY, TRY_L: catch and finally offsets, zero when absent
+            0x3B | 0x3C => {
+                let (catch, finally) = operand.split_at(operand.len() / 2);
+                for handler in [catch, finally] {
+                    let delta = relative(handler);
+                    if delta != 0 {
+                        targets.push(offset + delta);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    targets
+}
+
+/// Signed little-endian offset from a 1- or 4-byte operand
+fn relative(operand: &[u8]) -> isize {
+    match *operand {
+        [byte] => byte as i8 as isize,
+        [a, b, c, d] => i32::from_le_bytes([a, b, c, d]) as isize,
+        _ => 0,
+    }
+}
+
+/// `Vec<[u8; 32]>` as a list of hex strings
+mod hex_hashes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hashes: &[[u8; 32]], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(hashes.iter().map(hex::encode))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<[u8; 32]>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|text| {
+                hex::decode(&text)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| de::Error::custom(format!("invalid script hash '{text}'")))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo_vm_core::ScriptBuilder;
+
+    fn syscall(id: u32) -> Vec<u8> {
+        ScriptBuilder::new().emit_syscall(id).to_bytes()
+    }
+
+    #[test]
+    fn test_size_and_allow_list() {
+        let script = vec![0x11, 0x12, 0x9E, 0x40];
+        assert_eq!(ScriptPolicy::default().check(&script), Ok(()));
+
+        let policy = ScriptPolicy {
+            max_script_size: Some(3),
+            ..ScriptPolicy::default()
+        };
+        assert_eq!(
+            policy.check(&script),
+            Err(PolicyViolation::TooLarge { size: 4, max: 3 })
+        );
+
+        let policy = ScriptPolicy {
+            allowed_scripts: vec![Sha256::digest(&script).into()],
+            ..ScriptPolicy::default()
+        };
+        assert_eq!(policy.check(&script), Ok(()));
+        assert!(matches!(
+            policy.check(&[0x40]),
+            Err(PolicyViolation::NotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_banned_opcodes() {
+        let put = syscall(syscall::SYSTEM_STORAGE_PUT);
+        let policy = ScriptPolicy {
+            banned_opcodes: vec![
+                OpcodeRule::new("ABORT"),
+                OpcodeRule::syscall(syscall::SYSTEM_STORAGE_PUT).unless_signed(),
+            ],
+            ..ScriptPolicy::default()
+        };
+        policy.validate().unwrap();
+
+        let mut script = vec![0x11];
+        script.extend_from_slice(&put);
+        assert_eq!(
+            policy.check(&script),
+            Err(PolicyViolation::BannedOpcode {
+                opcode: "SYSCALL".into(),
+                offset: 1
+            })
+        );
+        // Other syscalls pass, and an asserted signature check lifts the ban
+        assert_eq!(policy.check(&syscall(syscall::SYSTEM_RUNTIME_LOG)), Ok(()));
+        let mut signed = syscall(syscall::SYSTEM_CRYPTO_CHECKSIG);
+        signed.push(0x39);
+        signed.extend_from_slice(&put);
+        assert_eq!(policy.check(&signed), Ok(()));
+        signed.push(0x38);
+        assert!(matches!(
+            policy.check(&signed),
+            Err(PolicyViolation::BannedOpcode { offset: 11, .. })
+        ));
+
+        // A PUSHDATA1 operand hides nothing from the decoder
+        let hidden = ScriptBuilder::new().emit_push_bytes(&[0x38]).to_bytes();
+        assert_eq!(policy.check(&hidden), Ok(()));
+        assert_eq!(
+            policy.check(&[0x0C, 0x05, 0x38]),
+            Err(PolicyViolation::Malformed(0))
+        );
+    }
+
+    #[test]
+    fn test_unless_signed_needs_tested_check() {
+        let policy = ScriptPolicy {
+            banned_opcodes: vec![OpcodeRule::syscall(syscall::SYSTEM_STORAGE_PUT).unless_signed()],
+            ..ScriptPolicy::default()
+        };
+        let put = [0x41, 0x11, 0x00, 0x00, 0x00];
+        let check = |prefix: &[u8]| policy.check(&[prefix, &put, &[0x40]].concat());
+
+        // CHECKSIG, DROP: the result is thrown away
+        assert!(check(&[0xF3, 0x45]).is_err());
+        // CHECKSIG, JMPIFNOT past the write
+        assert_eq!(check(&[0xF3, 0x26, 0x07]), Ok(()));
+        // The test comes after the write it should guard
+        let late = [&put[..], &[0xF3, 0x39, 0x40]].concat();
+        assert!(policy.check(&late).is_err());
+        // JMP over CHECKSIG, ASSERT lands between the test and the write
+        assert!(check(&[0x22, 0x04, 0xF3, 0x39]).is_err());
+        // JMPIFNOT lands on the write when the check fails
+        assert!(check(&[0xF3, 0x26, 0x02]).is_err());
+    }
+
+    #[test]
+    fn test_policy_json() {
+        let policy: ScriptPolicy = serde_json::from_str(
+            r#"{"max_script_size": 10,
+                "banned_opcodes": ["ABORT", {"opcode": "SYSCALL", "syscall": 17, "unless_signed": true}]}"#,
+        )
+        .unwrap();
+        assert_eq!(policy.banned_opcodes[0], OpcodeRule::new("ABORT"));
+        assert_eq!(
+            policy.banned_opcodes[1],
+            OpcodeRule::syscall(17).unless_signed()
+        );
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(serde_json::from_str::<ScriptPolicy>(&json).unwrap(), policy);
+
+        let unknown = ScriptPolicy {
+            banned_opcodes: vec![OpcodeRule::new("abort")],
+            ..ScriptPolicy::default()
+        };
+        assert_eq!(
+            unknown.validate(),
+            Err(PolicyError::UnknownOpcode("abort".into()))
+        );
+        let not_syscall = ScriptPolicy {
+            banned_opcodes: vec![OpcodeRule {
+                syscall: Some(1),
+                ..OpcodeRule::new("ABORT")
+            }],
+            ..ScriptPolicy::default()
+        };
+        assert!(not_syscall.validate().is_err());
+        assert!(serde_json::from_str::<ScriptPolicy>(r#"{"allowed_scripts": ["00"]}"#).is_err());
+    }
+}
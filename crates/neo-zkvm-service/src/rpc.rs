@@ -0,0 +1,275 @@
+//! Neo RPC compatible JSON-RPC endpoint
+//!
+//! Serves `invokescript` and `invokefunction` with the parameters and
+//! response shape of a Neo N3 node, so Neo SDKs can point at the service
+//! instead of a node. Each result carries an extra `proof` field: the proof
+//! of the invocation in the `NeoProof::to_bytes` format, base64 encoded.
+//!
+//! ```json
+//! {"script": "EhOeQA==", "state": "HALT", "gasconsumed": "12",
+//!  "exception": null, "notifications": [],
+//!  "stack": [{"type": "Integer", "value": "5"}], "proof": "TlpLUA..."}
+//! ```
+//!
+//! Invocations are proven synchronously in the service's default mode.
+//! Signers are accepted but ignored, since scripts run without witnesses.
+//! `invokefunction` needs [`ServiceConfig::neo_rpc_url`]: contracts and
+//! their storage are fetched from that node. With tenants configured, the
+//! API key goes in the `x-api-key` header and each invocation counts
+//! against the tenant's slots and reserves the default gas limit from its
+//! daily quota, see [`tenants`](crate::tenants). Scripts the service's
+//! [`ScriptPolicy`](crate::ScriptPolicy) refuses fail with error -32003.
+
+use crate::service::Inner;
+use crate::tenants::{Tenant, API_KEY_HEADER};
+use crate::{ServiceConfig, ZkvmService};
+use axum::extract::State;
+use axum::http::{header, HeaderMap};
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use neo_vm_core::{
+    BlockContext, ContractParameter, GasSchedule, NotificationEvent, OracleResponses,
+    RuntimeConfig, StackItem, VMLimits,
+};
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::rpc::RpcClient;
+use neo_zkvm_prover::NeoProof;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const UNAUTHORIZED: i64 = -32001;
+const QUOTA_EXCEEDED: i64 = -32002;
+const POLICY_VIOLATION: i64 = -32003;
+
+/// Router answering JSON-RPC 2.0 requests, single or batched, on `POST /`
+pub fn router(service: ZkvmService) -> Router {
+    Router::new().route("/", post(handle)).with_state(service)
+}
+
+struct RpcFailure {
+    code: i64,
+    message: String,
+}
+
+/// Failures running the prover are internal errors
+impl From<String> for RpcFailure {
+    fn from(message: String) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+}
+
+impl RpcFailure {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+async fn handle(
+    State(service): State<ZkvmService>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    let api_key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|key| key.to_str().ok());
+    let tenant = service.authenticate(api_key);
+    let response = match (tenant, serde_json::from_str::<Value>(&body)) {
+        (Err(e), _) => failure(Value::Null, RpcFailure::new(UNAUTHORIZED, e)),
+        (_, Err(e)) => failure(Value::Null, RpcFailure::new(PARSE_ERROR, e.to_string())),
+        (_, Ok(Value::Array(batch))) if batch.is_empty() => {
+            failure(Value::Null, RpcFailure::new(INVALID_REQUEST, "Empty batch"))
+        }
+        (Ok(tenant), Ok(Value::Array(batch))) => {
+            let mut responses = Vec::with_capacity(batch.len());
+            for request in batch {
+                responses.push(call(&service, tenant.as_ref(), request).await);
+            }
+            Value::Array(responses)
+        }
+        (Ok(tenant), Ok(request)) => call(&service, tenant.as_ref(), request).await,
+    };
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        response.to_string(),
+    )
+}
+
+async fn call(service: &ZkvmService, tenant: Option<&Arc<Tenant>>, request: Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some("invokescript") => invoke_script(service, tenant, &params).await,
+        Some("invokefunction") => invoke_function(service, tenant, &params).await,
+        Some(method) => Err(RpcFailure::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {method}"),
+        )),
+        None => Err(RpcFailure::new(INVALID_REQUEST, "Missing method")),
+    };
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => failure(id, e),
+    }
+}
+
+fn failure(id: Value, failure: RpcFailure) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": failure.code, "message": failure.message},
+    })
+}
+
+fn param<'a>(params: &'a Value, index: usize, name: &str) -> Result<&'a str, RpcFailure> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcFailure::new(INVALID_PARAMS, format!("Missing {name}")))
+}
+
+/// `invokescript(script, signers?)`
+async fn invoke_script(
+    service: &ZkvmService,
+    tenant: Option<&Arc<Tenant>>,
+    params: &Value,
+) -> Result<Value, RpcFailure> {
+    let script = BASE64
+        .decode(param(params, 0, "script")?)
+        .map_err(|_| RpcFailure::new(INVALID_PARAMS, "Script is not base64"))?;
+    if script.is_empty() {
+        return Err(RpcFailure::new(INVALID_PARAMS, "Script is empty"));
+    }
+    let input = ProofInput {
+        script,
+        arguments: Vec::new(),
+        gas_limit: service.config().default_gas_limit,
+        storage: Vec::new(),
+        context: [0; 32],
+        gas_schedule: GasSchedule::Legacy,
+        runtime: RuntimeConfig::default(),
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        limits: VMLimits::default(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
+    };
+    let (script, proof, bytes) = prove(service, tenant, move |_| Ok(input)).await?;
+    Ok(invoke_result(&script, &proof, &bytes))
+}
+
+/// `invokefunction(scripthash, operation, params?, signers?)`
+async fn invoke_function(
+    service: &ZkvmService,
+    tenant: Option<&Arc<Tenant>>,
+    params: &Value,
+) -> Result<Value, RpcFailure> {
+    let hash = param(params, 0, "script hash")?.to_string();
+    let operation = param(params, 1, "operation")?.to_string();
+    let args = match params.get(2) {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(args)) => args
+            .iter()
+            .map(|arg| {
+                ContractParameter::from_json(arg)
+                    .map(|p| StackItem::from_contract_parameter(&p))
+                    .map_err(|e| RpcFailure::new(INVALID_PARAMS, e.to_string()))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(RpcFailure::new(INVALID_PARAMS, "Params must be an array")),
+    };
+    let url = neo_rpc_url(service.config())?;
+
+    let (script, proof, bytes) = prove(service, tenant, move |inner| {
+        RpcClient::new(url)
+            .invocation_input(&hash, &operation, args, inner.config.default_gas_limit)
+            .map_err(|e| e.to_string())
+    })
+    .await?;
+    Ok(invoke_result(&script, &proof, &bytes))
+}
+
+/// Prove the input `build` returns in the default mode, on behalf of
+/// `tenant`, returning its script, the proof and the encoded proof
+///
+/// The script is checked against the policy once built, since
+/// `invokefunction` only learns it from the node.
+async fn prove(
+    service: &ZkvmService,
+    tenant: Option<&Arc<Tenant>>,
+    build: impl FnOnce(&Inner) -> Result<ProofInput, String> + Send + 'static,
+) -> Result<(Vec<u8>, NeoProof, Vec<u8>), RpcFailure> {
+    let gas_limit = service.config().default_gas_limit;
+    let day = match tenant {
+        Some(tenant) => Some(
+            tenant
+                .reserve(gas_limit)
+                .map_err(|e| RpcFailure::new(QUOTA_EXCEEDED, e))?,
+        ),
+        None => None,
+    };
+    let proved = service
+        .run_blocking(tenant.map(Arc::as_ref), move |inner| {
+            let input = build(inner)?;
+            inner
+                .config
+                .policy
+                .check(&input.script)
+                .map_err(|e| RpcFailure::new(POLICY_VIOLATION, e.to_string()))?;
+            let script = input.script.clone();
+            let (proof, bytes) = inner.prove(input, inner.config.default_mode)?;
+            Ok((script, proof, bytes))
+        })
+        .await;
+    if let (Some(tenant), Some(day)) = (tenant, day) {
+        let consumed = proved
+            .as_ref()
+            .map_or(0, |(_, proof, _)| proof.public_inputs.gas_consumed);
+        tenant.settle(day, gas_limit, consumed);
+    }
+    proved
+}
+
+fn neo_rpc_url(config: &ServiceConfig) -> Result<String, RpcFailure> {
+    config.neo_rpc_url.clone().ok_or_else(|| {
+        RpcFailure::new(
+            INTERNAL_ERROR,
+            "invokefunction needs a Neo node to fetch contracts from; start the service with --neo-rpc",
+        )
+    })
+}
+
+/// Notification in Neo RPC's shape, with the contract as a `0x` UInt160
+fn notification(event: &NotificationEvent) -> Value {
+    let mut contract = event.script_hash;
+    contract.reverse();
+    json!({
+        "contract": format!("0x{}", hex::encode(contract)),
+        "eventname": event.event_name,
+        "state": event.state,
+    })
+}
+
+/// Neo `invokescript` result with the proof attached
+fn invoke_result(script: &[u8], proof: &NeoProof, proof_bytes: &[u8]) -> Value {
+    let output = &proof.output;
+    json!({
+        "script": BASE64.encode(script),
+        "state": if output.state == 0 { "HALT" } else { "FAULT" },
+        "gasconsumed": output.gas_consumed.to_string(),
+        "exception": output.error,
+        "notifications": output.notifications.iter().map(notification).collect::<Vec<_>>(),
+        "stack": output.result.iter().collect::<Vec<_>>(),
+        "proof": BASE64.encode(proof_bytes),
+    })
+}
@@ -0,0 +1,568 @@
+//! gRPC `ProofService` implementation
+//!
+//! Submitted jobs are recorded in the [`JobStore`] and queued for a
+//! dispatcher task, which proves them on the blocking thread pool by
+//! priority, see [`tenants`](crate::tenants). Queued jobs and the
+//! synchronous [`rpc`](crate::rpc) invocations share `max_concurrent_jobs`
+//! proving slots. Scripts the [`ScriptPolicy`] refuses are turned away
+//! before they are queued.
+
+use crate::policy::{PolicyError, ScriptPolicy};
+use crate::proto::proof_service_server::{ProofService, ProofServiceServer};
+use crate::proto::{
+    self, FetchProofRequest, FetchProofResponse, GetJobStatusRequest, GetJobStatusResponse,
+    SubmitProofJobRequest, SubmitProofJobResponse, VerifyRequest, VerifyResponse,
+};
+use crate::store::{JobRecord, JobState, JobStore, StoreError};
+use crate::tenants::{self, JobQueue, Tenant, TenantConfig, TenantError, Tenants, API_KEY_HEADER};
+use neo_vm_core::contract_parameter::parse_arguments;
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::{
+    CachePolicy, EncodeOptions, NeoProof, NeoProver, ProofCache, ProofJobStore, ProofMode,
+    ProverConfig, TEST_MOCK_KEY,
+};
+use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// Settings of a [`ZkvmService`]
+#[derive(Clone, Debug)]
+pub struct ServiceConfig {
+    /// Directory for job records and proofs
+    pub data_dir: PathBuf,
+    /// Jobs proven at the same time
+    pub max_concurrent_jobs: usize,
+    /// Queued and running jobs accepted before submissions are refused
+    pub max_pending_jobs: usize,
+    /// Mode for jobs that do not choose one
+    pub default_mode: ProofMode,
+    /// Gas limit for jobs that do not set one
+    pub default_gas_limit: u64,
+    /// Highest gas limit a job may request
+    pub max_gas_limit: u64,
+    /// Key authenticating mock proofs, shared by prover and verifier
+    pub mock_key: [u8; 32],
+    /// Whether `Verify` accepts mock proofs
+    pub allow_mock_proofs: bool,
+    /// Time limit for SP1 proofs
+    pub prove_timeout: Option<Duration>,
+    /// Guest memory limit for SP1 execution, in bytes
+    pub max_memory_bytes: Option<u64>,
+    /// Neo node that `invokefunction` fetches contracts and storage from
+    pub neo_rpc_url: Option<String>,
+    /// Serve identical executions from a proof cache kept under the data
+    /// directory, see [`ProofCache`]
+    pub proof_cache: Option<CachePolicy>,
+    /// API keys and quotas; empty leaves the service open to anyone
+    pub tenants: Vec<TenantConfig>,
+    /// Scripts the service agrees to prove
+    pub policy: ScriptPolicy,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from("neo-zkvm-data"),
+            max_concurrent_jobs: 1,
+            max_pending_jobs: 1024,
+            default_mode: ProofMode::Sp1,
+            default_gas_limit: 1_000_000,
+            max_gas_limit: 100_000_000,
+            mock_key: TEST_MOCK_KEY,
+            allow_mock_proofs: false,
+            prove_timeout: None,
+            max_memory_bytes: None,
+            neo_rpc_url: None,
+            proof_cache: None,
+            tenants: Vec::new(),
+            policy: ScriptPolicy::default(),
+        }
+    }
+}
+
+/// Why a [`ZkvmService`] could not start
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Tenants(#[from] TenantError),
+    #[error(transparent)]
+    Policy(#[from] PolicyError),
+}
+
+/// Proving service backed by a [`JobStore`]
+#[derive(Clone)]
+pub struct ZkvmService {
+    inner: Arc<Inner>,
+}
+
+pub(crate) struct Inner {
+    pub(crate) config: ServiceConfig,
+    store: Mutex<JobStore>,
+    /// Outstanding prover network requests
+    proof_jobs: Arc<ProofJobStore>,
+    cache: Option<Arc<ProofCache>>,
+    tenants: Tenants,
+    queue: JobQueue,
+    /// Proving slots
+    permits: Arc<Semaphore>,
+}
+
+impl ZkvmService {
+    /// Open the data directory, requeue unfinished jobs and start the
+    /// dispatcher
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn start(config: ServiceConfig) -> Result<Self, ServiceError> {
+        config.policy.validate()?;
+        let tenants = Tenants::new(&config.tenants)?;
+        let mut store = JobStore::open(&config.data_dir)?;
+        let proof_jobs = store.proof_jobs()?;
+        let cache = match config.proof_cache {
+            Some(policy) => Some(Arc::new(store.proof_cache(policy)?)),
+            None => None,
+        };
+        let queue = JobQueue::default();
+
+        for job in proof_jobs.outstanding().map_err(StoreError::from)? {
+            info!(request = %hex::encode(job.request_id), "found outstanding network proof request");
+        }
+
+        // Count today's jobs against their tenants' quotas again
+        let today = tenants::day_of(tenants::now());
+        for job in store.jobs() {
+            let tenant = job.tenant.as_deref().and_then(|name| tenants.get(name));
+            if let Some(tenant) = tenant.filter(|_| tenants::day_of(job.submitted_at) == today) {
+                let gas = if job.state.is_pending() {
+                    job.input.gas_limit
+                } else {
+                    job.gas_consumed
+                };
+                tenant.charge(today, gas);
+            }
+        }
+
+        // Jobs that were running when the service stopped start over
+        for mut job in store.pending() {
+            if job.state == JobState::Running {
+                job.state = JobState::Queued;
+                store.save(&job)?;
+            }
+            info!(job = %job.id, "requeued job");
+            let tenant = job.tenant.as_deref().and_then(|name| tenants.get(name));
+            queue.push(job.id, job.sequence, tenant);
+        }
+
+        let inner = Arc::new(Inner {
+            permits: Arc::new(Semaphore::new(config.max_concurrent_jobs.max(1))),
+            config,
+            store: Mutex::new(store),
+            proof_jobs: Arc::new(proof_jobs),
+            cache,
+            tenants,
+            queue,
+        });
+        tokio::spawn(dispatch(inner.clone()));
+        Ok(Self { inner })
+    }
+
+    pub fn config(&self) -> &ServiceConfig {
+        &self.inner.config
+    }
+
+    /// Wrap the service for `tonic::transport::Server::add_service`
+    pub fn into_server(self) -> ProofServiceServer<Self> {
+        ProofServiceServer::new(self)
+    }
+
+    /// Tenant owning `api_key`, `None` when no tenants are configured
+    pub(crate) fn authenticate(
+        &self,
+        api_key: Option<&str>,
+    ) -> Result<Option<Arc<Tenant>>, &'static str> {
+        self.inner.tenants.authenticate(api_key)
+    }
+
+    /// Run `work` on the blocking thread pool once a slot of `tenant`, if
+    /// any, and a proving slot are free
+    pub(crate) async fn run_blocking<T, E>(
+        &self,
+        tenant: Option<&Tenant>,
+        work: impl FnOnce(&Inner) -> Result<T, E> + Send + 'static,
+    ) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: From<String> + Send + 'static,
+    {
+        let slot = match tenant {
+            Some(tenant) => Some(tenant.slot().await),
+            None => None,
+        };
+        let permit = self
+            .inner
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| E::from(e.to_string()))?;
+        let inner = self.inner.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            work(&inner)
+        })
+        .await
+        .unwrap_or_else(|e| Err(E::from(format!("Prover panicked: {e}"))));
+        if slot.is_some() {
+            drop(slot);
+            self.inner.queue.wake();
+        }
+        result
+    }
+}
+
+impl Inner {
+    fn store(&self) -> MutexGuard<'_, JobStore> {
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Tenant named by the API key of `request`, `None` when no tenants are
+    /// configured
+    fn tenant<T>(&self, request: &Request<T>) -> Result<Option<Arc<Tenant>>, &'static str> {
+        let api_key = request
+            .metadata()
+            .get(API_KEY_HEADER)
+            .and_then(|key| key.to_str().ok());
+        self.tenants.authenticate(api_key)
+    }
+
+    /// Job `id`, if it exists and `tenant` may see it
+    fn job(&self, store: &JobStore, id: &str, tenant: Option<&Tenant>) -> Option<JobRecord> {
+        store
+            .get(id)
+            .filter(|job| {
+                tenant.is_none_or(|tenant| job.tenant.as_deref() == Some(&tenant.config.name))
+            })
+            .cloned()
+    }
+
+    fn verifier(&self) -> NeoVerifier {
+        let policy = if self.config.allow_mock_proofs {
+            VerifyPolicy::allow_mock_proofs()
+        } else {
+            VerifyPolicy::default()
+        };
+        NeoVerifier::new()
+            .with_mock_key(self.config.mock_key)
+            .with_policy(policy)
+    }
+
+    /// Prove one job and record the outcome
+    async fn run_job(self: Arc<Self>, id: String) {
+        let job = {
+            let mut store = self.store();
+            let Some(mut job) = store.get(&id).cloned() else {
+                return;
+            };
+            job.state = JobState::Running;
+            if let Err(e) = store.save(&job) {
+                warn!(job = %id, error = %e, "could not mark job as running");
+            }
+            job
+        };
+
+        let inner = self.clone();
+        let (input, mode) = (job.input.clone(), job.mode);
+        let proved = tokio::task::spawn_blocking(move || inner.prove(input, mode))
+            .await
+            .unwrap_or_else(|e| Err(format!("Prover panicked: {e}")));
+
+        let mut store = self.store();
+        let mut job = job;
+        let outcome = proved.and_then(|(proof, bytes)| {
+            store
+                .write_proof(&id, &bytes)
+                .map(|()| proof)
+                .map_err(|e| e.to_string())
+        });
+        match outcome {
+            Ok(proof) => {
+                job.state = JobState::Succeeded;
+                job.gas_consumed = proof.public_inputs.gas_consumed;
+                job.execution_success = proof.public_inputs.execution_success;
+                info!(job = %id, "job succeeded");
+            }
+            Err(error) => {
+                warn!(job = %id, %error, "job failed");
+                job.state = JobState::Failed;
+                job.error = Some(error);
+            }
+        }
+        if let Err(e) = store.save(&job) {
+            warn!(job = %id, error = %e, "could not record job outcome");
+        }
+        if let Some(tenant) = job
+            .tenant
+            .as_deref()
+            .and_then(|name| self.tenants.get(name))
+        {
+            let day = tenants::day_of(job.submitted_at);
+            tenant.settle(day, job.input.gas_limit, job.gas_consumed);
+        }
+    }
+
+    /// Prove `input` and encode the proof for storage
+    pub(crate) fn prove(
+        &self,
+        input: ProofInput,
+        mode: ProofMode,
+    ) -> Result<(NeoProof, Vec<u8>), String> {
+        let mut builder = ProverConfig::builder()
+            .proof_mode(mode)
+            .mock_key(self.config.mock_key);
+        if let Some(timeout) = self.config.prove_timeout {
+            // Modes without SP1 proving reject a timeout
+            if !matches!(mode, ProofMode::Execute | ProofMode::Mock) {
+                builder = builder.max_proving_time(timeout);
+            }
+        }
+        if let Some(bytes) = self.config.max_memory_bytes {
+            // Mock proofs never run the guest program
+            if mode != ProofMode::Mock {
+                builder = builder.max_memory_bytes(bytes);
+            }
+        }
+        let config = builder.build().map_err(|e| e.to_string())?;
+        let mut prover = NeoProver::new(config).with_job_store(self.proof_jobs.clone());
+        if let Some(cache) = &self.cache {
+            prover = prover.with_cache(cache.clone());
+        }
+        let proof = prover.prove(input).map_err(|e| e.to_string())?;
+        let bytes = proof
+            .to_bytes(EncodeOptions::compact())
+            .map_err(|e| e.to_string())?;
+        Ok((proof, bytes))
+    }
+
+    /// Turn a request into a proof input, applying the configured limits
+    ///
+    /// Errors describe the invalid argument.
+    fn proof_input(
+        &self,
+        request: SubmitProofJobRequest,
+    ) -> Result<(ProofInput, ProofMode), String> {
+        if request.script.is_empty() {
+            return Err("Script is empty".to_string());
+        }
+        let arguments = if request.arguments_json.trim().is_empty() {
+            Vec::new()
+        } else {
+            parse_arguments(&request.arguments_json).map_err(|e| e.to_string())?
+        };
+        let gas_limit = match request.gas_limit {
+            0 => self.config.default_gas_limit,
+            limit if limit > self.config.max_gas_limit => {
+                return Err(format!(
+                    "Gas limit {limit} exceeds the maximum of {}",
+                    self.config.max_gas_limit
+                ))
+            }
+            limit => limit,
+        };
+        let mode = match proto::ProofMode::try_from(request.mode) {
+            Ok(proto::ProofMode::Unspecified) => self.config.default_mode,
+            Ok(proto::ProofMode::Execute) => ProofMode::Execute,
+            Ok(proto::ProofMode::Mock) => ProofMode::Mock,
+            Ok(proto::ProofMode::Sp1) => ProofMode::Sp1,
+            Ok(proto::ProofMode::Plonk) => ProofMode::Plonk,
+            Ok(proto::ProofMode::Groth16) => ProofMode::Groth16,
+            Err(_) => return Err(format!("Unknown proof mode {}", request.mode)),
+        };
+        let context = parse_context(&request.context)?.unwrap_or([0; 32]);
+        let input = ProofInput {
+            script: request.script,
+            arguments,
+            gas_limit,
+            storage: Vec::new(),
+            context,
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        Ok((input, mode))
+    }
+}
+
+/// Start queued jobs as concurrency permits and tenant slots free up
+async fn dispatch(inner: Arc<Inner>) {
+    loop {
+        let Ok(permit) = inner.permits.clone().acquire_owned().await else {
+            return;
+        };
+        // Hand the permit back while waiting, so synchronous invocations
+        // holding the slot a queued job needs can finish
+        let Some((id, slot)) = inner.queue.take() else {
+            drop(permit);
+            inner.queue.changed().await;
+            continue;
+        };
+        let inner = inner.clone();
+        tokio::spawn(async move {
+            inner.clone().run_job(id).await;
+            drop(permit);
+            if slot.is_some() {
+                drop(slot);
+                inner.queue.wake();
+            }
+        });
+    }
+}
+
+/// An empty context means none; anything else must be 32 bytes
+fn parse_context(bytes: &[u8]) -> Result<Option<[u8; 32]>, String> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    bytes
+        .try_into()
+        .map(Some)
+        .map_err(|_| format!("Context is {} bytes, expected 32", bytes.len()))
+}
+
+fn job_status(state: JobState) -> proto::JobStatus {
+    match state {
+        JobState::Queued => proto::JobStatus::Queued,
+        JobState::Running => proto::JobStatus::Running,
+        JobState::Succeeded => proto::JobStatus::Succeeded,
+        JobState::Failed => proto::JobStatus::Failed,
+    }
+}
+
+#[tonic::async_trait]
+impl ProofService for ZkvmService {
+    async fn submit_proof_job(
+        &self,
+        request: Request<SubmitProofJobRequest>,
+    ) -> Result<Response<SubmitProofJobResponse>, Status> {
+        let tenant = self
+            .inner
+            .tenant(&request)
+            .map_err(Status::unauthenticated)?;
+        let (input, mode) = self
+            .inner
+            .proof_input(request.into_inner())
+            .map_err(Status::invalid_argument)?;
+        self.inner
+            .config
+            .policy
+            .check(&input.script)
+            .map_err(|e| Status::permission_denied(e.to_string()))?;
+        let gas_limit = input.gas_limit;
+        let job = {
+            let mut store = self.inner.store();
+            if store.pending_count() >= self.inner.config.max_pending_jobs {
+                return Err(Status::resource_exhausted("Job queue is full"));
+            }
+            let day = match &tenant {
+                Some(tenant) => Some(
+                    tenant
+                        .reserve(gas_limit)
+                        .map_err(Status::resource_exhausted)?,
+                ),
+                None => None,
+            };
+            let name = tenant.as_ref().map(|tenant| tenant.config.name.as_str());
+            store.create_for(input, mode, name).map_err(|e| {
+                if let (Some(tenant), Some(day)) = (&tenant, day) {
+                    tenant.settle(day, gas_limit, 0);
+                }
+                Status::internal(e.to_string())
+            })?
+        };
+        let job_id = job.id.clone();
+        self.inner.queue.push(job.id, job.sequence, tenant);
+        Ok(Response::new(SubmitProofJobResponse { job_id }))
+    }
+
+    async fn get_job_status(
+        &self,
+        request: Request<GetJobStatusRequest>,
+    ) -> Result<Response<GetJobStatusResponse>, Status> {
+        let tenant = self
+            .inner
+            .tenant(&request)
+            .map_err(Status::unauthenticated)?;
+        let id = request.into_inner().job_id;
+        let job = self
+            .inner
+            .job(&self.inner.store(), &id, tenant.as_deref())
+            .ok_or_else(|| Status::not_found(format!("No job {id}")))?;
+        Ok(Response::new(GetJobStatusResponse {
+            job_id: id.clone(),
+            status: job_status(job.state).into(),
+            error: job.error.unwrap_or_default(),
+            gas_consumed: job.gas_consumed,
+            execution_success: job.execution_success,
+        }))
+    }
+
+    async fn fetch_proof(
+        &self,
+        request: Request<FetchProofRequest>,
+    ) -> Result<Response<FetchProofResponse>, Status> {
+        let tenant = self
+            .inner
+            .tenant(&request)
+            .map_err(Status::unauthenticated)?;
+        let id = request.into_inner().job_id;
+        let store = self.inner.store();
+        let job = self
+            .inner
+            .job(&store, &id, tenant.as_deref())
+            .ok_or_else(|| Status::not_found(format!("No job {id}")))?;
+        if job.state != JobState::Succeeded {
+            return Err(Status::failed_precondition(format!(
+                "Job {id} is {:?}",
+                job.state
+            )));
+        }
+        let proof = store
+            .read_proof(&id)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(FetchProofResponse { proof }))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        self.inner
+            .tenant(&request)
+            .map_err(Status::unauthenticated)?;
+        let request = request.into_inner();
+        let proof = NeoProof::from_bytes(&request.proof)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let context = parse_context(&request.context).map_err(Status::invalid_argument)?;
+        let verifier = self.inner.verifier();
+        let result = tokio::task::spawn_blocking(move || match context {
+            Some(context) => verifier.verify_with_context(&proof, &context),
+            None => verifier.verify_detailed(&proof),
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(VerifyResponse {
+            valid: result.valid,
+            error: result.error.unwrap_or_default(),
+        }))
+    }
+}
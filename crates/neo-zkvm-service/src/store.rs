@@ -0,0 +1,214 @@
+//! Disk-backed job records and proof artifacts
+//!
+//! Layout under the data directory:
+//!
+//! - `jobs/<id>.json`: one [`JobRecord`] per job, rewritten on every state
+//!   change through a temporary file and a rename
+//! - `proofs/<id>.proof`: the encoded proof of a succeeded job
+//! - `remote/<request id>.json`: prover network requests still being
+//!   proven, kept by a [`ProofJobStore`]
+//! - `cache/`: proofs served again for identical executions, kept by a
+//!   [`ProofCache`] when the service enables one
+//!
+//! Records are written before a job is acknowledged, so a restarted service
+//! finds every accepted job and requeues the ones that had not finished.
+//! A requeued job whose proof was requested from the prover network polls
+//! that request rather than paying for a second proof.
+
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::{
+    CacheError, CachePolicy, JobStoreError, ProofCache, ProofJobStore, ProofMode,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors reading or writing the data directory
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Corrupt job record {path}: {message}")]
+    Corrupt { path: PathBuf, message: String },
+    #[error(transparent)]
+    ProofJobs(#[from] JobStoreError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+}
+
+/// Lifecycle of a proof job
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobState {
+    /// Whether the job still has to be proven
+    pub fn is_pending(self) -> bool {
+        matches!(self, JobState::Queued | JobState::Running)
+    }
+}
+
+/// A proof job as persisted in `jobs/<id>.json`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    /// Submission order, used to requeue pending jobs in order
+    pub sequence: u64,
+    pub state: JobState,
+    pub mode: ProofMode,
+    pub input: ProofInput,
+    /// Why the job failed
+    pub error: Option<String>,
+    pub gas_consumed: u64,
+    pub execution_success: bool,
+    /// Tenant that submitted the job, `None` when the service is open
+    #[serde(default)]
+    pub tenant: Option<String>,
+    /// Unix time in seconds when the job was submitted
+    #[serde(default)]
+    pub submitted_at: u64,
+}
+
+/// Job records and proofs in a data directory
+///
+/// Records are cached in memory; every change is written through to disk.
+pub struct JobStore {
+    root: PathBuf,
+    jobs: BTreeMap<String, JobRecord>,
+    next_sequence: u64,
+}
+
+impl JobStore {
+    /// Open `root`, creating it if needed, and load the existing records
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join("jobs"))?;
+        fs::create_dir_all(root.join("proofs"))?;
+
+        let mut jobs = BTreeMap::new();
+        for entry in fs::read_dir(root.join("jobs"))? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let record: JobRecord =
+                serde_json::from_slice(&fs::read(&path)?).map_err(|e| StoreError::Corrupt {
+                    path: path.clone(),
+                    message: e.to_string(),
+                })?;
+            jobs.insert(record.id.clone(), record);
+        }
+        let next_sequence = jobs.values().map(|job| job.sequence + 1).max().unwrap_or(0);
+        Ok(Self {
+            root,
+            jobs,
+            next_sequence,
+        })
+    }
+
+    /// Record a new queued job and return it
+    pub fn create(&mut self, input: ProofInput, mode: ProofMode) -> Result<JobRecord, StoreError> {
+        self.create_for(input, mode, None)
+    }
+
+    /// Record a new queued job submitted by `tenant` and return it
+    pub fn create_for(
+        &mut self,
+        input: ProofInput,
+        mode: ProofMode,
+        tenant: Option<&str>,
+    ) -> Result<JobRecord, StoreError> {
+        let sequence = self.next_sequence;
+        let record = JobRecord {
+            id: format!("{sequence:016x}"),
+            sequence,
+            state: JobState::Queued,
+            mode,
+            input,
+            error: None,
+            gas_consumed: 0,
+            execution_success: false,
+            tenant: tenant.map(str::to_string),
+            submitted_at: crate::tenants::now(),
+        };
+        self.save(&record)?;
+        self.next_sequence += 1;
+        Ok(record)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&JobRecord> {
+        self.jobs.get(id)
+    }
+
+    /// Write `record`, replacing the stored job with the same id
+    pub fn save(&mut self, record: &JobRecord) -> Result<(), StoreError> {
+        let json = serde_json::to_vec_pretty(record).map_err(io::Error::other)?;
+        write_atomic(&self.job_path(&record.id), &json)?;
+        self.jobs.insert(record.id.clone(), record.clone());
+        Ok(())
+    }
+
+    /// Every job, in id order
+    pub fn jobs(&self) -> impl Iterator<Item = &JobRecord> {
+        self.jobs.values()
+    }
+
+    /// Jobs that have not finished, in submission order
+    pub fn pending(&self) -> Vec<JobRecord> {
+        let mut pending: Vec<_> = self
+            .jobs
+            .values()
+            .filter(|job| job.state.is_pending())
+            .cloned()
+            .collect();
+        pending.sort_by_key(|job| job.sequence);
+        pending
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.jobs
+            .values()
+            .filter(|job| job.state.is_pending())
+            .count()
+    }
+
+    pub fn write_proof(&self, id: &str, proof: &[u8]) -> Result<(), StoreError> {
+        Ok(write_atomic(&self.proof_path(id), proof)?)
+    }
+
+    pub fn read_proof(&self, id: &str) -> Result<Vec<u8>, StoreError> {
+        Ok(fs::read(self.proof_path(id))?)
+    }
+
+    /// Open the store of outstanding prover network requests
+    pub fn proof_jobs(&self) -> Result<ProofJobStore, StoreError> {
+        Ok(ProofJobStore::open(self.root.join("remote"))?)
+    }
+
+    /// Open the proof cache, dropping proofs `policy` no longer keeps
+    pub fn proof_cache(&self, policy: CachePolicy) -> Result<ProofCache, StoreError> {
+        Ok(ProofCache::open(self.root.join("cache"), policy)?)
+    }
+
+    fn job_path(&self, id: &str) -> PathBuf {
+        self.root.join("jobs").join(format!("{id}.json"))
+    }
+
+    fn proof_path(&self, id: &str) -> PathBuf {
+        self.root.join("proofs").join(format!("{id}.proof"))
+    }
+}
+
+/// Write through a temporary file so readers never see a partial file
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
@@ -0,0 +1,409 @@
+//! API keys and per-tenant quotas
+//!
+//! With tenants configured, every call names its tenant with an API key in
+//! the `x-api-key` gRPC metadata or HTTP header, and tenants only see their
+//! own jobs. Each tenant has
+//!
+//! - a limit on its jobs and invocations proven at the same time, within the
+//!   service-wide `max_concurrent_jobs`
+//! - an optional gas quota per UTC day: a job reserves its gas limit when it
+//!   is submitted and is charged the gas it consumed once proven, and
+//!   submissions that would go over the quota are refused
+//! - a priority: queued jobs of higher priority tenants are proven first,
+//!   jobs of equal priority in submission order
+//!
+//! Without tenants the service is open to anyone and jobs are proven in
+//! submission order.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+/// gRPC metadata key and HTTP header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Order in which queued jobs of different tenants are proven
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// A team allowed to use the service, as listed in the tenants file
+///
+/// ```json
+/// {"name": "bridge", "api_key": "s3cret", "max_concurrent_jobs": 2,
+///  "gas_per_day": 1000000000, "priority": "high"}
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantConfig {
+    pub name: String,
+    pub api_key: String,
+    /// Jobs and invocations proven at the same time
+    #[serde(default = "one")]
+    pub max_concurrent_jobs: usize,
+    /// Gas that submitted jobs may reserve per UTC day, unlimited if unset
+    #[serde(default)]
+    pub gas_per_day: Option<u64>,
+    #[serde(default)]
+    pub priority: Priority,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl TenantConfig {
+    /// Tenant with one concurrent job, no gas quota and normal priority
+    pub fn new(name: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            api_key: api_key.into(),
+            max_concurrent_jobs: 1,
+            gas_per_day: None,
+            priority: Priority::Normal,
+        }
+    }
+}
+
+impl fmt::Debug for TenantConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantConfig")
+            .field("name", &self.name)
+            .field("api_key", &"<redacted>")
+            .field("max_concurrent_jobs", &self.max_concurrent_jobs)
+            .field("gas_per_day", &self.gas_per_day)
+            .field("priority", &self.priority)
+            .finish()
+    }
+}
+
+/// Why a tenant list was rejected
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TenantError {
+    #[error("Tenant {0} is configured twice")]
+    DuplicateName(String),
+    #[error("Tenant {0} reuses another tenant's API key")]
+    DuplicateKey(String),
+    #[error("Tenant {0} has an empty API key")]
+    EmptyKey(String),
+    #[error("Tenant {0} allows no concurrent jobs")]
+    NoSlots(String),
+}
+
+/// A configured tenant's proving slots and the gas it used today
+pub(crate) struct Tenant {
+    pub(crate) config: TenantConfig,
+    /// SHA-256 of the API key, compared in constant time
+    key_hash: [u8; 32],
+    slots: Arc<Semaphore>,
+    usage: Mutex<GasUsage>,
+}
+
+/// Gas reserved and charged on one UTC day
+#[derive(Default)]
+struct GasUsage {
+    day: u64,
+    used: u64,
+}
+
+impl Tenant {
+    fn usage(&self, day: u64) -> MutexGuard<'_, GasUsage> {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        if usage.day < day {
+            *usage = GasUsage { day, used: 0 };
+        }
+        usage
+    }
+
+    /// Reserve `gas` of today's quota, returning the day it counts against
+    pub(crate) fn reserve(&self, gas: u64) -> Result<u64, String> {
+        let day = today();
+        let mut usage = self.usage(day);
+        if let Some(quota) = self.config.gas_per_day {
+            if usage.used.saturating_add(gas) > quota {
+                return Err(format!(
+                    "Tenant {} has used {} of its {quota} gas for today",
+                    self.config.name, usage.used
+                ));
+            }
+        }
+        usage.used = usage.used.saturating_add(gas);
+        Ok(day)
+    }
+
+    /// Replace the `reserved` gas counted against `day` with the gas a job
+    /// `consumed`
+    pub(crate) fn settle(&self, day: u64, reserved: u64, consumed: u64) {
+        let mut usage = self.usage(day);
+        if usage.day == day {
+            usage.used = usage.used.saturating_sub(reserved).saturating_add(consumed);
+        }
+    }
+
+    /// Count `gas` against `day`, for jobs recorded before a restart
+    pub(crate) fn charge(&self, day: u64, gas: u64) {
+        let mut usage = self.usage(day);
+        if usage.day == day {
+            usage.used = usage.used.saturating_add(gas);
+        }
+    }
+
+    /// Take a proving slot if one is free
+    fn try_slot(&self) -> Option<OwnedSemaphorePermit> {
+        self.slots.clone().try_acquire_owned().ok()
+    }
+
+    /// Wait for a proving slot
+    pub(crate) async fn slot(&self) -> OwnedSemaphorePermit {
+        self.slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("tenant slots are never closed")
+    }
+}
+
+/// The configured tenants, looked up by API key
+#[derive(Default)]
+pub(crate) struct Tenants {
+    tenants: Vec<Arc<Tenant>>,
+}
+
+impl Tenants {
+    pub(crate) fn new(configs: &[TenantConfig]) -> Result<Self, TenantError> {
+        let mut names = HashSet::new();
+        let mut keys = HashSet::new();
+        for config in configs {
+            if !names.insert(config.name.as_str()) {
+                return Err(TenantError::DuplicateName(config.name.clone()));
+            }
+            if config.api_key.is_empty() {
+                return Err(TenantError::EmptyKey(config.name.clone()));
+            }
+            if !keys.insert(config.api_key.as_str()) {
+                return Err(TenantError::DuplicateKey(config.name.clone()));
+            }
+            if config.max_concurrent_jobs == 0 {
+                return Err(TenantError::NoSlots(config.name.clone()));
+            }
+        }
+        let tenants = configs
+            .iter()
+            .map(|config| {
+                Arc::new(Tenant {
+                    config: config.clone(),
+                    key_hash: Sha256::digest(&config.api_key).into(),
+                    slots: Arc::new(Semaphore::new(config.max_concurrent_jobs)),
+                    usage: Mutex::new(GasUsage::default()),
+                })
+            })
+            .collect();
+        Ok(Self { tenants })
+    }
+
+    /// Tenant owning `api_key`, `None` when no tenants are configured
+    pub(crate) fn authenticate(
+        &self,
+        api_key: Option<&str>,
+    ) -> Result<Option<Arc<Tenant>>, &'static str> {
+        if self.tenants.is_empty() {
+            return Ok(None);
+        }
+        let key_hash: [u8; 32] = Sha256::digest(api_key.ok_or("Missing API key")?).into();
+        // Every key is compared, so the time taken does not reveal which
+        // tenant's key a guess came close to
+        let mut found = None;
+        for tenant in &self.tenants {
+            if bool::from(tenant.key_hash.ct_eq(&key_hash)) {
+                found = Some(tenant.clone());
+            }
+        }
+        found.map(Some).ok_or("Unknown API key")
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<Arc<Tenant>> {
+        self.tenants
+            .iter()
+            .find(|tenant| tenant.config.name == name)
+            .cloned()
+    }
+}
+
+/// Queued job ids, highest priority first and then in submission order
+#[derive(Default)]
+pub(crate) struct JobQueue {
+    jobs: Mutex<BTreeMap<(Reverse<Priority>, u64), QueuedJob>>,
+    changed: Notify,
+}
+
+struct QueuedJob {
+    id: String,
+    tenant: Option<Arc<Tenant>>,
+}
+
+impl JobQueue {
+    pub(crate) fn push(&self, id: String, sequence: u64, tenant: Option<Arc<Tenant>>) {
+        let priority = tenant
+            .as_ref()
+            .map_or(Priority::Normal, |tenant| tenant.config.priority);
+        self.jobs()
+            .insert((Reverse(priority), sequence), QueuedJob { id, tenant });
+        self.changed.notify_one();
+    }
+
+    /// Take the first job whose tenant has a free slot, with that slot
+    pub(crate) fn take(&self) -> Option<(String, Option<OwnedSemaphorePermit>)> {
+        let mut jobs = self.jobs();
+        let (key, slot) = jobs.iter().find_map(|(key, job)| match &job.tenant {
+            None => Some((*key, None)),
+            Some(tenant) => tenant.try_slot().map(|slot| (*key, Some(slot))),
+        })?;
+        let job = jobs.remove(&key).expect("key was just found");
+        Some((job.id, slot))
+    }
+
+    /// Wait until a job is queued or a tenant slot is released
+    pub(crate) async fn changed(&self) {
+        self.changed.notified().await
+    }
+
+    /// Wake the dispatcher after a tenant slot was released
+    pub(crate) fn wake(&self) {
+        self.changed.notify_one();
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, BTreeMap<(Reverse<Priority>, u64), QueuedJob>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// UTC day of unix time `secs`
+pub(crate) fn day_of(secs: u64) -> u64 {
+    secs / SECONDS_PER_DAY
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn today() -> u64 {
+    day_of(now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenant(name: &str, priority: Priority, gas_per_day: Option<u64>) -> TenantConfig {
+        TenantConfig {
+            priority,
+            gas_per_day,
+            ..TenantConfig::new(name, format!("{name}-key"))
+        }
+    }
+
+    #[test]
+    fn test_tenant_config() {
+        let tenants = Tenants::new(&[
+            tenant("a", Priority::High, None),
+            tenant("b", Priority::Low, None),
+        ])
+        .unwrap();
+        assert_eq!(
+            tenants
+                .authenticate(Some("b-key"))
+                .unwrap()
+                .unwrap()
+                .config
+                .name,
+            "b"
+        );
+        assert_eq!(tenants.authenticate(None).err(), Some("Missing API key"));
+        assert_eq!(
+            tenants.authenticate(Some("c-key")).err(),
+            Some("Unknown API key")
+        );
+        assert!(Tenants::default().authenticate(None).unwrap().is_none());
+
+        let duplicate = [
+            tenant("a", Priority::High, None),
+            TenantConfig::new("b", "a-key"),
+        ];
+        assert_eq!(
+            Tenants::new(&duplicate).err(),
+            Some(TenantError::DuplicateKey("b".into()))
+        );
+        assert!(!format!("{:?}", duplicate[0]).contains("a-key"));
+
+        let parsed: TenantConfig =
+            serde_json::from_str(r#"{"name": "c", "api_key": "k", "priority": "high"}"#).unwrap();
+        assert_eq!(parsed.max_concurrent_jobs, 1);
+        assert_eq!(parsed.priority, Priority::High);
+    }
+
+    #[test]
+    fn test_gas_quota() {
+        let tenants = Tenants::new(&[tenant("a", Priority::Normal, Some(100))]).unwrap();
+        let a = tenants.get("a").unwrap();
+        let day = a.reserve(60).unwrap();
+        assert!(a.reserve(60).is_err());
+        // Charged what the job consumed rather than its limit
+        a.settle(day, 60, 10);
+        a.reserve(60).unwrap();
+        assert!(a.reserve(31).is_err());
+        // Reservations from earlier days are not settled against today
+        a.settle(day - 1, 60, 0);
+        a.reserve(30).unwrap();
+
+        // Huge gas limits saturate instead of overflowing
+        let tenants = Tenants::new(&[tenant("b", Priority::Normal, None)]).unwrap();
+        let b = tenants.get("b").unwrap();
+        let day = b.reserve(u64::MAX).unwrap();
+        b.reserve(u64::MAX).unwrap();
+        b.charge(day, u64::MAX);
+        b.settle(day, 1, u64::MAX);
+        assert_eq!(b.usage(day).used, u64::MAX);
+    }
+
+    #[test]
+    fn test_job_queue_order() {
+        let tenants = Tenants::new(&[
+            tenant("high", Priority::High, None),
+            tenant("low", Priority::Low, None),
+        ])
+        .unwrap();
+        let queue = JobQueue::default();
+        queue.push("low".into(), 0, tenants.get("low"));
+        queue.push("open".into(), 1, None);
+        queue.push("high-1".into(), 2, tenants.get("high"));
+        queue.push("high-2".into(), 3, tenants.get("high"));
+
+        let (first, slot) = queue.take().unwrap();
+        assert_eq!(first, "high-1");
+        // high's only slot is taken, so its second job waits
+        assert_eq!(queue.take().unwrap().0, "open");
+        let (low, _low_slot) = queue.take().unwrap();
+        assert_eq!(low, "low");
+        assert!(queue.take().is_none());
+        drop(slot);
+        assert_eq!(queue.take().unwrap().0, "high-2");
+    }
+}
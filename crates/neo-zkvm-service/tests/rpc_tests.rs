@@ -0,0 +1,249 @@
+//! JSON-RPC endpoint tests over HTTP, with a fake Neo node for
+//! `invokefunction`
+
+use axum::routing::post;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use neo_zkvm_prover::{NeoProof, ProofMode};
+use neo_zkvm_service::{
+    rpc, OpcodeRule, ScriptPolicy, ServiceConfig, TenantConfig, ZkvmService, API_KEY_HEADER,
+};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Serve `router` on a free local port
+async fn spawn(router: Router) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    addr
+}
+
+/// A Neo node with one contract: `sub(a, b)` at 0: SWAP, SUB, RET
+fn neo_node() -> Router {
+    Router::new().route(
+        "/",
+        post(|body: String| async move {
+            let request: Value = serde_json::from_str(&body).unwrap();
+            let result = match request["method"].as_str().unwrap() {
+                "getcontractstate" => json!({
+                    "hash": "0x0102030405060708090a0b0c0d0e0f1011121314",
+                    "nef": {"script": BASE64.encode([0x50, 0x9F, 0x40])},
+                    "manifest": {"name": "Test", "abi": {"methods": [
+                        {"name": "sub", "parameters": [{}, {}], "offset": 0}
+                    ]}},
+                }),
+                "findstorage" => json!({"results": [], "next": 0, "truncated": false}),
+                method => panic!("unexpected {method}"),
+            };
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": result}).to_string()
+        }),
+    )
+}
+
+async fn start(name: &str, neo_rpc_url: Option<String>) -> (String, PathBuf) {
+    let data_dir = std::env::temp_dir().join(format!("neo-zkvm-rpc-{name}-{}", std::process::id()));
+    let service = ZkvmService::start(ServiceConfig {
+        data_dir: data_dir.clone(),
+        default_mode: ProofMode::Mock,
+        neo_rpc_url,
+        ..ServiceConfig::default()
+    })
+    .unwrap();
+    let addr = spawn(rpc::router(service)).await;
+    (format!("http://{addr}"), data_dir)
+}
+
+async fn post_json(url: &str, body: Value) -> Value {
+    reqwest::Client::new()
+        .post(url)
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_invokescript() {
+    let (url, data_dir) = start("invokescript", None).await;
+    let response = post_json(
+        &url,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "invokescript",
+            "params": [BASE64.encode([0x12, 0x13, 0x9E, 0x40])],
+        }),
+    )
+    .await;
+
+    assert_eq!(response["id"], 1);
+    let result = &response["result"];
+    assert_eq!(result["script"], "EhOeQA==");
+    assert_eq!(result["state"], "HALT");
+    assert_eq!(result["exception"], Value::Null);
+    assert_eq!(result["notifications"], json!([]));
+    assert_eq!(result["stack"], json!([{"type": "Integer", "value": "5"}]));
+    let gas: u64 = result["gasconsumed"].as_str().unwrap().parse().unwrap();
+
+    let proof = BASE64.decode(result["proof"].as_str().unwrap()).unwrap();
+    let proof = NeoProof::from_bytes(&proof).unwrap();
+    assert_eq!(proof.proof_mode, ProofMode::Mock);
+    assert_eq!(proof.public_inputs.gas_consumed, gas);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_invokescript_notifications() {
+    let (url, data_dir) = start("notifications", None).await;
+    // NEWARRAY0, PUSH7, APPEND, PUSHDATA1 "Transfer", SYSCALL System.Runtime.Notify, RET
+    let mut script = vec![0xC2, 0x17, 0xCF, 0x0C, 0x08];
+    script.extend_from_slice(b"Transfer");
+    script.extend_from_slice(&[0x41, 0x02, 0x00, 0x00, 0x00, 0x40]);
+    let response = post_json(
+        &url,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "invokescript", "params": [BASE64.encode(&script)]}),
+    )
+    .await;
+
+    let notifications = response["result"]["notifications"].as_array().unwrap();
+    assert_eq!(notifications.len(), 1, "{response}");
+    let contract = notifications[0]["contract"].as_str().unwrap();
+    assert!(contract.starts_with("0x") && contract.len() == 42);
+    assert_eq!(notifications[0]["eventname"], "Transfer");
+    assert_eq!(
+        notifications[0]["state"],
+        json!({"type": "Array", "value": [{"type": "Integer", "value": "7"}]})
+    );
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_invokefunction() {
+    let node = spawn(neo_node()).await;
+    let (url, data_dir) = start("invokefunction", Some(format!("http://{node}"))).await;
+    let response = post_json(
+        &url,
+        json!({
+            "jsonrpc": "2.0",
+            "id": "a",
+            "method": "invokefunction",
+            "params": [
+                "0x0102030405060708090a0b0c0d0e0f1011121314",
+                "sub",
+                [{"type": "Integer", "value": "5"}, {"type": "Integer", "value": "3"}],
+            ],
+        }),
+    )
+    .await;
+    let result = &response["result"];
+    assert_eq!(result["state"], "HALT", "{response}");
+    assert_eq!(result["stack"], json!([{"type": "Integer", "value": "2"}]));
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_api_key() {
+    let data_dir = std::env::temp_dir().join(format!("neo-zkvm-rpc-keys-{}", std::process::id()));
+    let service = ZkvmService::start(ServiceConfig {
+        data_dir: data_dir.clone(),
+        default_mode: ProofMode::Mock,
+        tenants: vec![TenantConfig::new("wallet", "wallet-key")],
+        ..ServiceConfig::default()
+    })
+    .unwrap();
+    let url = format!("http://{}", spawn(rpc::router(service)).await);
+    let request =
+        json!({"jsonrpc": "2.0", "id": 1, "method": "invokescript", "params": ["EhOeQA=="]});
+    let send = |api_key: Option<&str>| {
+        let mut post = reqwest::Client::new().post(&url).body(request.to_string());
+        if let Some(api_key) = api_key {
+            post = post.header(API_KEY_HEADER, api_key);
+        }
+        async move { post.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+
+    assert_eq!(send(None).await["error"]["code"], -32001);
+    assert_eq!(send(Some("other-key")).await["error"]["code"], -32001);
+    assert_eq!(send(Some("wallet-key")).await["result"]["state"], "HALT");
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_script_policy() {
+    let node = spawn(neo_node()).await;
+    let data_dir = std::env::temp_dir().join(format!("neo-zkvm-rpc-policy-{}", std::process::id()));
+    let service = ZkvmService::start(ServiceConfig {
+        data_dir: data_dir.clone(),
+        default_mode: ProofMode::Mock,
+        neo_rpc_url: Some(format!("http://{node}")),
+        policy: ScriptPolicy {
+            banned_opcodes: vec![OpcodeRule::new("SUB")],
+            ..ScriptPolicy::default()
+        },
+        ..ServiceConfig::default()
+    })
+    .unwrap();
+    let url = format!("http://{}", spawn(rpc::router(service)).await);
+
+    let add = json!({"jsonrpc": "2.0", "id": 1, "method": "invokescript", "params": ["EhOeQA=="]});
+    assert_eq!(post_json(&url, add).await["result"]["state"], "HALT");
+    // The contract's script is checked once fetched from the node
+    let sub = json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "invokefunction",
+        "params": [
+            "0x0102030405060708090a0b0c0d0e0f1011121314",
+            "sub",
+            [{"type": "Integer", "value": "5"}, {"type": "Integer", "value": "3"}],
+        ],
+    });
+    let response = post_json(&url, sub).await;
+    assert_eq!(response["error"]["code"], -32003, "{response}");
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let (url, data_dir) = start("errors", None).await;
+    let code = |response: &Value| response["error"]["code"].as_i64().unwrap();
+
+    let parse_error = reqwest::Client::new()
+        .post(&url)
+        .body("{")
+        .send()
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap();
+    assert_eq!(code(&parse_error), -32700);
+
+    let batch = post_json(
+        &url,
+        json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "getversion"},
+            {"jsonrpc": "2.0", "id": 2, "method": "invokescript", "params": ["not base64!"]},
+            {"jsonrpc": "2.0", "id": 3, "method": "invokefunction", "params": ["0x01", "sub"]},
+        ]),
+    )
+    .await;
+    assert_eq!(code(&batch[0]), -32601);
+    assert_eq!(code(&batch[1]), -32602);
+    // No Neo node to fetch the contract from
+    assert_eq!(code(&batch[2]), -32603);
+    assert_eq!(batch[2]["id"], 3);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
@@ -0,0 +1,381 @@
+//! Proving service tests, calling the gRPC handlers directly
+
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::{CachePolicy, ProofMode};
+use neo_zkvm_service::proto::proof_service_server::ProofService;
+use neo_zkvm_service::proto::{
+    FetchProofRequest, GetJobStatusRequest, GetJobStatusResponse, JobStatus, SubmitProofJobRequest,
+    VerifyRequest,
+};
+use neo_zkvm_service::{
+    JobState, JobStore, OpcodeRule, PolicyError, Priority, ScriptPolicy, ServiceConfig,
+    ServiceError, TenantConfig, TenantError, ZkvmService, API_KEY_HEADER,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+use tonic::{Code, Request};
+
+const ADD: [u8; 4] = [0x12, 0x13, 0x9E, 0x40]; // PUSH2 PUSH3 ADD RET
+
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("neo-zkvm-service-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn config(data_dir: PathBuf) -> ServiceConfig {
+    ServiceConfig {
+        data_dir,
+        default_mode: ProofMode::Mock,
+        allow_mock_proofs: true,
+        ..ServiceConfig::default()
+    }
+}
+
+fn mock_job(script: &[u8], context: Vec<u8>) -> SubmitProofJobRequest {
+    SubmitProofJobRequest {
+        script: script.to_vec(),
+        context,
+        ..Default::default()
+    }
+}
+
+async fn submit(service: &ZkvmService, request: SubmitProofJobRequest) -> String {
+    service
+        .submit_proof_job(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+        .job_id
+}
+
+/// Poll until the job has finished
+async fn wait(service: &ZkvmService, job_id: &str) -> GetJobStatusResponse {
+    for _ in 0..500 {
+        let status = service
+            .get_job_status(Request::new(GetJobStatusRequest {
+                job_id: job_id.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        if !matches!(status.status(), JobStatus::Queued | JobStatus::Running) {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("job {job_id} did not finish");
+}
+
+#[tokio::test]
+async fn test_submit_fetch_verify() {
+    let dir = data_dir("round-trip");
+    let service = ZkvmService::start(config(dir.clone())).unwrap();
+    let context = vec![7; 32];
+    let job_id = submit(&service, mock_job(&ADD, context.clone())).await;
+
+    let status = wait(&service, &job_id).await;
+    assert_eq!(status.status(), JobStatus::Succeeded, "{}", status.error);
+    assert!(status.execution_success);
+    assert!(status.gas_consumed > 0);
+
+    let proof = service
+        .fetch_proof(Request::new(FetchProofRequest {
+            job_id: job_id.clone(),
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .proof;
+    assert!(dir.join("proofs").join(format!("{job_id}.proof")).exists());
+
+    let verify = |context: Vec<u8>| {
+        service.verify(Request::new(VerifyRequest {
+            proof: proof.clone(),
+            context,
+        }))
+    };
+    assert!(verify(context).await.unwrap().into_inner().valid);
+    assert!(verify(Vec::new()).await.unwrap().into_inner().valid);
+    let wrong = verify(vec![0; 32]).await.unwrap().into_inner();
+    assert!(!wrong.valid);
+    assert!(wrong.error.contains("context"), "{}", wrong.error);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_requests() {
+    let dir = data_dir("invalid");
+    let service = ZkvmService::start(ServiceConfig {
+        max_gas_limit: 1_000,
+        ..config(dir.clone())
+    })
+    .unwrap();
+
+    let requests = [
+        mock_job(&[], Vec::new()),
+        mock_job(&ADD, vec![1, 2, 3]),
+        SubmitProofJobRequest {
+            arguments_json: r#"[{"type":"Integer"}]"#.to_string(),
+            ..mock_job(&ADD, Vec::new())
+        },
+        SubmitProofJobRequest {
+            gas_limit: 1_001,
+            ..mock_job(&ADD, Vec::new())
+        },
+        SubmitProofJobRequest {
+            mode: 42,
+            ..mock_job(&ADD, Vec::new())
+        },
+    ];
+    for request in requests {
+        let status = service.submit_proof_job(Request::new(request)).await;
+        assert_eq!(status.unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    let missing = service
+        .get_job_status(Request::new(GetJobStatusRequest {
+            job_id: "missing".to_string(),
+        }))
+        .await;
+    assert_eq!(missing.unwrap_err().code(), Code::NotFound);
+
+    let garbage = service
+        .verify(Request::new(VerifyRequest {
+            proof: b"not a proof".to_vec(),
+            context: Vec::new(),
+        }))
+        .await;
+    assert_eq!(garbage.unwrap_err().code(), Code::InvalidArgument);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_queue_limit_and_faulting_script() {
+    let dir = data_dir("limits");
+    let service = ZkvmService::start(ServiceConfig {
+        max_pending_jobs: 0,
+        ..config(dir.clone())
+    })
+    .unwrap();
+    let full = service
+        .submit_proof_job(Request::new(mock_job(&ADD, Vec::new())))
+        .await;
+    assert_eq!(full.unwrap_err().code(), Code::ResourceExhausted);
+
+    // A script that runs out of gas is still proven, as a failed execution
+    let service = ZkvmService::start(config(dir.clone())).unwrap();
+    let job_id = submit(
+        &service,
+        SubmitProofJobRequest {
+            gas_limit: 1,
+            ..mock_job(&ADD, Vec::new())
+        },
+    )
+    .await;
+    let status = wait(&service, &job_id).await;
+    assert_eq!(status.status(), JobStatus::Succeeded);
+    assert!(!status.execution_success);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_proof_cache() {
+    let dir = data_dir("cache");
+    let service = ZkvmService::start(ServiceConfig {
+        proof_cache: Some(CachePolicy::default()),
+        ..config(dir.clone())
+    })
+    .unwrap();
+    let fetch = |job_id: String| service.fetch_proof(Request::new(FetchProofRequest { job_id }));
+
+    let first = submit(&service, mock_job(&ADD, Vec::new())).await;
+    assert_eq!(wait(&service, &first).await.status(), JobStatus::Succeeded);
+    let second = submit(&service, mock_job(&ADD, Vec::new())).await;
+    assert_eq!(wait(&service, &second).await.status(), JobStatus::Succeeded);
+
+    // The second job is served the first job's proof
+    let first = fetch(first).await.unwrap().into_inner().proof;
+    let second = fetch(second).await.unwrap().into_inner().proof;
+    assert_eq!(first, second);
+    let index = std::fs::read_to_string(dir.join("cache").join("index.json")).unwrap();
+    assert!(index.contains("\"hits\": 1"), "{index}");
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// `request` carrying `api_key`
+fn keyed<T>(request: T, api_key: &str) -> Request<T> {
+    let mut request = Request::new(request);
+    request
+        .metadata_mut()
+        .insert(API_KEY_HEADER, api_key.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_tenants() {
+    let dir = data_dir("tenants");
+    let tenants = vec![
+        TenantConfig {
+            gas_per_day: Some(1_500_000),
+            priority: Priority::High,
+            ..TenantConfig::new("bridge", "bridge-key")
+        },
+        TenantConfig::new("oracle", "oracle-key"),
+    ];
+    let service = ZkvmService::start(ServiceConfig {
+        tenants: tenants.clone(),
+        ..config(dir.clone())
+    })
+    .unwrap();
+
+    for request in [
+        Request::new(mock_job(&ADD, Vec::new())),
+        keyed(mock_job(&ADD, Vec::new()), "wrong-key"),
+    ] {
+        let status = service.submit_proof_job(request).await;
+        assert_eq!(status.unwrap_err().code(), Code::Unauthenticated);
+    }
+
+    let job_id = service
+        .submit_proof_job(keyed(mock_job(&ADD, Vec::new()), "bridge-key"))
+        .await
+        .unwrap()
+        .into_inner()
+        .job_id;
+    let over = SubmitProofJobRequest {
+        gas_limit: 2_000_000,
+        ..mock_job(&ADD, Vec::new())
+    };
+    let over = service.submit_proof_job(keyed(over, "bridge-key")).await;
+    assert_eq!(over.unwrap_err().code(), Code::ResourceExhausted);
+
+    let status = |api_key: &str| {
+        service.get_job_status(keyed(
+            GetJobStatusRequest {
+                job_id: job_id.clone(),
+            },
+            api_key,
+        ))
+    };
+    let mut finished = false;
+    for _ in 0..500 {
+        let state = status("bridge-key").await.unwrap().into_inner().status();
+        if state == JobStatus::Succeeded {
+            finished = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(finished);
+    // Tenants only see their own jobs
+    assert_eq!(
+        status("oracle-key").await.unwrap_err().code(),
+        Code::NotFound
+    );
+    let record = JobStore::open(&dir).unwrap().get(&job_id).unwrap().clone();
+    assert_eq!(record.tenant.as_deref(), Some("bridge"));
+
+    // The finished job is charged the gas it consumed, not its gas limit
+    let large = SubmitProofJobRequest {
+        gas_limit: 1_400_000,
+        ..mock_job(&ADD, Vec::new())
+    };
+    service
+        .submit_proof_job(keyed(large, "bridge-key"))
+        .await
+        .unwrap();
+
+    let duplicate = ZkvmService::start(ServiceConfig {
+        tenants: vec![tenants[0].clone(), tenants[0].clone()],
+        ..config(dir.clone())
+    });
+    assert!(matches!(
+        duplicate,
+        Err(ServiceError::Tenants(TenantError::DuplicateName(_)))
+    ));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_script_policy() {
+    let dir = data_dir("policy");
+    let policy = ScriptPolicy {
+        max_script_size: Some(8),
+        banned_opcodes: vec![OpcodeRule::new("SUB")],
+        ..ScriptPolicy::default()
+    };
+    let service = ZkvmService::start(ServiceConfig {
+        policy,
+        ..config(dir.clone())
+    })
+    .unwrap();
+
+    let job_id = submit(&service, mock_job(&ADD, Vec::new())).await;
+    assert_eq!(wait(&service, &job_id).await.status(), JobStatus::Succeeded);
+    for script in [vec![0x12, 0x13, 0x9F, 0x40], vec![0x11; 9]] {
+        let refused = service
+            .submit_proof_job(Request::new(mock_job(&script, Vec::new())))
+            .await
+            .unwrap_err();
+        assert_eq!(refused.code(), Code::PermissionDenied);
+    }
+    // Refused scripts are never recorded
+    assert_eq!(JobStore::open(&dir).unwrap().jobs().count(), 1);
+
+    let unknown = ZkvmService::start(ServiceConfig {
+        policy: ScriptPolicy {
+            banned_opcodes: vec![OpcodeRule::new("NOPE")],
+            ..ScriptPolicy::default()
+        },
+        ..config(dir.clone())
+    });
+    assert!(matches!(
+        unknown,
+        Err(ServiceError::Policy(PolicyError::UnknownOpcode(_)))
+    ));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_requeues_unfinished_jobs_on_start() {
+    let dir = data_dir("requeue");
+    let job_id = {
+        let mut store = JobStore::open(&dir).unwrap();
+        let input = ProofInput {
+            script: ADD.to_vec(),
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        let mut job = store.create(input, ProofMode::Mock).unwrap();
+        job.state = JobState::Running;
+        store.save(&job).unwrap();
+        job.id
+    };
+
+    let service = ZkvmService::start(config(dir.clone())).unwrap();
+    assert_eq!(wait(&service, &job_id).await.status(), JobStatus::Succeeded);
+
+    // Records survive a restart, and ids keep counting up
+    let store = JobStore::open(&dir).unwrap();
+    assert_eq!(store.get(&job_id).unwrap().state, JobState::Succeeded);
+    assert_ne!(submit(&service, mock_job(&ADD, Vec::new())).await, job_id);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
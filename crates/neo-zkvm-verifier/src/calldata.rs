@@ -0,0 +1,193 @@
+//! Public inputs encoded for on-chain verifiers
+//!
+//! SP1's Groth16 and Plonk verifier contracts check a proof against the
+//! SHA-256 of the public values the guest committed, with the top three
+//! bits cleared so it fits the BN254 scalar field. The guest
+//! commits [`PublicInputs`] as fixed-width little-endian fields, which
+//! [`encode_for_neo`] reproduces; [`committed_values_digest`] is the field
+//! element a verifier derives from them.
+//!
+//! EVM contracts read values as 32-byte big-endian words, so
+//! [`encode_public_inputs_for_evm`] ABI-encodes the same fields as a static
+//! tuple:
+//!
+//! ```solidity
+//! struct NeoPublicInputs {
+//!     bytes32 scriptHash;
+//!     bytes32 inputHash;
+//!     bytes32 outputHash;
+//!     uint64 gasConsumed;
+//!     bool executionSuccess;
+//!     bytes32 writeSetHash;
+//!     bytes32 txHash;
+//!     bytes32 context;
+//!     uint8 gasSchedule;
+//!     uint32 network;
+//!     uint8 trigger;
+//!     bytes32 randomSeed;
+//!     uint32 randomDraws;
+//!     uint32 blockIndex;
+//!     uint64 timestamp;
+//!     bytes32 oracleHash;
+//! }
+//! ```
+//!
+//! A contract given the tuple must rebuild the committed bytes from it,
+//! byte-swapping the integers, before checking the proof; trusting decoded
+//! fields that were not hashed into the proof would accept any values.
+
+use neo_zkvm_prover::PublicInputs;
+use sha2::{Digest, Sha256};
+
+/// Size of the public values the guest commits
+pub const PUBLIC_VALUES_SIZE: usize = 287;
+
+/// Size of [`encode_public_inputs_for_evm`]'s output, sixteen ABI words
+pub const EVM_PUBLIC_INPUTS_SIZE: usize = 16 * 32;
+
+/// The public values the guest commits, byte for byte
+///
+/// Hashes are copied as they are and integers are little-endian, so a Neo
+/// contract can slice fields out of the byte string directly.
+pub fn encode_for_neo(inputs: &PublicInputs) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(PUBLIC_VALUES_SIZE);
+    bytes.extend_from_slice(&inputs.script_hash);
+    bytes.extend_from_slice(&inputs.input_hash);
+    bytes.extend_from_slice(&inputs.output_hash);
+    bytes.extend_from_slice(&inputs.gas_consumed.to_le_bytes());
+    bytes.push(inputs.execution_success as u8);
+    bytes.extend_from_slice(&inputs.write_set_hash);
+    bytes.extend_from_slice(&inputs.tx_hash);
+    bytes.extend_from_slice(&inputs.context);
+    bytes.push(inputs.gas_schedule.id());
+    bytes.extend_from_slice(&inputs.runtime.network.to_le_bytes());
+    bytes.push(inputs.runtime.trigger.id());
+    bytes.extend_from_slice(&inputs.runtime.random_seed);
+    bytes.extend_from_slice(&inputs.random_draws.to_le_bytes());
+    bytes.extend_from_slice(&inputs.block_context.index.to_le_bytes());
+    bytes.extend_from_slice(&inputs.block_context.timestamp.to_le_bytes());
+    bytes.extend_from_slice(&inputs.oracle_hash);
+    bytes
+}
+
+/// `abi.encode` of the public inputs as the `NeoPublicInputs` tuple in the
+/// module docs
+pub fn encode_public_inputs_for_evm(inputs: &PublicInputs) -> Vec<u8> {
+    let mut encoder = AbiEncoder(Vec::with_capacity(EVM_PUBLIC_INPUTS_SIZE));
+    encoder.bytes32(&inputs.script_hash);
+    encoder.bytes32(&inputs.input_hash);
+    encoder.bytes32(&inputs.output_hash);
+    encoder.uint(inputs.gas_consumed);
+    encoder.uint(inputs.execution_success as u64);
+    encoder.bytes32(&inputs.write_set_hash);
+    encoder.bytes32(&inputs.tx_hash);
+    encoder.bytes32(&inputs.context);
+    encoder.uint(inputs.gas_schedule.id().into());
+    encoder.uint(inputs.runtime.network.into());
+    encoder.uint(inputs.runtime.trigger.id().into());
+    encoder.bytes32(&inputs.runtime.random_seed);
+    encoder.uint(inputs.random_draws.into());
+    encoder.uint(inputs.block_context.index.into());
+    encoder.uint(inputs.block_context.timestamp);
+    encoder.bytes32(&inputs.oracle_hash);
+    encoder.0
+}
+
+/// SHA-256 of the committed public values with the top three bits cleared,
+/// the public input SP1's Groth16 and Plonk verifiers check, big-endian
+pub fn committed_values_digest(inputs: &PublicInputs) -> [u8; 32] {
+    let mut digest: [u8; 32] = Sha256::digest(encode_for_neo(inputs)).into();
+    digest[0] &= 0b0001_1111;
+    digest
+}
+
+struct AbiEncoder(Vec<u8>);
+
+impl AbiEncoder {
+    fn bytes32(&mut self, value: &[u8; 32]) {
+        self.0.extend_from_slice(value);
+    }
+
+    /// An unsigned integer, left-padded to a word
+    fn uint(&mut self, value: u64) {
+        self.0.extend_from_slice(&[0; 24]);
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo_vm_core::{BlockContext, GasSchedule, RuntimeConfig, TriggerType};
+    use sp1_sdk::SP1PublicValues;
+
+    fn inputs() -> PublicInputs {
+        PublicInputs {
+            script_hash: [1u8; 32],
+            input_hash: [2u8; 32],
+            output_hash: [3u8; 32],
+            gas_consumed: 0x0102,
+            execution_success: true,
+            write_set_hash: [4u8; 32],
+            tx_hash: [5u8; 32],
+            context: [6u8; 32],
+            gas_schedule: GasSchedule::NeoN3,
+            runtime: RuntimeConfig {
+                network: 0x0A0B0C0D,
+                trigger: TriggerType::Verification,
+                random_seed: [0xFF; 32],
+            },
+            random_draws: 7,
+            block_context: BlockContext {
+                index: 0x0102_0304,
+                timestamp: 0x0506,
+            },
+            oracle_hash: [8u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_neo_encoding_matches_committed_values() {
+        let inputs = inputs();
+        let mut committed = SP1PublicValues::new();
+        committed.write(&inputs);
+
+        let encoded = encode_for_neo(&inputs);
+        assert_eq!(encoded.len(), PUBLIC_VALUES_SIZE);
+        assert_eq!(encoded, committed.as_slice());
+
+        let digest = committed_values_digest(&inputs);
+        let expected = committed.hash_bn254().to_bytes_be();
+        let padding = 32 - expected.len();
+        assert!(digest[..padding].iter().all(|&b| b == 0));
+        assert_eq!(digest[padding..], expected[..]);
+    }
+
+    #[test]
+    fn test_evm_encoding_is_big_endian_words() {
+        let inputs = inputs();
+        let encoded = encode_public_inputs_for_evm(&inputs);
+        assert_eq!(encoded.len(), EVM_PUBLIC_INPUTS_SIZE);
+        let word = |index: usize| &encoded[index * 32..(index + 1) * 32];
+
+        assert_eq!(word(0), [1u8; 32]);
+        let mut gas = [0u8; 32];
+        gas[30..].copy_from_slice(&[0x01, 0x02]);
+        assert_eq!(word(3), gas);
+        assert_eq!(word(4)[31], 1);
+        assert_eq!(word(7), [6u8; 32]);
+        assert_eq!(word(8)[31], GasSchedule::NeoN3.id());
+        assert_eq!(word(9)[28..], [0x0A, 0x0B, 0x0C, 0x0D]);
+        assert_eq!(word(10)[31], TriggerType::Verification.id());
+        assert_eq!(word(11), [0xFF; 32]);
+        assert_eq!(word(12)[31], 7);
+        assert_eq!(word(13)[28..], [0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(word(14)[30..], [0x05, 0x06]);
+        assert_eq!(word(15), [8u8; 32]);
+        assert!(encoded
+            .chunks(32)
+            .enumerate()
+            .filter(|(index, _)| [3, 4, 8, 9, 10, 12, 13, 14].contains(index))
+            .all(|(_, word)| word[..24].iter().all(|&b| b == 0)));
+    }
+}
@@ -6,7 +6,8 @@
 //!
 //! ```rust
 //! use neo_zkvm_prover::{NeoProver, ProverConfig};
-//! use neo_zkvm_verifier::{verify, verify_detailed, VerificationResult};
+//! use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
+//! use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
 //! use neo_vm_guest::ProofInput;
 //!
 //! let prover = NeoProver::new(ProverConfig::default());
@@ -14,25 +15,151 @@
 //!     script: vec![0x12, 0x13, 0x9E, 0x40],
 //!     arguments: vec![],
 //!     gas_limit: 1_000_000,
+//!     storage: Vec::new(),
+//!     context: [0; 32],
+//!     gas_schedule: GasSchedule::Legacy,
+//!     runtime: RuntimeConfig::default(),
+//!     pre_state_root: None,
+//!     storage_witnesses: Vec::new(),
+//!     limits: VMLimits::default(),
+//!     block_context: BlockContext::default(),
+//!     oracle_responses: OracleResponses::default(),
 //! };
 //!
-//! let proof = prover.prove(input);
-//! assert!(verify(&proof));
+//! let proof = prover.prove(input).unwrap();
+//!
+//! // Without the SP1 toolchain this is a mock proof, which verifiers only
+//! // accept when asked to
+//! let verifier = NeoVerifier::new().with_policy(VerifyPolicy::allow_mock_proofs());
+//! assert!(verifier.verify(&proof));
 //! ```
 
 use bincode::Options;
-use neo_zkvm_prover::{MockProof, NeoProof, ProofMode, PublicInputs, NEO_ZKVM_ELF};
+use neo_vm_core::OracleResponses;
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::{
+    MockProof, NeoProof, NeoProver, ProgramId, ProofEncodingError, ProofMetadata, ProofMode,
+    PublicInputs, NEO_ZKVM_ELF, TEST_MOCK_KEY,
+};
 use sha2::{Digest, Sha256};
-use sp1_sdk::{ProverClient, SP1ProofWithPublicValues, SP1PublicValues};
+use sp1_sdk::{
+    CpuProver, HashableKey, Prover, ProverClient, SP1ProofWithPublicValues, SP1PublicValues,
+    SP1VerifyingKey,
+};
+use std::io::Read;
+use std::sync::{Arc, LazyLock, OnceLock};
+use thiserror::Error;
+use tracing::{instrument, warn};
+
+pub mod calldata;
+pub mod registry;
 
-const BINCODE_LIMIT: u64 = 10 * 1024 * 1024; // 10MB limit
+pub use calldata::{committed_values_digest, encode_for_neo, encode_public_inputs_for_evm};
+pub use registry::VkeyRegistry;
 
-fn bincode_options() -> impl Options {
+/// Default limit on the size of a serialized proof
+pub const DEFAULT_SIZE_LIMIT: u64 = 10 * 1024 * 1024; // 10MB limit
+
+fn bincode_options(limit: u64) -> impl Options {
     bincode::DefaultOptions::new()
-        .with_limit(BINCODE_LIMIT)
+        .with_limit(limit)
         .with_fixint_encoding()
 }
 
+/// Why a proof could not be read
+#[derive(Error, Debug)]
+pub enum VerifyError {
+    #[error("Proof is {size} bytes, limit is {limit}")]
+    ProofTooLarge { size: u64, limit: u64 },
+    #[error("Malformed proof: {0}")]
+    Malformed(String),
+    #[error("Failed to read proof: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid verifying key: {0}")]
+    InvalidVkey(String),
+}
+
+/// Which kinds of proof a [`NeoVerifier`] accepts
+///
+/// SP1 proofs are always accepted when they verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyPolicy {
+    /// Accept [`ProofMode::Execute`] results, which carry no proof at all
+    pub accept_execute: bool,
+    /// Accept mock proofs whose MAC checks out under the verifier's mock
+    /// key
+    ///
+    /// A mock proof only shows that someone holding the key ran the
+    /// script, never that the execution is correct. It is off by default
+    /// outside this crate's own tests, and every accepted mock proof is
+    /// logged as a warning.
+    pub accept_mock: bool,
+}
+
+impl Default for VerifyPolicy {
+    fn default() -> Self {
+        Self {
+            accept_execute: true,
+            accept_mock: cfg!(test),
+        }
+    }
+}
+
+impl VerifyPolicy {
+    /// Also accept mock proofs, for development and tests
+    pub fn allow_mock_proofs() -> Self {
+        Self {
+            accept_mock: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Reusable proof verifier
+///
+/// The SP1 client and the verifying keys of the embedded guest programs are
+/// created on first use and shared by clones, so only the first SP1
+/// verification of each program pays for the key setup. Verification always runs locally on the CPU,
+/// whatever `SP1_PROVER` says. The free functions of this crate use one
+/// process-wide default verifier.
+#[derive(Clone)]
+pub struct NeoVerifier {
+    client: Arc<OnceLock<CpuProver>>,
+    vkey: Arc<OnceLock<SP1VerifyingKey>>,
+    arithmetic_vkey: Arc<OnceLock<SP1VerifyingKey>>,
+    registry: Arc<VkeyRegistry>,
+    policy: VerifyPolicy,
+    mock_key: [u8; 32],
+    size_limit: u64,
+}
+
+impl Default for NeoVerifier {
+    fn default() -> Self {
+        Self {
+            client: Arc::default(),
+            vkey: Arc::default(),
+            arithmetic_vkey: Arc::default(),
+            registry: Arc::default(),
+            policy: VerifyPolicy::default(),
+            mock_key: TEST_MOCK_KEY,
+            size_limit: DEFAULT_SIZE_LIMIT,
+        }
+    }
+}
+
+impl std::fmt::Debug for NeoVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NeoVerifier")
+            .field("vkey", &self.vkey.get().map(HashableKey::bytes32))
+            .field("registry", &self.registry)
+            .field("policy", &self.policy)
+            .field("size_limit", &self.size_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+static DEFAULT_VERIFIER: LazyLock<NeoVerifier> = LazyLock::new(NeoVerifier::default);
+
 /// Verification result
 #[derive(Debug, Clone)]
 pub struct VerificationResult {
@@ -42,6 +169,11 @@ pub struct VerificationResult {
     pub error: Option<String>,
     /// Detected proof type
     pub proof_type: ProofType,
+    /// Provenance recorded by the prover, unverified
+    ///
+    /// `None` when the proof was rejected before it was inspected, e.g. for
+    /// exceeding the size limit.
+    pub metadata: Option<ProofMetadata>,
 }
 
 /// Proof type detected during verification
@@ -55,156 +187,474 @@ pub enum ProofType {
     Unknown,
 }
 
-/// Verify a Neo zkVM proof (simple interface)
-pub fn verify(proof: &NeoProof) -> bool {
-    verify_detailed(proof).valid
-}
+impl NeoVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-/// Verify with detailed result
-pub fn verify_detailed(proof: &NeoProof) -> VerificationResult {
-    match proof.proof_mode {
-        ProofMode::Execute => {
-            if proof.output.state != 0 {
-                return VerificationResult {
-                    valid: false,
-                    error: Some("Execution faulted".to_string()),
-                    proof_type: ProofType::Unknown,
-                };
-            }
-            VerificationResult {
-                valid: true,
-                error: None,
-                proof_type: ProofType::Empty,
-            }
+    /// Verifier for SP1 proofs of the guest program with this verifying
+    /// key, as serialized by bincode
+    ///
+    /// Skips the key setup entirely, so it also works where the guest ELF
+    /// was not built.
+    pub fn from_vkey_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
+        let vkey =
+            bincode::deserialize(bytes).map_err(|e| VerifyError::InvalidVkey(e.to_string()))?;
+        Ok(Self::default().with_vkey(vkey))
+    }
+
+    pub fn with_vkey(self, vkey: SP1VerifyingKey) -> Self {
+        Self {
+            vkey: Arc::new(OnceLock::from(vkey)),
+            ..self
         }
-        ProofMode::Mock => {
-            if proof.output.state != 0 {
-                return VerificationResult {
-                    valid: false,
-                    error: Some("Execution faulted".to_string()),
-                    proof_type: ProofType::Unknown,
-                };
+    }
+
+    /// Verify SP1 proofs of older guest programs with their own keys
+    ///
+    /// A proof whose [`ProofMetadata::elf_hash`] is in `registry` is
+    /// checked against that key. Any other proof is checked against the key
+    /// of the embedded [`ProgramId`] with that ELF hash, or [`Self::vkey`].
+    /// The ELF hash is unproven, so a proof is accepted if it verifies under
+    /// any key the verifier trusts.
+    pub fn with_registry(mut self, registry: VkeyRegistry) -> Self {
+        self.registry = Arc::new(registry);
+        self
+    }
+
+    pub fn registry(&self) -> &VkeyRegistry {
+        &self.registry
+    }
+
+    pub fn with_policy(mut self, policy: VerifyPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> VerifyPolicy {
+        self.policy
+    }
+
+    /// Check mock proof MACs with the key the prover was configured with,
+    /// instead of the public [`TEST_MOCK_KEY`]
+    pub fn with_mock_key(mut self, key: [u8; 32]) -> Self {
+        self.mock_key = key;
+        self
+    }
+
+    /// Reject proofs whose serialized form is larger than `limit` bytes
+    pub fn with_size_limit(mut self, limit: u64) -> Self {
+        self.size_limit = limit;
+        self
+    }
+
+    pub fn size_limit(&self) -> u64 {
+        self.size_limit
+    }
+
+    fn client(&self) -> &CpuProver {
+        self.client
+            .get_or_init(|| ProverClient::builder().cpu().build())
+    }
+
+    /// Verifying key for SP1 proofs, set up from [`NEO_ZKVM_ELF`] unless
+    /// one was given
+    ///
+    /// `None` when no key was given and the guest ELF was not built.
+    pub fn vkey(&self) -> Option<&SP1VerifyingKey> {
+        if let Some(vkey) = self.vkey.get() {
+            return Some(vkey);
+        }
+        if !NeoProver::is_elf_available() {
+            return None;
+        }
+        Some(
+            self.vkey
+                .get_or_init(|| self.client().setup(NEO_ZKVM_ELF).1),
+        )
+    }
+
+    /// Verifying key of an embedded guest program
+    ///
+    /// `None` when it was not given and its ELF was not built.
+    fn program_vkey(&self, program: ProgramId) -> Option<&SP1VerifyingKey> {
+        match program {
+            ProgramId::Vm => self.vkey(),
+            ProgramId::Arithmetic if program.is_available() => Some(
+                self.arithmetic_vkey
+                    .get_or_init(|| self.client().setup(program.elf()).1),
+            ),
+            ProgramId::Arithmetic => None,
+        }
+    }
+
+    /// [`Self::vkey`] serialized by bincode, the format
+    /// [`Self::from_vkey_bytes`] and [`VkeyRegistry`] read
+    pub fn vkey_bytes(&self) -> Option<Vec<u8>> {
+        self.vkey()
+            .map(|vkey| bincode::serialize(vkey).expect("verifying keys serialize"))
+    }
+
+    /// SP1 program hash of [`Self::vkey`] as `0x`-prefixed hex, the value
+    /// on-chain SP1 verifiers are configured with
+    pub fn vkey_hash(&self) -> Option<String> {
+        self.vkey().map(HashableKey::bytes32)
+    }
+
+    /// Read a proof written by [`NeoProof::to_bytes`] or as plain bincode
+    ///
+    /// The proof is decoded as it streams in, so large aggregated proofs
+    /// are never buffered whole. If it exceeds the size limit, the rest of
+    /// `reader` is drained to report the full size.
+    pub fn read_proof(&self, reader: impl Read) -> Result<NeoProof, VerifyError> {
+        let mut reader = CountingReader {
+            inner: reader,
+            count: 0,
+        };
+        match NeoProof::read_from(&mut reader, self.size_limit) {
+            Ok(proof) => Ok(proof),
+            Err(ProofEncodingError::ProofTooLarge { size, limit }) => {
+                Err(VerifyError::ProofTooLarge { size, limit })
             }
+            Err(ProofEncodingError::Malformed(e)) => match *e {
+                bincode::ErrorKind::SizeLimit => {
+                    let rest = std::io::copy(&mut reader.inner, &mut std::io::sink())?;
+                    Err(VerifyError::ProofTooLarge {
+                        size: reader.count + rest,
+                        limit: self.size_limit,
+                    })
+                }
+                bincode::ErrorKind::Io(e) => Err(VerifyError::Io(e)),
+                e => Err(VerifyError::Malformed(e.to_string())),
+            },
+            Err(ProofEncodingError::Io(e)) => Err(VerifyError::Io(e)),
+            Err(e) => Err(VerifyError::Malformed(e.to_string())),
+        }
+    }
+
+    /// Verify a Neo zkVM proof (simple interface)
+    pub fn verify(&self, proof: &NeoProof) -> bool {
+        self.verify_detailed(proof).valid
+    }
+
+    /// Verify with detailed result
+    #[instrument(name = "neo_verify", skip_all, fields(mode = ?proof.proof_mode))]
+    pub fn verify_detailed(&self, proof: &NeoProof) -> VerificationResult {
+        if let Err(e) = self.check_size(proof) {
+            return VerificationResult {
+                valid: false,
+                error: Some(e.to_string()),
+                proof_type: ProofType::Unknown,
+                metadata: None,
+            };
+        }
+
+        VerificationResult {
+            metadata: Some(proof.metadata.clone()),
+            ..self.verify_contents(proof)
+        }
+    }
 
-            let result = verify_mock_proof(proof);
-            VerificationResult {
-                valid: result,
-                error: if result {
-                    None
-                } else {
-                    Some("Mock proof verification failed".to_string())
-                },
+    fn verify_contents(&self, proof: &NeoProof) -> VerificationResult {
+        match proof.proof_mode {
+            ProofMode::Execute if !self.policy.accept_execute => VerificationResult {
+                valid: false,
+                error: Some("Execute-only results are rejected by policy".to_string()),
+                proof_type: ProofType::Empty,
+                metadata: None,
+            },
+            ProofMode::Mock if !self.policy.accept_mock => VerificationResult {
+                valid: false,
+                error: Some(
+                    "Mock proofs are rejected by policy; see VerifyPolicy::accept_mock".to_string(),
+                ),
                 proof_type: ProofType::Mock,
+                metadata: None,
+            },
+            ProofMode::Execute => {
+                if proof.output.state != 0 {
+                    return VerificationResult {
+                        valid: false,
+                        error: Some("Execution faulted".to_string()),
+                        proof_type: ProofType::Unknown,
+                        metadata: None,
+                    };
+                }
+                VerificationResult {
+                    valid: true,
+                    error: None,
+                    proof_type: ProofType::Empty,
+                    metadata: None,
+                }
             }
+            ProofMode::Mock => {
+                if proof.output.state != 0 {
+                    return VerificationResult {
+                        valid: false,
+                        error: Some("Execution faulted".to_string()),
+                        proof_type: ProofType::Unknown,
+                        metadata: None,
+                    };
+                }
+
+                let result = self.verify_mock_proof(proof);
+                if result {
+                    warn!("accepted a mock proof, which does not prove execution");
+                }
+                VerificationResult {
+                    valid: result,
+                    error: if result {
+                        None
+                    } else {
+                        Some("Mock proof verification failed".to_string())
+                    },
+                    proof_type: ProofType::Mock,
+                    metadata: None,
+                }
+            }
+            ProofMode::Sp1 | ProofMode::Plonk | ProofMode::Groth16 => self.verify_sp1_proof(proof),
         }
-        ProofMode::Sp1 | ProofMode::Plonk | ProofMode::Groth16 => verify_sp1_proof(proof),
     }
-}
 
-/// Verify a proof with explicit vkey
-///
-/// This is useful when you have the vkey but not the original prover.
-pub fn verify_with_vkey(proof: &NeoProof, vkey: &sp1_sdk::SP1VerifyingKey) -> bool {
-    if proof.proof_mode == ProofMode::Mock || proof.proof_mode == ProofMode::Execute {
-        return verify(proof);
+    /// Verify a proof and check that it was made for `context`
+    ///
+    /// Fails if the proof commits to any other [`ProofInput::context`], so a
+    /// proof produced for one request cannot be replayed as the answer to
+    /// another.
+    ///
+    /// [`ProofInput::context`]: neo_vm_guest::ProofInput::context
+    pub fn verify_with_context(&self, proof: &NeoProof, context: &[u8; 32]) -> VerificationResult {
+        if proof.public_inputs.context != *context {
+            return VerificationResult {
+                valid: false,
+                error: Some("Proof was made for a different context".to_string()),
+                proof_type: ProofType::Unknown,
+                metadata: None,
+            };
+        }
+        self.verify_detailed(proof)
     }
 
-    match bincode_options().deserialize::<SP1ProofWithPublicValues>(&proof.proof_bytes) {
-        Ok(sp1_proof) => {
-            let public_inputs = match decode_public_inputs(&sp1_proof.public_values) {
-                Ok(inputs) => inputs,
-                Err(_) => return false,
+    /// Verify a proof and check that it relied on `responses`
+    ///
+    /// Fails if the proof commits to other oracle responses than
+    /// `responses`, so a verifier knows exactly which off-chain data the
+    /// execution saw.
+    pub fn verify_with_oracle_responses(
+        &self,
+        proof: &NeoProof,
+        responses: &OracleResponses,
+    ) -> VerificationResult {
+        if proof.public_inputs.oracle_hash != responses.commitment() {
+            return VerificationResult {
+                valid: false,
+                error: Some("Proof relied on different oracle responses".to_string()),
+                proof_type: ProofType::Unknown,
+                metadata: None,
+            };
+        }
+        self.verify_detailed(proof)
+    }
+
+    /// Verify a proof and check that it was made for `input`
+    ///
+    /// Recomputes [`ProofInput::input_hash`], which covers the canonically
+    /// encoded arguments, and fails if the proof commits to a different one.
+    pub fn verify_with_input(&self, proof: &NeoProof, input: &ProofInput) -> VerificationResult {
+        if input.input_hash(proof.public_inputs.tx_hash) != proof.public_inputs.input_hash {
+            return VerificationResult {
+                valid: false,
+                error: Some("Proof was made for a different input".to_string()),
+                proof_type: ProofType::Unknown,
+                metadata: None,
             };
-            if !public_inputs_equal(&public_inputs, &proof.public_inputs) {
-                return false;
+        }
+        self.verify_detailed(proof)
+    }
+
+    /// Verify a proof with explicit vkey
+    ///
+    /// This is useful when you have the vkey but not the original prover.
+    #[instrument(name = "neo_verify", skip_all, fields(mode = ?proof.proof_mode))]
+    pub fn verify_with_vkey(&self, proof: &NeoProof, vkey: &SP1VerifyingKey) -> bool {
+        if proof.proof_mode == ProofMode::Mock || proof.proof_mode == ProofMode::Execute {
+            return self.verify(proof);
+        }
+
+        if self.check_size(proof).is_err() {
+            return false;
+        }
+
+        match bincode_options(self.size_limit)
+            .deserialize::<SP1ProofWithPublicValues>(&proof.proof_bytes)
+        {
+            Ok(sp1_proof) => {
+                let public_inputs = match decode_public_inputs(&sp1_proof.public_values) {
+                    Ok(inputs) => inputs,
+                    Err(_) => return false,
+                };
+                if !public_inputs_equal(&public_inputs, &proof.public_inputs) {
+                    return false;
+                }
+                self.client().verify(&sp1_proof, vkey).is_ok()
             }
-            let prover = ProverClient::from_env();
-            prover.verify(&sp1_proof, vkey).is_ok()
+            Err(_) => false,
         }
-        Err(_) => false,
     }
-}
 
-/// Setup the ELF and return verification key
-///
-/// This can be used to verify proofs without having the original prover.
-pub fn setup_elf() -> sp1_sdk::SP1VerifyingKey {
-    let prover = ProverClient::from_env();
-    let (_, vk) = prover.setup(NEO_ZKVM_ELF);
-    vk
-}
+    fn check_size(&self, proof: &NeoProof) -> Result<(), VerifyError> {
+        let size = proof.proof_bytes.len() as u64;
+        if size > self.size_limit {
+            return Err(VerifyError::ProofTooLarge {
+                size,
+                limit: self.size_limit,
+            });
+        }
+        Ok(())
+    }
 
-fn verify_mock_proof(proof: &NeoProof) -> bool {
-    let mock: MockProof = match bincode_options().deserialize(&proof.proof_bytes) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
+    fn verify_mock_proof(&self, proof: &NeoProof) -> bool {
+        let mock: MockProof = match bincode_options(self.size_limit).deserialize(&proof.proof_bytes)
+        {
+            Ok(m) => m,
+            Err(_) => return false,
+        };
+
+        // Verify commitment matches public inputs
+        let expected = compute_commitment(&proof.public_inputs);
+        if mock.commitment != expected || !mock.verify_mac(&self.mock_key) {
+            return false;
+        }
 
-    // Verify commitment matches public inputs
-    let expected = compute_commitment(&proof.public_inputs);
-    if mock.commitment != expected {
-        return false;
+        // Verify all public inputs match
+        mock.public_inputs.script_hash == proof.public_inputs.script_hash
+            && mock.public_inputs.input_hash == proof.public_inputs.input_hash
+            && mock.public_inputs.output_hash == proof.public_inputs.output_hash
+            && mock.public_inputs.gas_consumed == proof.public_inputs.gas_consumed
+            && mock.public_inputs.execution_success == proof.public_inputs.execution_success
+            && mock.public_inputs.write_set_hash == proof.public_inputs.write_set_hash
+            && mock.public_inputs.tx_hash == proof.public_inputs.tx_hash
+            && mock.public_inputs.context == proof.public_inputs.context
+            && mock.public_inputs.gas_schedule == proof.public_inputs.gas_schedule
+            && mock.public_inputs.runtime == proof.public_inputs.runtime
+            && mock.public_inputs.random_draws == proof.public_inputs.random_draws
+            && mock.public_inputs.block_context == proof.public_inputs.block_context
+            && mock.public_inputs.oracle_hash == proof.public_inputs.oracle_hash
     }
 
-    // Verify all public inputs match
-    mock.public_inputs.script_hash == proof.public_inputs.script_hash
-        && mock.public_inputs.input_hash == proof.public_inputs.input_hash
-        && mock.public_inputs.output_hash == proof.public_inputs.output_hash
-        && mock.public_inputs.gas_consumed == proof.public_inputs.gas_consumed
-        && mock.public_inputs.execution_success == proof.public_inputs.execution_success
-}
+    fn verify_sp1_proof(&self, proof: &NeoProof) -> VerificationResult {
+        let sp1_proof: SP1ProofWithPublicValues =
+            match bincode_options(self.size_limit).deserialize(&proof.proof_bytes) {
+                Ok(p) => p,
+                Err(e) => {
+                    return VerificationResult {
+                        valid: false,
+                        error: Some(format!("Failed to deserialize SP1 proof: {}", e)),
+                        proof_type: ProofType::Unknown,
+                        metadata: None,
+                    };
+                }
+            };
+
+        // Determine proof type from the proof structure
+        let proof_type = detect_sp1_proof_type(&sp1_proof);
 
-fn verify_sp1_proof(proof: &NeoProof) -> VerificationResult {
-    let sp1_proof: SP1ProofWithPublicValues =
-        match bincode_options().deserialize(&proof.proof_bytes) {
-            Ok(p) => p,
+        let public_inputs = match decode_public_inputs(&sp1_proof.public_values) {
+            Ok(inputs) => inputs,
             Err(e) => {
                 return VerificationResult {
                     valid: false,
-                    error: Some(format!("Failed to deserialize SP1 proof: {}", e)),
-                    proof_type: ProofType::Unknown,
-                };
+                    error: Some(e),
+                    proof_type,
+                    metadata: None,
+                }
             }
         };
 
-    // Determine proof type from the proof structure
-    let proof_type = detect_sp1_proof_type(&sp1_proof);
-
-    let public_inputs = match decode_public_inputs(&sp1_proof.public_values) {
-        Ok(inputs) => inputs,
-        Err(e) => {
+        if !public_inputs_equal(&public_inputs, &proof.public_inputs) {
             return VerificationResult {
                 valid: false,
-                error: Some(e),
+                error: Some("Public inputs do not match SP1 proof values".to_string()),
                 proof_type,
-            }
+                metadata: None,
+            };
         }
-    };
 
-    if !public_inputs_equal(&public_inputs, &proof.public_inputs) {
-        return VerificationResult {
-            valid: false,
-            error: Some("Public inputs do not match SP1 proof values".to_string()),
-            proof_type,
+        let elf_hash = &proof.metadata.elf_hash;
+        let vkey = match self.registry.get(elf_hash) {
+            Some(vkey) => Some(vkey),
+            None => self.program_vkey(ProgramId::from_elf_hash(elf_hash).unwrap_or(ProgramId::Vm)),
+        };
+        let Some(vkey) = vkey else {
+            return VerificationResult {
+                valid: false,
+                error: Some("No verifying key: the SP1 guest ELF was not built".to_string()),
+                proof_type,
+                metadata: None,
+            };
         };
+
+        match self.client().verify(&sp1_proof, vkey) {
+            Ok(_) => VerificationResult {
+                valid: true,
+                error: None,
+                proof_type,
+                metadata: None,
+            },
+            Err(e) => VerificationResult {
+                valid: false,
+                error: Some(format!("SP1 verification failed: {}", e)),
+                proof_type,
+                metadata: None,
+            },
+        }
     }
+}
 
-    // Create client and verify
-    let prover = ProverClient::from_env();
-    let (_, vk) = prover.setup(NEO_ZKVM_ELF);
+/// Verify a Neo zkVM proof (simple interface)
+pub fn verify(proof: &NeoProof) -> bool {
+    DEFAULT_VERIFIER.verify(proof)
+}
 
-    match prover.verify(&sp1_proof, &vk) {
-        Ok(_) => VerificationResult {
-            valid: true,
-            error: None,
-            proof_type,
-        },
-        Err(e) => VerificationResult {
-            valid: false,
-            error: Some(format!("SP1 verification failed: {}", e)),
-            proof_type,
-        },
-    }
+/// Verify with detailed result
+pub fn verify_detailed(proof: &NeoProof) -> VerificationResult {
+    DEFAULT_VERIFIER.verify_detailed(proof)
+}
+
+/// Verify a proof and check that it was made for `context`
+pub fn verify_with_context(proof: &NeoProof, context: &[u8; 32]) -> VerificationResult {
+    DEFAULT_VERIFIER.verify_with_context(proof, context)
+}
+
+/// Verify a proof and check that it relied on `responses`
+pub fn verify_with_oracle_responses(
+    proof: &NeoProof,
+    responses: &OracleResponses,
+) -> VerificationResult {
+    DEFAULT_VERIFIER.verify_with_oracle_responses(proof, responses)
+}
+
+/// Verify a proof and check that it was made for `input`
+pub fn verify_with_input(proof: &NeoProof, input: &ProofInput) -> VerificationResult {
+    DEFAULT_VERIFIER.verify_with_input(proof, input)
+}
+
+/// Verify a proof with explicit vkey
+///
+/// This is useful when you have the vkey but not the original prover.
+pub fn verify_with_vkey(proof: &NeoProof, vkey: &SP1VerifyingKey) -> bool {
+    DEFAULT_VERIFIER.verify_with_vkey(proof, vkey)
+}
+
+/// Setup the ELF and return verification key
+///
+/// This can be used to verify proofs without having the original prover.
+/// Panics if the guest ELF was not built.
+pub fn setup_elf() -> SP1VerifyingKey {
+    DEFAULT_VERIFIER
+        .vkey()
+        .expect("SP1 guest ELF not built")
+        .clone()
 }
 
 fn detect_sp1_proof_type(_proof: &SP1ProofWithPublicValues) -> ProofType {
@@ -214,7 +664,7 @@ fn detect_sp1_proof_type(_proof: &SP1ProofWithPublicValues) -> ProofType {
 }
 
 fn decode_public_inputs(values: &SP1PublicValues) -> Result<PublicInputs, String> {
-    bincode_options()
+    bincode_options(DEFAULT_SIZE_LIMIT)
         .deserialize(values.as_slice())
         .map_err(|e| format!("Failed to decode public values: {e}"))
 }
@@ -225,6 +675,14 @@ fn public_inputs_equal(a: &PublicInputs, b: &PublicInputs) -> bool {
         && a.output_hash == b.output_hash
         && a.gas_consumed == b.gas_consumed
         && a.execution_success == b.execution_success
+        && a.write_set_hash == b.write_set_hash
+        && a.tx_hash == b.tx_hash
+        && a.context == b.context
+        && a.gas_schedule == b.gas_schedule
+        && a.runtime == b.runtime
+        && a.random_draws == b.random_draws
+        && a.block_context == b.block_context
+        && a.oracle_hash == b.oracle_hash
 }
 
 fn compute_commitment(inputs: &PublicInputs) -> [u8; 32] {
@@ -234,65 +692,124 @@ fn compute_commitment(inputs: &PublicInputs) -> [u8; 32] {
     hasher.update(inputs.output_hash);
     hasher.update(inputs.gas_consumed.to_le_bytes());
     hasher.update([inputs.execution_success as u8]);
+    hasher.update(inputs.write_set_hash);
+    hasher.update(inputs.tx_hash);
+    hasher.update(inputs.context);
+    hasher.update([inputs.gas_schedule.id()]);
+    hasher.update(inputs.runtime.network.to_le_bytes());
+    hasher.update([inputs.runtime.trigger.id()]);
+    hasher.update(inputs.runtime.random_seed);
+    hasher.update(inputs.random_draws.to_le_bytes());
+    hasher.update(inputs.block_context.index.to_le_bytes());
+    hasher.update(inputs.block_context.timestamp.to_le_bytes());
+    hasher.update(inputs.oracle_hash);
     hasher.finalize().into()
 }
 
+/// Counts the bytes read through it, for [`VerifyError::ProofTooLarge`]
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use neo_vm_core::StackItem;
-    use neo_vm_guest::ProofInput;
-    use neo_zkvm_prover::{NeoProver, ProofMode, ProverConfig};
+    use neo_vm_core::{
+        BlockContext, GasSchedule, OracleResponses, RuntimeConfig, StackItem, TriggerType, VMLimits,
+    };
+    use neo_zkvm_prover::{EncodeOptions, NeoProver, ProofMode, ProverConfig};
     use sp1_sdk::SP1PublicValues;
 
     #[test]
     fn test_verify_mock_proof() {
-        let prover = NeoProver::new(ProverConfig {
-            proof_mode: ProofMode::Mock,
-            ..Default::default()
-        });
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
 
         let input = ProofInput {
             script: vec![0x12, 0x13, 0x9E, 0x40],
             arguments: vec![],
             gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
         };
 
-        let proof = prover.prove(input);
+        let proof = prover.prove(input).unwrap();
         assert!(verify(&proof));
     }
 
     #[test]
     fn test_verify_execute_only() {
-        let prover = NeoProver::new(ProverConfig {
-            proof_mode: ProofMode::Execute,
-            ..Default::default()
-        });
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Execute)
+                .build()
+                .unwrap(),
+        );
 
         let input = ProofInput {
             script: vec![0x12, 0x13, 0x9E, 0x40],
             arguments: vec![],
             gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
         };
 
-        let proof = prover.prove(input);
+        let proof = prover.prove(input).unwrap();
         assert!(verify(&proof));
     }
 
     #[test]
     fn test_verify_detailed() {
-        let prover = NeoProver::new(ProverConfig {
-            proof_mode: ProofMode::Mock,
-            ..Default::default()
-        });
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
 
         let input = ProofInput {
             script: vec![0x12, 0x13, 0x9E, 0x40],
             arguments: vec![StackItem::Integer(42)],
             gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
         };
 
-        let proof = prover.prove(input);
+        let proof = prover.prove(input).unwrap();
         let result = verify_detailed(&proof);
 
         assert!(result.valid);
@@ -308,6 +825,21 @@ mod tests {
             output_hash: [3u8; 32],
             gas_consumed: 42,
             execution_success: true,
+            write_set_hash: [4u8; 32],
+            tx_hash: [5u8; 32],
+            context: [6u8; 32],
+            gas_schedule: GasSchedule::NeoN3,
+            runtime: RuntimeConfig {
+                network: neo_vm_core::runtime::TESTNET_MAGIC,
+                trigger: TriggerType::Verification,
+                random_seed: [7u8; 32],
+            },
+            random_draws: 8,
+            block_context: BlockContext {
+                index: 9,
+                timestamp: 1_700_000_000_000,
+            },
+            oracle_hash: [10u8; 32],
         };
 
         let mut public_values = SP1PublicValues::new();
@@ -319,5 +851,251 @@ mod tests {
         assert_eq!(decoded.output_hash, inputs.output_hash);
         assert_eq!(decoded.gas_consumed, inputs.gas_consumed);
         assert_eq!(decoded.execution_success, inputs.execution_success);
+        assert_eq!(decoded.write_set_hash, inputs.write_set_hash);
+        assert_eq!(decoded.tx_hash, inputs.tx_hash);
+        assert_eq!(decoded.context, inputs.context);
+        assert_eq!(decoded.gas_schedule, inputs.gas_schedule);
+        assert_eq!(decoded.runtime, inputs.runtime);
+        assert_eq!(decoded.random_draws, inputs.random_draws);
+        assert_eq!(decoded.block_context, inputs.block_context);
+        assert_eq!(decoded.oracle_hash, inputs.oracle_hash);
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        let size = proof.proof_bytes.len() as u64;
+
+        assert!(NeoVerifier::new().with_size_limit(size).verify(&proof));
+        let result = NeoVerifier::new()
+            .with_size_limit(size - 1)
+            .verify_detailed(&proof);
+        assert!(!result.valid);
+        assert_eq!(
+            result.error.unwrap(),
+            format!("Proof is {size} bytes, limit is {}", size - 1)
+        );
+    }
+
+    #[test]
+    fn test_read_proof() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        let verifier = NeoVerifier::new();
+
+        for options in [EncodeOptions::default(), EncodeOptions::compact()] {
+            let bytes = proof.to_bytes(options).unwrap();
+            assert!(verifier.verify(&verifier.read_proof(&bytes[..]).unwrap()));
+        }
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert!(verifier.verify(&verifier.read_proof(&bytes[..]).unwrap()));
+        match verifier.with_size_limit(64).read_proof(&bytes[..]) {
+            Err(VerifyError::ProofTooLarge { size, limit }) => {
+                assert_eq!(size, bytes.len() as u64);
+                assert_eq!(limit, 64);
+            }
+            other => panic!("expected ProofTooLarge, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_policy() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+
+        let strict = NeoVerifier::new().with_policy(VerifyPolicy {
+            accept_execute: true,
+            accept_mock: false,
+        });
+        let result = strict.verify_detailed(&proof);
+        assert!(!result.valid);
+        assert_eq!(result.proof_type, ProofType::Mock);
+        assert!(NeoVerifier::new().verify(&proof));
+    }
+
+    #[test]
+    fn test_mock_proof_mac() {
+        let key = [9u8; 32];
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .mock_key(key)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+        assert!(NeoVerifier::new().with_mock_key(key).verify(&proof));
+        assert!(!NeoVerifier::new().verify(&proof));
+
+        // Recomputing the public commitment is not enough to forge a proof
+        let mut forged = proof.clone();
+        forged.public_inputs.gas_consumed += 1;
+        let commitment = compute_commitment(&forged.public_inputs);
+        forged.proof_bytes = bincode::serialize(&MockProof {
+            public_inputs: forged.public_inputs.clone(),
+            commitment,
+            timestamp: 0,
+            mac: MockProof::compute_mac(&TEST_MOCK_KEY, &commitment),
+        })
+        .unwrap();
+        assert!(!NeoVerifier::new().with_mock_key(key).verify(&forged));
+    }
+
+    #[test]
+    fn test_verify_with_context() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover
+            .prove(ProofInput {
+                script: vec![0x12, 0x13, 0x9E, 0x40],
+                arguments: vec![],
+                gas_limit: 1_000_000,
+                storage: Vec::new(),
+                context: [7u8; 32],
+                gas_schedule: GasSchedule::Legacy,
+                runtime: RuntimeConfig::default(),
+                pre_state_root: None,
+                storage_witnesses: Vec::new(),
+                limits: VMLimits::default(),
+                block_context: BlockContext::default(),
+                oracle_responses: OracleResponses::default(),
+            })
+            .unwrap();
+        assert_eq!(proof.public_inputs.context, [7u8; 32]);
+
+        let verifier = NeoVerifier::new();
+        assert!(verifier.verify_with_context(&proof, &[7u8; 32]).valid);
+        let result = verifier.verify_with_context(&proof, &[8u8; 32]);
+        assert!(!result.valid);
+        assert!(result.error.unwrap().contains("different context"));
+
+        // Rewriting the context breaks the commitment
+        let mut replayed = proof.clone();
+        replayed.public_inputs.context = [8u8; 32];
+        assert!(!verifier.verify_with_context(&replayed, &[8u8; 32]).valid);
+    }
+
+    #[test]
+    fn test_verify_with_input() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let input = ProofInput {
+            script: vec![0x9E, 0x40],
+            arguments: vec![StackItem::Integer(2), StackItem::Integer(3)],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: OracleResponses::default(),
+        };
+        let proof = prover.prove(input.clone()).unwrap();
+
+        let verifier = NeoVerifier::new();
+        assert!(verifier.verify_with_input(&proof, &input).valid);
+        let other = ProofInput {
+            arguments: vec![StackItem::Integer(3), StackItem::Integer(2)],
+            ..input
+        };
+        let result = verifier.verify_with_input(&proof, &other);
+        assert!(!result.valid);
+        assert!(result.error.unwrap().contains("different input"));
+    }
+
+    #[test]
+    fn test_verify_with_oracle_responses() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let mut oracle_responses = OracleResponses::new();
+        oracle_responses.insert("https://api.example/price", "$.neo", b"12.5".to_vec());
+        let input = ProofInput {
+            script: vec![0x12, 0x40],
+            arguments: vec![],
+            gas_limit: 1_000_000,
+            storage: Vec::new(),
+            context: [0; 32],
+            gas_schedule: GasSchedule::Legacy,
+            runtime: RuntimeConfig::default(),
+            pre_state_root: None,
+            storage_witnesses: Vec::new(),
+            limits: VMLimits::default(),
+            block_context: BlockContext::default(),
+            oracle_responses: oracle_responses.clone(),
+        };
+        let proof = prover.prove(input).unwrap();
+
+        let verifier = NeoVerifier::new();
+        assert!(
+            verifier
+                .verify_with_oracle_responses(&proof, &oracle_responses)
+                .valid
+        );
+        oracle_responses.insert("https://api.example/price", "$.neo", b"13".to_vec());
+        let result = verifier.verify_with_oracle_responses(&proof, &oracle_responses);
+        assert!(!result.valid);
+        assert!(result.error.unwrap().contains("oracle responses"));
+    }
+
+    #[test]
+    fn test_verify_detailed_reports_metadata() {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover.prove_script(vec![0x12, 0x13, 0x9E, 0x40]).unwrap();
+
+        let result = NeoVerifier::new().verify_detailed(&proof);
+        assert!(result.valid);
+        assert_eq!(result.metadata, Some(proof.metadata.clone()));
+
+        // Oversized proofs are rejected unread
+        let result = NeoVerifier::new()
+            .with_size_limit(1)
+            .verify_detailed(&proof);
+        assert!(!result.valid);
+        assert_eq!(result.metadata, None);
+    }
+
+    #[test]
+    fn test_from_vkey_bytes_rejects_garbage() {
+        assert!(matches!(
+            NeoVerifier::from_vkey_bytes(&[1, 2, 3]),
+            Err(VerifyError::InvalidVkey(_))
+        ));
     }
 }
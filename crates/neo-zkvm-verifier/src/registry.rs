@@ -0,0 +1,160 @@
+//! Verifying keys for several guest program versions
+//!
+//! Each build of the guest program has its own SP1 verifying key. A
+//! [`VkeyRegistry`] maps the SHA-256 of a guest ELF, as recorded in
+//! [`ProofMetadata::elf_hash`](neo_zkvm_prover::ProofMetadata::elf_hash), to
+//! the key for that build, so a verifier can still check proofs made by an
+//! older guest. See [`NeoVerifier::with_registry`](crate::NeoVerifier::with_registry).
+
+use crate::VerifyError;
+use sp1_sdk::SP1VerifyingKey;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Environment variable naming the directory [`VkeyRegistry::from_env`]
+/// loads
+pub const VKEY_DIR_ENV: &str = "NEO_ZKVM_VKEY_DIR";
+
+/// File extension of verifying keys in a registry directory
+pub const VKEY_EXTENSION: &str = "vkey";
+
+/// Verifying keys by guest ELF hash
+#[derive(Clone, Default)]
+pub struct VkeyRegistry {
+    keys: BTreeMap<[u8; 32], SP1VerifyingKey>,
+}
+
+impl VkeyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry of keys serialized by bincode, e.g. embedded with
+    /// `include_bytes!`
+    pub fn from_embedded(entries: &[([u8; 32], &[u8])]) -> Result<Self, VerifyError> {
+        let mut registry = Self::new();
+        for (elf_hash, bytes) in entries {
+            registry.insert_bytes(*elf_hash, bytes)?;
+        }
+        Ok(registry)
+    }
+
+    /// Load every `<elf hash in hex>.vkey` file in `dir`
+    ///
+    /// Each file holds a bincode-serialized key, as written by
+    /// [`NeoVerifier::vkey_bytes`](crate::NeoVerifier::vkey_bytes) or
+    /// `neo-zkvm vkey export`. Files with another extension are ignored.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, VerifyError> {
+        let mut registry = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != VKEY_EXTENSION)
+            {
+                continue;
+            }
+            let elf_hash = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| hex::decode(stem).ok())
+                .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+                .ok_or_else(|| {
+                    VerifyError::InvalidVkey(format!(
+                        "{}: file name is not a 32-byte hex ELF hash",
+                        path.display()
+                    ))
+                })?;
+            registry.insert_bytes(elf_hash, &std::fs::read(&path)?)?;
+        }
+        Ok(registry)
+    }
+
+    /// Load the directory named by [`VKEY_DIR_ENV`], or an empty registry
+    /// when it is unset
+    pub fn from_env() -> Result<Self, VerifyError> {
+        match std::env::var_os(VKEY_DIR_ENV) {
+            Some(dir) => Self::load_dir(dir),
+            None => Ok(Self::new()),
+        }
+    }
+
+    /// Register `vkey` for the guest ELF with this hash, replacing any key
+    /// already registered for it
+    pub fn insert(&mut self, elf_hash: [u8; 32], vkey: SP1VerifyingKey) {
+        self.keys.insert(elf_hash, vkey);
+    }
+
+    /// Register a key serialized by bincode
+    pub fn insert_bytes(&mut self, elf_hash: [u8; 32], bytes: &[u8]) -> Result<(), VerifyError> {
+        let vkey =
+            bincode::deserialize(bytes).map_err(|e| VerifyError::InvalidVkey(e.to_string()))?;
+        self.insert(elf_hash, vkey);
+        Ok(())
+    }
+
+    pub fn get(&self, elf_hash: &[u8; 32]) -> Option<&SP1VerifyingKey> {
+        self.keys.get(elf_hash)
+    }
+
+    /// Registered ELF hashes, in ascending order
+    pub fn elf_hashes(&self) -> impl Iterator<Item = &[u8; 32]> {
+        self.keys.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl std::fmt::Debug for VkeyRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.keys.keys().map(hex::encode))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("neo-vkeys-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("README.txt"), "ignored").unwrap();
+        assert!(VkeyRegistry::load_dir(&dir).unwrap().is_empty());
+
+        // Keys must be named by their ELF hash and deserialize
+        std::fs::write(dir.join("latest.vkey"), [0u8; 4]).unwrap();
+        let err = VkeyRegistry::load_dir(&dir).unwrap_err();
+        assert!(err.to_string().contains("not a 32-byte hex ELF hash"));
+        std::fs::remove_file(dir.join("latest.vkey")).unwrap();
+        std::fs::write(
+            dir.join(format!("{}.vkey", hex::encode([7u8; 32]))),
+            [0u8; 4],
+        )
+        .unwrap();
+        assert!(matches!(
+            VkeyRegistry::load_dir(&dir),
+            Err(VerifyError::InvalidVkey(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            VkeyRegistry::load_dir(&dir),
+            Err(VerifyError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_embedded_rejects_garbage() {
+        assert!(VkeyRegistry::from_embedded(&[]).unwrap().is_empty());
+        assert!(VkeyRegistry::from_embedded(&[([1u8; 32], &[0xFF])]).is_err());
+    }
+}
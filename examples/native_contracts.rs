@@ -42,7 +42,7 @@ fn main() {
     // =========================================================================
     println!("\n--- Part 2: Base64 Encoding ---\n");
 
-    let message = StackItem::ByteString(b"Hello, Neo zkVM!".to_vec());
+    let message = StackItem::ByteString(b"Hello, Neo zkVM!".to_vec().into());
     let encoded = stdlib.invoke("base64Encode", vec![message.clone()]).unwrap();
     
     if let StackItem::ByteString(bytes) = &encoded {
@@ -83,7 +83,7 @@ fn main() {
     }
 
     // String to integer
-    let str_num = StackItem::ByteString(b"42".to_vec());
+    let str_num = StackItem::ByteString(b"42".to_vec().into());
     let parsed = stdlib.invoke("atoi", vec![str_num]).unwrap();
     println!("Parsed '42':    {:?}", parsed);
 
@@ -94,7 +94,7 @@ fn main() {
 
     let cryptolib = CryptoLib::new();
 
-    let data_to_hash = StackItem::ByteString(b"Neo zkVM".to_vec());
+    let data_to_hash = StackItem::ByteString(b"Neo zkVM".to_vec().into());
     
     // SHA256 hash
     let sha256_result = cryptolib.invoke("sha256", vec![data_to_hash.clone()]).unwrap();
@@ -15,10 +15,10 @@
 //! - SP1: Generate real ZK proof (production)
 //! - SP1Plonk: Generate PLONK proof (on-chain verification)
 
-use neo_vm_core::StackItem;
+use neo_vm_core::{BlockContext, OracleResponses, StackItem};
 use neo_vm_guest::ProofInput;
 use neo_zkvm_prover::{NeoProver, ProofMode, ProverConfig};
-use neo_zkvm_verifier::{verify, verify_detailed};
+use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
 
 fn main() {
     println!("=== Neo zkVM Proof Generation Example ===\n");
@@ -37,27 +37,40 @@ fn main() {
         script: add_script.clone(),
         arguments: vec![], // No additional arguments needed
         gas_limit: 100_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
     // Create prover with mock mode (for demonstration)
-    let config = ProverConfig {
-        max_cycles: 1_000_000,
-        proof_mode: ProofMode::Mock,
-    };
+    let config = ProverConfig::builder()
+        .max_cycles(1_000_000)
+        .proof_mode(ProofMode::Mock)
+        .build()
+        .expect("valid prover config");
     let prover = NeoProver::new(config);
 
+    // Mock proofs are rejected unless the verifier opts in
+    let verifier = NeoVerifier::new().with_policy(VerifyPolicy::allow_mock_proofs());
+
     // Generate proof
     println!("Generating proof...");
-    let proof = prover.prove(input);
+    let proof = prover.prove_unwrap(input);
 
     // Display results
     println!("Execution result: {:?}", proof.output.result);
     println!("Gas consumed: {}", proof.output.gas_consumed);
     println!("Proof size: {} bytes", proof.proof_bytes.len());
-    println!("Script hash: 0x{}", hex_encode(&proof.public_inputs.script_hash[..8]));
+    println!(
+        "Script hash: 0x{}",
+        hex_encode(&proof.public_inputs.script_hash[..8])
+    );
 
     // Verify the proof
-    let is_valid = verify(&proof);
+    let is_valid = verifier.verify(&proof);
     println!("Proof valid: {}", is_valid);
     assert!(is_valid, "Proof should be valid");
 
@@ -74,19 +87,25 @@ fn main() {
         script: square_script,
         arguments: vec![StackItem::Integer(7)], // 7² = 49
         gas_limit: 100_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
-    let proof2 = prover.prove(input_with_args);
+    let proof2 = prover.prove_unwrap(input_with_args);
     println!("Input: 7");
     println!("Result (7²): {:?}", proof2.output.result);
-    println!("Verification: {}", verify(&proof2));
+    println!("Verification: {}", verifier.verify(&proof2));
 
     // =========================================================================
     // Part 3: Detailed Verification
     // =========================================================================
     println!("\n--- Part 3: Detailed Verification ---\n");
 
-    let result = verify_detailed(&proof);
+    let result = verifier.verify_detailed(&proof);
     println!("Detailed verification result:");
     println!("  Valid: {}", result.valid);
     if let Some(err) = &result.error {
@@ -98,21 +117,31 @@ fn main() {
     // =========================================================================
     println!("\n--- Part 4: Execute-Only Mode ---\n");
 
-    let exec_config = ProverConfig {
-        max_cycles: 1_000_000,
-        proof_mode: ProofMode::Execute,
-    };
+    let exec_config = ProverConfig::builder()
+        .max_cycles(1_000_000)
+        .proof_mode(ProofMode::Execute)
+        .build()
+        .expect("valid prover config");
     let exec_prover = NeoProver::new(exec_config);
 
     let input3 = ProofInput {
         script: vec![0x15, 0x14, 0xA0, 0x40], // PUSH5, PUSH4, MUL, RET = 20
         arguments: vec![],
         gas_limit: 100_000,
+        storage: Vec::new(),
+        context: [0; 32],
+        pre_state_root: None,
+        storage_witnesses: Vec::new(),
+        block_context: BlockContext::default(),
+        oracle_responses: OracleResponses::default(),
     };
 
-    let exec_result = exec_prover.prove(input3);
+    let exec_result = exec_prover.prove_unwrap(input3);
     println!("Execute-only result: {:?}", exec_result.output.result);
-    println!("Proof bytes (should be empty): {} bytes", exec_result.proof_bytes.len());
+    println!(
+        "Proof bytes (should be empty): {} bytes",
+        exec_result.proof_bytes.len()
+    );
 
     // =========================================================================
     // Part 5: Public Inputs Analysis
@@ -120,11 +149,23 @@ fn main() {
     println!("\n--- Part 5: Public Inputs Analysis ---\n");
 
     println!("Public inputs for verification:");
-    println!("  Script hash:       0x{}", hex_encode(&proof.public_inputs.script_hash));
-    println!("  Input hash:        0x{}", hex_encode(&proof.public_inputs.input_hash));
-    println!("  Output hash:       0x{}", hex_encode(&proof.public_inputs.output_hash));
+    println!(
+        "  Script hash:       0x{}",
+        hex_encode(&proof.public_inputs.script_hash)
+    );
+    println!(
+        "  Input hash:        0x{}",
+        hex_encode(&proof.public_inputs.input_hash)
+    );
+    println!(
+        "  Output hash:       0x{}",
+        hex_encode(&proof.public_inputs.output_hash)
+    );
     println!("  Gas consumed:      {}", proof.public_inputs.gas_consumed);
-    println!("  Execution success: {}", proof.public_inputs.execution_success);
+    println!(
+        "  Execution success: {}",
+        proof.public_inputs.execution_success
+    );
 
     println!("\n=== Proof Generation Example Complete ===");
 }
@@ -0,0 +1,85 @@
+//! Fuzz target for the CLI assembler
+//!
+//! Arbitrary source must assemble or fail cleanly. Programs built from
+//! known instructions must round-trip: assembling, decoding and
+//! disassembling gives back the same instruction sequence.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use neo_vm_core::decode_script;
+
+#[allow(dead_code)]
+#[path = "../../crates/neo-zkvm-cli/src/assembler.rs"]
+mod assembler;
+#[allow(dead_code)]
+#[path = "../../crates/neo-zkvm-cli/src/debug_info.rs"]
+mod debug_info;
+#[allow(dead_code)]
+#[path = "../../crates/neo-zkvm-cli/src/disassembler.rs"]
+mod disassembler;
+
+use assembler::Assembler;
+use disassembler::Disassembler;
+
+/// Instructions without operands, named the same by both tools
+const SIMPLE: &[&str] = &[
+    "PUSH0", "PUSH1", "PUSH2", "PUSH16", "PUSHM1", "PUSHNULL", "NOP", "DROP", "DUP", "SWAP",
+    "EQUAL", "INC", "NOT", "NEWMAP", "SIZE", "SHA256", "RET",
+];
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Simple(u8),
+    PushInt8(i8),
+    PushInt16(i16),
+    PushInt32(i32),
+    PushInt64(i64),
+    PushData1(Vec<u8>),
+}
+
+impl Op {
+    fn source(&self) -> String {
+        match self {
+            Op::Simple(i) => SIMPLE[*i as usize % SIMPLE.len()].to_string(),
+            Op::PushInt8(v) => format!("PUSHINT8 {v}"),
+            Op::PushInt16(v) => format!("PUSHINT16 {v}"),
+            Op::PushInt32(v) => format!("PUSHINT32 {v}"),
+            Op::PushInt64(v) => format!("PUSHINT64 {v}"),
+            Op::PushData1(data) => {
+                format!("PUSHDATA1 0x{}", hex::encode(&data[..data.len().min(255)]))
+            }
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzInput {
+    raw_source: String,
+    program: Vec<Op>,
+}
+
+fuzz_target!(|input: FuzzInput| {
+    if let Ok(bytes) = Assembler::new().assemble(&input.raw_source) {
+        Disassembler::new(&bytes).disassemble();
+        decode_script(&bytes);
+    }
+
+    if input.program.is_empty() {
+        return;
+    }
+    let lines: Vec<String> = input.program.iter().map(Op::source).collect();
+    let bytes = Assembler::new()
+        .assemble(&lines.join("\n"))
+        .expect("generated program must assemble");
+
+    let decoded = decode_script(&bytes);
+    assert_eq!(decoded.len(), lines.len());
+    let disassembler = Disassembler::new(&bytes);
+    for (instruction, line) in decoded.instructions().iter().zip(&lines) {
+        let (text, size) = disassembler.decode_instruction(instruction.offset);
+        assert_eq!(&text, line);
+        assert_eq!(size, instruction.size);
+    }
+});
@@ -0,0 +1,28 @@
+//! Fuzz target for `decode_script`
+//!
+//! Decoded instructions must tile a prefix of the script: each starts where
+//! the previous one ends, operands stay in bounds, and the offset index
+//! agrees with the instruction list.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use neo_vm_core::decode_script;
+
+fuzz_target!(|script: &[u8]| {
+    let decoded = decode_script(script);
+
+    let mut offset = 0;
+    for instruction in decoded.instructions() {
+        assert_eq!(instruction.offset, offset);
+        assert_eq!(instruction.opcode, script[offset]);
+        assert!(instruction.next_offset() <= script.len());
+        assert_eq!(instruction.operand(script).len(), instruction.operand_len);
+        assert_eq!(decoded.get(offset), Some(instruction));
+        for inner in offset + 1..instruction.next_offset() {
+            assert!(decoded.get(inner).is_none());
+        }
+        offset = instruction.next_offset();
+    }
+    assert!(decoded.get(script.len()).is_none());
+});
@@ -4,9 +4,9 @@
 
 #![no_main]
 
-use libfuzzer_sys::fuzz_target;
 use arbitrary::Arbitrary;
-use neo_vm_core::{NeoVM, VMState, StackItem};
+use libfuzzer_sys::fuzz_target;
+use neo_vm_core::{NeoVM, StackItem, VMState};
 
 #[derive(Arbitrary, Debug)]
 struct FuzzInput {
@@ -18,22 +18,24 @@ struct FuzzInput {
 fuzz_target!(|input: FuzzInput| {
     // Limit gas to prevent long runs
     let gas = (input.gas_limit % 10_000) as u64 + 100;
-    
+
     let mut vm = NeoVM::new(gas);
-    
+
     // Add initial stack items
     for val in input.initial_stack.iter().take(10) {
         vm.eval_stack.push(StackItem::Integer(*val as i128));
     }
-    
+
     // Append RET to script
     let mut script = input.script;
     if script.len() > 1000 {
         script.truncate(1000);
     }
     script.push(0x40);
-    
-    vm.load_script(script);
+
+    if vm.load_script(script).is_err() {
+        return;
+    }
 
     let mut steps = 0;
     while !matches!(vm.state, VMState::Halt | VMState::Fault) {
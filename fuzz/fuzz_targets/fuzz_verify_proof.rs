@@ -0,0 +1,74 @@
+//! Fuzz target for `NeoVerifier::verify_detailed` on mutated proofs
+//!
+//! Starts from a valid mock proof, flips the bytes chosen by the fuzzer in
+//! its serialization and verifies whatever still deserializes. Verification
+//! must reject or accept without panicking.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use bincode::Options;
+use libfuzzer_sys::fuzz_target;
+use neo_vm_core::{BlockContext, GasSchedule, OracleResponses, RuntimeConfig, VMLimits};
+use neo_vm_guest::ProofInput;
+use neo_zkvm_prover::{NeoProof, NeoProver, ProofMode, ProverConfig};
+use neo_zkvm_verifier::{NeoVerifier, VerifyPolicy};
+use std::sync::OnceLock;
+
+fn valid_proof() -> &'static [u8] {
+    static PROOF: OnceLock<Vec<u8>> = OnceLock::new();
+    PROOF.get_or_init(|| {
+        let prover = NeoProver::new(
+            ProverConfig::builder()
+                .proof_mode(ProofMode::Mock)
+                .build()
+                .unwrap(),
+        );
+        let proof = prover
+            .prove(ProofInput {
+                script: vec![0x12, 0x13, 0x9E, 0x40], // 2 + 3
+                arguments: vec![],
+                gas_limit: 1_000_000,
+                storage: Vec::new(),
+                context: [0; 32],
+                gas_schedule: GasSchedule::Legacy,
+                runtime: RuntimeConfig::default(),
+                pre_state_root: None,
+                storage_witnesses: Vec::new(),
+                limits: VMLimits::default(),
+                block_context: BlockContext::default(),
+                oracle_responses: OracleResponses::default(),
+            })
+            .unwrap();
+        bincode::serialize(&proof).unwrap()
+    })
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzInput {
+    /// (position, xor mask) pairs applied to the serialized proof
+    mutations: Vec<(u16, u8)>,
+    truncate: Option<u16>,
+}
+
+fuzz_target!(|input: FuzzInput| {
+    let mut bytes = valid_proof().to_vec();
+    for (position, mask) in input.mutations {
+        let len = bytes.len();
+        bytes[position as usize % len] ^= mask;
+    }
+    if let Some(len) = input.truncate {
+        bytes.truncate(len as usize);
+    }
+
+    // Mutated length prefixes must not turn into huge allocations
+    let options = bincode::DefaultOptions::new()
+        .with_limit(1 << 20)
+        .with_fixint_encoding();
+    if let Ok(proof) = options.deserialize::<NeoProof>(&bytes) {
+        // Accept mock proofs so mutations reach the MAC and commitment checks
+        NeoVerifier::new()
+            .with_policy(VerifyPolicy::allow_mock_proofs())
+            .verify_detailed(&proof);
+    }
+});
@@ -15,12 +15,14 @@ fuzz_target!(|data: &[u8]| {
 
     // Create VM with limited gas to prevent infinite loops
     let mut vm = NeoVM::new(10_000);
-    
+
     // Append RET opcode to ensure termination
     let mut script = data.to_vec();
     script.push(0x40); // RET
-    
-    vm.load_script(script);
+
+    if vm.load_script(script).is_err() {
+        return;
+    }
 
     // Execute until halt or fault
     let mut steps = 0;
@@ -0,0 +1,47 @@
+//! Fuzz target for `NeoVM::run`
+//!
+//! Runs arbitrary scripts to completion with arbitrary arguments. Gas bounds
+//! the run; the VM must end in `Halt` or `Fault` without panicking, and may
+//! only go over its gas limit by faulting.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use neo_vm_core::{NeoVM, StackItem, VMState};
+
+#[derive(Arbitrary, Debug)]
+enum Arg {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzInput {
+    script: Vec<u8>,
+    args: Vec<Arg>,
+    gas_limit: u16,
+}
+
+fuzz_target!(|input: FuzzInput| {
+    let mut vm = NeoVM::new(input.gas_limit as u64 * 10 + 100);
+    if vm.load_script(input.script).is_err() {
+        return;
+    }
+    for arg in input.args.into_iter().take(16) {
+        vm.eval_stack.push(match arg {
+            Arg::Null => StackItem::Null,
+            Arg::Boolean(b) => StackItem::Boolean(b),
+            Arg::Integer(i) => StackItem::Integer(i as i128),
+            Arg::Bytes(b) => StackItem::ByteString(b.into()),
+        });
+    }
+
+    vm.run();
+    assert!(matches!(vm.state, VMState::Halt | VMState::Fault));
+    if vm.gas_consumed > vm.gas_limit {
+        assert!(matches!(vm.state, VMState::Fault));
+    }
+});